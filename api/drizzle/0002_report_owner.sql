@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `api_key_id` text;
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `suspected_mod` text;
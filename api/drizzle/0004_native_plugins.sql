@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `native_plugins_json` text;
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `graphics_injectors_json` text;
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `game_edition` text;
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `papyrus_stacks_json` text;
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `game_state_json` text;
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `recent_assets_json` text;
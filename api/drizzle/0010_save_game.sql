@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `save_game_json` text;
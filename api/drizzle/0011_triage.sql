@@ -0,0 +1,2 @@
+ALTER TABLE `crash_report` ADD `crash_category` text;--> statement-breakpoint
+ALTER TABLE `crash_report` ADD `triage_hint` text;
@@ -0,0 +1,2 @@
+ALTER TABLE `crash_report` ADD `uptime_secs` integer;--> statement-breakpoint
+ALTER TABLE `crash_report` ADD `secs_since_last_save` integer;
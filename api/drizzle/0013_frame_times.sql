@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `frame_times_json` text;
\ No newline at end of file
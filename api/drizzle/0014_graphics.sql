@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `graphics_json` text;
\ No newline at end of file
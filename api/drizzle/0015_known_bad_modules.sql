@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `known_bad_modules_json` text;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE `crash_report` ADD `breadcrumbs_json` text;--> statement-breakpoint
+ALTER TABLE `crash_report` ADD `mod_context_json` text;--> statement-breakpoint
+ALTER TABLE `crash_report` ADD `mod_errors_json` text;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE `crash_report` ADD `extras_json` text;
\ No newline at end of file
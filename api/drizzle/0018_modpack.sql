@@ -0,0 +1,2 @@
+ALTER TABLE `crash_report` ADD `modpack_name` text;--> statement-breakpoint
+ALTER TABLE `crash_report` ADD `modpack_version` text;
\ No newline at end of file
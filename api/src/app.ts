@@ -6,6 +6,7 @@ import { requestId } from 'hono/request-id';
 import { apiKeysApp } from '@/routes/api-keys';
 import { configApp } from '@/routes/config';
 import { crashesApp } from '@/routes/crashes';
+import { knownIssuesApp } from '@/routes/known-issues';
 
 const app = new OpenAPIHono();
 
@@ -22,6 +23,7 @@ app.get('/health', (c) => {
 app.route('/api-keys', apiKeysApp);
 app.route('/config', configApp);
 app.route('/crashes', crashesApp);
+app.route('/known-issues', knownIssuesApp);
 
 // OpenAPI documentation
 app.doc('/doc', {
@@ -6,6 +6,7 @@ export const crashReport = sqliteTable('crash_report', {
 	schemaVersion: integer('schema_version').notNull().default(1),
 	gameId: text('game_id').notNull(),
 	userId: text('user_id'),
+	apiKeyId: text('api_key_id'), // Key that submitted the report, if any
 
 	// Crash identification
 	crashHash: text('crash_hash').notNull(),
@@ -13,15 +14,36 @@ export const crashReport = sqliteTable('crash_report', {
 	exceptionCode: text('exception_code'),
 	exceptionAddress: text('exception_address'),
 	faultingModule: text('faulting_module'),
+	suspectedMod: text('suspected_mod'), // Mod that shipped the faulting module
+	crashCategory: text('crash_category'), // null-deref, stack-overflow, oom, ...
+	triageHint: text('triage_hint'), // What the category means and what to try
 
 	// Environment
 	gameVersion: text('game_version').notNull(),
+	gameEdition: text('game_edition'), // Normalized runtime (SE, AE, VR, GOG)
 	scriptExtenderVersion: text('script_extender_version'),
 	osVersion: text('os_version'),
 
 	// Load order (JSON array)
 	loadOrderJson: text('load_order_json').notNull(),
 	pluginCount: integer('plugin_count').notNull(),
+	nativePluginsJson: text('native_plugins_json'), // Script extender DLLs (JSON array)
+	modpackName: text('modpack_name'), // Wabbajack list or Nexus Collection
+	modpackVersion: text('modpack_version'),
+	graphicsInjectorsJson: text('graphics_injectors_json'), // ENB/ReShade (JSON array)
+	graphicsJson: text('graphics_json'), // Display mode, GPUs, upscalers (JSON object)
+	knownBadModulesJson: text('known_bad_modules_json'), // Crash-prone DLLs (JSON array)
+	breadcrumbsJson: text('breadcrumbs_json'), // Steps other mods recorded (JSON array)
+	modContextJson: text('mod_context_json'), // State other mods set (JSON object)
+	modErrorsJson: text('mod_errors_json'), // Errors other mods handled (JSON array)
+	extrasJson: text('extras_json'), // Free-form context (JSON object)
+	papyrusStacksJson: text('papyrus_stacks_json'), // Papyrus VM frames (JSON array)
+	gameStateJson: text('game_state_json'), // Location and game time (JSON object)
+	recentAssetsJson: text('recent_assets_json'), // Recent loose files (JSON array)
+	saveGameJson: text('save_game_json'), // Latest save vs load order (JSON)
+	uptimeSecs: integer('uptime_secs'), // Seconds the game had been running
+	secsSinceLastSave: integer('secs_since_last_save'), // Null if it never saved
+	frameTimesJson: text('frame_times_json'), // Frame time percentiles (JSON object)
 
 	// Timestamps (stored as ms since epoch)
 	crashedAt: integer('crashed_at', { mode: 'timestamp_ms' }).notNull(),
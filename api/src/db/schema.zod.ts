@@ -4,10 +4,153 @@ export const loadOrderItemSchema = z.object({
 	name: z.string(),
 	enabled: z.boolean().optional(),
 	index: z.number().int().optional(),
+	isLight: z.boolean().optional(),
+	masters: z.array(z.string()).optional(),
+	modName: z.string().optional(),
+	nexusId: z.number().int().optional(),
 });
 
 export const loadOrderSchema = z.array(loadOrderItemSchema);
 
+export const nativePluginSchema = z.object({
+	name: z.string(),
+	fileHash: z.string().optional(),
+	fileSize: z.number().int().optional(),
+	version: z.string().optional(),
+	index: z.number().int().optional(),
+	enabled: z.boolean().optional(),
+});
+
+export const nativePluginsSchema = z.array(nativePluginSchema).max(1000);
+
+export const graphicsInjectorSchema = z.object({
+	kind: z.enum(['enb', 'reshade', 'unknown']),
+	file: z.string(),
+	version: z.string().optional(),
+});
+
+export const graphicsInjectorsSchema = z.array(graphicsInjectorSchema).max(16);
+
+export const graphicsSchema = z.object({
+	width: z.number().int().min(0).optional(),
+	height: z.number().int().min(0).optional(),
+	refreshRate: z.number().int().min(0).optional(),
+	displayMode: z.enum(['fullscreen', 'borderless', 'windowed']).optional(),
+	hdr: z.boolean().optional(),
+	adapters: z
+		.array(
+			z.object({
+				name: z.string().max(255),
+				vendorId: z.number().int().min(0),
+				vramMb: z.number().int().min(0),
+				active: z.boolean(),
+			}),
+		)
+		.max(8)
+		.optional(),
+	upscalers: z
+		.array(
+			z.object({
+				kind: z.enum(['dlss', 'dlss-g', 'fsr', 'xess']),
+				file: z.string().max(255),
+				version: z.string().optional(),
+				loaded: z.boolean(),
+			}),
+		)
+		.max(16)
+		.optional(),
+});
+
+export const knownBadModulesSchema = z
+	.array(
+		z.object({
+			name: z.string().max(255),
+			version: z.string().optional(),
+			reason: z.string().max(500),
+		}),
+	)
+	.max(32);
+
+export const breadcrumbsSchema = z
+	.array(
+		z.object({
+			source: z.string().min(1).max(100),
+			message: z.string().max(500),
+			at: z.number().int(),
+		}),
+	)
+	.max(32);
+
+export const modContextSchema = z
+	.record(z.string().min(1).max(100), z.string().max(500))
+	.refine((context) => Object.keys(context).length <= 32, {
+		message: 'At most 32 context keys',
+	});
+
+export const extrasSchema = z
+	.record(z.string().min(1).max(100), z.string().max(500))
+	.refine((extras) => Object.keys(extras).length <= 32, {
+		message: 'At most 32 extras',
+	});
+
+export const modErrorsSchema = z
+	.array(
+		z.object({
+			source: z.string().min(1).max(100),
+			message: z.string().max(500),
+			count: z.number().int().min(1),
+			lastAt: z.number().int(),
+		}),
+	)
+	.max(16);
+
+export const papyrusFrameSchema = z.object({
+	stackId: z.number().int(),
+	depth: z.number().int(),
+	script: z.string(),
+	function: z.string(),
+	line: z.number().int().optional(),
+});
+
+export const papyrusStacksSchema = z.array(papyrusFrameSchema).max(256);
+
+export const gameStateSchema = z.object({
+	worldspace: z.string().max(255).optional(),
+	cell: z.string().max(255).optional(),
+	position: z.tuple([z.number(), z.number(), z.number()]).optional(),
+	daysPassed: z.number().optional(),
+});
+
+export const recentAssetsSchema = z.array(z.string().max(260)).max(64);
+
+export const crashCategorySchema = z.enum([
+	'null-deref',
+	'stack-overflow',
+	'oom',
+	'gpu',
+	'disk-io',
+	'hook-conflict',
+	'script',
+]);
+
+export const saveGameSchema = z.object({
+	saveNumber: z.number().int().min(0),
+	characterHash: z.string().max(64),
+	level: z.number().int().min(0),
+	playtime: z.string().max(50),
+	missingFromSave: z.array(z.string().max(255)).max(4096).optional(),
+	addedSinceSave: z.array(z.string().max(255)).max(4096).optional(),
+});
+
+export const frameTimesSchema = z.object({
+	samples: z.number().int().min(0),
+	p50Ms: z.number().min(0),
+	p99Ms: z.number().min(0),
+	minMs: z.number().min(0),
+	maxMs: z.number().min(0),
+	recentMs: z.array(z.number().min(0)).max(16).optional(),
+});
+
 // Individual field schemas for zocker supply()
 export const gameIdSchema = z.string().min(1);
 export const stackTraceSchema = z.string().min(1).max(100000);
@@ -34,12 +177,33 @@ export const createCrashReportSchema = z.object({
 	exceptionCode: z.string().max(50).optional(),
 	exceptionAddress: z.string().max(50).optional(),
 	faultingModule: z.string().max(255).optional(),
+	suspectedMod: z.string().max(255).optional(),
+	crashCategory: crashCategorySchema.optional(),
+	triageHint: z.string().max(500).optional(),
 	gameVersion: gameVersionSchema,
+	gameEdition: z.string().max(20).optional(),
 	scriptExtenderVersion: z.string().max(50).optional(),
 	osVersion: z.string().max(100).optional(),
 	loadOrderJson: loadOrderJsonSchema,
 	pluginCount: pluginCountSchema,
+	nativePlugins: nativePluginsSchema.optional(),
+	modpackName: z.string().min(1).max(100).optional(),
+	modpackVersion: z.string().max(50).optional(),
+	graphicsInjectors: graphicsInjectorsSchema.optional(),
+	graphics: graphicsSchema.optional(),
+	knownBadModules: knownBadModulesSchema.optional(),
+	breadcrumbs: breadcrumbsSchema.optional(),
+	modContext: modContextSchema.optional(),
+	modErrors: modErrorsSchema.optional(),
+	extras: extrasSchema.optional(),
+	papyrusStacks: papyrusStacksSchema.optional(),
+	gameState: gameStateSchema.optional(),
+	recentAssets: recentAssetsSchema.optional(),
+	saveGame: saveGameSchema.optional(),
 	crashedAt: crashedAtSchema,
+	uptimeSecs: z.number().int().min(0).optional(),
+	secsSinceLastSave: z.number().int().min(0).optional(),
+	frameTimes: frameTimesSchema.optional(),
 	notes: z.string().max(5000).optional(),
 });
 
@@ -1,9 +1,10 @@
 import { createRoute, OpenAPIHono, z } from '@hono/zod-openapi';
-import { eq } from 'drizzle-orm';
+import { and, count, eq } from 'drizzle-orm';
 import Sqids from 'sqids';
 import { ulid } from 'ulid';
 
 import { crashPattern, crashReport, db } from '@/db/index';
+import { hashApiKey, isValidApiKeyFormat } from '@/lib/api-key';
 import { computeCrashHash } from '@/lib/crash-hash';
 
 // Schemas
@@ -28,7 +29,35 @@ const CreateCrashReportSchema = z
 			.max(255)
 			.optional()
 			.openapi({ example: 'SkyrimSE.exe' }),
+		suspectedMod: z.string().max(255).optional().openapi({
+			description: 'Mod that most likely shipped the faulting module',
+			example: 'SSE Engine Fixes 7.0.18 (Nexus 17230)',
+		}),
+		crashCategory: z
+			.enum([
+				'null-deref',
+				'stack-overflow',
+				'oom',
+				'gpu',
+				'disk-io',
+				'hook-conflict',
+				'script',
+			])
+			.optional()
+			.openapi({
+				description: 'Kind of crash, classified by the client',
+				example: 'null-deref',
+			}),
+		triageHint: z.string().max(500).optional().openapi({
+			description: 'What the crash category means and what to try',
+			example:
+				'SkyrimSE.exe read from a null pointer (address 0x1A8). An object it expected was missing, often a form, mesh, or script target that a mod removed or never loaded.',
+		}),
 		gameVersion: z.string().min(1).max(50).openapi({ example: '1.6.1170' }),
+		gameEdition: z.string().max(20).optional().openapi({
+			description: 'Normalized runtime edition (SE, AE, VR, GOG)',
+			example: 'AE',
+		}),
 		scriptExtenderVersion: z
 			.string()
 			.max(50)
@@ -57,9 +86,282 @@ const CreateCrashReportSchema = z
 				example: '[{"name":"Skyrim.esm","enabled":true,"index":0}]',
 			}),
 		pluginCount: z.number().int().min(0).max(10000),
+		nativePlugins: z
+			.array(z.object({ name: z.string() }).passthrough())
+			.max(1000)
+			.optional()
+			.openapi({
+				description: 'Script extender DLL plugins with hashes and versions',
+				example: [
+					{
+						name: 'EngineFixes.dll',
+						fileHash: 'a1b2c3d4e5f60718',
+						version: '7.0.18.0',
+					},
+				],
+			}),
+		modpackName: z.string().min(1).max(100).optional().openapi({
+			description: 'Wabbajack list or Nexus Collection the game was installed from',
+			example: 'Lorerim',
+		}),
+		modpackVersion: z.string().max(50).optional().openapi({ example: '3.1.4' }),
+		graphicsInjectors: z
+			.array(
+				z.object({
+					kind: z.enum(['enb', 'reshade', 'unknown']),
+					file: z.string(),
+					version: z.string().optional(),
+				}),
+			)
+			.max(16)
+			.optional()
+			.openapi({
+				description: 'ENB/ReShade proxy DLLs found in the game directory',
+				example: [{ kind: 'enb', file: 'd3d11.dll' }],
+			}),
+		graphics: z
+			.object({
+				width: z.number().int().min(0).optional(),
+				height: z.number().int().min(0).optional(),
+				refreshRate: z.number().int().min(0).optional(),
+				displayMode: z.enum(['fullscreen', 'borderless', 'windowed']).optional(),
+				hdr: z.boolean().optional(),
+				adapters: z
+					.array(
+						z.object({
+							name: z.string().max(255),
+							vendorId: z.number().int().min(0),
+							vramMb: z.number().int().min(0),
+							active: z.boolean(),
+						}),
+					)
+					.max(8)
+					.optional(),
+				upscalers: z
+					.array(
+						z.object({
+							kind: z.enum(['dlss', 'dlss-g', 'fsr', 'xess']),
+							file: z.string().max(255),
+							version: z.string().optional(),
+							loaded: z.boolean(),
+						}),
+					)
+					.max(16)
+					.optional(),
+			})
+			.optional()
+			.openapi({
+				description:
+					'Resolution, window mode, HDR, GPUs with the one the game renders on, and upscaler DLLs',
+				example: {
+					width: 2560,
+					height: 1440,
+					refreshRate: 165,
+					displayMode: 'borderless',
+					hdr: false,
+					adapters: [
+						{
+							name: 'NVIDIA GeForce RTX 4070',
+							vendorId: 4318,
+							vramMb: 12282,
+							active: true,
+						},
+					],
+					upscalers: [
+						{
+							kind: 'dlss',
+							file: 'nvngx_dlss.dll',
+							version: '3.7.10.0',
+							loaded: true,
+						},
+					],
+				},
+			}),
+		knownBadModules: z
+			.array(
+				z.object({
+					name: z.string().max(255),
+					version: z.string().optional(),
+					reason: z.string().max(500),
+				}),
+			)
+			.max(32)
+			.optional()
+			.openapi({
+				description: 'Loaded DLLs known to crash games, and why each is flagged',
+				example: [
+					{
+						name: 'X3DAudio1_7.dll',
+						reason:
+							"An X3DAudio replacement in the game folder, usually left by an old audio fix.",
+					},
+				],
+			}),
+		breadcrumbs: z
+			.array(
+				z.object({
+					source: z.string().min(1).max(100),
+					message: z.string().max(500),
+					at: z.number().int(),
+				}),
+			)
+			.max(32)
+			.optional()
+			.openapi({
+				description: 'Steps other mods recorded through the C API, newest first',
+				example: [
+					{ source: 'MyQuestMod', message: 'Stage 40 started', at: 1704067190000 },
+				],
+			}),
+		modContext: z
+			.record(z.string().min(1).max(100), z.string().max(500))
+			.refine((context) => Object.keys(context).length <= 32, {
+				message: 'At most 32 context keys',
+			})
+			.optional()
+			.openapi({
+				description: 'Key/value state other mods set through the C API',
+				example: { 'MyQuestMod.stage': '40' },
+			}),
+		modErrors: z
+			.array(
+				z.object({
+					source: z.string().min(1).max(100),
+					message: z.string().max(500),
+					count: z.number().int().min(1),
+					lastAt: z.number().int(),
+				}),
+			)
+			.max(16)
+			.optional()
+			.openapi({
+				description: 'Errors other mods caught and reported through the C API',
+				example: [
+					{
+						source: 'MyQuestMod',
+						message: 'Actor 0x0001A2B3 not found',
+						count: 3,
+						lastAt: 1704067195000,
+					},
+				],
+			}),
+		extras: z
+			.record(z.string().min(1).max(100), z.string().max(500))
+			.refine((extras) => Object.keys(extras).length <= 32, {
+				message: 'At most 32 extras',
+			})
+			.optional()
+			.openapi({
+				description: 'Free-form context from the game plugin or an integration',
+				example: { difficulty: 'Legendary', quest: 'MQ101' },
+			}),
+		papyrusStacks: z
+			.array(
+				z.object({
+					stackId: z.number().int(),
+					depth: z.number().int(),
+					script: z.string(),
+					function: z.string(),
+					line: z.number().int().optional(),
+				}),
+			)
+			.max(256)
+			.optional()
+			.openapi({
+				description: 'Papyrus call stacks running at crash time (Skyrim)',
+				example: [
+					{
+						stackId: 7,
+						depth: 0,
+						script: 'MyModQuestScript',
+						function: 'OnUpdate',
+						line: 42,
+					},
+				],
+			}),
+		gameState: z
+			.object({
+				worldspace: z.string().max(255).optional(),
+				cell: z.string().max(255).optional(),
+				position: z.tuple([z.number(), z.number(), z.number()]).optional(),
+				daysPassed: z.number().optional(),
+			})
+			.optional()
+			.openapi({
+				description: 'Player location and in-game time at crash (Skyrim)',
+				example: {
+					worldspace: 'Tamriel',
+					cell: '0x0000DA6C (Skyrim.esm)',
+					position: [1024, -512.5, 64],
+					daysPassed: 3.5,
+				},
+			}),
+		recentAssets: z
+			.array(z.string().max(260))
+			.max(64)
+			.optional()
+			.openapi({
+				description: 'Asset paths opened last before the crash, newest first',
+				example: ['meshes\\armor\\mymod\\cuirass.nif'],
+			}),
+		saveGame: z
+			.object({
+				saveNumber: z.number().int().min(0),
+				characterHash: z.string().max(64),
+				level: z.number().int().min(0),
+				playtime: z.string().max(50),
+				missingFromSave: z
+					.array(z.string().max(255))
+					.max(4096)
+					.optional(),
+				addedSinceSave: z.array(z.string().max(255)).max(4096).optional(),
+			})
+			.optional()
+			.openapi({
+				description:
+					'Most recent save and plugins changed since it was made (Skyrim, FO4)',
+				example: {
+					saveNumber: 42,
+					characterHash: 'a1b2c3d4e5f60718',
+					level: 31,
+					playtime: '012.34.56',
+					missingFromSave: ['RemovedMod.esp'],
+					addedSinceSave: ['NewMod.esp'],
+				},
+			}),
 		crashedAt: z.number().int().positive().openapi({
 			description: 'Timestamp when crash occurred (ms since epoch)',
 		}),
+		uptimeSecs: z.number().int().min(0).optional().openapi({
+			description: 'Seconds the game process had been running',
+			example: 10800,
+		}),
+		secsSinceLastSave: z.number().int().min(0).optional().openapi({
+			description: 'Seconds since the game last saved, if it saved',
+			example: 95,
+		}),
+		frameTimes: z
+			.object({
+				samples: z.number().int().min(0),
+				p50Ms: z.number().min(0),
+				p99Ms: z.number().min(0),
+				minMs: z.number().min(0),
+				maxMs: z.number().min(0),
+				recentMs: z.array(z.number().min(0)).max(16).optional(),
+			})
+			.optional()
+			.openapi({
+				description:
+					'Frame time percentiles over the last frames, and the last frames newest first',
+				example: {
+					samples: 512,
+					p50Ms: 16.7,
+					p99Ms: 212.4,
+					minMs: 8.3,
+					maxMs: 640.2,
+					recentMs: [640.2, 512.8, 388.1, 212.4],
+				},
+			}),
 		notes: z.string().max(5000).optional(),
 	})
 	.openapi('CreateCrashReport');
@@ -92,12 +394,33 @@ const CrashReportSchema = z
 		exceptionCode: z.string().nullable(),
 		exceptionAddress: z.string().nullable(),
 		faultingModule: z.string().nullable(),
+		suspectedMod: z.string().nullable(),
+		crashCategory: z.string().nullable(),
+		triageHint: z.string().nullable(),
 		gameVersion: z.string(),
+		gameEdition: z.string().nullable(),
 		scriptExtenderVersion: z.string().nullable(),
 		osVersion: z.string().nullable(),
 		loadOrder: z.array(z.object({})),
 		pluginCount: z.number(),
+		nativePlugins: z.array(z.object({})),
+		modpackName: z.string().nullable(),
+		modpackVersion: z.string().nullable(),
+		graphicsInjectors: z.array(z.object({})),
+		graphics: z.object({}).nullable(),
+		knownBadModules: z.array(z.object({})),
+		breadcrumbs: z.array(z.object({})),
+		modContext: z.record(z.string(), z.string()),
+		modErrors: z.array(z.object({})),
+		extras: z.record(z.string(), z.string()),
+		papyrusStacks: z.array(z.object({})),
+		gameState: z.object({}).nullable(),
+		recentAssets: z.array(z.string()),
+		saveGame: z.object({}).nullable(),
 		crashedAt: z.number(),
+		uptimeSecs: z.number().nullable(),
+		secsSinceLastSave: z.number().nullable(),
+		frameTimes: z.object({}).nullable(),
 		submittedAt: z.number(),
 		isPublic: z.boolean(),
 		notes: z.string().nullable(),
@@ -105,6 +428,29 @@ const CrashReportSchema = z
 	})
 	.openapi('CrashReport');
 
+const CrashReportSummarySchema = z
+	.object({
+		id: z.string(),
+		gameId: z.string(),
+		gameVersion: z.string(),
+		crashHash: z.string(),
+		exceptionCode: z.string().nullable(),
+		faultingModule: z.string().nullable(),
+		crashedAt: z.number(),
+		submittedAt: z.number(),
+		isPublic: z.boolean(),
+	})
+	.openapi('CrashReportSummary');
+
+const CrashReportListSchema = z
+	.object({
+		reports: z.array(CrashReportSummarySchema),
+		page: z.number(),
+		perPage: z.number(),
+		total: z.number(),
+	})
+	.openapi('CrashReportList');
+
 const ValidationErrorSchema = z
 	.object({
 		error: z.object({
@@ -161,13 +507,47 @@ const submitCrashRoute = createRoute({
 	},
 });
 
+const listCrashesRoute = createRoute({
+	method: 'get',
+	path: '/',
+	tags: ['Crashes'],
+	summary: 'List my crash reports',
+	description:
+		'List crash reports submitted with the API key in the Authorization header, newest first.',
+	security: [{ apiKey: [] }],
+	request: {
+		query: z.object({
+			page: z.coerce.number().int().min(1).default(1),
+			perPage: z.coerce.number().int().min(1).max(100).default(20),
+		}),
+	},
+	responses: {
+		200: {
+			content: {
+				'application/json': {
+					schema: CrashReportListSchema,
+				},
+			},
+			description: 'Page of crash reports',
+		},
+		401: {
+			content: {
+				'application/json': {
+					schema: ErrorSchema,
+				},
+			},
+			description: 'Missing, invalid, or expired API key',
+		},
+	},
+});
+
 const getCrashRoute = createRoute({
 	method: 'get',
 	path: '/{id}',
 	tags: ['Crashes'],
 	summary: 'Get crash report',
 	description:
-		'Retrieve a crash report by ID. Requires share token for non-public reports.',
+		'Retrieve a crash report by ID. Non-public reports require the share token or the API key that submitted them.',
 	request: {
 		params: z.object({
 			id: z.string().openapi({ description: 'Crash report ID (ULID)' }),
@@ -198,9 +578,131 @@ const getCrashRoute = createRoute({
 	},
 });
 
+const deleteCrashRoute = createRoute({
+	method: 'delete',
+	path: '/{id}',
+	tags: ['Crashes'],
+	summary: 'Delete crash report',
+	description:
+		'Permanently delete a crash report submitted with the API key in the Authorization header.',
+	security: [{ apiKey: [] }],
+	request: {
+		params: z.object({
+			id: z.string().openapi({ description: 'Crash report ID (ULID)' }),
+		}),
+	},
+	responses: {
+		204: {
+			description: 'Crash report deleted',
+		},
+		401: {
+			content: {
+				'application/json': {
+					schema: ErrorSchema,
+				},
+			},
+			description: 'Missing, invalid, or expired API key',
+		},
+		404: {
+			content: {
+				'application/json': {
+					schema: ErrorSchema,
+				},
+			},
+			description: 'Crash report not found',
+		},
+	},
+});
+
+const MAX_NOTES_LENGTH = 5000;
+
+const appendNoteRoute = createRoute({
+	method: 'post',
+	path: '/{id}/notes',
+	tags: ['Crashes'],
+	summary: 'Append note',
+	description:
+		'Append a note to a crash report, e.g. what the user was doing. Requires the share token or the API key that submitted the report.',
+	request: {
+		params: z.object({
+			id: z.string().openapi({ description: 'Crash report ID (ULID)' }),
+		}),
+		query: z.object({
+			token: z.string().optional().openapi({
+				description: 'Share token returned on submission',
+			}),
+		}),
+		body: {
+			content: {
+				'application/json': {
+					schema: z.object({
+						text: z.string().trim().min(1).max(MAX_NOTES_LENGTH),
+					}),
+				},
+			},
+			required: true,
+		},
+	},
+	responses: {
+		200: {
+			content: {
+				'application/json': {
+					schema: z.object({ notes: z.string() }),
+				},
+			},
+			description: 'Note appended; returns all notes',
+		},
+		404: {
+			content: {
+				'application/json': {
+					schema: ErrorSchema,
+				},
+			},
+			description: 'Crash report not found',
+		},
+		422: {
+			content: {
+				'application/json': {
+					schema: ErrorSchema,
+				},
+			},
+			description: 'Notes would exceed the maximum length',
+		},
+	},
+});
+
 // App and handlers
 const crashesApp = new OpenAPIHono();
 
+/**
+ * Resolve the API key sent as `Authorization: Bearer <key>`.
+ * Returns the key ID, or null if the header is missing or the key is
+ * malformed, unknown, or expired.
+ */
+async function authenticatedKeyId(
+	header: string | undefined,
+): Promise<string | null> {
+	const key = header?.match(/^Bearer\s+(\S+)$/)?.[1];
+	if (!key || !isValidApiKeyFormat(key)) {
+		return null;
+	}
+
+	const existingKey = await db.query.apiKey.findFirst({
+		where: (k, { eq }) => eq(k.keyHash, hashApiKey(key)),
+	});
+	if (!existingKey) {
+		return null;
+	}
+	if (existingKey.expiresAt && existingKey.expiresAt <= new Date()) {
+		return null;
+	}
+	return existingKey.id;
+}
+
+const unauthorized = {
+	error: { code: 'UNAUTHORIZED', message: 'Invalid or missing API key' },
+};
+
 // Sqids for URL-safe share tokens
 const sqids = new Sqids({
 	minLength: 12,
@@ -221,23 +723,56 @@ crashesApp.openapi(submitCrashRoute, async (c) => {
 	const id = ulid();
 	const shareToken = generateShareToken();
 	const crashHash = body.crashHash || computeCrashHash(body.stackTrace);
+	const apiKeyId = await authenticatedKeyId(c.req.header('Authorization'));
 	const now = new Date();
 
 	await db.insert(crashReport).values({
 		id,
 		schemaVersion: body.schemaVersion,
 		gameId: body.gameId,
+		apiKeyId,
 		crashHash,
 		stackTrace: body.stackTrace,
 		exceptionCode: body.exceptionCode,
 		exceptionAddress: body.exceptionAddress,
 		faultingModule: body.faultingModule,
+		suspectedMod: body.suspectedMod,
+		crashCategory: body.crashCategory,
+		triageHint: body.triageHint,
 		gameVersion: body.gameVersion,
+		gameEdition: body.gameEdition,
 		scriptExtenderVersion: body.scriptExtenderVersion,
 		osVersion: body.osVersion,
 		loadOrderJson: body.loadOrderJson,
 		pluginCount: body.pluginCount,
+		nativePluginsJson: body.nativePlugins
+			? JSON.stringify(body.nativePlugins)
+			: null,
+		modpackName: body.modpackName,
+		modpackVersion: body.modpackVersion,
+		graphicsInjectorsJson: body.graphicsInjectors
+			? JSON.stringify(body.graphicsInjectors)
+			: null,
+		graphicsJson: body.graphics ? JSON.stringify(body.graphics) : null,
+		knownBadModulesJson: body.knownBadModules
+			? JSON.stringify(body.knownBadModules)
+			: null,
+		breadcrumbsJson: body.breadcrumbs ? JSON.stringify(body.breadcrumbs) : null,
+		modContextJson: body.modContext ? JSON.stringify(body.modContext) : null,
+		modErrorsJson: body.modErrors ? JSON.stringify(body.modErrors) : null,
+		extrasJson: body.extras ? JSON.stringify(body.extras) : null,
+		papyrusStacksJson: body.papyrusStacks
+			? JSON.stringify(body.papyrusStacks)
+			: null,
+		gameStateJson: body.gameState ? JSON.stringify(body.gameState) : null,
+		recentAssetsJson: body.recentAssets
+			? JSON.stringify(body.recentAssets)
+			: null,
+		saveGameJson: body.saveGame ? JSON.stringify(body.saveGame) : null,
 		crashedAt: new Date(body.crashedAt),
+		uptimeSecs: body.uptimeSecs,
+		secsSinceLastSave: body.secsSinceLastSave,
+		frameTimesJson: body.frameTimes ? JSON.stringify(body.frameTimes) : null,
 		submittedAt: now,
 		shareToken,
 		notes: body.notes,
@@ -289,8 +824,12 @@ crashesApp.openapi(getCrashRoute, async (c) => {
 		);
 	}
 
-	// Check access: public or valid share token
-	if (!report.isPublic && report.shareToken !== token) {
+	// Check access: public, valid share token, or the submitting API key
+	const isOwner =
+		report.apiKeyId !== null &&
+		report.apiKeyId ===
+			(await authenticatedKeyId(c.req.header('Authorization')));
+	if (!report.isPublic && report.shareToken !== token && !isOwner) {
 		return c.json(
 			{ error: { code: 'NOT_FOUND', message: 'Crash report not found' } },
 			404,
@@ -311,12 +850,47 @@ crashesApp.openapi(getCrashRoute, async (c) => {
 		exceptionCode: report.exceptionCode,
 		exceptionAddress: report.exceptionAddress,
 		faultingModule: report.faultingModule,
+		suspectedMod: report.suspectedMod,
+		crashCategory: report.crashCategory,
+		triageHint: report.triageHint,
 		gameVersion: report.gameVersion,
+		gameEdition: report.gameEdition,
 		scriptExtenderVersion: report.scriptExtenderVersion,
 		osVersion: report.osVersion,
 		loadOrder: JSON.parse(report.loadOrderJson),
 		pluginCount: report.pluginCount,
+		nativePlugins: report.nativePluginsJson
+			? JSON.parse(report.nativePluginsJson)
+			: [],
+		modpackName: report.modpackName,
+		modpackVersion: report.modpackVersion,
+		graphicsInjectors: report.graphicsInjectorsJson
+			? JSON.parse(report.graphicsInjectorsJson)
+			: [],
+		graphics: report.graphicsJson ? JSON.parse(report.graphicsJson) : null,
+		knownBadModules: report.knownBadModulesJson
+			? JSON.parse(report.knownBadModulesJson)
+			: [],
+		breadcrumbs: report.breadcrumbsJson ? JSON.parse(report.breadcrumbsJson) : [],
+		modContext: report.modContextJson ? JSON.parse(report.modContextJson) : {},
+		modErrors: report.modErrorsJson ? JSON.parse(report.modErrorsJson) : [],
+		extras: report.extrasJson ? JSON.parse(report.extrasJson) : {},
+		papyrusStacks: report.papyrusStacksJson
+			? JSON.parse(report.papyrusStacksJson)
+			: [],
+		gameState: report.gameStateJson
+			? JSON.parse(report.gameStateJson)
+			: null,
+		recentAssets: report.recentAssetsJson
+			? JSON.parse(report.recentAssetsJson)
+			: [],
+		saveGame: report.saveGameJson ? JSON.parse(report.saveGameJson) : null,
 		crashedAt: report.crashedAt.getTime(),
+		uptimeSecs: report.uptimeSecs,
+		secsSinceLastSave: report.secsSinceLastSave,
+		frameTimes: report.frameTimesJson
+			? JSON.parse(report.frameTimesJson)
+			: null,
 		submittedAt: report.submittedAt.getTime(),
 		isPublic: report.isPublic,
 		notes: report.notes,
@@ -331,4 +905,104 @@ crashesApp.openapi(getCrashRoute, async (c) => {
 	});
 });
 
+crashesApp.openapi(listCrashesRoute, async (c) => {
+	const apiKeyId = await authenticatedKeyId(c.req.header('Authorization'));
+	if (!apiKeyId) {
+		return c.json(unauthorized, 401);
+	}
+
+	const { page, perPage } = c.req.valid('query');
+	const reports = await db.query.crashReport.findMany({
+		where: (r, { eq }) => eq(r.apiKeyId, apiKeyId),
+		orderBy: (r, { desc }) => [desc(r.submittedAt), desc(r.id)],
+		limit: perPage,
+		offset: (page - 1) * perPage,
+	});
+	const [{ total }] = await db
+		.select({ total: count() })
+		.from(crashReport)
+		.where(eq(crashReport.apiKeyId, apiKeyId));
+
+	return c.json(
+		{
+			reports: reports.map((r) => ({
+				id: r.id,
+				gameId: r.gameId,
+				gameVersion: r.gameVersion,
+				crashHash: r.crashHash,
+				exceptionCode: r.exceptionCode,
+				faultingModule: r.faultingModule,
+				crashedAt: r.crashedAt.getTime(),
+				submittedAt: r.submittedAt.getTime(),
+				isPublic: r.isPublic,
+			})),
+			page,
+			perPage,
+			total,
+		},
+		200,
+	);
+});
+
+crashesApp.openapi(deleteCrashRoute, async (c) => {
+	const apiKeyId = await authenticatedKeyId(c.req.header('Authorization'));
+	if (!apiKeyId) {
+		return c.json(unauthorized, 401);
+	}
+
+	const { id } = c.req.valid('param');
+	const deleted = await db
+		.delete(crashReport)
+		.where(and(eq(crashReport.id, id), eq(crashReport.apiKeyId, apiKeyId)))
+		.returning({ id: crashReport.id });
+
+	if (deleted.length === 0) {
+		return c.json(
+			{ error: { code: 'NOT_FOUND', message: 'Crash report not found' } },
+			404,
+		);
+	}
+
+	return c.body(null, 204);
+});
+
+crashesApp.openapi(appendNoteRoute, async (c) => {
+	const { id } = c.req.valid('param');
+	const { token } = c.req.valid('query');
+	const { text } = c.req.valid('json');
+
+	const report = await db.query.crashReport.findFirst({
+		where: (r, { eq }) => eq(r.id, id),
+	});
+
+	// Same 404 for missing and inaccessible reports, as in GET /crashes/{id}
+	const isOwner = report?.apiKeyId
+		? report.apiKeyId ===
+			(await authenticatedKeyId(c.req.header('Authorization')))
+		: false;
+	if (!report || (report.shareToken !== token && !isOwner)) {
+		return c.json(
+			{ error: { code: 'NOT_FOUND', message: 'Crash report not found' } },
+			404,
+		);
+	}
+
+	const notes = report.notes ? `${report.notes}\n\n${text}` : text;
+	if (notes.length > MAX_NOTES_LENGTH) {
+		return c.json(
+			{
+				error: {
+					code: 'NOTES_TOO_LONG',
+					message: `Notes cannot exceed ${MAX_NOTES_LENGTH} characters`,
+				},
+			},
+			422,
+		);
+	}
+
+	await db.update(crashReport).set({ notes }).where(eq(crashReport.id, id));
+
+	return c.json({ notes }, 200);
+});
+
 export { crashesApp };
@@ -0,0 +1,95 @@
+import { createRoute, OpenAPIHono, z } from '@hono/zod-openapi';
+
+import { db } from '@/db/index';
+
+// Schemas
+const KnownIssueSchema = z
+	.object({
+		crashHash: z.string(),
+		patternName: z.string().nullable(),
+		knownFix: z.string().openapi({
+			example: 'Caused by OldMod.dll < 1.4 - update OldMod',
+		}),
+		occurrenceCount: z.number(),
+		suspectedMods: z.array(z.string()),
+	})
+	.openapi('KnownIssue');
+
+const ErrorSchema = z
+	.object({
+		error: z.object({
+			code: z.string(),
+			message: z.string(),
+		}),
+	})
+	.openapi('Error');
+
+// Routes
+const getKnownIssueRoute = createRoute({
+	method: 'get',
+	path: '/{crashHash}',
+	tags: ['Known Issues'],
+	summary: 'Get known issue',
+	description:
+		'Look up the known fix for a crash signature. Returns 404 when the signature has no recorded fix.',
+	request: {
+		params: z.object({
+			crashHash: z
+				.string()
+				.min(1)
+				.max(64)
+				.openapi({ description: 'Crash hash (as computed on submission)' }),
+		}),
+	},
+	responses: {
+		200: {
+			content: {
+				'application/json': {
+					schema: KnownIssueSchema,
+				},
+			},
+			description: 'Known issue for this crash signature',
+		},
+		404: {
+			content: {
+				'application/json': {
+					schema: ErrorSchema,
+				},
+			},
+			description: 'No known fix for this crash signature',
+		},
+	},
+});
+
+// App and handlers
+const knownIssuesApp = new OpenAPIHono();
+
+knownIssuesApp.openapi(getKnownIssueRoute, async (c) => {
+	const { crashHash } = c.req.valid('param');
+
+	const pattern = await db.query.crashPattern.findFirst({
+		where: (p, { eq }) => eq(p.crashHash, crashHash),
+	});
+
+	if (!pattern?.knownFix) {
+		return c.json(
+			{ error: { code: 'NOT_FOUND', message: 'No known issue for this crash' } },
+			404,
+		);
+	}
+
+	return c.json(
+		{
+			crashHash: pattern.crashHash,
+			patternName: pattern.patternName,
+			knownFix: pattern.knownFix,
+			occurrenceCount: pattern.occurrenceCount,
+			suspectedMods: pattern.suspectedModsJson
+				? (JSON.parse(pattern.suspectedModsJson) as string[])
+				: [],
+		},
+		200,
+	);
+});
+
+export { knownIssuesApp };
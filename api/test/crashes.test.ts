@@ -78,6 +78,329 @@ describe('GET /crashes/:id', () => {
 		expect(json.loadOrder).toBeInstanceOf(Array);
 	});
 
+	it('returns suspected mod and native plugins', async () => {
+		const report = {
+			...mockCrashReport(),
+			suspectedMod: 'SSE Engine Fixes 7.0.18 (Nexus 17230)',
+			nativePlugins: [
+				{
+					name: 'EngineFixes.dll',
+					fileHash: 'a1b2c3d4e5f60718',
+					fileSize: 1024,
+				},
+			],
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.suspectedMod).toBe(report.suspectedMod);
+		expect(json.nativePlugins).toEqual(report.nativePlugins);
+	});
+
+	it('returns game edition', async () => {
+		const report = { ...mockCrashReport(), gameEdition: 'VR' };
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.gameEdition).toBe('VR');
+	});
+
+	it('returns the modpack', async () => {
+		const report = {
+			...mockCrashReport(),
+			modpackName: 'Lorerim',
+			modpackVersion: '3.1.4',
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.modpackName).toBe('Lorerim');
+		expect(json.modpackVersion).toBe('3.1.4');
+	});
+
+	it('returns papyrus stacks', async () => {
+		const report = {
+			...mockCrashReport(),
+			papyrusStacks: [
+				{
+					stackId: 7,
+					depth: 0,
+					script: 'MyModQuestScript',
+					function: 'OnUpdate',
+					line: 42,
+				},
+				{
+					stackId: 7,
+					depth: 1,
+					script: 'MyModQuestScript',
+					function: 'OnInit',
+				},
+			],
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.papyrusStacks).toEqual(report.papyrusStacks);
+	});
+
+	it('returns game state', async () => {
+		const report = {
+			...mockCrashReport(),
+			gameState: {
+				worldspace: 'Tamriel',
+				cell: '0x0000DA6C (Skyrim.esm)',
+				position: [1024, -512.5, 64],
+				daysPassed: 3.5,
+			},
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.gameState).toEqual(report.gameState);
+	});
+
+	it('returns recent assets', async () => {
+		const report = {
+			...mockCrashReport(),
+			recentAssets: [
+				'meshes\\armor\\mymod\\cuirass.nif',
+				'textures\\armor\\mymod\\cuirass.dds',
+			],
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.recentAssets).toEqual(report.recentAssets);
+	});
+
+	it('returns crash category and triage hint', async () => {
+		const report = {
+			...mockCrashReport(),
+			crashCategory: 'disk-io',
+			triageHint: "Windows couldn't read a game file from disk.",
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.crashCategory).toBe('disk-io');
+		expect(json.triageHint).toBe(report.triageHint);
+	});
+
+	it('returns uptime and time since the last save', async () => {
+		const report = {
+			...mockCrashReport(),
+			uptimeSecs: 10800,
+			secsSinceLastSave: 95,
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.uptimeSecs).toBe(10800);
+		expect(json.secsSinceLastSave).toBe(95);
+	});
+
+	it('returns frame times', async () => {
+		const report = {
+			...mockCrashReport(),
+			frameTimes: {
+				samples: 512,
+				p50Ms: 16.7,
+				p99Ms: 212.4,
+				minMs: 8.3,
+				maxMs: 640.2,
+				recentMs: [640.2, 512.8],
+			},
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.frameTimes).toEqual(report.frameTimes);
+	});
+
+	it('returns save game details', async () => {
+		const report = {
+			...mockCrashReport(),
+			saveGame: {
+				saveNumber: 42,
+				characterHash: 'a1b2c3d4e5f60718',
+				level: 31,
+				playtime: '012.34.56',
+				missingFromSave: ['RemovedMod.esp'],
+				addedSinceSave: ['NewMod.esp'],
+			},
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.saveGame).toEqual(report.saveGame);
+	});
+
+	it('returns graphics injectors', async () => {
+		const report = {
+			...mockCrashReport(),
+			graphicsInjectors: [
+				{ kind: 'enb', file: 'd3d11.dll' },
+				{ kind: 'reshade', file: 'dxgi.dll', version: '5.9.2.1' },
+			],
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.graphicsInjectors).toEqual(report.graphicsInjectors);
+	});
+
+	it('returns graphics settings', async () => {
+		const report = {
+			...mockCrashReport(),
+			graphics: {
+				width: 1920,
+				height: 1080,
+				displayMode: 'fullscreen',
+				hdr: true,
+				adapters: [
+					{ name: 'Intel(R) UHD Graphics', vendorId: 32902, vramMb: 128, active: false },
+					{ name: 'NVIDIA GeForce RTX 4060 Laptop GPU', vendorId: 4318, vramMb: 8188, active: true },
+				],
+				upscalers: [{ kind: 'xess', file: 'libxess.dll', loaded: false }],
+			},
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.graphics).toEqual(report.graphics);
+	});
+
+	it('returns known bad modules', async () => {
+		const report = {
+			...mockCrashReport(),
+			knownBadModules: [
+				{ name: 'nvngx_dlss.dll', version: '1.0.13.0', reason: 'A DLSS 1.x DLL.' },
+			],
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.knownBadModules).toEqual(report.knownBadModules);
+	});
+
+	it('returns breadcrumbs, context, and errors from other mods', async () => {
+		const report = {
+			...mockCrashReport(),
+			breadcrumbs: [{ source: 'MyQuestMod', message: 'Stage 40 started', at: 1000 }],
+			modContext: { 'MyQuestMod.stage': '40' },
+			modErrors: [
+				{ source: 'MyQuestMod', message: 'Actor not found', count: 3, lastAt: 2000 },
+			],
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.breadcrumbs).toEqual(report.breadcrumbs);
+		expect(json.modContext).toEqual(report.modContext);
+		expect(json.modErrors).toEqual(report.modErrors);
+	});
+
+	it('returns extras', async () => {
+		const report = {
+			...mockCrashReport(),
+			extras: { difficulty: 'Legendary', quest: 'MQ101' },
+		};
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.extras).toEqual(report.extras);
+	});
+
 	it('returns 404 for private report without token', async () => {
 		// Create a report
 		const report = mockCrashReport();
@@ -100,3 +423,131 @@ describe('GET /crashes/:id', () => {
 		expect(res.status).toBe(404);
 	});
 });
+
+async function createApiKey(): Promise<string> {
+	const res = await app.request('/api-keys', {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify({ name: 'test' }),
+	});
+	const { key } = await res.json();
+	return key;
+}
+
+async function submitWithKey(key: string): Promise<string> {
+	const res = await app.request('/crashes', {
+		method: 'POST',
+		headers: {
+			'Content-Type': 'application/json',
+			Authorization: `Bearer ${key}`,
+		},
+		body: JSON.stringify(mockCrashReport()),
+	});
+	const { id } = await res.json();
+	return id;
+}
+
+describe('GET /crashes', () => {
+	it('lists only reports submitted with the API key', async () => {
+		const key = await createApiKey();
+		const otherKey = await createApiKey();
+		const first = await submitWithKey(key);
+		const second = await submitWithKey(key);
+		await submitWithKey(otherKey);
+
+		const res = await app.request('/crashes?perPage=1', {
+			headers: { Authorization: `Bearer ${key}` },
+		});
+
+		expect(res.status).toBe(200);
+		const json = await res.json();
+		expect(json.total).toBe(2);
+		expect(json.perPage).toBe(1);
+		expect(json.reports).toHaveLength(1);
+		expect([first, second]).toContain(json.reports[0].id);
+	});
+
+	it('requires an API key', async () => {
+		const res = await app.request('/crashes');
+
+		expect(res.status).toBe(401);
+	});
+});
+
+describe('DELETE /crashes/:id', () => {
+	it('deletes a report owned by the API key', async () => {
+		const key = await createApiKey();
+		const id = await submitWithKey(key);
+
+		const ownerRes = await app.request(`/crashes/${id}`, {
+			headers: { Authorization: `Bearer ${key}` },
+		});
+		expect(ownerRes.status).toBe(200);
+
+		const res = await app.request(`/crashes/${id}`, {
+			method: 'DELETE',
+			headers: { Authorization: `Bearer ${key}` },
+		});
+		expect(res.status).toBe(204);
+
+		const after = await app.request(`/crashes/${id}`, {
+			headers: { Authorization: `Bearer ${key}` },
+		});
+		expect(after.status).toBe(404);
+	});
+
+	it('returns 404 for reports owned by another key', async () => {
+		const id = await submitWithKey(await createApiKey());
+
+		const res = await app.request(`/crashes/${id}`, {
+			method: 'DELETE',
+			headers: { Authorization: `Bearer ${await createApiKey()}` },
+		});
+
+		expect(res.status).toBe(404);
+	});
+});
+
+describe('POST /crashes/:id/notes', () => {
+	it('appends notes with the share token', async () => {
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify({ ...mockCrashReport(), notes: undefined }),
+		});
+		const { id, shareToken } = await createRes.json();
+
+		for (const text of ['Opened the map', 'Fast travelled to Whiterun']) {
+			const res = await app.request(
+				`/crashes/${id}/notes?token=${shareToken}`,
+				{
+					method: 'POST',
+					headers: { 'Content-Type': 'application/json' },
+					body: JSON.stringify({ text }),
+				},
+			);
+			expect(res.status).toBe(200);
+		}
+
+		const res = await app.request(`/crashes/${id}?token=${shareToken}`);
+		const json = await res.json();
+		expect(json.notes).toBe('Opened the map\n\nFast travelled to Whiterun');
+	});
+
+	it('returns 404 without the share token', async () => {
+		const createRes = await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(mockCrashReport()),
+		});
+		const { id } = await createRes.json();
+
+		const res = await app.request(`/crashes/${id}/notes`, {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify({ text: 'hello' }),
+		});
+
+		expect(res.status).toBe(404);
+	});
+});
@@ -0,0 +1,61 @@
+import { eq } from 'drizzle-orm';
+import { describe, expect, it } from 'vitest';
+
+import { app } from '../src/app';
+import { crashPattern, db } from '../src/db/index';
+import { computeCrashHash } from '../src/lib/crash-hash';
+import { mockCrashReport } from './fixtures';
+
+describe('GET /known-issues/:crashHash', () => {
+	it('returns the known fix for a recorded pattern', async () => {
+		const report = mockCrashReport();
+		report.stackTrace = '[0] 0x7FF712345678 OldMod.dll+0x1234';
+		report.crashHash = undefined;
+		await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+
+		const crashHash = computeCrashHash(report.stackTrace);
+		await db
+			.update(crashPattern)
+			.set({
+				patternName: 'OldMod null deref',
+				knownFix: 'Caused by OldMod.dll < 1.4 - update OldMod',
+				suspectedModsJson: JSON.stringify(['OldMod.esp']),
+			})
+			.where(eq(crashPattern.crashHash, crashHash));
+
+		const res = await app.request(`/known-issues/${crashHash}`);
+
+		expect(res.status).toBe(200);
+		const json = await res.json();
+		expect(json.crashHash).toBe(crashHash);
+		expect(json.knownFix).toContain('OldMod.dll < 1.4');
+		expect(json.suspectedMods).toEqual(['OldMod.esp']);
+	});
+
+	it('returns 404 for a pattern without a known fix', async () => {
+		const report = mockCrashReport();
+		report.stackTrace = '[0] 0x7FF712345678 NewMod.dll+0x5678';
+		report.crashHash = undefined;
+		await app.request('/crashes', {
+			method: 'POST',
+			headers: { 'Content-Type': 'application/json' },
+			body: JSON.stringify(report),
+		});
+
+		const res = await app.request(
+			`/known-issues/${computeCrashHash(report.stackTrace)}`,
+		);
+
+		expect(res.status).toBe(404);
+	});
+
+	it('returns 404 for an unknown hash', async () => {
+		const res = await app.request('/known-issues/0000000000000000');
+
+		expect(res.status).toBe(404);
+	});
+});
@@ -0,0 +1,236 @@
+//! Disassembly of the instructions around a crash.
+//!
+//! In a module without symbols, the faulting instruction says what kind of
+//! bug it was: `mov rax, [rcx+0x1a8]` reads through a bad object pointer,
+//! `call qword ptr [rax]` through a bad vtable. [`read_code`] copies the
+//! bytes around the exception address with `ReadProcessMemory`, which fails
+//! rather than faults on unmapped pages, and [`disassemble`] decodes them
+//! with iced-x86 into one Intel-syntax line per instruction, the faulting
+//! one marked with `>`:
+//!
+//! ```text
+//!   0x00007FF600001000  4883EC28          sub rsp, 0x28
+//! > 0x00007FF600001004  488B81A8010000    mov rax, [rcx+0x1a8]
+//!   0x00007FF60000100B  FF10              call qword ptr [rax]
+//! ```
+//!
+//! x86 instructions vary in length, so the bytes before the fault can't be
+//! decoded backwards. [`disassemble`] decodes from the earliest offset in
+//! the window whose instructions line up with the exception address.
+
+use std::fmt::Write;
+
+use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction, IntelFormatter};
+
+use crate::exception::is_crt_termination;
+
+/// Bytes read before the exception address.
+pub const BYTES_BEFORE: u64 = 16;
+
+/// Bytes read from the exception address on, enough for the longest
+/// instruction.
+pub const BYTES_AFTER: u64 = 16;
+
+/// Most instructions shown before the faulting one.
+const MAX_BEFORE: usize = 8;
+
+/// Most instructions shown in all.
+const MAX_INSTRUCTIONS: usize = 16;
+
+/// Longest text [`disassemble`] returns, the limit of a report's
+/// `disassembly`.
+pub const MAX_TEXT_LEN: usize = 2000;
+
+/// Code bytes copied from around an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeWindow {
+    /// Address of the first byte.
+    pub start: u64,
+    /// The bytes, up to [`BYTES_BEFORE`] + [`BYTES_AFTER`].
+    pub bytes: Vec<u8>,
+}
+
+/// Reads and disassembles the code around an exception's address.
+///
+/// Returns `None` if the code can't be read or doesn't decode, and for CRT
+/// terminations, whose address is in the hook that reported them.
+pub fn disassemble_exception(code: u32, address: u64) -> Option<String> {
+    if is_crt_termination(code) {
+        return None;
+    }
+    read_code(address).and_then(|window| disassemble(&window, address))
+}
+
+/// Copies the code around `address` from this process.
+///
+/// Falls back to the bytes from `address` on when the ones before it are on
+/// an unmapped page. Returns `None` if `address` itself can't be read, as
+/// when the crash was a jump to a bad address.
+#[cfg(windows)]
+pub fn read_code(address: u64) -> Option<CodeWindow> {
+    use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let attempts = [
+        (
+            address.saturating_sub(BYTES_BEFORE),
+            BYTES_BEFORE + BYTES_AFTER,
+        ),
+        (address, BYTES_AFTER),
+    ];
+    attempts.into_iter().find_map(|(start, len)| {
+        let mut bytes = vec![0u8; len as usize];
+        let mut read = 0usize;
+        // SAFETY: The buffer is writable for its whole length; the source
+        // is checked by ReadProcessMemory
+        unsafe {
+            ReadProcessMemory(
+                GetCurrentProcess(),
+                start as *const std::ffi::c_void,
+                bytes.as_mut_ptr().cast(),
+                bytes.len(),
+                Some(&mut read),
+            )
+        }
+        .ok()?;
+        bytes.truncate(read);
+        Some(CodeWindow { start, bytes })
+    })
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+pub fn read_code(_address: u64) -> Option<CodeWindow> {
+    None
+}
+
+/// Disassembles a window of this process's code (x86-64, or x86 in a 32-bit
+/// process), marking the instruction at `fault`.
+///
+/// Returns `None` if `fault` is outside the window or the code there
+/// doesn't decode.
+pub fn disassemble(window: &CodeWindow, fault: u64) -> Option<String> {
+    let fault_offset = usize::try_from(fault.checked_sub(window.start)?).ok()?;
+    if fault_offset >= window.bytes.len() {
+        return None;
+    }
+
+    let (instructions, fault_index) = (0..=fault_offset).find_map(|offset| {
+        let instructions = decode(&window.bytes[offset..], window.start + offset as u64);
+        let fault_index = instructions.iter().position(|i| i.ip() == fault)?;
+        Some((instructions, fault_index))
+    })?;
+
+    let mut formatter = IntelFormatter::new();
+    let options = formatter.options_mut();
+    options.set_hex_prefix("0x");
+    options.set_hex_suffix("");
+    options.set_uppercase_hex(false);
+    options.set_space_after_operand_separator(true);
+
+    let mut text = String::new();
+    let mut line = String::new();
+    let first = fault_index.saturating_sub(MAX_BEFORE);
+    for instruction in instructions.iter().skip(first).take(MAX_INSTRUCTIONS) {
+        let offset = (instruction.ip() - window.start) as usize;
+        let hex: String = window.bytes[offset..offset + instruction.len()]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+        let marker = if instruction.ip() == fault { '>' } else { ' ' };
+
+        line.clear();
+        let _ = write!(
+            line,
+            "{} 0x{:016X}  {:<16}  ",
+            marker,
+            instruction.ip(),
+            hex
+        );
+        formatter.format(instruction, &mut line);
+        if text.len() + line.len() + 1 > MAX_TEXT_LEN {
+            break;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&line);
+    }
+    Some(text)
+}
+
+/// Decodes instructions from `bytes` until the first invalid or truncated
+/// one.
+fn decode(bytes: &[u8], ip: u64) -> Vec<Instruction> {
+    let mut decoder = Decoder::with_ip(usize::BITS, bytes, ip, DecoderOptions::NONE);
+    let mut instructions = Vec::new();
+    while decoder.can_decode() {
+        let instruction = decoder.decode();
+        if instruction.is_invalid() {
+            break;
+        }
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 0x7FF600001000;
+
+    /// `sub rsp, 0x28`, `mov rax, [rcx+0x1a8]`, `call qword ptr [rax]`, `ret`.
+    const CODE: [u8; 14] = [
+        0x48, 0x83, 0xEC, 0x28, 0x48, 0x8B, 0x81, 0xA8, 0x01, 0x00, 0x00, 0xFF, 0x10, 0xC3,
+    ];
+
+    #[test]
+    fn marks_the_faulting_instruction() {
+        let window = CodeWindow {
+            start: START,
+            bytes: CODE.to_vec(),
+        };
+
+        let text = disassemble(&window, START + 4).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            "  0x00007FF600001000  4883EC28          sub rsp, 0x28"
+        );
+        assert_eq!(
+            lines[1],
+            "> 0x00007FF600001004  488B81A8010000    mov rax, [rcx+0x1a8]"
+        );
+        assert!(lines[2].ends_with("call qword ptr [rax]"));
+        assert!(lines[3].ends_with("ret"));
+    }
+
+    #[test]
+    fn starts_where_instructions_line_up_with_the_fault() {
+        // From offset 0, `mov rax, imm64` swallows the fault address
+        let mut bytes = vec![0x48, 0xB8];
+        bytes.extend(CODE);
+        let window = CodeWindow {
+            start: START,
+            bytes,
+        };
+
+        let text = disassemble(&window, START + 6).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("  0x00007FF600001001"));
+        assert!(lines[1].starts_with("> 0x00007FF600001006"));
+        assert!(lines[1].ends_with("mov rax, [rcx+0x1a8]"));
+    }
+
+    #[test]
+    fn fault_outside_the_window_is_none() {
+        let window = CodeWindow {
+            start: START,
+            bytes: CODE.to_vec(),
+        };
+        assert_eq!(disassemble(&window, START - 1), None);
+        assert_eq!(disassemble(&window, START + CODE.len() as u64), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! Windows exception codes: which ones are crashes, and their names.
+
+/// Access to memory the thread may not touch.
+pub const ACCESS_VIOLATION: u32 = 0xC0000005;
+/// Page could not be read in, often from a failing or removed disk.
+pub const IN_PAGE_ERROR: u32 = 0xC0000006;
+/// Use of a closed or invalid handle.
+pub const INVALID_HANDLE: u32 = 0xC0000008;
+/// Instruction the CPU does not support.
+pub const ILLEGAL_INSTRUCTION: u32 = 0xC000001D;
+/// Integer division by zero.
+pub const INTEGER_DIVIDE_BY_ZERO: u32 = 0xC0000094;
+/// Integer overflow in a checked operation.
+pub const INTEGER_OVERFLOW: u32 = 0xC0000095;
+/// Instruction not allowed in user mode.
+pub const PRIVILEGED_INSTRUCTION: u32 = 0xC0000096;
+/// Thread ran out of stack.
+pub const STACK_OVERFLOW: u32 = 0xC00000FD;
+/// Heap manager found its structures corrupted.
+pub const HEAP_CORRUPTION: u32 = 0xC0000374;
+/// Stack cookie check or fail-fast.
+pub const STACK_BUFFER_OVERRUN: u32 = 0xC0000409;
+/// CRT function called with an invalid parameter
+/// (`STATUS_INVALID_CRUNTIME_PARAMETER`).
+pub const INVALID_CRT_PARAMETER: u32 = 0xC0000417;
+
+/// Pure virtual function called (CTD's own code, for the CRT's `_purecall`).
+pub const PURE_VIRTUAL_CALL: u32 = 0xE0C7D001;
+/// `std::terminate` called, usually for an uncaught C++ exception (CTD's own
+/// code).
+pub const CPP_TERMINATE: u32 = 0xE0C7D002;
+/// `abort()` called (CTD's own code).
+pub const CRT_ABORT: u32 = 0xE0C7D003;
+
+/// Returns true if the exception code represents a fatal crash.
+///
+/// Everything else (C++ exceptions, debugger breakpoints, guard pages) is
+/// raised and handled routinely by games and their mods.
+pub fn is_fatal_exception(code: u32) -> bool {
+    matches!(
+        code,
+        ACCESS_VIOLATION
+            | STACK_OVERFLOW
+            | ILLEGAL_INSTRUCTION
+            | INTEGER_DIVIDE_BY_ZERO
+            | INTEGER_OVERFLOW
+            | PRIVILEGED_INSTRUCTION
+            | IN_PAGE_ERROR
+            | INVALID_HANDLE
+            | HEAP_CORRUPTION
+            | STACK_BUFFER_OVERRUN
+            | INVALID_CRT_PARAMETER
+            | PURE_VIRTUAL_CALL
+            | CPP_TERMINATE
+            | CRT_ABORT
+    )
+}
+
+/// Returns true for the codes CTD's CRT termination hooks report, which
+/// no instruction raised.
+pub fn is_crt_termination(code: u32) -> bool {
+    matches!(
+        code,
+        INVALID_CRT_PARAMETER | PURE_VIRTUAL_CALL | CPP_TERMINATE | CRT_ABORT
+    )
+}
+
+/// Returns a human-readable name for a Windows exception code.
+pub fn exception_code_name(code: u32) -> &'static str {
+    match code {
+        ACCESS_VIOLATION => "ACCESS_VIOLATION",
+        STACK_OVERFLOW => "STACK_OVERFLOW",
+        ILLEGAL_INSTRUCTION => "ILLEGAL_INSTRUCTION",
+        INTEGER_DIVIDE_BY_ZERO => "INTEGER_DIVIDE_BY_ZERO",
+        INTEGER_OVERFLOW => "INTEGER_OVERFLOW",
+        PRIVILEGED_INSTRUCTION => "PRIVILEGED_INSTRUCTION",
+        IN_PAGE_ERROR => "IN_PAGE_ERROR",
+        INVALID_HANDLE => "INVALID_HANDLE",
+        HEAP_CORRUPTION => "HEAP_CORRUPTION",
+        STACK_BUFFER_OVERRUN => "STACK_BUFFER_OVERRUN",
+        INVALID_CRT_PARAMETER => "INVALID_CRT_PARAMETER",
+        PURE_VIRTUAL_CALL => "PURE_VIRTUAL_CALL",
+        CPP_TERMINATE => "CPP_TERMINATE",
+        CRT_ABORT => "CRT_ABORT",
+        _ => "UNKNOWN_EXCEPTION",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exception_code_name() {
+        assert_eq!(exception_code_name(0xC0000005), "ACCESS_VIOLATION");
+        assert_eq!(exception_code_name(0xC00000FD), "STACK_OVERFLOW");
+        assert_eq!(exception_code_name(CPP_TERMINATE), "CPP_TERMINATE");
+        assert_eq!(exception_code_name(0x12345678), "UNKNOWN_EXCEPTION");
+    }
+
+    #[test]
+    fn test_is_fatal_exception() {
+        assert!(is_fatal_exception(ACCESS_VIOLATION));
+        assert!(is_fatal_exception(STACK_BUFFER_OVERRUN));
+        assert!(is_fatal_exception(PURE_VIRTUAL_CALL));
+        assert!(is_crt_termination(CRT_ABORT));
+        assert!(!is_crt_termination(ACCESS_VIOLATION));
+        // MSVC C++ exception and breakpoint
+        assert!(!is_fatal_exception(0xE06D7363));
+        assert!(!is_fatal_exception(0x80000003));
+    }
+}
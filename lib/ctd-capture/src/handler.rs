@@ -0,0 +1,644 @@
+//! The crash handler the game plugins share.
+//!
+//! [`register`] installs the vectored exception handler with a callback that
+//! reports a fatal exception in three steps, so a crash in a corrupted
+//! process still leaves a report behind:
+//!
+//! 1. The crashed thread copies the exception into a slot preallocated for
+//!    it and wakes the reporting thread, which was started at registration
+//!    with a stack of its own, so a crash that exhausted the crashed thread's
+//!    stack (`STACK_OVERFLOW`) is reported too. Nothing on the crashed thread
+//!    allocates.
+//! 2. The reporting thread records the return addresses with
+//!    `ctd_core::crash_arena` before anything touches a possibly corrupt
+//!    heap, captures the rest of [`CrashData`], and passes it to the plugin's
+//!    [`ReportFn`] while the crashed thread waits.
+//! 3. If the capture faults, panics, or runs past [`CAPTURE_BUDGET`], the
+//!    crashed thread stops waiting and queues the `ctd_core::crash_guard`
+//!    fallback report, which is skipped when the arena already queued one.
+//!    A capture that finishes after that is dropped rather than reported
+//!    twice.
+//!
+//! A fault on the reporting thread that no guarded read handles stops that
+//! thread for good in the unhandled exception filter, rather than ending the
+//! process before the crashed thread falls back. Non-fatal exceptions are
+//! counted for `ctd_core::exception_tally` and remembered for
+//! `ctd_core::exception_chain`.
+
+#[cfg(windows)]
+use std::cell::Cell;
+#[cfg(windows)]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+#[cfg(windows)]
+use std::time::Instant;
+
+use ctd_core::config::{Config, OnCrash};
+#[cfg(windows)]
+use ctd_core::crash_arena::MAX_FRAMES;
+#[cfg(windows)]
+use ctd_core::crash_guard::{CAPTURE_BUDGET, HandlerGuard};
+use ctd_core::crash_report::{ChainedException, InPageError, ProbableObject};
+#[cfg(windows)]
+use ctd_core::exception_chain;
+use ctd_core::{crash_arena, crash_guard, exception_tally};
+use tracing::warn;
+
+use crate::veh::{self, Exception, VehError, VehOptions};
+#[cfg(windows)]
+use crate::veh::{Context, MAX_NESTED, MAX_PARAMETERS, NestedException};
+
+/// Longest the reporting thread waits for the plugin's submission when the
+/// process ends right after the handler.
+pub const SUBMIT_WAIT: Duration = Duration::from_secs(30);
+
+/// Longest the crashed thread waits for the report to be handed over. The
+/// reporting thread bounds its own wait for the submission.
+#[cfg(windows)]
+const REPORT_WAIT: Duration = Duration::from_secs(35);
+
+/// Stack reserved for the reporting thread, which walks the stack of a
+/// crashed thread that may have exhausted its own.
+#[cfg(windows)]
+const REPORTING_STACK_SIZE: usize = 4 * 1024 * 1024;
+
+/// A crash the handler captured, for the plugin to build a report from.
+#[derive(Debug, Clone, Default)]
+pub struct CrashData {
+    /// The Windows exception code (e.g., 0xC0000005 for ACCESS_VIOLATION).
+    pub exception_code: u32,
+
+    /// The address where the exception occurred.
+    pub exception_address: u64,
+
+    /// The captured stack trace as a formatted string.
+    pub stack_trace: String,
+
+    /// Module name where the crash occurred (if available).
+    pub faulting_module: Option<String>,
+
+    /// Instructions around the exception address (if readable).
+    pub disassembly: Option<String>,
+
+    /// General-purpose registers at the crash, in
+    /// `ctd_core::rtti::REGISTERS_64` order (`REGISTERS_32` in a 32-bit
+    /// process).
+    pub registers: Vec<u64>,
+
+    /// Objects with MSVC RTTI the crashed thread's registers and stack
+    /// pointed at.
+    pub probable_objects: Vec<ProbableObject>,
+
+    /// Exceptions nested in the fatal one's record, then the first-chance
+    /// exceptions raised before it.
+    pub exception_chain: Vec<ChainedException>,
+
+    /// The failed read behind an `IN_PAGE_ERROR` crash.
+    pub in_page_error: Option<InPageError>,
+
+    /// The exception record's `ExceptionInformation`.
+    pub exception_parameters: Vec<u64>,
+}
+
+/// Builds and sends the report for a captured crash.
+///
+/// Runs on the reporting thread while the crashed thread waits, so game
+/// state read here is read before the game carries on. Returns the thread
+/// submitting the report, which the handler waits for when the process ends
+/// next.
+pub type ReportFn = fn(CrashData) -> Option<JoinHandle<()>>;
+
+/// How the handler captures crashes and what it does afterwards.
+///
+/// Read from config at registration, so the handler never touches the
+/// filesystem while the process is crashing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HandlerOptions {
+    /// Register ahead of other vectored exception handlers.
+    pub first: bool,
+    /// Report only the faulting frame, leaving the stack walk to another
+    /// crash logger.
+    pub passive: bool,
+    /// DbgHelp search path for naming frames, or `None` to skip
+    /// symbolization.
+    pub symbol_search_path: Option<String>,
+    /// What to do after a crash is captured.
+    pub on_crash: OnCrash,
+    /// Count non-fatal exceptions for the next report.
+    pub handled_exceptions: bool,
+}
+
+impl HandlerOptions {
+    /// Reads `[handler]` and `[symbols]` from `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            first: config.handler.first,
+            passive: false,
+            symbol_search_path: config
+                .symbols
+                .runtime_symbolization()
+                .then(|| config.symbols.dbghelp_search_path()),
+            on_crash: config.handler.on_crash,
+            handled_exceptions: config.handler.handled_exceptions,
+        }
+    }
+
+    /// Switches to passive capture for `[coexistence] mode = "passive"`,
+    /// registering last so the other crash logger runs first.
+    pub fn passive(self) -> Self {
+        Self {
+            first: false,
+            passive: true,
+            ..self
+        }
+    }
+}
+
+/// What [`register`] was given.
+#[cfg_attr(not(windows), allow(dead_code))]
+struct Handler {
+    options: HandlerOptions,
+    report: ReportFn,
+}
+
+static HANDLER: OnceLock<Handler> = OnceLock::new();
+
+/// Registers the crash handler for a game.
+///
+/// Prepares the crash arena and fallback report for `game_id` and
+/// `game_version` and starts the reporting thread first, while allocating
+/// is still safe.
+///
+/// # Errors
+///
+/// Returns `VehError::AlreadyRegistered` if called before, and
+/// `VehError::RegistrationFailed` if Windows API fails.
+pub fn register(
+    game_id: &str,
+    game_version: &str,
+    options: HandlerOptions,
+    report: ReportFn,
+) -> Result<(), VehError> {
+    if HANDLER.get().is_some() {
+        return Err(VehError::AlreadyRegistered);
+    }
+
+    if let Err(e) = crash_guard::prepare_fallback(game_id, game_version)
+        .and_then(|()| crash_arena::init(game_id, game_version))
+    {
+        warn!("Failed to prepare crash-path buffers: {}", e);
+    }
+    exception_tally::set_enabled(options.handled_exceptions);
+
+    let veh_options = VehOptions {
+        first: options.first,
+    };
+    let _ = HANDLER.set(Handler { options, report });
+
+    #[cfg(windows)]
+    if let Err(e) = start_reporting_thread() {
+        warn!("Failed to start crash reporting thread: {}", e);
+    }
+
+    veh::register_veh(veh_options, on_exception)
+}
+
+/// Progress of the last crash handed to the reporting thread.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobState {
+    /// Nothing handed over yet.
+    Idle,
+    /// Handed over; the capture is running.
+    Pending,
+    /// Captured; the plugin is being handed the report.
+    Captured,
+    /// Report handed to the plugin.
+    Reported,
+    /// The capture or the plugin's report failed.
+    Failed,
+    /// The handler stopped waiting and fell back; the capture is dropped
+    /// when it finishes.
+    Abandoned,
+}
+
+/// A preallocated slot the crashed thread hands a job to the reporting
+/// thread through, without allocating.
+#[cfg_attr(not(windows), allow(dead_code))]
+struct Exchange<J> {
+    slot: Mutex<Slot<J>>,
+    changed: Condvar,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+struct Slot<J> {
+    state: JobState,
+    job: Option<J>,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl<J> Exchange<J> {
+    const fn new() -> Self {
+        Self {
+            slot: Mutex::new(Slot {
+                state: JobState::Idle,
+                job: None,
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Locks the slot, ignoring poisoning from a panic on another thread.
+    fn lock(&self) -> MutexGuard<'_, Slot<J>> {
+        self.slot.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Hands `job` to the reporting thread and waits up to `budget` for the
+    /// capture.
+    ///
+    /// Returns false if the reporting thread is still busy with an earlier
+    /// crash, or the capture failed or ran past the budget; the job is then
+    /// abandoned.
+    fn hand_off(&self, job: J, budget: Duration) -> bool {
+        let mut slot = self.lock();
+        if !matches!(
+            slot.state,
+            JobState::Idle | JobState::Reported | JobState::Failed
+        ) {
+            return false;
+        }
+        slot.state = JobState::Pending;
+        slot.job = Some(job);
+        self.changed.notify_all();
+
+        let (mut slot, _) = self
+            .changed
+            .wait_timeout_while(slot, budget, |slot| slot.state == JobState::Pending)
+            .unwrap_or_else(PoisonError::into_inner);
+        match slot.state {
+            JobState::Captured | JobState::Reported => true,
+            JobState::Pending => {
+                slot.state = JobState::Abandoned;
+                slot.job = None;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Waits up to `timeout` for the captured crash to reach the plugin.
+    ///
+    /// Returns true if it did.
+    fn wait_reported(&self, timeout: Duration) -> bool {
+        let (slot, _) = self
+            .changed
+            .wait_timeout_while(self.lock(), timeout, |slot| {
+                slot.state == JobState::Captured
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        slot.state == JobState::Reported
+    }
+
+    /// Waits for the next job, on the reporting thread.
+    fn take(&self) -> J {
+        let mut slot = self.lock();
+        loop {
+            if let Some(job) = slot.job.take() {
+                return job;
+            }
+            slot = self
+                .changed
+                .wait(slot)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Marks the job captured, on the reporting thread.
+    ///
+    /// Returns false if the handler gave up on it meanwhile; the capture must
+    /// then be dropped, and the slot is free for the next crash.
+    fn captured(&self) -> bool {
+        let mut slot = self.lock();
+        let wanted = slot.state != JobState::Abandoned;
+        slot.state = if wanted {
+            JobState::Captured
+        } else {
+            JobState::Idle
+        };
+        self.changed.notify_all();
+        wanted
+    }
+
+    /// Ends the job as `state` and wakes the handler.
+    fn finish(&self, state: JobState) {
+        self.lock().state = state;
+        self.changed.notify_all();
+    }
+}
+
+/// A crash handed from the handler to the reporting thread.
+#[cfg(windows)]
+struct Job {
+    context: Context,
+    code: u32,
+    address: u64,
+    /// The record's parameters, the first `information_count` used.
+    information: [u64; MAX_PARAMETERS],
+    information_count: usize,
+    /// Records linked from the exception's, the first `nested_count` used.
+    nested: [NestedException; MAX_NESTED],
+    nested_count: usize,
+    /// Wait for the submission, because the process ends after the handler.
+    wait: bool,
+}
+
+#[cfg(windows)]
+static EXCHANGE: Exchange<Job> = Exchange::new();
+
+/// Set while the reporting thread can take a crash.
+#[cfg(windows)]
+static REPORTER_READY: AtomicBool = AtomicBool::new(false);
+
+#[cfg(windows)]
+thread_local! {
+    /// Set on the reporting thread.
+    static IS_REPORTER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Called by the VEH for every exception in the process.
+#[cfg(windows)]
+fn on_exception(exception: &Exception<'_>) {
+    use windows::Win32::System::Threading::{GetCurrentProcess, TerminateProcess};
+
+    let code = exception.code;
+    if !exception.is_fatal() {
+        exception_tally::record(code);
+        exception_chain::record(code, exception.address);
+        return;
+    }
+
+    // The capture's own fault: a guarded read handles it, and otherwise the
+    // unhandled exception filter stops the thread (see park_if_reporter)
+    if IS_REPORTER.get() {
+        if exception.terminating {
+            park_if_reporter();
+        }
+        return;
+    }
+
+    // One crash at a time
+    let Some(_guard) = HandlerGuard::enter() else {
+        return;
+    };
+    let Some(handler) = HANDLER.get() else {
+        return;
+    };
+    let on_crash = handler.options.on_crash;
+
+    // Only the hand-off runs here, on what may be an exhausted stack; wait
+    // for the submission if the process ends after the handler
+    let reported = REPORTER_READY.load(Ordering::Acquire)
+        && exception.context.is_some_and(|context| {
+            let job = Job {
+                context: *context,
+                code,
+                address: exception.address,
+                information: std::array::from_fn(|i| {
+                    exception.information.get(i).copied().unwrap_or_default()
+                }),
+                information_count: exception.information.len().min(MAX_PARAMETERS),
+                nested: std::array::from_fn(|i| {
+                    exception.nested.get(i).copied().unwrap_or_default()
+                }),
+                nested_count: exception.nested.len().min(MAX_NESTED),
+                wait: on_crash.ends_process() || exception.terminating,
+            };
+            EXCHANGE.hand_off(job, CAPTURE_BUDGET) && EXCHANGE.wait_reported(REPORT_WAIT)
+        });
+    if !reported {
+        // Queue what is known without allocating, unless the arena did
+        crash_guard::write_fallback(code, exception.address);
+    }
+
+    if on_crash.ends_process() {
+        if on_crash == OnCrash::Dialog {
+            show_crash_dialog();
+        }
+        // SAFETY: Terminating our own process with the exception code.
+        let _ = unsafe { TerminateProcess(GetCurrentProcess(), code) };
+    }
+}
+
+/// Non-Windows stub: the handler never runs.
+#[cfg(not(windows))]
+fn on_exception(_exception: &Exception<'_>) {}
+
+/// Stops the reporting thread for good if it is the calling thread.
+///
+/// Called for a fault on the reporting thread that nothing handled, so the
+/// crashed thread sees the capture fail and falls back rather than the
+/// process ending here. Returns at once on any other thread.
+#[cfg(windows)]
+pub(crate) fn park_if_reporter() {
+    if !IS_REPORTER.get() {
+        return;
+    }
+    REPORTER_READY.store(false, Ordering::Release);
+    EXCHANGE.finish(JobState::Failed);
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Starts the reporting thread that [`on_exception`] hands crashes to.
+#[cfg(windows)]
+fn start_reporting_thread() -> std::io::Result<()> {
+    std::thread::Builder::new()
+        .name("ctd-reporter".to_string())
+        .stack_size(REPORTING_STACK_SIZE)
+        .spawn(reporting_thread)?;
+    REPORTER_READY.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Serves crashes from the handler for the life of the process.
+#[cfg(windows)]
+fn reporting_thread() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    IS_REPORTER.set(true);
+
+    loop {
+        let job = EXCHANGE.take();
+        let Some(handler) = HANDLER.get() else {
+            EXCHANGE.finish(JobState::Failed);
+            continue;
+        };
+
+        // A panic fails this crash, not the thread
+        let reported = catch_unwind(AssertUnwindSafe(|| {
+            let crash = capture(&job, &handler.options);
+            if !EXCHANGE.captured() {
+                // The handler already fell back
+                return None;
+            }
+
+            let submission = (handler.report)(crash);
+            if job.wait
+                && let Some(submission) = submission
+            {
+                let deadline = Instant::now() + SUBMIT_WAIT;
+                while !submission.is_finished() && Instant::now() < deadline {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+            }
+            Some(JobState::Reported)
+        }));
+        match reported {
+            Ok(Some(state)) => EXCHANGE.finish(state),
+            Ok(None) => {}
+            Err(_) => EXCHANGE.finish(JobState::Failed),
+        }
+    }
+}
+
+/// Captures a crash on the reporting thread.
+#[cfg(windows)]
+fn capture(job: &Job, options: &HandlerOptions) -> CrashData {
+    use crate::stack_walk::{capture_stack_trace, fault_frame_trace, walk_frames};
+
+    let information = &job.information[..job.information_count];
+
+    // Record the crash before anything touches a possibly corrupt heap; in
+    // passive mode only the faulting frame, as in the full report
+    let mut frames = [0u64; MAX_FRAMES];
+    let count = if options.passive {
+        frames[0] = job.address;
+        1
+    } else {
+        walk_frames(&job.context, &mut frames)
+    };
+    crash_arena::record_crash(job.code, job.address, &frames[..count]);
+
+    let stack_trace = if options.passive {
+        fault_frame_trace(job.address)
+    } else {
+        capture_stack_trace(
+            &job.context,
+            job.address,
+            options.symbol_search_path.as_deref(),
+        )
+    };
+    let registers = ctd_core::rtti::context_registers(&job.context);
+
+    CrashData {
+        exception_code: job.code,
+        exception_address: job.address,
+        stack_trace,
+        faulting_module: crate::module::module_at_address(job.address),
+        disassembly: crate::disasm::disassemble_exception(job.code, job.address),
+        probable_objects: ctd_core::rtti::scan(&registers),
+        registers,
+        exception_chain: exception_chain::capture(
+            job.nested[..job.nested_count]
+                .iter()
+                .map(|nested| (nested.code, nested.address)),
+        ),
+        in_page_error: ctd_core::in_page::diagnose(job.code, information),
+        exception_parameters: information.to_vec(),
+    }
+}
+
+/// Tells the user the game crashed, for `[handler] on_crash = "dialog"`.
+#[cfg(windows)]
+fn show_crash_dialog() {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{MB_ICONERROR, MB_OK, MB_TOPMOST, MessageBoxW};
+    use windows::core::w;
+
+    // SAFETY: Both strings are static and null-terminated.
+    unsafe {
+        MessageBoxW(
+            HWND::default(),
+            w!(
+                "The game has crashed and CTD has captured it.\n\nWhether the report was sent is in the CTD log (Documents\\My Games\\CTD\\logs)."
+            ),
+            w!("CTD Crash Reporter"),
+            MB_OK | MB_ICONERROR | MB_TOPMOST,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serves one job the way the reporting thread does, after `delay`.
+    fn serve(exchange: &'static Exchange<u32>, delay: Duration) -> std::thread::JoinHandle<bool> {
+        std::thread::spawn(move || {
+            let _job = exchange.take();
+            std::thread::sleep(delay);
+            let wanted = exchange.captured();
+            if wanted {
+                exchange.finish(JobState::Reported);
+            }
+            wanted
+        })
+    }
+
+    #[test]
+    fn captured_crashes_are_reported() {
+        static EXCHANGE: Exchange<u32> = Exchange::new();
+
+        let reporter = serve(&EXCHANGE, Duration::ZERO);
+        assert!(EXCHANGE.hand_off(1, Duration::from_secs(10)));
+        assert!(EXCHANGE.wait_reported(Duration::from_secs(10)));
+        assert!(reporter.join().unwrap());
+    }
+
+    #[test]
+    fn late_captures_are_dropped() {
+        static EXCHANGE: Exchange<u32> = Exchange::new();
+
+        let reporter = serve(&EXCHANGE, Duration::from_millis(200));
+        assert!(!EXCHANGE.hand_off(1, Duration::from_millis(10)));
+        // Still busy with the abandoned crash
+        assert!(!EXCHANGE.hand_off(2, Duration::ZERO));
+        assert!(!reporter.join().unwrap());
+
+        // The slot is free again once the late capture is dropped
+        let reporter = serve(&EXCHANGE, Duration::ZERO);
+        assert!(EXCHANGE.hand_off(3, Duration::from_secs(10)));
+        assert!(reporter.join().unwrap());
+    }
+
+    #[test]
+    fn failed_captures_fall_back() {
+        static EXCHANGE: Exchange<u32> = Exchange::new();
+
+        let reporter = std::thread::spawn(|| {
+            EXCHANGE.take();
+            EXCHANGE.finish(JobState::Failed);
+        });
+        assert!(!EXCHANGE.hand_off(1, Duration::from_secs(10)));
+        reporter.join().unwrap();
+        assert!(!EXCHANGE.wait_reported(Duration::ZERO));
+    }
+
+    #[test]
+    fn passive_registers_last() {
+        let mut config = Config::default();
+        config.handler.first = true;
+        config.symbols.dbghelp = true;
+
+        let options = HandlerOptions::from_config(&config);
+        assert!(options.first);
+        assert!(options.symbol_search_path.is_some());
+
+        let passive = options.passive();
+        assert!(passive.passive);
+        assert!(!passive.first);
+    }
+}
@@ -0,0 +1,25 @@
+//! CTD Capture Library
+//!
+//! Windows crash capture shared by the game plugins, so a new game crate
+//! only has to decide what to report:
+//! - The crash handler itself, which captures on a reporting thread within
+//!   a time budget and falls back to a minimal report
+//! - Vectored exception handler registration, with the exception records
+//!   nested in each
+//! - Reports for CRT terminations (pure virtual calls, invalid parameters,
+//!   `std::terminate`, `abort()`) and unhandled exceptions
+//! - Fatal exception filtering and exception code names
+//! - Stack walks from a crashed thread's context, with and without allocating
+//! - Disassembly of the instructions around the exception address
+//! - Lookup of the module an address belongs to
+//!
+//! Off Windows the handler never runs and module lookups return `None`, so
+//! the plugins build and test on any host.
+
+pub mod disasm;
+pub mod exception;
+pub mod handler;
+pub mod module;
+pub mod stack_walk;
+mod termination;
+pub mod veh;
@@ -0,0 +1,73 @@
+//! Which loaded module an address belongs to.
+//!
+//! Lookups take no reference on the module, so they are safe to call for
+//! addresses in modules that may be unloading when the process crashes.
+
+/// Returns the file name of the module containing `address`.
+#[cfg(windows)]
+pub fn module_at_address(address: u64) -> Option<String> {
+    use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+
+    let module = module_handle(address)?;
+
+    let mut filename = [0u16; 260];
+    // SAFETY: GetModuleFileNameW is safe with valid buffer
+    let len = unsafe { GetModuleFileNameW(module, &mut filename) };
+    if len == 0 {
+        return None;
+    }
+
+    let path = String::from_utf16_lossy(&filename[..len as usize]);
+    path.rsplit('\\').next().map(|s| s.to_string())
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+pub fn module_at_address(_address: u64) -> Option<String> {
+    None
+}
+
+/// Returns the base address of the module containing `address`.
+#[cfg(windows)]
+pub fn module_base(address: u64) -> Option<u64> {
+    module_handle(address).map(|module| module.0 as u64)
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+pub fn module_base(_address: u64) -> Option<u64> {
+    None
+}
+
+/// Returns the module containing `address`, without taking a reference.
+#[cfg(windows)]
+fn module_handle(address: u64) -> Option<windows::Win32::Foundation::HMODULE> {
+    use windows::Win32::Foundation::HMODULE;
+    use windows::Win32::System::LibraryLoader::{
+        GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS, GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+        GetModuleHandleExW,
+    };
+
+    let mut module = HMODULE::default();
+    // SAFETY: With FROM_ADDRESS the "name" is only used as an address
+    unsafe {
+        GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            windows::core::PCWSTR::from_raw(address as *const u16),
+            &mut module,
+        )
+    }
+    .ok()?;
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_is_in_no_module() {
+        assert_eq!(module_at_address(0), None);
+        assert_eq!(module_base(0), None);
+    }
+}
@@ -0,0 +1,396 @@
+//! Stack traces for a crashed thread, walked from its exception context.
+//!
+//! [`capture_stack_trace`] walks the stack with DbgHelp's `StackWalk64`
+//! and writes one `[n] module+0xOFFSET (0xADDRESS)` line per frame, the
+//! format crash hashing and blame expect. With a symbol search path, frames
+//! in modules that have a PDB are also named with `Function+0xDISP`.
+//!
+//! `capture_stack_trace` allocates, so the plugins call it on a reporting
+//! thread. [`walk_frames`] records bare return addresses from the unwind
+//! tables (the EBP chain in a 32-bit process) without allocating, for
+//! `ctd_core::crash_arena` to write before anything touches a possibly
+//! corrupt heap.
+
+use std::fmt::Write;
+
+use crate::module::{module_at_address, module_base};
+
+/// Most frames walked per trace.
+pub const MAX_WALK_FRAMES: usize = 64;
+
+/// Walks the crashed thread's stack from `context`.
+///
+/// `symbol_search_path` is a DbgHelp search path (see
+/// `ctd_core::config::SymbolsConfig::dbghelp_search_path`), or `None` to skip
+/// symbolization. If the walk finds no frames, the trace is the exception
+/// address alone.
+#[cfg(windows)]
+pub fn capture_stack_trace(
+    context: &windows::Win32::System::Diagnostics::Debug::CONTEXT,
+    exception_address: u64,
+    symbol_search_path: Option<&str>,
+) -> String {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::System::Diagnostics::Debug::{
+        ADDRESS_MODE, CONTEXT, STACKFRAME64, StackWalk64, SymCleanup,
+    };
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut result = String::with_capacity(4096);
+
+    // SAFETY: Returns a pseudo-handle that needs no closing
+    let process: HANDLE = unsafe { GetCurrentProcess() };
+
+    // Initialize stack frame from context
+    let mut frame = STACKFRAME64::default();
+
+    #[cfg(target_arch = "x86_64")]
+    let (pc, frame_pointer, stack_pointer, machine_type) = (
+        context.Rip,
+        context.Rbp,
+        context.Rsp,
+        0x8664u32, // IMAGE_FILE_MACHINE_AMD64
+    );
+    #[cfg(target_arch = "x86")]
+    let (pc, frame_pointer, stack_pointer, machine_type) = (
+        u64::from(context.Eip),
+        u64::from(context.Ebp),
+        u64::from(context.Esp),
+        0x014Cu32, // IMAGE_FILE_MACHINE_I386
+    );
+
+    frame.AddrPC.Offset = pc;
+    frame.AddrPC.Mode = ADDRESS_MODE(3); // AddrModeFlat
+    frame.AddrFrame.Offset = frame_pointer;
+    frame.AddrFrame.Mode = ADDRESS_MODE(3);
+    frame.AddrStack.Offset = stack_pointer;
+    frame.AddrStack.Mode = ADDRESS_MODE(3);
+
+    let symbolize = symbol_search_path.is_some_and(|path| init_symbols(process, path));
+
+    // StackWalk64 updates the context as it goes
+    let mut context_copy: CONTEXT = *context;
+
+    let mut frame_count = 0;
+    while frame_count < MAX_WALK_FRAMES {
+        // SAFETY: frame and context_copy are our own, and process is valid
+        let success = unsafe {
+            StackWalk64(
+                machine_type,
+                process,
+                HANDLE::default(), // Use 0 for current thread in exception context
+                &mut frame,
+                std::ptr::addr_of_mut!(context_copy).cast(),
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+
+        if !success.as_bool() || frame.AddrPC.Offset == 0 {
+            break;
+        }
+
+        let address = frame.AddrPC.Offset;
+        write_frame(&mut result, frame_count, address);
+        if symbolize && let Some(symbol) = resolve_symbol(process, address) {
+            let _ = write!(result, " {}", symbol);
+        }
+        result.push('\n');
+        frame_count += 1;
+    }
+
+    if symbolize {
+        // SAFETY: Matches the successful SymInitializeW in init_symbols
+        let _ = unsafe { SymCleanup(process) };
+    }
+
+    if result.is_empty() {
+        write_frame(&mut result, 0, exception_address);
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Records the crashed thread's return addresses, innermost first, using only
+/// the unwind tables so nothing is allocated.
+///
+/// Returns the number of frames written to `frames`.
+#[cfg(all(windows, target_arch = "x86_64"))]
+pub fn walk_frames(
+    context: &windows::Win32::System::Diagnostics::Debug::CONTEXT,
+    frames: &mut [u64],
+) -> usize {
+    use windows::Win32::System::Diagnostics::Debug::{
+        RtlLookupFunctionEntry, RtlVirtualUnwind, UNW_FLAG_NHANDLER,
+    };
+
+    let mut context = *context;
+    let mut count = 0;
+
+    while count < frames.len() && context.Rip != 0 {
+        frames[count] = context.Rip;
+        count += 1;
+
+        let mut image_base = 0;
+        // SAFETY: Looks up the unwind data for an address; no memory is written
+        let function = unsafe { RtlLookupFunctionEntry(context.Rip, &mut image_base, None) };
+        if function.is_null() {
+            // Leaf function: the return address is on top of the stack, if
+            // Rsp still points at one after an overflow or a corrupt frame
+            if !is_readable(context.Rsp, 8) {
+                break;
+            }
+            // SAFETY: Checked readable above
+            context.Rip = unsafe { *(context.Rsp as *const u64) };
+            context.Rsp += 8;
+            continue;
+        }
+
+        let mut handler_data = std::ptr::null_mut();
+        let mut establisher_frame = 0;
+        // SAFETY: function came from RtlLookupFunctionEntry for this Rip and
+        // context is our own copy
+        unsafe {
+            RtlVirtualUnwind(
+                UNW_FLAG_NHANDLER,
+                image_base,
+                context.Rip,
+                function,
+                &mut context,
+                &mut handler_data,
+                &mut establisher_frame,
+                None,
+            );
+        }
+    }
+
+    count
+}
+
+/// Records the crashed thread's return addresses, innermost first, along the
+/// EBP chain, since 32-bit code has no unwind tables.
+///
+/// Returns the number of frames written to `frames`.
+#[cfg(all(windows, target_arch = "x86"))]
+pub fn walk_frames(
+    context: &windows::Win32::System::Diagnostics::Debug::CONTEXT,
+    frames: &mut [u64],
+) -> usize {
+    if frames.is_empty() {
+        return 0;
+    }
+    frames[0] = u64::from(context.Eip);
+    let mut count = 1;
+
+    let mut ebp = context.Ebp;
+    while count < frames.len() && ebp != 0 && is_readable(u64::from(ebp), 8) {
+        let frame = ebp as usize as *const u32;
+        // SAFETY: Both words were checked readable above
+        let (next, ret) = unsafe { (*frame, *frame.add(1)) };
+        if ret == 0 {
+            break;
+        }
+        frames[count] = u64::from(ret);
+        count += 1;
+
+        // Frames move up the stack; anything else is a broken chain
+        if next <= ebp {
+            break;
+        }
+        ebp = next;
+    }
+
+    count
+}
+
+/// Returns true if `len` bytes at `address` are committed and readable.
+#[cfg(windows)]
+fn is_readable(address: u64, len: u64) -> bool {
+    use windows::Win32::System::Memory::{
+        MEM_COMMIT, MEMORY_BASIC_INFORMATION, PAGE_GUARD, PAGE_NOACCESS, VirtualQuery,
+    };
+
+    let mut info = MEMORY_BASIC_INFORMATION::default();
+    // SAFETY: VirtualQuery only describes the region; info is sized correctly
+    let written = unsafe {
+        VirtualQuery(
+            Some(address as usize as *const std::ffi::c_void),
+            &mut info,
+            std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+    if written == 0 {
+        return false;
+    }
+
+    let end = info.BaseAddress as u64 + info.RegionSize as u64;
+    info.State == MEM_COMMIT
+        && info.Protect.0 & (PAGE_NOACCESS.0 | PAGE_GUARD.0) == 0
+        && address.saturating_add(len) <= end
+}
+
+/// Returns the trace of a crash described by its faulting frame alone, for
+/// when another crash logger walks the stack.
+pub fn fault_frame_trace(address: u64) -> String {
+    let mut trace = String::new();
+    write_frame(&mut trace, 0, address);
+    trace.push('\n');
+    trace
+}
+
+/// Appends `[index] module+0xOFFSET (0xADDRESS)` for `address`, without a
+/// newline.
+fn write_frame(out: &mut String, index: usize, address: u64) {
+    let module = module_at_address(address);
+    let base = module_base(address).unwrap_or(0);
+    let _ = write!(
+        out,
+        "[{:2}] {}+0x{:X} (0x{:016X})",
+        index,
+        module.as_deref().unwrap_or("unknown"),
+        address.saturating_sub(base),
+        address
+    );
+}
+
+/// Initializes DbgHelp with `configured` plus the directory of every loaded
+/// module, so PDBs shipped next to plugin DLLs are found.
+///
+/// Returns true if DbgHelp is ready for `resolve_symbol`.
+#[cfg(windows)]
+fn init_symbols(process: windows::Win32::Foundation::HANDLE, configured: &str) -> bool {
+    use windows::Win32::Foundation::HMODULE;
+    use windows::Win32::System::Diagnostics::Debug::{
+        SYMOPT_DEFERRED_LOADS, SYMOPT_LOAD_LINES, SYMOPT_UNDNAME, SymInitializeW, SymSetOptions,
+    };
+    use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+    use windows::Win32::System::ProcessStatus::EnumProcessModules;
+
+    let mut dirs: Vec<String> = configured
+        .split(';')
+        .filter(|d| !d.is_empty())
+        .map(String::from)
+        .collect();
+
+    let mut modules = [HMODULE::default(); 1024];
+    let mut needed = 0u32;
+
+    // SAFETY: The buffer size matches the array we pass in
+    let enumerated = unsafe {
+        EnumProcessModules(
+            process,
+            modules.as_mut_ptr(),
+            std::mem::size_of_val(&modules) as u32,
+            &mut needed,
+        )
+    };
+
+    if enumerated.is_ok() {
+        let count = (needed as usize / std::mem::size_of::<HMODULE>()).min(modules.len());
+        for module in &modules[..count] {
+            let mut filename = [0u16; 260];
+            // SAFETY: GetModuleFileNameW is safe with valid buffer
+            let len = unsafe { GetModuleFileNameW(*module, &mut filename) };
+            if len == 0 {
+                continue;
+            }
+
+            let path = String::from_utf16_lossy(&filename[..len as usize]);
+            if let Some((dir, _)) = path.rsplit_once('\\')
+                && !dirs.iter().any(|d| d.eq_ignore_ascii_case(dir))
+            {
+                dirs.push(dir.to_string());
+            }
+        }
+    }
+
+    let search_path: Vec<u16> = dirs
+        .join(";")
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+
+    // SAFETY: search_path is a valid null-terminated wide string
+    unsafe {
+        SymSetOptions(SYMOPT_LOAD_LINES | SYMOPT_UNDNAME | SYMOPT_DEFERRED_LOADS);
+        SymInitializeW(
+            process,
+            windows::core::PCWSTR::from_raw(search_path.as_ptr()),
+            true,
+        )
+        .is_ok()
+    }
+}
+
+/// Resolves an address to `Function+0xDISP` using DbgHelp.
+///
+/// Only PDB-backed symbols are used; export-only names point at the
+/// nearest export and are misleading in crash reports.
+#[cfg(windows)]
+fn resolve_symbol(process: windows::Win32::Foundation::HANDLE, address: u64) -> Option<String> {
+    use windows::Win32::System::Diagnostics::Debug::{
+        IMAGEHLP_MODULE64, MAX_SYM_NAME, SYMBOL_INFO, SymFromAddr, SymGetModuleInfo64, SymPdb,
+    };
+
+    let mut module_info = IMAGEHLP_MODULE64 {
+        SizeOfStruct: std::mem::size_of::<IMAGEHLP_MODULE64>() as u32,
+        ..Default::default()
+    };
+
+    // SAFETY: module_info is properly sized
+    let has_module = unsafe { SymGetModuleInfo64(process, address, &mut module_info) }.is_ok();
+    if !has_module || module_info.SymType != SymPdb {
+        return None;
+    }
+
+    // SYMBOL_INFO is followed by a variable-length name buffer
+    #[repr(C)]
+    struct SymbolBuffer {
+        info: SYMBOL_INFO,
+        name: [u8; MAX_SYM_NAME as usize],
+    }
+
+    let mut buffer = SymbolBuffer {
+        info: SYMBOL_INFO {
+            SizeOfStruct: std::mem::size_of::<SYMBOL_INFO>() as u32,
+            MaxNameLen: MAX_SYM_NAME,
+            ..Default::default()
+        },
+        name: [0; MAX_SYM_NAME as usize],
+    };
+
+    let mut displacement = 0u64;
+
+    // SAFETY: buffer has room for MaxNameLen characters after SYMBOL_INFO
+    unsafe { SymFromAddr(process, address, Some(&mut displacement), &mut buffer.info) }.ok()?;
+
+    let name_len = (buffer.info.NameLen as usize).min(MAX_SYM_NAME as usize);
+    // SAFETY: Name starts inside SYMBOL_INFO and continues into buffer.name
+    let name =
+        unsafe { std::slice::from_raw_parts(buffer.info.Name.as_ptr() as *const u8, name_len) };
+
+    Some(format!(
+        "{}+0x{:X}",
+        String::from_utf8_lossy(name),
+        displacement
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_outside_any_module_are_unknown() {
+        let mut trace = String::new();
+        write_frame(&mut trace, 3, 0x10);
+        assert_eq!(trace, "[ 3] unknown+0x10 (0x0000000000000010)");
+        assert_eq!(
+            fault_frame_trace(0x10),
+            "[ 0] unknown+0x10 (0x0000000000000010)\n"
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Reports for terminations the vectored handler never sees.
+//!
+//! Many crashes to desktop are the C runtime ending the process on purpose:
+//! a pure virtual call, a CRT function given an invalid parameter,
+//! `std::terminate` after an uncaught C++ exception, or `abort()`. The CRT
+//! ends the process with `__fastfail`, which skips exception handlers, so
+//! the VEH never runs. [`install`] hooks each of them, plus an unhandled
+//! exception filter for exceptions nothing caught, and passes them to the
+//! [`register_veh`](crate::veh::register_veh) callback as terminating
+//! exceptions, with the thread's context captured in the hook.
+//!
+//! The CRT hooks are set in the shared Universal CRT and VC runtime, so they
+//! cover the game and every mod linked against them; a module with a
+//! statically linked CRT keeps its own handlers. Only the first termination
+//! is reported, since an uncaught exception goes on to call `std::terminate`
+//! and that calls `abort()`. Each hook then chains to the handler it
+//! replaced, or ends the process with the termination's code. An invalid
+//! parameter with a handler already installed isn't a termination: that
+//! handler may return so the CRT function fails with `EINVAL` and the game
+//! carries on, so it is passed on without a report.
+
+#[cfg(windows)]
+use std::sync::OnceLock;
+#[cfg(windows)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(windows)]
+use windows::Win32::System::Diagnostics::Debug::{
+    EXCEPTION_POINTERS, LPTOP_LEVEL_EXCEPTION_FILTER,
+};
+
+use crate::exception::{
+    CPP_TERMINATE, CRT_ABORT, INVALID_CRT_PARAMETER, PURE_VIRTUAL_CALL, is_fatal_exception,
+};
+#[cfg(windows)]
+use crate::veh::{
+    Exception, MAX_NESTED, MAX_PARAMETERS, NestedException, dispatch, exception_parameters,
+    instruction_pointer, nested_exceptions,
+};
+
+/// `_purecall_handler` and `terminate_handler`.
+#[cfg(windows)]
+type CrtHandler = unsafe extern "C" fn();
+
+/// `_invalid_parameter_handler`: expression, function, file, line, and a
+/// reserved value. The strings are null in release builds of the CRT.
+#[cfg(windows)]
+type InvalidParameterHandler = unsafe extern "C" fn(*const u16, *const u16, *const u16, u32, usize);
+
+/// A `signal` handler.
+#[cfg(windows)]
+type SignalHandler = unsafe extern "C" fn(i32);
+
+/// The CRT's abort signal.
+#[cfg(windows)]
+const SIGABRT: i32 = 22;
+
+/// `signal`'s default disposition.
+const SIG_DFL: usize = 0;
+
+/// `signal`'s ignore disposition.
+const SIG_IGN: usize = 1;
+
+/// What `signal` returns when it fails.
+const SIG_ERR: usize = usize::MAX;
+
+/// A CRT termination [`install`] hooks.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hook {
+    /// A pure virtual call (`_purecall`).
+    PureCall,
+    /// A CRT function given an invalid parameter.
+    InvalidParameter,
+    /// `std::terminate`.
+    Terminate,
+    /// `abort()`, through `SIGABRT`.
+    Abort,
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+impl Hook {
+    /// The exception code the termination is reported with.
+    fn code(self) -> u32 {
+        match self {
+            Hook::PureCall => PURE_VIRTUAL_CALL,
+            Hook::InvalidParameter => INVALID_CRT_PARAMETER,
+            Hook::Terminate => CPP_TERMINATE,
+            Hook::Abort => CRT_ABORT,
+        }
+    }
+}
+
+/// Returns true if the unhandled exception filter reports `code`: only for
+/// the first termination, and only for codes the VEH let through.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn filter_reports(first: bool, code: u32) -> bool {
+    first && !is_fatal_exception(code)
+}
+
+/// Returns the abort handler to chain to, or `None` if `signal` returned
+/// `SIG_DFL`, `SIG_IGN`, or `SIG_ERR` rather than a handler.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn previous_abort_handler(previous: usize) -> Option<usize> {
+    (!matches!(previous, SIG_DFL | SIG_IGN | SIG_ERR)).then_some(previous)
+}
+
+/// What the invalid parameter hook does with a handler it replaced.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, PartialEq, Eq)]
+enum InvalidParameterAction<H> {
+    /// Pass the call to the game's or a mod's handler and return.
+    Chain(H),
+    /// Report it and end the process, as the CRT would without a handler.
+    Terminate,
+}
+
+/// Decides what the invalid parameter hook does, given the handler it
+/// replaced.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn invalid_parameter_action<H>(previous: Option<H>) -> InvalidParameterAction<H> {
+    match previous {
+        Some(handler) => InvalidParameterAction::Chain(handler),
+        None => InvalidParameterAction::Terminate,
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "C" {
+    fn _set_purecall_handler(handler: Option<CrtHandler>) -> Option<CrtHandler>;
+    fn _set_invalid_parameter_handler(
+        handler: Option<InvalidParameterHandler>,
+    ) -> Option<InvalidParameterHandler>;
+    fn set_terminate(handler: Option<CrtHandler>) -> Option<CrtHandler>;
+    fn signal(signal: i32, handler: usize) -> usize;
+}
+
+/// The handlers [`install`] replaced.
+#[cfg(windows)]
+struct Previous {
+    purecall: Option<CrtHandler>,
+    invalid_parameter: Option<InvalidParameterHandler>,
+    terminate: Option<CrtHandler>,
+    /// A handler address, or [`SIG_DFL`], [`SIG_IGN`], or [`SIG_ERR`].
+    abort: usize,
+    filter: LPTOP_LEVEL_EXCEPTION_FILTER,
+}
+
+#[cfg(windows)]
+static PREVIOUS: OnceLock<Previous> = OnceLock::new();
+
+/// Set by the first termination that reaches a hook.
+#[cfg(windows)]
+static TERMINATING: AtomicBool = AtomicBool::new(false);
+
+/// Installs the CRT termination hooks and the unhandled exception filter.
+#[cfg(windows)]
+pub(crate) fn install() {
+    use windows::Win32::System::Diagnostics::Debug::SetUnhandledExceptionFilter;
+
+    // SAFETY: The hooks are plain functions that live as long as the process
+    let previous = unsafe {
+        Previous {
+            purecall: _set_purecall_handler(Some(on_purecall)),
+            invalid_parameter: _set_invalid_parameter_handler(Some(on_invalid_parameter)),
+            terminate: set_terminate(Some(on_terminate)),
+            abort: signal(SIGABRT, on_abort as SignalHandler as usize),
+            filter: SetUnhandledExceptionFilter(Some(on_unhandled_exception)),
+        }
+    };
+    let _ = PREVIOUS.set(previous);
+}
+
+/// Returns true for the first termination in the process only.
+#[cfg(windows)]
+fn first_termination() -> bool {
+    !TERMINATING.swap(true, Ordering::AcqRel)
+}
+
+/// Passes a CRT termination to the callback with the current thread's
+/// context, if it is the first.
+#[cfg(windows)]
+fn report(hook: Hook) {
+    use windows::Win32::System::Diagnostics::Debug::{CONTEXT, RtlCaptureContext};
+
+    if !first_termination() {
+        return;
+    }
+    let mut context = CONTEXT::default();
+    // SAFETY: The context is our own, aligned as RtlCaptureContext requires
+    unsafe { RtlCaptureContext(&mut context) };
+    dispatch(&Exception {
+        code: hook.code(),
+        address: instruction_pointer(&context),
+        context: Some(&context),
+        information: &[],
+        nested: &[],
+        terminating: true,
+    });
+}
+
+/// Ends the process with a termination's code.
+#[cfg(windows)]
+fn end_process(code: u32) -> ! {
+    use windows::Win32::System::Threading::{GetCurrentProcess, TerminateProcess};
+
+    // SAFETY: Terminating our own process
+    let _ = unsafe { TerminateProcess(GetCurrentProcess(), code) };
+    std::process::abort()
+}
+
+#[cfg(windows)]
+unsafe extern "C" fn on_purecall() {
+    report(Hook::PureCall);
+    if let Some(previous) = PREVIOUS.get().and_then(|p| p.purecall) {
+        // SAFETY: The CRT accepted it as a purecall handler
+        unsafe { previous() };
+    }
+    end_process(Hook::PureCall.code())
+}
+
+#[cfg(windows)]
+unsafe extern "C" fn on_invalid_parameter(
+    expression: *const u16,
+    function: *const u16,
+    file: *const u16,
+    line: u32,
+    reserved: usize,
+) {
+    let previous = PREVIOUS.get().and_then(|p| p.invalid_parameter);
+    match invalid_parameter_action(previous) {
+        InvalidParameterAction::Chain(previous) => {
+            // SAFETY: The CRT accepted it as an invalid parameter handler.
+            // If it returns, the CRT function fails with EINVAL as intended.
+            unsafe { previous(expression, function, file, line, reserved) };
+        }
+        InvalidParameterAction::Terminate => {
+            report(Hook::InvalidParameter);
+            // Returning would let the CRT function fail with EINVAL and carry on
+            end_process(Hook::InvalidParameter.code())
+        }
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "C" fn on_terminate() {
+    report(Hook::Terminate);
+    if let Some(previous) = PREVIOUS.get().and_then(|p| p.terminate) {
+        // SAFETY: The CRT accepted it as a terminate handler
+        unsafe { previous() };
+    }
+    end_process(Hook::Terminate.code())
+}
+
+/// Runs inside `abort()`, which ends the process once the handler returns.
+#[cfg(windows)]
+unsafe extern "C" fn on_abort(signal: i32) {
+    report(Hook::Abort);
+    if let Some(previous) = PREVIOUS.get().and_then(|p| previous_abort_handler(p.abort)) {
+        // SAFETY: Anything but SIG_DFL, SIG_IGN, and SIG_ERR is a handler
+        // the CRT accepted
+        unsafe { std::mem::transmute::<usize, SignalHandler>(previous)(signal) };
+    }
+}
+
+/// Reports exceptions nothing handled that the VEH let through as
+/// non-fatal, such as an uncaught C++ exception on a thread without a CRT
+/// frame.
+#[cfg(windows)]
+unsafe extern "system" fn on_unhandled_exception(info: *const EXCEPTION_POINTERS) -> i32 {
+    use windows::Win32::System::Diagnostics::Debug::EXCEPTION_CONTINUE_SEARCH;
+
+    // A fault in the crash capture ends its thread, not the process
+    crate::handler::park_if_reporter();
+
+    // SAFETY: Windows passes valid exception pointers to the filter
+    if let Some(info) = unsafe { info.as_ref() }
+        && let Some(record) = unsafe { info.ExceptionRecord.as_ref() }
+    {
+        let code = record.ExceptionCode.0 as u32;
+        // The VEH already reported fatal codes
+        if filter_reports(first_termination(), code) {
+            let mut information = [0u64; MAX_PARAMETERS];
+            let parameters = exception_parameters(record, &mut information);
+            let mut nested = [NestedException::default(); MAX_NESTED];
+            let count = nested_exceptions(record, &mut nested);
+            dispatch(&Exception {
+                code,
+                address: record.ExceptionAddress as u64,
+                // SAFETY: As above
+                context: unsafe { info.ContextRecord.as_ref() },
+                information: &information[..parameters],
+                nested: &nested[..count],
+                terminating: true,
+            });
+        }
+    }
+
+    match PREVIOUS.get().and_then(|p| p.filter) {
+        // SAFETY: Windows accepted it as a filter; the pointers are passed on
+        Some(previous) => unsafe { previous(info) },
+        None => EXCEPTION_CONTINUE_SEARCH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exception::{ACCESS_VIOLATION, is_crt_termination};
+
+    #[test]
+    fn hooks_report_their_own_codes() {
+        assert_eq!(Hook::PureCall.code(), PURE_VIRTUAL_CALL);
+        assert_eq!(Hook::InvalidParameter.code(), INVALID_CRT_PARAMETER);
+        assert_eq!(Hook::Terminate.code(), CPP_TERMINATE);
+        assert_eq!(Hook::Abort.code(), CRT_ABORT);
+
+        for hook in [
+            Hook::PureCall,
+            Hook::InvalidParameter,
+            Hook::Terminate,
+            Hook::Abort,
+        ] {
+            assert!(is_crt_termination(hook.code()));
+            assert!(is_fatal_exception(hook.code()));
+        }
+    }
+
+    #[test]
+    fn filter_reports_only_first_non_fatal() {
+        // An uncaught C++ exception the VEH let through
+        assert!(filter_reports(true, 0xE06D7363));
+        assert!(!filter_reports(false, 0xE06D7363));
+
+        // The VEH already reported these
+        assert!(!filter_reports(true, ACCESS_VIOLATION));
+        assert!(!filter_reports(true, CRT_ABORT));
+    }
+
+    #[test]
+    fn invalid_parameter_ends_process_only_without_handler() {
+        assert_eq!(
+            invalid_parameter_action(Some(0x1234_5678usize)),
+            InvalidParameterAction::Chain(0x1234_5678)
+        );
+        assert_eq!(
+            invalid_parameter_action(None::<usize>),
+            InvalidParameterAction::Terminate
+        );
+    }
+
+    #[test]
+    fn abort_chains_only_to_handlers() {
+        assert_eq!(previous_abort_handler(SIG_DFL), None);
+        assert_eq!(previous_abort_handler(SIG_IGN), None);
+        assert_eq!(previous_abort_handler(SIG_ERR), None);
+        assert_eq!(previous_abort_handler(0x1234_5678), Some(0x1234_5678));
+    }
+}
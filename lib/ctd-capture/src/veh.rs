@@ -0,0 +1,257 @@
+//! Vectored exception handler registration.
+//!
+//! [`register_veh`] installs one handler per process that decodes the
+//! exception pointers and passes them to a plain function. The callback
+//! decides what is fatal and how to report it; the handler always returns
+//! `EXCEPTION_CONTINUE_SEARCH`, so the game, other crash loggers, and a
+//! debugger still see the exception.
+//!
+//! The callback runs on the faulting thread, possibly with an exhausted
+//! stack or a corrupt heap, so it should hand the work to another thread
+//! rather than allocate.
+//!
+//! Registering also installs the CRT termination hooks and an unhandled
+//! exception filter (see `termination.rs`), which pass the terminations the
+//! handler never sees to the same callback.
+
+use std::sync::OnceLock;
+
+use thiserror::Error;
+
+/// The crashed thread's register state.
+#[cfg(windows)]
+pub type Context = windows::Win32::System::Diagnostics::Debug::CONTEXT;
+
+/// The crashed thread's register state (opaque off Windows).
+#[cfg(not(windows))]
+pub type Context = std::ffi::c_void;
+
+/// Returns the address of the instruction a thread context stopped at.
+#[cfg(windows)]
+pub fn instruction_pointer(context: &Context) -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    let address = context.Rip;
+    #[cfg(target_arch = "x86")]
+    let address = u64::from(context.Eip);
+    address
+}
+
+/// An exception record as Windows passes it to handlers.
+#[cfg(windows)]
+pub type ExceptionRecord = windows::Win32::System::Diagnostics::Debug::EXCEPTION_RECORD;
+
+/// Most parameters an exception record holds (`EXCEPTION_MAXIMUM_PARAMETERS`).
+pub const MAX_PARAMETERS: usize = 15;
+
+/// Most records [`nested_exceptions`] follows.
+pub const MAX_NESTED: usize = 8;
+
+/// An exception linked from another's record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NestedException {
+    /// The Windows exception code.
+    pub code: u32,
+    /// The address where it occurred.
+    pub address: u64,
+}
+
+/// Errors from registering the handler.
+#[derive(Error, Debug)]
+pub enum VehError {
+    /// A handler was already registered in this process.
+    #[error("Exception handler already registered")]
+    AlreadyRegistered,
+
+    /// Windows refused the registration.
+    #[error("Failed to register VEH handler: {0}")]
+    RegistrationFailed(String),
+}
+
+/// How the handler is registered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VehOptions {
+    /// Run before handlers other tools registered, rather than after.
+    pub first: bool,
+}
+
+/// An exception as the handler saw it.
+#[derive(Clone, Copy)]
+pub struct Exception<'a> {
+    /// The Windows exception code (e.g., 0xC0000005 for ACCESS_VIOLATION).
+    pub code: u32,
+    /// The address where the exception occurred.
+    pub address: u64,
+    /// The faulting thread's context, valid only during the callback.
+    pub context: Option<&'a Context>,
+    /// The record's `ExceptionInformation`, such as the access type and
+    /// address of an access violation.
+    pub information: &'a [u64],
+    /// Exceptions the record links to through `ExceptionRecord`, innermost
+    /// first: the fault happened while they were being dispatched.
+    pub nested: &'a [NestedException],
+    /// The process is ending: a CRT termination hook or the unhandled
+    /// exception filter raised this, so it is fatal whatever its code.
+    pub terminating: bool,
+}
+
+impl Exception<'_> {
+    /// Returns true if the exception is a crash worth reporting.
+    pub fn is_fatal(&self) -> bool {
+        self.terminating || crate::exception::is_fatal_exception(self.code)
+    }
+}
+
+/// Called for every exception the handler sees, fatal or not.
+pub type ExceptionCallback = fn(&Exception<'_>);
+
+/// The callback [`register_veh`] installed.
+static CALLBACK: OnceLock<ExceptionCallback> = OnceLock::new();
+
+/// Registers the process's vectored exception handler.
+///
+/// # Errors
+///
+/// Returns `VehError::AlreadyRegistered` if called before, and
+/// `VehError::RegistrationFailed` if Windows API fails.
+#[cfg(windows)]
+pub fn register_veh(options: VehOptions, callback: ExceptionCallback) -> Result<(), VehError> {
+    use windows::Win32::System::Diagnostics::Debug::AddVectoredExceptionHandler;
+
+    CALLBACK
+        .set(callback)
+        .map_err(|_| VehError::AlreadyRegistered)?;
+
+    // SAFETY: veh_handler is a valid handler for the life of the process
+    let handle =
+        unsafe { AddVectoredExceptionHandler(u32::from(options.first), Some(veh_handler)) };
+    if handle.is_null() {
+        return Err(VehError::RegistrationFailed(
+            "AddVectoredExceptionHandler returned null".to_string(),
+        ));
+    }
+    crate::termination::install();
+
+    Ok(())
+}
+
+/// Non-Windows stub: records the callback, which never runs.
+#[cfg(not(windows))]
+pub fn register_veh(_options: VehOptions, callback: ExceptionCallback) -> Result<(), VehError> {
+    CALLBACK
+        .set(callback)
+        .map_err(|_| VehError::AlreadyRegistered)
+}
+
+/// The handler Windows calls for every exception in the process.
+#[cfg(windows)]
+unsafe extern "system" fn veh_handler(
+    exception_info: *mut windows::Win32::System::Diagnostics::Debug::EXCEPTION_POINTERS,
+) -> i32 {
+    use windows::Win32::System::Diagnostics::Debug::EXCEPTION_CONTINUE_SEARCH;
+
+    // SAFETY: Windows guarantees exception_info is valid when this callback is invoked
+    let Some(info) = (unsafe { exception_info.as_ref() }) else {
+        return EXCEPTION_CONTINUE_SEARCH;
+    };
+    // SAFETY: The record and context live until the handler returns
+    let Some(record) = (unsafe { info.ExceptionRecord.as_ref() }) else {
+        return EXCEPTION_CONTINUE_SEARCH;
+    };
+
+    let mut information = [0u64; MAX_PARAMETERS];
+    let parameters = exception_parameters(record, &mut information);
+    let mut nested = [NestedException::default(); MAX_NESTED];
+    let count = nested_exceptions(record, &mut nested);
+    dispatch(&Exception {
+        code: record.ExceptionCode.0 as u32,
+        address: record.ExceptionAddress as u64,
+        // SAFETY: As above
+        context: unsafe { info.ContextRecord.as_ref() },
+        information: &information[..parameters],
+        nested: &nested[..count],
+        terminating: false,
+    });
+
+    EXCEPTION_CONTINUE_SEARCH
+}
+
+/// Copies a record's `ExceptionInformation` into `out`.
+///
+/// Returns how many parameters were copied, at most `out.len()`.
+#[cfg(windows)]
+pub fn exception_parameters(record: &ExceptionRecord, out: &mut [u64]) -> usize {
+    let count = (record.NumberParameters as usize)
+        .min(record.ExceptionInformation.len())
+        .min(out.len());
+    for (slot, &value) in out.iter_mut().zip(&record.ExceptionInformation[..count]) {
+        *slot = value as u64;
+    }
+    count
+}
+
+/// Copies the records `record` links to into `out`, without allocating.
+///
+/// Returns how many were copied, at most `out.len()`.
+#[cfg(windows)]
+pub fn nested_exceptions(record: &ExceptionRecord, out: &mut [NestedException]) -> usize {
+    let mut count = 0;
+    // SAFETY: Windows keeps the chain alive as long as the outer record
+    let mut next = unsafe { record.ExceptionRecord.as_ref() };
+    while let Some(nested) = next
+        && count < out.len()
+    {
+        out[count] = NestedException {
+            code: nested.ExceptionCode.0 as u32,
+            address: nested.ExceptionAddress as u64,
+        };
+        count += 1;
+        // SAFETY: As above
+        next = unsafe { nested.ExceptionRecord.as_ref() };
+    }
+    count
+}
+
+/// Passes an exception to the registered callback, if there is one.
+#[cfg(windows)]
+pub(crate) fn dispatch(exception: &Exception<'_>) {
+    if let Some(callback) = CALLBACK.get() {
+        callback(exception);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_once() {
+        fn ignore(_: &Exception<'_>) {}
+
+        let options = VehOptions::default();
+        assert!(register_veh(options, ignore).is_ok());
+        assert!(matches!(
+            register_veh(options, ignore),
+            Err(VehError::AlreadyRegistered)
+        ));
+    }
+
+    #[test]
+    fn terminations_are_always_fatal() {
+        let exception = Exception {
+            code: 0xE06D7363,
+            address: 0x7FF600001234,
+            context: None,
+            information: &[],
+            nested: &[],
+            terminating: false,
+        };
+        assert!(!exception.is_fatal());
+        assert!(
+            Exception {
+                terminating: true,
+                ..exception
+            }
+            .is_fatal()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// CTD Crash Reporter - C API for other mods
+//
+// Lets a native mod add context to the next CTD crash report without
+// linking Rust or CTD: breadcrumbs of what it was doing, key/value state,
+// and errors it caught instead of crashing. Copy this header into your
+// project (it is attached to every CTD release); it is plain C and compiles
+// as C++.
+//
+// Every CTD game DLL (ctd-skyrim.dll, ctd-cyberpunk.dll, the UE4SS mods'
+// main.dll, ...) exports the functions below. The DLL's name differs by
+// game and CTD may not be installed, so look the functions up at runtime
+// with ctd_load() instead of linking an import library:
+//
+//     ctd_api ctd;
+//     if (ctd_load(&ctd)) {
+//         ctd.add_breadcrumb("MyQuestMod", "Stage 40 started");
+//         ctd.set_context_kv("MyQuestMod.stage", "40");
+//     }
+//
+// Call ctd_load() after the game has loaded its plugins, e.g. from SKSE's
+// kPostLoad message, since CTD may load after your mod.
+//
+// All strings are NUL-terminated UTF-8. The functions copy them, take a
+// lock, and return quickly; call them from any thread, but not from an
+// exception handler. Nothing is sent when they are called: the entries go
+// in the next crash, hang, or manual report. Each returns false if it
+// rejected its arguments.
+
+#ifndef CTD_H
+#define CTD_H
+
+#include <stdbool.h>
+
+#if defined(_WIN32) && !defined(CTD_NO_LOADER)
+#include <windows.h>
+#include <tlhelp32.h>
+#endif
+
+// Bumped when a function is added; existing functions never change.
+#define CTD_ABI_VERSION 1
+
+// Defined by the CTD DLLs to export the functions, which also define
+// CTD_NO_LOADER to leave out ctd_load() and, in C++, CTD_DEFINE_EXPORTS to
+// define them; leave all three undefined.
+#ifndef CTD_API
+#define CTD_API
+#endif
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Records a step in the last 32 steps reported as `breadcrumbs`.
+// `source` names your mod (1-100 bytes); `message` is cut to 500 bytes.
+CTD_API bool ctd_add_breadcrumb(const char* source, const char* message);
+
+// Sets a key reported in `modContext`, or removes it if `value` is NULL or
+// empty. Prefix keys with your mod's name ("MyQuestMod.stage"). Keys are
+// 1-100 bytes and values are cut to 500 bytes. Returns false when 32 keys
+// are already set.
+CTD_API bool ctd_set_context_kv(const char* key, const char* value);
+
+// Records an error your mod caught, reported in `modErrors` with a count
+// of how often it happened. The 16 most recent distinct errors are kept.
+CTD_API bool ctd_report_handled_error(const char* source, const char* message);
+
+typedef bool (*ctd_add_breadcrumb_fn)(const char* source, const char* message);
+typedef bool (*ctd_set_context_kv_fn)(const char* key, const char* value);
+typedef bool (*ctd_report_handled_error_fn)(const char* source, const char* message);
+
+// The functions, as found by ctd_load().
+typedef struct ctd_api {
+    ctd_add_breadcrumb_fn add_breadcrumb;
+    ctd_set_context_kv_fn set_context_kv;
+    ctd_report_handled_error_fn report_handled_error;
+} ctd_api;
+
+#if defined(_WIN32) && !defined(CTD_NO_LOADER)
+
+// Finds the CTD DLL loaded in this process and fills `api` with its
+// functions. Returns false, leaving `api` zeroed, if CTD isn't loaded.
+static inline bool ctd_load(ctd_api* api) {
+    HANDLE snapshot;
+    MODULEENTRY32W entry;
+    bool found = false;
+
+    api->add_breadcrumb = NULL;
+    api->set_context_kv = NULL;
+    api->report_handled_error = NULL;
+
+    snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPMODULE, 0);
+    if (snapshot == INVALID_HANDLE_VALUE) {
+        return false;
+    }
+    entry.dwSize = sizeof(entry);
+    if (Module32FirstW(snapshot, &entry)) {
+        do {
+            FARPROC add = GetProcAddress(entry.hModule, "ctd_add_breadcrumb");
+            if (add) {
+                api->add_breadcrumb = (ctd_add_breadcrumb_fn)(void*)add;
+                api->set_context_kv = (ctd_set_context_kv_fn)(void*)GetProcAddress(
+                    entry.hModule, "ctd_set_context_kv");
+                api->report_handled_error = (ctd_report_handled_error_fn)(void*)GetProcAddress(
+                    entry.hModule, "ctd_report_handled_error");
+                found = api->set_context_kv && api->report_handled_error;
+                break;
+            }
+        } while (Module32NextW(snapshot, &entry));
+    }
+    CloseHandle(snapshot);
+
+    if (!found) {
+        api->add_breadcrumb = NULL;
+        api->set_context_kv = NULL;
+        api->report_handled_error = NULL;
+    }
+    return found;
+}
+
+#endif  // _WIN32 && !CTD_NO_LOADER
+
+#ifdef __cplusplus
+}
+#endif
+
+#if defined(__cplusplus) && defined(CTD_DEFINE_EXPORTS)
+
+// The C++ CTD DLLs include this after their cxx bridge header, which
+// declares the Rust functions these forward to. Strings that aren't valid
+// UTF-8 are rejected.
+
+#include <stdexcept>
+
+extern "C" {
+
+CTD_API bool ctd_add_breadcrumb(const char* source, const char* message) {
+    if (!source || !message) {
+        return false;
+    }
+    try {
+        return ctd::add_breadcrumb(rust::Str(source), rust::Str(message));
+    } catch (const std::invalid_argument&) {
+        return false;
+    }
+}
+
+CTD_API bool ctd_set_context_kv(const char* key, const char* value) {
+    if (!key) {
+        return false;
+    }
+    try {
+        return ctd::set_context(rust::Str(key), rust::Str(value ? value : ""));
+    } catch (const std::invalid_argument&) {
+        return false;
+    }
+}
+
+CTD_API bool ctd_report_handled_error(const char* source, const char* message) {
+    if (!source || !message) {
+        return false;
+    }
+    try {
+        return ctd::report_handled_error(rust::Str(source), rust::Str(message));
+    } catch (const std::invalid_argument&) {
+        return false;
+    }
+}
+
+}  // extern "C"
+
+#endif  // __cplusplus && CTD_DEFINE_EXPORTS
+
+#endif  // CTD_H
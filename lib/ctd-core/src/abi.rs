@@ -0,0 +1,433 @@
+//! C API for other native mods to add context to reports (ctd-abi).
+//!
+//! A quest framework knows which stage it was running and a renderer mod
+//! knows which preset it loaded, but neither shows up in a stack trace.
+//! Each plugin DLL exports three C functions, declared in
+//! `lib/ctd-core/include/ctd.h`, that other mods call without linking Rust:
+//!
+//! - `ctd_add_breadcrumb(source, message)` records a step in a ring of the
+//!   last [`MAX_BREADCRUMBS`], reported newest first as `breadcrumbs`.
+//! - `ctd_set_context_kv(key, value)` sets one of up to [`MAX_MOD_CONTEXT`]
+//!   keys in `modContext`; an empty or null value removes the key.
+//! - `ctd_report_handled_error(source, message)` records an error the mod
+//!   caught instead of crashing. Repeats of the same error are counted, and
+//!   the last [`MAX_MOD_ERRORS`] go in `modErrors`.
+//!
+//! Nothing is submitted when they are called: the entries ride along in the
+//! next crash, hang, or manual report, like the
+//! [`exception_tally`](crate::exception_tally) counts. Rust plugins export
+//! the functions with [`export_abi!`](crate::export_abi); the C++ plugins
+//! define `CTD_DEFINE_EXPORTS` before including `ctd.h`, which then defines
+//! them by forwarding through the cxx bridge to [`add_breadcrumb`],
+//! [`set_context`], and [`report_handled_error`].
+//!
+//! Script mods reach the same state through their game's script API, such
+//! as Papyrus's `CTD.AddBreadcrumb` on Skyrim and Fallout 4 (with the
+//! calling script as the source) or CET's `CTD.Breadcrumb` on Cyberpunk 2077. Scripts can also leave notes with
+//! [`add_note`]: the last [`MAX_MOD_NOTES`] go at the end of the report's
+//! `notes`, after the player's own.
+//!
+//! The functions take a lock, so they are safe from any thread but not from
+//! a signal or exception handler. Reports read the entries with `try_lock`,
+//! so a thread that crashed while holding the lock costs the sections, not
+//! the report.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::ffi::{CStr, c_char};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::crash_guard::now_millis;
+use crate::crash_report::{
+    Breadcrumb, MAX_BREADCRUMBS, MAX_MOD_CONTEXT, MAX_MOD_ERRORS, MAX_MOD_MESSAGE_LEN,
+    MAX_MOD_NOTES, ModError,
+};
+
+/// Maximum length of a source or context key; longer ones are rejected.
+const MAX_NAME_LEN: usize = 100;
+
+/// Maximum length of a context value; longer ones are truncated.
+const MAX_VALUE_LEN: usize = 500;
+
+/// Everything mods recorded this session.
+static STATE: Mutex<State> = Mutex::new(State::new());
+
+struct State {
+    /// Oldest first.
+    breadcrumbs: VecDeque<Breadcrumb>,
+    context: BTreeMap<String, String>,
+    /// Least recently reported first.
+    errors: VecDeque<ModError>,
+    /// Oldest first, as `[source] text`.
+    notes: VecDeque<String>,
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            breadcrumbs: VecDeque::new(),
+            context: BTreeMap::new(),
+            errors: VecDeque::new(),
+            notes: VecDeque::new(),
+        }
+    }
+
+    fn add_note(&mut self, source: &str, text: &str) {
+        if self.notes.len() == MAX_MOD_NOTES {
+            self.notes.pop_front();
+        }
+        self.notes.push_back(format!(
+            "[{}] {}",
+            source,
+            truncate(text, MAX_MOD_MESSAGE_LEN)
+        ));
+    }
+
+    fn add_breadcrumb(&mut self, source: &str, message: &str, at: u64) {
+        if self.breadcrumbs.len() == MAX_BREADCRUMBS {
+            self.breadcrumbs.pop_front();
+        }
+        self.breadcrumbs.push_back(Breadcrumb {
+            source: source.to_string(),
+            message: truncate(message, MAX_MOD_MESSAGE_LEN),
+            at,
+        });
+    }
+
+    /// Returns false if the key is new and the context is full.
+    fn set_context(&mut self, key: &str, value: &str) -> bool {
+        if value.is_empty() {
+            self.context.remove(key);
+            return true;
+        }
+        if self.context.len() == MAX_MOD_CONTEXT && !self.context.contains_key(key) {
+            return false;
+        }
+        self.context
+            .insert(key.to_string(), truncate(value, MAX_VALUE_LEN));
+        true
+    }
+
+    fn report_error(&mut self, source: &str, message: &str, at: u64) {
+        let message = truncate(message, MAX_MOD_MESSAGE_LEN);
+        let mut error = match self
+            .errors
+            .iter()
+            .position(|error| error.source == source && error.message == message)
+        {
+            Some(index) => self.errors.remove(index).expect("index is in bounds"),
+            None => ModError {
+                source: source.to_string(),
+                message,
+                count: 0,
+                last_at: at,
+            },
+        };
+        error.count = error.count.saturating_add(1);
+        error.last_at = at;
+
+        if self.errors.len() == MAX_MOD_ERRORS {
+            self.errors.pop_front();
+        }
+        self.errors.push_back(error);
+    }
+}
+
+/// Records a step for the next report.
+///
+/// Returns false if `source` is empty or longer than 100 bytes or `message`
+/// is empty. Longer messages are truncated.
+pub fn add_breadcrumb(source: &str, message: &str) -> bool {
+    if !is_valid_name(source) || message.is_empty() {
+        return false;
+    }
+    lock().add_breadcrumb(source, message, now_millis());
+    true
+}
+
+/// Sets a context key for the next report, or removes it if `value` is
+/// empty.
+///
+/// Returns false if `key` is empty or longer than 100 bytes, or if the
+/// context already holds [`MAX_MOD_CONTEXT`] other keys.
+pub fn set_context(key: &str, value: &str) -> bool {
+    is_valid_name(key) && lock().set_context(key, value)
+}
+
+/// Records an error a mod handled, for the next report.
+///
+/// Returns false if `source` is empty or longer than 100 bytes or `message`
+/// is empty.
+pub fn report_handled_error(source: &str, message: &str) -> bool {
+    if !is_valid_name(source) || message.is_empty() {
+        return false;
+    }
+    lock().report_error(source, message, now_millis());
+    true
+}
+
+/// Records a note for the end of the next report's notes.
+///
+/// Returns false if `source` is empty or longer than 100 bytes or `text` is
+/// empty. Longer notes are truncated.
+pub fn add_note(source: &str, text: &str) -> bool {
+    if !is_valid_name(source) || text.trim().is_empty() {
+        return false;
+    }
+    lock().add_note(source, text.trim());
+    true
+}
+
+/// Returns the recorded breadcrumbs, newest first.
+pub fn breadcrumbs() -> Vec<Breadcrumb> {
+    try_lock()
+        .map(|state| state.breadcrumbs.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns the context keys mods set.
+pub fn context() -> BTreeMap<String, String> {
+    try_lock()
+        .map(|state| state.context.clone())
+        .unwrap_or_default()
+}
+
+/// Returns the errors mods reported, most recent first.
+pub fn mod_errors() -> Vec<ModError> {
+    try_lock()
+        .map(|state| state.errors.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns the notes mods added, oldest first, as `[source] text`.
+pub fn notes() -> Vec<String> {
+    try_lock()
+        .map(|state| state.notes.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// [`add_breadcrumb`] for `ctd_add_breadcrumb`.
+///
+/// # Safety
+///
+/// `source` and `message` must each be null or a valid NUL-terminated
+/// string.
+pub unsafe fn add_breadcrumb_c(source: *const c_char, message: *const c_char) -> bool {
+    // SAFETY: the caller guarantees both are null or valid C strings
+    match unsafe { (from_c(source), from_c(message)) } {
+        (Some(source), Some(message)) => add_breadcrumb(source, message),
+        _ => false,
+    }
+}
+
+/// [`set_context`] for `ctd_set_context_kv`, where a null `value` also
+/// removes the key.
+///
+/// # Safety
+///
+/// `key` and `value` must each be null or a valid NUL-terminated string.
+pub unsafe fn set_context_c(key: *const c_char, value: *const c_char) -> bool {
+    // SAFETY: the caller guarantees `key` is null or a valid C string
+    let Some(key) = (unsafe { from_c(key) }) else {
+        return false;
+    };
+    if value.is_null() {
+        return set_context(key, "");
+    }
+    // SAFETY: the caller guarantees `value` is a valid C string
+    match unsafe { from_c(value) } {
+        Some(value) => set_context(key, value),
+        None => false, // Not valid UTF-8
+    }
+}
+
+/// [`report_handled_error`] for `ctd_report_handled_error`.
+///
+/// # Safety
+///
+/// `source` and `message` must each be null or a valid NUL-terminated
+/// string.
+pub unsafe fn report_handled_error_c(source: *const c_char, message: *const c_char) -> bool {
+    // SAFETY: the caller guarantees both are null or valid C strings
+    match unsafe { (from_c(source), from_c(message)) } {
+        (Some(source), Some(message)) => report_handled_error(source, message),
+        _ => false,
+    }
+}
+
+/// Exports `ctd_add_breadcrumb`, `ctd_set_context_kv`, and
+/// `ctd_report_handled_error` from a Rust plugin DLL.
+///
+/// Invoke once, at the crate root, in the DLL's crate; the C++ plugins
+/// export the same functions from their own sources instead.
+#[macro_export]
+macro_rules! export_abi {
+    () => {
+        /// Records a step for the next crash report (see `ctd.h`).
+        ///
+        /// # Safety
+        ///
+        /// Both arguments must be null or valid NUL-terminated strings.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn ctd_add_breadcrumb(
+            source: *const ::std::ffi::c_char,
+            message: *const ::std::ffi::c_char,
+        ) -> bool {
+            // SAFETY: the caller upholds the contract in ctd.h
+            unsafe { $crate::abi::add_breadcrumb_c(source, message) }
+        }
+
+        /// Sets or removes a context key for the next crash report (see
+        /// `ctd.h`).
+        ///
+        /// # Safety
+        ///
+        /// Both arguments must be null or valid NUL-terminated strings.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn ctd_set_context_kv(
+            key: *const ::std::ffi::c_char,
+            value: *const ::std::ffi::c_char,
+        ) -> bool {
+            // SAFETY: the caller upholds the contract in ctd.h
+            unsafe { $crate::abi::set_context_c(key, value) }
+        }
+
+        /// Records an error the calling mod handled, for the next crash
+        /// report (see `ctd.h`).
+        ///
+        /// # Safety
+        ///
+        /// Both arguments must be null or valid NUL-terminated strings.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn ctd_report_handled_error(
+            source: *const ::std::ffi::c_char,
+            message: *const ::std::ffi::c_char,
+        ) -> bool {
+            // SAFETY: the caller upholds the contract in ctd.h
+            unsafe { $crate::abi::report_handled_error_c(source, message) }
+        }
+    };
+}
+
+/// Reads a UTF-8 C string; `None` if it is null or not valid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must be null or a valid NUL-terminated string that outlives `'a`.
+unsafe fn from_c<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: the caller guarantees `ptr` is a valid C string
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_NAME_LEN
+}
+
+pub(crate) fn truncate(text: &str, max_len: usize) -> String {
+    let mut end = text.len().min(max_len);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
+
+fn lock() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The state, unless another thread holds the lock.
+fn try_lock() -> Option<MutexGuard<'static, State>> {
+    match STATE.try_lock() {
+        Ok(state) => Some(state),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_breadcrumbs_and_counts_repeated_errors() {
+        let mut state = State::new();
+        for n in 0..MAX_BREADCRUMBS + 5 {
+            state.add_breadcrumb("MyQuestMod", &format!("step {}", n), n as u64);
+        }
+        assert_eq!(state.breadcrumbs.len(), MAX_BREADCRUMBS);
+        assert_eq!(state.breadcrumbs[0].message, "step 5");
+
+        state.report_error("MyQuestMod", "Actor not found", 10);
+        state.report_error("OtherMod", "Bad preset", 20);
+        state.report_error("MyQuestMod", "Actor not found", 30);
+        assert_eq!(state.errors.len(), 2);
+        let newest = state.errors.back().unwrap();
+        assert_eq!((newest.count, newest.last_at), (2, 30));
+        assert_eq!(newest.source, "MyQuestMod");
+    }
+
+    #[test]
+    fn notes_keep_their_source_and_the_newest_few() {
+        let mut state = State::new();
+        for n in 0..MAX_MOD_NOTES + 2 {
+            state.add_note("MyQuestScript", &format!("note {}", n));
+        }
+        assert_eq!(state.notes.len(), MAX_MOD_NOTES);
+        assert_eq!(state.notes[0], "[MyQuestScript] note 2");
+
+        assert!(!add_note("MyQuestScript", "   "));
+        assert!(add_note("AbiNoteTest", "  Boss spawned\n"));
+        assert!(notes().contains(&"[AbiNoteTest] Boss spawned".to_string()));
+    }
+
+    #[test]
+    fn context_is_capped_and_empty_values_remove_keys() {
+        let mut state = State::new();
+        for n in 0..MAX_MOD_CONTEXT {
+            assert!(state.set_context(&format!("key{}", n), "value"));
+        }
+        assert!(!state.set_context("one-too-many", "value"));
+        assert!(state.set_context("key0", "updated"));
+        assert!(state.set_context("key1", ""));
+        assert!(state.set_context("one-too-many", &"é".repeat(MAX_VALUE_LEN)));
+        assert_eq!(state.context["key0"], "updated");
+        assert_eq!(state.context["one-too-many"].len(), MAX_VALUE_LEN);
+
+        assert!(!set_context("", "value"));
+        assert!(!set_context(&"k".repeat(MAX_NAME_LEN + 1), "value"));
+    }
+
+    #[test]
+    fn c_functions_check_their_strings() {
+        // SAFETY: every pointer is null or a NUL-terminated literal
+        unsafe {
+            assert!(add_breadcrumb_c(c"AbiTest".as_ptr(), c"Loaded".as_ptr()));
+            assert!(!add_breadcrumb_c(std::ptr::null(), c"Loaded".as_ptr()));
+            assert!(!add_breadcrumb_c(c"AbiTest".as_ptr(), c"\xff".as_ptr()));
+
+            assert!(set_context_c(c"AbiTest.preset".as_ptr(), c"Ultra".as_ptr()));
+            assert_eq!(context()["AbiTest.preset"], "Ultra");
+            assert!(set_context_c(c"AbiTest.preset".as_ptr(), std::ptr::null()));
+            assert!(!context().contains_key("AbiTest.preset"));
+
+            assert!(report_handled_error_c(
+                c"AbiTest".as_ptr(),
+                c"Oops".as_ptr()
+            ));
+            assert!(!report_handled_error_c(c"AbiTest".as_ptr(), c"".as_ptr()));
+        }
+        assert!(breadcrumbs().iter().any(|crumb| crumb.source == "AbiTest"));
+        assert!(mod_errors().iter().any(|error| error.message == "Oops"));
+
+        let header = include_str!("../include/ctd.h");
+        for function in [
+            "ctd_add_breadcrumb",
+            "ctd_set_context_kv",
+            "ctd_report_handled_error",
+        ] {
+            assert!(header.contains(&format!("CTD_API bool {}(", function)));
+        }
+    }
+}
@@ -3,32 +3,244 @@
 //! This module provides an HTTP client for communicating with the CTD backend API.
 //! Configuration is loaded from `ctd.toml` or environment variables.
 
-use tracing::{debug, instrument};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Once, OnceLock};
+use std::time::{Duration, SystemTime};
 
-use crate::config::{ApiConfig, Config};
-use crate::crash_report::{CrashReportResponse, CreateCrashReport};
+use hmac::{Hmac, Mac};
+use reqwest::header::{CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::{debug, info, instrument, warn};
+
+use crate::config::{ApiConfig, Config, NotifyConfig, SentryConfig};
+use crate::crash_hash::compute_crash_hash;
+use crate::crash_report::{
+    CURRENT_SCHEMA_VERSION, CrashReportResponse, CreateCrashReport, MAX_NOTES_LEN, ReportPage,
+    StoredReport,
+};
+use crate::known_bad::BadModuleRule;
+use crate::notify::DiscordWebhook;
+use crate::sentry::SentryTransport;
+use crate::session::SessionReport;
 use crate::{CtdError, Result};
 
+/// Version of this CTD client, sent with every request and report.
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Header carrying [`CLIENT_VERSION`] on every request.
+pub const CLIENT_HEADER: &str = "X-CTD-Client";
+
+/// API path for the health-check endpoint.
+pub const HEALTH_PATH: &str = "/health";
+
+/// Header carrying the HMAC-SHA256 signature of a submission body.
+pub const SIGNATURE_HEADER: &str = "X-CTD-Signature";
+
+/// API path for schema/feature negotiation.
+pub const CAPABILITIES_PATH: &str = "/v1/capabilities";
+
+/// API path for known-issue lookups by crash hash.
+pub const KNOWN_ISSUES_PATH: &str = "/known-issues";
+
+/// API path for the latest released client version.
+pub const LATEST_VERSION_PATH: &str = "/v1/client/latest";
+
+/// How often [`spawn_ping_check`] asks for the latest client version.
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Reports per page requested by [`ApiClient::list_my_reports`].
+pub const REPORTS_PAGE_SIZE: u32 = 20;
+
+/// Guards [`spawn_ping_check`] so the server is only pinged once per process.
+static PING_CHECK: Once = Once::new();
+
+/// Capabilities fetched by [`spawn_ping_check`].
+static SERVER_CAPABILITIES: OnceLock<ServerCapabilities> = OnceLock::new();
+
+/// Schema version and features supported by the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    /// Highest crash report schema version the server accepts.
+    pub max_schema_version: u32,
+    /// Optional features the server supports.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// DLLs the server flags as crash-prone, on top of the client's list
+    /// (see [`crate::known_bad`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_bad_modules: Vec<BadModuleRule>,
+}
+
+impl ServerCapabilities {
+    /// Capabilities assumed for servers without a capabilities endpoint.
+    ///
+    /// Those predate schema negotiation and only accept v1 reports.
+    pub fn legacy() -> Self {
+        Self {
+            max_schema_version: 1,
+            features: Vec::new(),
+            known_bad_modules: Vec::new(),
+        }
+    }
+
+    /// Returns true if the server advertises a feature.
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Returns the schema version to build reports with.
+    pub fn schema_version(&self) -> u32 {
+        self.max_schema_version.min(CURRENT_SCHEMA_VERSION)
+    }
+}
+
+/// Advice the server has recorded for a crash signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownIssue {
+    /// Crash hash the advice applies to.
+    pub crash_hash: String,
+    /// Human-readable name of the crash pattern, if one has been assigned.
+    #[serde(default)]
+    pub pattern_name: Option<String>,
+    /// What to do about it (e.g., "caused by OldMod.dll < 1.4").
+    pub known_fix: String,
+    /// How many reports share this signature.
+    #[serde(default)]
+    pub occurrence_count: u64,
+    /// Mods suspected of causing the crash.
+    #[serde(default)]
+    pub suspected_mods: Vec<String>,
+}
+
+impl KnownIssue {
+    /// Formats the advice as a single line for logs and notifications.
+    pub fn advice(&self) -> String {
+        let mut advice = match self.pattern_name {
+            Some(ref name) => format!("Known issue \"{}\": {}", name, self.known_fix),
+            None => format!("Known issue: {}", self.known_fix),
+        };
+        if !self.suspected_mods.is_empty() {
+            advice.push_str(&format!(" (suspected: {})", self.suspected_mods.join(", ")));
+        }
+        advice
+    }
+}
+
+/// The newest CTD release, as announced by the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestVersion {
+    /// Version number (e.g., "0.3.1").
+    pub version: String,
+    /// Where to download it, if the server says.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl LatestVersion {
+    /// Formats the notice logged when this version is newer than ours.
+    pub fn notice(&self) -> String {
+        let mut notice = format!(
+            "CTD {} is available (this is {})",
+            self.version, CLIENT_VERSION
+        );
+        if let Some(ref url) = self.url {
+            notice.push_str(&format!(": {}", url));
+        }
+        notice
+    }
+}
+
 /// HTTP client for the CTD API.
 #[derive(Debug, Clone)]
 pub struct ApiClient {
     config: ApiConfig,
     client: reqwest::Client,
+    sentry: Option<SentryTransport>,
+    discord: Option<DiscordWebhook>,
 }
 
 impl ApiClient {
     /// Creates a new API client with the given API configuration.
     ///
+    /// Applies the configured proxy and extra root certificates.
+    ///
     /// # Errors
     ///
-    /// Returns `CtdError::ApiRequest` if the HTTP client cannot be created.
+    /// Returns `CtdError::Config` if the proxy URL or CA bundle is invalid, or
+    /// `CtdError::ApiRequest` if the HTTP client cannot be created.
     pub fn new(config: ApiConfig) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(config.timeout_secs))
+        let mut headers = HeaderMap::new();
+        headers.insert(CLIENT_HEADER, HeaderValue::from_static(CLIENT_VERSION));
+        let mut builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .user_agent(concat!("ctd/", env!("CARGO_PKG_VERSION")))
+            .default_headers(headers);
+
+        if let Some(ref proxy_url) = config.proxy_url {
+            let mut proxy = reqwest::Proxy::all(proxy_url)
+                .map_err(|e| CtdError::Config(format!("Invalid proxy_url: {}", e)))?;
+            if !config.no_proxy.is_empty() {
+                proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&config.no_proxy.join(",")));
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(ref path) = config.extra_ca_cert_path {
+            for cert in load_certificates(path)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        let client = builder
             .build()
             .map_err(|e| CtdError::ApiRequest(e.to_string()))?;
 
-        Ok(Self { config, client })
+        Ok(Self {
+            config,
+            client,
+            sentry: None,
+            discord: None,
+        })
+    }
+
+    /// Sends crash reports to Sentry instead when `config.dsn` is set.
+    ///
+    /// Sentry requests share this client's proxy, certificates, and timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if the DSN is invalid.
+    pub fn with_sentry(mut self, config: &SentryConfig) -> Result<Self> {
+        self.sentry = match config.dsn {
+            Some(ref dsn) => Some(
+                SentryTransport::new(dsn, self.client.clone())?
+                    .environment(config.environment.clone()),
+            ),
+            None => None,
+        };
+        Ok(self)
+    }
+
+    /// Posts a Discord notification after each submission when
+    /// `config.discord.webhook_url` is set.
+    pub fn with_notify(mut self, config: &NotifyConfig) -> Self {
+        self.discord = config
+            .discord
+            .webhook_url
+            .as_ref()
+            .map(|url| DiscordWebhook::new(url, self.client.clone()));
+        self
+    }
+
+    /// Returns true if crash reports go to Sentry rather than the CTD API.
+    pub fn uses_sentry(&self) -> bool {
+        self.sentry.is_some()
     }
 
     /// Creates a new API client by loading configuration from file/environment.
@@ -41,8 +253,14 @@ impl ApiClient {
     /// Returns `CtdError::Config` if config loading fails, or
     /// `CtdError::ApiRequest` if the HTTP client cannot be created.
     pub fn from_config() -> Result<Self> {
+        if let Some(settings) = crate::config_watcher::active() {
+            return Ok(settings.client.clone());
+        }
+
         let config = Config::load()?;
-        Self::new(config.api)
+        Ok(Self::new(config.api)?
+            .with_sentry(&config.sentry)?
+            .with_notify(&config.notify))
     }
 
     /// Creates a new API client with default configuration.
@@ -57,7 +275,10 @@ impl ApiClient {
         Self::new(ApiConfig::default())
     }
 
-    /// Submits a crash report to the API.
+    /// Submits a crash report to the API, or to Sentry when configured.
+    ///
+    /// Once the report is accepted, posts the configured Discord
+    /// notification; a failed notification is only logged.
     ///
     /// # Errors
     ///
@@ -67,10 +288,37 @@ impl ApiClient {
         &self,
         report: &CreateCrashReport,
     ) -> Result<CrashReportResponse> {
+        let response = match self.sentry {
+            Some(ref sentry) => sentry.send(report).await?,
+            None => self.post_crash_report(report).await?,
+        };
+
+        if let Some(ref discord) = self.discord {
+            let share_url = (!self.uses_sentry()).then(|| self.share_url(&response));
+            discord
+                .notify(report, &response, share_url.as_deref())
+                .await;
+        }
+        Ok(response)
+    }
+
+    /// Posts a crash report to the CTD API.
+    async fn post_crash_report(&self, report: &CreateCrashReport) -> Result<CrashReportResponse> {
         let url = format!("{}{}", self.config.url, self.config.crashes_path);
         debug!("Submitting crash report to {}", url);
 
-        let mut request = self.client.post(&url).json(report);
+        // Serialize up front so the signature covers the exact bytes sent
+        let body = serde_json::to_vec(report)?;
+        let mut request = self
+            .client
+            .post(&url)
+            .header(CONTENT_TYPE, "application/json");
+
+        if let Some(ref secret) = self.config.signing_secret {
+            request = request.header(SIGNATURE_HEADER, sign_body(secret, &body));
+        }
+
+        let mut request = request.body(body);
 
         if let Some(ref api_key) = self.config.api_key {
             request = request.header("Authorization", format!("Bearer {}", api_key));
@@ -98,10 +346,587 @@ impl ApiClient {
         Ok(result)
     }
 
+    /// Submits a session summary to the API.
+    ///
+    /// Signed like crash reports when a signing secret is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::ApiRequest` if the request fails or the server
+    /// returns an error status.
+    #[instrument(skip(self, report), fields(game_id = %report.game_id))]
+    pub async fn submit_session(&self, report: &SessionReport) -> Result<()> {
+        let url = format!(
+            "{}{}",
+            self.config.url.trim_end_matches('/'),
+            self.config.sessions_path
+        );
+        debug!("Submitting session summary to {}", url);
+
+        let body = serde_json::to_vec(report)?;
+        let mut request = self
+            .client
+            .post(&url)
+            .header(CONTENT_TYPE, "application/json");
+
+        if let Some(ref secret) = self.config.signing_secret {
+            request = request.header(SIGNATURE_HEADER, sign_body(secret, &body));
+        }
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| CtdError::ApiRequest(format!("Cannot reach {}: {}", url, e)))?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Checks that the server is reachable and accepts the configured API key.
+    ///
+    /// Requests `/health`. Servers without a health endpoint (404) are probed
+    /// with `HEAD` on the crashes path instead, where 405 still counts as reachable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::ApiRequest` if the server cannot be reached, rejects
+    /// the API key, or returns an error status.
+    #[instrument(skip(self))]
+    pub async fn ping(&self) -> Result<()> {
+        let mut status = self.request_status(Method::GET, HEALTH_PATH).await?;
+        if status == StatusCode::NOT_FOUND {
+            debug!("No health endpoint, probing crashes path");
+            status = self
+                .request_status(Method::HEAD, &self.config.crashes_path)
+                .await?;
+        }
+
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(CtdError::ApiRequest(format!(
+                "Server rejected the API key (status {})",
+                status
+            ))),
+            StatusCode::METHOD_NOT_ALLOWED => Ok(()),
+            s if s.is_success() => Ok(()),
+            s => Err(CtdError::ApiRequest(format!(
+                "Server returned status {}",
+                s
+            ))),
+        }
+    }
+
+    /// Fetches the schema version and features the server supports.
+    ///
+    /// Servers without the capabilities endpoint (404) are treated as
+    /// [`ServerCapabilities::legacy`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::ApiRequest` if the request fails or the response
+    /// cannot be parsed.
+    #[instrument(skip(self))]
+    pub async fn capabilities(&self) -> Result<ServerCapabilities> {
+        let response = self.send(Method::GET, CAPABILITIES_PATH).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            debug!("No capabilities endpoint, assuming a legacy server");
+            return Ok(ServerCapabilities::legacy());
+        }
+        if !response.status().is_success() {
+            return Err(CtdError::ApiRequest(format!(
+                "Server returned status {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| CtdError::ApiRequest(e.to_string()))
+    }
+
+    /// Asks the server for the latest CTD release.
+    ///
+    /// Returns it only if it is newer than [`CLIENT_VERSION`]. Servers that
+    /// don't publish releases (404) answer `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::ApiRequest` if the request fails or the response
+    /// cannot be parsed.
+    #[instrument(skip(self))]
+    pub async fn check_latest_version(&self) -> Result<Option<LatestVersion>> {
+        let response = self.send(Method::GET, LATEST_VERSION_PATH).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let latest: LatestVersion = check_status(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| CtdError::ApiRequest(e.to_string()))?;
+
+        Ok(is_newer(&latest.version, CLIENT_VERSION).then_some(latest))
+    }
+
+    /// Looks up the known fix for a crash hash.
+    ///
+    /// Returns `None` if the server has no advice for this signature or
+    /// predates the known-issues endpoint (both answer 404).
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::ApiRequest` if the request fails or the response
+    /// cannot be parsed.
+    #[instrument(skip(self))]
+    pub async fn get_known_issue(&self, crash_hash: &str) -> Result<Option<KnownIssue>> {
+        let path = format!("{}/{}", KNOWN_ISSUES_PATH, crash_hash);
+        let response = self.send(Method::GET, &path).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(CtdError::ApiRequest(format!(
+                "Server returned status {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| CtdError::ApiRequest(e.to_string()))
+    }
+
+    /// Looks up the known fix for a submitted report, if any.
+    ///
+    /// Uses the report's `crash_hash`, or computes it the way the server
+    /// does. Lookup failures are logged and treated as no match, since
+    /// the report has already been submitted.
+    pub async fn known_issue_for(&self, report: &CreateCrashReport) -> Option<KnownIssue> {
+        // Sentry has no known-issue database
+        if self.sentry.is_some() {
+            return None;
+        }
+
+        let crash_hash = report
+            .crash_hash
+            .clone()
+            .unwrap_or_else(|| compute_crash_hash(&report.stack_trace));
+
+        match self.get_known_issue(&crash_hash).await {
+            Ok(issue) => issue,
+            Err(e) => {
+                debug!("Known-issue lookup failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Lists reports submitted with the configured API key, newest first.
+    ///
+    /// Pages start at 1 and hold up to [`REPORTS_PAGE_SIZE`] reports.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if no API key is configured, or
+    /// `CtdError::ApiRequest` if the request fails or the key is rejected.
+    #[instrument(skip(self))]
+    pub async fn list_my_reports(&self, page: u32) -> Result<ReportPage> {
+        self.require_api_key()?;
+        let path = format!(
+            "{}?page={}&perPage={}",
+            self.config.crashes_path,
+            page.max(1),
+            REPORTS_PAGE_SIZE
+        );
+        let response = self.send(Method::GET, &path).await?;
+
+        check_status(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| CtdError::ApiRequest(e.to_string()))
+    }
+
+    /// Fetches a report submitted with the configured API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if no API key is configured,
+    /// `CtdError::Validation` if the ID is malformed, or
+    /// `CtdError::ApiRequest` if the report is not found or the request fails.
+    #[instrument(skip(self))]
+    pub async fn get_report(&self, id: &str) -> Result<StoredReport> {
+        self.require_api_key()?;
+        let response = self.send(Method::GET, &self.report_path(id)?).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(CtdError::ApiRequest(format!("Report {} not found", id)));
+        }
+
+        check_status(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| CtdError::ApiRequest(e.to_string()))
+    }
+
+    /// Permanently deletes a report submitted with the configured API key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if no API key is configured,
+    /// `CtdError::Validation` if the ID is malformed, or
+    /// `CtdError::ApiRequest` if the report is not found or the request fails.
+    #[instrument(skip(self))]
+    pub async fn delete_report(&self, id: &str) -> Result<()> {
+        self.require_api_key()?;
+        let response = self.send(Method::DELETE, &self.report_path(id)?).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(CtdError::ApiRequest(format!("Report {} not found", id)));
+        }
+
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Appends a note to a report submitted with the configured API key.
+    ///
+    /// Notes are added after any existing ones, so users can explain what
+    /// they were doing once they know.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if no API key is configured,
+    /// `CtdError::Validation` if the ID or text is invalid, or
+    /// `CtdError::ApiRequest` if the report is not found or the request fails.
+    #[instrument(skip(self, text))]
+    pub async fn append_note(&self, report_id: &str, text: &str) -> Result<()> {
+        self.require_api_key()?;
+        self.post_note(report_id, None, text).await
+    }
+
+    /// Appends a note to a report using the share token returned on submission.
+    ///
+    /// Works without an API key, for plugins that only kept the submission
+    /// response.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Validation` if the ID, token, or text is invalid, or
+    /// `CtdError::ApiRequest` if the report is not found or the request fails.
+    #[instrument(skip(self, share_token, text))]
+    pub async fn append_note_with_token(
+        &self,
+        report_id: &str,
+        share_token: &str,
+        text: &str,
+    ) -> Result<()> {
+        self.post_note(report_id, Some(share_token), text).await
+    }
+
+    /// Posts a note to `<crashes path>/{id}/notes`.
+    async fn post_note(
+        &self,
+        report_id: &str,
+        share_token: Option<&str>,
+        text: &str,
+    ) -> Result<()> {
+        let text = text.trim();
+        if text.is_empty() || text.len() > MAX_NOTES_LEN {
+            return Err(CtdError::Validation(format!(
+                "Note must be 1-{} characters",
+                MAX_NOTES_LEN
+            )));
+        }
+
+        let mut path = format!("{}/notes", self.report_path(report_id)?);
+        if let Some(token) = share_token {
+            if !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(CtdError::Validation("Invalid share token".into()));
+            }
+            path = format!("{}?token={}", path, token);
+        }
+
+        let url = format!("{}{}", self.config.url.trim_end_matches('/'), path);
+        let mut request = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "text": text }));
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| CtdError::ApiRequest(format!("Cannot reach {}: {}", url, e)))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(CtdError::ApiRequest(format!(
+                "Report {} not found",
+                report_id
+            )));
+        }
+
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Fails unless an API key is configured; reports are scoped to it.
+    fn require_api_key(&self) -> Result<()> {
+        match self.config.api_key {
+            Some(_) => Ok(()),
+            None => Err(CtdError::Config(
+                "api_key is required to manage reports".into(),
+            )),
+        }
+    }
+
+    /// Returns the API path for a report, rejecting IDs that would change it.
+    fn report_path(&self, id: &str) -> Result<String> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(CtdError::Validation(format!("Invalid report ID: {:?}", id)));
+        }
+        Ok(format!("{}/{}", self.config.crashes_path, id))
+    }
+
+    /// Sends a body-less request to an API path and returns the status.
+    async fn request_status(&self, method: Method, path: &str) -> Result<StatusCode> {
+        Ok(self.send(method, path).await?.status())
+    }
+
+    /// Sends a body-less, authenticated request to an API path.
+    async fn send(&self, method: Method, path: &str) -> Result<reqwest::Response> {
+        let url = format!("{}{}", self.config.url.trim_end_matches('/'), path);
+        let mut request = self.client.request(method, &url);
+
+        if let Some(ref api_key) = self.config.api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+
+        request
+            .send()
+            .await
+            .map_err(|e| CtdError::ApiRequest(format!("Cannot reach {}: {}", url, e)))
+    }
+
     /// Returns the base URL of the API.
     pub fn base_url(&self) -> &str {
         &self.config.url
     }
+
+    /// Returns the link that opens a submitted report with its share token.
+    pub fn share_url(&self, response: &CrashReportResponse) -> String {
+        share_url(&self.config, response)
+    }
+}
+
+/// Returns the link that opens a report with its share token on the API in
+/// `config` (`GET {crashes_path}/{id}?token=...`).
+pub fn share_url(config: &ApiConfig, response: &CrashReportResponse) -> String {
+    format!(
+        "{}{}/{}?token={}",
+        config.url.trim_end_matches('/'),
+        config.crashes_path,
+        response.id,
+        response.share_token
+    )
+}
+
+/// Passes through a successful response, or describes the error status.
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        return Err(CtdError::ApiRequest(format!(
+            "Server rejected the API key (status {})",
+            status
+        )));
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Err(CtdError::ApiRequest(format!(
+        "Server returned status {}: {}",
+        status, body
+    )))
+}
+
+/// Reads every certificate from a PEM bundle.
+fn load_certificates(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    let pem = std::fs::read(path).map_err(|e| {
+        CtdError::Config(format!(
+            "Failed to read extra_ca_cert_path {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+        CtdError::Config(format!("Invalid certificate in {}: {}", path.display(), e))
+    })?;
+    if certs.is_empty() {
+        return Err(CtdError::Config(format!(
+            "No certificates found in {}",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
+/// Computes the `X-CTD-Signature` value for a request body.
+///
+/// The signature is the lowercase hex HMAC-SHA256 of the body, keyed with
+/// the shared `signing_secret`.
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Pings the configured server on a background thread, once per process.
+///
+/// Called from [`crate::startup::init`] so that users learn about an
+/// unreachable server or a bad API key before a crash happens.
+/// On success the server's capabilities are cached for
+/// [`negotiated_schema_version`]. Never blocks the caller.
+pub fn spawn_ping_check() {
+    PING_CHECK.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("ctd-ping".to_string())
+            .spawn(|| {
+                let client = match ApiClient::from_config() {
+                    Ok(client) => client,
+                    Err(e) => {
+                        warn!("CTD cannot create an API client: {}", e);
+                        return;
+                    }
+                };
+
+                let rt = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        warn!(
+                            "CTD cannot start a runtime for the connectivity check: {}",
+                            e
+                        );
+                        return;
+                    }
+                };
+
+                if let Err(e) = rt.block_on(client.ping()) {
+                    warn!(
+                        "CTD cannot reach the server at {} — reports will be queued: {}",
+                        client.base_url(),
+                        e
+                    );
+                    return;
+                }
+
+                match rt.block_on(client.capabilities()) {
+                    Ok(capabilities) => {
+                        info!(
+                            "CTD server reachable at {} (schema v{})",
+                            client.base_url(),
+                            capabilities.max_schema_version
+                        );
+                        let _ = SERVER_CAPABILITIES.set(capabilities);
+                    }
+                    Err(e) => warn!("CTD server reachable but capabilities unavailable: {}", e),
+                }
+
+                if client.config.check_updates && update_check_due(&update_check_path()) {
+                    match rt.block_on(client.check_latest_version()) {
+                        Ok(Some(latest)) => info!("{}", latest.notice()),
+                        Ok(None) => debug!("CTD {} is up to date", CLIENT_VERSION),
+                        Err(e) => debug!("Update check failed: {}", e),
+                    }
+                }
+            });
+
+        if let Err(e) = spawned {
+            warn!("Failed to start CTD connectivity check: {}", e);
+        }
+    });
+}
+
+/// Returns the file whose age times the daily update check
+/// (`<local data dir>/ctd/update-check`).
+fn update_check_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ctd")
+        .join("update-check")
+}
+
+/// Returns true if the last update check is a day old, and marks one as
+/// done now.
+fn update_check_due(path: &Path) -> bool {
+    let checked_recently = fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < UPDATE_CHECK_INTERVAL);
+    if checked_recently {
+        return false;
+    }
+
+    // If the mark can't be written, the check runs on every launch instead
+    let _ = path.parent().map_or(Ok(()), fs::create_dir_all);
+    let _ = fs::write(path, CLIENT_VERSION);
+    true
+}
+
+/// Returns true if dotted version `candidate` is newer than `current`.
+///
+/// A leading `v` and any pre-release or build suffix are ignored, and
+/// missing components count as 0.
+pub(crate) fn is_newer(candidate: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+
+    let (candidate, current) = (parts(candidate), parts(current));
+    let len = candidate.len().max(current.len());
+    let padded =
+        |v: &[u64]| -> Vec<u64> { (0..len).map(|i| v.get(i).copied().unwrap_or(0)).collect() };
+    padded(&candidate) > padded(&current)
+}
+
+/// Returns the capabilities fetched by [`spawn_ping_check`], if it has finished.
+pub fn cached_capabilities() -> Option<&'static ServerCapabilities> {
+    SERVER_CAPABILITIES.get()
+}
+
+/// Returns the schema version reports should be built with.
+///
+/// Uses the cached server capabilities, or the current version if the
+/// server has not been contacted yet.
+pub fn negotiated_schema_version() -> u32 {
+    cached_capabilities().map_or(CURRENT_SCHEMA_VERSION, ServerCapabilities::schema_version)
 }
 
 #[cfg(test)]
@@ -121,6 +946,22 @@ mod tests {
         assert_eq!(client.base_url(), DEFAULT_API_URL);
     }
 
+    #[test]
+    fn share_url_carries_token() {
+        let config = ApiConfig {
+            url: "https://ctd.example.com/".to_string(),
+            ..ApiConfig::default()
+        };
+        let response = CrashReportResponse {
+            id: "01HX".into(),
+            share_token: "abc123".into(),
+        };
+        assert_eq!(
+            share_url(&config, &response),
+            "https://ctd.example.com/crashes/01HX?token=abc123"
+        );
+    }
+
     #[test]
     fn client_with_custom_config() {
         let config = ApiConfig {
@@ -128,8 +969,152 @@ mod tests {
             crashes_path: "/api/v2/crashes".to_string(),
             api_key: Some("test-key".to_string()),
             timeout_secs: 60,
+            ..ApiConfig::default()
         };
         let client = ApiClient::new(config).unwrap();
         assert_eq!(client.base_url(), "https://custom.example.com");
     }
+
+    #[test]
+    fn client_with_proxy() {
+        let config = ApiConfig {
+            proxy_url: Some("http://proxy.example.com:8080".to_string()),
+            no_proxy: vec!["localhost".to_string()],
+            ..ApiConfig::default()
+        };
+        assert!(ApiClient::new(config).is_ok());
+    }
+
+    #[test]
+    fn client_rejects_missing_ca_bundle() {
+        let config = ApiConfig {
+            extra_ca_cert_path: Some("/nonexistent/ctd/root.pem".into()),
+            ..ApiConfig::default()
+        };
+        let err = ApiClient::new(config).unwrap_err();
+        assert!(matches!(err, CtdError::Config(_)));
+    }
+
+    #[test]
+    fn sign_body_matches_rfc4231() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_body("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn parse_capabilities() {
+        let json = r#"{"maxSchemaVersion":3,"features":["signing"]}"#;
+        let caps: ServerCapabilities = serde_json::from_str(json).unwrap();
+
+        assert_eq!(caps.max_schema_version, 3);
+        assert!(caps.supports("signing"));
+        assert!(!caps.supports("breadcrumbs"));
+        assert_eq!(caps.schema_version(), CURRENT_SCHEMA_VERSION);
+        assert!(caps.known_bad_modules.is_empty());
+
+        let json = r#"{"maxSchemaVersion":2,"knownBadModules":[{"module":"example.dll","reason":"Crashes on load."}]}"#;
+        let caps: ServerCapabilities = serde_json::from_str(json).unwrap();
+        assert_eq!(caps.known_bad_modules[0].module, "example.dll");
+    }
+
+    #[test]
+    fn legacy_capabilities_cap_schema_at_v1() {
+        assert_eq!(ServerCapabilities::legacy().schema_version(), 1);
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("0.3.1", "0.1.3"));
+        assert!(is_newer("0.10.0", "0.9.9"));
+        assert!(is_newer("v1.0", "0.9.12"));
+        assert!(is_newer("0.1.3.1", "0.1.3"));
+        assert!(!is_newer("0.1.3", "0.1.3"));
+        assert!(!is_newer("0.1.3-beta.1", "0.1.3"));
+        assert!(!is_newer("0.1.2", "0.1.3"));
+    }
+
+    #[test]
+    fn update_check_runs_once_a_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctd").join("update-check");
+
+        assert!(update_check_due(&path));
+        assert!(!update_check_due(&path));
+    }
+
+    #[test]
+    fn latest_version_notice() {
+        let latest: LatestVersion = serde_json::from_str(
+            r#"{"version":"0.3.1","url":"https://github.com/ezmode-games/ctd/releases"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            latest.notice(),
+            format!(
+                "CTD 0.3.1 is available (this is {}): https://github.com/ezmode-games/ctd/releases",
+                CLIENT_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn parse_known_issue() {
+        let json = r#"{"crashHash":"0123456789abcdef","patternName":null,"knownFix":"Update OldMod to 1.4","occurrenceCount":12,"suspectedMods":["OldMod.esp"]}"#;
+        let issue: KnownIssue = serde_json::from_str(json).unwrap();
+
+        assert_eq!(issue.occurrence_count, 12);
+        assert_eq!(
+            issue.advice(),
+            "Known issue: Update OldMod to 1.4 (suspected: OldMod.esp)"
+        );
+    }
+
+    #[tokio::test]
+    async fn report_management_requires_api_key() {
+        let client = ApiClient::with_defaults().unwrap();
+        let err = client.list_my_reports(1).await.unwrap_err();
+        assert!(matches!(err, CtdError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn report_ids_cannot_escape_crashes_path() {
+        let config = ApiConfig {
+            api_key: Some("test-key".to_string()),
+            ..ApiConfig::default()
+        };
+        let client = ApiClient::new(config).unwrap();
+        let err = client.delete_report("../api-keys/01ABC").await.unwrap_err();
+        assert!(matches!(err, CtdError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn append_note_rejects_empty_text() {
+        let client = ApiClient::with_defaults().unwrap();
+        let err = client
+            .append_note_with_token("01ABC", "token", "   ")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CtdError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn ping_fails_when_server_is_down() {
+        // Bind and drop a listener to get a port nothing is listening on
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = ApiConfig {
+            url: format!("http://127.0.0.1:{}", port),
+            timeout_secs: 5,
+            ..ApiConfig::default()
+        };
+
+        let err = ApiClient::new(config).unwrap().ping().await.unwrap_err();
+        assert!(err.to_string().contains("Cannot reach"));
+    }
 }
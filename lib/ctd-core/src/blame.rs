@@ -0,0 +1,235 @@
+//! Attribution of a crash's faulting module to the mod that shipped it.
+//!
+//! "Which mod crashed my game?" is the first question every user asks, and
+//! the faulting module alone rarely answers it: `po3_Tweaks.dll` means
+//! nothing to most players, and under Mod Organizer 2 the DLL appears to
+//! live in `Data/` rather than in the folder of the mod that installed it.
+//!
+//! [`suspected_mod`] tries, in order:
+//! 1. the installed mod whose folder contains the module,
+//! 2. the scanned mod whose file hash matches the module,
+//! 3. the installed mod that ships a file with the module's name and hash
+//!    (how MO2 mods are found through the virtual `Data/`),
+//! 4. the scanned mod with the module's file name.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::file_hash::compute_file_hash;
+use crate::load_order::{ModEntry, ModList, format_mod_name};
+use crate::mo2::{Mo2Instance, Mo2Mod};
+
+/// How deep to search a mod folder for the faulting module.
+///
+/// Deep enough for `SKSE/Plugins/x.dll` and `red4ext/plugins/X/x.dll`.
+const MAX_SEARCH_DEPTH: usize = 4;
+
+/// A mod installed in its own folder (an MO2 mod, a UE4SS mod, ...).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledMod {
+    /// Name to report (e.g., "SkyUI 5.2SE (Nexus 12604)").
+    pub name: String,
+    /// The mod's folder.
+    pub dir: PathBuf,
+}
+
+impl InstalledMod {
+    /// Creates an installed mod.
+    pub fn new(name: impl Into<String>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            dir: dir.into(),
+        }
+    }
+}
+
+impl From<&Mo2Mod> for InstalledMod {
+    fn from(m: &Mo2Mod) -> Self {
+        Self::new(
+            format_mod_name(&m.name, m.version.as_deref(), m.nexus_id),
+            &m.dir,
+        )
+    }
+}
+
+/// Returns the mods installed through a detected mod manager (MO2 today).
+pub fn installed_mods() -> Vec<InstalledMod> {
+    Mo2Instance::detect()
+        .map(|mo2| mo2.active_mods().iter().map(InstalledMod::from).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the mod that most likely shipped `module`.
+///
+/// `module` is the faulting module's path, or just its file name, in which
+/// case the module loaded into this process under that name is used.
+/// Returns `None` if no mod can be tied to it, as for the game itself.
+pub fn suspected_mod(module: &Path, mods: &ModList, installed: &[InstalledMod]) -> Option<String> {
+    let file_name = module.file_name()?.to_str()?;
+    let path = locate(module);
+
+    if let Some(path) = &path
+        && let Some(owner) = installed.iter().find(|m| is_within(path, &m.dir))
+    {
+        return Some(owner.name.clone());
+    }
+
+    if let Some((hash, _)) = path.as_deref().and_then(|p| compute_file_hash(p).ok()) {
+        if let Some(entry) = mods.iter().find(|entry| entry.file_hash == hash) {
+            return Some(entry.display_name());
+        }
+        if let Some(owner) = installed
+            .iter()
+            .find(|m| ships(&m.dir, file_name, &hash, MAX_SEARCH_DEPTH))
+        {
+            return Some(owner.name.clone());
+        }
+    }
+
+    mods.iter()
+        .find(|entry| entry.name.eq_ignore_ascii_case(file_name))
+        .map(ModEntry::display_name)
+}
+
+/// Returns the module's full path if it can be found.
+fn locate(module: &Path) -> Option<PathBuf> {
+    if module.is_absolute() {
+        return module.is_file().then(|| module.to_path_buf());
+    }
+    loaded_module_path(module.to_str()?)
+}
+
+/// Returns true if `path` is inside `dir`, ignoring case and separator style.
+fn is_within(path: &Path, dir: &Path) -> bool {
+    let normalize = |p: &Path| p.to_string_lossy().replace('\\', "/").to_lowercase();
+    let dir = normalize(dir);
+    let dir = dir.trim_end_matches('/');
+    normalize(path)
+        .strip_prefix(dir)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Returns true if `dir` holds a file named `file_name` with hash `hash`.
+fn ships(dir: &Path, file_name: &str, hash: &str, depth: usize) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            depth > 0 && ships(&path, file_name, hash, depth - 1)
+        } else {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(file_name))
+                && compute_file_hash(&path).is_ok_and(|(h, _)| h == hash)
+        }
+    })
+}
+
+/// Returns the path of the module loaded into this process as `name`.
+#[cfg(windows)]
+fn loaded_module_path(name: &str) -> Option<PathBuf> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::Win32::System::LibraryLoader::{GetModuleFileNameW, GetModuleHandleW};
+    use windows::core::PCWSTR;
+
+    let wide_name: Vec<u16> = OsStr::new(name)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    unsafe {
+        let module = GetModuleHandleW(PCWSTR(wide_name.as_ptr())).ok()?;
+        let mut path = [0u16; 1024];
+        let len = GetModuleFileNameW(module, &mut path) as usize;
+        (len > 0).then(|| PathBuf::from(String::from_utf16_lossy(&path[..len])))
+    }
+}
+
+/// Stub implementation for non-Windows platforms.
+///
+/// Always returns `None`; callers must pass a full path instead.
+#[cfg(not(windows))]
+fn loaded_module_path(_name: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blames_mod_folder_containing_module() {
+        let dir = tempfile::tempdir().unwrap();
+        let mod_dir = dir.path().join("mods").join("Tweaks");
+        fs::create_dir_all(mod_dir.join("SKSE/Plugins")).unwrap();
+        let dll = mod_dir.join("SKSE/Plugins/po3_Tweaks.dll");
+        fs::write(&dll, b"tweaks").unwrap();
+
+        let installed = vec![
+            InstalledMod::new("Tweak", dir.path().join("mods").join("Tweak")),
+            InstalledMod::new("Tweaks 1.9 (Nexus 51073)", &mod_dir),
+        ];
+        assert_eq!(
+            suspected_mod(&dll, &ModList::new(), &installed).as_deref(),
+            Some("Tweaks 1.9 (Nexus 51073)")
+        );
+    }
+
+    #[test]
+    fn blames_mod_shipping_same_file_through_vfs() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // MO2 shows the DLL under Data/, but the real file is in the mod folder
+        let data_dll = dir.path().join("Data/SKSE/Plugins/EngineFixes.dll");
+        fs::create_dir_all(data_dll.parent().unwrap()).unwrap();
+        fs::write(&data_dll, b"engine fixes").unwrap();
+
+        let mod_dir = dir.path().join("mods/SSE Engine Fixes");
+        fs::create_dir_all(mod_dir.join("SKSE/Plugins")).unwrap();
+        fs::write(
+            mod_dir.join("SKSE/Plugins/EngineFixes.dll"),
+            b"engine fixes",
+        )
+        .unwrap();
+
+        let other_dir = dir.path().join("mods/Old Engine Fixes");
+        fs::create_dir_all(other_dir.join("SKSE/Plugins")).unwrap();
+        fs::write(other_dir.join("SKSE/Plugins/EngineFixes.dll"), b"old build").unwrap();
+
+        let installed = vec![
+            InstalledMod::new("Old Engine Fixes", &other_dir),
+            InstalledMod::new("SSE Engine Fixes", &mod_dir),
+        ];
+        assert_eq!(
+            suspected_mod(&data_dll, &ModList::new(), &installed).as_deref(),
+            Some("SSE Engine Fixes")
+        );
+    }
+
+    #[test]
+    fn blames_scanned_mod_by_hash_then_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let dll = dir.path().join("ArchiveXL.dll");
+        fs::write(&dll, b"archivexl").unwrap();
+        let (hash, size) = compute_file_hash(&dll).unwrap();
+
+        let mods = ModList::from_entries(vec![
+            ModEntry::new("[RED4ext] ArchiveXL.dll", hash, size).with_version("1.14.0"),
+            ModEntry::new("TweakXL.dll", "0000000000000000", 0),
+        ]);
+        assert_eq!(
+            suspected_mod(&dll, &mods, &[]).as_deref(),
+            Some("[RED4ext] ArchiveXL.dll")
+        );
+        assert_eq!(
+            suspected_mod(Path::new("tweakxl.dll"), &mods, &[]).as_deref(),
+            Some("TweakXL.dll")
+        );
+        assert_eq!(suspected_mod(Path::new("SkyrimSE.exe"), &mods, &[]), None);
+    }
+}
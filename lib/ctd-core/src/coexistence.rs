@@ -0,0 +1,346 @@
+//! Coexistence with other crash loggers (Crash Logger SSE/VR, Buffout 4,
+//! Trainwreck).
+//!
+//! Those loggers catch the same crash CTD does. When both walk the stack of
+//! one crashing thread, DbgHelp state set up by one handler disturbs the
+//! other, and users end up with two worse traces instead of one good one.
+//! `[coexistence] mode` in `ctd.toml` picks how CTD steps aside:
+//!
+//! - `passive` (default): the game plugin registers its exception handler
+//!   last and submits the crash without walking the stack.
+//! - `import-log`: the game plugin does not handle crashes at all. On the
+//!   next launch it imports the log the other logger wrote and submits that.
+//! - `ignore`: CTD captures crashes as if no other logger were installed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::api_client::ApiClient;
+use crate::spool::Spool;
+use crate::{CtdError, Result, history, import};
+
+/// Another crash logger installed as a script extender plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashLogger {
+    /// Crash Logger SSE/AE/VR (Skyrim).
+    CrashLogger,
+    /// Buffout 4 (Fallout 4).
+    Buffout4,
+    /// Trainwreck (Skyrim).
+    Trainwreck,
+}
+
+impl CrashLogger {
+    const ALL: [Self; 3] = [Self::CrashLogger, Self::Buffout4, Self::Trainwreck];
+
+    /// DLL file name in the script extender plugin directory.
+    pub fn dll_name(self) -> &'static str {
+        match self {
+            Self::CrashLogger => "CrashLogger.dll",
+            Self::Buffout4 => "Buffout4.dll",
+            Self::Trainwreck => "Trainwreck.dll",
+        }
+    }
+
+    /// Display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CrashLogger => "Crash Logger",
+            Self::Buffout4 => "Buffout 4",
+            Self::Trainwreck => "Trainwreck",
+        }
+    }
+
+    /// Returns true if [`crate::import`] can parse the logs this logger writes.
+    pub fn is_importable(self) -> bool {
+        !matches!(self, Self::Trainwreck)
+    }
+
+    /// Directory the logger writes `crash-*.log` files to, given the game's
+    /// folder under `Documents/My Games` (e.g., "Skyrim Special Edition").
+    ///
+    /// Returns `None` for loggers whose logs cannot be imported.
+    pub fn log_dir(self, my_games_folder: &str) -> Option<PathBuf> {
+        let extender = match self {
+            Self::CrashLogger => "SKSE",
+            Self::Buffout4 => "F4SE",
+            Self::Trainwreck => return None,
+        };
+        Some(
+            dirs::document_dir()?
+                .join("My Games")
+                .join(my_games_folder)
+                .join(extender),
+        )
+    }
+}
+
+impl std::fmt::Display for CrashLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How CTD behaves when another crash logger is installed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoexistenceMode {
+    /// Register the handler last and submit without walking the stack.
+    #[default]
+    #[serde(rename = "passive")]
+    Passive,
+    /// Don't handle crashes; submit the other logger's log on next launch.
+    #[serde(rename = "import-log")]
+    ImportLog,
+    /// Capture crashes as usual.
+    #[serde(rename = "ignore")]
+    Ignore,
+}
+
+/// What the game plugin does this session, from [`plan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoexistencePlan {
+    /// No other logger, or `ignore`: capture crashes as usual.
+    Standalone,
+    /// Register the handler last and skip the stack walk.
+    Passive(CrashLogger),
+    /// Leave crashes to the other logger and import its logs.
+    ImportLog(CrashLogger),
+}
+
+impl CoexistencePlan {
+    /// Returns true if the game plugin should register its exception handler.
+    pub fn handles_crashes(self) -> bool {
+        !matches!(self, Self::ImportLog(_))
+    }
+}
+
+/// Returns the crash loggers installed in a script extender plugin directory
+/// (e.g., `Data/SKSE/Plugins`).
+pub fn detect_crash_loggers(plugins_dir: &Path) -> Vec<CrashLogger> {
+    CrashLogger::ALL
+        .into_iter()
+        .filter(|logger| plugins_dir.join(logger.dll_name()).is_file())
+        .collect()
+}
+
+/// Decides how to coexist with the `installed` crash loggers.
+///
+/// `import-log` needs a logger whose logs can be imported; with only
+/// Trainwreck installed it falls back to `passive`.
+pub fn plan(mode: CoexistenceMode, installed: &[CrashLogger]) -> CoexistencePlan {
+    let Some(&first) = installed.first() else {
+        return CoexistencePlan::Standalone;
+    };
+
+    match mode {
+        CoexistenceMode::Ignore => CoexistencePlan::Standalone,
+        CoexistenceMode::Passive => CoexistencePlan::Passive(first),
+        CoexistenceMode::ImportLog => match installed.iter().find(|l| l.is_importable()) {
+            Some(&logger) => CoexistencePlan::ImportLog(logger),
+            None => CoexistencePlan::Passive(first),
+        },
+    }
+}
+
+/// Returns the default state file recording which logs were imported for a
+/// game (`<local data dir>/ctd/imported-logs/<game_id>`).
+pub fn default_state_file(game_id: &str) -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ctd")
+        .join("imported-logs")
+        .join(game_id)
+}
+
+/// Returns the `crash-*.log` files in `dir` written since the last call,
+/// oldest first, and records the newest in `state_file`.
+///
+/// The first call only records the current time, so logs from before the
+/// mode was enabled are not submitted (use `ctd submit` for those).
+///
+/// # Errors
+///
+/// Returns `CtdError::Import` if the state file cannot be written.
+pub fn take_new_crash_logs(dir: &Path, state_file: &Path) -> Result<Vec<PathBuf>> {
+    let since = fs::read_to_string(state_file)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok());
+
+    let Some(since) = since else {
+        write_state(state_file, now_millis())?;
+        return Ok(Vec::new());
+    };
+
+    let mut logs: Vec<(u64, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    name.starts_with("crash-") && name.ends_with(".log")
+                })
+                .filter_map(|entry| {
+                    let modified = entry.metadata().ok()?.modified().ok()?;
+                    Some((to_millis(modified), entry.path()))
+                })
+                .filter(|(modified, _)| *modified > since)
+                .collect()
+        })
+        .unwrap_or_default();
+    logs.sort();
+
+    if let Some((newest, _)) = logs.last() {
+        write_state(state_file, *newest)?;
+    }
+    Ok(logs.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Imports the logs `logger` wrote since the last launch and submits them on
+/// a background thread.
+///
+/// Reports that fail to submit are queued in the spool. Never blocks the caller.
+pub fn spawn_log_import(logger: CrashLogger, my_games_folder: &str, game_id: &str) {
+    let Some(dir) = logger.log_dir(my_games_folder) else {
+        return;
+    };
+    let state_file = default_state_file(game_id);
+
+    let spawned = std::thread::Builder::new()
+        .name("ctd-import".to_string())
+        .spawn(move || {
+            let logs = match take_new_crash_logs(&dir, &state_file) {
+                Ok(logs) => logs,
+                Err(e) => {
+                    warn!("Cannot track imported {} logs: {}", logger, e);
+                    return;
+                }
+            };
+            for log in logs {
+                if let Err(e) = submit_log(&log) {
+                    warn!("Failed to import {}: {}", log.display(), e);
+                }
+            }
+        });
+
+    if let Err(e) = spawned {
+        warn!("Failed to start crash log import: {}", e);
+    }
+}
+
+/// Imports one crash log and submits it, queueing it on failure.
+fn submit_log(path: &Path) -> Result<()> {
+    let report = import::import_file(path)?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CtdError::Import(format!("Failed to start runtime: {}", e)))?;
+    let result = rt.block_on(async {
+        let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
+        client.submit_crash_report(&report).await
+    });
+
+    match result {
+        Ok(response) => {
+            info!("Imported {} as report {}", path.display(), response.id);
+            history::record_submitted(&report, &response);
+            Ok(())
+        }
+        Err(e) => {
+            let queued = Spool::open_default().enqueue(&report)?;
+            warn!("Submission failed, report queued at {:?}", queued);
+            Err(e)
+        }
+    }
+}
+
+fn write_state(state_file: &Path, millis: u64) -> Result<()> {
+    if let Some(parent) = state_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| CtdError::Import(format!("Failed to create {:?}: {}", parent, e)))?;
+    }
+    fs::write(state_file, millis.to_string())
+        .map_err(|e| CtdError::Import(format!("Failed to write {:?}: {}", state_file, e)))
+}
+
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn now_millis() -> u64 {
+    to_millis(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn detects_installed_loggers() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_crash_loggers(dir.path()).is_empty());
+
+        fs::write(dir.path().join("Trainwreck.dll"), b"MZ").unwrap();
+        fs::write(dir.path().join("CrashLogger.dll"), b"MZ").unwrap();
+        assert_eq!(
+            detect_crash_loggers(dir.path()),
+            vec![CrashLogger::CrashLogger, CrashLogger::Trainwreck]
+        );
+    }
+
+    #[test]
+    fn plan_follows_mode() {
+        use CoexistenceMode::*;
+        let crash_logger = [CrashLogger::CrashLogger];
+        let trainwreck = [CrashLogger::Trainwreck];
+
+        assert_eq!(plan(Passive, &[]), CoexistencePlan::Standalone);
+        assert_eq!(plan(Ignore, &crash_logger), CoexistencePlan::Standalone);
+        assert_eq!(
+            plan(Passive, &crash_logger),
+            CoexistencePlan::Passive(CrashLogger::CrashLogger)
+        );
+        assert_eq!(
+            plan(ImportLog, &crash_logger),
+            CoexistencePlan::ImportLog(CrashLogger::CrashLogger)
+        );
+        assert_eq!(
+            plan(ImportLog, &trainwreck),
+            CoexistencePlan::Passive(CrashLogger::Trainwreck)
+        );
+        assert!(!plan(ImportLog, &crash_logger).handles_crashes());
+    }
+
+    #[test]
+    fn takes_only_logs_written_since_last_call() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = dir.path().join("state").join("skyrim-se");
+        let old = dir.path().join("crash-2024-01-01-10-00-00.log");
+        fs::write(&old, "old").unwrap();
+
+        // First call records a starting point without importing history
+        assert!(take_new_crash_logs(dir.path(), &state).unwrap().is_empty());
+
+        let new = dir.path().join("crash-2024-01-02-10-00-00.log");
+        fs::write(&new, "new").unwrap();
+        fs::write(dir.path().join("CrashLogger.log"), "not a crash").unwrap();
+        let later = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(&new)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        assert_eq!(take_new_crash_logs(dir.path(), &state).unwrap(), vec![new]);
+        assert!(take_new_crash_logs(dir.path(), &state).unwrap().is_empty());
+    }
+}
@@ -5,16 +5,26 @@
 //! 2. `./ctd.toml` (current directory)
 //! 3. `~/.config/ctd/config.toml` (user config directory)
 //!
-//! Environment variables can override config file values:
-//! - `CTD_API_URL` - Base URL for the API server
-//! - `CTD_API_KEY` - API key for authentication
+//! Every config value can be overridden by an environment variable named
+//! `CTD_<SECTION>_<KEY>` in upper case, e.g. `CTD_API_TIMEOUT_SECS` for
+//! `[api] timeout_secs` or `CTD_SUBMISSION_SAMPLE_RATE`. Top-level keys
+//! drop the section (`CTD_ALLOW_TEST_CRASH`), and `api.api_key` is
+//! `CTD_API_KEY`. See [`ENV_OVERRIDES`] for the full list.
 
 use std::env;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use tracing::{info, warn};
+
+use crate::coexistence::CoexistenceMode;
+use crate::dpapi;
+use crate::file_hash::HashAlgorithm;
+use crate::known_bad::BadModuleRule;
+use crate::symbols::PdbMatchMode;
 use crate::{CtdError, Result};
 
 /// Default base URL for local development.
@@ -23,17 +33,373 @@ pub const DEFAULT_API_URL: &str = "http://localhost:3000";
 /// Default API path for crash reports.
 pub const DEFAULT_CRASHES_PATH: &str = "/crashes";
 
+/// Default API path for session summaries.
+pub const DEFAULT_SESSIONS_PATH: &str = "/sessions";
+
 /// Default request timeout in seconds.
 pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
+/// How serious a [`ConfigIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// Reports will not be submitted correctly.
+    Error,
+    /// Reports are submitted, but probably not as intended.
+    Warning,
+}
+
+/// A problem found by [`Config::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// How serious the problem is.
+    pub severity: IssueSeverity,
+    /// Config key the problem is about (e.g., "api.url").
+    pub field: &'static str,
+    /// Human-readable description, starting with the key.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            field,
+            message: message.into(),
+        }
+    }
+
+    fn warning(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            field,
+            message: message.into(),
+        }
+    }
+
+    /// Returns true for [`IssueSeverity::Error`].
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 /// Configuration for the CTD client.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
+    /// Allow `trigger_test_crash()` to crash the game on purpose (default: false).
+    pub allow_test_crash: bool,
     /// API configuration.
     pub api: ApiConfig,
     /// Symbol resolution configuration.
     pub symbols: SymbolsConfig,
+    /// Submission sampling and rate limiting.
+    pub submission: SubmissionConfig,
+    /// Plugin log files.
+    pub logging: LoggingConfig,
+    /// Behavior when another crash logger is installed.
+    pub coexistence: CoexistenceConfig,
+    /// Exception handler ordering and what happens after a crash.
+    pub handler: HandlerConfig,
+    /// Freeze detection.
+    pub watchdog: WatchdogConfig,
+    /// Session summaries on clean exit.
+    pub session: SessionConfig,
+    /// What identifying data reports carry.
+    pub privacy: PrivacyConfig,
+    /// How mod files are fingerprinted.
+    pub fingerprint: FingerprintConfig,
+    /// Flagging of loaded DLLs known to crash games.
+    pub known_bad_modules: KnownBadModulesConfig,
+    /// The modpack reports name.
+    pub modpack: ModpackConfig,
+    /// Windows Error Reporting dumps.
+    pub dumps: DumpsConfig,
+    /// Submission to Sentry instead of the CTD API.
+    pub sentry: SentryConfig,
+    /// Notifications after each submission.
+    pub notify: NotifyConfig,
+}
+
+/// What a crash handler does once it has captured a crash.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OnCrash {
+    /// Pass the exception on to other handlers and the game (default).
+    #[default]
+    #[serde(rename = "continue-search")]
+    ContinueSearch,
+    /// Wait for the report to be submitted, then terminate the game.
+    #[serde(rename = "terminate")]
+    Terminate,
+    /// Wait for the report, tell the user in a message box, then terminate.
+    #[serde(rename = "dialog")]
+    Dialog,
+}
+
+impl OnCrash {
+    /// Returns true if the handler ends the process itself, so the report
+    /// must be submitted before the handler returns.
+    pub fn ends_process(self) -> bool {
+        self != Self::ContinueSearch
+    }
+}
+
+/// Settings for the in-game Vectored Exception Handlers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandlerConfig {
+    /// Register ahead of other vectored exception handlers (default: true).
+    ///
+    /// `[coexistence] mode = "passive"` registers last regardless.
+    pub first: bool,
+    /// What happens after a crash is captured (default: continue-search).
+    pub on_crash: OnCrash,
+    /// Count non-fatal exceptions and attach the counts to the next report
+    /// (default: false). See [`crate::exception_tally`].
+    pub handled_exceptions: bool,
+}
+
+impl Default for HandlerConfig {
+    fn default() -> Self {
+        Self {
+            first: true,
+            on_crash: OnCrash::default(),
+            handled_exceptions: false,
+        }
+    }
+}
+
+/// Settings for the freeze watchdog (see [`crate::watchdog`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// Report the game as hung when its main thread stops (default: true).
+    pub enabled: bool,
+    /// Seconds without a heartbeat before a hang is reported (default: 60).
+    pub timeout_secs: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: 60,
+        }
+    }
+}
+
+/// Settings for session summaries (see [`crate::session`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Submit a summary of each session when the game exits cleanly
+    /// (default: false).
+    pub enabled: bool,
+}
+
+/// Settings for what reports reveal about the player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Send random installation and session IDs so the backend can group
+    /// reports by player and launch (default: true). See [`crate::identity`].
+    pub anonymous_ids: bool,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            anonymous_ids: true,
+        }
+    }
+}
+
+/// Settings for mod file fingerprints (see [`crate::file_hash`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FingerprintConfig {
+    /// Hash algorithm for mod files (default: partial-sha256).
+    pub algorithm: HashAlgorithm,
+}
+
+/// Settings for flagging crash-prone DLLs (see [`crate::known_bad`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnownBadModulesConfig {
+    /// Check loaded DLLs against the known-bad list (default: true).
+    pub enabled: bool,
+    /// Extra rules, applied after the bundled and server rules.
+    pub rules: Vec<BadModuleRule>,
+}
+
+impl Default for KnownBadModulesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Settings for naming the modpack a game was installed from (see
+/// [`crate::modpack`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModpackConfig {
+    /// Look for Wabbajack and Nexus Collections markers (default: true).
+    pub detect: bool,
+    /// Modpack name to report instead of the detected one, for list authors.
+    pub name: Option<String>,
+    /// Version reported with `name`.
+    pub version: Option<String>,
+}
+
+impl Default for ModpackConfig {
+    fn default() -> Self {
+        Self {
+            detect: true,
+            name: None,
+            version: None,
+        }
+    }
+}
+
+/// Where Windows Error Reporting writes minidumps (see `ctd_core::wer`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DumpsConfig {
+    /// Dump folder for `LocalDumps` (default: `<local data dir>/ctd/dumps`).
+    pub directory: Option<PathBuf>,
+}
+
+impl DumpsConfig {
+    /// Returns the configured directory, or `<local data dir>/ctd/dumps`.
+    pub fn dump_dir(&self) -> PathBuf {
+        self.directory.clone().unwrap_or_else(default_dump_dir)
+    }
+}
+
+/// Returns the default dump folder, `<local data dir>/ctd/dumps`.
+pub fn default_dump_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ctd")
+        .join("dumps")
+}
+
+/// Sending reports to a Sentry project instead of the CTD API (see [`crate::sentry`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SentryConfig {
+    /// Project DSN; when set, reports go to Sentry instead of `api.url`.
+    pub dsn: Option<String>,
+    /// Environment events are tagged with (e.g., "production").
+    pub environment: Option<String>,
+}
+
+/// Where submitted reports are announced (see [`crate::notify`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Discord channel webhook.
+    pub discord: DiscordConfig,
+}
+
+/// A Discord channel that gets a message for each submitted report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    /// Webhook URL from the channel's Integrations settings.
+    pub webhook_url: Option<String>,
+}
+
+/// How the game plugins share crashes with other crash loggers.
+///
+/// See [`crate::coexistence`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoexistenceConfig {
+    /// What to do when Crash Logger, Buffout 4, or Trainwreck is installed
+    /// (default: passive).
+    pub mode: CoexistenceMode,
+}
+
+/// Settings for the plugins' rolling log files.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Minimum level written: off, error, warn, info (default), debug, or trace.
+    pub level: String,
+    /// Directory for log files (default: `Documents/My Games/CTD/logs`).
+    pub directory: Option<PathBuf>,
+    /// Rotated files kept per game, besides the current one (default: 5).
+    pub max_files: usize,
+    /// Size in megabytes at which the current file is rotated (default: 10).
+    pub max_size_mb: u64,
+    /// Also write each crash as a Crash Logger-style `crash-*.log` in the
+    /// log directory, for tools that read those logs (default: false).
+    pub crash_log: bool,
+    /// Also write an Application Event Log entry for each captured crash
+    /// or hang, on Windows (default: false).
+    pub event_log: bool,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            directory: None,
+            max_files: 5,
+            max_size_mb: 10,
+            crash_log: false,
+            event_log: false,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Returns the configured directory, or `Documents/My Games/CTD/logs`.
+    ///
+    /// Falls back to `<local data dir>/ctd/logs` when there is no Documents folder.
+    pub fn log_dir(&self) -> PathBuf {
+        if let Some(ref dir) = self.directory {
+            return dir.clone();
+        }
+
+        match dirs::document_dir() {
+            Some(docs) => docs.join("My Games").join("CTD").join("logs"),
+            None => dirs::data_local_dir()
+                .unwrap_or_else(env::temp_dir)
+                .join("ctd")
+                .join("logs"),
+        }
+    }
+}
+
+/// Limits on how many crash reports the plugins submit.
+///
+/// Applies to reports from in-game crash handlers, not to `ctd` commands.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubmissionConfig {
+    /// Fraction of crashes to report, from 0.0 (none) to 1.0 (all; default).
+    pub sample_rate: f64,
+    /// Maximum reports submitted in any rolling hour (default: unlimited).
+    pub max_reports_per_hour: Option<u32>,
+}
+
+impl Default for SubmissionConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 1.0,
+            max_reports_per_hour: None,
+        }
+    }
 }
 
 /// Configuration for PDB symbol resolution.
@@ -46,6 +412,18 @@ pub struct SymbolsConfig {
     pub cache_dir: Option<PathBuf>,
     /// Additional directories to search for PDB files.
     pub search_dirs: Vec<PathBuf>,
+    /// Symbolize frames in-process with DbgHelp at crash time (default: false).
+    ///
+    /// When a PDB sits next to a DLL, the submitted stack trace already
+    /// contains function names instead of bare offsets. Off by default, since
+    /// DbgHelp loads PDBs inside the crashed process.
+    pub dbghelp: bool,
+    /// How PDBs are validated against modules before loading (default: strict).
+    ///
+    /// `strict` never loads a PDB whose GUID and age differ from the module's
+    /// CodeView record; `best-effort` falls back to a filename match. A PDB
+    /// that can't be checked is loaded with a warning in either mode.
+    pub match_mode: PdbMatchMode,
 }
 
 impl Default for SymbolsConfig {
@@ -54,10 +432,32 @@ impl Default for SymbolsConfig {
             enabled: true,
             cache_dir: None,
             search_dirs: Vec::new(),
+            dbghelp: false,
+            match_mode: PdbMatchMode::default(),
         }
     }
 }
 
+impl SymbolsConfig {
+    /// Returns true if crash handlers should symbolize frames with DbgHelp.
+    pub fn runtime_symbolization(&self) -> bool {
+        self.enabled && self.dbghelp
+    }
+
+    /// Builds a DbgHelp symbol search path from the configured directories.
+    ///
+    /// Directories are joined with `;` as expected by `SymInitialize`.
+    /// Crash handlers append the directories of loaded modules themselves.
+    pub fn dbghelp_search_path(&self) -> String {
+        self.search_dirs
+            .iter()
+            .chain(self.cache_dir.iter())
+            .map(|dir| dir.display().to_string())
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+}
+
 /// API-specific configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -66,10 +466,30 @@ pub struct ApiConfig {
     pub url: String,
     /// API path for crash reports endpoint.
     pub crashes_path: String,
+    /// API path for session summaries endpoint.
+    pub sessions_path: String,
     /// Optional API key for authentication.
     pub api_key: Option<String>,
+    /// API key encrypted with Windows DPAPI, as written by `ctd config set-key`.
+    ///
+    /// Decrypted into `api_key` on load when `api_key` is not set. Only the
+    /// Windows user who encrypted it can decrypt it.
+    pub api_key_encrypted: Option<String>,
     /// Request timeout in seconds.
     pub timeout_secs: u64,
+    /// Shared secret for signing submissions with `X-CTD-Signature`.
+    ///
+    /// Self-hosted servers can use it to reject forged reports.
+    pub signing_secret: Option<String>,
+    /// HTTP(S) proxy for all API requests (e.g., "http://proxy:8080").
+    pub proxy_url: Option<String>,
+    /// Hosts that bypass the proxy (e.g., "localhost", ".corp.example.com").
+    pub no_proxy: Vec<String>,
+    /// PEM file with extra root certificates to trust, for TLS-intercepting gateways.
+    pub extra_ca_cert_path: Option<PathBuf>,
+    /// Ask the server once a day whether a newer CTD is out, and log it
+    /// (default: true).
+    pub check_updates: bool,
 }
 
 impl Default for ApiConfig {
@@ -77,8 +497,15 @@ impl Default for ApiConfig {
         Self {
             url: DEFAULT_API_URL.to_string(),
             crashes_path: DEFAULT_CRASHES_PATH.to_string(),
+            sessions_path: DEFAULT_SESSIONS_PATH.to_string(),
             api_key: None,
+            api_key_encrypted: None,
             timeout_secs: DEFAULT_TIMEOUT_SECS,
+            signing_secret: None,
+            proxy_url: None,
+            no_proxy: Vec::new(),
+            extra_ca_cert_path: None,
+            check_updates: true,
         }
     }
 }
@@ -91,10 +518,15 @@ impl Config {
     /// 2. `./ctd.toml`
     /// 3. `~/.config/ctd/config.toml`
     ///
-    /// Environment variables override file values:
-    /// - `CTD_API_URL`
-    /// - `CTD_API_KEY`
+    /// Environment variables listed in [`ENV_OVERRIDES`] override file values.
+    ///
+    /// While a [`config_watcher`](crate::config_watcher) is running, returns
+    /// its current config instead.
     pub fn load() -> Result<Self> {
+        if let Some(settings) = crate::config_watcher::active() {
+            return Ok(settings.config.clone());
+        }
+
         let mut config = Self::load_from_file().unwrap_or_default();
         config.apply_env_overrides();
         Ok(config)
@@ -108,56 +540,387 @@ impl Config {
         let mut config: Config = toml::from_str(&contents)
             .map_err(|e| CtdError::Config(format!("Failed to parse config file: {}", e)))?;
 
+        config.decrypt_stored_key();
         config.apply_env_overrides();
         Ok(config)
     }
 
-    /// Attempts to load config from default file locations.
-    fn load_from_file() -> Option<Self> {
-        // 1. Check CTD_CONFIG env var
-        if let Ok(path) = env::var("CTD_CONFIG")
-            && let Ok(config) = Self::load_from_path(&PathBuf::from(path))
+    /// Checks the config for values that would break submission.
+    ///
+    /// Returns one message per problem; an empty list means the config is valid.
+    /// Warnings from [`Config::validate`] are not included.
+    pub fn validation_errors(&self) -> Vec<String> {
+        self.validate()
+            .into_iter()
+            .filter(ConfigIssue::is_error)
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    /// Checks the config for errors and likely mistakes.
+    ///
+    /// Errors break submission (bad URL, zero timeout); warnings flag settings
+    /// that work but are probably unintended (no API key, a localhost URL).
+    pub fn validate(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+
+        if !self.api.url.starts_with("http://") && !self.api.url.starts_with("https://") {
+            issues.push(ConfigIssue::error(
+                "api.url",
+                format!(
+                    "api.url must start with http:// or https:// (got \"{}\")",
+                    self.api.url
+                ),
+            ));
+        } else if is_loopback_url(&self.api.url) {
+            issues.push(ConfigIssue::warning(
+                "api.url",
+                format!(
+                    "api.url points at this machine ({}); reports only arrive if a server runs locally",
+                    self.api.url
+                ),
+            ));
+        }
+
+        if !self.api.crashes_path.starts_with('/') {
+            issues.push(ConfigIssue::error(
+                "api.crashes_path",
+                format!(
+                    "api.crashes_path must start with / (got \"{}\")",
+                    self.api.crashes_path
+                ),
+            ));
+        }
+
+        if !self.api.sessions_path.starts_with('/') {
+            issues.push(ConfigIssue::error(
+                "api.sessions_path",
+                format!(
+                    "api.sessions_path must start with / (got \"{}\")",
+                    self.api.sessions_path
+                ),
+            ));
+        }
+
+        if self.api.timeout_secs == 0 {
+            issues.push(ConfigIssue::error(
+                "api.timeout_secs",
+                "api.timeout_secs must be greater than 0",
+            ));
+        }
+
+        match self.api.api_key.as_deref() {
+            Some(key) if key.trim().is_empty() => {
+                issues.push(ConfigIssue::error(
+                    "api.api_key",
+                    "api.api_key is set but empty",
+                ));
+            }
+            Some(_) if self.api.api_key_encrypted.is_some() => {
+                issues.push(ConfigIssue::warning(
+                    "api.api_key_encrypted",
+                    "api.api_key and api.api_key_encrypted are both set; api.api_key is used",
+                ));
+            }
+            Some(_) => {}
+            None if self.api.api_key_encrypted.is_some() => issues.push(ConfigIssue::warning(
+                "api.api_key_encrypted",
+                "api.api_key_encrypted could not be decrypted; only the Windows user who ran `ctd config set-key` can use it",
+            )),
+            None => issues.push(ConfigIssue::warning(
+                "api.api_key",
+                "api.api_key is not set; servers that require a key will reject reports",
+            )),
+        }
+
+        if self
+            .api
+            .signing_secret
+            .as_deref()
+            .is_some_and(|k| k.trim().is_empty())
         {
-            return Some(config);
+            issues.push(ConfigIssue::error(
+                "api.signing_secret",
+                "api.signing_secret is set but empty",
+            ));
         }
 
-        // 2. Check ./ctd.toml
-        let local_path = PathBuf::from("ctd.toml");
-        if local_path.exists()
-            && let Ok(config) = Self::load_from_path(&local_path)
+        if let Some(ref proxy) = self.api.proxy_url
+            && !proxy.starts_with("http://")
+            && !proxy.starts_with("https://")
+        {
+            issues.push(ConfigIssue::error(
+                "api.proxy_url",
+                format!(
+                    "api.proxy_url must start with http:// or https:// (got \"{}\")",
+                    proxy
+                ),
+            ));
+        }
+
+        if let Some(ref path) = self.api.extra_ca_cert_path
+            && !path.is_file()
+        {
+            issues.push(ConfigIssue::error(
+                "api.extra_ca_cert_path",
+                format!("api.extra_ca_cert_path does not exist: {}", path.display()),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.submission.sample_rate) {
+            issues.push(ConfigIssue::error(
+                "submission.sample_rate",
+                format!(
+                    "submission.sample_rate must be between 0.0 and 1.0 (got {})",
+                    self.submission.sample_rate
+                ),
+            ));
+        }
+
+        if !matches!(
+            self.logging.level.to_ascii_lowercase().as_str(),
+            "off" | "error" | "warn" | "info" | "debug" | "trace"
+        ) {
+            issues.push(ConfigIssue::error(
+                "logging.level",
+                format!(
+                    "logging.level must be off, error, warn, info, debug, or trace (got \"{}\")",
+                    self.logging.level
+                ),
+            ));
+        }
+
+        if self.logging.max_size_mb == 0 {
+            issues.push(ConfigIssue::error(
+                "logging.max_size_mb",
+                "logging.max_size_mb must be greater than 0",
+            ));
+        }
+
+        if self.watchdog.timeout_secs == 0 {
+            issues.push(ConfigIssue::error(
+                "watchdog.timeout_secs",
+                "watchdog.timeout_secs must be greater than 0",
+            ));
+        } else if self.watchdog.enabled && self.watchdog.timeout_secs < 10 {
+            issues.push(ConfigIssue::warning(
+                "watchdog.timeout_secs",
+                format!(
+                    "watchdog.timeout_secs is {}; long loading screens may be reported as hangs",
+                    self.watchdog.timeout_secs
+                ),
+            ));
+        }
+
+        if let Some(ref dsn) = self.sentry.dsn
+            && let Err(e) = crate::sentry::Dsn::parse(dsn)
+        {
+            issues.push(ConfigIssue::error("sentry.dsn", e.to_string()));
+        }
+
+        if let Some(ref url) = self.notify.discord.webhook_url
+            && !url.starts_with("https://")
+        {
+            issues.push(ConfigIssue::error(
+                "notify.discord.webhook_url",
+                format!(
+                    "notify.discord.webhook_url must start with https:// (got \"{}\")",
+                    url
+                ),
+            ));
+        }
+
+        if self.submission.max_reports_per_hour == Some(0) {
+            issues.push(ConfigIssue::error(
+                "submission.max_reports_per_hour",
+                "submission.max_reports_per_hour must be greater than 0 (use sample_rate = 0.0 to stop reporting)",
+            ));
+        }
+
+        issues
+    }
+
+    /// Writes the commented example config to `path` unless a file is already there.
+    ///
+    /// Returns true if the file was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if the file cannot be written.
+    pub fn write_default(path: &Path) -> Result<bool> {
+        if path.exists() {
+            return Ok(false);
+        }
+
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).map_err(|e| {
+                CtdError::Config(format!("Failed to create {}: {}", parent.display(), e))
+            })?;
+        }
+        fs::write(path, Self::example())
+            .map_err(|e| CtdError::Config(format!("Failed to write {}: {}", path.display(), e)))?;
+        Ok(true)
+    }
+
+    /// Prepares the config when a game plugin starts.
+    ///
+    /// Writes a commented `ctd.toml` to the working directory (the game
+    /// directory) on first run, then logs every validation issue so
+    /// problems show up in the plugin log before a crash. Finally starts
+    /// [`config_watcher::watch`](crate::config_watcher::watch) so edits
+    /// apply without restarting the game.
+    pub fn startup_check() {
+        if Self::discover_path().is_none() {
+            match Self::write_default(Path::new("ctd.toml")) {
+                Ok(true) => info!("Wrote default config to ctd.toml"),
+                Ok(false) => {}
+                Err(e) => warn!("{}", e),
+            }
+        }
+
+        let config = match Self::discover_path() {
+            Some(path) => match Self::load_from_path(&path) {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!("{}: {}", path.display(), e);
+                    return;
+                }
+            },
+            None => Self::load().unwrap_or_default(),
+        };
+
+        for issue in config.validate() {
+            match issue.severity {
+                IssueSeverity::Error => warn!("Config error: {}", issue),
+                IssueSeverity::Warning => info!("Config warning: {}", issue),
+            }
+        }
+
+        if let Some(path) = Self::discover_path()
+            && let Err(e) = crate::config_watcher::watch(path)
         {
-            return Some(config);
+            warn!("Config changes will apply after a restart: {}", e);
+        }
+    }
+
+    /// Returns the config file locations in search order.
+    ///
+    /// The `CTD_CONFIG` path is included only when the variable is set.
+    pub fn candidate_paths() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        // 1. Check CTD_CONFIG env var
+        if let Ok(path) = env::var("CTD_CONFIG") {
+            paths.push(PathBuf::from(path));
         }
 
+        // 2. Check ./ctd.toml
+        paths.push(PathBuf::from("ctd.toml"));
+
         // 3. Check ~/.config/ctd/config.toml
         if let Some(config_dir) = dirs::config_dir() {
-            let user_path = config_dir.join("ctd").join("config.toml");
-            if user_path.exists()
-                && let Ok(config) = Self::load_from_path(&user_path)
-            {
-                return Some(config);
-            }
+            paths.push(config_dir.join("ctd").join("config.toml"));
         }
 
-        None
+        paths
+    }
+
+    /// Returns the first existing config file in search order, if any.
+    pub fn discover_path() -> Option<PathBuf> {
+        Self::candidate_paths().into_iter().find(|p| p.exists())
+    }
+
+    /// Attempts to load config from default file locations.
+    ///
+    /// Files that exist but fail to parse are skipped.
+    fn load_from_file() -> Option<Self> {
+        Self::candidate_paths()
+            .iter()
+            .filter(|p| p.exists())
+            .find_map(|p| Self::load_from_path(p).ok())
+    }
+
+    /// Encrypts an API key for `api.api_key_encrypted`.
+    ///
+    /// Returns the hex-encoded DPAPI blob, decryptable only by the current
+    /// Windows user.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if encryption fails or DPAPI is unavailable
+    /// (any platform other than Windows).
+    pub fn encrypt_api_key(key: &str) -> Result<String> {
+        Ok(hex::encode(dpapi::protect(key.as_bytes())?))
+    }
+
+    /// Decrypts an `api.api_key_encrypted` value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if the value is not valid hex, was encrypted
+    /// by another user or machine, or DPAPI is unavailable.
+    pub fn decrypt_api_key(encrypted: &str) -> Result<String> {
+        let blob = hex::decode(encrypted.trim())
+            .map_err(|e| CtdError::Config(format!("api.api_key_encrypted is not hex: {}", e)))?;
+        let key = dpapi::unprotect(&blob)?;
+        String::from_utf8(key)
+            .map_err(|_| CtdError::Config("api.api_key_encrypted is not UTF-8".to_string()))
+    }
+
+    /// Fills `api.api_key` from `api.api_key_encrypted` if only the latter is set.
+    ///
+    /// A key that cannot be decrypted is logged and left unset, so the rest
+    /// of the file still applies.
+    fn decrypt_stored_key(&mut self) {
+        if self.api.api_key.is_some() {
+            return;
+        }
+
+        if let Some(ref encrypted) = self.api.api_key_encrypted {
+            match Self::decrypt_api_key(encrypted) {
+                Ok(key) => self.api.api_key = Some(key),
+                Err(e) => warn!("{}", e),
+            }
+        }
     }
 
     /// Applies environment variable overrides to the config.
     fn apply_env_overrides(&mut self) {
-        if let Ok(url) = env::var("CTD_API_URL") {
-            self.api.url = url;
-        }
+        self.apply_overrides_from(|var| env::var(var).ok());
+    }
 
-        if let Ok(key) = env::var("CTD_API_KEY") {
-            self.api.api_key = Some(key);
+    /// Applies [`ENV_OVERRIDES`], looking up each variable with `lookup`.
+    ///
+    /// Values that fail to parse are logged and ignored.
+    fn apply_overrides_from(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        for o in ENV_OVERRIDES {
+            if let Some(value) = lookup(o.var)
+                && let Err(e) = (o.apply)(self, &value)
+            {
+                warn!("Ignoring {}={:?}: {}", o.var, value, e);
+            }
         }
     }
 
+    /// Returns the override variables set in this process's environment.
+    pub fn active_env_overrides() -> Vec<&'static EnvOverride> {
+        ENV_OVERRIDES
+            .iter()
+            .filter(|o| env::var_os(o.var).is_some())
+            .collect()
+    }
+
     /// Returns an example config file as a string.
     pub fn example() -> &'static str {
         r#"# CTD Configuration File
 # Place this file at ./ctd.toml or ~/.config/ctd/config.toml
 
+# Allow `ctd test-crash` and the plugins' trigger_test_crash() export to crash
+# the game on purpose, to check that reports reach the server
+allow_test_crash = false
+
 [api]
 # Base URL of the crash report server
 url = "http://localhost:3000"
@@ -165,12 +928,59 @@ url = "http://localhost:3000"
 # API path for crash reports endpoint
 crashes_path = "/crashes"
 
+# API path for session summaries endpoint
+sessions_path = "/sessions"
+
 # Optional API key for authentication
 # api_key = "your-api-key-here"
 
+# Or store the key encrypted for your Windows user with `ctd config set-key`
+# api_key_encrypted = "01000000d08c9ddf..."
+
 # Request timeout in seconds
 timeout_secs = 30
 
+# Optional shared secret; submissions are signed with an X-CTD-Signature
+# header (HMAC-SHA256 of the body) so the server can reject forged reports
+# signing_secret = "your-shared-secret"
+
+# Optional proxy for API requests, and hosts that bypass it
+# proxy_url = "http://proxy.example.com:8080"
+# no_proxy = ["localhost", ".corp.example.com"]
+
+# Optional PEM bundle of extra root certificates (TLS-intercepting gateways)
+# extra_ca_cert_path = "C:/certs/corp-root.pem"
+
+# Once a day, ask the server for the latest CTD version and log a notice if
+# this one is out of date
+check_updates = true
+
+[submission]
+# Fraction of crashes to report (0.0 - 1.0)
+sample_rate = 1.0
+
+# Stop reporting after this many crashes in an hour (default: unlimited)
+# max_reports_per_hour = 10
+
+[logging]
+# Minimum level written to the plugin log: off, error, warn, info, debug, trace
+level = "info"
+
+# Where log files go (default: Documents/My Games/CTD/logs)
+# directory = "C:/CTD/logs"
+
+# Rotated files kept per game, and the size in MB at which to rotate
+max_files = 5
+max_size_mb = 10
+
+# Also write each crash as a Crash Logger / Buffout 4 style crash-*.log here,
+# for analyzers and parsers that read those logs
+crash_log = false
+
+# Also write a Windows Event Log entry (source "CTD Crash Reporter") with the
+# crash hash and share link for each captured crash or hang
+event_log = false
+
 [symbols]
 # Enable PDB symbol resolution for enhanced stack traces
 enabled = true
@@ -180,10 +990,514 @@ enabled = true
 
 # Additional directories to search for PDB files
 # search_dirs = ["C:/Games/Skyrim/Data/SKSE/Plugins"]
+
+# Resolve function names in-process with DbgHelp when a crash is captured
+dbghelp = false
+
+# PDB validation: "strict" skips a PDB whose GUID/age doesn't match, "best-effort"
+# falls back to loading a PDB with a matching filename
+match_mode = "strict"
+
+[coexistence]
+# When Crash Logger, Buffout 4, or Trainwreck is also installed:
+#   "passive"    - let it capture the crash; CTD submits without a stack walk
+#   "import-log" - don't capture; submit its crash log on the next launch
+#   "ignore"     - capture crashes as usual
+mode = "passive"
+
+[handler]
+# Register the exception handler ahead of other tools' handlers
+first = true
+
+# After capturing a crash: "continue-search" passes it on to the game and
+# other handlers, "terminate" ends the game once the report is sent, and
+# "dialog" shows a message box first
+on_crash = "continue-search"
+
+# Count exceptions the game handles itself (C++ throws, guard pages) and
+# include the counts in the next report
+handled_exceptions = false
+
+[watchdog]
+# Report a hang, with every thread's stack, when the game's main thread
+# stops responding for timeout_secs
+enabled = true
+timeout_secs = 60
+
+[session]
+# When the game exits cleanly, send a summary of the session (play time, mod
+# count, peak memory, handled exceptions, hangs) so crash rates per mod can
+# be computed
+enabled = false
+
+[privacy]
+# Send a random installation ID (kept in the config directory) and a random
+# per-launch session ID, so one player crashing 50 times isn't counted as 50
+# players. Neither identifies you or your machine
+anonymous_ids = true
+
+[fingerprint]
+# How mod files are hashed: "partial-sha256" (first 64KB + size, fastest),
+# "full-sha256", "xxhash64" (whole file, fast), or "crc32" (whole file,
+# matches the checksums Nexus Mods publishes). Whole-file modes tell apart
+# repacks that share a header but read every byte of large archives
+algorithm = "partial-sha256"
+
+[known_bad_modules]
+# Flag loaded DLLs known to crash games (old X3DAudio replacements, outdated
+# DLSS, legacy allocators) in each report, using the bundled list plus any
+# rules the server sends
+enabled = true
+
+# Extra rules, matched by file name; below_version limits a rule to older
+# versions and outside_windows to copies outside the Windows directory
+# [[known_bad_modules.rules]]
+# module = "example.dll"
+# below_version = "1.2"
+# reason = "Crashes when a save loads; update to 1.2 or later."
+
+[modpack]
+# Name the Wabbajack list or Nexus Collection the game was installed from in
+# each report, read from the list's metadata or the collection's manifest
+detect = true
+
+# Modpack authors can stamp their pack instead, in the ctd.toml they ship
+# name = "My Modpack"
+# version = "1.0.0"
+
+[dumps]
+# Folder Windows Error Reporting writes crash dumps to once `ctd dumps enable`
+# has registered a game; `ctd dumps submit` reports the dumps found there
+# (default: <local data dir>/ctd/dumps)
+# directory = "C:/CTD/dumps"
+
+[sentry]
+# Send reports to a Sentry project instead of the CTD API. Known issues,
+# notes, and share links need the CTD API and are unavailable in this mode.
+# dsn = "https://<key>@o0.ingest.sentry.io/<project>"
+# environment = "production"
+
+[notify.discord]
+# Post a short message to a Discord channel after each submitted report:
+# game, exception, suspected mod, and share link
+# webhook_url = "https://discord.com/api/webhooks/<id>/<token>"
 "#
     }
 }
 
+/// An environment variable that overrides one config value.
+#[derive(Debug)]
+pub struct EnvOverride {
+    /// Variable name (e.g., "CTD_API_TIMEOUT_SECS").
+    pub var: &'static str,
+    /// Config key it overrides (e.g., "api.timeout_secs").
+    pub field: &'static str,
+    apply: fn(&mut Config, &str) -> std::result::Result<(), String>,
+}
+
+/// Every supported `CTD_*` override. Add new config fields here.
+pub static ENV_OVERRIDES: &[EnvOverride] = &[
+    EnvOverride {
+        var: "CTD_ALLOW_TEST_CRASH",
+        field: "allow_test_crash",
+        apply: |c, v| {
+            c.allow_test_crash = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_URL",
+        field: "api.url",
+        apply: |c, v| {
+            c.api.url = v.to_string();
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_CRASHES_PATH",
+        field: "api.crashes_path",
+        apply: |c, v| {
+            c.api.crashes_path = v.to_string();
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_SESSIONS_PATH",
+        field: "api.sessions_path",
+        apply: |c, v| {
+            c.api.sessions_path = v.to_string();
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_KEY",
+        field: "api.api_key",
+        apply: |c, v| {
+            c.api.api_key = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_TIMEOUT_SECS",
+        field: "api.timeout_secs",
+        apply: |c, v| {
+            c.api.timeout_secs = parse_number(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_SIGNING_SECRET",
+        field: "api.signing_secret",
+        apply: |c, v| {
+            c.api.signing_secret = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_PROXY_URL",
+        field: "api.proxy_url",
+        apply: |c, v| {
+            c.api.proxy_url = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_NO_PROXY",
+        field: "api.no_proxy",
+        apply: |c, v| {
+            c.api.no_proxy = v
+                .split(',')
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+                .map(String::from)
+                .collect();
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_EXTRA_CA_CERT_PATH",
+        field: "api.extra_ca_cert_path",
+        apply: |c, v| {
+            c.api.extra_ca_cert_path = Some(PathBuf::from(v));
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_API_CHECK_UPDATES",
+        field: "api.check_updates",
+        apply: |c, v| {
+            c.api.check_updates = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SYMBOLS_ENABLED",
+        field: "symbols.enabled",
+        apply: |c, v| {
+            c.symbols.enabled = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SYMBOLS_CACHE_DIR",
+        field: "symbols.cache_dir",
+        apply: |c, v| {
+            c.symbols.cache_dir = Some(PathBuf::from(v));
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SYMBOLS_SEARCH_DIRS",
+        field: "symbols.search_dirs",
+        apply: |c, v| {
+            c.symbols.search_dirs = env::split_paths(v).collect();
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SYMBOLS_DBGHELP",
+        field: "symbols.dbghelp",
+        apply: |c, v| {
+            c.symbols.dbghelp = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SYMBOLS_MATCH_MODE",
+        field: "symbols.match_mode",
+        apply: |c, v| {
+            c.symbols.match_mode = match v {
+                "strict" => PdbMatchMode::StrictMatch,
+                "best-effort" => PdbMatchMode::BestEffort,
+                _ => return Err("expected \"strict\" or \"best-effort\"".to_string()),
+            };
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SUBMISSION_SAMPLE_RATE",
+        field: "submission.sample_rate",
+        apply: |c, v| {
+            c.submission.sample_rate = parse_number(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SUBMISSION_MAX_REPORTS_PER_HOUR",
+        field: "submission.max_reports_per_hour",
+        apply: |c, v| {
+            c.submission.max_reports_per_hour = Some(parse_number(v)?);
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_LOGGING_LEVEL",
+        field: "logging.level",
+        apply: |c, v| {
+            c.logging.level = v.to_string();
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_LOGGING_DIRECTORY",
+        field: "logging.directory",
+        apply: |c, v| {
+            c.logging.directory = Some(PathBuf::from(v));
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_DUMPS_DIRECTORY",
+        field: "dumps.directory",
+        apply: |c, v| {
+            c.dumps.directory = Some(PathBuf::from(v));
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SENTRY_DSN",
+        field: "sentry.dsn",
+        apply: |c, v| {
+            c.sentry.dsn = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SENTRY_ENVIRONMENT",
+        field: "sentry.environment",
+        apply: |c, v| {
+            c.sentry.environment = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_NOTIFY_DISCORD_WEBHOOK_URL",
+        field: "notify.discord.webhook_url",
+        apply: |c, v| {
+            c.notify.discord.webhook_url = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_LOGGING_MAX_FILES",
+        field: "logging.max_files",
+        apply: |c, v| {
+            c.logging.max_files = parse_number(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_LOGGING_MAX_SIZE_MB",
+        field: "logging.max_size_mb",
+        apply: |c, v| {
+            c.logging.max_size_mb = parse_number(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_LOGGING_CRASH_LOG",
+        field: "logging.crash_log",
+        apply: |c, v| {
+            c.logging.crash_log = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_LOGGING_EVENT_LOG",
+        field: "logging.event_log",
+        apply: |c, v| {
+            c.logging.event_log = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_COEXISTENCE_MODE",
+        field: "coexistence.mode",
+        apply: |c, v| {
+            c.coexistence.mode = match v {
+                "passive" => CoexistenceMode::Passive,
+                "import-log" => CoexistenceMode::ImportLog,
+                "ignore" => CoexistenceMode::Ignore,
+                _ => return Err("expected \"passive\", \"import-log\", or \"ignore\"".to_string()),
+            };
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_HANDLER_FIRST",
+        field: "handler.first",
+        apply: |c, v| {
+            c.handler.first = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_HANDLER_ON_CRASH",
+        field: "handler.on_crash",
+        apply: |c, v| {
+            c.handler.on_crash = match v {
+                "continue-search" => OnCrash::ContinueSearch,
+                "terminate" => OnCrash::Terminate,
+                "dialog" => OnCrash::Dialog,
+                _ => {
+                    return Err(
+                        "expected \"continue-search\", \"terminate\", or \"dialog\"".to_string()
+                    );
+                }
+            };
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_HANDLER_HANDLED_EXCEPTIONS",
+        field: "handler.handled_exceptions",
+        apply: |c, v| {
+            c.handler.handled_exceptions = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_WATCHDOG_ENABLED",
+        field: "watchdog.enabled",
+        apply: |c, v| {
+            c.watchdog.enabled = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_WATCHDOG_TIMEOUT_SECS",
+        field: "watchdog.timeout_secs",
+        apply: |c, v| {
+            c.watchdog.timeout_secs = parse_number(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_SESSION_ENABLED",
+        field: "session.enabled",
+        apply: |c, v| {
+            c.session.enabled = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_PRIVACY_ANONYMOUS_IDS",
+        field: "privacy.anonymous_ids",
+        apply: |c, v| {
+            c.privacy.anonymous_ids = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_KNOWN_BAD_MODULES_ENABLED",
+        field: "known_bad_modules.enabled",
+        apply: |c, v| {
+            c.known_bad_modules.enabled = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_MODPACK_DETECT",
+        field: "modpack.detect",
+        apply: |c, v| {
+            c.modpack.detect = parse_bool(v)?;
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_MODPACK_NAME",
+        field: "modpack.name",
+        apply: |c, v| {
+            c.modpack.name = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_MODPACK_VERSION",
+        field: "modpack.version",
+        apply: |c, v| {
+            c.modpack.version = Some(v.to_string());
+            Ok(())
+        },
+    },
+    EnvOverride {
+        var: "CTD_FINGERPRINT_ALGORITHM",
+        field: "fingerprint.algorithm",
+        apply: |c, v| {
+            c.fingerprint.algorithm = match v {
+                "partial-sha256" => HashAlgorithm::PartialSha256,
+                "full-sha256" => HashAlgorithm::FullSha256,
+                "xxhash64" => HashAlgorithm::XxHash64,
+                "crc32" => HashAlgorithm::Crc32,
+                _ => {
+                    return Err(
+                        "expected \"partial-sha256\", \"full-sha256\", \"xxhash64\", or \"crc32\""
+                            .to_string(),
+                    );
+                }
+            };
+            Ok(())
+        },
+    },
+];
+
+/// Parses an override flag ("1"/"0", "true"/"false", "yes"/"no", "on"/"off").
+fn parse_bool(value: &str) -> std::result::Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+/// Parses an override number.
+fn parse_number<T: std::str::FromStr>(value: &str) -> std::result::Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    value.trim().parse().map_err(|e: T::Err| e.to_string())
+}
+
+/// Returns true if a URL's host is localhost or a loopback address.
+fn is_loopback_url(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    // Strip the port, keeping bracketed IPv6 hosts intact
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +1526,40 @@ mod tests {
         assert_eq!(config.api.crashes_path, "/api/v2/crashes");
         assert_eq!(config.api.api_key, Some("secret123".to_string()));
         assert_eq!(config.api.timeout_secs, 60);
+        assert!(config.api.signing_secret.is_none());
+    }
+
+    #[test]
+    fn parse_proxy_settings() {
+        let toml = r#"
+            [api]
+            proxy_url = "http://proxy:8080"
+            no_proxy = ["localhost", ".corp.example.com"]
+            extra_ca_cert_path = "certs/root.pem"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.api.proxy_url.as_deref(), Some("http://proxy:8080"));
+        assert_eq!(config.api.no_proxy, vec!["localhost", ".corp.example.com"]);
+        assert_eq!(
+            config.api.extra_ca_cert_path,
+            Some(PathBuf::from("certs/root.pem"))
+        );
+
+        let errors = config.validation_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("extra_ca_cert_path"));
+    }
+
+    #[test]
+    fn parse_signing_secret() {
+        let toml = r#"
+            [api]
+            signing_secret = "hunter2"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.api.signing_secret.as_deref(), Some("hunter2"));
     }
 
     #[test]
@@ -227,9 +1575,431 @@ mod tests {
         assert!(config.api.api_key.is_none());
     }
 
+    #[test]
+    fn dbghelp_search_path_joins_dirs() {
+        let symbols = SymbolsConfig {
+            cache_dir: Some(PathBuf::from("C:/cache")),
+            search_dirs: vec![PathBuf::from("C:/a"), PathBuf::from("C:/b")],
+            ..Default::default()
+        };
+        assert_eq!(symbols.dbghelp_search_path(), "C:/a;C:/b;C:/cache");
+    }
+
+    #[test]
+    fn runtime_symbolization_requires_both_flags() {
+        let mut symbols = SymbolsConfig::default();
+        assert!(!symbols.runtime_symbolization());
+
+        symbols.dbghelp = true;
+        assert!(symbols.runtime_symbolization());
+
+        symbols.enabled = false;
+        assert!(!symbols.runtime_symbolization());
+    }
+
+    #[test]
+    fn parse_symbols_match_mode() {
+        let toml = r#"
+            [symbols]
+            match_mode = "best-effort"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.symbols.match_mode, PdbMatchMode::BestEffort);
+        assert_eq!(
+            SymbolsConfig::default().match_mode,
+            PdbMatchMode::StrictMatch
+        );
+    }
+
+    #[test]
+    fn parse_submission_limits() {
+        let toml = r#"
+            [submission]
+            sample_rate = 0.25
+            max_reports_per_hour = 5
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.submission.sample_rate, 0.25);
+        assert_eq!(config.submission.max_reports_per_hour, Some(5));
+        assert_eq!(Config::default().submission.sample_rate, 1.0);
+
+        let invalid: Config = toml::from_str("[submission]\nsample_rate = 1.5").unwrap();
+        assert_eq!(invalid.validation_errors().len(), 1);
+    }
+
+    #[test]
+    fn parse_logging() {
+        let toml = r#"
+            [logging]
+            level = "debug"
+            directory = "C:/CTD/logs"
+            max_files = 2
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.logging.level, "debug");
+        assert_eq!(config.logging.log_dir(), PathBuf::from("C:/CTD/logs"));
+        assert_eq!(config.logging.max_files, 2);
+        assert_eq!(config.logging.max_size_mb, 10);
+        assert!(!config.logging.crash_log);
+
+        let config: Config = toml::from_str("[logging]\ncrash_log = true").unwrap();
+        assert!(config.logging.crash_log);
+        assert!(!config.logging.event_log);
+
+        let config: Config = toml::from_str("[logging]\nevent_log = true").unwrap();
+        assert!(config.logging.event_log);
+
+        let invalid: Config = toml::from_str("[logging]\nlevel = \"loud\"").unwrap();
+        assert_eq!(invalid.validation_errors().len(), 1);
+    }
+
+    #[test]
+    fn parse_coexistence_mode() {
+        assert_eq!(Config::default().coexistence.mode, CoexistenceMode::Passive);
+
+        let config: Config = toml::from_str("[coexistence]\nmode = \"import-log\"").unwrap();
+        assert_eq!(config.coexistence.mode, CoexistenceMode::ImportLog);
+
+        assert!(toml::from_str::<Config>("[coexistence]\nmode = \"off\"").is_err());
+    }
+
+    #[test]
+    fn parse_handler() {
+        let handler = Config::default().handler;
+        assert!(handler.first);
+        assert_eq!(handler.on_crash, OnCrash::ContinueSearch);
+        assert!(!handler.on_crash.ends_process());
+
+        let toml = r#"
+            [handler]
+            first = false
+            on_crash = "dialog"
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(!config.handler.first);
+        assert_eq!(config.handler.on_crash, OnCrash::Dialog);
+        assert!(config.handler.on_crash.ends_process());
+    }
+
+    #[test]
+    fn parse_handled_exceptions() {
+        assert!(!Config::default().handler.handled_exceptions);
+
+        let toml = r#"
+            [handler]
+            handled_exceptions = true
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.handler.handled_exceptions);
+        assert!(config.handler.first);
+    }
+
+    #[test]
+    fn parse_watchdog() {
+        let watchdog = Config::default().watchdog;
+        assert!(watchdog.enabled);
+        assert_eq!(watchdog.timeout_secs, 60);
+
+        let config: Config = toml::from_str("[watchdog]\ntimeout_secs = 5").unwrap();
+        assert!(config.watchdog.enabled);
+        assert_eq!(config.watchdog.timeout_secs, 5);
+        assert!(
+            config
+                .validate()
+                .iter()
+                .any(|i| i.field == "watchdog.timeout_secs" && !i.is_error())
+        );
+
+        let config: Config = toml::from_str("[watchdog]\ntimeout_secs = 0").unwrap();
+        assert!(
+            config
+                .validation_errors()
+                .iter()
+                .any(|e| e.contains("watchdog.timeout_secs"))
+        );
+    }
+
+    #[test]
+    fn parse_session() {
+        let config = Config::default();
+        assert!(!config.session.enabled);
+        assert_eq!(config.api.sessions_path, DEFAULT_SESSIONS_PATH);
+
+        let toml = r#"
+            [api]
+            sessions_path = "/v2/sessions"
+
+            [session]
+            enabled = true
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.session.enabled);
+        assert_eq!(config.api.sessions_path, "/v2/sessions");
+        assert!(config.validation_errors().is_empty());
+
+        let config: Config = toml::from_str("[api]\nsessions_path = \"sessions\"").unwrap();
+        assert!(
+            config
+                .validation_errors()
+                .iter()
+                .any(|e| e.contains("api.sessions_path"))
+        );
+    }
+
+    #[test]
+    fn parse_privacy() {
+        assert!(Config::default().privacy.anonymous_ids);
+
+        let config: Config = toml::from_str("[privacy]\nanonymous_ids = false").unwrap();
+        assert!(!config.privacy.anonymous_ids);
+    }
+
+    #[test]
+    fn parse_modpack() {
+        let config = Config::default().modpack;
+        assert!(config.detect);
+        assert!(config.name.is_none());
+
+        let config: Config = toml::from_str(
+            r#"
+            [modpack]
+            detect = false
+            name = "Lorerim"
+            version = "3.1.4"
+            "#,
+        )
+        .unwrap();
+        assert!(!config.modpack.detect);
+        assert_eq!(config.modpack.name.as_deref(), Some("Lorerim"));
+        assert_eq!(config.modpack.version.as_deref(), Some("3.1.4"));
+    }
+
+    #[test]
+    fn parse_known_bad_modules() {
+        assert!(Config::default().known_bad_modules.enabled);
+
+        let config: Config = toml::from_str(
+            r#"
+            [known_bad_modules]
+            enabled = false
+
+            [[known_bad_modules.rules]]
+            module = "example.dll"
+            outside_windows = true
+            reason = "Crashes on load."
+            "#,
+        )
+        .unwrap();
+        assert!(!config.known_bad_modules.enabled);
+        assert_eq!(config.known_bad_modules.rules.len(), 1);
+        assert!(config.known_bad_modules.rules[0].outside_windows);
+    }
+
+    #[test]
+    fn parse_fingerprint() {
+        assert_eq!(
+            Config::default().fingerprint.algorithm,
+            HashAlgorithm::PartialSha256
+        );
+
+        let config: Config = toml::from_str("[fingerprint]\nalgorithm = \"crc32\"").unwrap();
+        assert_eq!(config.fingerprint.algorithm, HashAlgorithm::Crc32);
+        assert!(toml::from_str::<Config>("[fingerprint]\nalgorithm = \"md5\"").is_err());
+    }
+
+    #[test]
+    fn parse_dumps() {
+        assert_eq!(Config::default().dumps.dump_dir(), default_dump_dir());
+
+        let config: Config = toml::from_str("[dumps]\ndirectory = \"C:/CTD/dumps\"").unwrap();
+        assert_eq!(config.dumps.dump_dir(), PathBuf::from("C:/CTD/dumps"));
+    }
+
+    #[test]
+    fn parse_notify_discord() {
+        assert!(Config::default().notify.discord.webhook_url.is_none());
+
+        let config: Config = toml::from_str(
+            "[notify.discord]\nwebhook_url = \"https://discord.com/api/webhooks/1/abc\"",
+        )
+        .unwrap();
+        assert_eq!(
+            config.notify.discord.webhook_url.as_deref(),
+            Some("https://discord.com/api/webhooks/1/abc")
+        );
+        assert!(config.validation_errors().is_empty());
+
+        let invalid: Config =
+            toml::from_str("[notify.discord]\nwebhook_url = \"discord.com/x\"").unwrap();
+        assert_eq!(invalid.validation_errors().len(), 1);
+    }
+
+    #[test]
+    fn parse_sentry() {
+        assert!(Config::default().sentry.dsn.is_none());
+
+        let config: Config =
+            toml::from_str("[sentry]\ndsn = \"https://key@o0.ingest.sentry.io/42\"").unwrap();
+        assert!(config.validation_errors().is_empty());
+
+        let invalid: Config = toml::from_str("[sentry]\ndsn = \"o0.ingest.sentry.io\"").unwrap();
+        assert_eq!(invalid.validation_errors().len(), 1);
+    }
+
+    #[test]
+    fn parse_allow_test_crash() {
+        assert!(!Config::default().allow_test_crash);
+
+        let config: Config = toml::from_str("allow_test_crash = true").unwrap();
+        assert!(config.allow_test_crash);
+    }
+
     #[test]
     fn example_config_is_valid() {
         let example = Config::example();
-        let _config: Config = toml::from_str(example).unwrap();
+        let config: Config = toml::from_str(example).unwrap();
+        assert!(config.validation_errors().is_empty());
+    }
+
+    #[test]
+    fn validation_errors_reports_each_problem() {
+        let mut config = Config::default();
+        config.api.url = "localhost:3000".into();
+        config.api.crashes_path = "crashes".into();
+        config.api.timeout_secs = 0;
+        config.api.api_key = Some(" ".into());
+
+        let errors = config.validation_errors();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].contains("api.url"));
+    }
+
+    #[test]
+    fn validate_warns_about_likely_mistakes() {
+        let issues = Config::default().validate();
+        assert!(issues.iter().all(|i| !i.is_error()));
+        assert!(issues.iter().any(|i| i.field == "api.url"));
+        assert!(issues.iter().any(|i| i.field == "api.api_key"));
+
+        let mut config = Config::default();
+        config.api.url = "https://ctd.example.com".into();
+        config.api.api_key = Some("ctd_key".into());
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn undecryptable_key_keeps_rest_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+        fs::write(
+            &path,
+            "[api]\nurl = \"https://ctd.example.com\"\napi_key_encrypted = \"not-hex\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from_path(&path).unwrap();
+        assert_eq!(config.api.url, "https://ctd.example.com");
+        if env::var("CTD_API_KEY").is_err() {
+            assert!(config.api.api_key.is_none());
+            assert!(
+                config
+                    .validate()
+                    .iter()
+                    .any(|i| i.field == "api.api_key_encrypted")
+            );
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn api_key_encryption_round_trip() {
+        let encrypted = Config::encrypt_api_key("ctd_secret").unwrap();
+        assert!(!encrypted.contains("ctd_secret"));
+        assert_eq!(Config::decrypt_api_key(&encrypted).unwrap(), "ctd_secret");
+    }
+
+    #[test]
+    fn env_overrides_cover_nested_fields() {
+        let vars: std::collections::HashMap<&str, &str> = [
+            ("CTD_API_TIMEOUT_SECS", "5"),
+            ("CTD_API_CRASHES_PATH", "/v2/crashes"),
+            ("CTD_API_NO_PROXY", "localhost, .corp"),
+            ("CTD_API_CHECK_UPDATES", "false"),
+            ("CTD_SYMBOLS_DBGHELP", "on"),
+            ("CTD_SYMBOLS_MATCH_MODE", "best-effort"),
+            ("CTD_SUBMISSION_SAMPLE_RATE", "0.5"),
+            ("CTD_ALLOW_TEST_CRASH", "yes"),
+            ("CTD_FINGERPRINT_ALGORITHM", "xxhash64"),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut config = Config::default();
+        config.apply_overrides_from(|var| vars.get(var).map(|v| v.to_string()));
+
+        assert_eq!(config.api.timeout_secs, 5);
+        assert_eq!(config.api.crashes_path, "/v2/crashes");
+        assert_eq!(config.api.no_proxy, vec!["localhost", ".corp"]);
+        assert!(!config.api.check_updates);
+        assert!(config.symbols.dbghelp);
+        assert_eq!(config.symbols.match_mode, PdbMatchMode::BestEffort);
+        assert_eq!(config.submission.sample_rate, 0.5);
+        assert!(config.allow_test_crash);
+        assert_eq!(config.fingerprint.algorithm, HashAlgorithm::XxHash64);
+    }
+
+    #[test]
+    fn invalid_env_override_is_ignored() {
+        let mut config = Config::default();
+        config.apply_overrides_from(|var| {
+            (var == "CTD_API_TIMEOUT_SECS").then(|| "soon".to_string())
+        });
+        assert_eq!(config.api.timeout_secs, DEFAULT_TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn env_override_names_follow_scheme() {
+        let mut seen = std::collections::HashSet::new();
+        for o in ENV_OVERRIDES {
+            assert!(seen.insert(o.var), "duplicate {}", o.var);
+            if o.var != "CTD_API_KEY" {
+                let expected = format!("CTD_{}", o.field.replace('.', "_").to_ascii_uppercase());
+                assert_eq!(o.var, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn loopback_urls() {
+        assert!(is_loopback_url("http://localhost:3000"));
+        assert!(is_loopback_url("http://127.0.0.1/api"));
+        assert!(is_loopback_url("http://[::1]:8080"));
+        assert!(!is_loopback_url("https://ctd.ezmode.games"));
+        assert!(!is_loopback_url("http://192.168.1.10:3000"));
+    }
+
+    #[test]
+    fn write_default_keeps_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+
+        assert!(Config::write_default(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), Config::example());
+
+        fs::write(&path, "# mine").unwrap();
+        assert!(!Config::write_default(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# mine");
+    }
+
+    #[test]
+    fn candidate_paths_include_local_file() {
+        assert!(
+            Config::candidate_paths()
+                .iter()
+                .any(|p| p == &PathBuf::from("ctd.toml"))
+        );
     }
 }
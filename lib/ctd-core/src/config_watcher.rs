@@ -0,0 +1,247 @@
+//! Hot reload of `ctd.toml`.
+//!
+//! Loading a heavily modded game can take ten minutes, so restarting it to
+//! pick up a pasted API key or a new server URL is not an option. A
+//! [`ConfigWatcher`] watches the config file and swaps in a freshly built
+//! [`Settings`] whenever it changes. Once [`watch`] has installed the
+//! process-wide watcher, [`Config::load`] and [`ApiClient::from_config`]
+//! return the active settings instead of reading the file again.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use arc_swap::ArcSwap;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{info, warn};
+
+use crate::api_client::ApiClient;
+use crate::config::Config;
+use crate::{CtdError, Result};
+
+/// The process-wide watcher installed by [`watch`].
+static ACTIVE: OnceLock<ConfigWatcher> = OnceLock::new();
+
+/// A loaded config and the API client built from it.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// The loaded configuration, with environment overrides applied.
+    pub config: Config,
+    /// Client for `config.api`, or `config.sentry` when a DSN is set.
+    pub client: ApiClient,
+}
+
+impl Settings {
+    /// Loads a config file and builds its client, rejecting invalid configs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Config` if the file cannot be read or parsed or
+    /// fails validation, or any error from [`ApiClient::new`].
+    pub fn load_from_path(path: &Path) -> Result<Self> {
+        let config = Config::load_from_path(&path.to_path_buf())?;
+
+        let errors = config.validation_errors();
+        if !errors.is_empty() {
+            return Err(CtdError::Config(errors.join("; ")));
+        }
+
+        let client = ApiClient::new(config.api.clone())?
+            .with_sentry(&config.sentry)?
+            .with_notify(&config.notify);
+        Ok(Self { config, client })
+    }
+}
+
+/// Watches a config file and keeps [`Settings`] in sync with it.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    settings: Arc<ArcSwap<Settings>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Loads `path` and starts watching it for changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the initial load fails (see
+    /// [`Settings::load_from_path`]) or the file cannot be watched.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let settings = Arc::new(ArcSwap::from_pointee(Settings::load_from_path(&path)?));
+
+        // Editors often save by replacing the file, which drops a watch on the
+        // file itself, so watch the directory and filter by name
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let watched_path = path.clone();
+        let watched_settings = Arc::clone(&settings);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if is_change_to(&event, &watched_path) => {
+                    reload_into(&watched_path, &watched_settings);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Config watcher error: {}", e),
+            })
+            .map_err(|e| CtdError::Config(format!("Failed to watch {}: {}", path.display(), e)))?;
+
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| CtdError::Config(format!("Failed to watch {}: {}", dir.display(), e)))?;
+
+        Ok(Self {
+            path,
+            settings,
+            _watcher: watcher,
+        })
+    }
+
+    /// Returns the config file being watched.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the current settings.
+    pub fn settings(&self) -> Arc<Settings> {
+        self.settings.load_full()
+    }
+
+    /// Reloads the file now, keeping the current settings if it is invalid.
+    ///
+    /// Returns true if the new settings were applied.
+    pub fn reload(&self) -> bool {
+        reload_into(&self.path, &self.settings)
+    }
+}
+
+/// Starts the process-wide watcher on `path`.
+///
+/// Does nothing if a watcher is already running.
+///
+/// # Errors
+///
+/// Returns an error if the watcher cannot be created (see [`ConfigWatcher::new`]).
+pub fn watch(path: impl Into<PathBuf>) -> Result<()> {
+    if ACTIVE.get().is_some() {
+        return Ok(());
+    }
+
+    let watcher = ConfigWatcher::new(path)?;
+    info!("Watching {} for changes", watcher.path().display());
+    // Lost a race with another caller; its watcher is equivalent
+    let _ = ACTIVE.set(watcher);
+    Ok(())
+}
+
+/// Returns the settings of the process-wide watcher, if one is running.
+pub fn active() -> Option<Arc<Settings>> {
+    ACTIVE.get().map(ConfigWatcher::settings)
+}
+
+/// Returns true if `event` modifies, creates, or removes `path`.
+fn is_change_to(event: &Event, path: &Path) -> bool {
+    let relevant = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    relevant
+        && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == path.file_name())
+}
+
+/// Loads `path` and swaps it into `settings` if valid.
+fn reload_into(path: &Path, settings: &ArcSwap<Settings>) -> bool {
+    match Settings::load_from_path(path) {
+        Ok(loaded) => {
+            info!(
+                "Reloaded {} (server: {})",
+                path.display(),
+                loaded.client.base_url()
+            );
+            settings.store(Arc::new(loaded));
+            true
+        }
+        Err(e) => {
+            warn!(
+                "Keeping previous config; {} is invalid: {}",
+                path.display(),
+                e
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Replace `path` by renaming a finished file over it, as editors do, so
+    /// the watcher never sees it truncated.
+    fn replace(path: &Path, contents: &str) {
+        let temp = path.with_extension("toml.tmp");
+        fs::write(&temp, contents).unwrap();
+        fs::rename(&temp, path).unwrap();
+    }
+
+    #[test]
+    fn reload_swaps_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+        fs::write(&path, "[api]\nurl = \"https://one.example.com\"\n").unwrap();
+
+        let watcher = ConfigWatcher::new(&path).unwrap();
+        assert_eq!(watcher.settings().config.api.url, "https://one.example.com");
+
+        replace(
+            &path,
+            "[api]\nurl = \"https://two.example.com\"\napi_key = \"ctd_key\"\n",
+        );
+        assert!(watcher.reload());
+        assert_eq!(watcher.settings().config.api.url, "https://two.example.com");
+        assert_eq!(
+            watcher.settings().config.api.api_key.as_deref(),
+            Some("ctd_key")
+        );
+    }
+
+    #[test]
+    fn invalid_config_keeps_previous_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+        fs::write(&path, "[api]\nurl = \"https://one.example.com\"\n").unwrap();
+        let watcher = ConfigWatcher::new(&path).unwrap();
+
+        replace(&path, "[api]\ntimeout_secs = 0\n");
+        assert!(!watcher.reload());
+
+        replace(&path, "[api\n");
+        assert!(!watcher.reload());
+
+        assert_eq!(watcher.settings().config.api.url, "https://one.example.com");
+    }
+
+    #[test]
+    fn change_filter_matches_file_name() {
+        let path = Path::new("/game/ctd.toml");
+        let modify = |p: &str| {
+            Event::new(EventKind::Modify(notify::event::ModifyKind::Any)).add_path(PathBuf::from(p))
+        };
+
+        assert!(is_change_to(&modify("/game/ctd.toml"), path));
+        assert!(!is_change_to(&modify("/game/other.toml"), path));
+        assert!(!is_change_to(
+            &Event::new(EventKind::Access(notify::event::AccessKind::Any))
+                .add_path(PathBuf::from("/game/ctd.toml")),
+            path
+        ));
+    }
+}
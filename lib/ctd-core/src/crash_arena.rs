@@ -0,0 +1,474 @@
+//! Preallocated buffers for reporting a crash without touching the heap.
+//!
+//! Crashes often come from heap corruption, and the thread that crashed may
+//! hold the allocator's lock. Building `String`s and `Vec`s while reporting
+//! such a crash deadlocks or faults again. A [`CrashArena`] allocates every
+//! buffer once, when the handler is registered, and [`record_crash`] fills
+//! them and queues a report in the spool using nothing else:
+//!
+//! - the stack trace, one `[n] module+0xOFFSET (0xADDRESS)` line per frame;
+//! - module names, resolved straight into fixed buffers;
+//! - the serialized report, written to the spool with raw file calls.
+//!
+//! The game plugins record a crash here first and then take the full path,
+//! which reads the load order, builds a `CreateCrashReport`, and submits it.
+//! Once that report is submitted or queued, [`discard_record`] removes the
+//! arena's copy so the crash is not reported twice.
+
+use std::fmt::{self, Write};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::crash_guard::{SpoolFile, now_millis};
+use crate::spool::Spool;
+
+/// Most frames recorded per crash.
+pub const MAX_FRAMES: usize = 64;
+
+/// Bytes reserved for the stack trace.
+const STACK_TRACE_CAPACITY: usize = 16 * 1024;
+
+/// Bytes reserved for one module name (MAX_PATH UTF-16 units as UTF-8).
+pub(crate) const MODULE_NAME_CAPACITY: usize = 260 * 3;
+
+/// Bytes reserved for the report's fixed fields.
+const RECORD_OVERHEAD: usize = 1024;
+
+/// Arena prepared by [`init`].
+static ARENA: OnceLock<Mutex<CrashArena>> = OnceLock::new();
+
+/// Set while a record written by [`record_crash`] is in the spool.
+static HAS_RECORD: AtomicBool = AtomicBool::new(false);
+
+/// A byte buffer with a fixed capacity that never reallocates.
+///
+/// A write that does not fit is dropped whole and marks the buffer
+/// truncated, so the contents are always valid UTF-8.
+#[derive(Debug)]
+pub struct FixedBuf {
+    buf: Box<[u8]>,
+    len: usize,
+    truncated: bool,
+}
+
+impl FixedBuf {
+    /// Allocates a buffer holding up to `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: vec![0; capacity].into_boxed_slice(),
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Empties the buffer, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    /// Returns the bytes written so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns the text written so far.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(self.as_bytes()).unwrap_or_default()
+    }
+
+    /// Returns true if a write was dropped for lack of room.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Appends `bytes` if they fit. Returns false (and marks the buffer
+    /// truncated) if they don't.
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> bool {
+        if bytes.len() > self.buf.len() - self.len {
+            self.truncated = true;
+            return false;
+        }
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        true
+    }
+}
+
+impl Write for FixedBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.push_bytes(s.as_bytes()) {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+/// Buffers for one crash report, allocated ahead of the crash.
+#[derive(Debug)]
+pub struct CrashArena {
+    /// Game ID as a quoted JSON string.
+    game_id: String,
+    /// Game version as a quoted JSON string.
+    game_version: String,
+    stack_trace: FixedBuf,
+    module_name: FixedBuf,
+    faulting_module: FixedBuf,
+    record: FixedBuf,
+    frames: usize,
+    code: u32,
+    address: u64,
+    file: SpoolFile,
+    /// Crash time of the record in the spool, if any.
+    recorded_at: Option<u64>,
+}
+
+impl CrashArena {
+    /// Allocates an arena for a game's crashes, to be queued in `spool_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spool directory cannot be created.
+    pub fn new(game_id: &str, game_version: &str, spool_dir: &Path) -> io::Result<Self> {
+        let game_id = serde_json::to_string(game_id)?;
+        let game_version = serde_json::to_string(game_version)?;
+
+        // Escaping at most doubles the stack trace (newlines become "\n")
+        let record_capacity = RECORD_OVERHEAD
+            + game_id.len()
+            + game_version.len()
+            + 2 * STACK_TRACE_CAPACITY
+            + 2 * MODULE_NAME_CAPACITY;
+
+        Ok(Self {
+            game_id,
+            game_version,
+            stack_trace: FixedBuf::with_capacity(STACK_TRACE_CAPACITY),
+            module_name: FixedBuf::with_capacity(MODULE_NAME_CAPACITY),
+            faulting_module: FixedBuf::with_capacity(MODULE_NAME_CAPACITY),
+            record: FixedBuf::with_capacity(record_capacity),
+            frames: 0,
+            code: 0,
+            address: 0,
+            file: SpoolFile::prepare(spool_dir, "arena")?,
+            recorded_at: None,
+        })
+    }
+
+    /// Starts recording a crash, clearing the previous one.
+    pub fn begin(&mut self, code: u32, address: u64) {
+        self.stack_trace.clear();
+        self.faulting_module.clear();
+        self.record.clear();
+        self.frames = 0;
+        self.code = code;
+        self.address = address;
+        resolve_module(address, &mut self.faulting_module);
+    }
+
+    /// Appends a frame to the stack trace.
+    ///
+    /// Returns false once the trace is full or has [`MAX_FRAMES`] frames.
+    pub fn push_frame(&mut self, module: &str, offset: u64, address: u64) -> bool {
+        push_frame_line(
+            &mut self.stack_trace,
+            &mut self.frames,
+            module,
+            offset,
+            address,
+        )
+    }
+
+    /// Appends a frame for a return address, resolving its module.
+    pub fn push_address(&mut self, address: u64) -> bool {
+        self.module_name.clear();
+        let base = resolve_module(address, &mut self.module_name);
+        push_frame_line(
+            &mut self.stack_trace,
+            &mut self.frames,
+            self.module_name.as_str(),
+            address.wrapping_sub(base),
+            address,
+        )
+    }
+
+    /// The stack trace recorded so far.
+    pub fn stack_trace(&self) -> &str {
+        self.stack_trace.as_str()
+    }
+
+    /// Serializes the crash as a report crashed at `crashed_at` (Unix
+    /// milliseconds), in the same JSON form the spool stores.
+    ///
+    /// Returns `None` if the report does not fit the record buffer.
+    pub fn serialize(&mut self, crashed_at: u64) -> Option<&[u8]> {
+        let record = &mut self.record;
+        record.clear();
+
+        let fits = (|| -> fmt::Result {
+            write!(
+                record,
+                r#"{{"schemaVersion":1,"gameId":{},"gameVersion":{},"stackTrace":"#,
+                self.game_id, self.game_version
+            )?;
+            write_json_str(record, self.stack_trace.as_str())?;
+            write!(
+                record,
+                r#","exceptionCode":"0x{:08X}","exceptionAddress":"0x{:016X}","#,
+                self.code, self.address
+            )?;
+            if !self.faulting_module.as_str().is_empty() {
+                record.write_str(r#""faultingModule":"#)?;
+                write_json_str(record, self.faulting_module.as_str())?;
+                record.write_char(',')?;
+            }
+            write!(
+                record,
+                r#""loadOrderJson":"[]","pluginCount":0,"crashedAt":{}}}"#,
+                crashed_at
+            )
+        })()
+        .is_ok();
+
+        fits.then(|| self.record.as_bytes())
+    }
+
+    /// Serializes the crash and writes it to the spool.
+    ///
+    /// Returns false if it does not fit or could not be written.
+    pub fn write_to_spool(&mut self) -> bool {
+        let crashed_at = now_millis();
+        if self.serialize(crashed_at).is_none() {
+            return false;
+        }
+        let written = self.file.write(crashed_at, self.record.as_bytes());
+        if written {
+            self.recorded_at = Some(crashed_at);
+        }
+        written
+    }
+
+    /// Removes the record written by [`CrashArena::write_to_spool`], if any.
+    pub fn discard(&mut self) {
+        if let Some(crashed_at) = self.recorded_at.take() {
+            self.file.remove(crashed_at);
+        }
+    }
+}
+
+/// Appends `[n] module+0xOFFSET (0xADDRESS)` to `trace`, or nothing if it
+/// does not fit.
+fn push_frame_line(
+    trace: &mut FixedBuf,
+    frames: &mut usize,
+    module: &str,
+    offset: u64,
+    address: u64,
+) -> bool {
+    if *frames >= MAX_FRAMES {
+        return false;
+    }
+
+    let start = trace.len;
+    if writeln!(
+        trace,
+        "[{}] {}+0x{:X} (0x{:016X})",
+        frames, module, offset, address
+    )
+    .is_err()
+    {
+        // Drop the partial line
+        trace.len = start;
+        return false;
+    }
+    *frames += 1;
+    true
+}
+
+/// Writes `s` as a quoted JSON string.
+fn write_json_str(out: &mut impl Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if u32::from(c) < 0x20 => write!(out, "\\u{:04x}", u32::from(c))?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Writes the file name of the module containing `address` to `out`, or
+/// "unknown". Returns the module's base address, or 0 if unknown.
+#[cfg(windows)]
+pub(crate) fn resolve_module(address: u64, out: &mut FixedBuf) -> u64 {
+    use windows::Win32::Foundation::HMODULE;
+    use windows::Win32::System::LibraryLoader::{
+        GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS, GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+        GetModuleFileNameW, GetModuleHandleExW,
+    };
+    use windows::core::PCWSTR;
+
+    let mut module = HMODULE::default();
+    // SAFETY: With FROM_ADDRESS the pointer is only used as an address
+    let found = unsafe {
+        GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            PCWSTR::from_raw(address as *const u16),
+            &mut module,
+        )
+    }
+    .is_ok();
+
+    let mut path = [0u16; 260];
+    // SAFETY: The buffer is valid for its whole length
+    let len = if found {
+        unsafe { GetModuleFileNameW(module, &mut path) as usize }
+    } else {
+        0
+    };
+    if len == 0 {
+        let _ = out.write_str("unknown");
+        return 0;
+    }
+
+    let path = &path[..len];
+    let name_start = path
+        .iter()
+        .rposition(|&c| c == u16::from(b'\\'))
+        .map_or(0, |i| i + 1);
+    for c in char::decode_utf16(path[name_start..].iter().copied()) {
+        let _ = out.write_char(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+    }
+    module.0 as u64
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+pub(crate) fn resolve_module(_address: u64, out: &mut FixedBuf) -> u64 {
+    let _ = out.write_str("unknown");
+    0
+}
+
+/// Allocates the process-wide arena, queueing records in the default spool.
+///
+/// Game plugins call this when registering their handler. Later calls are
+/// ignored.
+///
+/// # Errors
+///
+/// Returns an error if the spool directory cannot be created.
+pub fn init(game_id: &str, game_version: &str) -> io::Result<()> {
+    if ARENA.get().is_none() {
+        let arena = CrashArena::new(game_id, game_version, &Spool::default_dir())?;
+        let _ = ARENA.set(Mutex::new(arena));
+    }
+    Ok(())
+}
+
+/// Records a crash in the arena and queues it in the spool, without
+/// allocating.
+///
+/// `frames` are the crashed thread's return addresses, innermost first.
+/// Returns false if [`init`] was not called, the arena is in use, or the
+/// record could not be written.
+pub fn record_crash(code: u32, address: u64, frames: &[u64]) -> bool {
+    let Some(Ok(mut arena)) = ARENA.get().map(Mutex::try_lock) else {
+        return false;
+    };
+
+    arena.begin(code, address);
+    for &frame in frames {
+        if !arena.push_address(frame) {
+            break;
+        }
+    }
+
+    let written = arena.write_to_spool();
+    HAS_RECORD.store(written, Ordering::Release);
+    written
+}
+
+/// Returns true if a record from [`record_crash`] is waiting in the spool.
+pub fn has_record() -> bool {
+    HAS_RECORD.load(Ordering::Acquire)
+}
+
+/// Removes the record from [`record_crash`] once the full report for the
+/// same crash has been submitted or queued.
+pub fn discard_record() {
+    if HAS_RECORD.swap(false, Ordering::AcqRel)
+        && let Some(arena) = ARENA.get()
+    {
+        arena
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .discard();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crash_report::CreateCrashReport;
+
+    #[test]
+    fn fixed_buf_drops_writes_that_do_not_fit() {
+        let mut buf = FixedBuf::with_capacity(8);
+        assert!(buf.write_str("abcd").is_ok());
+        assert!(buf.write_str("efghi").is_err());
+        assert_eq!(buf.as_str(), "abcd");
+        assert!(buf.is_truncated());
+
+        buf.clear();
+        assert_eq!(buf.as_str(), "");
+        assert!(!buf.is_truncated());
+    }
+
+    #[test]
+    fn stops_at_max_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut arena = CrashArena::new("fallout4", "1.10.984", dir.path()).unwrap();
+        arena.begin(0xC0000005, 0x1000);
+        for i in 0..MAX_FRAMES as u64 {
+            assert!(arena.push_frame("Fallout4.exe", i, 0x1000 + i));
+        }
+        assert!(!arena.push_frame("Fallout4.exe", 0, 0x1000));
+        assert_eq!(arena.stack_trace().lines().count(), MAX_FRAMES);
+    }
+
+    #[test]
+    fn spooled_record_loads_as_a_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut arena = CrashArena::new("skyrim-se", "1.6.1170", dir.path()).unwrap();
+        arena.begin(0xC0000005, 0x7FF6_D4B3_C2A0);
+        arena.push_frame("SkyrimSE.exe", 0x2F5A12, 0x7FF6_D4B3_C2A0);
+        arena.push_frame("My \"Mod\".dll", 0x1A0, 0x7FFA_0000_11A0);
+        assert!(arena.write_to_spool());
+
+        let spool = Spool::new(dir.path());
+        let pending = spool.pending();
+        assert_eq!(pending.len(), 1);
+
+        let report: CreateCrashReport = spool.load(&pending[0]).unwrap();
+        assert_eq!(report.game_id, "skyrim-se");
+        assert_eq!(report.game_version, "1.6.1170");
+        assert_eq!(
+            report.stack_trace,
+            "[0] SkyrimSE.exe+0x2F5A12 (0x00007FF6D4B3C2A0)\n\
+             [1] My \"Mod\".dll+0x1A0 (0x00007FFA000011A0)\n"
+        );
+        assert_eq!(report.exception_code.as_deref(), Some("0xC0000005"));
+        assert_eq!(report.faulting_module.as_deref(), Some("unknown"));
+
+        arena.discard();
+        assert!(spool.pending().is_empty());
+    }
+}
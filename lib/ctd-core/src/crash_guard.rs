@@ -0,0 +1,354 @@
+//! Protection for the crash handlers against faulting in themselves.
+//!
+//! A handler that walks the stack of a corrupted process can fault or hang
+//! on its own. Without care that fault re-enters the handler and recurses,
+//! or the handler never returns and the game hangs instead of closing.
+//!
+//! - [`HandlerGuard`] admits one crash at a time and recognizes a fault
+//!   raised from inside the handler.
+//! - [`CAPTURE_BUDGET`] bounds the stack walk, which handlers run on a
+//!   worker thread so a hang can be abandoned.
+//! - [`FallbackReport`] is written when that rich path fails: a report with
+//!   only the exception code and address, preformatted at registration and
+//!   written to the spool without allocating.
+
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::crash_arena;
+use crate::spool::Spool;
+
+/// Longest a handler spends walking the stack before it falls back.
+pub const CAPTURE_BUDGET: Duration = Duration::from_secs(5);
+
+/// Largest fallback report, including the game ID and version.
+const MAX_FALLBACK_LEN: usize = 1024;
+
+/// Longest spool file path, in UTF-16 units.
+const MAX_PATH_LEN: usize = 512;
+
+/// Digits reserved for the crash time in milliseconds (good until 2286).
+const MILLIS_DIGITS: usize = 13;
+
+/// Set while any thread is handling a crash.
+static HANDLING: AtomicBool = AtomicBool::new(false);
+
+/// Fallback report prepared by [`prepare_fallback`].
+static FALLBACK: OnceLock<FallbackReport> = OnceLock::new();
+
+thread_local! {
+    /// Set on the thread that holds the [`HandlerGuard`].
+    static IN_HANDLER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as handling a crash until dropped.
+#[derive(Debug)]
+pub struct HandlerGuard {
+    _private: (),
+}
+
+impl HandlerGuard {
+    /// Enters the handler.
+    ///
+    /// Returns `None` if this thread is already inside the handler (the
+    /// handler itself faulted) or another thread is handling a crash.
+    pub fn enter() -> Option<Self> {
+        if Self::is_held() || HANDLING.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        IN_HANDLER.set(true);
+        Some(Self { _private: () })
+    }
+
+    /// Returns true if the current thread holds the guard.
+    pub fn is_held() -> bool {
+        IN_HANDLER.get()
+    }
+}
+
+impl Drop for HandlerGuard {
+    fn drop(&mut self) {
+        IN_HANDLER.set(false);
+        HANDLING.store(false, Ordering::Release);
+    }
+}
+
+/// A minimal report queued in the spool when the full capture fails.
+///
+/// The JSON and file name are built once; [`FallbackReport::write`] only
+/// patches the exception code, address, and crash time into a copy on the
+/// stack.
+#[derive(Debug)]
+pub struct FallbackReport {
+    json: Vec<u8>,
+    code_at: usize,
+    address_at: [usize; 2],
+    millis_at: usize,
+    file: SpoolFile,
+}
+
+impl FallbackReport {
+    /// Prepares a fallback report for a game, to be queued in `spool_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spool directory cannot be created or the
+    /// report would exceed its fixed size.
+    pub fn prepare(game_id: &str, game_version: &str, spool_dir: &Path) -> io::Result<Self> {
+        let file = SpoolFile::prepare(spool_dir, "fallback")?;
+
+        let mut json = Vec::with_capacity(MAX_FALLBACK_LEN);
+        let mut push = |s: &str| json.extend_from_slice(s.as_bytes());
+        push(r#"{"schemaVersion":1,"gameId":"#);
+        push(&serde_json::to_string(game_id)?);
+        push(r#","gameVersion":"#);
+        push(&serde_json::to_string(game_version)?);
+        push(r#","stackTrace":"[0] unknown+0x0 (0x"#);
+        let first_address_at = json.len();
+        json.extend_from_slice(&[b'0'; 16]);
+        json.extend_from_slice(br#")\n","exceptionCode":"0x"#);
+        let code_at = json.len();
+        json.extend_from_slice(&[b'0'; 8]);
+        json.extend_from_slice(br#"","exceptionAddress":"0x"#);
+        let second_address_at = json.len();
+        json.extend_from_slice(&[b'0'; 16]);
+        json.extend_from_slice(br#"","loadOrderJson":"[]","pluginCount":0,"#);
+        json.extend_from_slice(
+            br#""notes":"Fallback report: the crash handler could not capture the stack.","#,
+        );
+        json.extend_from_slice(br#""crashedAt":"#);
+        let millis_at = json.len();
+        json.extend_from_slice(&[b'1'; MILLIS_DIGITS]);
+        json.push(b'}');
+
+        if json.len() > MAX_FALLBACK_LEN {
+            return Err(io::Error::other("fallback report too large"));
+        }
+
+        Ok(Self {
+            json,
+            code_at,
+            address_at: [first_address_at, second_address_at],
+            millis_at,
+            file,
+        })
+    }
+
+    /// Writes the report to the spool for an exception.
+    ///
+    /// Does not allocate. Returns false if the file could not be written.
+    pub fn write(&self, code: u32, address: u64) -> bool {
+        let millis = now_millis();
+
+        let mut json = [0u8; MAX_FALLBACK_LEN];
+        let json = &mut json[..self.json.len()];
+        json.copy_from_slice(&self.json);
+        write_hex(&mut json[self.code_at..self.code_at + 8], code.into());
+        for at in self.address_at {
+            write_hex(&mut json[at..at + 16], address);
+        }
+        write_dec(
+            &mut json[self.millis_at..self.millis_at + MILLIS_DIGITS],
+            millis,
+        );
+
+        self.file.write(millis, json)
+    }
+}
+
+/// A spool file path built ahead of a crash. Only the timestamp digits are
+/// patched in when the file is written.
+#[derive(Debug)]
+pub(crate) struct SpoolFile {
+    path: Vec<u16>,
+    millis_at: usize,
+}
+
+impl SpoolFile {
+    /// Prepares a path in `spool_dir` named like `Spool::enqueue` names its
+    /// files, so flush picks it up in order, with `kind` in place of the
+    /// sequence number.
+    pub(crate) fn prepare(spool_dir: &Path, kind: &str) -> io::Result<Self> {
+        fs::create_dir_all(spool_dir)?;
+
+        let tail = format!("-{}-{}.json", std::process::id(), kind);
+        let name = format!("{}{}", "0".repeat(20), tail);
+        let path: Vec<u16> = spool_dir
+            .join(name)
+            .to_string_lossy()
+            .encode_utf16()
+            .collect();
+        if path.len() >= MAX_PATH_LEN {
+            return Err(io::Error::other("spool path too long"));
+        }
+
+        let millis_at = path.len() - tail.len() - MILLIS_DIGITS;
+        Ok(Self { path, millis_at })
+    }
+
+    /// Writes `contents` to the file for a crash at `millis`, without
+    /// allocating. Returns false if the file could not be written.
+    pub(crate) fn write(&self, millis: u64, contents: &[u8]) -> bool {
+        let mut path = [0u16; MAX_PATH_LEN];
+        let path = &mut path[..self.path.len()];
+        path.copy_from_slice(&self.path);
+        let at = self.millis_at;
+        write_dec(&mut path[at..at + MILLIS_DIGITS], millis);
+
+        write_file(path, contents)
+    }
+
+    /// Removes the file written for a crash at `millis`, if any.
+    pub(crate) fn remove(&self, millis: u64) {
+        let mut path = self.path.clone();
+        let at = self.millis_at;
+        write_dec(&mut path[at..at + MILLIS_DIGITS], millis);
+        let _ = fs::remove_file(String::from_utf16_lossy(&path));
+    }
+}
+
+/// Milliseconds since the Unix epoch, the crash time spool files are named by.
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Prepares the process-wide fallback report, in the default spool.
+///
+/// Game plugins call this when registering their handler. Later calls are
+/// ignored.
+///
+/// # Errors
+///
+/// Returns an error if the report cannot be prepared.
+pub fn prepare_fallback(game_id: &str, game_version: &str) -> io::Result<()> {
+    if FALLBACK.get().is_none() {
+        let report = FallbackReport::prepare(game_id, game_version, &Spool::default_dir())?;
+        let _ = FALLBACK.set(report);
+    }
+    Ok(())
+}
+
+/// Writes the prepared fallback report for an exception.
+///
+/// Skipped (returning true) when [`crate::crash_arena`] already queued a
+/// record with the stack for this crash. Returns false if none was prepared
+/// or it could not be written.
+pub fn write_fallback(code: u32, address: u64) -> bool {
+    if crash_arena::has_record() {
+        return true;
+    }
+    FALLBACK
+        .get()
+        .is_some_and(|report| report.write(code, address))
+}
+
+/// Writes uppercase hex digits of `value` right-aligned into `dst`.
+fn write_hex(dst: &mut [u8], mut value: u64) {
+    for byte in dst.iter_mut().rev() {
+        *byte = b"0123456789ABCDEF"[(value & 0xF) as usize];
+        value >>= 4;
+    }
+}
+
+/// Writes decimal digits of `value` right-aligned into `dst`.
+fn write_dec<T: From<u8>>(dst: &mut [T], mut value: u64) {
+    for digit in dst.iter_mut().rev() {
+        *digit = T::from(b'0' + (value % 10) as u8);
+        value /= 10;
+    }
+}
+
+/// Writes `contents` to a new file at the UTF-16 `path` with raw Win32 calls.
+#[cfg(windows)]
+fn write_file(path: &[u16], contents: &[u8]) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, GENERIC_WRITE, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CREATE_NEW, CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_SHARE_NONE, WriteFile,
+    };
+    use windows::core::PCWSTR;
+
+    // Prepare checked the path is shorter than the buffer, so it ends in NUL
+    let mut path_z = [0u16; MAX_PATH_LEN];
+    path_z[..path.len()].copy_from_slice(path);
+
+    // SAFETY: path_z is null-terminated and outlives the call; the handle is
+    // closed on every path after it is opened.
+    unsafe {
+        let Ok(file) = CreateFileW(
+            PCWSTR::from_raw(path_z.as_ptr()),
+            GENERIC_WRITE.0,
+            FILE_SHARE_NONE,
+            None,
+            CREATE_NEW,
+            FILE_ATTRIBUTE_NORMAL,
+            HANDLE::default(),
+        ) else {
+            return false;
+        };
+        let mut written = 0u32;
+        let ok = WriteFile(file, Some(contents), Some(&mut written), None).is_ok()
+            && written as usize == contents.len();
+        let _ = CloseHandle(file);
+        ok
+    }
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+fn write_file(path: &[u16], contents: &[u8]) -> bool {
+    String::from_utf16(path).is_ok_and(|path| fs::write(path, contents).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crash_report::CreateCrashReport;
+
+    #[test]
+    fn guard_rejects_reentry() {
+        let guard = HandlerGuard::enter().unwrap();
+        assert!(HandlerGuard::is_held());
+        assert!(HandlerGuard::enter().is_none());
+        drop(guard);
+
+        assert!(!HandlerGuard::is_held());
+        assert!(HandlerGuard::enter().is_some());
+    }
+
+    #[test]
+    fn fallback_report_is_a_valid_pending_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = FallbackReport::prepare("skyrim-se", "1.6.1170", dir.path()).unwrap();
+        assert!(report.write(0xC0000005, 0x7FF6_D4B3_C2A0));
+
+        let spool = Spool::new(dir.path());
+        let pending = spool.pending();
+        assert_eq!(pending.len(), 1);
+
+        let parsed: CreateCrashReport = spool.load(&pending[0]).unwrap();
+        assert_eq!(parsed.game_id, "skyrim-se");
+        assert_eq!(parsed.game_version, "1.6.1170");
+        assert_eq!(parsed.exception_code.as_deref(), Some("0xC0000005"));
+        assert_eq!(
+            parsed.exception_address.as_deref(),
+            Some("0x00007FF6D4B3C2A0")
+        );
+        assert_eq!(parsed.stack_trace, "[0] unknown+0x0 (0x00007FF6D4B3C2A0)\n");
+        assert!(parsed.crashed_at > 1_600_000_000_000);
+
+        let name = pending[0]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        assert!(name.starts_with(&format!("0000000{}", parsed.crashed_at)));
+    }
+}
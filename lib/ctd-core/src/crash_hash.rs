@@ -0,0 +1,191 @@
+//! Crash signature hashing.
+//!
+//! Mirrors `computeCrashHash` in `api/src/lib/crash-hash.ts` so the client
+//! can look up a crash's signature (e.g., for known issues) and get the same
+//! hash the server assigns on submission.
+
+use sha2::{Digest, Sha256};
+
+/// Number of non-system frames included in the hash.
+const HASHED_FRAMES: usize = 10;
+
+/// Length of the hex hash, in characters.
+const HASH_LEN: usize = 16;
+
+/// Modules ignored when hashing, since nearly every crash passes through them.
+const SYSTEM_MODULES: &[&str] = &[
+    "ntdll.dll",
+    "kernel32.dll",
+    "kernelbase.dll",
+    "win32u.dll",
+    "user32.dll",
+    "gdi32.dll",
+    "msvcrt.dll",
+    "ucrtbase.dll",
+    "vcruntime140.dll",
+    "msvcp140.dll",
+];
+
+/// A `module+offset` frame parsed from a stack trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashFrame<'a> {
+    /// Module name as written in the trace.
+    pub module: &'a str,
+    /// Offset text as written in the trace (e.g., "0x12345").
+    pub offset: &'a str,
+}
+
+/// Returns true if a module is a Windows system or CRT module.
+pub fn is_system_module(module: &str) -> bool {
+    SYSTEM_MODULES
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(module))
+}
+
+/// Parses `module+offset` frames in Crash Logger and .NET Script Framework formats.
+///
+/// Lines in other formats are skipped.
+pub fn parse_frames(stack_trace: &str) -> Vec<HashFrame<'_>> {
+    stack_trace
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| parse_crash_logger_frame(line).or_else(|| parse_bare_frame(line)))
+        .collect()
+}
+
+/// Computes the 16-character crash hash for a stack trace.
+///
+/// Hashes the first ten non-system frames as `module+offset` joined with
+/// `|`, with module names lowercased. Traces without recognisable frames
+/// hash the whole text instead.
+pub fn compute_crash_hash(stack_trace: &str) -> String {
+    let normalized = parse_frames(stack_trace)
+        .iter()
+        .filter(|f| !is_system_module(f.module))
+        .take(HASHED_FRAMES)
+        .map(|f| format!("{}+{}", f.module.to_lowercase(), f.offset))
+        .collect::<Vec<_>>()
+        .join("|");
+
+    let input = if normalized.is_empty() {
+        stack_trace
+    } else {
+        &normalized
+    };
+
+    let mut hash = hex::encode(Sha256::digest(input.as_bytes()));
+    hash.truncate(HASH_LEN);
+    hash
+}
+
+/// Matches `[N] 0xADDRESS module+offset` anywhere in a line.
+fn parse_crash_logger_frame(line: &str) -> Option<HashFrame<'_>> {
+    line.match_indices('[').find_map(|(start, _)| {
+        let rest = &line[start + 1..];
+        let rest = rest.strip_prefix(|c: char| c.is_ascii_digit())?;
+        let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+        let rest = rest.strip_prefix(']')?;
+
+        let rest = strip_whitespace(rest)?;
+        let rest = rest.strip_prefix("0x")?;
+        let hex_len = rest
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(rest.len());
+        if hex_len == 0 {
+            return None;
+        }
+
+        let rest = strip_whitespace(&rest[hex_len..])?;
+        split_module_offset(rest)
+    })
+}
+
+/// Matches a line that is exactly `module+offset`.
+fn parse_bare_frame(line: &str) -> Option<HashFrame<'_>> {
+    let frame = split_module_offset(line)?;
+    let consumed = frame.module.len() + 1 + frame.offset.len();
+    (consumed == line.len()).then_some(frame)
+}
+
+/// Splits the leading `module+offset` token, stopping at whitespace.
+fn split_module_offset(text: &str) -> Option<HashFrame<'_>> {
+    let module_len = text.find(|c: char| c.is_whitespace() || c == '+')?;
+    if module_len == 0 || !text[module_len..].starts_with('+') {
+        return None;
+    }
+
+    let offset_text = &text[module_len + 1..];
+    let offset_len = offset_text
+        .find(char::is_whitespace)
+        .unwrap_or(offset_text.len());
+    if offset_len == 0 {
+        return None;
+    }
+
+    Some(HashFrame {
+        module: &text[..module_len],
+        offset: &offset_text[..offset_len],
+    })
+}
+
+/// Strips one or more leading whitespace characters, failing if there are none.
+fn strip_whitespace(text: &str) -> Option<&str> {
+    let trimmed = text.trim_start();
+    (trimmed.len() < text.len()).then_some(trimmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_crash_logger_frames() {
+        let frames =
+            parse_frames("[0] 0x7FF712345678 SkyrimSE.exe+0x12345\n\t[1] 0x7FF8 ntdll.dll+0x10");
+        assert_eq!(
+            frames,
+            vec![
+                HashFrame {
+                    module: "SkyrimSE.exe",
+                    offset: "0x12345"
+                },
+                HashFrame {
+                    module: "ntdll.dll",
+                    offset: "0x10"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_bare_frames_only_when_whole_line() {
+        assert_eq!(parse_frames("SkyrimSE.exe+12345").len(), 1);
+        assert!(parse_frames("SkyrimSE.exe+12345 (0x7FF6)").is_empty());
+        assert!(parse_frames("[0] SkyrimSE.exe+0x1234 (0x7FF6)").is_empty());
+    }
+
+    #[test]
+    fn skips_system_modules() {
+        let with_system = "[0] 0x1 KERNELBASE.dll+0x10\n[1] 0x2 SkyrimSE.exe+0x20";
+        let without = "[0] 0x2 SkyrimSE.exe+0x20";
+        assert_eq!(compute_crash_hash(with_system), compute_crash_hash(without));
+    }
+
+    #[test]
+    fn hash_matches_server() {
+        // sha256("skyrimse.exe+0x12345|skse64_1_6_1170.dll+0xabc")
+        let trace = "[0] 0x7FF712345678 SkyrimSE.exe+0x12345\n[1] 0x7FF8 skse64_1_6_1170.dll+0xabc";
+        let expected = hex::encode(Sha256::digest(
+            b"skyrimse.exe+0x12345|skse64_1_6_1170.dll+0xabc",
+        ));
+        assert_eq!(compute_crash_hash(trace), expected[..16]);
+    }
+
+    #[test]
+    fn falls_back_to_whole_trace() {
+        let trace = "[0] SkyrimSE.exe+0x1234 (0x7FF6A0001234)";
+        let expected = hex::encode(Sha256::digest(trace.as_bytes()));
+        assert_eq!(compute_crash_hash(trace), expected[..16]);
+    }
+}
@@ -0,0 +1,443 @@
+//! Crash Logger-compatible text logs.
+//!
+//! Analyzers in the modding community (CLASSIC, the web and Discord bot
+//! log readers) only understand the `crash-*.log` layout written by Crash
+//! Logger SSE/AE and Buffout 4. With `[logging] crash_log = true`, plugins
+//! also render each crash report in that layout next to their own log, so
+//! existing triage tooling works on CTD captures without a second crash
+//! logger installed.
+//!
+//! The log is generated from the report, so it only carries what CTD
+//! captured: there are no register or stack dumps, and frames rewritten as
+//! Address Library IDs keep the ID instead of the raw offset. Logs are
+//! written to the CTD log directory rather than `SKSE`/`F4SE`, so
+//! [`crate::coexistence`] never imports them back.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use crate::api_client::CLIENT_VERSION;
+use crate::config::Config;
+use crate::crash_report::CreateCrashReport;
+use crate::import::exception_name;
+use crate::load_order::{LoadOrder, ModList};
+
+/// How a game's crash logger names itself in its logs.
+struct LogStyle {
+    /// Game name on the first line (e.g., "Skyrim SSE").
+    game: &'static str,
+    /// Crash logger name on the second line (e.g., "CrashLoggerSSE").
+    logger: &'static str,
+    /// Script extender prefix of the native plugin section (e.g., "SKSE").
+    script_extender: &'static str,
+}
+
+/// Returns the log style for a game, if a community crash logger exists for it.
+fn log_style(game_id: &str) -> Option<LogStyle> {
+    match game_id {
+        "skyrim-se" => Some(LogStyle {
+            game: "Skyrim SSE",
+            logger: "CrashLoggerSSE",
+            script_extender: "SKSE",
+        }),
+        "fallout4" => Some(LogStyle {
+            game: "Fallout 4",
+            logger: "Buffout 4",
+            script_extender: "F4SE",
+        }),
+        _ => None,
+    }
+}
+
+/// Returns true if a Crash Logger-style log can be rendered for the report:
+/// a crash in a game that has a community crash logger.
+pub fn supports(report: &CreateCrashReport) -> bool {
+    report.report_type.is_crash() && log_style(&report.game_id).is_some()
+}
+
+/// Renders a report in the Crash Logger SSE/AE (Skyrim) or Buffout 4
+/// (Fallout 4) layout.
+///
+/// Returns `None` if [`supports`] is false.
+pub fn render(report: &CreateCrashReport) -> Option<String> {
+    if !supports(report) {
+        return None;
+    }
+    let style = log_style(&report.game_id)?;
+    let frames: Vec<Frame> = report
+        .stack_trace
+        .lines()
+        .filter_map(Frame::parse)
+        .collect();
+
+    let mut out = String::new();
+    out.push_str(&format!("{} v{}\n", style.game, report.game_version));
+    out.push_str(&format!(
+        "{} compatible log written by CTD v{}\n",
+        style.logger,
+        report.reporter_version.as_deref().unwrap_or(CLIENT_VERSION)
+    ));
+    out.push('\n');
+    out.push_str(&exception_line(report, &frames));
+    out.push_str("\n\n");
+
+    out.push_str("SYSTEM SPECS:\n");
+    if let Some(os) = &report.os_version {
+        out.push_str(&format!("\tOS: {}\n", os));
+    }
+    out.push('\n');
+
+    out.push_str("PROBABLE CALL STACK:\n");
+    for (i, frame) in frames.iter().enumerate() {
+        out.push_str(&format!("\t[{:>2}] {}\n", i, frame.render()));
+    }
+    out.push('\n');
+
+    out.push_str(&format!("{} PLUGINS:\n", style.script_extender));
+    for plugin in report.native_plugins.iter() {
+        match &plugin.version {
+            Some(version) => out.push_str(&format!("\t{} v{}\n", plugin.name, version)),
+            None => out.push_str(&format!("\t{}\n", plugin.name)),
+        }
+    }
+    out.push('\n');
+
+    out.push_str(&plugins_section(&report.load_order_json));
+    Some(out)
+}
+
+/// Returns the file name Crash Logger would use for a crash at `crashed_at`
+/// (Unix milliseconds): `crash-YYYY-MM-DD-HH-MM-SS.log`, in UTC.
+pub fn file_name(crashed_at: u64) -> String {
+    let secs = crashed_at / 1000;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!(
+        "crash-{:04}-{:02}-{:02}-{:02}-{:02}-{:02}.log",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Writes the rendered log into `dir`, returning its path.
+///
+/// Returns `Ok(None)` if [`supports`] is false.
+///
+/// # Errors
+///
+/// Returns an error if the directory or file cannot be written.
+pub fn write(report: &CreateCrashReport, dir: &Path) -> io::Result<Option<PathBuf>> {
+    let Some(contents) = render(report) else {
+        return Ok(None);
+    };
+
+    let path = dir.join(file_name(report.crashed_at));
+    fs::create_dir_all(dir)?;
+    fs::write(&path, contents)?;
+    Ok(Some(path))
+}
+
+/// Writes the log into the configured log directory when
+/// `[logging] crash_log` is on, logging instead of failing.
+pub fn write_if_enabled(report: &CreateCrashReport) -> Option<PathBuf> {
+    let config = Config::load().unwrap_or_default();
+    if !config.logging.crash_log {
+        return None;
+    }
+
+    match write(report, &config.logging.log_dir()) {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("Failed to write crash log: {}", e);
+            None
+        }
+    }
+}
+
+/// A frame parsed from CTD's `[i] module+0xOFFSET (0xADDR) symbol` format.
+pub(crate) struct Frame<'a> {
+    pub(crate) address: u64,
+    /// `module+0xOFFSET`, `module (ID n+0xD)`, or `unknown+0x...`.
+    pub(crate) location: &'a str,
+    pub(crate) symbol: &'a str,
+}
+
+impl<'a> Frame<'a> {
+    pub(crate) fn parse(line: &'a str) -> Option<Self> {
+        let rest = line.trim().strip_prefix('[')?.split_once(']')?.1;
+
+        // The address is the last "(0x" group, as module names and
+        // Address Library IDs may carry parentheses of their own
+        let open = rest.rfind("(0x")?;
+        let close = open + rest[open..].find(')')?;
+        let address = u64::from_str_radix(&rest[open + 3..close], 16).ok()?;
+
+        Some(Self {
+            address,
+            location: rest[..open].trim(),
+            symbol: rest[close + 1..].trim(),
+        })
+    }
+
+    /// Returns the module name, unless the frame is outside every module.
+    pub(crate) fn module(&self) -> Option<&'a str> {
+        self.module_offset().map(|(module, _)| module)
+    }
+
+    /// Returns the module and its Crash Logger offset column.
+    fn module_offset(&self) -> Option<(&'a str, Option<u64>)> {
+        if let Some((module, offset)) = self.location.split_once("+0x")
+            && let Ok(offset) = u64::from_str_radix(offset, 16)
+        {
+            return (module != "unknown").then_some((module, Some(offset)));
+        }
+        // Address Library frame: "SkyrimSE.exe (ID 50988+0x12)"
+        let (module, _) = self.location.split_once(" (ID ")?;
+        Some((module, None))
+    }
+
+    /// Formats the frame after its index, like
+    /// `0x7FF6D4B3C2A0      SkyrimSE.exe+02FC2A0\tsymbol`.
+    fn render(&self) -> String {
+        let mut line = format!("0x{:X}", self.address);
+        if let Some((module, offset)) = self.module_offset() {
+            line.push_str("      ");
+            line.push_str(&location(module, offset));
+        }
+        if let Some(id) = self
+            .location
+            .split_once(" (ID ")
+            .map(|(_, id)| id.trim_end_matches(')'))
+        {
+            line.push_str(&format!(" -> {}", id));
+        }
+        if !self.symbol.is_empty() {
+            line.push('\t');
+            line.push_str(self.symbol);
+        }
+        line
+    }
+}
+
+/// Formats `module+OFFSET` with Crash Logger's 7-digit offset.
+fn location(module: &str, offset: Option<u64>) -> String {
+    match offset {
+        Some(offset) => format!("{}+{:07X}", module, offset),
+        None => module.to_string(),
+    }
+}
+
+/// Formats the `Unhandled exception` line, locating the exception address
+/// through the frame that contains it.
+fn exception_line(report: &CreateCrashReport, frames: &[Frame]) -> String {
+    let code = report.exception_code.as_deref().unwrap_or("0x00000000");
+    let name = exception_name(code).unwrap_or(code);
+
+    let address = report
+        .exception_address
+        .as_deref()
+        .and_then(|a| u64::from_str_radix(a.trim_start_matches("0x"), 16).ok());
+    let mut line = format!(
+        "Unhandled exception \"{}\" at 0x{:X}",
+        name,
+        address.unwrap_or_default()
+    );
+
+    let faulting = frames
+        .iter()
+        .find(|f| Some(f.address) == address)
+        .and_then(Frame::module_offset)
+        .map(|(module, offset)| location(module, offset))
+        .or_else(|| report.faulting_module.clone());
+    if let Some(faulting) = faulting {
+        line.push(' ');
+        line.push_str(&faulting);
+    }
+    line
+}
+
+/// Formats the `PLUGINS:` section with Crash Logger's load order indices:
+/// `[00]` for full plugins and `[FE:000]` for light ones.
+fn plugins_section(load_order_json: &str) -> String {
+    let plugins: Vec<(String, bool)> = match ModList::from_json(load_order_json) {
+        Ok(mods) => mods
+            .iter()
+            .filter(|m| m.enabled != Some(false))
+            .map(|m| (m.name.clone(), m.is_light == Some(true)))
+            .collect(),
+        Err(_) => LoadOrder::from_json(load_order_json)
+            .map(|order| {
+                order
+                    .iter()
+                    .filter(|e| e.enabled != Some(false))
+                    .map(|e| (e.name.clone(), e.is_light == Some(true)))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    // Only game plugins take a load order slot
+    let plugins: Vec<(String, bool)> = plugins
+        .into_iter()
+        .filter_map(|(name, light)| {
+            let ext = Path::new(&name)
+                .extension()?
+                .to_string_lossy()
+                .to_ascii_lowercase();
+            let light = light || ext == "esl";
+            matches!(ext.as_str(), "esm" | "esp" | "esl").then_some((name, light))
+        })
+        .collect();
+
+    let light = plugins.iter().filter(|(_, light)| *light).count();
+    let mut out = format!(
+        "PLUGINS:\n\tLight: {}\tRegular: {}\tTotal: {}\n",
+        light,
+        plugins.len() - light,
+        plugins.len()
+    );
+
+    let (mut regular_index, mut light_index) = (0, 0);
+    for (name, light) in &plugins {
+        if *light {
+            out.push_str(&format!("\t[FE:{:03X}] {}\n", light_index, name));
+            light_index += 1;
+        } else {
+            out.push_str(&format!("\t[{:02X}]     {}\n", regular_index, name));
+            regular_index += 1;
+        }
+    }
+    out
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's algorithm, with eras of 400 years starting in March
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crash_report::ReportType;
+    use crate::import;
+    use crate::load_order::ModEntry;
+
+    fn sample_report() -> CreateCrashReport {
+        let mods = ModList::from_entries(vec![
+            ModEntry::new("Skyrim.esm", "a", 1).with_index(0),
+            ModEntry::new("ccBGSSSE001-Fish.esm", "b", 1)
+                .with_index(1)
+                .with_light(true),
+            ModEntry::new("SkyUI_SE.esp", "c", 1).with_index(2),
+        ]);
+        let mut plugins = ModList::new();
+        plugins.push(ModEntry::new("EngineFixes.dll", "d", 1).with_version("6.2.0"));
+
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace(
+                "[ 0] SkyrimSE.exe+0x2FC2A0 (0x00007FF6D4B3C2A0) BSTArray::Grow+0x10\n\
+                 [ 1] SkyrimSE.exe (ID 50988+0x12) (0x00007FF6D4B40000)\n\
+                 [ 2] unknown+0x0 (0x0000000012345678)",
+            )
+            .exception_code("0xC0000005")
+            .exception_address("0x00007FF6D4B3C2A0")
+            .faulting_module("SkyrimSE.exe")
+            .os_version("Windows 11 (10.0.22631)")
+            .load_order_v2(mods)
+            .native_plugins(plugins)
+            .crashed_at(1_704_067_200_000)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn rendered_log_imports_back() {
+        let log = render(&sample_report()).unwrap();
+        assert!(log.starts_with("Skyrim SSE v1.6.1170\nCrashLoggerSSE"));
+        assert!(log.contains(
+            "Unhandled exception \"EXCEPTION_ACCESS_VIOLATION\" at 0x7FF6D4B3C2A0 SkyrimSE.exe+02FC2A0"
+        ));
+        assert!(
+            log.contains("\t[ 0] 0x7FF6D4B3C2A0      SkyrimSE.exe+02FC2A0\tBSTArray::Grow+0x10")
+        );
+        assert!(log.contains("\t[ 1] 0x7FF6D4B40000      SkyrimSE.exe -> 50988+0x12"));
+        assert!(log.contains("\t[ 2] 0x12345678\n"));
+        assert!(log.contains("SKSE PLUGINS:\n\tEngineFixes.dll v6.2.0\n"));
+        assert!(log.contains("\tLight: 1\tRegular: 2\tTotal: 3"));
+        assert!(log.contains("\t[01]     SkyUI_SE.esp"));
+
+        let imported = import::parse_crash_log(&log).unwrap();
+        assert_eq!(imported.game_id, "skyrim-se");
+        assert_eq!(imported.game_version, "1.6.1170");
+        assert_eq!(imported.exception_code.as_deref(), Some("0xC0000005"));
+        assert_eq!(imported.faulting_module.as_deref(), Some("SkyrimSE.exe"));
+        assert_eq!(
+            imported.os_version.as_deref(),
+            Some("Windows 11 (10.0.22631)")
+        );
+        assert_eq!(
+            imported.plugins,
+            ["Skyrim.esm", "ccBGSSSE001-Fish.esm", "SkyUI_SE.esp"]
+        );
+        assert!(
+            imported
+                .stack_trace
+                .starts_with("[0] SkyrimSE.exe+0x2FC2A0 (0x7FF6D4B3C2A0)")
+        );
+    }
+
+    #[test]
+    fn fallout4_uses_buffout_layout() {
+        let mut report = sample_report();
+        report.game_id = "fallout4".into();
+        let log = render(&report).unwrap();
+        assert!(log.starts_with("Fallout 4 v1.6.1170\nBuffout 4"));
+        assert!(log.contains("F4SE PLUGINS:"));
+        assert_eq!(
+            import::detect_format(&log),
+            Some(import::CrashLogFormat::Buffout4)
+        );
+    }
+
+    #[test]
+    fn only_crashes_in_supported_games_render() {
+        let mut hang = sample_report();
+        hang.report_type = ReportType::Hang;
+        assert!(render(&hang).is_none());
+
+        let mut other = sample_report();
+        other.game_id = "cyberpunk2077".into();
+        assert!(!supports(&other));
+    }
+
+    #[test]
+    fn file_name_uses_crash_time() {
+        assert_eq!(
+            file_name(1_704_067_200_000),
+            "crash-2024-01-01-00-00-00.log"
+        );
+        assert_eq!(
+            file_name(1_709_210_096_000),
+            "crash-2024-02-29-12-34-56.log"
+        );
+    }
+}
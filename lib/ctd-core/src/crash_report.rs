@@ -2,15 +2,113 @@
 //!
 //! These types exactly match the API's `createCrashReportSchema`.
 
+use std::collections::BTreeMap;
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+use crate::abi;
+use crate::api_client::CLIENT_VERSION;
+use crate::file_hash::HashAlgorithm;
+use crate::frame_times;
+use crate::graphics_injectors::GraphicsInjector;
+use crate::identity;
 use crate::load_order::{LoadOrder, ModList};
+use crate::modpack::{self, Modpack};
+use crate::save_game::SaveGameInfo;
+use crate::triage;
+use crate::uptime;
 use crate::{CtdError, Result};
 
 /// Current schema version for crash reports.
 /// - v1: LoadOrderEntry with name/enabled/index only
 /// - v2: ModEntry with file_hash/file_size/version for pattern detection
-const CURRENT_SCHEMA_VERSION: u32 = 2;
+///   (plus the optional, additive `isLight` flag)
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Maximum length of a report's notes, including notes appended later.
+pub const MAX_NOTES_LEN: usize = 5000;
+
+/// Maximum length of a report's `disassembly`.
+pub const MAX_DISASSEMBLY_LEN: usize = 2000;
+
+/// Maximum number of entries in a report's `nativePlugins` section.
+pub const MAX_NATIVE_PLUGINS: usize = 1000;
+
+/// Maximum number of entries in a report's `graphicsInjectors` section.
+pub const MAX_GRAPHICS_INJECTORS: usize = 16;
+
+/// Maximum number of GPUs in a report's `graphics` section.
+pub const MAX_GPU_ADAPTERS: usize = 8;
+
+/// Maximum number of upscaler DLLs in a report's `graphics` section.
+pub const MAX_UPSCALERS: usize = 16;
+
+/// Maximum number of frames in a report's `papyrusStacks` section.
+pub const MAX_PAPYRUS_FRAMES: usize = 256;
+
+/// Maximum number of entries in a report's `recentAssets` section.
+pub const MAX_RECENT_ASSETS: usize = 64;
+
+/// Maximum number of entries in a report's `handledExceptions` section.
+pub const MAX_HANDLED_EXCEPTIONS: usize = 32;
+
+/// Maximum number of entries in a report's `hardwareEvents` section.
+pub const MAX_HARDWARE_EVENTS: usize = 16;
+
+/// Maximum number of entries in a report's `knownBadModules` section.
+pub const MAX_KNOWN_BAD_MODULES: usize = 32;
+
+/// Maximum number of entries in a report's `breadcrumbs` section.
+pub const MAX_BREADCRUMBS: usize = 32;
+
+/// Maximum number of entries in a report's `modContext` section.
+pub const MAX_MOD_CONTEXT: usize = 32;
+
+/// Maximum number of entries in a report's `modErrors` section.
+pub const MAX_MOD_ERRORS: usize = 16;
+
+/// Maximum number of entries in a report's `extras` section.
+pub const MAX_EXTRAS: usize = 32;
+
+/// Maximum length of an `extras` key; longer ones are dropped.
+const MAX_EXTRA_KEY_LEN: usize = 100;
+
+/// Maximum length of an `extras` value; longer ones are truncated.
+const MAX_EXTRA_VALUE_LEN: usize = 500;
+
+/// Maximum number of notes other mods add to a report's `notes`.
+pub const MAX_MOD_NOTES: usize = 8;
+
+/// Maximum length of a breadcrumb, mod error, or mod note.
+pub const MAX_MOD_MESSAGE_LEN: usize = 500;
+
+/// Maximum number of entries in a report's `hookedFunctions` section.
+pub const MAX_HOOKED_FUNCTIONS: usize = 32;
+
+/// Maximum number of entries in a report's `probableObjects` section.
+pub const MAX_PROBABLE_OBJECTS: usize = 64;
+
+/// Maximum number of entries in a report's `exceptionChain` section.
+pub const MAX_EXCEPTION_CHAIN: usize = 32;
+
+/// Maximum length of the triage hint.
+pub const MAX_TRIAGE_HINT_LEN: usize = 500;
+
+/// Maximum number of recent frame times in `frameTimes`.
+pub const MAX_RECENT_FRAMES: usize = 16;
+
+/// Maximum number of entries in a report's `frameworks` section.
+pub const MAX_FRAMEWORKS: usize = 32;
+
+/// Maximum number of entries in a report's `conflicts` section.
+pub const MAX_CONFLICTS: usize = 64;
+
+/// Maximum number of entries in a report's `attachments` section.
+pub const MAX_ATTACHMENTS: usize = 8;
+
+/// Maximum length of one attachment's content, in bytes.
+pub const MAX_ATTACHMENT_LEN: usize = 64 * 1024;
 
 /// A crash report to be submitted to the API.
 ///
@@ -27,6 +125,10 @@ pub struct CreateCrashReport {
     /// Required, min length 1.
     pub game_id: String,
 
+    /// What the report is about. Omitted for crashes, the default.
+    #[serde(default, skip_serializing_if = "ReportType::is_crash")]
+    pub report_type: ReportType,
+
     /// Full stack trace from the crash.
     /// Required, min length 1, max 100000.
     pub stack_trace: String,
@@ -44,18 +146,63 @@ pub struct CreateCrashReport {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exception_address: Option<String>,
 
+    /// Instructions around the exception address, one per line, with the
+    /// faulting one marked by `>` (see `ctd_capture::disasm`). Max 2000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disassembly: Option<String>,
+
+    /// The memory around the exception address, when no loaded module
+    /// contains it (see [`crate::memory_map`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_region: Option<AddressRegion>,
+
+    /// Kind of crash, when it was recognized (e.g., "null-deref"; see
+    /// [`crate::triage`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crash_category: Option<CrashCategory>,
+
+    /// What the crash category means for this crash and what to try, in
+    /// plain words. Max 500.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub triage_hint: Option<String>,
+
+    /// Why the GPU device was lost, for `gpu` crashes
+    /// (e.g., "0x887A0006 DXGI_ERROR_DEVICE_HUNG"). Max 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_removed_reason: Option<String>,
+
+    /// The failed read behind an `IN_PAGE_ERROR` crash (see
+    /// [`crate::in_page`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_page_error: Option<InPageError>,
+
     /// Module that caused the crash (e.g., "SkyrimSE.exe").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub faulting_module: Option<String>,
 
+    /// Mod that most likely shipped the faulting module (see [`crate::blame`]).
+    /// Max 255.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspected_mod: Option<String>,
+
     /// Game version string (e.g., "1.6.1170").
     /// Required, min length 1, max 50.
     pub game_version: String,
 
+    /// Normalized runtime edition (e.g., "SE", "AE", "VR", "GOG").
+    /// Max 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_edition: Option<String>,
+
     /// Script extender version (e.g., "2.2.3").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub script_extender_version: Option<String>,
 
+    /// Version of the CTD client that built the report (e.g., "0.1.3").
+    /// Max 50.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reporter_version: Option<String>,
+
     /// Operating system version.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub os_version: Option<String>,
@@ -68,13 +215,615 @@ pub struct CreateCrashReport {
     /// Required, 0-10000.
     pub plugin_count: u32,
 
+    /// Script extender DLL plugins (see [`crate::native_plugins`]).
+    /// Max 1000 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "ModList::is_empty")]
+    pub native_plugins: ModList,
+
+    /// Wabbajack list or Nexus Collection the game was installed from (see
+    /// [`crate::modpack`]). Max 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modpack_name: Option<String>,
+
+    /// Version of the modpack, when known. Max 50.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modpack_version: Option<String>,
+
+    /// Algorithm behind the `fileHash` values in the load order and native
+    /// plugins (see [`crate::file_hash`]); omitted for the default,
+    /// partial SHA-256.
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_default")]
+    pub hash_algorithm: HashAlgorithm,
+
+    /// Versions of the mod frameworks most mods depend on, by name
+    /// (e.g., "ArchiveXL" -> "1.21.1.0"). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub frameworks: BTreeMap<String, String>,
+
+    /// ENB/ReShade proxy DLLs in the game root (see [`crate::graphics_injectors`]).
+    /// Max 16 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graphics_injectors: Vec<GraphicsInjector>,
+
+    /// Resolution, window mode, HDR, GPUs, and upscaler DLLs (see
+    /// [`crate::graphics`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graphics: Option<GraphicsSettings>,
+
+    /// Papyrus call stacks running when the game crashed (Skyrim).
+    /// Max 256 frames; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub papyrus_stacks: Vec<PapyrusFrame>,
+
+    /// Player location and in-game time at crash (Skyrim).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_state: Option<GameState>,
+
+    /// Frame times over the seconds before the crash (see
+    /// [`crate::frame_times`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_times: Option<FrameTimes>,
+
+    /// Asset paths the game opened last, newest first (Skyrim).
+    /// Max 64 entries of 260 characters; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_assets: Vec<String>,
+
+    /// Non-fatal exceptions raised before the crash, most frequent first
+    /// (see [`crate::exception_tally`]). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handled_exceptions: Vec<HandledException>,
+
+    /// GPU driver resets and WHEA hardware errors logged shortly before the
+    /// crash, newest first (see [`crate::hardware_events`]). Max 16 entries;
+    /// omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hardware_events: Vec<HardwareEvent>,
+
+    /// Loaded DLLs known to crash games, with why each is flagged (see
+    /// [`crate::known_bad`]). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_bad_modules: Vec<KnownBadModule>,
+
+    /// Steps other mods recorded through the C API, newest first (see
+    /// [`crate::abi`]). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breadcrumbs: Vec<Breadcrumb>,
+
+    /// Key/value context other mods set through the C API (e.g.,
+    /// "MyQuestMod.stage" -> "40"). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mod_context: BTreeMap<String, String>,
+
+    /// Errors other mods caught and reported through the C API, most
+    /// recent first (see [`crate::abi`]). Max 16 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mod_errors: Vec<ModError>,
+
+    /// Free-form context a game crate or integration attached (e.g.,
+    /// "difficulty" -> "Legendary"), for details without a field of their
+    /// own. Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extras: BTreeMap<String, String>,
+
+    /// Exported functions of the faulting module whose entry points were
+    /// patched with a jump, and where the jump leads (see
+    /// [`crate::detours`]). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooked_functions: Vec<HookedFunction>,
+
+    /// Objects with MSVC RTTI that the crashed thread's registers and stack
+    /// pointed at (see [`crate::rtti`]). Max 64 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub probable_objects: Vec<ProbableObject>,
+
+    /// Exceptions nested in the fatal one's record, then the first-chance
+    /// exceptions raised shortly before it, newest first (see
+    /// [`crate::exception_chain`]). Max 32 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exception_chain: Vec<ChainedException>,
+
+    /// Mod files installed twice, e.g. the same archive loose and in a
+    /// REDmod. Max 64 entries; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<ModConflict>,
+
+    /// Text files captured with the crash, such as the tails of script logs.
+    /// Max 8 entries of 64KB; omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+
+    /// Most recent save and its plugin differences from the load order
+    /// (see [`crate::save_game`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_game: Option<SaveGameInfo>,
+
     /// Unix timestamp (milliseconds) when the crash occurred.
     /// Required.
     pub crashed_at: u64,
 
+    /// Seconds the game process had been running (see [`crate::uptime`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uptime_secs: Option<u64>,
+
+    /// Seconds since the game last saved; omitted if it hadn't saved since
+    /// launch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secs_since_last_save: Option<u64>,
+
     /// User notes about the crash.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
+
+    /// Random ID of the installation that sent the report
+    /// (see [`crate::identity`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installation_id: Option<String>,
+
+    /// Random ID of the game launch that sent the report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+/// What a report describes.
+///
+/// Serialized as `reportType`, omitted for crashes so reports to servers
+/// that predate report types are unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportType {
+    /// The game crashed (default).
+    #[default]
+    Crash,
+    /// The game thread stopped responding (see [`crate::watchdog`]).
+    Hang,
+    /// The user reported a problem without a crash.
+    Manual,
+    /// Summary of a session that ended cleanly (see [`crate::session`]).
+    Session,
+    /// A script raised an error the game survived.
+    ScriptError,
+}
+
+impl ReportType {
+    /// Returns true for [`ReportType::Crash`].
+    pub fn is_crash(&self) -> bool {
+        *self == Self::Crash
+    }
+
+    /// Returns the name used in the API (e.g., "script-error").
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Crash => "crash",
+            Self::Hang => "hang",
+            Self::Manual => "manual",
+            Self::Session => "session",
+            Self::ScriptError => "script-error",
+        }
+    }
+}
+
+impl fmt::Display for ReportType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Trims a player's description for a [`ReportType::Manual`] report.
+///
+/// # Errors
+///
+/// Returns `CtdError::Validation` unless it is 1 to [`MAX_NOTES_LEN`]
+/// characters after trimming.
+pub fn manual_report_notes(text: &str) -> Result<&str> {
+    let text = text.trim();
+    if text.is_empty() || text.len() > MAX_NOTES_LEN {
+        return Err(CtdError::Validation(format!(
+            "Describe the problem in 1-{} characters",
+            MAX_NOTES_LEN
+        )));
+    }
+    Ok(text)
+}
+
+/// Appends notes from other mods to the user's, one per line, leaving out
+/// those that would take the notes past [`MAX_NOTES_LEN`].
+fn with_mod_notes(notes: Option<String>, mod_notes: &[String]) -> Option<String> {
+    let mut notes = notes.unwrap_or_default();
+    let mut separator = if notes.is_empty() { "" } else { "\n\n" };
+    for note in mod_notes {
+        if notes.len() + separator.len() + note.len() > MAX_NOTES_LEN {
+            break;
+        }
+        notes.push_str(separator);
+        notes.push_str(note);
+        separator = "\n";
+    }
+    (!notes.is_empty()).then_some(notes)
+}
+
+/// One frame of a Papyrus (script VM) call stack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PapyrusFrame {
+    /// VM stack the frame belongs to; frames of one stack share it.
+    pub stack_id: u32,
+    /// Position in the stack, 0 being the innermost call.
+    pub depth: u32,
+    /// Script name (e.g., "MQ101QuestScript").
+    pub script: String,
+    /// Function name.
+    pub function: String,
+    /// Source line, if the script was compiled with line info.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+/// A non-fatal exception code and how often the game raised it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandledException {
+    /// Exception code (e.g., "0xE06D7363").
+    pub code: String,
+    /// Times it was raised since the game started; saturates at `u32::MAX`.
+    pub count: u32,
+}
+
+/// A hardware or driver event from the Windows System log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareEvent {
+    /// What the event reports.
+    pub kind: HardwareEventKind,
+    /// Event source (e.g., "Display", "Microsoft-Windows-WHEA-Logger").
+    pub source: String,
+    /// Event ID (e.g., 4101 for a display driver reset).
+    pub event_id: u32,
+    /// Unix timestamp (milliseconds) when the event was logged.
+    pub occurred_at: u64,
+}
+
+/// A loaded DLL that is known to crash games.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownBadModule {
+    /// File name (e.g., "X3DAudio1_7.dll"). Max 255.
+    pub name: String,
+    /// PE file version, if the DLL has a version resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Why it is flagged and what to do about it. Max 500.
+    pub reason: String,
+}
+
+/// A step another mod recorded before the crash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    /// Mod that recorded it (e.g., "MyQuestMod"). Max 100.
+    pub source: String,
+    /// What happened. Max 500.
+    pub message: String,
+    /// Unix timestamp (milliseconds) when it was recorded.
+    pub at: u64,
+}
+
+/// An error another mod caught and reported instead of crashing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModError {
+    /// Mod that reported it. Max 100.
+    pub source: String,
+    /// The error. Max 500.
+    pub message: String,
+    /// Times the same error was reported.
+    pub count: u32,
+    /// Unix timestamp (milliseconds) when it was last reported.
+    pub last_at: u64,
+}
+
+/// The memory region around an address outside every loaded module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressRegion {
+    /// Start of the region (e.g., "0x1F2A0000").
+    pub base: String,
+    /// Size of the region in bytes.
+    pub size: u64,
+    /// "commit", "reserve", or "free".
+    pub state: String,
+    /// Page protection of committed memory (e.g., "PAGE_EXECUTE_READWRITE").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protection: Option<String>,
+    /// "image", "mapped", or "private"; absent for free memory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Nearest module loaded below the address, with the offset from its
+    /// base (e.g., "EngineFixes.dll+0x1A2B3C").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_before: Option<String>,
+    /// Nearest module loaded above the address, with the distance to its
+    /// base (e.g., "SkyrimSE.exe-0x4000").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_after: Option<String>,
+}
+
+/// An exported function whose entry point was patched with a jump.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookedFunction {
+    /// Exported name (e.g., "D3D11CreateDevice").
+    pub function: String,
+    /// Where the jump leads after any trampoline stubs, as
+    /// "module+0xOFFSET" or a bare address outside every module.
+    pub target: String,
+    /// Loaded module the target is in, the likely hooking mod's DLL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+/// An object with MSVC RTTI found through a register or stack slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbableObject {
+    /// Where the pointer was found (e.g., "RCX", "RSP+0x28").
+    pub location: String,
+    /// The object's address (e.g., "0x1F2A0000").
+    pub address: String,
+    /// Class name from the RTTI (e.g., "Actor", "RE::NiNode").
+    pub class_name: String,
+}
+
+/// An exception that came before the fatal one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainedException {
+    /// How the exception is related to the fatal one.
+    pub source: ExceptionSource,
+    /// Exception code (e.g., "0xE06D7363").
+    pub code: String,
+    /// Address where it was raised (e.g., "0x7FF6A1234567").
+    pub address: String,
+    /// Unix timestamp (milliseconds) when it was raised; absent for nested
+    /// records, which carry no time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurred_at: Option<u64>,
+}
+
+/// How a [`ChainedException`] is related to the fatal exception.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExceptionSource {
+    /// Linked from the fatal exception's record
+    /// (`EXCEPTION_RECORD.ExceptionRecord`), as when the fault happened
+    /// while another exception was being dispatched.
+    Nested,
+    /// A non-fatal exception some thread raised before the crash, whether
+    /// or not it was caught.
+    FirstChance,
+}
+
+/// What a [`HardwareEvent`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HardwareEventKind {
+    /// The display driver stopped responding and was reset (TDR).
+    GpuReset,
+    /// A WHEA hardware error: CPU machine checks, memory, or PCIe errors.
+    Whea,
+}
+
+/// A kind of crash recognized from its exception, the diagnostics, or the
+/// game's logs (see [`crate::triage`]).
+///
+/// Serialized as `crashCategory`, omitted when the crash wasn't recognized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrashCategory {
+    /// An access violation in the first 64 KiB of memory: code used a null
+    /// pointer, usually to an object that was never created or was freed.
+    NullDeref,
+    /// A thread ran out of stack space (`EXCEPTION_STACK_OVERFLOW`).
+    StackOverflow,
+    /// The process ran out of memory or the system out of commit.
+    #[serde(rename = "oom")]
+    OutOfMemory,
+    /// The GPU driver removed the D3D device (`DXGI_ERROR_DEVICE_REMOVED`
+    /// and related errors) or crashed in its own module: a driver timeout,
+    /// hang, or reset rather than a fault in game or mod code.
+    Gpu,
+    /// Windows couldn't read a page of a mapped file back in because the
+    /// drive reported an error (`IN_PAGE_ERROR` with a storage failure
+    /// status): a failing or disconnected disk rather than a fault in game
+    /// or mod code.
+    DiskIo,
+    /// The faulting module's exports were detoured by other DLLs.
+    HookConflict,
+    /// A game script was running when the game crashed.
+    Script,
+}
+
+/// Why Windows couldn't page in memory, for an `IN_PAGE_ERROR` crash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InPageError {
+    /// NTSTATUS of the failed read (e.g., "0xC000009C").
+    pub status: String,
+    /// Its name (e.g., "STATUS_DEVICE_DATA_ERROR"), when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_name: Option<String>,
+    /// Address that couldn't be paged in (e.g., "0x1F2A0000").
+    pub address: String,
+    /// File mapped at the address (e.g., a BSA archive), when there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+/// Mod files that install the same thing twice.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModConflict {
+    /// How the files collide.
+    pub kind: ConflictKind,
+    /// The colliding files, relative to the game directory.
+    pub files: Vec<String>,
+}
+
+/// How the files in a [`ModConflict`] collide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictKind {
+    /// The same file name in two install locations.
+    DuplicateName,
+    /// The same content under different names.
+    DuplicateContent,
+}
+
+/// A text file attached to a report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// File name (e.g., "cyber_engine_tweaks.log"). Max 255.
+    pub name: String,
+    /// File content, possibly only its end (see [`crate::log_tail`]).
+    pub content: String,
+}
+
+impl Attachment {
+    /// Creates an attachment.
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Display and GPU settings when a report was built (see
+/// [`crate::graphics`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphicsSettings {
+    /// Width of the game window's client area in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Height of the game window's client area in pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Refresh rate of the monitor showing the game, in Hz.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_rate: Option<u32>,
+    /// How the game window covers its monitor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_mode: Option<DisplayMode>,
+    /// Whether HDR is on for the monitor showing the game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hdr: Option<bool>,
+    /// Hardware GPUs. Max 8.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adapters: Vec<GpuAdapter>,
+    /// Upscaler DLLs loaded or in the game folder. Max 16.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub upscalers: Vec<Upscaler>,
+}
+
+/// How the game window covers its monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayMode {
+    /// Exclusive fullscreen, with the game owning the display mode.
+    Fullscreen,
+    /// A borderless window covering the whole monitor.
+    Borderless,
+    /// A window with a title bar, or smaller than the monitor.
+    Windowed,
+}
+
+/// A GPU in the system.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuAdapter {
+    /// Adapter name (e.g., "NVIDIA GeForce RTX 4070 Laptop GPU"). Max 255.
+    pub name: String,
+    /// PCI vendor ID (0x10DE NVIDIA, 0x1002 AMD, 0x8086 Intel).
+    pub vendor_id: u32,
+    /// Dedicated video memory in MiB.
+    pub vram_mb: u64,
+    /// Whether the game renders on this GPU.
+    pub active: bool,
+}
+
+/// Which upscaler an [`Upscaler`] DLL belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpscalerKind {
+    /// NVIDIA DLSS Super Resolution or Ray Reconstruction.
+    Dlss,
+    /// NVIDIA DLSS Frame Generation.
+    #[serde(rename = "dlss-g")]
+    DlssFrameGeneration,
+    /// AMD FidelityFX Super Resolution.
+    Fsr,
+    /// Intel XeSS.
+    Xess,
+}
+
+/// An upscaler DLL in the game folder or loaded in the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Upscaler {
+    /// Which upscaler the DLL belongs to.
+    pub kind: UpscalerKind,
+    /// File name (e.g., "nvngx_dlss.dll"). Max 255.
+    pub file: String,
+    /// PE file version, if the DLL has a version resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether the game has loaded it; an unloaded DLL is installed but off.
+    pub loaded: bool,
+}
+
+/// How long the game's frames took over the seconds before a crash.
+///
+/// A frame time collapse just before a crash points at streaming or VRAM
+/// exhaustion rather than a bad record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameTimes {
+    /// Frames measured.
+    pub samples: u32,
+    /// Median frame time in milliseconds.
+    pub p50_ms: f32,
+    /// 99th percentile frame time in milliseconds.
+    pub p99_ms: f32,
+    /// Fastest frame in milliseconds.
+    pub min_ms: f32,
+    /// Slowest frame in milliseconds.
+    pub max_ms: f32,
+    /// The last frames in milliseconds, newest first. Max 16.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_ms: Vec<f32>,
+}
+
+/// Where the player was, and when, at crash time.
+///
+/// Crashes tied to one location (broken navmesh, a bad mesh in a cell)
+/// only show up as a pattern when reports carry the location.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameState {
+    /// Worldspace editor ID (e.g., "Tamriel"); absent in interiors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worldspace: Option<String>,
+    /// Cell editor ID, or form ID and plugin when the editor ID is unknown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<String>,
+    /// Player position `[x, y, z]` in game units.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<[f32; 3]>,
+    /// In-game days passed since the start of the game.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_passed: Option<f32>,
 }
 
 fn default_schema_version() -> u32 {
@@ -91,6 +840,212 @@ pub struct CrashReportResponse {
     pub share_token: String,
 }
 
+/// Summary of a stored report, as listed by `GET /crashes`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSummary {
+    /// The report ID (ULID).
+    pub id: String,
+    /// Game identifier.
+    pub game_id: String,
+    /// What the report describes; crash if the server doesn't say.
+    #[serde(default)]
+    pub report_type: ReportType,
+    /// Game version string.
+    pub game_version: String,
+    /// Crash hash assigned by the server.
+    pub crash_hash: String,
+    /// Exception code, if recorded.
+    pub exception_code: Option<String>,
+    /// Faulting module, if recorded.
+    pub faulting_module: Option<String>,
+    /// Unix timestamp (milliseconds) when the crash occurred.
+    pub crashed_at: u64,
+    /// Unix timestamp (milliseconds) when the report was received.
+    pub submitted_at: u64,
+    /// Whether the report is publicly visible.
+    pub is_public: bool,
+}
+
+/// One page of the reports submitted with an API key, newest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportPage {
+    /// Reports on this page.
+    pub reports: Vec<ReportSummary>,
+    /// Page number, starting at 1.
+    pub page: u32,
+    /// Maximum reports per page.
+    pub per_page: u32,
+    /// Total reports across all pages.
+    pub total: u64,
+}
+
+impl ReportPage {
+    /// Returns true if there are reports after this page.
+    pub fn has_more(&self) -> bool {
+        u64::from(self.page) * u64::from(self.per_page) < self.total
+    }
+}
+
+/// A report fetched back from the API with its server-side metadata.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "StoredReportWire")]
+pub struct StoredReport {
+    /// The report ID (ULID).
+    pub id: String,
+    /// Unix timestamp (milliseconds) when the report was received.
+    pub submitted_at: u64,
+    /// Whether the report is publicly visible.
+    pub is_public: bool,
+    /// The report as submitted, with `crash_hash` set to the server's hash.
+    pub report: CreateCrashReport,
+}
+
+/// `GET /crashes/{id}` response body, which returns the load order parsed.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredReportWire {
+    id: String,
+    schema_version: u32,
+    game_id: String,
+    #[serde(default)]
+    report_type: ReportType,
+    crash_hash: String,
+    stack_trace: String,
+    exception_code: Option<String>,
+    exception_address: Option<String>,
+    disassembly: Option<String>,
+    address_region: Option<AddressRegion>,
+    crash_category: Option<CrashCategory>,
+    triage_hint: Option<String>,
+    device_removed_reason: Option<String>,
+    in_page_error: Option<InPageError>,
+    faulting_module: Option<String>,
+    suspected_mod: Option<String>,
+    game_version: String,
+    game_edition: Option<String>,
+    script_extender_version: Option<String>,
+    reporter_version: Option<String>,
+    os_version: Option<String>,
+    load_order: serde_json::Value,
+    plugin_count: u32,
+    #[serde(default)]
+    native_plugins: ModList,
+    modpack_name: Option<String>,
+    modpack_version: Option<String>,
+    #[serde(default)]
+    hash_algorithm: HashAlgorithm,
+    #[serde(default)]
+    frameworks: BTreeMap<String, String>,
+    #[serde(default)]
+    graphics_injectors: Vec<GraphicsInjector>,
+    graphics: Option<GraphicsSettings>,
+    #[serde(default)]
+    papyrus_stacks: Vec<PapyrusFrame>,
+    game_state: Option<GameState>,
+    frame_times: Option<FrameTimes>,
+    #[serde(default)]
+    recent_assets: Vec<String>,
+    #[serde(default)]
+    handled_exceptions: Vec<HandledException>,
+    #[serde(default)]
+    hardware_events: Vec<HardwareEvent>,
+    #[serde(default)]
+    known_bad_modules: Vec<KnownBadModule>,
+    #[serde(default)]
+    breadcrumbs: Vec<Breadcrumb>,
+    #[serde(default)]
+    mod_context: BTreeMap<String, String>,
+    #[serde(default)]
+    mod_errors: Vec<ModError>,
+    #[serde(default)]
+    extras: BTreeMap<String, String>,
+    #[serde(default)]
+    hooked_functions: Vec<HookedFunction>,
+    #[serde(default)]
+    probable_objects: Vec<ProbableObject>,
+    #[serde(default)]
+    exception_chain: Vec<ChainedException>,
+    #[serde(default)]
+    conflicts: Vec<ModConflict>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
+    save_game: Option<SaveGameInfo>,
+    crashed_at: u64,
+    uptime_secs: Option<u64>,
+    secs_since_last_save: Option<u64>,
+    submitted_at: u64,
+    is_public: bool,
+    notes: Option<String>,
+    installation_id: Option<String>,
+    session_id: Option<String>,
+}
+
+impl From<StoredReportWire> for StoredReport {
+    fn from(wire: StoredReportWire) -> Self {
+        Self {
+            id: wire.id,
+            submitted_at: wire.submitted_at,
+            is_public: wire.is_public,
+            report: CreateCrashReport {
+                schema_version: wire.schema_version,
+                game_id: wire.game_id,
+                report_type: wire.report_type,
+                stack_trace: wire.stack_trace,
+                crash_hash: Some(wire.crash_hash),
+                exception_code: wire.exception_code,
+                exception_address: wire.exception_address,
+                disassembly: wire.disassembly,
+                address_region: wire.address_region,
+                crash_category: wire.crash_category,
+                triage_hint: wire.triage_hint,
+                device_removed_reason: wire.device_removed_reason,
+                in_page_error: wire.in_page_error,
+                faulting_module: wire.faulting_module,
+                suspected_mod: wire.suspected_mod,
+                game_version: wire.game_version,
+                game_edition: wire.game_edition,
+                script_extender_version: wire.script_extender_version,
+                reporter_version: wire.reporter_version,
+                os_version: wire.os_version,
+                load_order_json: wire.load_order.to_string(),
+                plugin_count: wire.plugin_count,
+                native_plugins: wire.native_plugins,
+                modpack_name: wire.modpack_name,
+                modpack_version: wire.modpack_version,
+                hash_algorithm: wire.hash_algorithm,
+                frameworks: wire.frameworks,
+                graphics_injectors: wire.graphics_injectors,
+                graphics: wire.graphics,
+                papyrus_stacks: wire.papyrus_stacks,
+                game_state: wire.game_state,
+                frame_times: wire.frame_times,
+                recent_assets: wire.recent_assets,
+                handled_exceptions: wire.handled_exceptions,
+                hardware_events: wire.hardware_events,
+                known_bad_modules: wire.known_bad_modules,
+                breadcrumbs: wire.breadcrumbs,
+                mod_context: wire.mod_context,
+                mod_errors: wire.mod_errors,
+                extras: wire.extras,
+                hooked_functions: wire.hooked_functions,
+                probable_objects: wire.probable_objects,
+                exception_chain: wire.exception_chain,
+                conflicts: wire.conflicts,
+                attachments: wire.attachments,
+                save_game: wire.save_game,
+                crashed_at: wire.crashed_at,
+                uptime_secs: wire.uptime_secs,
+                secs_since_last_save: wire.secs_since_last_save,
+                notes: wire.notes,
+                installation_id: wire.installation_id,
+                session_id: wire.session_id,
+            },
+        }
+    }
+}
+
 /// Internal enum to hold either v1 or v2 load order data.
 #[derive(Debug)]
 enum LoadOrderData {
@@ -102,17 +1057,57 @@ enum LoadOrderData {
 #[derive(Debug, Default)]
 pub struct CrashReportBuilder {
     game_id: Option<String>,
+    report_type: ReportType,
     stack_trace: Option<String>,
     crash_hash: Option<String>,
     exception_code: Option<String>,
     exception_address: Option<String>,
+    exception_parameters: Vec<u64>,
+    disassembly: Option<String>,
+    address_region: Option<AddressRegion>,
+    crash_category: Option<CrashCategory>,
+    triage_hint: Option<String>,
+    device_removed_reason: Option<String>,
+    in_page_error: Option<InPageError>,
     faulting_module: Option<String>,
+    suspected_mod: Option<String>,
     game_version: Option<String>,
+    game_edition: Option<String>,
     script_extender_version: Option<String>,
+    reporter_version: Option<String>,
     os_version: Option<String>,
     load_order_data: Option<LoadOrderData>,
+    native_plugins: Option<ModList>,
+    modpack: Option<Modpack>,
+    hash_algorithm: HashAlgorithm,
+    frameworks: Option<BTreeMap<String, String>>,
+    graphics_injectors: Option<Vec<GraphicsInjector>>,
+    graphics: Option<GraphicsSettings>,
+    papyrus_stacks: Option<Vec<PapyrusFrame>>,
+    game_state: Option<GameState>,
+    frame_times: Option<FrameTimes>,
+    recent_assets: Option<Vec<String>>,
+    handled_exceptions: Option<Vec<HandledException>>,
+    hardware_events: Option<Vec<HardwareEvent>>,
+    known_bad_modules: Option<Vec<KnownBadModule>>,
+    breadcrumbs: Option<Vec<Breadcrumb>>,
+    mod_context: Option<BTreeMap<String, String>>,
+    mod_errors: Option<Vec<ModError>>,
+    mod_notes: Vec<String>,
+    extras: BTreeMap<String, String>,
+    hooked_functions: Option<Vec<HookedFunction>>,
+    probable_objects: Option<Vec<ProbableObject>>,
+    exception_chain: Option<Vec<ChainedException>>,
+    conflicts: Option<Vec<ModConflict>>,
+    attachments: Option<Vec<Attachment>>,
+    save_game: Option<SaveGameInfo>,
     crashed_at: Option<u64>,
+    uptime_secs: Option<u64>,
+    secs_since_last_save: Option<u64>,
     notes: Option<String>,
+    installation_id: Option<String>,
+    session_id: Option<String>,
+    max_schema_version: Option<u32>,
 }
 
 impl CrashReportBuilder {
@@ -127,6 +1122,12 @@ impl CrashReportBuilder {
         self
     }
 
+    /// Sets what the report describes (default: crash).
+    pub fn report_type(mut self, report_type: ReportType) -> Self {
+        self.report_type = report_type;
+        self
+    }
+
     /// Sets the stack trace (required).
     pub fn stack_trace(mut self, trace: impl Into<String>) -> Self {
         self.stack_trace = Some(trace.into());
@@ -151,24 +1152,92 @@ impl CrashReportBuilder {
         self
     }
 
+    /// Sets the disassembly around the exception address (optional).
+    pub fn disassembly(mut self, text: impl Into<String>) -> Self {
+        self.disassembly = Some(text.into());
+        self
+    }
+
+    /// Sets the memory region around an exception address outside every
+    /// loaded module (optional).
+    pub fn address_region(mut self, region: AddressRegion) -> Self {
+        self.address_region = Some(region);
+        self
+    }
+
+    /// Sets the kind of crash, when the plugin recognized it (optional).
+    ///
+    /// Otherwise [`build`](Self::build) classifies crashes itself.
+    pub fn crash_category(mut self, category: CrashCategory) -> Self {
+        self.crash_category = Some(category);
+        self
+    }
+
+    /// Sets the triage hint (optional).
+    ///
+    /// Otherwise [`build`](Self::build) writes one for the crash category.
+    pub fn triage_hint(mut self, hint: impl Into<String>) -> Self {
+        self.triage_hint = Some(hint.into());
+        self
+    }
+
+    /// Sets the exception record's `ExceptionInformation` (optional).
+    ///
+    /// Not sent; [`build`](Self::build) reads the access violation's target
+    /// from it to recognize null dereferences.
+    pub fn exception_parameters(mut self, parameters: &[u64]) -> Self {
+        self.exception_parameters = parameters.to_vec();
+        self
+    }
+
+    /// Sets why the GPU device was lost (optional).
+    pub fn device_removed_reason(mut self, reason: impl Into<String>) -> Self {
+        self.device_removed_reason = Some(reason.into());
+        self
+    }
+
+    /// Sets the failed read behind an `IN_PAGE_ERROR` crash (optional).
+    pub fn in_page_error(mut self, error: InPageError) -> Self {
+        self.in_page_error = Some(error);
+        self
+    }
+
     /// Sets the faulting module (optional).
     pub fn faulting_module(mut self, module: impl Into<String>) -> Self {
         self.faulting_module = Some(module.into());
         self
     }
 
+    /// Sets the mod suspected of causing the crash (optional).
+    pub fn suspected_mod(mut self, name: impl Into<String>) -> Self {
+        self.suspected_mod = Some(name.into());
+        self
+    }
+
     /// Sets the game version (required).
     pub fn game_version(mut self, version: impl Into<String>) -> Self {
         self.game_version = Some(version.into());
         self
     }
 
+    /// Sets the normalized runtime edition (optional), e.g. "AE" or "VR".
+    pub fn game_edition(mut self, edition: impl Into<String>) -> Self {
+        self.game_edition = Some(edition.into());
+        self
+    }
+
     /// Sets the script extender version (optional).
     pub fn script_extender_version(mut self, version: impl Into<String>) -> Self {
         self.script_extender_version = Some(version.into());
         self
     }
 
+    /// Sets the CTD client version (default: this crate's version).
+    pub fn reporter_version(mut self, version: impl Into<String>) -> Self {
+        self.reporter_version = Some(version.into());
+        self
+    }
+
     /// Sets the OS version (optional).
     pub fn os_version(mut self, version: impl Into<String>) -> Self {
         self.os_version = Some(version.into());
@@ -198,34 +1267,265 @@ impl CrashReportBuilder {
         self
     }
 
-    /// Sets the crash timestamp in milliseconds (required).
-    pub fn crashed_at(mut self, timestamp: u64) -> Self {
-        self.crashed_at = Some(timestamp);
+    /// Sets the script extender DLL plugins (optional).
+    pub fn native_plugins(mut self, plugins: ModList) -> Self {
+        self.native_plugins = Some(plugins);
         self
     }
 
-    /// Sets crash timestamp to now.
-    pub fn crashed_now(self) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-        self.crashed_at(now)
+    /// Sets the modpack the game was installed from (optional; `None`
+    /// leaves it unset, as [`modpack::detect`] returns for most games).
+    pub fn modpack(mut self, modpack: Option<Modpack>) -> Self {
+        self.modpack = modpack;
+        self
     }
 
-    /// Sets user notes (optional).
-    pub fn notes(mut self, notes: impl Into<String>) -> Self {
-        self.notes = Some(notes.into());
+    /// Sets the graphics injectors found in the game root (optional).
+    pub fn graphics_injectors(mut self, injectors: Vec<GraphicsInjector>) -> Self {
+        self.graphics_injectors = Some(injectors);
         self
     }
 
-    /// Builds the crash report, validating all required fields.
-    ///
-    /// # Errors
-    ///
-    /// Returns `CtdError::Validation` if required fields are missing or invalid.
-    pub fn build(self) -> Result<CreateCrashReport> {
-        let game_id = self
+    /// Sets the display and graphics settings (optional). Settings with
+    /// nothing in them are left out.
+    pub fn graphics(mut self, graphics: GraphicsSettings) -> Self {
+        self.graphics = (graphics != GraphicsSettings::default()).then_some(graphics);
+        self
+    }
+
+    /// Sets the Papyrus call stacks running at crash time (optional).
+    pub fn papyrus_stacks(mut self, frames: Vec<PapyrusFrame>) -> Self {
+        self.papyrus_stacks = Some(frames);
+        self
+    }
+
+    /// Sets the player location and in-game time (optional).
+    pub fn game_state(mut self, state: GameState) -> Self {
+        self.game_state = Some(state);
+        self
+    }
+
+    /// Sets the frame times before the crash (optional).
+    pub fn frame_times(mut self, times: FrameTimes) -> Self {
+        self.frame_times = Some(times);
+        self
+    }
+
+    /// Sets the asset paths the game opened last, newest first (optional).
+    pub fn recent_assets(mut self, paths: Vec<String>) -> Self {
+        self.recent_assets = Some(paths);
+        self
+    }
+
+    /// Sets the non-fatal exception counts, most frequent first (optional).
+    pub fn handled_exceptions(mut self, tallies: Vec<HandledException>) -> Self {
+        self.handled_exceptions = Some(tallies);
+        self
+    }
+
+    /// Sets the hardware events logged before the crash, newest first
+    /// (optional).
+    pub fn hardware_events(mut self, events: Vec<HardwareEvent>) -> Self {
+        self.hardware_events = Some(events);
+        self
+    }
+
+    /// Sets the loaded DLLs known to crash games (optional).
+    pub fn known_bad_modules(mut self, modules: Vec<KnownBadModule>) -> Self {
+        self.known_bad_modules = Some(modules);
+        self
+    }
+
+    /// Sets the steps other mods recorded, newest first (optional).
+    pub fn breadcrumbs(mut self, breadcrumbs: Vec<Breadcrumb>) -> Self {
+        self.breadcrumbs = Some(breadcrumbs);
+        self
+    }
+
+    /// Sets the key/value context other mods set (optional).
+    pub fn mod_context(mut self, context: BTreeMap<String, String>) -> Self {
+        self.mod_context = Some(context);
+        self
+    }
+
+    /// Sets the errors other mods caught, most recent first (optional).
+    pub fn mod_errors(mut self, errors: Vec<ModError>) -> Self {
+        self.mod_errors = Some(errors);
+        self
+    }
+
+    /// Adds notes from other mods, each `[source] text`, after the user
+    /// notes (optional). Notes that don't fit in [`MAX_NOTES_LEN`] are left
+    /// out.
+    pub fn mod_notes(mut self, notes: Vec<String>) -> Self {
+        self.mod_notes = notes;
+        self
+    }
+
+    /// Adds a free-form context entry (optional), replacing any earlier
+    /// value for `key`. Empty keys or keys over 100 characters are ignored,
+    /// as are new keys once there are [`MAX_EXTRAS`]; values are truncated
+    /// to 500 characters.
+    pub fn extra(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        if key.is_empty() || key.len() > MAX_EXTRA_KEY_LEN {
+            return self;
+        }
+        if self.extras.len() == MAX_EXTRAS && !self.extras.contains_key(&key) {
+            return self;
+        }
+        let value = abi::truncate(&value.into(), MAX_EXTRA_VALUE_LEN);
+        self.extras.insert(key, value);
+        self
+    }
+
+    /// Sets the breadcrumbs, context, errors, and notes other mods recorded
+    /// through [`crate::abi`], as of now.
+    pub fn abi_context(mut self) -> Self {
+        self.breadcrumbs = Some(abi::breadcrumbs());
+        self.mod_context = Some(abi::context());
+        self.mod_errors = Some(abi::mod_errors());
+        self.mod_notes = abi::notes();
+        self
+    }
+
+    /// Sets the faulting module's detoured exports (optional).
+    pub fn hooked_functions(mut self, hooks: Vec<HookedFunction>) -> Self {
+        self.hooked_functions = Some(hooks);
+        self
+    }
+
+    /// Sets the objects the crashed thread was working on (optional).
+    pub fn probable_objects(mut self, objects: Vec<ProbableObject>) -> Self {
+        self.probable_objects = Some(objects);
+        self
+    }
+
+    /// Sets the exceptions that came before the fatal one (optional).
+    pub fn exception_chain(mut self, chain: Vec<ChainedException>) -> Self {
+        self.exception_chain = Some(chain);
+        self
+    }
+
+    /// Sets the mod framework versions, by framework name (optional).
+    pub fn frameworks(mut self, frameworks: BTreeMap<String, String>) -> Self {
+        self.frameworks = Some(frameworks);
+        self
+    }
+
+    /// Sets the mod files found installed twice (optional).
+    pub fn conflicts(mut self, conflicts: Vec<ModConflict>) -> Self {
+        self.conflicts = Some(conflicts);
+        self
+    }
+
+    /// Sets files to attach, such as script log tails (optional).
+    pub fn attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = Some(attachments);
+        self
+    }
+
+    /// Sets the most recent save's details (optional).
+    pub fn save_game(mut self, save: SaveGameInfo) -> Self {
+        self.save_game = Some(save);
+        self
+    }
+
+    /// Sets the crash timestamp in milliseconds (required).
+    pub fn crashed_at(mut self, timestamp: u64) -> Self {
+        self.crashed_at = Some(timestamp);
+        self
+    }
+
+    /// Sets crash timestamp to now.
+    pub fn crashed_now(self) -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        self.crashed_at(now)
+    }
+
+    /// Sets how long the game process had been running, in seconds
+    /// (optional).
+    pub fn uptime_secs(mut self, secs: u64) -> Self {
+        self.uptime_secs = Some(secs);
+        self
+    }
+
+    /// Sets how long since the game last saved, in seconds (optional).
+    pub fn secs_since_last_save(mut self, secs: u64) -> Self {
+        self.secs_since_last_save = Some(secs);
+        self
+    }
+
+    /// Sets the uptime and time since the last save from
+    /// [`crate::uptime`], as of now.
+    pub fn uptime(mut self) -> Self {
+        self.uptime_secs = uptime::uptime_secs();
+        self.secs_since_last_save = uptime::secs_since_last_save();
+        self
+    }
+
+    /// Sets the frame times recorded by [`crate::frame_times`], if the
+    /// plugin timed any frames.
+    pub fn recent_frame_times(mut self) -> Self {
+        self.frame_times = frame_times::summary();
+        self
+    }
+
+    /// Sets user notes (optional).
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Sets the algorithm the mod fingerprints were made with
+    /// (default: partial SHA-256).
+    pub fn hash_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.hash_algorithm = algorithm;
+        self
+    }
+
+    /// Sets the installation ID (optional, a UUID).
+    pub fn installation_id(mut self, id: impl Into<String>) -> Self {
+        self.installation_id = Some(id.into());
+        self
+    }
+
+    /// Sets the session ID (optional, a UUID).
+    pub fn session_id(mut self, id: impl Into<String>) -> Self {
+        self.session_id = Some(id.into());
+        self
+    }
+
+    /// Sets the installation and session IDs from [`crate::identity`],
+    /// unless `[privacy] anonymous_ids` is off.
+    pub fn anonymous_ids(mut self) -> Self {
+        if let Some(ids) = identity::current() {
+            self.installation_id = ids.installation_id;
+            self.session_id = Some(ids.session_id);
+        }
+        self
+    }
+
+    /// Caps the schema version for servers that predate the current one.
+    ///
+    /// With a cap of 1, a v2 `ModList` is downgraded to a v1 `LoadOrder`.
+    /// Use the server's [`ServerCapabilities`](crate::api_client::ServerCapabilities)
+    /// to pick the value.
+    pub fn max_schema_version(mut self, version: u32) -> Self {
+        self.max_schema_version = Some(version);
+        self
+    }
+
+    /// Builds the crash report, validating all required fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Validation` if required fields are missing or invalid.
+    pub fn build(self) -> Result<CreateCrashReport> {
+        let game_id = self
             .game_id
             .filter(|s| !s.is_empty())
             .ok_or_else(|| CtdError::Validation("game_id is required".into()))?;
@@ -264,6 +1564,14 @@ impl CrashReportBuilder {
                 })?;
                 (count, json, 1)
             }
+            // Older servers only understand the v1 load order
+            LoadOrderData::V2(ml) if self.max_schema_version.is_some_and(|v| v < 2) => {
+                let count = ml.len() as u32;
+                let json = ml.to_load_order().to_json().map_err(|e| {
+                    CtdError::Validation(format!("failed to serialize load_order: {}", e))
+                })?;
+                (count, json, 1)
+            }
             LoadOrderData::V2(ml) => {
                 let count = ml.len() as u32;
                 let json = ml.to_json().map_err(|e| {
@@ -277,6 +1585,287 @@ impl CrashReportBuilder {
             return Err(CtdError::Validation("plugin_count exceeds 10000".into()));
         }
 
+        let native_plugins = self.native_plugins.unwrap_or_default();
+        if native_plugins.len() > MAX_NATIVE_PLUGINS {
+            return Err(CtdError::Validation(format!(
+                "native_plugins exceeds {} entries",
+                MAX_NATIVE_PLUGINS
+            )));
+        }
+
+        let (modpack_name, modpack_version) = match self.modpack {
+            Some(pack) => (Some(pack.name), pack.version),
+            None => (None, None),
+        };
+        if modpack_name
+            .as_ref()
+            .is_some_and(|name| name.is_empty() || name.len() > modpack::MAX_NAME_LEN)
+        {
+            return Err(CtdError::Validation(
+                "modpack_name must be 1-100 characters".into(),
+            ));
+        }
+        if modpack_version
+            .as_ref()
+            .is_some_and(|version| version.len() > modpack::MAX_VERSION_LEN)
+        {
+            return Err(CtdError::Validation(
+                "modpack_version exceeds 50 characters".into(),
+            ));
+        }
+
+        let graphics_injectors = self.graphics_injectors.unwrap_or_default();
+        if graphics_injectors.len() > MAX_GRAPHICS_INJECTORS {
+            return Err(CtdError::Validation(format!(
+                "graphics_injectors exceeds {} entries",
+                MAX_GRAPHICS_INJECTORS
+            )));
+        }
+
+        if let Some(ref graphics) = self.graphics {
+            if graphics.adapters.len() > MAX_GPU_ADAPTERS {
+                return Err(CtdError::Validation(format!(
+                    "graphics exceeds {} adapters",
+                    MAX_GPU_ADAPTERS
+                )));
+            }
+            if graphics.upscalers.len() > MAX_UPSCALERS {
+                return Err(CtdError::Validation(format!(
+                    "graphics exceeds {} upscalers",
+                    MAX_UPSCALERS
+                )));
+            }
+            if graphics
+                .adapters
+                .iter()
+                .any(|adapter| adapter.name.len() > 255)
+                || graphics
+                    .upscalers
+                    .iter()
+                    .any(|upscaler| upscaler.file.len() > 255)
+            {
+                return Err(CtdError::Validation(
+                    "graphics names exceed 255 characters".into(),
+                ));
+            }
+        }
+
+        let papyrus_stacks = self.papyrus_stacks.unwrap_or_default();
+        if papyrus_stacks.len() > MAX_PAPYRUS_FRAMES {
+            return Err(CtdError::Validation(format!(
+                "papyrus_stacks exceeds {} frames",
+                MAX_PAPYRUS_FRAMES
+            )));
+        }
+
+        let recent_assets = self.recent_assets.unwrap_or_default();
+        if recent_assets.len() > MAX_RECENT_ASSETS {
+            return Err(CtdError::Validation(format!(
+                "recent_assets exceeds {} entries",
+                MAX_RECENT_ASSETS
+            )));
+        }
+        if recent_assets.iter().any(|path| path.len() > 260) {
+            return Err(CtdError::Validation(
+                "recent_assets paths exceed 260 characters".into(),
+            ));
+        }
+
+        let handled_exceptions = self.handled_exceptions.unwrap_or_default();
+        if handled_exceptions.len() > MAX_HANDLED_EXCEPTIONS {
+            return Err(CtdError::Validation(format!(
+                "handled_exceptions exceeds {} entries",
+                MAX_HANDLED_EXCEPTIONS
+            )));
+        }
+
+        let hardware_events = self.hardware_events.unwrap_or_default();
+        if hardware_events.len() > MAX_HARDWARE_EVENTS {
+            return Err(CtdError::Validation(format!(
+                "hardware_events exceeds {} entries",
+                MAX_HARDWARE_EVENTS
+            )));
+        }
+        if hardware_events.iter().any(|event| event.source.len() > 100) {
+            return Err(CtdError::Validation(
+                "hardware event sources exceed 100 characters".into(),
+            ));
+        }
+
+        let known_bad_modules = self.known_bad_modules.unwrap_or_default();
+        if known_bad_modules.len() > MAX_KNOWN_BAD_MODULES {
+            return Err(CtdError::Validation(format!(
+                "known_bad_modules exceeds {} entries",
+                MAX_KNOWN_BAD_MODULES
+            )));
+        }
+        if known_bad_modules
+            .iter()
+            .any(|module| module.name.len() > 255 || module.reason.len() > 500)
+        {
+            return Err(CtdError::Validation(
+                "known bad module names exceed 255 or reasons 500 characters".into(),
+            ));
+        }
+
+        let breadcrumbs = self.breadcrumbs.unwrap_or_default();
+        if breadcrumbs.len() > MAX_BREADCRUMBS {
+            return Err(CtdError::Validation(format!(
+                "breadcrumbs exceeds {} entries",
+                MAX_BREADCRUMBS
+            )));
+        }
+        if breadcrumbs.iter().any(|crumb| {
+            crumb.source.is_empty()
+                || crumb.source.len() > 100
+                || crumb.message.len() > MAX_MOD_MESSAGE_LEN
+        }) {
+            return Err(CtdError::Validation(format!(
+                "breadcrumb sources must be 1-100 characters and messages at most {}",
+                MAX_MOD_MESSAGE_LEN
+            )));
+        }
+
+        let mod_context = self.mod_context.unwrap_or_default();
+        if mod_context.len() > MAX_MOD_CONTEXT {
+            return Err(CtdError::Validation(format!(
+                "mod_context exceeds {} entries",
+                MAX_MOD_CONTEXT
+            )));
+        }
+        if mod_context
+            .iter()
+            .any(|(key, value)| key.is_empty() || key.len() > 100 || value.len() > 500)
+        {
+            return Err(CtdError::Validation(
+                "mod context keys must be 1-100 characters and values at most 500".into(),
+            ));
+        }
+
+        let mod_errors = self.mod_errors.unwrap_or_default();
+        if mod_errors.len() > MAX_MOD_ERRORS {
+            return Err(CtdError::Validation(format!(
+                "mod_errors exceeds {} entries",
+                MAX_MOD_ERRORS
+            )));
+        }
+        if mod_errors.iter().any(|error| {
+            error.source.is_empty()
+                || error.source.len() > 100
+                || error.message.len() > MAX_MOD_MESSAGE_LEN
+        }) {
+            return Err(CtdError::Validation(format!(
+                "mod error sources must be 1-100 characters and messages at most {}",
+                MAX_MOD_MESSAGE_LEN
+            )));
+        }
+
+        let hooked_functions = self.hooked_functions.unwrap_or_default();
+        if hooked_functions.len() > MAX_HOOKED_FUNCTIONS {
+            return Err(CtdError::Validation(format!(
+                "hooked_functions exceeds {} entries",
+                MAX_HOOKED_FUNCTIONS
+            )));
+        }
+        if hooked_functions.iter().any(|hook| {
+            hook.function.len() > 300
+                || hook.target.len() > 300
+                || hook.owner.as_ref().is_some_and(|owner| owner.len() > 300)
+        }) {
+            return Err(CtdError::Validation(
+                "hooked function fields exceed 300 characters".into(),
+            ));
+        }
+
+        let probable_objects = self.probable_objects.unwrap_or_default();
+        if probable_objects.len() > MAX_PROBABLE_OBJECTS {
+            return Err(CtdError::Validation(format!(
+                "probable_objects exceeds {} entries",
+                MAX_PROBABLE_OBJECTS
+            )));
+        }
+        if probable_objects.iter().any(|object| {
+            object.location.len() > 50 || object.address.len() > 50 || object.class_name.len() > 300
+        }) {
+            return Err(CtdError::Validation(
+                "probable object fields exceed their limits".into(),
+            ));
+        }
+
+        let exception_chain = self.exception_chain.unwrap_or_default();
+        if exception_chain.len() > MAX_EXCEPTION_CHAIN {
+            return Err(CtdError::Validation(format!(
+                "exception_chain exceeds {} entries",
+                MAX_EXCEPTION_CHAIN
+            )));
+        }
+        if exception_chain
+            .iter()
+            .any(|exception| exception.code.len() > 20 || exception.address.len() > 50)
+        {
+            return Err(CtdError::Validation(
+                "chained exception fields exceed their limits".into(),
+            ));
+        }
+
+        let frameworks = self.frameworks.unwrap_or_default();
+        if frameworks.len() > MAX_FRAMEWORKS {
+            return Err(CtdError::Validation(format!(
+                "frameworks exceeds {} entries",
+                MAX_FRAMEWORKS
+            )));
+        }
+        if frameworks
+            .iter()
+            .any(|(name, version)| name.is_empty() || name.len() > 100 || version.len() > 50)
+        {
+            return Err(CtdError::Validation(
+                "framework names must be 1-100 characters and versions at most 50".into(),
+            ));
+        }
+
+        let conflicts = self.conflicts.unwrap_or_default();
+        if conflicts.len() > MAX_CONFLICTS {
+            return Err(CtdError::Validation(format!(
+                "conflicts exceeds {} entries",
+                MAX_CONFLICTS
+            )));
+        }
+        if conflicts
+            .iter()
+            .flat_map(|conflict| &conflict.files)
+            .any(|file| file.len() > 260)
+        {
+            return Err(CtdError::Validation(
+                "conflict file paths exceed 260 characters".into(),
+            ));
+        }
+
+        let attachments = self.attachments.unwrap_or_default();
+        if attachments.len() > MAX_ATTACHMENTS {
+            return Err(CtdError::Validation(format!(
+                "attachments exceeds {} entries",
+                MAX_ATTACHMENTS
+            )));
+        }
+        if attachments
+            .iter()
+            .any(|a| a.name.is_empty() || a.name.len() > 255)
+        {
+            return Err(CtdError::Validation(
+                "attachment names must be 1-255 characters".into(),
+            ));
+        }
+        if attachments
+            .iter()
+            .any(|a| a.content.len() > MAX_ATTACHMENT_LEN)
+        {
+            return Err(CtdError::Validation(format!(
+                "attachment content exceeds {} bytes",
+                MAX_ATTACHMENT_LEN
+            )));
+        }
+
         let crashed_at = self
             .crashed_at
             .ok_or_else(|| CtdError::Validation("crashed_at is required".into()))?;
@@ -306,6 +1895,62 @@ impl CrashReportBuilder {
             ));
         }
 
+        if let Some(ref text) = self.disassembly
+            && text.len() > MAX_DISASSEMBLY_LEN
+        {
+            return Err(CtdError::Validation(format!(
+                "disassembly exceeds {} characters",
+                MAX_DISASSEMBLY_LEN
+            )));
+        }
+
+        if let Some(ref region) = self.address_region {
+            let strings = [
+                Some(&region.base),
+                Some(&region.state),
+                region.protection.as_ref(),
+                region.kind.as_ref(),
+                region.module_before.as_ref(),
+                region.module_after.as_ref(),
+            ];
+            if strings.into_iter().flatten().any(|value| value.len() > 300) {
+                return Err(CtdError::Validation(
+                    "address_region field exceeds 300 characters".into(),
+                ));
+            }
+        }
+
+        if let Some(ref hint) = self.triage_hint
+            && hint.len() > MAX_TRIAGE_HINT_LEN
+        {
+            return Err(CtdError::Validation(format!(
+                "triage_hint exceeds {} characters",
+                MAX_TRIAGE_HINT_LEN
+            )));
+        }
+
+        if let Some(ref reason) = self.device_removed_reason
+            && reason.len() > 100
+        {
+            return Err(CtdError::Validation(
+                "device_removed_reason exceeds 100 characters".into(),
+            ));
+        }
+
+        if let Some(ref error) = self.in_page_error
+            && (error.status.len() > 20
+                || error
+                    .status_name
+                    .as_ref()
+                    .is_some_and(|name| name.len() > 100)
+                || error.address.len() > 50
+                || error.file.as_ref().is_some_and(|file| file.len() > 500))
+        {
+            return Err(CtdError::Validation(
+                "in_page_error fields exceed their limits".into(),
+            ));
+        }
+
         if let Some(ref module) = self.faulting_module
             && module.len() > 255
         {
@@ -314,6 +1959,42 @@ impl CrashReportBuilder {
             ));
         }
 
+        if let Some(ref name) = self.suspected_mod
+            && name.len() > 255
+        {
+            return Err(CtdError::Validation(
+                "suspected_mod exceeds 255 characters".into(),
+            ));
+        }
+
+        if let Some(ref edition) = self.game_edition
+            && edition.len() > 20
+        {
+            return Err(CtdError::Validation(
+                "game_edition exceeds 20 characters".into(),
+            ));
+        }
+
+        if let Some(ref state) = self.game_state
+            && [&state.worldspace, &state.cell]
+                .into_iter()
+                .flatten()
+                .any(|name| name.len() > 255)
+        {
+            return Err(CtdError::Validation(
+                "game_state names exceed 255 characters".into(),
+            ));
+        }
+
+        if let Some(ref times) = self.frame_times
+            && times.recent_ms.len() > MAX_RECENT_FRAMES
+        {
+            return Err(CtdError::Validation(format!(
+                "frame_times exceeds {} recent frames",
+                MAX_RECENT_FRAMES
+            )));
+        }
+
         if let Some(ref ver) = self.script_extender_version
             && ver.len() > 50
         {
@@ -322,6 +2003,15 @@ impl CrashReportBuilder {
             ));
         }
 
+        let reporter_version = self
+            .reporter_version
+            .unwrap_or_else(|| CLIENT_VERSION.to_string());
+        if reporter_version.len() > 50 {
+            return Err(CtdError::Validation(
+                "reporter_version exceeds 50 characters".into(),
+            ));
+        }
+
         if let Some(ref ver) = self.os_version
             && ver.len() > 100
         {
@@ -331,27 +2021,81 @@ impl CrashReportBuilder {
         }
 
         if let Some(ref notes) = self.notes
-            && notes.len() > 5000
+            && notes.len() > MAX_NOTES_LEN
         {
-            return Err(CtdError::Validation("notes exceeds 5000 characters".into()));
+            return Err(CtdError::Validation(format!(
+                "notes exceeds {} characters",
+                MAX_NOTES_LEN
+            )));
         }
+        let notes = with_mod_notes(self.notes, &self.mod_notes);
 
-        Ok(CreateCrashReport {
-            schema_version,
+        if [&self.installation_id, &self.session_id]
+            .into_iter()
+            .flatten()
+            .any(|id| !identity::is_valid_id(id))
+        {
+            return Err(CtdError::Validation(
+                "installation_id and session_id must be UUIDs".into(),
+            ));
+        }
+
+        let mut report = CreateCrashReport {
+            schema_version,
             game_id,
+            report_type: self.report_type,
             stack_trace,
             crash_hash: self.crash_hash,
             exception_code: self.exception_code,
             exception_address: self.exception_address,
+            disassembly: self.disassembly,
+            address_region: self.address_region,
+            crash_category: self.crash_category,
+            triage_hint: self.triage_hint,
+            device_removed_reason: self.device_removed_reason,
+            in_page_error: self.in_page_error,
             faulting_module: self.faulting_module,
+            suspected_mod: self.suspected_mod,
             game_version,
+            game_edition: self.game_edition,
             script_extender_version: self.script_extender_version,
+            reporter_version: Some(reporter_version),
             os_version: self.os_version,
             load_order_json,
             plugin_count,
+            native_plugins,
+            modpack_name,
+            modpack_version,
+            hash_algorithm: self.hash_algorithm,
+            frameworks,
+            graphics_injectors,
+            graphics: self.graphics,
+            papyrus_stacks,
+            game_state: self.game_state,
+            frame_times: self.frame_times,
+            recent_assets,
+            handled_exceptions,
+            hardware_events,
+            known_bad_modules,
+            breadcrumbs,
+            mod_context,
+            mod_errors,
+            extras: self.extras,
+            hooked_functions,
+            probable_objects,
+            exception_chain,
+            conflicts,
+            attachments,
+            save_game: self.save_game,
             crashed_at,
-            notes: self.notes,
-        })
+            uptime_secs: self.uptime_secs,
+            secs_since_last_save: self.secs_since_last_save,
+            notes,
+            installation_id: self.installation_id,
+            session_id: self.session_id,
+        };
+        triage::apply(&mut report, &self.exception_parameters);
+        Ok(report)
     }
 }
 
@@ -370,6 +2114,7 @@ impl CreateCrashReport {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graphics_injectors::InjectorKind;
     use crate::load_order::{LoadOrderEntry, ModEntry, ModList};
 
     #[allow(deprecated)]
@@ -381,6 +2126,16 @@ mod tests {
         LoadOrder::from_entries(entries)
     }
 
+    /// A builder with only the required fields set.
+    fn minimal_builder() -> CrashReportBuilder {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("trace")
+            .load_order_v2(ModList::new())
+            .crashed_at(1000)
+    }
+
     fn sample_mod_list() -> ModList {
         let mut list = ModList::new();
         list.push(ModEntry::new("Skyrim.esm", "a1b2c3d4e5f67890", 1000).with_index(0));
@@ -429,6 +2184,27 @@ mod tests {
         assert!(report.load_order_json.contains("a1b2c3d4e5f67890"));
     }
 
+    #[test]
+    fn builder_downgrades_for_old_servers() {
+        let report = CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("SkyrimSE.exe+0x12345")
+            .load_order_v2(sample_mod_list())
+            .crashed_at(1700000000000)
+            .max_schema_version(1)
+            .build()
+            .unwrap();
+
+        assert_eq!(report.schema_version, 1);
+        assert_eq!(report.plugin_count, 2);
+        assert!(!report.load_order_json.contains("fileHash"));
+
+        let lo = LoadOrder::from_json(&report.load_order_json).unwrap();
+        assert_eq!(lo.0[1].name, "SkyUI_SE.esp");
+        assert_eq!(lo.0[1].index, Some(1));
+    }
+
     #[test]
     #[allow(deprecated)]
     fn builder_requires_game_id() {
@@ -481,6 +2257,8 @@ mod tests {
             .game_version("1.0")
             .stack_trace("trace")
             .load_order(LoadOrder::new())
+            .suspected_mod("SkyUI")
+            .game_edition("AE")
             .crashed_at(1000)
             .build()
             .unwrap();
@@ -488,6 +2266,8 @@ mod tests {
         let json = report.to_json().unwrap();
 
         assert!(json.contains("gameId"));
+        assert!(json.contains("suspectedMod"));
+        assert!(json.contains("gameEdition"));
         assert!(json.contains("gameVersion"));
         assert!(json.contains("stackTrace"));
         assert!(json.contains("loadOrderJson"));
@@ -496,6 +2276,929 @@ mod tests {
         assert!(json.contains("schemaVersion"));
     }
 
+    #[test]
+    fn native_plugins_serialize_when_present() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("nativePlugins"));
+
+        let plugins = ModList::from_entries(vec![
+            ModEntry::new("EngineFixes.dll", "abcd1234abcd1234", 10).with_version("7.0.18.0"),
+        ]);
+        let report = minimal_builder()
+            .native_plugins(plugins.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains("\"nativePlugins\":[{\"name\":\"EngineFixes.dll\""));
+
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.native_plugins, plugins);
+
+        let too_many = (0..=MAX_NATIVE_PLUGINS)
+            .map(|i| ModEntry::new(format!("{}.dll", i), "0000000000000000", 0))
+            .collect();
+        assert!(minimal_builder().native_plugins(too_many).build().is_err());
+    }
+
+    #[test]
+    fn graphics_injectors_serialize_when_present() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("graphicsInjectors"));
+
+        let injectors = vec![GraphicsInjector {
+            kind: InjectorKind::Enb,
+            file: "d3d11.dll".to_string(),
+            version: None,
+        }];
+        let report = minimal_builder()
+            .graphics_injectors(injectors.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""graphicsInjectors":[{"kind":"enb","file":"d3d11.dll"}]"#));
+
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.graphics_injectors, injectors);
+    }
+
+    #[test]
+    fn graphics_are_validated() {
+        let empty = minimal_builder()
+            .graphics(GraphicsSettings::default())
+            .build();
+        assert!(!empty.unwrap().to_json().unwrap().contains("graphics"));
+
+        let graphics = GraphicsSettings {
+            width: Some(2560),
+            height: Some(1440),
+            refresh_rate: Some(165),
+            display_mode: Some(DisplayMode::Borderless),
+            hdr: Some(true),
+            adapters: vec![GpuAdapter {
+                name: "NVIDIA GeForce RTX 4070".into(),
+                vendor_id: 0x10DE,
+                vram_mb: 12282,
+                active: true,
+            }],
+            upscalers: vec![Upscaler {
+                kind: UpscalerKind::DlssFrameGeneration,
+                file: "nvngx_dlssg.dll".into(),
+                version: Some("3.7.10.0".into()),
+                loaded: true,
+            }],
+        };
+        let report = minimal_builder()
+            .graphics(graphics.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""displayMode":"borderless","hdr":true"#));
+        assert!(json.contains(
+            r#""upscalers":[{"kind":"dlss-g","file":"nvngx_dlssg.dll","version":"3.7.10.0","loaded":true}]"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.graphics, Some(graphics.clone()));
+
+        let too_many = GraphicsSettings {
+            adapters: vec![graphics.adapters[0].clone(); MAX_GPU_ADAPTERS + 1],
+            ..graphics
+        };
+        assert!(minimal_builder().graphics(too_many).build().is_err());
+    }
+
+    #[test]
+    fn papyrus_stacks_serialize_when_present() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("papyrusStacks"));
+
+        let frames = vec![
+            PapyrusFrame {
+                stack_id: 7,
+                depth: 0,
+                script: "MyModQuestScript".to_string(),
+                function: "OnUpdate".to_string(),
+                line: Some(42),
+            },
+            PapyrusFrame {
+                stack_id: 7,
+                depth: 1,
+                script: "MyModQuestScript".to_string(),
+                function: "OnInit".to_string(),
+                line: None,
+            },
+        ];
+        let report = minimal_builder()
+            .papyrus_stacks(frames.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""papyrusStacks":[{"stackId":7,"depth":0,"script":"MyModQuestScript","function":"OnUpdate","line":42}"#
+        ));
+
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.papyrus_stacks, frames);
+    }
+
+    #[test]
+    fn game_state_serializes_when_present() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("gameState"));
+
+        let state = GameState {
+            worldspace: Some("Tamriel".to_string()),
+            cell: Some("0x0000DA6C (Skyrim.esm)".to_string()),
+            position: Some([1024.0, -512.5, 64.0]),
+            days_passed: Some(3.5),
+        };
+        let report = minimal_builder().game_state(state.clone()).build().unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""gameState":{"worldspace":"Tamriel","cell":"0x0000DA6C (Skyrim.esm)","position":[1024.0,-512.5,64.0],"daysPassed":3.5}"#
+        ));
+
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.game_state, Some(state));
+    }
+
+    #[test]
+    fn report_type_serializes_for_hangs_only() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("reportType"));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.report_type, ReportType::Crash);
+
+        let report = minimal_builder()
+            .report_type(ReportType::Hang)
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""reportType":"hang""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.report_type, ReportType::Hang);
+    }
+
+    #[test]
+    fn reporter_version_defaults_to_client_version() {
+        let report = minimal_builder().build().unwrap();
+        assert_eq!(report.reporter_version.as_deref(), Some(CLIENT_VERSION));
+        assert!(
+            report
+                .to_json()
+                .unwrap()
+                .contains(&format!(r#""reporterVersion":"{}""#, CLIENT_VERSION))
+        );
+
+        let report = minimal_builder().reporter_version("0.3.1").build().unwrap();
+        assert_eq!(report.reporter_version.as_deref(), Some("0.3.1"));
+        assert!(
+            minimal_builder()
+                .reporter_version("x".repeat(51))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn hash_algorithm_is_omitted_by_default() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("hashAlgorithm"));
+
+        let report = minimal_builder()
+            .hash_algorithm(HashAlgorithm::Crc32)
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""hashAlgorithm":"crc32""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.hash_algorithm, HashAlgorithm::Crc32);
+    }
+
+    #[test]
+    fn anonymous_ids_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("installationId"));
+        assert!(!json.contains("sessionId"));
+
+        let report = minimal_builder()
+            .installation_id("6f1c2b7e-3d4a-4c5b-9e8f-0a1b2c3d4e5f")
+            .session_id("0d9e8f7a-6b5c-4d3e-8f2a-1b0c9d8e7f6a")
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""installationId":"6f1c2b7e-3d4a-4c5b-9e8f-0a1b2c3d4e5f""#));
+        assert!(json.contains(r#""sessionId":"0d9e8f7a-6b5c-4d3e-8f2a-1b0c9d8e7f6a""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.installation_id, report.installation_id);
+
+        assert!(minimal_builder().session_id("player-1").build().is_err());
+    }
+
+    #[test]
+    fn handled_exceptions_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("handledExceptions"));
+
+        let tallies = vec![HandledException {
+            code: "0xE06D7363".to_string(),
+            count: 412,
+        }];
+        let report = minimal_builder()
+            .handled_exceptions(tallies.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""handledExceptions":[{"code":"0xE06D7363","count":412}]"#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.handled_exceptions, tallies);
+
+        let too_many = vec![tallies[0].clone(); MAX_HANDLED_EXCEPTIONS + 1];
+        assert!(
+            minimal_builder()
+                .handled_exceptions(too_many)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn hooked_functions_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("hookedFunctions"));
+
+        let hooks = vec![HookedFunction {
+            function: "D3D11CreateDevice".to_string(),
+            target: "d3d11_enb.dll+0x1A2B0".to_string(),
+            owner: Some("d3d11_enb.dll".to_string()),
+        }];
+        let report = minimal_builder()
+            .hooked_functions(hooks.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""hookedFunctions":[{"function":"D3D11CreateDevice","target":"d3d11_enb.dll+0x1A2B0","owner":"d3d11_enb.dll"}]"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.hooked_functions, hooks);
+
+        let too_many = vec![hooks[0].clone(); MAX_HOOKED_FUNCTIONS + 1];
+        assert!(
+            minimal_builder()
+                .hooked_functions(too_many)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn probable_objects_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("probableObjects"));
+
+        let objects = vec![ProbableObject {
+            location: "RCX".to_string(),
+            address: "0x1F2A0000".to_string(),
+            class_name: "Actor".to_string(),
+        }];
+        let report = minimal_builder()
+            .probable_objects(objects.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""probableObjects":[{"location":"RCX","address":"0x1F2A0000","className":"Actor"}]"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.probable_objects, objects);
+
+        let too_many = vec![objects[0].clone(); MAX_PROBABLE_OBJECTS + 1];
+        assert!(
+            minimal_builder()
+                .probable_objects(too_many)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn exception_chain_is_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("exceptionChain"));
+
+        let chain = vec![
+            ChainedException {
+                source: ExceptionSource::Nested,
+                code: "0xC0000005".to_string(),
+                address: "0x7FF6A1234567".to_string(),
+                occurred_at: None,
+            },
+            ChainedException {
+                source: ExceptionSource::FirstChance,
+                code: "0xE06D7363".to_string(),
+                address: "0x7FFB10001000".to_string(),
+                occurred_at: Some(999),
+            },
+        ];
+        let report = minimal_builder()
+            .exception_chain(chain.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""exceptionChain":[{"source":"nested","code":"0xC0000005","address":"0x7FF6A1234567"},{"source":"first-chance","code":"0xE06D7363","address":"0x7FFB10001000","occurredAt":999}]"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.exception_chain, chain);
+
+        let too_many = vec![chain[0].clone(); MAX_EXCEPTION_CHAIN + 1];
+        assert!(minimal_builder().exception_chain(too_many).build().is_err());
+    }
+
+    #[test]
+    fn hardware_events_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("hardwareEvents"));
+
+        let events = vec![HardwareEvent {
+            kind: HardwareEventKind::GpuReset,
+            source: "Display".to_string(),
+            event_id: 4101,
+            occurred_at: 900,
+        }];
+        let report = minimal_builder()
+            .hardware_events(events.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""hardwareEvents":[{"kind":"gpu-reset","source":"Display","eventId":4101,"occurredAt":900}]"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.hardware_events, events);
+
+        let too_many = vec![events[0].clone(); MAX_HARDWARE_EVENTS + 1];
+        assert!(minimal_builder().hardware_events(too_many).build().is_err());
+    }
+
+    #[test]
+    fn known_bad_modules_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("knownBadModules"));
+
+        let modules = vec![KnownBadModule {
+            name: "X3DAudio1_7.dll".to_string(),
+            version: None,
+            reason: "Old audio fix.".to_string(),
+        }];
+        let report = minimal_builder()
+            .known_bad_modules(modules.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""knownBadModules":[{"name":"X3DAudio1_7.dll","reason":"Old audio fix."}]"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.known_bad_modules, modules);
+
+        let too_many = vec![modules[0].clone(); MAX_KNOWN_BAD_MODULES + 1];
+        assert!(
+            minimal_builder()
+                .known_bad_modules(too_many)
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn mod_sections_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("breadcrumbs"));
+        assert!(!json.contains("modContext"));
+        assert!(!json.contains("modErrors"));
+
+        let breadcrumbs = vec![Breadcrumb {
+            source: "MyQuestMod".to_string(),
+            message: "Stage 40 started".to_string(),
+            at: 900,
+        }];
+        let context = BTreeMap::from([("MyQuestMod.stage".to_string(), "40".to_string())]);
+        let errors = vec![ModError {
+            source: "MyQuestMod".to_string(),
+            message: "Actor not found".to_string(),
+            count: 3,
+            last_at: 950,
+        }];
+        let report = minimal_builder()
+            .breadcrumbs(breadcrumbs.clone())
+            .mod_context(context.clone())
+            .mod_errors(errors.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""breadcrumbs":[{"source":"MyQuestMod","message":"Stage 40 started","at":900}]"#
+        ));
+        assert!(json.contains(r#""modContext":{"MyQuestMod.stage":"40"}"#));
+        assert!(json.contains(r#""lastAt":950"#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.breadcrumbs, breadcrumbs);
+        assert_eq!(parsed.mod_context, context);
+        assert_eq!(parsed.mod_errors, errors);
+
+        let too_many = vec![breadcrumbs[0].clone(); MAX_BREADCRUMBS + 1];
+        assert!(minimal_builder().breadcrumbs(too_many).build().is_err());
+        let empty_key = BTreeMap::from([(String::new(), "40".to_string())]);
+        assert!(minimal_builder().mod_context(empty_key).build().is_err());
+        let long_message = vec![ModError {
+            message: "x".repeat(MAX_MOD_MESSAGE_LEN + 1),
+            ..errors[0].clone()
+        }];
+        assert!(minimal_builder().mod_errors(long_message).build().is_err());
+    }
+
+    #[test]
+    fn modpack_serializes_when_present() {
+        let json = minimal_builder()
+            .modpack(None)
+            .build()
+            .unwrap()
+            .to_json()
+            .unwrap();
+        assert!(!json.contains("modpack"));
+
+        let pack = Modpack::new("Lorerim", Some("3.1.4"));
+        let report = minimal_builder().modpack(pack).build().unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""modpackName":"Lorerim","modpackVersion":"3.1.4""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.modpack_name.as_deref(), Some("Lorerim"));
+        assert_eq!(parsed.modpack_version.as_deref(), Some("3.1.4"));
+
+        let too_long = Modpack {
+            name: "x".repeat(modpack::MAX_NAME_LEN + 1),
+            version: None,
+        };
+        assert!(minimal_builder().modpack(Some(too_long)).build().is_err());
+    }
+
+    #[test]
+    fn extras_are_capped() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("extras"));
+
+        let report = minimal_builder()
+            .extra("difficulty", "Adept")
+            .extra("quest", "MQ101")
+            .extra("difficulty", "Legendary")
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""extras":{"difficulty":"Legendary","quest":"MQ101"}"#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.extras, report.extras);
+
+        // Oversized input is capped rather than costing the report
+        let report = (0..=MAX_EXTRAS)
+            .fold(minimal_builder(), |b, i| b.extra(format!("key{i:02}"), "x"))
+            .extra("key00", "replaced")
+            .extra("", "x")
+            .extra("k".repeat(101), "x")
+            .extra("quest", "x".repeat(501))
+            .build()
+            .unwrap();
+        assert_eq!(report.extras.len(), MAX_EXTRAS);
+        assert_eq!(report.extras["key00"], "replaced");
+        assert!(!report.extras.contains_key(&format!("key{MAX_EXTRAS}")));
+        assert!(!report.extras.contains_key("quest"));
+        assert!(!report.extras.contains_key(""));
+
+        let report = minimal_builder()
+            .extra("quest", "x".repeat(501))
+            .build()
+            .unwrap();
+        assert_eq!(report.extras["quest"].len(), 500);
+    }
+
+    #[test]
+    fn mod_notes_follow_the_user_notes() {
+        let mod_notes = vec![
+            "[MyQuestScript] Stage 40".to_string(),
+            "[MyQuestScript] Boss spawned".to_string(),
+        ];
+
+        let report = minimal_builder()
+            .mod_notes(mod_notes.clone())
+            .build()
+            .unwrap();
+        assert_eq!(
+            report.notes.as_deref(),
+            Some("[MyQuestScript] Stage 40\n[MyQuestScript] Boss spawned")
+        );
+
+        let report = minimal_builder()
+            .notes("Crashed entering the keep")
+            .mod_notes(mod_notes.clone())
+            .build()
+            .unwrap();
+        assert_eq!(
+            report.notes.as_deref(),
+            Some(
+                "Crashed entering the keep\n\n[MyQuestScript] Stage 40\n[MyQuestScript] Boss spawned"
+            )
+        );
+
+        // Mod notes never push the user's notes over the limit
+        let full = "a".repeat(MAX_NOTES_LEN - 30);
+        let report = minimal_builder()
+            .notes(full.clone())
+            .mod_notes(mod_notes)
+            .build()
+            .unwrap();
+        assert_eq!(
+            report.notes.unwrap(),
+            format!("{}\n\n[MyQuestScript] Stage 40", full)
+        );
+
+        assert_eq!(minimal_builder().build().unwrap().notes, None);
+    }
+
+    #[test]
+    fn report_types_use_api_names() {
+        for report_type in [
+            ReportType::Crash,
+            ReportType::Hang,
+            ReportType::Manual,
+            ReportType::Session,
+            ReportType::ScriptError,
+        ] {
+            let json = serde_json::to_string(&report_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", report_type));
+            let parsed: ReportType = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, report_type);
+        }
+
+        let report = CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .report_type(ReportType::ScriptError)
+            .game_version("1.6.1170")
+            .stack_trace("trace")
+            .load_order_v2(ModList::new())
+            .crashed_at(1000)
+            .build()
+            .unwrap();
+        assert!(
+            report
+                .to_json()
+                .unwrap()
+                .contains(r#""reportType":"script-error""#)
+        );
+    }
+
+    #[test]
+    fn manual_report_notes_are_trimmed() {
+        assert_eq!(
+            manual_report_notes("  Save won't load past the main menu\n").unwrap(),
+            "Save won't load past the main menu"
+        );
+        assert!(manual_report_notes("   ").is_err());
+        assert!(manual_report_notes(&"a".repeat(MAX_NOTES_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn frameworks_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("frameworks"));
+
+        let frameworks = BTreeMap::from([
+            ("ArchiveXL".to_string(), "1.21.1.0".to_string()),
+            ("TweakXL".to_string(), "1.10.6.0".to_string()),
+        ]);
+        let report = minimal_builder()
+            .frameworks(frameworks.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""frameworks":{"ArchiveXL":"1.21.1.0","TweakXL":"1.10.6.0"}"#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.frameworks, frameworks);
+
+        let too_many = (0..=MAX_FRAMEWORKS)
+            .map(|i| (format!("Framework{}", i), "1.0".to_string()))
+            .collect();
+        assert!(minimal_builder().frameworks(too_many).build().is_err());
+        let long_version = BTreeMap::from([("Codeware".to_string(), "1".repeat(51))]);
+        assert!(minimal_builder().frameworks(long_version).build().is_err());
+    }
+
+    #[test]
+    fn crash_category_is_serialized() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("crashCategory"));
+        assert!(!json.contains("triageHint"));
+        assert!(!json.contains("deviceRemovedReason"));
+
+        let report = minimal_builder()
+            .crash_category(CrashCategory::Gpu)
+            .device_removed_reason("0x887A0006 DXGI_ERROR_DEVICE_HUNG")
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""crashCategory":"gpu""#));
+        assert!(json.contains(r#""deviceRemovedReason":"0x887A0006 DXGI_ERROR_DEVICE_HUNG""#));
+
+        assert!(
+            minimal_builder()
+                .device_removed_reason("x".repeat(101))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn frame_times_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("frameTimes"));
+
+        let times = FrameTimes {
+            samples: 512,
+            p50_ms: 16.7,
+            p99_ms: 120.0,
+            min_ms: 8.3,
+            max_ms: 250.0,
+            recent_ms: vec![250.0, 120.0, 16.7],
+        };
+        let report = minimal_builder()
+            .frame_times(times.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(
+            r#""frameTimes":{"samples":512,"p50Ms":16.7,"p99Ms":120.0,"minMs":8.3,"maxMs":250.0,"recentMs":[250.0,120.0,16.7]}"#
+        ));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.frame_times, Some(times.clone()));
+
+        let long = FrameTimes {
+            recent_ms: vec![16.7; MAX_RECENT_FRAMES + 1],
+            ..times
+        };
+        assert!(minimal_builder().frame_times(long).build().is_err());
+    }
+
+    #[test]
+    fn uptime_is_serialized() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("uptimeSecs"));
+        assert!(!json.contains("secsSinceLastSave"));
+
+        let report = minimal_builder()
+            .uptime_secs(10_800)
+            .secs_since_last_save(95)
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""uptimeSecs":10800"#));
+        assert!(json.contains(r#""secsSinceLastSave":95"#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.uptime_secs, Some(10_800));
+        assert_eq!(parsed.secs_since_last_save, Some(95));
+    }
+
+    #[test]
+    fn crashes_are_triaged() {
+        let builder = || {
+            minimal_builder()
+                .exception_code("0xC0000005")
+                .faulting_module("SkyrimSE.exe")
+        };
+
+        let report = builder().exception_parameters(&[0, 0x28]).build().unwrap();
+        assert_eq!(report.crash_category, Some(CrashCategory::NullDeref));
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""crashCategory":"null-deref""#));
+        assert!(
+            json.contains(r#""triageHint":"SkyrimSE.exe read from a null pointer (address 0x28)."#)
+        );
+        assert!(!json.contains("exceptionParameters"));
+
+        // The plugin's own category and hint win
+        let report = builder()
+            .exception_parameters(&[0, 0x28])
+            .crash_category(CrashCategory::OutOfMemory)
+            .triage_hint("Lower the texture resolution.")
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""crashCategory":"oom""#));
+        assert!(json.contains(r#""triageHint":"Lower the texture resolution.""#));
+
+        let report = builder()
+            .exception_parameters(&[0, 0x1F2A0000])
+            .build()
+            .unwrap();
+        assert_eq!(report.crash_category, None);
+        assert_eq!(report.triage_hint, None);
+
+        assert!(
+            builder()
+                .triage_hint("x".repeat(MAX_TRIAGE_HINT_LEN + 1))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn address_region_is_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("addressRegion"));
+
+        let region = AddressRegion {
+            base: "0x1F2A0000".into(),
+            size: 0x10000,
+            state: "commit".into(),
+            protection: Some("PAGE_EXECUTE_READWRITE".into()),
+            kind: Some("private".into()),
+            module_before: Some("EngineFixes.dll+0x1A2B3C".into()),
+            module_after: None,
+        };
+        let report = minimal_builder()
+            .address_region(region.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains("\"moduleBefore\":\"EngineFixes.dll+0x1A2B3C\""));
+        assert!(!json.contains("moduleAfter"));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.address_region, Some(region.clone()));
+
+        let long = AddressRegion {
+            module_after: Some("x".repeat(301)),
+            ..region
+        };
+        assert!(minimal_builder().address_region(long).build().is_err());
+    }
+
+    #[test]
+    fn in_page_error_is_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("inPageError"));
+
+        let error = InPageError {
+            status: "0xC000009C".into(),
+            status_name: Some("STATUS_DEVICE_DATA_ERROR".into()),
+            address: "0x1F2A0000".into(),
+            file: Some(r"D:\Steam\Skyrim\Data\Skyrim - Textures0.bsa".into()),
+        };
+        let report = minimal_builder()
+            .crash_category(CrashCategory::DiskIo)
+            .in_page_error(error.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""crashCategory":"disk-io""#));
+        assert!(json.contains(r#""statusName":"STATUS_DEVICE_DATA_ERROR""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.in_page_error, Some(error.clone()));
+
+        let long = InPageError {
+            file: Some("x".repeat(501)),
+            ..error
+        };
+        assert!(minimal_builder().in_page_error(long).build().is_err());
+    }
+
+    #[test]
+    fn disassembly_is_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("disassembly"));
+
+        let text = "> 0x00007FF6A1B2C3CC  488B81A8010000    mov rax, [rcx+0x1a8]";
+        let report = minimal_builder().disassembly(text).build().unwrap();
+        let parsed: CreateCrashReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.disassembly.as_deref(), Some(text));
+
+        assert!(
+            minimal_builder()
+                .disassembly("x".repeat(MAX_DISASSEMBLY_LEN + 1))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn conflicts_are_validated() {
+        let conflicts = vec![ModConflict {
+            kind: ConflictKind::DuplicateName,
+            files: vec![
+                "archive/pc/mod/hair.archive".to_string(),
+                "mods/Hair/archives/hair.archive".to_string(),
+            ],
+        }];
+        let report = minimal_builder()
+            .conflicts(conflicts.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""conflicts":[{"kind":"duplicate-name","files":["#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.conflicts, conflicts);
+
+        let too_many = vec![conflicts[0].clone(); MAX_CONFLICTS + 1];
+        assert!(minimal_builder().conflicts(too_many).build().is_err());
+        let long_path = vec![ModConflict {
+            kind: ConflictKind::DuplicateContent,
+            files: vec!["a".repeat(261)],
+        }];
+        assert!(minimal_builder().conflicts(long_path).build().is_err());
+    }
+
+    #[test]
+    fn attachments_are_validated() {
+        let json = minimal_builder().build().unwrap().to_json().unwrap();
+        assert!(!json.contains("attachments"));
+
+        let logs = vec![Attachment::new(
+            "cyber_engine_tweaks.log",
+            "[error] init.lua:12: attempt to index nil",
+        )];
+        let report = minimal_builder().attachments(logs.clone()).build().unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""attachments":[{"name":"cyber_engine_tweaks.log""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.attachments, logs);
+
+        let too_many = vec![logs[0].clone(); MAX_ATTACHMENTS + 1];
+        assert!(minimal_builder().attachments(too_many).build().is_err());
+        let too_long = Attachment::new("a.log", "x".repeat(MAX_ATTACHMENT_LEN + 1));
+        assert!(
+            minimal_builder()
+                .attachments(vec![too_long])
+                .build()
+                .is_err()
+        );
+        let unnamed = Attachment::new("", "text");
+        assert!(
+            minimal_builder()
+                .attachments(vec![unnamed])
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn recent_assets_are_validated() {
+        let assets = vec![
+            r"meshes\armor\mymod\cuirass.nif".to_string(),
+            r"textures\armor\mymod\cuirass.dds".to_string(),
+        ];
+        let report = minimal_builder()
+            .recent_assets(assets.clone())
+            .build()
+            .unwrap();
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""recentAssets":["meshes\\armor\\mymod\\cuirass.nif""#));
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.recent_assets, assets);
+
+        let too_many = vec!["meshes\\a.nif".to_string(); MAX_RECENT_ASSETS + 1];
+        assert!(minimal_builder().recent_assets(too_many).build().is_err());
+        assert!(
+            minimal_builder()
+                .recent_assets(vec!["a".repeat(261)])
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn save_game_serializes_when_present() {
+        let save = SaveGameInfo {
+            save_number: 7,
+            character_hash: "a1b2c3d4e5f60718".to_string(),
+            level: 31,
+            playtime: "012.34.56".to_string(),
+            missing_from_save: vec!["Removed.esp".to_string()],
+            added_since_save: Vec::new(),
+        };
+        let report = CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("trace")
+            .load_order_v2(ModList::new())
+            .save_game(save.clone())
+            .crashed_at(1000)
+            .build()
+            .unwrap();
+
+        let json = report.to_json().unwrap();
+        assert!(json.contains(r#""saveGame":{"saveNumber":7,"characterHash":"a1b2c3d4e5f60718","level":31,"playtime":"012.34.56","missingFromSave":["Removed.esp"]}"#));
+
+        let parsed: CreateCrashReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.save_game, Some(save));
+    }
+
     #[test]
     fn response_deserializes() {
         let json = r#"{"id":"01ABC","shareToken":"xyz123"}"#;
@@ -504,4 +3207,39 @@ mod tests {
         assert_eq!(response.id, "01ABC");
         assert_eq!(response.share_token, "xyz123");
     }
+
+    #[test]
+    fn stored_report_deserializes() {
+        let json = r#"{
+            "id": "01ABC", "schemaVersion": 2, "gameId": "skyrim-se",
+            "crashHash": "0123456789abcdef", "stackTrace": "SkyrimSE.exe+0x1",
+            "exceptionCode": "0xC0000005", "exceptionAddress": null,
+            "faultingModule": null, "gameVersion": "1.6.1170",
+            "scriptExtenderVersion": null, "osVersion": null,
+            "loadOrder": [{"name": "Skyrim.esm"}], "pluginCount": 1,
+            "crashedAt": 1700000000000, "submittedAt": 1700000001000,
+            "isPublic": false, "notes": null, "pattern": null
+        }"#;
+        let stored: StoredReport = serde_json::from_str(json).unwrap();
+
+        assert_eq!(stored.id, "01ABC");
+        assert_eq!(stored.submitted_at, 1_700_000_001_000);
+        assert_eq!(
+            stored.report.crash_hash.as_deref(),
+            Some("0123456789abcdef")
+        );
+        assert_eq!(stored.report.load_order_json, r#"[{"name":"Skyrim.esm"}]"#);
+    }
+
+    #[test]
+    fn report_page_has_more() {
+        let page = ReportPage {
+            reports: Vec::new(),
+            page: 1,
+            per_page: 20,
+            total: 21,
+        };
+        assert!(page.has_more());
+        assert!(!ReportPage { page: 2, ..page }.has_more());
+    }
 }
@@ -0,0 +1,105 @@
+//! Symbol name demangling.
+//!
+//! PDB public symbols carry decorated names (e.g., `?Update@Actor@@UEAAXM@Z`).
+//! This module turns MSVC, Itanium (`_Z`), and Rust (`_R` / legacy `_ZN...E`)
+//! mangled names back into readable form for stack traces.
+
+use msvc_demangler::DemangleFlags;
+
+/// Demangles a symbol name, returning `None` if it is not mangled or cannot be parsed.
+pub fn demangle(name: &str) -> Option<String> {
+    if name.starts_with('?') {
+        demangle_msvc(name)
+    } else if name.starts_with("_R") || is_rust_legacy(name) {
+        rustc_demangle::try_demangle(name)
+            .ok()
+            .map(|d| format!("{:#}", d))
+    } else if name.starts_with("_Z") {
+        demangle_itanium(name)
+    } else {
+        None
+    }
+}
+
+/// Demangles a symbol name, falling back to the raw name.
+pub fn demangle_or_raw(name: &str) -> String {
+    demangle(name).unwrap_or_else(|| name.to_string())
+}
+
+/// Demangles an MSVC decorated name without access specifiers, return
+/// types, or calling conventions (e.g., `Actor::Update(float)`).
+fn demangle_msvc(name: &str) -> Option<String> {
+    let flags = DemangleFlags::llvm()
+        | DemangleFlags::NO_ACCESS_SPECIFIERS
+        | DemangleFlags::NO_FUNCTION_RETURNS
+        | DemangleFlags::NO_MEMBER_TYPE
+        | DemangleFlags::NO_MS_KEYWORDS;
+
+    msvc_demangler::demangle(name, flags).ok()
+}
+
+/// Demangles an Itanium C++ ABI name (MinGW/Clang builds).
+fn demangle_itanium(name: &str) -> Option<String> {
+    cpp_demangle::Symbol::new(name)
+        .ok()?
+        .demangle(&cpp_demangle::DemangleOptions::default())
+        .ok()
+}
+
+/// Returns true for legacy Rust symbols, which are Itanium-style names
+/// ending in a `17h<16 hex digits>E` hash segment.
+fn is_rust_legacy(name: &str) -> bool {
+    let Some(body) = name
+        .strip_prefix("_ZN")
+        .and_then(|rest| rest.strip_suffix('E'))
+    else {
+        return false;
+    };
+
+    let body = body.as_bytes();
+    body.len() >= 19
+        && body[body.len() - 19..].starts_with(b"17h")
+        && body[body.len() - 16..].iter().all(u8::is_ascii_hexdigit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demangles_msvc_member_function() {
+        let name = demangle("?Update@Actor@@UEAAXM@Z").unwrap();
+        assert!(name.contains("Actor::Update"), "got {}", name);
+        assert!(!name.contains("public"), "got {}", name);
+    }
+
+    #[test]
+    fn demangles_itanium_function() {
+        let name = demangle("_ZN5Actor6UpdateEf").unwrap();
+        assert!(name.starts_with("Actor::Update"), "got {}", name);
+    }
+
+    #[test]
+    fn demangles_rust_legacy_without_hash() {
+        let name = demangle("_ZN8ctd_core7symbols7resolve17h0123456789abcdefE").unwrap();
+        assert_eq!(name, "ctd_core::symbols::resolve");
+    }
+
+    #[test]
+    fn plain_names_are_not_demangled() {
+        assert_eq!(demangle("WinMain"), None);
+        assert_eq!(demangle_or_raw("WinMain"), "WinMain");
+    }
+
+    #[test]
+    fn invalid_mangled_names_fall_back_to_raw() {
+        assert_eq!(demangle_or_raw("?"), "?");
+    }
+
+    #[test]
+    fn rust_legacy_detection_requires_hash() {
+        assert!(is_rust_legacy("_ZN3foo3bar17h0123456789abcdefE"));
+        assert!(!is_rust_legacy("_ZN5Actor6UpdateEf"));
+        assert!(!is_rust_legacy("_ZN3foo3barE"));
+    }
+}
@@ -0,0 +1,315 @@
+//! Detours on the faulting module's exported functions.
+//!
+//! Mods hook game and system functions by overwriting their first
+//! instructions with a jump to their own code. Two mods hooking the same
+//! function, or a hook written for another build, is a classic crash cause,
+//! and none of it shows in a stack trace. [`scan`] compares the first bytes
+//! of every function the faulting module exports with the same bytes in the
+//! file on disk; an entry point that now starts with a jump is a detour. The
+//! jump is followed through any trampoline stubs to the loaded module that
+//! owns the code it ends in.
+//!
+//! Only jumps count: relocations rewrite absolute addresses in 32-bit
+//! prologues without anything having hooked them.
+
+use std::path::Path;
+
+use tracing::debug;
+
+use crate::crash_report::{HookedFunction, MAX_HOOKED_FUNCTIONS};
+use crate::memory_map::{self, LoadedModule, ReadMemory};
+use crate::pe::{PeError, PeImage};
+
+/// Bytes compared at each entry point, enough for every jump encoding.
+const ENTRY_BYTES: usize = 16;
+
+/// Most trampoline stubs followed from a patched entry point.
+const MAX_HOPS: usize = 4;
+
+/// Most exports checked in one module.
+const MAX_EXPORTS: usize = 8192;
+
+/// A jump at the start of patched code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Jump {
+    /// Jumps to this address.
+    Direct(u64),
+    /// Jumps to the address stored at this address.
+    Indirect(u64),
+}
+
+/// Returns the exported functions of `faulting_module` (a file name or path)
+/// whose entry points were patched with a jump, at most
+/// [`MAX_HOOKED_FUNCTIONS`]. Empty if the module isn't loaded or its file
+/// can't be read, and on other platforms.
+pub fn scan(faulting_module: &str) -> Vec<HookedFunction> {
+    let Some(name) = Path::new(faulting_module)
+        .file_name()
+        .and_then(|name| name.to_str())
+    else {
+        return Vec::new();
+    };
+    let modules = memory_map::loaded_modules();
+    let Some(module) = modules
+        .iter()
+        .find(|module| module.name.eq_ignore_ascii_case(name))
+    else {
+        return Vec::new();
+    };
+
+    match scan_module(module, &modules, &memory_map::read) {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            debug!("Cannot check {} for detours: {}", module.name, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Compares a loaded module's exported entry points with its file on disk.
+fn scan_module(
+    module: &LoadedModule,
+    modules: &[LoadedModule],
+    read: ReadMemory,
+) -> Result<Vec<HookedFunction>, PeError> {
+    let mut image = PeImage::open(&module.path)?;
+    let is_64 = image.is_64();
+
+    let mut hooks = Vec::new();
+    for export in image.exports()?.into_iter().take(MAX_EXPORTS) {
+        let Ok(on_disk) = image.read_rva(export.rva, ENTRY_BYTES) else {
+            continue;
+        };
+        let address = module.base + u64::from(export.rva);
+        let Some(in_memory) = read(address, ENTRY_BYTES) else {
+            continue;
+        };
+        let Some(jump) = patched_entry(&on_disk, &in_memory, address, is_64) else {
+            continue;
+        };
+        let Some(target) = follow(jump, module, modules, is_64, read) else {
+            continue;
+        };
+
+        let owner = modules.iter().find(|m| m.contains(target));
+        hooks.push(HookedFunction {
+            function: export.name,
+            target: match owner {
+                Some(owner) => format!("{}+0x{:X}", owner.name, target - owner.base),
+                None => format!("0x{:X}", target),
+            },
+            owner: owner.map(|owner| owner.name.clone()),
+        });
+        if hooks.len() >= MAX_HOOKED_FUNCTIONS {
+            break;
+        }
+    }
+    Ok(hooks)
+}
+
+/// Returns the jump an entry point was patched with, or `None` if its bytes
+/// match the file or don't start with a jump.
+fn patched_entry(on_disk: &[u8], in_memory: &[u8], address: u64, is_64: bool) -> Option<Jump> {
+    if in_memory == on_disk {
+        return None;
+    }
+    let jump = decode_jump(in_memory, address, is_64)?;
+    // A jump the file already had is the function's own code
+    (decode_jump(on_disk, address, is_64) != Some(jump)).then_some(jump)
+}
+
+/// Decodes the jump encodings detour libraries write.
+fn decode_jump(code: &[u8], address: u64, is_64: bool) -> Option<Jump> {
+    let at = |offset: usize, len: usize| code.get(offset..offset + len);
+    let i32_at = |offset: usize| at(offset, 4).map(|b| i32::from_le_bytes(b.try_into().unwrap()));
+    let u32_at = |offset: usize| at(offset, 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+    let u64_at = |offset: usize| at(offset, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap()));
+    let relative = |len: u64, displacement: i64| {
+        let target = address.wrapping_add(len).wrapping_add_signed(displacement);
+        if is_64 { target } else { target & 0xFFFF_FFFF }
+    };
+
+    match code {
+        // jmp rel32
+        [0xE9, ..] => Some(Jump::Direct(relative(5, i64::from(i32_at(1)?)))),
+        // jmp rel8, as in hot-patched functions
+        [0xEB, displacement, ..] => Some(Jump::Direct(relative(2, i64::from(*displacement as i8)))),
+        // jmp [rip+disp32] on x64, jmp [abs32] on x86
+        [0xFF, 0x25, ..] if is_64 => Some(Jump::Indirect(relative(6, i64::from(i32_at(2)?)))),
+        [0xFF, 0x25, ..] => Some(Jump::Indirect(u64::from(u32_at(2)?))),
+        // mov rax, imm64; jmp rax
+        [0x48, 0xB8, ..] if is_64 && at(10, 2)? == [0xFF, 0xE0] => Some(Jump::Direct(u64_at(2)?)),
+        // mov r11, imm64; jmp r11
+        [0x49, 0xBB, ..] if is_64 && at(10, 3)? == [0x41, 0xFF, 0xE3] => {
+            Some(Jump::Direct(u64_at(2)?))
+        }
+        // push imm32; ret
+        [0x68, ..] if at(5, 1)? == [0xC3] => Some(Jump::Direct(u64::from(u32_at(1)?))),
+        _ => None,
+    }
+}
+
+/// Follows a detour through trampoline stubs until it reaches code in
+/// another module or code that isn't a jump. Returns `None` if the first
+/// jump's pointer can't be read.
+fn follow(
+    mut jump: Jump,
+    hooked: &LoadedModule,
+    modules: &[LoadedModule],
+    is_64: bool,
+    read: ReadMemory,
+) -> Option<u64> {
+    let mut target = resolve(jump, is_64, read)?;
+    for _ in 1..MAX_HOPS {
+        // Stubs live in private memory or in code caves of the hooked module
+        let in_other_module = modules
+            .iter()
+            .any(|module| module.contains(target) && module.base != hooked.base);
+        if in_other_module {
+            break;
+        }
+        match read(target, ENTRY_BYTES).and_then(|code| decode_jump(&code, target, is_64)) {
+            Some(next) => jump = next,
+            None => break,
+        }
+        match resolve(jump, is_64, read) {
+            Some(next) => target = next,
+            None => break,
+        }
+    }
+    Some(target)
+}
+
+/// Returns where a jump goes, reading the pointer of an indirect one.
+fn resolve(jump: Jump, is_64: bool, read: ReadMemory) -> Option<u64> {
+    match jump {
+        Jump::Direct(target) => Some(target),
+        Jump::Indirect(slot) if is_64 => read(slot, 8)
+            .and_then(|bytes| Some(u64::from_le_bytes(bytes.as_slice().try_into().ok()?))),
+        Jump::Indirect(slot) => read(slot, 4).and_then(|bytes| {
+            Some(u64::from(u32::from_le_bytes(
+                bytes.as_slice().try_into().ok()?,
+            )))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    const GAME_BASE: u64 = 0x7FF600000000;
+    const HOOK_BASE: u64 = 0x7FFA10000000;
+    /// Trampoline stubs are allocated within 2 GB of the hooked module.
+    const STUB: u64 = GAME_BASE - 0x10000;
+
+    fn module(name: &str, base: u64) -> LoadedModule {
+        LoadedModule {
+            name: name.to_string(),
+            path: PathBuf::from(name),
+            base,
+            size: 0x100000,
+        }
+    }
+
+    /// Fake process memory: code blocks by start address.
+    fn reader(memory: HashMap<u64, Vec<u8>>) -> impl Fn(u64, usize) -> Option<Vec<u8>> {
+        move |address, len| {
+            memory.iter().find_map(|(&start, bytes)| {
+                let offset = usize::try_from(address.checked_sub(start)?).ok()?;
+                bytes.get(offset..offset + len).map(<[u8]>::to_vec)
+            })
+        }
+    }
+
+    fn jmp_rel32(from: u64, to: u64) -> Vec<u8> {
+        let displacement = (to as i64 - (from as i64 + 5)) as i32;
+        let mut code = vec![0xE9];
+        code.extend(displacement.to_le_bytes());
+        code.resize(ENTRY_BYTES, 0xCC);
+        code
+    }
+
+    #[test]
+    fn only_new_jumps_are_detours() {
+        let prologue = [0x48, 0x89, 0x5C, 0x24, 0x08, 0x57, 0x48, 0x83, 0xEC, 0x20];
+        let hooked = jmp_rel32(GAME_BASE + 0x1000, STUB);
+
+        assert_eq!(
+            patched_entry(&prologue, &hooked[..10], GAME_BASE + 0x1000, true),
+            Some(Jump::Direct(STUB))
+        );
+        // Unchanged, or a jump the file already had
+        assert_eq!(
+            patched_entry(&hooked, &hooked, GAME_BASE + 0x1000, true),
+            None
+        );
+        let mut relocated = hooked.clone();
+        relocated[10] = 0x90;
+        assert_eq!(
+            patched_entry(&hooked, &relocated, GAME_BASE + 0x1000, true),
+            None
+        );
+        // A relocated 32-bit prologue isn't a jump
+        let push = [0x55, 0x8B, 0xEC, 0x68, 0x00, 0x10, 0x40, 0x00];
+        let moved = [0x55, 0x8B, 0xEC, 0x68, 0x00, 0x10, 0x50, 0x00];
+        assert_eq!(patched_entry(&push, &moved, 0x401000, false), None);
+    }
+
+    #[test]
+    fn decodes_detour_encodings() {
+        let mut movabs = vec![0x48, 0xB8];
+        movabs.extend(HOOK_BASE.to_le_bytes());
+        movabs.extend([0xFF, 0xE0]);
+        assert_eq!(decode_jump(&movabs, 0, true), Some(Jump::Direct(HOOK_BASE)));
+
+        let rip_relative = [0xFF, 0x25, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(
+            decode_jump(&rip_relative, STUB, true),
+            Some(Jump::Indirect(STUB + 6))
+        );
+        let absolute = [0xFF, 0x25, 0x00, 0x20, 0x40, 0x00];
+        assert_eq!(
+            decode_jump(&absolute, 0x401000, false),
+            Some(Jump::Indirect(0x402000))
+        );
+
+        let push_ret = [0x68, 0x00, 0x30, 0x40, 0x00, 0xC3];
+        assert_eq!(
+            decode_jump(&push_ret, 0x401000, false),
+            Some(Jump::Direct(0x403000))
+        );
+        // Hot patch: a short jump back into the padding above the function
+        assert_eq!(
+            decode_jump(&[0xEB, 0xF9], 0x401005, false),
+            Some(Jump::Direct(0x401000))
+        );
+        assert_eq!(decode_jump(&[0x48, 0x89, 0x5C], 0, true), None);
+    }
+
+    #[test]
+    fn follows_trampoline_stubs_to_the_hooking_module() {
+        let game = module("SkyrimSE.exe", GAME_BASE);
+        let modules = [game.clone(), module("EngineFixes.dll", HOOK_BASE)];
+
+        // The entry jumps to a stub in private memory, which jumps through
+        // an absolute pointer into the hooking DLL
+        let mut stub = vec![0xFF, 0x25, 0x00, 0x00, 0x00, 0x00];
+        stub.extend((HOOK_BASE + 0x2000).to_le_bytes());
+        stub.resize(ENTRY_BYTES, 0xCC);
+        let read = reader(HashMap::from([(STUB, stub)]));
+
+        assert_eq!(
+            follow(Jump::Direct(STUB), &game, &modules, true, &read),
+            Some(HOOK_BASE + 0x2000)
+        );
+        // An unreadable pointer ends the search before it starts
+        assert_eq!(
+            follow(Jump::Indirect(0x1000), &game, &modules, true, &read),
+            None
+        );
+    }
+}
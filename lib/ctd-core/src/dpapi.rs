@@ -0,0 +1,121 @@
+//! Windows DPAPI encryption for secrets stored in `ctd.toml`.
+//!
+//! Data is encrypted with `CryptProtectData` under the current Windows user,
+//! so an encrypted API key copied to another machine or account is useless.
+//! Other platforms have no DPAPI and always return an error.
+
+use crate::{CtdError, Result};
+
+/// Extra entropy so other programs running as the same user can't decrypt
+/// CTD's secrets by accident.
+#[cfg(windows)]
+const ENTROPY: &[u8] = b"ctd-api-key";
+
+/// Encrypts `data` for the current Windows user.
+///
+/// # Errors
+///
+/// Returns `CtdError::Config` if encryption fails or DPAPI is unavailable.
+#[cfg(windows)]
+pub(crate) fn protect(data: &[u8]) -> Result<Vec<u8>> {
+    use windows::Win32::Security::Cryptography::{
+        CRYPT_INTEGER_BLOB, CRYPTPROTECT_UI_FORBIDDEN, CryptProtectData,
+    };
+
+    let input = blob(data);
+    let entropy = blob(ENTROPY);
+    let mut output = CRYPT_INTEGER_BLOB::default();
+
+    unsafe {
+        CryptProtectData(
+            &input,
+            windows::core::w!("CTD API key"),
+            Some(&entropy),
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+        .map_err(|e| CtdError::Config(format!("DPAPI encryption failed: {}", e)))?;
+
+        Ok(take_blob(output))
+    }
+}
+
+/// Decrypts data encrypted by [`protect`] for the current Windows user.
+///
+/// # Errors
+///
+/// Returns `CtdError::Config` if the data was encrypted by another user or
+/// machine, is corrupt, or DPAPI is unavailable.
+#[cfg(windows)]
+pub(crate) fn unprotect(data: &[u8]) -> Result<Vec<u8>> {
+    use windows::Win32::Security::Cryptography::{
+        CRYPT_INTEGER_BLOB, CRYPTPROTECT_UI_FORBIDDEN, CryptUnprotectData,
+    };
+
+    let input = blob(data);
+    let entropy = blob(ENTROPY);
+    let mut output = CRYPT_INTEGER_BLOB::default();
+
+    unsafe {
+        CryptUnprotectData(
+            &input,
+            None,
+            Some(&entropy),
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+        .map_err(|e| CtdError::Config(format!("DPAPI decryption failed: {}", e)))?;
+
+        Ok(take_blob(output))
+    }
+}
+
+/// Wraps a byte slice for DPAPI, which never writes through input blobs.
+#[cfg(windows)]
+fn blob(data: &[u8]) -> windows::Win32::Security::Cryptography::CRYPT_INTEGER_BLOB {
+    windows::Win32::Security::Cryptography::CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr().cast_mut(),
+    }
+}
+
+/// Copies a DPAPI output blob and frees it.
+///
+/// # Safety
+///
+/// `blob` must have been filled in by `CryptProtectData`/`CryptUnprotectData`.
+#[cfg(windows)]
+unsafe fn take_blob(blob: windows::Win32::Security::Cryptography::CRYPT_INTEGER_BLOB) -> Vec<u8> {
+    use windows::Win32::Foundation::{HLOCAL, LocalFree};
+
+    unsafe {
+        let bytes = std::slice::from_raw_parts(blob.pbData, blob.cbData as usize).to_vec();
+        let _ = LocalFree(HLOCAL(blob.pbData.cast()));
+        bytes
+    }
+}
+
+/// Stub implementation for non-Windows platforms.
+///
+/// Always returns `CtdError::Config` since DPAPI only exists on Windows.
+#[cfg(not(windows))]
+pub(crate) fn protect(_data: &[u8]) -> Result<Vec<u8>> {
+    Err(unsupported())
+}
+
+/// Stub implementation for non-Windows platforms.
+///
+/// Always returns `CtdError::Config` since DPAPI only exists on Windows.
+#[cfg(not(windows))]
+pub(crate) fn unprotect(_data: &[u8]) -> Result<Vec<u8>> {
+    Err(unsupported())
+}
+
+#[cfg(not(windows))]
+fn unsupported() -> CtdError {
+    CtdError::Config("Encrypted API keys require Windows (DPAPI)".to_string())
+}
@@ -0,0 +1,118 @@
+//! DWARF symbol resolution for ELF binaries.
+//!
+//! Windows builds ship PDBs, but development builds on Linux (and the test
+//! suite itself) produce ELF binaries with DWARF debug info. This backend lets
+//! [`SymbolResolver`](crate::symbols::SymbolResolver) symbolicate those frames
+//! with the same offsets-from-module-base used for PE modules.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::demangle;
+use crate::{CtdError, Result};
+
+/// Magic bytes at the start of every ELF file.
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+/// Symbol lookup result: function name, source file, and line.
+pub type DwarfLocation = (String, Option<String>, Option<u32>);
+
+/// Parsed symbol and line information for one ELF module.
+pub struct DwarfSymbols {
+    loader: addr2line::Loader,
+}
+
+impl DwarfSymbols {
+    /// Opens an ELF file and prepares its symbol table and DWARF line info.
+    pub fn open(path: &Path) -> Result<Self> {
+        let loader = addr2line::Loader::new(path)
+            .map_err(|e| CtdError::Symbol(format!("Failed to load DWARF info: {}", e)))?;
+        Ok(Self { loader })
+    }
+
+    /// Looks up the function and source location at an offset from the module base.
+    ///
+    /// The function name comes from the symbol table (so stripped-DWARF
+    /// binaries still resolve); file and line come from DWARF when present.
+    pub fn lookup(&self, offset: u64) -> Option<DwarfLocation> {
+        let probe = self.loader.relative_address_base().wrapping_add(offset);
+        let function = self.loader.find_symbol(probe)?;
+
+        let (file, line) = match self.loader.find_location(probe) {
+            Ok(Some(location)) => (location.file.map(str::to_string), location.line),
+            _ => (None, None),
+        };
+
+        Some((demangle::demangle_or_raw(function), file, line))
+    }
+}
+
+/// Returns true if the file at `path` is an ELF binary.
+pub fn is_elf(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && &magic == ELF_MAGIC
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A function with a stable, unmangled-in-source name to look up.
+    #[cfg(target_os = "linux")]
+    #[inline(never)]
+    pub(crate) fn dwarf_probe_marker() -> u32 {
+        std::hint::black_box(42)
+    }
+
+    /// Finds the load address of the running test executable on Linux.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn current_exe_base() -> Option<u64> {
+        let exe = std::env::current_exe().ok()?;
+        let maps = std::fs::read_to_string("/proc/self/maps").ok()?;
+
+        maps.lines().find_map(|line| {
+            let mut fields = line.split_whitespace();
+            let range = fields.next()?;
+            let _perms = fields.next()?;
+            let offset = fields.next()?;
+            let path = fields.nth(2)?;
+
+            (offset.trim_start_matches('0').is_empty() && Path::new(path) == exe)
+                .then(|| u64::from_str_radix(range.split('-').next()?, 16).ok())
+                .flatten()
+        })
+    }
+
+    #[test]
+    fn detects_elf_magic() {
+        let dir = tempfile::tempdir().unwrap();
+        let elf = dir.path().join("a.so");
+        let other = dir.path().join("a.dll");
+        std::fs::write(&elf, b"\x7fELF\x02\x01\x01").unwrap();
+        std::fs::write(&other, b"MZ\x90\x00").unwrap();
+
+        assert!(is_elf(&elf));
+        assert!(!is_elf(&other));
+        assert!(!is_elf(&dir.path().join("missing")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn resolves_function_in_test_binary() {
+        let exe = std::env::current_exe().unwrap();
+        let base = current_exe_base().expect("test executable mapping");
+        let offset = dwarf_probe_marker as *const () as usize as u64 - base;
+
+        let symbols = DwarfSymbols::open(&exe).unwrap();
+        let (function, file, _line) = symbols.lookup(offset).unwrap();
+
+        assert!(function.contains("dwarf_probe_marker"), "got {}", function);
+        if let Some(file) = file {
+            assert!(file.ends_with("dwarf.rs"), "got {}", file);
+        }
+    }
+}
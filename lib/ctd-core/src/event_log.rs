@@ -0,0 +1,326 @@
+//! Windows Event Log entries for captured crashes.
+//!
+//! With `[logging] event_log = true`, the crash handlers write an entry to
+//! the Application log under the source [`SOURCE`] for every crash or hang
+//! they capture, carrying the crash hash, the triage hint, and the share
+//! link (or a note that the report is queued). Admins and power users can then line crashes up
+//! with GPU driver resets (TDRs), WHEA hardware errors, and the rest of the
+//! system's events in Event Viewer.
+//!
+//! Windows shows entries from an unregistered source with a "description
+//! cannot be found" preamble. [`register_source`] (`ctd event-log register`,
+//! run as administrator) registers the source once so only the text shows.
+
+use std::io;
+
+use tracing::warn;
+
+use crate::api_client;
+use crate::config::Config;
+use crate::crash_hash::compute_crash_hash;
+use crate::crash_report::{CrashReportResponse, CreateCrashReport, ReportType};
+use crate::import::exception_name;
+
+/// Event source name shown in Event Viewer.
+pub const SOURCE: &str = "CTD Crash Reporter";
+
+/// Event ID of crash entries.
+pub const CRASH_EVENT_ID: u32 = 1000;
+
+/// Event ID of hang entries.
+pub const HANG_EVENT_ID: u32 = 1001;
+
+/// Registry key under which Application log sources are registered.
+pub const SOURCES_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog\Application";
+
+/// Message file whose every event ID formats as its first string, shipped
+/// with the .NET Framework on every supported Windows version.
+#[cfg_attr(not(windows), allow(dead_code))]
+const MESSAGE_FILE: &str =
+    r"%SystemRoot%\Microsoft.NET\Framework64\v4.0.30319\EventLogMessages.dll";
+
+/// Severity of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLevel {
+    /// Shown with the red error icon.
+    Error,
+    /// Shown with the yellow warning icon.
+    Warning,
+}
+
+/// Returns the entry's event ID and level, or `None` for reports that are
+/// not crash events (manual reports, sessions, script errors).
+pub fn event_kind(report: &CreateCrashReport) -> Option<(u32, EventLevel)> {
+    match report.report_type {
+        ReportType::Crash => Some((CRASH_EVENT_ID, EventLevel::Error)),
+        ReportType::Hang => Some((HANG_EVENT_ID, EventLevel::Warning)),
+        ReportType::Manual | ReportType::Session | ReportType::ScriptError => None,
+    }
+}
+
+/// Formats the entry text for a report.
+///
+/// `share_url` is the link to the submitted report; without one the entry
+/// says the report is waiting in the queue.
+pub fn message(report: &CreateCrashReport, share_url: Option<&str>) -> String {
+    let kind = if report.report_type == ReportType::Hang {
+        "Hang"
+    } else {
+        "Crash"
+    };
+    let crash_hash = report
+        .crash_hash
+        .clone()
+        .unwrap_or_else(|| compute_crash_hash(&report.stack_trace));
+
+    let mut lines = vec![
+        format!("{} in {} {}", kind, report.game_id, report.game_version),
+        format!("Crash hash: {}", crash_hash),
+    ];
+    if let Some(code) = &report.exception_code {
+        match exception_name(code) {
+            Some(name) => lines.push(format!("Exception: {} ({})", name, code)),
+            None => lines.push(format!("Exception: {}", code)),
+        }
+    }
+    if let Some(address) = &report.exception_address {
+        lines.push(format!("Address: {}", address));
+    }
+    if let Some(module) = &report.faulting_module {
+        lines.push(format!("Faulting module: {}", module));
+    }
+    if let Some(suspect) = &report.suspected_mod {
+        lines.push(format!("Suspected mod: {}", suspect));
+    }
+    if let Some(hint) = &report.triage_hint {
+        lines.push(format!("Triage: {}", hint));
+    }
+    lines.push(match share_url {
+        Some(url) => format!("Report: {}", url),
+        None => "Report: not submitted yet; retry with `ctd pending flush`".to_string(),
+    });
+    lines.join("\r\n")
+}
+
+/// Writes the entry for a captured crash when `[logging] event_log` is on,
+/// logging instead of failing.
+///
+/// `response` is the server's answer, or `None` if submission failed.
+pub fn record(report: &CreateCrashReport, response: Option<&CrashReportResponse>) {
+    let config = Config::load().unwrap_or_default();
+    if !config.logging.event_log {
+        return;
+    }
+    let Some((event_id, level)) = event_kind(report) else {
+        return;
+    };
+
+    // Sentry events have no share link
+    let share_url = response
+        .filter(|_| config.sentry.dsn.is_none())
+        .map(|response| api_client::share_url(&config.api, response));
+    if let Err(e) = report_event(level, event_id, &message(report, share_url.as_deref())) {
+        warn!("Failed to write Event Log entry: {}", e);
+    }
+}
+
+/// Writes an entry to the Application log under [`SOURCE`].
+///
+/// # Errors
+///
+/// Returns an error if the event source cannot be opened or the entry
+/// cannot be written.
+#[cfg(windows)]
+pub fn report_event(level: EventLevel, event_id: u32, message: &str) -> io::Result<()> {
+    use windows::Win32::System::EventLog::{
+        DeregisterEventSource, EVENTLOG_ERROR_TYPE, EVENTLOG_WARNING_TYPE, RegisterEventSourceW,
+        ReportEventW,
+    };
+    use windows::core::{HSTRING, PCWSTR};
+
+    let event_type = match level {
+        EventLevel::Error => EVENTLOG_ERROR_TYPE,
+        EventLevel::Warning => EVENTLOG_WARNING_TYPE,
+    };
+    let text = HSTRING::from(message);
+    let strings = [PCWSTR(text.as_ptr())];
+
+    // SAFETY: The source name and message are valid wide strings that
+    // outlive the calls; the handle is deregistered below
+    unsafe {
+        let handle = RegisterEventSourceW(PCWSTR::null(), &HSTRING::from(SOURCE))
+            .map_err(io::Error::other)?;
+        let result = ReportEventW(
+            handle,
+            event_type,
+            0,
+            event_id,
+            None,
+            0,
+            Some(&strings),
+            None,
+        );
+        let _ = DeregisterEventSource(handle);
+        result.map_err(io::Error::other)
+    }
+}
+
+/// Writes an entry to the Application log under [`SOURCE`].
+///
+/// # Errors
+///
+/// The Event Log only exists on Windows; always returns `Unsupported`.
+#[cfg(not(windows))]
+pub fn report_event(_level: EventLevel, _event_id: u32, _message: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the Event Log is only available on Windows",
+    ))
+}
+
+/// Registers [`SOURCE`] with the Application log, so Event Viewer shows
+/// entries without a "description cannot be found" preamble.
+///
+/// Writes `HKLM\{SOURCES_KEY}\CTD Crash Reporter`, which needs administrator
+/// rights.
+///
+/// # Errors
+///
+/// Returns an error if the registry key cannot be written.
+#[cfg(windows)]
+pub fn register_source() -> io::Result<()> {
+    use windows::Win32::System::Registry::{
+        HKEY, HKEY_LOCAL_MACHINE, KEY_SET_VALUE, REG_DWORD, REG_EXPAND_SZ, REG_OPTION_NON_VOLATILE,
+        RegCloseKey, RegCreateKeyExW, RegSetValueExW,
+    };
+    use windows::core::{HSTRING, PCWSTR};
+
+    let subkey = HSTRING::from(format!("{}\\{}", SOURCES_KEY, SOURCE));
+    let mut key = HKEY::default();
+    // SAFETY: The subkey is a valid wide string; the key is closed below
+    unsafe {
+        RegCreateKeyExW(
+            HKEY_LOCAL_MACHINE,
+            &subkey,
+            0,
+            PCWSTR::null(),
+            REG_OPTION_NON_VOLATILE,
+            KEY_SET_VALUE,
+            None,
+            &mut key,
+            None,
+        )
+    }
+    .ok()
+    .map_err(io::Error::other)?;
+
+    // REG_EXPAND_SZ data is the null-terminated UTF-16 string
+    let message_file: Vec<u8> = MESSAGE_FILE
+        .encode_utf16()
+        .chain([0])
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let values = [
+        ("EventMessageFile", REG_EXPAND_SZ, message_file),
+        // Error, warning, and information entries
+        ("TypesSupported", REG_DWORD, 7u32.to_le_bytes().to_vec()),
+    ];
+    let result = values.iter().try_for_each(|(name, kind, data)| {
+        // SAFETY: The key is open for writing; the data outlives the call
+        unsafe { RegSetValueExW(key, &HSTRING::from(*name), 0, *kind, Some(data.as_slice())) }
+            .ok()
+            .map_err(io::Error::other)
+    });
+    // SAFETY: The key was opened above
+    let _ = unsafe { RegCloseKey(key) };
+    result
+}
+
+/// Registers [`SOURCE`] with the Application log.
+///
+/// # Errors
+///
+/// The Event Log only exists on Windows; always returns `Unsupported`.
+#[cfg(not(windows))]
+pub fn register_source() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the Event Log is only available on Windows",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModList;
+
+    fn sample_report() -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("[0] SkyrimSE.exe+0x1234 (0x7FF600001234)")
+            .exception_code("0xC0000005")
+            .exception_address("0x7FF600001234")
+            .faulting_module("SkyrimSE.exe")
+            .load_order_v2(ModList::new())
+            .crashed_at(1000)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn message_has_hash_and_link() {
+        let report = sample_report();
+        let text = message(&report, Some("https://ctd.example/crashes/01HX?token=tok"));
+        let lines: Vec<&str> = text.split("\r\n").collect();
+
+        assert_eq!(lines[0], "Crash in skyrim-se 1.6.1170");
+        assert_eq!(
+            lines[1],
+            format!("Crash hash: {}", compute_crash_hash(&report.stack_trace))
+        );
+        assert!(lines.contains(&"Exception: EXCEPTION_ACCESS_VIOLATION (0xC0000005)"));
+        assert_eq!(
+            lines.last(),
+            Some(&"Report: https://ctd.example/crashes/01HX?token=tok")
+        );
+
+        let queued = message(&report, None);
+        assert!(queued.ends_with("retry with `ctd pending flush`"));
+    }
+
+    #[test]
+    fn message_has_triage_hint() {
+        let report = sample_report();
+        assert!(!message(&report, None).contains("Triage:"));
+
+        let report = CreateCrashReport {
+            triage_hint: Some("The game ran out of memory.".into()),
+            ..report
+        };
+        let text = message(&report, None);
+        assert!(
+            text.split("\r\n")
+                .any(|line| line == "Triage: The game ran out of memory.")
+        );
+    }
+
+    #[test]
+    fn only_crashes_and_hangs_are_events() {
+        let mut report = sample_report();
+        assert_eq!(
+            event_kind(&report),
+            Some((CRASH_EVENT_ID, EventLevel::Error))
+        );
+
+        report.report_type = ReportType::Hang;
+        assert_eq!(
+            event_kind(&report),
+            Some((HANG_EVENT_ID, EventLevel::Warning))
+        );
+        assert!(message(&report, None).starts_with("Hang in"));
+
+        report.report_type = ReportType::Manual;
+        assert_eq!(event_kind(&report), None);
+    }
+}
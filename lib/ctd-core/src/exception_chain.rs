@@ -0,0 +1,191 @@
+//! Exceptions that came before a crash.
+//!
+//! The fatal fault is often a symptom of an earlier exception: a C++
+//! `throw` a mod caught and ignored left an object half-built, or the fault
+//! happened while another exception was being dispatched. The crash
+//! handlers call [`record`] for every exception that isn't fatal, which
+//! keeps the last [`RECENT_EXCEPTIONS`] with their address and time, and
+//! build the report's `exceptionChain` with [`capture`]: the records nested
+//! in the fatal exception's own (`EXCEPTION_RECORD.ExceptionRecord`), then
+//! the recent first-chance exceptions, newest first.
+//!
+//! [`record`] runs inside the exception handler, on whichever thread raised
+//! the exception, so it only touches atomics: a fixed ring that overwrites
+//! its oldest entry. Debugger output and thread names, which some games
+//! raise by the thousand, are left out so they don't flush the ring.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::crash_guard::now_millis;
+use crate::crash_report::{ChainedException, ExceptionSource, MAX_EXCEPTION_CHAIN};
+
+/// First-chance exceptions kept for the next report.
+pub const RECENT_EXCEPTIONS: usize = 16;
+
+/// Most nested records a report lists; the rest of `exceptionChain` is
+/// left for the recent exceptions.
+pub const MAX_NESTED: usize = MAX_EXCEPTION_CHAIN - RECENT_EXCEPTIONS;
+
+/// `DBG_PRINTEXCEPTION_C` and `DBG_PRINTEXCEPTION_WIDE_C` from
+/// `OutputDebugString`, and MSVC's thread-naming exception.
+const IGNORED: [u32; 3] = [0x4001_0006, 0x4001_000A, 0x406D_1388];
+
+/// Recent exceptions for the whole process.
+static RECENT: Ring = Ring::new();
+
+/// Remembers a non-fatal exception for the next report.
+///
+/// Safe to call from an exception handler: it never allocates or blocks.
+pub fn record(code: u32, address: u64) {
+    if !IGNORED.contains(&code) {
+        RECENT.record(code, address, now_millis());
+    }
+}
+
+/// Builds a report's exception chain from the fatal exception's nested
+/// records, innermost first, and the recent first-chance exceptions.
+pub fn capture(nested: impl IntoIterator<Item = (u32, u64)>) -> Vec<ChainedException> {
+    let mut chain: Vec<ChainedException> = nested
+        .into_iter()
+        .take(MAX_NESTED)
+        .map(|(code, address)| entry(ExceptionSource::Nested, code, address, None))
+        .collect();
+    chain.extend(RECENT.snapshot());
+    chain
+}
+
+fn entry(
+    source: ExceptionSource,
+    code: u32,
+    address: u64,
+    occurred_at: Option<u64>,
+) -> ChainedException {
+    ChainedException {
+        source,
+        code: format!("0x{:08X}", code),
+        address: format!("0x{:X}", address),
+        occurred_at,
+    }
+}
+
+/// One recorded exception. A sequence of 0 marks a slot never written.
+struct Slot {
+    sequence: AtomicU64,
+    code: AtomicU32,
+    address: AtomicU64,
+    occurred_at: AtomicU64,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            sequence: AtomicU64::new(0),
+            code: AtomicU32::new(0),
+            address: AtomicU64::new(0),
+            occurred_at: AtomicU64::new(0),
+        }
+    }
+}
+
+/// A fixed ring of the most recent exceptions.
+///
+/// An entry overwritten while [`Ring::snapshot`] reads it may mix two
+/// exceptions; the crash is reported once, so this is rare and harmless.
+struct Ring {
+    slots: [Slot; RECENT_EXCEPTIONS],
+    next: AtomicU64,
+}
+
+impl Ring {
+    const fn new() -> Self {
+        Self {
+            slots: [const { Slot::new() }; RECENT_EXCEPTIONS],
+            next: AtomicU64::new(0),
+        }
+    }
+
+    fn record(&self, code: u32, address: u64, occurred_at: u64) {
+        let sequence = self.next.fetch_add(1, Ordering::Relaxed) + 1;
+        let slot = &self.slots[(sequence % RECENT_EXCEPTIONS as u64) as usize];
+        slot.code.store(code, Ordering::Relaxed);
+        slot.address.store(address, Ordering::Relaxed);
+        slot.occurred_at.store(occurred_at, Ordering::Relaxed);
+        slot.sequence.store(sequence, Ordering::Release);
+    }
+
+    fn snapshot(&self) -> Vec<ChainedException> {
+        let mut entries: Vec<(u64, ChainedException)> = self
+            .slots
+            .iter()
+            .filter_map(|slot| {
+                let sequence = slot.sequence.load(Ordering::Acquire);
+                (sequence != 0).then(|| {
+                    (
+                        sequence,
+                        entry(
+                            ExceptionSource::FirstChance,
+                            slot.code.load(Ordering::Relaxed),
+                            slot.address.load(Ordering::Relaxed),
+                            Some(slot.occurred_at.load(Ordering::Relaxed)),
+                        ),
+                    )
+                })
+            })
+            .collect();
+        entries.sort_by_key(|(sequence, _)| std::cmp::Reverse(*sequence));
+
+        entries
+            .into_iter()
+            .map(|(_, exception)| exception)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_exceptions_first() {
+        let ring = Ring::new();
+        for n in 0..RECENT_EXCEPTIONS as u64 + 3 {
+            ring.record(0xE06D_7363, 0x7FF6_0000_1000 + n, 1000 + n);
+        }
+
+        let recent = ring.snapshot();
+        assert_eq!(recent.len(), RECENT_EXCEPTIONS);
+        assert_eq!(recent[0].source, ExceptionSource::FirstChance);
+        assert_eq!(recent[0].code, "0xE06D7363");
+        assert_eq!(recent[0].address, "0x7FF600001012");
+        assert_eq!(recent[0].occurred_at, Some(1018));
+        assert_eq!(recent[RECENT_EXCEPTIONS - 1].occurred_at, Some(1003));
+    }
+
+    #[test]
+    fn nested_records_come_first() {
+        let nested = (0..MAX_NESTED as u32 + 2).map(|n| (0xC000_0005, 0x1000 + u64::from(n)));
+
+        let chain = capture(nested);
+        assert!(chain.len() >= MAX_NESTED);
+        assert!(chain.len() <= MAX_EXCEPTION_CHAIN);
+        assert!(
+            chain[..MAX_NESTED]
+                .iter()
+                .all(|exception| exception.source == ExceptionSource::Nested
+                    && exception.occurred_at.is_none())
+        );
+        assert_eq!(chain[0].address, "0x1000");
+    }
+
+    #[test]
+    fn debugger_messages_are_not_recorded() {
+        record(0x4001_0006, 0x1000);
+        record(0x406D_1388, 0x1000);
+
+        assert!(
+            capture([])
+                .iter()
+                .all(|exception| exception.address != "0x1000")
+        );
+    }
+}
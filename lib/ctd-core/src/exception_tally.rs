@@ -0,0 +1,174 @@
+//! Tallies of non-fatal exceptions raised in the game process.
+//!
+//! Games and mods raise exceptions they catch themselves: C++ `throw`
+//! (`0xE06D7363`), guard pages, debugger messages. None of them is worth a
+//! report, but a flood of them often comes right before a crash. With
+//! `[handler] handled_exceptions = true`, the crash handlers call [`record`]
+//! for every exception that isn't fatal, and the counts ride along in the
+//! next crash or hang report's `handledExceptions` section. Nothing is
+//! submitted for the exceptions themselves, so the tallies fall under the
+//! same `[submission]` limits as the reports that carry them.
+//!
+//! [`record`] runs inside the exception handler, on whichever thread raised
+//! the exception, so it only touches atomics: a fixed table of
+//! [`MAX_HANDLED_EXCEPTIONS`] codes with saturating counts. Codes first
+//! seen after the table is full are not counted.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use crate::crash_report::{HandledException, MAX_HANDLED_EXCEPTIONS};
+
+/// Set by [`set_enabled`] from `[handler] handled_exceptions`.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Counts for the whole process.
+static TALLY: Tally = Tally::new();
+
+/// Turns counting on or off.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if [`record`] counts exceptions.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Counts one non-fatal exception, if counting is enabled.
+///
+/// Safe to call from an exception handler: it never allocates or blocks.
+pub fn record(code: u32) {
+    if is_enabled() {
+        TALLY.record(code);
+    }
+}
+
+/// Returns the counts so far, most frequent first.
+pub fn snapshot() -> Vec<HandledException> {
+    TALLY.snapshot()
+}
+
+/// One exception code and its count. A code of 0 marks a free slot.
+struct Slot {
+    code: AtomicU32,
+    count: AtomicU32,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            code: AtomicU32::new(0),
+            count: AtomicU32::new(0),
+        }
+    }
+}
+
+/// A fixed table of exception codes and counts.
+struct Tally {
+    slots: [Slot; MAX_HANDLED_EXCEPTIONS],
+}
+
+impl Tally {
+    const fn new() -> Self {
+        Self {
+            slots: [const { Slot::new() }; MAX_HANDLED_EXCEPTIONS],
+        }
+    }
+
+    fn record(&self, code: u32) {
+        if code == 0 {
+            return;
+        }
+
+        for slot in &self.slots {
+            // Claims a free slot, or finds the one already holding the code
+            let held = slot
+                .code
+                .compare_exchange(0, code, Ordering::AcqRel, Ordering::Acquire)
+                .unwrap_or_else(|existing| existing);
+            if held == 0 || held == code {
+                let _ = slot
+                    .count
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_add(1));
+                return;
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<HandledException> {
+        let mut tallies: Vec<(u32, u32)> = self
+            .slots
+            .iter()
+            .map(|slot| {
+                (
+                    slot.code.load(Ordering::Acquire),
+                    slot.count.load(Ordering::Relaxed),
+                )
+            })
+            .filter(|&(code, count)| code != 0 && count != 0)
+            .collect();
+        tallies.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        tallies
+            .into_iter()
+            .map(|(code, count)| HandledException {
+                code: format!("0x{:08X}", code),
+                count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_each_code_most_frequent_first() {
+        let tally = Tally::new();
+        tally.record(0x4001_0006);
+        for _ in 0..3 {
+            tally.record(0xE06D_7363);
+        }
+        tally.record(0);
+
+        let counts = tally.snapshot();
+        assert_eq!(
+            counts,
+            vec![
+                HandledException {
+                    code: "0xE06D7363".to_string(),
+                    count: 3,
+                },
+                HandledException {
+                    code: "0x40010006".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_codes_once_full() {
+        let tally = Tally::new();
+        for code in 1..=MAX_HANDLED_EXCEPTIONS as u32 + 5 {
+            tally.record(code);
+        }
+        tally.record(1);
+
+        let counts = tally.snapshot();
+        assert_eq!(counts.len(), MAX_HANDLED_EXCEPTIONS);
+        assert_eq!(counts[0].code, "0x00000001");
+        assert_eq!(counts[0].count, 2);
+    }
+
+    #[test]
+    fn counts_saturate() {
+        let tally = Tally::new();
+        tally.record(0x8000_0001);
+        tally.slots[0].count.store(u32::MAX, Ordering::Relaxed);
+        tally.record(0x8000_0001);
+
+        assert_eq!(tally.snapshot()[0].count, u32::MAX);
+    }
+}
@@ -9,12 +9,32 @@
 //! entire files (especially for large BSA/BA2 archives), but two files with
 //! identical first 64KB and size would produce the same hash. In practice,
 //! this is extremely rare for mod files.
+//!
+//! Repacked archives can share a header, though, so `[fingerprint]
+//! algorithm` can switch to a full-file [`HashAlgorithm`]: SHA-256,
+//! xxHash64 (much faster), or CRC32, which matches the checksums Nexus Mods
+//! publishes for uploaded files.
+//!
+//! Fingerprinting hundreds of plugins and DLLs on every launch still costs
+//! hundreds of file reads, so the fingerprint modules go through
+//! [`CachedHasher`], which remembers each file's hash under its path, size,
+//! and modification time and only re-hashes files that changed.
 
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fs::File;
+use std::collections::HashMap;
+use std::fs::{self, File};
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 use thiserror::Error;
+use tracing::{debug, warn};
+use xxhash_rust::xxh64::Xxh64;
+
+use crate::config::Config;
+
+/// Hash recorded for files that could not be hashed.
+pub const UNHASHED: &str = "0000000000000000";
 
 /// Errors that can occur when computing file hashes.
 #[derive(Error, Debug)]
@@ -24,23 +44,264 @@ pub enum HashError {
     IoError(#[from] std::io::Error),
 }
 
+/// How files are fingerprinted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    /// SHA-256 of the first 64KB and the file size (default).
+    #[default]
+    #[serde(rename = "partial-sha256")]
+    PartialSha256,
+    /// SHA-256 of the whole file.
+    #[serde(rename = "full-sha256")]
+    FullSha256,
+    /// xxHash64 of the whole file; far faster than SHA-256.
+    #[serde(rename = "xxhash64")]
+    XxHash64,
+    /// CRC32 of the whole file, as published by Nexus Mods.
+    #[serde(rename = "crc32")]
+    Crc32,
+}
+
+impl HashAlgorithm {
+    /// Returns the name used in `ctd.toml` and reports.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PartialSha256 => "partial-sha256",
+            Self::FullSha256 => "full-sha256",
+            Self::XxHash64 => "xxhash64",
+            Self::Crc32 => "crc32",
+        }
+    }
+
+    /// Returns true for the default, which reports leave out.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Returns the algorithm set by `[fingerprint] algorithm`.
+pub fn configured_algorithm() -> HashAlgorithm {
+    Config::load().unwrap_or_default().fingerprint.algorithm
+}
+
 /// Compute a fast fingerprint of a file.
 /// Uses SHA256 of the first 64KB + file size for speed.
 /// Returns (hash_hex, file_size) where hash_hex is 16 characters.
 pub fn compute_file_hash(path: &Path) -> Result<(String, u64), HashError> {
+    compute_file_hash_with(path, HashAlgorithm::PartialSha256)
+}
+
+/// Fingerprints a file with `algorithm`.
+///
+/// Returns (hash_hex, file_size). The hash is 16 hex characters, or 8 for
+/// CRC32.
+pub fn compute_file_hash_with(
+    path: &Path,
+    algorithm: HashAlgorithm,
+) -> Result<(String, u64), HashError> {
     let mut file = File::open(path)?;
     let size = file.metadata()?.len();
 
-    let read_size = 65536.min(size as usize);
-    let mut buffer = vec![0u8; read_size];
-    file.read_exact(&mut buffer)?;
+    let hash = match algorithm {
+        HashAlgorithm::PartialSha256 => {
+            let read_size = 65536.min(size as usize);
+            let mut buffer = vec![0u8; read_size];
+            file.read_exact(&mut buffer)?;
 
-    let mut hasher = Sha256::new();
-    hasher.update(&buffer);
-    hasher.update(size.to_le_bytes());
+            let mut hasher = Sha256::new();
+            hasher.update(&buffer);
+            hasher.update(size.to_le_bytes());
+            hex::encode(&hasher.finalize()[..8])
+        }
+        HashAlgorithm::FullSha256 => {
+            let mut hasher = Sha256::new();
+            read_chunks(&mut file, |chunk| hasher.update(chunk))?;
+            hex::encode(&hasher.finalize()[..8])
+        }
+        HashAlgorithm::XxHash64 => {
+            let mut hasher = Xxh64::new(0);
+            read_chunks(&mut file, |chunk| hasher.update(chunk))?;
+            format!("{:016x}", hasher.digest())
+        }
+        HashAlgorithm::Crc32 => {
+            let mut hasher = crc32fast::Hasher::new();
+            read_chunks(&mut file, |chunk| hasher.update(chunk))?;
+            format!("{:08x}", hasher.finalize())
+        }
+    };
+    Ok((hash, size))
+}
+
+/// Feeds a whole file to `update` in 64KB chunks.
+fn read_chunks(file: &mut File, mut update: impl FnMut(&[u8])) -> std::io::Result<()> {
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(()),
+            n => update(&buffer[..n]),
+        }
+    }
+}
 
-    let result = hasher.finalize();
-    Ok((hex::encode(&result[..8]), size))
+/// A file's hash as of its size and modification time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedHash {
+    #[serde(default)]
+    algorithm: HashAlgorithm,
+    size: u64,
+    mtime_ns: u64,
+    hash: String,
+}
+
+/// [`compute_file_hash`] with a persistent cache.
+///
+/// Entries are keyed by path and reused while the file's size and
+/// modification time are unchanged. Call [`CachedHasher::save`] after a
+/// scan to keep new hashes for the next launch; entries for files that no
+/// longer exist are dropped then. Hashes made with a different
+/// [`HashAlgorithm`] are never reused.
+#[derive(Debug)]
+pub struct CachedHasher {
+    path: PathBuf,
+    algorithm: HashAlgorithm,
+    entries: HashMap<String, CachedHash>,
+    dirty: bool,
+}
+
+impl CachedHasher {
+    /// Loads the cache at `path`, treating a missing or corrupt file as empty.
+    ///
+    /// Hashes with [`HashAlgorithm::PartialSha256`] unless
+    /// [`with_algorithm`](Self::with_algorithm) is called.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = fs::read(&path)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            algorithm: HashAlgorithm::default(),
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// Loads the cache from the default location, hashing with the
+    /// configured algorithm.
+    pub fn open_default() -> Self {
+        Self::open(Self::default_path()).with_algorithm(configured_algorithm())
+    }
+
+    /// Sets the algorithm new hashes are made with.
+    pub fn with_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Returns the algorithm new hashes are made with.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Returns the default cache file (`<local data dir>/ctd/hash-cache.json`).
+    pub fn default_path() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("ctd")
+            .join("hash-cache.json")
+    }
+
+    /// Fingerprints a file like [`compute_file_hash_with`], reusing the
+    /// cached hash if the file is unchanged.
+    pub fn hash(&mut self, path: &Path) -> Result<(String, u64), HashError> {
+        let metadata = fs::metadata(path)?;
+        if let Some(hash) = self.lookup(path, &metadata) {
+            return Ok((hash, metadata.len()));
+        }
+
+        let (hash, size) = compute_file_hash_with(path, self.algorithm)?;
+        // A file that changed while it was read is hashed again next time
+        if size == metadata.len() {
+            self.record(path, &metadata, &hash);
+        }
+        Ok((hash, size))
+    }
+
+    /// Returns the cached hash of `path` if its size and modification time
+    /// still match `metadata`.
+    pub fn lookup(&self, path: &Path, metadata: &fs::Metadata) -> Option<String> {
+        let mtime_ns = mtime_ns(metadata)?;
+        self.entries
+            .get(path.to_string_lossy().as_ref())
+            .filter(|cached| {
+                cached.algorithm == self.algorithm
+                    && cached.size == metadata.len()
+                    && cached.mtime_ns == mtime_ns
+            })
+            .map(|cached| cached.hash.clone())
+    }
+
+    /// Remembers `hash` for `path` as of `metadata`.
+    ///
+    /// Files without a modification time are not cached: their hash could
+    /// never be trusted later.
+    pub fn record(&mut self, path: &Path, metadata: &fs::Metadata, hash: &str) {
+        let Some(mtime_ns) = mtime_ns(metadata) else {
+            return;
+        };
+        let entry = CachedHash {
+            algorithm: self.algorithm,
+            size: metadata.len(),
+            mtime_ns,
+            hash: hash.to_string(),
+        };
+        let previous = self
+            .entries
+            .insert(path.to_string_lossy().into_owned(), entry.clone());
+        self.dirty |= previous.as_ref() != Some(&entry);
+    }
+
+    /// Writes the cache if anything changed, dropping entries for deleted files.
+    ///
+    /// Failures are logged; the next launch only re-hashes.
+    pub fn save(&mut self) {
+        let before = self.entries.len();
+        self.entries.retain(|path, _| Path::new(path).exists());
+        if !self.dirty && self.entries.len() == before {
+            return;
+        }
+
+        let written = serde_json::to_vec(&self.entries)
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&self.path, json)
+            });
+        match written {
+            Ok(()) => {
+                debug!(
+                    "Saved {} file hashes to {:?}",
+                    self.entries.len(),
+                    self.path
+                );
+                self.dirty = false;
+            }
+            Err(e) => warn!("Failed to save hash cache to {:?}: {}", self.path, e),
+        }
+    }
+}
+
+/// Returns a file's modification time in nanoseconds since the Unix epoch.
+fn mtime_ns(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
 }
 
 #[cfg(test)]
@@ -92,4 +353,112 @@ mod tests {
         let result = compute_file_hash(Path::new("/nonexistent/file.esp"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_full_file_algorithms() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"123456789").unwrap();
+
+        // Standard check values for the ASCII digits 1-9
+        let (crc, size) = compute_file_hash_with(file.path(), HashAlgorithm::Crc32).unwrap();
+        assert_eq!(crc, "cbf43926");
+        assert_eq!(size, 9);
+        let (sha, _) = compute_file_hash_with(file.path(), HashAlgorithm::FullSha256).unwrap();
+        assert_eq!(sha, "15e2b0d3c33891eb");
+        let (xxh, _) = compute_file_hash_with(file.path(), HashAlgorithm::XxHash64).unwrap();
+        assert_eq!(xxh.len(), 16);
+        assert_ne!(xxh, compute_file_hash(file.path()).unwrap().0);
+    }
+
+    #[test]
+    fn test_full_file_hash_sees_past_64kb() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = vec![7u8; 65536];
+        let a = dir.path().join("a.ba2");
+        let b = dir.path().join("b.ba2");
+        fs::write(&a, [header.as_slice(), b"tail a"].concat()).unwrap();
+        fs::write(&b, [header.as_slice(), b"tail b"].concat()).unwrap();
+
+        assert_eq!(
+            compute_file_hash(&a).unwrap(),
+            compute_file_hash(&b).unwrap()
+        );
+        for algorithm in [
+            HashAlgorithm::FullSha256,
+            HashAlgorithm::XxHash64,
+            HashAlgorithm::Crc32,
+        ] {
+            assert_ne!(
+                compute_file_hash_with(&a, algorithm).unwrap(),
+                compute_file_hash_with(&b, algorithm).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_cached_hasher_ignores_other_algorithms() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = dir.path().join("Test.esp");
+        let cache = dir.path().join("hash-cache.json");
+        fs::write(&plugin, b"content").unwrap();
+
+        let mut hasher = CachedHasher::open(&cache);
+        hasher.hash(&plugin).unwrap();
+        hasher.save();
+
+        let mut hasher = CachedHasher::open(&cache).with_algorithm(HashAlgorithm::Crc32);
+        assert_eq!(
+            hasher.hash(&plugin).unwrap(),
+            compute_file_hash_with(&plugin, HashAlgorithm::Crc32).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cached_hasher_reuses_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = dir.path().join("Test.esp");
+        let cache = dir.path().join("hash-cache.json");
+        fs::write(&plugin, b"content a").unwrap();
+
+        let mut hasher = CachedHasher::open(&cache);
+        let first = hasher.hash(&plugin).unwrap();
+        assert_eq!(first, compute_file_hash(&plugin).unwrap());
+        hasher.save();
+
+        // A stale hash with the file's size and mtime proves the cache is used
+        let mut hasher = CachedHasher::open(&cache);
+        hasher.entries.values_mut().next().unwrap().hash = "cachedcachedcach".into();
+        assert_eq!(hasher.hash(&plugin).unwrap().0, "cachedcachedcach");
+    }
+
+    #[test]
+    fn test_cached_hasher_rehashes_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = dir.path().join("Test.esp");
+        fs::write(&plugin, b"content a").unwrap();
+
+        let mut hasher = CachedHasher::open(dir.path().join("hash-cache.json"));
+        let (old, _) = hasher.hash(&plugin).unwrap();
+        fs::write(&plugin, b"content b, longer").unwrap();
+        let (new, size) = hasher.hash(&plugin).unwrap();
+
+        assert_ne!(old, new);
+        assert_eq!(size, 17);
+    }
+
+    #[test]
+    fn test_cached_hasher_drops_deleted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let plugin = dir.path().join("Test.esp");
+        let cache = dir.path().join("hash-cache.json");
+        fs::write(&plugin, b"content").unwrap();
+
+        let mut hasher = CachedHasher::open(&cache);
+        hasher.hash(&plugin).unwrap();
+        hasher.save();
+        fs::remove_file(&plugin).unwrap();
+        CachedHasher::open(&cache).save();
+
+        assert!(CachedHasher::open(&cache).entries.is_empty());
+    }
 }
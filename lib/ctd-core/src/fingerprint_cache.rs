@@ -0,0 +1,131 @@
+//! Mod fingerprints computed before a crash, off the crash path.
+//!
+//! Hashing every plugin inside the crash handler means hundreds of file
+//! reads while the game is dying. The Bethesda plugins instead fill a
+//! [`FingerprintCache`] on a background thread when game data has loaded,
+//! and the crash path takes the cached list. A crash before the thread
+//! finishes gets a report with plugin names but no hashes rather than a
+//! wait.
+//!
+//! The load order is fixed once data has loaded, but the crash path still
+//! checks the cached list against the live one with
+//! [`FingerprintCache::get_matching`] and ignores it if they differ.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use tracing::{info, warn};
+
+use crate::load_order::ModList;
+
+/// A mod list fingerprinted once, in the background.
+#[derive(Debug)]
+pub struct FingerprintCache {
+    mods: OnceLock<ModList>,
+    started: AtomicBool,
+}
+
+impl FingerprintCache {
+    /// Creates an empty cache, for use in a `static`.
+    pub const fn new() -> Self {
+        Self {
+            mods: OnceLock::new(),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs `build` on a background thread and caches its result.
+    ///
+    /// Only the first call starts a thread; later calls do nothing.
+    pub fn populate_in_background(&'static self, build: impl FnOnce() -> ModList + Send + 'static) {
+        if self.started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let spawned = thread::Builder::new()
+            .name("ctd-fingerprint".into())
+            .spawn(move || {
+                let mods = build();
+                info!("Fingerprinted {} mods in the background", mods.len());
+                let _ = self.mods.set(mods);
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to start background fingerprinting: {}", e);
+        }
+    }
+
+    /// Returns the cached mod list, or `None` if it isn't ready.
+    pub fn get(&self) -> Option<&ModList> {
+        self.mods.get()
+    }
+
+    /// Returns a copy of the cached mod list if it is ready and lists
+    /// exactly `names`, in order.
+    pub fn get_matching(&self, names: &[&str]) -> Option<ModList> {
+        self.get()
+            .filter(|mods| mods.len() == names.len())
+            .filter(|mods| {
+                mods.iter()
+                    .zip(names)
+                    .all(|(entry, name)| entry.name == *name)
+            })
+            .cloned()
+    }
+}
+
+impl Default for FingerprintCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModEntry;
+    use std::time::{Duration, Instant};
+
+    fn mods(names: &[&str]) -> ModList {
+        ModList::from_entries(
+            names
+                .iter()
+                .map(|name| ModEntry::new(*name, "0123456789abcdef", 1024))
+                .collect(),
+        )
+    }
+
+    fn wait_for(cache: &FingerprintCache) -> &ModList {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(mods) = cache.get() {
+                return mods;
+            }
+            assert!(Instant::now() < deadline, "cache never filled");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn populates_once_in_the_background() {
+        static CACHE: FingerprintCache = FingerprintCache::new();
+        assert!(CACHE.get().is_none());
+
+        CACHE.populate_in_background(|| mods(&["Skyrim.esm", "Update.esm"]));
+        CACHE.populate_in_background(|| mods(&["Other.esp"]));
+
+        assert_eq!(wait_for(&CACHE).len(), 2);
+        assert_eq!(wait_for(&CACHE).0[0].name, "Skyrim.esm");
+    }
+
+    #[test]
+    fn ignores_a_different_load_order() {
+        static CACHE: FingerprintCache = FingerprintCache::new();
+        CACHE.populate_in_background(|| mods(&["Skyrim.esm", "Update.esm"]));
+        wait_for(&CACHE);
+
+        assert!(CACHE.get_matching(&["Skyrim.esm", "Update.esm"]).is_some());
+        assert!(CACHE.get_matching(&["Update.esm", "Skyrim.esm"]).is_none());
+        assert!(CACHE.get_matching(&["Skyrim.esm"]).is_none());
+    }
+}
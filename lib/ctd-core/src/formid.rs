@@ -0,0 +1,101 @@
+//! Attribution of runtime form IDs to the plugins that define them.
+//!
+//! The top byte of a runtime form ID is the load index of its plugin among
+//! the full (non-light) plugins. Light (ESL) plugins share slot `0xFE`, with
+//! the next 12 bits selecting the plugin among the light ones. Slot `0xFF`
+//! holds forms created at runtime, which belong to the save rather than a
+//! plugin.
+//!
+//! A crash inside a form's code path points at the plugin that defined the
+//! form far more often than at the game itself, so naming that plugin is one
+//! of the most useful things a report can do.
+
+use crate::load_order::{ModEntry, ModList};
+
+/// Load slot shared by all light plugins.
+const LIGHT_SLOT: u32 = 0xFE;
+
+/// Load slot of forms created at runtime.
+const RUNTIME_SLOT: u32 = 0xFF;
+
+/// Returns the plugin that defines `form_id`.
+///
+/// `mods` must be in load order. Disabled entries are skipped, and entries
+/// without an `is_light` flag count as full plugins. Returns `None` for
+/// runtime-created forms and for slots past the end of the load order.
+pub fn resolve(form_id: u32, mods: &ModList) -> Option<&ModEntry> {
+    let slot = form_id >> 24;
+    if slot == RUNTIME_SLOT {
+        return None;
+    }
+
+    let loaded = mods.iter().filter(|entry| entry.enabled != Some(false));
+    if slot == LIGHT_SLOT {
+        let light_index = ((form_id >> 12) & 0xFFF) as usize;
+        loaded
+            .filter(|entry| entry.is_light == Some(true))
+            .nth(light_index)
+    } else {
+        loaded
+            .filter(|entry| entry.is_light != Some(true))
+            .nth(slot as usize)
+    }
+}
+
+/// Formats `form_id` with its owning plugin, e.g. `0x0A012345 (Patch.esp)`.
+pub fn describe(form_id: u32, mods: &ModList) -> String {
+    let owner = if form_id >> 24 == RUNTIME_SLOT {
+        "created at runtime"
+    } else {
+        resolve(form_id, mods).map_or("unknown plugin", |entry| entry.name.as_str())
+    };
+    format!("0x{:08X} ({})", form_id, owner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_order() -> ModList {
+        ModList::from_entries(vec![
+            ModEntry::new("Skyrim.esm", "0000000000000000", 0).with_light(false),
+            ModEntry::new("ccQDRSSE001-SurvivalMode.esl", "0000000000000000", 0).with_light(true),
+            ModEntry::new("Disabled.esp", "0000000000000000", 0).with_enabled(false),
+            ModEntry::new("Update.esm", "0000000000000000", 0).with_light(false),
+            ModEntry::new("SmallPatch.esp", "0000000000000000", 0).with_light(true),
+            ModEntry::new("Patch.esp", "0000000000000000", 0),
+        ])
+    }
+
+    #[test]
+    fn resolves_full_plugins_by_load_index() {
+        let mods = load_order();
+        assert_eq!(resolve(0x0001_2E46, &mods).unwrap().name, "Skyrim.esm");
+        assert_eq!(resolve(0x0100_0800, &mods).unwrap().name, "Update.esm");
+        assert_eq!(resolve(0x0200_0800, &mods).unwrap().name, "Patch.esp");
+        assert!(resolve(0x0300_0800, &mods).is_none());
+    }
+
+    #[test]
+    fn resolves_light_plugins_in_fe_slot() {
+        let mods = load_order();
+        assert_eq!(
+            resolve(0xFE00_0801, &mods).unwrap().name,
+            "ccQDRSSE001-SurvivalMode.esl"
+        );
+        assert_eq!(resolve(0xFE00_1801, &mods).unwrap().name, "SmallPatch.esp");
+        assert!(resolve(0xFE00_2801, &mods).is_none());
+    }
+
+    #[test]
+    fn runtime_forms_have_no_owner() {
+        let mods = load_order();
+        assert!(resolve(0xFF00_0ABC, &mods).is_none());
+        assert_eq!(
+            describe(0xFF00_0ABC, &mods),
+            "0xFF000ABC (created at runtime)"
+        );
+        assert_eq!(describe(0x0200_0800, &mods), "0x02000800 (Patch.esp)");
+        assert_eq!(describe(0x4000_0800, &mods), "0x40000800 (unknown plugin)");
+    }
+}
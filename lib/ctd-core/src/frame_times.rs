@@ -0,0 +1,154 @@
+//! Frame times leading up to a crash.
+//!
+//! A game that held 60 FPS and then spent 200 ms a frame for its last
+//! second was streaming in more than it could handle, usually running out
+//! of VRAM, rather than tripping over a bad record. Where the host calls
+//! back every frame (the same callback that beats the [`crate::watchdog`]),
+//! the plugins call [`record_frame`], which keeps the last
+//! [`FRAME_SAMPLES`] frame times in a fixed ring. [`summary`] turns them
+//! into the report's `frameTimes`: the median and 99th percentile, the
+//! fastest and slowest frame, and the last few frames, newest first.
+//!
+//! [`record_frame`] runs on the game thread every frame, so it only touches
+//! atomics. Loading screens count as one long frame when the host stops
+//! calling back during them.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::Instant;
+
+use crate::crash_report::{FrameTimes, MAX_RECENT_FRAMES};
+
+/// Frame times kept, about 8 seconds at 60 FPS.
+pub const FRAME_SAMPLES: usize = 512;
+
+/// Start of the clock frames are timed against.
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// When the last frame started, in microseconds since [`EPOCH`] plus one;
+/// 0 before the first frame.
+static LAST_FRAME: AtomicU64 = AtomicU64::new(0);
+
+/// Frame times for the whole process.
+static FRAMES: Ring = Ring::new();
+
+/// Records that a frame started. Call once per frame from the game thread.
+pub fn record_frame() {
+    let now = EPOCH.get_or_init(Instant::now).elapsed().as_micros() as u64 + 1;
+    let last = LAST_FRAME.swap(now, Ordering::Relaxed);
+    if last != 0 {
+        FRAMES.push(now.saturating_sub(last));
+    }
+}
+
+/// Summarizes the recorded frame times, or `None` if no frame was timed.
+pub fn summary() -> Option<FrameTimes> {
+    summarize(&FRAMES.snapshot())
+}
+
+/// Summarizes frame times in microseconds, oldest first.
+fn summarize(frames: &[u32]) -> Option<FrameTimes> {
+    if frames.is_empty() {
+        return None;
+    }
+    let mut sorted = frames.to_vec();
+    sorted.sort_unstable();
+    let percentile = |p: usize| millis(sorted[(sorted.len() - 1) * p / 100]);
+
+    Some(FrameTimes {
+        samples: frames.len() as u32,
+        p50_ms: percentile(50),
+        p99_ms: percentile(99),
+        min_ms: millis(sorted[0]),
+        max_ms: millis(sorted[sorted.len() - 1]),
+        recent_ms: frames
+            .iter()
+            .rev()
+            .take(MAX_RECENT_FRAMES)
+            .map(|&micros| millis(micros))
+            .collect(),
+    })
+}
+
+/// Converts microseconds to milliseconds, rounded to 0.1 ms.
+fn millis(micros: u32) -> f32 {
+    (micros as f32 / 100.0).round() / 10.0
+}
+
+/// A fixed ring of frame times in microseconds.
+///
+/// Only the game thread writes; a snapshot taken mid-write may hold one
+/// frame from the previous lap, which doesn't move a percentile.
+struct Ring {
+    frames: [AtomicU32; FRAME_SAMPLES],
+    count: AtomicU64,
+}
+
+impl Ring {
+    const fn new() -> Self {
+        Self {
+            frames: [const { AtomicU32::new(0) }; FRAME_SAMPLES],
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, micros: u64) {
+        let count = self.count.load(Ordering::Relaxed);
+        self.frames[(count % FRAME_SAMPLES as u64) as usize]
+            .store(micros.min(u64::from(u32::MAX)) as u32, Ordering::Relaxed);
+        self.count.store(count + 1, Ordering::Release);
+    }
+
+    /// Returns the frame times, oldest first.
+    fn snapshot(&self) -> Vec<u32> {
+        let count = self.count.load(Ordering::Acquire);
+        let len = count.min(FRAME_SAMPLES as u64);
+        (count - len..count)
+            .map(|n| self.frames[(n % FRAME_SAMPLES as u64) as usize].load(Ordering::Relaxed))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_percentiles_and_recent_frames() {
+        // 99 smooth frames at 16.7 ms, then a collapse
+        let mut frames = vec![16_667; 99];
+        frames.extend([120_000, 250_000]);
+
+        let times = summarize(&frames).unwrap();
+        assert_eq!(times.samples, 101);
+        assert_eq!(times.p50_ms, 16.7);
+        assert_eq!(times.p99_ms, 120.0);
+        assert_eq!(times.min_ms, 16.7);
+        assert_eq!(times.max_ms, 250.0);
+        assert_eq!(times.recent_ms.len(), MAX_RECENT_FRAMES);
+        assert_eq!(times.recent_ms[..3], [250.0, 120.0, 16.7]);
+
+        assert_eq!(summarize(&[]), None);
+    }
+
+    #[test]
+    fn ring_keeps_the_newest_frames() {
+        let ring = Ring::new();
+        assert!(ring.snapshot().is_empty());
+
+        for micros in 0..FRAME_SAMPLES as u64 + 10 {
+            ring.push(micros);
+        }
+        let frames = ring.snapshot();
+        assert_eq!(frames.len(), FRAME_SAMPLES);
+        assert_eq!(frames[0], 10);
+        assert_eq!(frames[FRAME_SAMPLES - 1], FRAME_SAMPLES as u32 + 9);
+    }
+
+    #[test]
+    fn the_first_frame_only_starts_the_clock() {
+        record_frame();
+        record_frame();
+        assert!(summary().is_some_and(|times| times.samples >= 1));
+    }
+}
@@ -0,0 +1,440 @@
+//! Display and graphics settings at crash time.
+//!
+//! The same load order crashes on one machine and not another because of
+//! what the game renders with: an exclusive fullscreen mode switch, HDR, a
+//! laptop rendering on its integrated GPU, or an upscaler DLL dropped into
+//! the game folder. [`capture`] reads these into the report's `graphics`
+//! section:
+//!
+//! - the game window's client size, and the refresh rate of its monitor
+//! - whether it runs fullscreen, borderless, or windowed
+//! - whether HDR is on for that monitor (`IDXGIOutput6`)
+//! - the GPUs, and which one the game renders on
+//! - DLSS, FSR, and XeSS DLLs in the game folder or loaded, with versions
+//!
+//! On laptops with two GPUs the display is usually wired to the integrated
+//! one even when the discrete GPU renders, so the active GPU is the one
+//! whose user-mode driver the game loaded, and the one driving the game's
+//! monitor only when no driver is recognized.
+
+use std::path::Path;
+
+#[cfg(windows)]
+use tracing::debug;
+
+#[cfg(windows)]
+use crate::crash_report::GpuAdapter;
+use crate::crash_report::{DisplayMode, GraphicsSettings, Upscaler, UpscalerKind};
+use crate::memory_map::{self, LoadedModule};
+use crate::version::get_dll_version;
+
+/// PCI vendor IDs.
+const NVIDIA: u32 = 0x10DE;
+const AMD: u32 = 0x1002;
+const INTEL: u32 = 0x8086;
+
+/// Prefixes of GPU driver user-mode modules, and their vendors.
+const GPU_DRIVERS: &[(&str, u32)] = &[
+    ("nvwgf2um", NVIDIA),
+    ("nvd3dum", NVIDIA),
+    ("nvoglv", NVIDIA),
+    ("nvgpucomp", NVIDIA),
+    ("atidxx", AMD),
+    ("amdxx", AMD),
+    ("atiumd", AMD),
+    ("amdvlk", AMD),
+    ("igd10", INTEL),
+    ("igd12", INTEL),
+    ("igxelpicd", INTEL),
+];
+
+/// Upscaler DLLs by file name, lowercase.
+const UPSCALER_DLLS: &[(&str, UpscalerKind)] = &[
+    ("nvngx_dlss.dll", UpscalerKind::Dlss),
+    ("nvngx_dlssd.dll", UpscalerKind::Dlss),
+    ("nvngx_dlssg.dll", UpscalerKind::DlssFrameGeneration),
+    ("ffx_fsr2_api_x64.dll", UpscalerKind::Fsr),
+    ("ffx_fsr2_api_dx12_x64.dll", UpscalerKind::Fsr),
+    ("ffx_fsr2_api_vk_x64.dll", UpscalerKind::Fsr),
+    ("amd_fidelityfx_dx12.dll", UpscalerKind::Fsr),
+    ("amd_fidelityfx_vk.dll", UpscalerKind::Fsr),
+    ("libxess.dll", UpscalerKind::Xess),
+];
+
+/// Reads the display and graphics settings of this process.
+///
+/// Fields that can't be read are left out; off Windows only the upscaler
+/// DLLs next to the executable are found.
+pub fn capture() -> GraphicsSettings {
+    let modules = memory_map::loaded_modules();
+    let game_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+
+    let mut settings = read_display();
+    let driver = modules
+        .iter()
+        .find_map(|module| driver_vendor(&module.name));
+    let vendors: Vec<u32> = settings
+        .adapters
+        .iter()
+        .map(|adapter| adapter.vendor_id)
+        .collect();
+    let display = settings.adapters.iter().position(|adapter| adapter.active);
+    for (index, adapter) in settings.adapters.iter_mut().enumerate() {
+        adapter.active = active_adapter(&vendors, driver, display) == Some(index);
+    }
+    settings.upscalers = find_upscalers(&modules, game_dir.as_deref());
+    settings
+}
+
+/// Returns the vendor of a GPU driver's user-mode module, or `None` if the
+/// module isn't one.
+pub(crate) fn driver_vendor(module: &str) -> Option<u32> {
+    let module = module.to_ascii_lowercase();
+    GPU_DRIVERS
+        .iter()
+        .find(|(prefix, _)| module.starts_with(prefix))
+        .map(|&(_, vendor)| vendor)
+}
+
+/// Picks the adapter the game renders on: the first one from the vendor
+/// whose driver it loaded, else the one driving its monitor.
+fn active_adapter(vendors: &[u32], driver: Option<u32>, display: Option<usize>) -> Option<usize> {
+    driver
+        .and_then(|driver| vendors.iter().position(|&vendor| vendor == driver))
+        .or(display)
+}
+
+/// Works out the window mode from the window's and monitor's bounds
+/// (`[left, top, right, bottom]`), whether the window has a title bar, and
+/// whether Windows reports an exclusive fullscreen Direct3D app.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn display_mode(
+    window: [i32; 4],
+    monitor: [i32; 4],
+    captioned: bool,
+    exclusive: bool,
+) -> DisplayMode {
+    if exclusive {
+        DisplayMode::Fullscreen
+    } else if !captioned && window == monitor {
+        DisplayMode::Borderless
+    } else {
+        DisplayMode::Windowed
+    }
+}
+
+/// Lists the upscaler DLLs loaded in the process, then those in `game_dir`
+/// that aren't loaded.
+fn find_upscalers(modules: &[LoadedModule], game_dir: Option<&Path>) -> Vec<Upscaler> {
+    let upscaler = |file: &str, path: &Path, loaded: bool| {
+        let lower = file.to_ascii_lowercase();
+        UPSCALER_DLLS
+            .iter()
+            .find(|(name, _)| *name == lower)
+            .map(|&(_, kind)| Upscaler {
+                kind,
+                file: file.to_string(),
+                version: get_dll_version(path).ok(),
+                loaded,
+            })
+    };
+
+    let mut upscalers: Vec<Upscaler> = modules
+        .iter()
+        .filter_map(|module| upscaler(&module.name, &module.path, true))
+        .collect();
+
+    let files = game_dir.and_then(|dir| std::fs::read_dir(dir).ok());
+    for entry in files.into_iter().flatten().flatten() {
+        let file = entry.file_name().to_string_lossy().into_owned();
+        if upscalers
+            .iter()
+            .any(|found| found.file.eq_ignore_ascii_case(&file))
+        {
+            continue;
+        }
+        if let Some(found) = upscaler(&file, &entry.path(), false) {
+            upscalers.push(found);
+        }
+    }
+    upscalers
+}
+
+/// Reads the game window, its monitor, and the GPUs.
+///
+/// The adapter driving the game's monitor is marked active; [`capture`]
+/// then decides which one really renders.
+#[cfg(windows)]
+fn read_display() -> GraphicsSettings {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{
+        DEVMODEW, ENUM_CURRENT_SETTINGS, EnumDisplaySettingsW, GetMonitorInfoW,
+        MONITOR_DEFAULTTONEAREST, MONITORINFO, MONITORINFOEXW, MonitorFromWindow,
+    };
+    use windows::Win32::UI::Shell::{QUNS_RUNNING_D3D_FULL_SCREEN, SHQueryUserNotificationState};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GWL_STYLE, GetClientRect, GetWindowLongW, GetWindowRect, WS_CAPTION,
+    };
+    use windows::core::PCWSTR;
+
+    let Some(window) = game_window() else {
+        debug!("No visible game window to read display settings from");
+        return GraphicsSettings {
+            adapters: read_adapters(None).0,
+            ..GraphicsSettings::default()
+        };
+    };
+
+    let mut settings = GraphicsSettings::default();
+    let mut client = RECT::default();
+    let mut bounds = RECT::default();
+    // SAFETY: window is a live top-level window of this process and both
+    // rectangles are valid for writes
+    if unsafe { GetClientRect(window, &mut client) }.is_ok() {
+        settings.width = Some((client.right - client.left) as u32);
+        settings.height = Some((client.bottom - client.top) as u32);
+    }
+    // SAFETY: As above
+    let have_bounds = unsafe { GetWindowRect(window, &mut bounds) }.is_ok();
+    // SAFETY: Reading a window's style has no preconditions
+    let captioned =
+        unsafe { GetWindowLongW(window, GWL_STYLE) } as u32 & WS_CAPTION.0 == WS_CAPTION.0;
+
+    // SAFETY: MonitorFromWindow always returns a monitor with
+    // MONITOR_DEFAULTTONEAREST
+    let monitor = unsafe { MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST) };
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    // SAFETY: info is a MONITORINFOEXW with cbSize set, which
+    // GetMonitorInfoW accepts in place of a MONITORINFO
+    let have_monitor = unsafe {
+        GetMonitorInfoW(
+            monitor,
+            &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+        )
+    }
+    .as_bool();
+
+    if have_monitor {
+        let mut mode = DEVMODEW {
+            dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+            ..Default::default()
+        };
+        // SAFETY: szDevice is the monitor's NUL-terminated device name and
+        // mode has dmSize set
+        if unsafe {
+            EnumDisplaySettingsW(
+                PCWSTR(info.szDevice.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut mode,
+            )
+        }
+        .as_bool()
+            && mode.dmDisplayFrequency > 1
+        {
+            settings.refresh_rate = Some(mode.dmDisplayFrequency);
+        }
+    }
+
+    if have_bounds && have_monitor {
+        let rect = |r: RECT| [r.left, r.top, r.right, r.bottom];
+        // SAFETY: Takes no arguments
+        let exclusive = unsafe { SHQueryUserNotificationState() }
+            .is_ok_and(|state| state == QUNS_RUNNING_D3D_FULL_SCREEN);
+        settings.display_mode = Some(display_mode(
+            rect(bounds),
+            rect(info.monitorInfo.rcMonitor),
+            captioned,
+            exclusive,
+        ));
+    }
+
+    let (adapters, hdr) = read_adapters(Some(monitor));
+    settings.adapters = adapters;
+    settings.hdr = hdr;
+    settings
+}
+
+#[cfg(not(windows))]
+fn read_display() -> GraphicsSettings {
+    GraphicsSettings::default()
+}
+
+/// Returns the largest visible top-level window of this process.
+#[cfg(windows)]
+fn game_window() -> Option<windows::Win32::Foundation::HWND> {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+    use windows::Win32::System::Threading::GetCurrentProcessId;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetClientRect, GetWindowThreadProcessId, IsWindowVisible,
+    };
+
+    struct Search {
+        process: u32,
+        best: Option<(HWND, i64)>,
+    }
+
+    unsafe extern "system" fn visit(window: HWND, param: LPARAM) -> BOOL {
+        // SAFETY: param is the Search passed to EnumWindows below, which
+        // outlives the enumeration
+        let search = unsafe { &mut *(param.0 as *mut Search) };
+        let mut process = 0u32;
+        // SAFETY: window comes from EnumWindows; process is valid for writes
+        unsafe { GetWindowThreadProcessId(window, Some(&mut process)) };
+        // SAFETY: As above
+        if process == search.process && unsafe { IsWindowVisible(window) }.as_bool() {
+            let mut client = RECT::default();
+            // SAFETY: As above; client is valid for writes
+            if unsafe { GetClientRect(window, &mut client) }.is_ok() {
+                let area =
+                    i64::from(client.right - client.left) * i64::from(client.bottom - client.top);
+                if search.best.is_none_or(|(_, best)| area > best) {
+                    search.best = Some((window, area));
+                }
+            }
+        }
+        BOOL(1)
+    }
+
+    let mut search = Search {
+        // SAFETY: Takes no arguments
+        process: unsafe { GetCurrentProcessId() },
+        best: None,
+    };
+    // SAFETY: search lives on this stack until EnumWindows returns
+    let _ = unsafe { EnumWindows(Some(visit), LPARAM(&mut search as *mut Search as isize)) };
+    search.best.map(|(window, _)| window)
+}
+
+/// Lists the hardware GPUs, marking the one with an output on `monitor`
+/// active, and whether HDR is on for that output.
+#[cfg(windows)]
+fn read_adapters(
+    monitor: Option<windows::Win32::Graphics::Gdi::HMONITOR>,
+) -> (Vec<GpuAdapter>, Option<bool>) {
+    use windows::Win32::Graphics::Dxgi::Common::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020;
+    use windows::Win32::Graphics::Dxgi::{
+        CreateDXGIFactory1, DXGI_ADAPTER_FLAG_SOFTWARE, IDXGIFactory1, IDXGIOutput6,
+    };
+    use windows::core::Interface;
+
+    // SAFETY: Creates a new factory owned by this function
+    let factory: IDXGIFactory1 = match unsafe { CreateDXGIFactory1() } {
+        Ok(factory) => factory,
+        Err(e) => {
+            debug!("Cannot create a DXGI factory: {}", e);
+            return (Vec::new(), None);
+        }
+    };
+
+    let mut adapters = Vec::new();
+    let mut hdr = None;
+    // SAFETY: EnumAdapters1 fails past the last adapter, ending the loop
+    for adapter in (0..).map_while(|index| unsafe { factory.EnumAdapters1(index) }.ok()) {
+        // SAFETY: adapter is a live IDXGIAdapter1
+        let Ok(desc) = (unsafe { adapter.GetDesc1() }) else {
+            continue;
+        };
+        if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+            continue;
+        }
+
+        let mut drives_monitor = false;
+        // SAFETY: EnumOutputs fails past the last output, ending the loop
+        for output in (0..).map_while(|index| unsafe { adapter.EnumOutputs(index) }.ok()) {
+            // SAFETY: output is a live IDXGIOutput; IDXGIOutput6 needs
+            // Windows 10 1803, and older systems skip the output
+            let Ok(output_desc) = output
+                .cast::<IDXGIOutput6>()
+                .and_then(|output| unsafe { output.GetDesc1() })
+            else {
+                continue;
+            };
+            if Some(output_desc.Monitor) == monitor {
+                drives_monitor = true;
+                hdr = Some(output_desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020);
+            }
+        }
+
+        let name_len = desc
+            .Description
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(desc.Description.len());
+        adapters.push(GpuAdapter {
+            name: String::from_utf16_lossy(&desc.Description[..name_len]),
+            vendor_id: desc.VendorId,
+            vram_mb: desc.DedicatedVideoMemory as u64 / (1024 * 1024),
+            active: drives_monitor,
+        });
+    }
+    (adapters, hdr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn loaded_driver_picks_the_active_gpu() {
+        // Intel drives the laptop panel, NVIDIA renders
+        let vendors = [INTEL, NVIDIA];
+        assert_eq!(active_adapter(&vendors, Some(NVIDIA), Some(0)), Some(1));
+        assert_eq!(active_adapter(&vendors, None, Some(0)), Some(0));
+        assert_eq!(active_adapter(&vendors, Some(AMD), None), None);
+
+        assert_eq!(driver_vendor("nvwgf2umx.dll"), Some(NVIDIA));
+        assert_eq!(driver_vendor("igd12umd64.dll"), Some(INTEL));
+        assert_eq!(driver_vendor("SkyrimSE.exe"), None);
+    }
+
+    #[test]
+    fn window_bounds_give_the_display_mode() {
+        let monitor = [0, 0, 2560, 1440];
+        assert_eq!(
+            display_mode(monitor, monitor, false, true),
+            DisplayMode::Fullscreen
+        );
+        assert_eq!(
+            display_mode(monitor, monitor, false, false),
+            DisplayMode::Borderless
+        );
+        assert_eq!(
+            display_mode(monitor, monitor, true, false),
+            DisplayMode::Windowed
+        );
+        assert_eq!(
+            display_mode([100, 100, 1380, 820], monitor, false, false),
+            DisplayMode::Windowed
+        );
+    }
+
+    #[test]
+    fn finds_loaded_and_installed_upscalers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("nvngx_dlss.dll"), b"not a real dll").unwrap();
+        fs::write(dir.path().join("libxess.dll"), b"not a real dll").unwrap();
+        fs::write(dir.path().join("d3d11.dll"), b"not a real dll").unwrap();
+
+        let modules = [LoadedModule {
+            name: "NVNGX_DLSS.DLL".into(),
+            path: PathBuf::from(r"C:\Games\Skyrim\NVNGX_DLSS.DLL"),
+            base: 0x7FF6_0000_0000,
+            size: 0x100000,
+        }];
+        let mut upscalers = find_upscalers(&modules, Some(dir.path()));
+        upscalers.sort_by(|a, b| a.file.cmp(&b.file));
+
+        assert_eq!(upscalers.len(), 2);
+        assert_eq!(upscalers[0].file, "NVNGX_DLSS.DLL");
+        assert_eq!(upscalers[0].kind, UpscalerKind::Dlss);
+        assert!(upscalers[0].loaded);
+        assert_eq!(upscalers[1].file, "libxess.dll");
+        assert_eq!(upscalers[1].kind, UpscalerKind::Xess);
+        assert!(!upscalers[1].loaded);
+    }
+}
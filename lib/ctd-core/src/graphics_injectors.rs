@@ -0,0 +1,161 @@
+//! Detection of graphics injectors (ENB, ReShade) in the game directory.
+//!
+//! Injectors install themselves as a proxy for a Direct3D DLL in the game
+//! root, where the game loads them instead of the system copy. They hook
+//! every frame, so they are behind a large share of crashes, yet they are
+//! not plugins and never show up in the load order.
+//!
+//! A proxy is identified by its exports: the ENB SDK exports `ENBGetVersion`
+//! and friends, and ReShade 5+ exports its add-on API. Older builds without
+//! those exports are recognized by the config file each one keeps next to it.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::pe::PeImage;
+use crate::version::get_dll_version;
+
+/// Direct3D DLLs an injector can stand in for.
+///
+/// `d3d9.dll` covers Fallout 3 and New Vegas; `d3d11.dll` and `dxgi.dll`
+/// cover Skyrim SE/AE and Fallout 4.
+const PROXY_DLLS: &[&str] = &["d3d11.dll", "dxgi.dll", "d3d9.dll"];
+
+/// Exports only present in ENB's proxy DLL.
+const ENB_EXPORTS: &[&str] = &["ENBGetSDKVersion", "ENBGetVersion"];
+
+/// Exports only present in ReShade's proxy DLL.
+const RESHADE_EXPORTS: &[&str] = &["ReShadeRegisterAddon", "ReShadeRegisterEvent"];
+
+/// Config files ENB reads from the game root.
+const ENB_CONFIGS: &[&str] = &["enbseries.ini", "enblocal.ini"];
+
+/// Config files ReShade reads from the game root.
+const RESHADE_CONFIGS: &[&str] = &["ReShade.ini"];
+
+/// The injector behind a proxy DLL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectorKind {
+    /// ENBSeries.
+    Enb,
+    /// ReShade.
+    ReShade,
+    /// Some other wrapper (DXVK, a frame generator, an overlay, ...).
+    Unknown,
+}
+
+/// A Direct3D proxy DLL found in the game root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphicsInjector {
+    /// Which injector the DLL belongs to.
+    pub kind: InjectorKind,
+    /// The proxy's file name (e.g., "d3d11.dll").
+    pub file: String,
+    /// PE file version, if the DLL has a version resource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Returns the graphics injectors installed in `game_dir`.
+///
+/// `game_dir` is the directory holding the game executable, not `Data/`.
+/// Returns an empty list if no proxy DLL is present.
+pub fn detect_graphics_injectors(game_dir: &Path) -> Vec<GraphicsInjector> {
+    PROXY_DLLS
+        .iter()
+        .filter_map(|&file| {
+            let path = game_dir.join(file);
+            if !path.is_file() {
+                return None;
+            }
+
+            let kind = identify(&path, game_dir);
+            debug!("Found {:?} graphics injector at {}", kind, path.display());
+            Some(GraphicsInjector {
+                kind,
+                file: file.to_string(),
+                version: get_dll_version(&path).ok(),
+            })
+        })
+        .collect()
+}
+
+/// Works out which injector a proxy DLL belongs to.
+fn identify(dll: &Path, game_dir: &Path) -> InjectorKind {
+    let exports = PeImage::open(dll)
+        .and_then(|mut pe| pe.export_names())
+        .unwrap_or_default();
+    let exports_any = |names: &[&str]| exports.iter().any(|e| names.contains(&e.as_str()));
+
+    if exports_any(ENB_EXPORTS) {
+        InjectorKind::Enb
+    } else if exports_any(RESHADE_EXPORTS) {
+        InjectorKind::ReShade
+    } else if has_any(game_dir, ENB_CONFIGS) {
+        InjectorKind::Enb
+    } else if has_any(game_dir, RESHADE_CONFIGS) {
+        InjectorKind::ReShade
+    } else {
+        InjectorKind::Unknown
+    }
+}
+
+/// Returns true if any of `files` exists in `dir`.
+fn has_any(dir: &Path, files: &[&str]) -> bool {
+    files.iter().any(|file| dir.join(file).is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn identifies_injectors_by_config_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("d3d11.dll"), b"not a real dll").unwrap();
+        fs::write(dir.path().join("enbseries.ini"), b"[GLOBAL]").unwrap();
+
+        let injectors = detect_graphics_injectors(dir.path());
+        assert_eq!(injectors.len(), 1);
+        assert_eq!(injectors[0].kind, InjectorKind::Enb);
+        assert_eq!(injectors[0].file, "d3d11.dll");
+
+        fs::remove_file(dir.path().join("enbseries.ini")).unwrap();
+        fs::write(dir.path().join("ReShade.ini"), b"[GENERAL]").unwrap();
+        assert_eq!(
+            detect_graphics_injectors(dir.path())[0].kind,
+            InjectorKind::ReShade
+        );
+
+        fs::remove_file(dir.path().join("ReShade.ini")).unwrap();
+        assert_eq!(
+            detect_graphics_injectors(dir.path())[0].kind,
+            InjectorKind::Unknown
+        );
+    }
+
+    #[test]
+    fn no_proxy_dlls_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("enbseries.ini"), b"[GLOBAL]").unwrap();
+        assert!(detect_graphics_injectors(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn serializes_kind_in_lowercase() {
+        let injector = GraphicsInjector {
+            kind: InjectorKind::ReShade,
+            file: "dxgi.dll".to_string(),
+            version: Some("5.9.2.1".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_string(&injector).unwrap(),
+            r#"{"kind":"reshade","file":"dxgi.dll","version":"5.9.2.1"}"#
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! GPU driver resets and WHEA hardware errors from the Windows System log.
+//!
+//! A crash that follows a display driver reset (TDR, event 4101 from
+//! `Display`) or a WHEA hardware error (a corrected machine check, a memory
+//! or PCIe error from `Microsoft-Windows-WHEA-Logger`) says more about an
+//! overclock, a failing GPU, or bad RAM than about the load order. The crash
+//! handlers call [`recent`] while building a report, and the events logged
+//! in the [`LOOKBACK`] before it ride along in the `hardwareEvents` section
+//! so users and mod authors can tell hardware instability from mod bugs.
+//!
+//! The System log is read with the classic Event Log API, newest first, and
+//! reading stops at the first record older than the window.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::debug;
+
+use crate::crash_report::{HardwareEvent, HardwareEventKind, MAX_HARDWARE_EVENTS};
+
+/// How far before the crash events are collected.
+pub const LOOKBACK: Duration = Duration::from_secs(5 * 60);
+
+/// Source of display driver events.
+pub const DISPLAY_SOURCE: &str = "Display";
+
+/// Event ID of "Display driver stopped responding and has recovered".
+pub const TDR_EVENT_ID: u32 = 4101;
+
+/// Source of WHEA hardware error events.
+pub const WHEA_SOURCE: &str = "Microsoft-Windows-WHEA-Logger";
+
+/// Size of the fixed `EVENTLOGRECORD` header; the source name follows it.
+#[cfg_attr(not(windows), allow(dead_code))]
+const RECORD_HEADER_LEN: usize = 56;
+
+/// Returns the hardware events logged in the [`LOOKBACK`] before now,
+/// newest first. Empty if the System log cannot be read or on other
+/// platforms.
+pub fn recent() -> Vec<HardwareEvent> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let since = now.saturating_sub(LOOKBACK).as_secs() as u32;
+
+    match read_system_log(since) {
+        Ok(events) => events,
+        Err(e) => {
+            debug!("Cannot read hardware events from the System log: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Returns what an event reports, or `None` if it isn't a hardware event.
+pub fn classify(source: &str, event_id: u32) -> Option<HardwareEventKind> {
+    match source {
+        DISPLAY_SOURCE if event_id == TDR_EVENT_ID => Some(HardwareEventKind::GpuReset),
+        WHEA_SOURCE => Some(HardwareEventKind::Whea),
+        _ => None,
+    }
+}
+
+/// The parts of an `EVENTLOGRECORD` that matter here.
+#[cfg_attr(not(windows), allow(dead_code))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Record {
+    /// Unix timestamp (seconds) when the event was logged.
+    time_generated: u32,
+    /// Event ID without the severity and facility bits.
+    event_id: u32,
+    source: String,
+}
+
+/// Splits a buffer filled by `ReadEventLogW` into records, stopping at the
+/// first truncated one.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_records(buffer: &[u8]) -> Vec<Record> {
+    let u32_at = |data: &[u8], offset: usize| {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    };
+
+    let mut records = Vec::new();
+    let mut rest = buffer;
+    while rest.len() >= RECORD_HEADER_LEN {
+        let len = u32_at(rest, 0) as usize;
+        if len < RECORD_HEADER_LEN || len > rest.len() {
+            break;
+        }
+        let record = &rest[..len];
+
+        let source: Vec<u16> = record[RECORD_HEADER_LEN..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        records.push(Record {
+            time_generated: u32_at(record, 12),
+            event_id: u32_at(record, 20) & 0xFFFF,
+            source: String::from_utf16_lossy(&source),
+        });
+        rest = &rest[len..];
+    }
+    records
+}
+
+/// Keeps the hardware events among records read newest first. Returns
+/// `true` once a record older than `since` shows the window is exhausted.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn collect(records: &[Record], since: u32, events: &mut Vec<HardwareEvent>) -> bool {
+    for record in records {
+        if record.time_generated < since || events.len() >= MAX_HARDWARE_EVENTS {
+            return true;
+        }
+        if let Some(kind) = classify(&record.source, record.event_id) {
+            events.push(HardwareEvent {
+                kind,
+                source: record.source.clone(),
+                event_id: record.event_id,
+                occurred_at: u64::from(record.time_generated) * 1000,
+            });
+        }
+    }
+    false
+}
+
+/// Reads the System log backwards to `since` (Unix seconds).
+#[cfg(windows)]
+fn read_system_log(since: u32) -> std::io::Result<Vec<HardwareEvent>> {
+    use std::io;
+
+    use windows::Win32::Foundation::{ERROR_HANDLE_EOF, ERROR_INSUFFICIENT_BUFFER};
+    use windows::Win32::System::EventLog::{
+        CloseEventLog, EVENTLOG_SEQUENTIAL_READ, OpenEventLogW, READ_EVENT_LOG_READ_FLAGS,
+        ReadEventLogW,
+    };
+    use windows::core::{HSTRING, PCWSTR};
+
+    // winnt.h's EVENTLOG_BACKWARDS_READ, which the windows crate leaves out
+    const EVENTLOG_BACKWARDS_READ: u32 = 0x0008;
+
+    // SAFETY: The log name is a valid wide string; the handle is closed below
+    let handle = unsafe { OpenEventLogW(PCWSTR::null(), &HSTRING::from("System")) }
+        .map_err(io::Error::other)?;
+
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut events = Vec::new();
+    let result = loop {
+        let mut read = 0u32;
+        let mut needed = 0u32;
+        // SAFETY: The buffer is writable for its whole length
+        let status = unsafe {
+            ReadEventLogW(
+                handle,
+                READ_EVENT_LOG_READ_FLAGS(EVENTLOG_SEQUENTIAL_READ.0 | EVENTLOG_BACKWARDS_READ),
+                0,
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as u32,
+                &mut read,
+                &mut needed,
+            )
+        };
+        match status {
+            Ok(()) => {
+                let records = parse_records(&buffer[..read as usize]);
+                if collect(&records, since, &mut events) {
+                    break Ok(());
+                }
+            }
+            Err(e) if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult() => {
+                buffer.resize(needed as usize, 0);
+            }
+            // Reached the oldest record with the whole log inside the window
+            Err(e) if e.code() == ERROR_HANDLE_EOF.to_hresult() => break Ok(()),
+            Err(e) => break Err(io::Error::other(e)),
+        }
+    };
+
+    // SAFETY: The handle was opened above
+    let _ = unsafe { CloseEventLog(handle) };
+    result.map(|()| events)
+}
+
+/// Reads the System log backwards to `since` (Unix seconds).
+#[cfg(not(windows))]
+fn read_system_log(_since: u32) -> std::io::Result<Vec<HardwareEvent>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the Event Log is only available on Windows",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes an `EVENTLOGRECORD` with no strings or data.
+    fn record(time_generated: u32, event_id: u32, source: &str) -> Vec<u8> {
+        let mut name: Vec<u8> = source
+            .encode_utf16()
+            .chain([0, 0])
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        name.resize(name.len().next_multiple_of(4), 0);
+
+        let mut bytes = vec![0u8; RECORD_HEADER_LEN];
+        let len = (RECORD_HEADER_LEN + name.len() + 4) as u32;
+        bytes[0..4].copy_from_slice(&len.to_le_bytes());
+        bytes[4..8].copy_from_slice(b"LfLe");
+        bytes[12..16].copy_from_slice(&time_generated.to_le_bytes());
+        bytes[20..24].copy_from_slice(&event_id.to_le_bytes());
+        bytes.extend(name);
+        // Trailing copy of the length
+        bytes.extend(len.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn parses_records_and_masks_event_ids() {
+        let mut buffer = record(2000, 0x4000_1005, DISPLAY_SOURCE);
+        buffer.extend(record(1990, 19, WHEA_SOURCE));
+        // A truncated record is dropped
+        buffer.extend(&record(1980, 7, "disk")[..20]);
+
+        let records = parse_records(&buffer);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].event_id, TDR_EVENT_ID);
+        assert_eq!(records[0].source, "Display");
+        assert_eq!(records[1].time_generated, 1990);
+        assert_eq!(records[1].source, WHEA_SOURCE);
+    }
+
+    #[test]
+    fn keeps_hardware_events_inside_the_window() {
+        let mut buffer = record(2000, TDR_EVENT_ID, DISPLAY_SOURCE);
+        buffer.extend(record(1995, 7036, "Service Control Manager"));
+        buffer.extend(record(1990, 4102, DISPLAY_SOURCE));
+        buffer.extend(record(1985, 47, WHEA_SOURCE));
+        buffer.extend(record(1000, TDR_EVENT_ID, DISPLAY_SOURCE));
+
+        let mut events = Vec::new();
+        assert!(collect(&parse_records(&buffer), 1500, &mut events));
+        assert_eq!(
+            events,
+            [
+                HardwareEvent {
+                    kind: HardwareEventKind::GpuReset,
+                    source: DISPLAY_SOURCE.to_string(),
+                    event_id: TDR_EVENT_ID,
+                    occurred_at: 2_000_000,
+                },
+                HardwareEvent {
+                    kind: HardwareEventKind::Whea,
+                    source: WHEA_SOURCE.to_string(),
+                    event_id: 47,
+                    occurred_at: 1_985_000,
+                },
+            ]
+        );
+
+        // Records that are all newer than the window keep the read going
+        let mut events = Vec::new();
+        assert!(!collect(&parse_records(&buffer), 0, &mut events));
+        assert_eq!(events.len(), 3);
+    }
+}
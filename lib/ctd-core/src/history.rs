@@ -0,0 +1,355 @@
+//! Local history of generated crash reports.
+//!
+//! Every report a plugin or tool builds is kept here, whether or not it
+//! reached the server, so users can look up what was sent and find the
+//! share link later with `ctd history`. Each entry is one JSON file in
+//! `<config dir>/ctd/history`, named by a key derived from the report, so
+//! recording the same report twice (queued, then flushed from the spool)
+//! updates one entry.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::api_client;
+use crate::config::Config;
+use crate::crash_report::{CrashReportResponse, CreateCrashReport};
+use crate::{CtdError, Result};
+
+/// Entries kept before the oldest are deleted.
+pub const MAX_ENTRIES: usize = 500;
+
+/// Whether a recorded report reached the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportStatus {
+    /// Not submitted yet; the report may be waiting in the spool.
+    #[default]
+    Pending,
+    /// Accepted by the server.
+    Submitted,
+}
+
+impl ReportStatus {
+    /// Returns the lowercase name used in listings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Submitted => "submitted",
+        }
+    }
+}
+
+/// A report in the local history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    /// Local key of the entry, e.g. `00000001704067200000-1a2b3c4d5e6f7081`.
+    pub key: String,
+    /// Whether the report reached the server.
+    pub status: ReportStatus,
+    /// Server-assigned report ID (ULID), once submitted.
+    pub report_id: Option<String>,
+    /// Link that opens the report with its share token, once submitted.
+    pub share_url: Option<String>,
+    /// Unix timestamp (milliseconds) when the report was first recorded.
+    pub recorded_at: u64,
+    /// Unix timestamp (milliseconds) when the server accepted the report.
+    pub submitted_at: Option<u64>,
+    /// The report itself.
+    pub report: CreateCrashReport,
+}
+
+/// A directory of recorded reports.
+#[derive(Debug, Clone)]
+pub struct History {
+    dir: PathBuf,
+}
+
+impl History {
+    /// Creates a history backed by the given directory.
+    ///
+    /// The directory is created on first record.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Creates a history in the default location.
+    pub fn open_default() -> Self {
+        Self::new(Self::default_dir())
+    }
+
+    /// Returns the default directory (`<config dir>/ctd/history`).
+    pub fn default_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("ctd")
+            .join("history")
+    }
+
+    /// Returns the history directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Records a report that has not been submitted.
+    ///
+    /// Does nothing if the report is already recorded, so a submitted entry
+    /// is never set back to pending.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::History` if the entry cannot be written.
+    pub fn record(&self, report: &CreateCrashReport) -> Result<HistoryEntry> {
+        let key = entry_key(report);
+        if let Some(existing) = self.get(&key) {
+            return Ok(existing);
+        }
+
+        let entry = HistoryEntry {
+            key,
+            status: ReportStatus::Pending,
+            report_id: None,
+            share_url: None,
+            recorded_at: now_millis(),
+            submitted_at: None,
+            report: report.clone(),
+        };
+        self.write(&entry)?;
+        self.prune();
+        Ok(entry)
+    }
+
+    /// Marks a report as submitted, recording it first if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::History` if the entry cannot be written.
+    pub fn mark_submitted(
+        &self,
+        report: &CreateCrashReport,
+        response: &CrashReportResponse,
+        share_url: Option<String>,
+    ) -> Result<HistoryEntry> {
+        let mut entry = self.record(report)?;
+        entry.status = ReportStatus::Submitted;
+        entry.report_id = Some(response.id.clone());
+        entry.share_url = share_url;
+        entry.submitted_at = Some(now_millis());
+        self.write(&entry)?;
+        Ok(entry)
+    }
+
+    /// Lists recorded reports, newest crash first.
+    ///
+    /// Unreadable files are skipped.
+    pub fn list(&self) -> Vec<HistoryEntry> {
+        self.paths()
+            .iter()
+            .rev()
+            .filter_map(|path| read_entry(path))
+            .collect()
+    }
+
+    /// Returns the entry with the given local key or server report ID.
+    pub fn get(&self, id: &str) -> Option<HistoryEntry> {
+        if is_valid_key(id)
+            && let Some(entry) = read_entry(&self.entry_path(id))
+        {
+            return Some(entry);
+        }
+        self.list()
+            .into_iter()
+            .find(|entry| entry.report_id.as_deref() == Some(id))
+    }
+
+    /// Returns the entry files, oldest crash first.
+    fn paths(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Writes an entry, replacing any previous version.
+    fn write(&self, entry: &HistoryEntry) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| CtdError::History(format!("Failed to create history directory: {}", e)))?;
+
+        // Write then rename so a listing never sees a half-written entry
+        let path = self.entry_path(&entry.key);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(entry)?)
+            .and_then(|()| fs::rename(&tmp_path, &path))
+            .map_err(|e| CtdError::History(format!("Failed to write history entry: {}", e)))
+    }
+
+    /// Deletes the oldest entries beyond [`MAX_ENTRIES`].
+    fn prune(&self) {
+        let paths = self.paths();
+        let excess = paths.len().saturating_sub(MAX_ENTRIES);
+        for path in &paths[..excess] {
+            if let Err(e) = fs::remove_file(path) {
+                debug!("Failed to prune {:?}: {}", path, e);
+            }
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+/// Records a report that could not be submitted in the default history,
+/// logging instead of failing.
+pub fn record_pending(report: &CreateCrashReport) {
+    if let Err(e) = History::open_default().record(report) {
+        warn!("Failed to record report in history: {}", e);
+    }
+}
+
+/// Records a submitted report in the default history, with a share link
+/// for the configured API, logging instead of failing.
+///
+/// Reports sent to Sentry get no share link.
+pub fn record_submitted(report: &CreateCrashReport, response: &CrashReportResponse) {
+    let config = Config::load().unwrap_or_default();
+    let share_url = config
+        .sentry
+        .dsn
+        .is_none()
+        .then(|| api_client::share_url(&config.api, response));
+    if let Err(e) = History::open_default().mark_submitted(report, response, share_url) {
+        warn!("Failed to record report in history: {}", e);
+    }
+}
+
+/// Returns the key of a report's entry: the zero-padded crash time, so
+/// keys sort chronologically, then a hash of the game and stack trace.
+pub fn entry_key(report: &CreateCrashReport) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(report.game_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(report.stack_trace.as_bytes());
+    let digest = hex::encode(hasher.finalize());
+    format!("{:020}-{}", report.crashed_at, &digest[..16])
+}
+
+/// Returns true if `key` has the shape [`entry_key`] produces, so it is
+/// safe to use as a file name.
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn read_entry(path: &Path) -> Option<HistoryEntry> {
+    let json = fs::read(path).ok()?;
+    match serde_json::from_slice(&json) {
+        Ok(entry) => Some(entry),
+        Err(e) => {
+            debug!("Ignoring unreadable {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModList;
+    use tempfile::tempdir;
+
+    fn sample_report(crashed_at: u64) -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("[0] SkyrimSE.exe+0x1234")
+            .load_order_v2(ModList::new())
+            .crashed_at(crashed_at)
+            .build()
+            .unwrap()
+    }
+
+    fn response() -> CrashReportResponse {
+        CrashReportResponse {
+            id: "01HX".into(),
+            share_token: "tok".into(),
+        }
+    }
+
+    #[test]
+    fn record_then_mark_submitted_updates_one_entry() {
+        let dir = tempdir().unwrap();
+        let history = History::new(dir.path());
+        let report = sample_report(1000);
+
+        let pending = history.record(&report).unwrap();
+        assert_eq!(pending.status, ReportStatus::Pending);
+        assert_eq!(pending.key, entry_key(&report));
+
+        let submitted = history
+            .mark_submitted(&report, &response(), Some("https://x/crashes/01HX".into()))
+            .unwrap();
+        assert_eq!(submitted.status, ReportStatus::Submitted);
+        assert_eq!(submitted.recorded_at, pending.recorded_at);
+        assert!(submitted.submitted_at.is_some());
+
+        // Recording again does not reset the status
+        history.record(&report).unwrap();
+        let entries = history.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, ReportStatus::Submitted);
+        assert_eq!(entries[0].report_id.as_deref(), Some("01HX"));
+    }
+
+    #[test]
+    fn list_is_newest_first_and_get_finds_by_either_id() {
+        let dir = tempdir().unwrap();
+        let history = History::new(dir.path());
+        let older = history.record(&sample_report(1000)).unwrap();
+        history
+            .mark_submitted(&sample_report(2000), &response(), None)
+            .unwrap();
+
+        let keys: Vec<_> = history
+            .list()
+            .into_iter()
+            .map(|e| e.report.crashed_at)
+            .collect();
+        assert_eq!(keys, [2000, 1000]);
+
+        assert_eq!(history.get(&older.key).unwrap().report.crashed_at, 1000);
+        assert_eq!(history.get("01HX").unwrap().report.crashed_at, 2000);
+        assert!(history.get("../escape").is_none());
+    }
+
+    #[test]
+    fn oldest_entries_are_pruned() {
+        let dir = tempdir().unwrap();
+        let history = History::new(dir.path());
+        for crashed_at in 0..=MAX_ENTRIES as u64 {
+            history.record(&sample_report(crashed_at)).unwrap();
+        }
+
+        let entries = history.list();
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries.last().unwrap().report.crashed_at, 1);
+    }
+}
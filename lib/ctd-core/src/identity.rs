@@ -0,0 +1,133 @@
+//! Anonymous installation and session IDs for reports.
+//!
+//! Without them the backend cannot tell one player crashing fifty times
+//! from fifty players crashing once. The installation ID is a random UUID
+//! created on first use and kept in `<config dir>/ctd/installation-id`; the
+//! session ID is a new random UUID for each launch. Neither is derived from
+//! the machine, the account, or the load order.
+//!
+//! Both are sent with crash reports and session summaries unless
+//! `[privacy] anonymous_ids = false`. Deleting the file starts a new
+//! installation ID.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::config::Config;
+
+/// This launch's session ID.
+static SESSION_ID: OnceLock<String> = OnceLock::new();
+
+/// This installation's ID, or `None` if it could not be stored.
+static INSTALLATION_ID: OnceLock<Option<String>> = OnceLock::new();
+
+/// The IDs to attach to a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnonymousIds {
+    /// Random UUID kept across launches, if it could be stored.
+    pub installation_id: Option<String>,
+    /// Random UUID for this launch.
+    pub session_id: String,
+}
+
+/// Returns the IDs to attach to a report, or `None` if
+/// `[privacy] anonymous_ids` is off.
+pub fn current() -> Option<AnonymousIds> {
+    let config = Config::load().unwrap_or_default();
+    if !config.privacy.anonymous_ids {
+        return None;
+    }
+
+    Some(AnonymousIds {
+        installation_id: installation_id(),
+        session_id: session_id(),
+    })
+}
+
+/// Returns this launch's session ID, generating it on first call.
+pub fn session_id() -> String {
+    SESSION_ID
+        .get_or_init(|| Uuid::new_v4().to_string())
+        .clone()
+}
+
+/// Returns this installation's ID, creating it on first use.
+///
+/// Returns `None` if the ID file cannot be written: an ID that changes every
+/// launch would count one player as many.
+pub fn installation_id() -> Option<String> {
+    INSTALLATION_ID
+        .get_or_init(|| load_or_create(&default_path()))
+        .clone()
+}
+
+/// Returns the installation ID file (`<config dir>/ctd/installation-id`).
+pub fn default_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("ctd")
+        .join("installation-id")
+}
+
+/// Reads the ID at `path`, replacing a missing or corrupt one.
+fn load_or_create(path: &Path) -> Option<String> {
+    if let Ok(existing) = fs::read_to_string(path)
+        && let Ok(id) = Uuid::parse_str(existing.trim())
+    {
+        return Some(id.to_string());
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, &id));
+    match written {
+        Ok(()) => Some(id),
+        Err(e) => {
+            warn!("Failed to store installation ID at {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Returns true if `id` is a UUID in any of its usual spellings.
+pub fn is_valid_id(id: &str) -> bool {
+    Uuid::parse_str(id).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installation_id_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctd").join("installation-id");
+
+        let first = load_or_create(&path).unwrap();
+        assert!(is_valid_id(&first));
+        assert_eq!(load_or_create(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn corrupt_installation_id_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("installation-id");
+        fs::write(&path, "not a uuid").unwrap();
+
+        let id = load_or_create(&path).unwrap();
+        assert!(is_valid_id(&id));
+        assert_eq!(fs::read_to_string(&path).unwrap(), id);
+    }
+
+    #[test]
+    fn session_id_is_stable_within_a_launch() {
+        assert!(is_valid_id(&session_id()));
+        assert_eq!(session_id(), session_id());
+    }
+}
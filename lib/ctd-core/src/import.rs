@@ -0,0 +1,479 @@
+//! Import of community crash logs.
+//!
+//! Users often have folders full of logs written by other crash loggers.
+//! This module parses the popular formats into a [`CreateCrashReport`] so they
+//! can be submitted for pattern analysis without reproducing the crash:
+//!
+//! - Crash Logger SSE/AE (`crash-*.log`)
+//! - Buffout 4 (`crash-*.log`)
+//! - .NET Script Framework (`Crash_*.txt`)
+
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::crash_report::{CrashReportBuilder, CreateCrashReport};
+use crate::load_order::{ModEntry, ModList};
+use crate::{CtdError, Result};
+
+/// A community crash log format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashLogFormat {
+    /// Crash Logger SSE/AE for Skyrim.
+    CrashLogger,
+    /// Buffout 4 for Fallout 4.
+    Buffout4,
+    /// .NET Script Framework for Skyrim.
+    NetScriptFramework,
+}
+
+/// A crash log parsed into the fields CTD reports on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedCrashLog {
+    /// Game identifier (e.g., "skyrim-se").
+    pub game_id: String,
+    /// Game version reported by the log.
+    pub game_version: String,
+    /// Exception code, as hex when the name is known.
+    pub exception_code: Option<String>,
+    /// Exception address.
+    pub exception_address: Option<String>,
+    /// Module containing the exception address.
+    pub faulting_module: Option<String>,
+    /// Stack trace in CTD's `[i] module+0xOFFSET (0xADDR)` format.
+    pub stack_trace: String,
+    /// Operating system version, if listed.
+    pub os_version: Option<String>,
+    /// Plugins in load order.
+    pub plugins: Vec<String>,
+}
+
+impl ImportedCrashLog {
+    /// Creates a report builder pre-filled with the imported fields.
+    ///
+    /// The crash timestamp is not part of most logs; callers set it with
+    /// [`CrashReportBuilder::crashed_at`]. The plugins' files aren't at hand,
+    /// so their entries carry no hash.
+    pub fn into_builder(self) -> CrashReportBuilder {
+        let mods = self
+            .plugins
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ModEntry::new(name, "", 0).with_index(i as u32))
+            .collect();
+
+        let mut builder = CreateCrashReport::builder()
+            .game_id(self.game_id)
+            .game_version(self.game_version)
+            .stack_trace(self.stack_trace)
+            .load_order_v2(ModList::from_entries(mods));
+
+        if let Some(code) = self.exception_code {
+            builder = builder.exception_code(code);
+        }
+        if let Some(address) = self.exception_address {
+            builder = builder.exception_address(address);
+        }
+        if let Some(module) = self.faulting_module {
+            builder = builder.faulting_module(module);
+        }
+        if let Some(os) = self.os_version {
+            builder = builder.os_version(os);
+        }
+
+        builder
+    }
+}
+
+/// Detects which crash logger wrote a log.
+pub fn detect_format(contents: &str) -> Option<CrashLogFormat> {
+    let head: Vec<&str> = contents.lines().take(5).map(str::trim).collect();
+
+    if head.iter().any(|l| l.starts_with("Buffout 4")) {
+        Some(CrashLogFormat::Buffout4)
+    } else if head.iter().any(|l| l.starts_with("CrashLoggerSSE")) {
+        Some(CrashLogFormat::CrashLogger)
+    } else if contents.contains("FrameworkName: NetScriptFramework") {
+        Some(CrashLogFormat::NetScriptFramework)
+    } else {
+        None
+    }
+}
+
+/// Parses a crash log in any supported format.
+pub fn parse_crash_log(contents: &str) -> Result<ImportedCrashLog> {
+    match detect_format(contents) {
+        Some(CrashLogFormat::CrashLogger | CrashLogFormat::Buffout4) => {
+            parse_crash_logger(contents)
+        }
+        Some(CrashLogFormat::NetScriptFramework) => parse_net_script_framework(contents),
+        None => Err(CtdError::Import("unrecognized crash log format".into())),
+    }
+}
+
+/// Imports a crash log file, using its modification time as the crash time.
+pub fn import_file(path: &Path) -> Result<CreateCrashReport> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| CtdError::Import(format!("Failed to read {:?}: {}", path, e)))?;
+
+    let crashed_at = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+
+    let builder = parse_crash_log(&contents)?.into_builder();
+    match crashed_at {
+        Some(ts) => builder.crashed_at(ts),
+        None => builder.crashed_now(),
+    }
+    .build()
+}
+
+/// Parses Crash Logger SSE/AE and Buffout 4 logs, which share a layout.
+fn parse_crash_logger(contents: &str) -> Result<ImportedCrashLog> {
+    let mut log = ImportedCrashLog::default();
+
+    // First line: "Skyrim SSE v1.6.640" or "Fallout 4 v1.10.163"
+    let header = contents.lines().next().unwrap_or_default().trim();
+    let (game, version) = header
+        .rsplit_once(" v")
+        .ok_or_else(|| CtdError::Import("missing game version header".into()))?;
+    log.game_id = match game {
+        "Skyrim SSE" | "Skyrim Special Edition" => "skyrim-se",
+        "Fallout 4" => "fallout4",
+        other => return Err(CtdError::Import(format!("unsupported game: {}", other))),
+    }
+    .to_string();
+    log.game_version = version.to_string();
+
+    let mut section = "";
+    let mut frames = Vec::new();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+
+        // Unhandled exception "EXCEPTION_ACCESS_VIOLATION" at 0x7FF6D4B3C2A0 SkyrimSE.exe+02FC2A0
+        if let Some(rest) = trimmed.strip_prefix("Unhandled exception \"") {
+            if let Some((name, rest)) = rest.split_once('"') {
+                log.exception_code = Some(exception_code_from_name(name));
+                let mut parts = rest.trim_start_matches(" at ").split_whitespace();
+                log.exception_address = parts.next().map(str::to_string);
+                log.faulting_module = parts
+                    .next()
+                    .and_then(|m| m.split_once('+'))
+                    .map(|(module, _)| module.to_string());
+            }
+            continue;
+        }
+
+        // Section headers are unindented lines ending in ':'
+        if !line.starts_with(['\t', ' ']) && trimmed.ends_with(':') {
+            section = trimmed.trim_end_matches(':');
+            continue;
+        }
+
+        match section {
+            "SYSTEM SPECS" => {
+                if let Some(os) = trimmed.strip_prefix("OS: ") {
+                    log.os_version = Some(os.to_string());
+                }
+            }
+            "PROBABLE CALL STACK" => {
+                // [ 0] 0x7FF6D4B3C2A0      SkyrimSE.exe+02FC2A0	mov rax, [rcx]
+                if let Some((_, rest)) = trimmed.split_once(']') {
+                    let mut parts = rest.split_whitespace();
+                    if let (Some(address), Some(location)) = (parts.next(), parts.next()) {
+                        frames.push(format_frame(frames.len(), location, address));
+                    }
+                }
+            }
+            "PLUGINS" => {
+                // [00]     Skyrim.esm / [FE:000] ccExample.esl
+                if trimmed.starts_with('[')
+                    && let Some((_, name)) = trimmed.split_once(']')
+                {
+                    let name = name.trim();
+                    if !name.is_empty() {
+                        log.plugins.push(name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    log.stack_trace = frames.join("\n");
+    Ok(log)
+}
+
+/// Parses .NET Script Framework crash logs.
+fn parse_net_script_framework(contents: &str) -> Result<ImportedCrashLog> {
+    let mut log = ImportedCrashLog::default();
+    let mut block = "";
+    let mut in_block = false;
+    let mut frames = Vec::new();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+
+        // Unhandled native exception occurred at 0x7FF6D4B3C2A0 (SkyrimSE.exe+2FC2A0) on thread 1234!
+        if let Some(rest) = trimmed.strip_prefix("Unhandled native exception occurred at ") {
+            let mut parts = rest.split_whitespace();
+            log.exception_address = parts.next().map(str::to_string);
+            log.faulting_module = parts
+                .next()
+                .map(|l| l.trim_matches(['(', ')']))
+                .and_then(|l| l.split_once('+'))
+                .map(|(module, _)| module.to_string());
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix("ApplicationName: ") {
+            log.game_id = match name {
+                "SkyrimSE.exe" => "skyrim-se".to_string(),
+                other => return Err(CtdError::Import(format!("unsupported game: {}", other))),
+            };
+            continue;
+        }
+        if let Some(version) = trimmed.strip_prefix("ApplicationVersion: ") {
+            log.game_version = version.to_string();
+            continue;
+        }
+
+        match trimmed {
+            "{" => in_block = true,
+            "}" => {
+                in_block = false;
+                block = "";
+            }
+            "" => {}
+            _ if !in_block => block = trimmed,
+            _ if block == "Probable callstack" => {
+                // [0]   0x7FF6D4B3C2A0     (SkyrimSE.exe+2FC2A0)     BSTArray...
+                if let Some((_, rest)) = trimmed.split_once(']') {
+                    let mut parts = rest.split_whitespace();
+                    if let (Some(address), Some(location)) = (parts.next(), parts.next()) {
+                        let location = location.trim_matches(['(', ')']);
+                        frames.push(format_frame(frames.len(), location, address));
+                    }
+                }
+            }
+            _ if block.starts_with("Plugins") => {
+                if let Some((_, name)) = trimmed.split_once(']') {
+                    let name = name.trim();
+                    if !name.is_empty() {
+                        log.plugins.push(name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if log.game_id.is_empty() {
+        return Err(CtdError::Import("missing ApplicationName".into()));
+    }
+
+    log.stack_trace = frames.join("\n");
+    Ok(log)
+}
+
+/// Formats a frame as `[i] module+0xOFFSET (0xADDR)`, normalizing the offset.
+fn format_frame(index: usize, location: &str, address: &str) -> String {
+    let location = match location.split_once('+') {
+        Some((module, offset)) => match u64::from_str_radix(offset.trim_start_matches("0x"), 16) {
+            Ok(offset) => format!("{}+0x{:X}", module, offset),
+            Err(_) => location.to_string(),
+        },
+        None => location.to_string(),
+    };
+    format!("[{}] {} ({})", index, location, address)
+}
+
+/// Exception names Crash Logger and Buffout 4 print, with their codes.
+const EXCEPTION_NAMES: &[(&str, u32)] = &[
+    ("EXCEPTION_ACCESS_VIOLATION", 0xC0000005),
+    ("EXCEPTION_ARRAY_BOUNDS_EXCEEDED", 0xC000008C),
+    ("EXCEPTION_BREAKPOINT", 0x80000003),
+    ("EXCEPTION_DATATYPE_MISALIGNMENT", 0x80000002),
+    ("EXCEPTION_FLT_DIVIDE_BY_ZERO", 0xC000008E),
+    ("EXCEPTION_ILLEGAL_INSTRUCTION", 0xC000001D),
+    ("EXCEPTION_IN_PAGE_ERROR", 0xC0000006),
+    ("EXCEPTION_INT_DIVIDE_BY_ZERO", 0xC0000094),
+    ("EXCEPTION_PRIV_INSTRUCTION", 0xC0000096),
+    ("EXCEPTION_STACK_OVERFLOW", 0xC00000FD),
+];
+
+/// Returns the name of an exception code like `0xC0000005`, if known.
+pub(crate) fn exception_name(code: &str) -> Option<&'static str> {
+    let code = u32::from_str_radix(code.trim_start_matches("0x"), 16).ok()?;
+    EXCEPTION_NAMES
+        .iter()
+        .find(|(_, known)| *known == code)
+        .map(|(name, _)| *name)
+}
+
+/// Converts an exception name like `EXCEPTION_ACCESS_VIOLATION` to its hex code.
+///
+/// Unknown names are returned unchanged.
+fn exception_code_from_name(name: &str) -> String {
+    match EXCEPTION_NAMES.iter().find(|(known, _)| *known == name) {
+        Some((_, code)) => format!("0x{:08X}", code),
+        None => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRASH_LOGGER_LOG: &str = "Skyrim SSE v1.6.640
+CrashLoggerSSE v1-12-1 Jan 17 2023 15:27:37
+
+Unhandled exception \"EXCEPTION_ACCESS_VIOLATION\" at 0x7FF6D4B3C2A0 SkyrimSE.exe+02FC2A0\tmov rax, [rcx]
+
+SYSTEM SPECS:
+\tOS: Microsoft Windows 11 Pro v10.0.22621
+\tCPU: AuthenticAMD AMD Ryzen 7 5800X3D 8-Core Processor
+
+PROBABLE CALL STACK:
+\t[ 0] 0x7FF6D4B3C2A0      SkyrimSE.exe+02FC2A0\tmov rax, [rcx]
+\t[ 1] 0x7FFB12345678 SomeMod.dll+0012345
+
+REGISTERS:
+\tRAX 0x0 (size_t) [0]
+
+PLUGINS:
+\tLight: 1\tRegular: 2\tTotal: 3
+\t[00]     Skyrim.esm
+\t[01]     Update.esm
+\t[FE:000] ccBGSSSE002-ExoticArrows.esl
+";
+
+    const BUFFOUT_LOG: &str = "Fallout 4 v1.10.163
+Buffout 4 v1.26.2
+
+Unhandled exception \"EXCEPTION_STACK_OVERFLOW\" at 0x7FF7A0A1B2C3 Fallout4.exe+1B2C3C3
+
+PROBABLE CALL STACK:
+\t[ 0] 0x7FF7A0A1B2C3 Fallout4.exe+1B2C3C3
+
+PLUGINS:
+\t[00]     Fallout4.esm
+";
+
+    const NSF_LOG: &str = "Unhandled native exception occurred at 0x7FF6D4B3C2A0 (SkyrimSE.exe+2FC2A0) on thread 1234!
+
+FrameworkName: NetScriptFramework
+FrameworkVersion: 15
+ApplicationName: SkyrimSE.exe
+ApplicationVersion: 1.5.97.0
+
+Probable callstack
+{
+\t[0]   0x7FF6D4B3C2A0     (SkyrimSE.exe+2FC2A0)     BSTArray::Get
+\t[1]   0x7FFB12345678     (SomeMod.dll+12345)
+}
+
+Plugins (2)
+{
+\t[00] Skyrim.esm
+\t[01] Update.esm
+}
+";
+
+    #[test]
+    fn detects_formats() {
+        assert_eq!(
+            detect_format(CRASH_LOGGER_LOG),
+            Some(CrashLogFormat::CrashLogger)
+        );
+        assert_eq!(detect_format(BUFFOUT_LOG), Some(CrashLogFormat::Buffout4));
+        assert_eq!(
+            detect_format(NSF_LOG),
+            Some(CrashLogFormat::NetScriptFramework)
+        );
+        assert_eq!(detect_format("hello"), None);
+    }
+
+    #[test]
+    fn parses_crash_logger() {
+        let log = parse_crash_log(CRASH_LOGGER_LOG).unwrap();
+        assert_eq!(log.game_id, "skyrim-se");
+        assert_eq!(log.game_version, "1.6.640");
+        assert_eq!(log.exception_code.as_deref(), Some("0xC0000005"));
+        assert_eq!(log.exception_address.as_deref(), Some("0x7FF6D4B3C2A0"));
+        assert_eq!(log.faulting_module.as_deref(), Some("SkyrimSE.exe"));
+        assert_eq!(
+            log.os_version.as_deref(),
+            Some("Microsoft Windows 11 Pro v10.0.22621")
+        );
+        assert_eq!(
+            log.stack_trace,
+            "[0] SkyrimSE.exe+0x2FC2A0 (0x7FF6D4B3C2A0)\n[1] SomeMod.dll+0x12345 (0x7FFB12345678)"
+        );
+        assert_eq!(
+            log.plugins,
+            vec!["Skyrim.esm", "Update.esm", "ccBGSSSE002-ExoticArrows.esl"]
+        );
+    }
+
+    #[test]
+    fn parses_buffout4() {
+        let log = parse_crash_log(BUFFOUT_LOG).unwrap();
+        assert_eq!(log.game_id, "fallout4");
+        assert_eq!(log.game_version, "1.10.163");
+        assert_eq!(log.exception_code.as_deref(), Some("0xC00000FD"));
+        assert_eq!(log.plugins, vec!["Fallout4.esm"]);
+    }
+
+    #[test]
+    fn parses_net_script_framework() {
+        let log = parse_crash_log(NSF_LOG).unwrap();
+        assert_eq!(log.game_id, "skyrim-se");
+        assert_eq!(log.game_version, "1.5.97.0");
+        assert_eq!(log.exception_address.as_deref(), Some("0x7FF6D4B3C2A0"));
+        assert_eq!(log.faulting_module.as_deref(), Some("SkyrimSE.exe"));
+        assert!(log.exception_code.is_none());
+        assert_eq!(
+            log.stack_trace,
+            "[0] SkyrimSE.exe+0x2FC2A0 (0x7FF6D4B3C2A0)\n[1] SomeMod.dll+0x12345 (0x7FFB12345678)"
+        );
+        assert_eq!(log.plugins, vec!["Skyrim.esm", "Update.esm"]);
+    }
+
+    #[test]
+    fn imported_log_builds_report() {
+        let report = parse_crash_log(CRASH_LOGGER_LOG)
+            .unwrap()
+            .into_builder()
+            .crashed_at(1_700_000_000_000)
+            .build()
+            .unwrap();
+
+        assert_eq!(report.game_id, "skyrim-se");
+        assert_eq!(report.plugin_count, 3);
+        assert_eq!(report.schema_version, 2);
+        assert!(!report.load_order_json.contains("fileHash"));
+    }
+
+    #[test]
+    fn import_file_uses_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crash-2023.log");
+        fs::write(&path, BUFFOUT_LOG).unwrap();
+
+        let report = import_file(&path).unwrap();
+        assert_eq!(report.game_id, "fallout4");
+        assert!(report.crashed_at > 0);
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        assert!(matches!(
+            parse_crash_log("not a crash log"),
+            Err(CtdError::Import(_))
+        ));
+    }
+}
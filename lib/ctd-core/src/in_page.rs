@@ -0,0 +1,130 @@
+//! Diagnosis of in-page errors.
+//!
+//! `IN_PAGE_ERROR` (0xC0000006) means Windows couldn't read a page of a
+//! mapped file back in: the executable, a DLL, or an archive the game maps,
+//! such as a BSA or BA2. Whatever code touched the page did nothing wrong.
+//! The exception's third parameter is the NTSTATUS of the failed read,
+//! which tells a failing drive (`STATUS_DEVICE_DATA_ERROR`,
+//! `STATUS_CRC_ERROR`) from a disconnected one or an exhausted page file.
+//! [`diagnose`] names the status and the file that was being read, and
+//! [`category`] marks storage failures `disk-io` and an exhausted page file
+//! `oom`, so the report says the drive is failing instead of blaming a mod.
+
+use crate::crash_report::{CrashCategory, InPageError};
+use crate::memory_map;
+
+/// The exception code for a page that couldn't be read in.
+pub const IN_PAGE_ERROR: u32 = 0xC0000006;
+
+const DISK: Option<CrashCategory> = Some(CrashCategory::DiskIo);
+const MEMORY: Option<CrashCategory> = Some(CrashCategory::OutOfMemory);
+
+/// Statuses of failed page reads, by NTSTATUS value, and the kind of crash
+/// each means when it is the storage device or memory at fault.
+const STATUSES: &[(u32, &str, Option<CrashCategory>)] = &[
+    (0xC000000E, "STATUS_NO_SUCH_DEVICE", DISK),
+    (0xC000003F, "STATUS_CRC_ERROR", DISK),
+    (0xC000009A, "STATUS_INSUFFICIENT_RESOURCES", MEMORY),
+    (0xC000009C, "STATUS_DEVICE_DATA_ERROR", DISK),
+    (0xC000009D, "STATUS_DEVICE_NOT_CONNECTED", DISK),
+    (0xC00000B5, "STATUS_IO_TIMEOUT", DISK),
+    (0xC00000C4, "STATUS_UNEXPECTED_NETWORK_ERROR", None),
+    (0xC0000102, "STATUS_FILE_CORRUPT_ERROR", DISK),
+    (0xC000012D, "STATUS_COMMITMENT_LIMIT", MEMORY),
+    (0xC0000185, "STATUS_IO_DEVICE_ERROR", DISK),
+    (0xC000020C, "STATUS_CONNECTION_DISCONNECTED", None),
+    (0xC000026E, "STATUS_VOLUME_DISMOUNTED", DISK),
+    (0xC0000467, "STATUS_FILE_NOT_AVAILABLE", None),
+    (0xC0000483, "STATUS_DEVICE_HARDWARE_ERROR", DISK),
+];
+
+/// Describes the failed read behind an `IN_PAGE_ERROR`.
+///
+/// `information` is the exception record's `ExceptionInformation`: the
+/// access type, the address that couldn't be paged in, and the NTSTATUS.
+/// Returns `None` for other exceptions and records without the status.
+pub fn diagnose(code: u32, information: &[u64]) -> Option<InPageError> {
+    if code != IN_PAGE_ERROR {
+        return None;
+    }
+    let (&address, &status) = (information.get(1)?, information.get(2)?);
+    Some(describe(
+        address,
+        status as u32,
+        memory_map::mapped_file(address).map(|path| path.display().to_string()),
+    ))
+}
+
+/// Builds the report section from the exception's parameters and the file
+/// mapped at the address.
+pub fn describe(address: u64, status: u32, file: Option<String>) -> InPageError {
+    InPageError {
+        status: format!("0x{:08X}", status),
+        status_name: lookup(status).map(|(name, _)| name.to_string()),
+        address: format!("0x{:X}", address),
+        file,
+    }
+}
+
+/// Returns `disk-io` if the read failed because of the storage device, or
+/// `oom` if there was no memory to read the page into; network failures
+/// have no category.
+pub fn category(error: &InPageError) -> Option<CrashCategory> {
+    let status = u32::from_str_radix(error.status.trim_start_matches("0x"), 16).ok()?;
+    lookup(status)?.1
+}
+
+/// Returns a status's name and the kind of crash it means.
+fn lookup(status: u32) -> Option<(&'static str, Option<CrashCategory>)> {
+    STATUSES
+        .iter()
+        .find(|(value, _, _)| *value == status)
+        .map(|&(_, name, category)| (name, category))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_status_and_file() {
+        let error = describe(
+            0x1F2A0000,
+            0xC000009C,
+            Some(r"D:\Steam\Skyrim\Data\Skyrim - Textures0.bsa".into()),
+        );
+
+        assert_eq!(error.status, "0xC000009C");
+        assert_eq!(
+            error.status_name.as_deref(),
+            Some("STATUS_DEVICE_DATA_ERROR")
+        );
+        assert_eq!(error.address, "0x1F2A0000");
+        assert_eq!(category(&error), Some(CrashCategory::DiskIo));
+    }
+
+    #[test]
+    fn memory_pressure_is_not_a_disk_error() {
+        let error = describe(0x1F2A0000, 0xC000009A, None);
+        assert_eq!(
+            error.status_name.as_deref(),
+            Some("STATUS_INSUFFICIENT_RESOURCES")
+        );
+        assert_eq!(category(&error), Some(CrashCategory::OutOfMemory));
+        assert_eq!(category(&describe(0x1F2A0000, 0xC00000C4, None)), None);
+
+        let unknown = describe(0x1F2A0000, 0xC0001234, None);
+        assert_eq!(unknown.status_name, None);
+        assert_eq!(category(&unknown), None);
+    }
+
+    #[test]
+    fn only_in_page_errors_are_diagnosed() {
+        assert_eq!(diagnose(0xC0000005, &[0, 0x1000, 0xC000009C]), None);
+        assert_eq!(diagnose(IN_PAGE_ERROR, &[0, 0x1000]), None);
+
+        let error = diagnose(IN_PAGE_ERROR, &[0, 0x1000, 0xC0000185]).unwrap();
+        assert_eq!(error.status_name.as_deref(), Some("STATUS_IO_DEVICE_ERROR"));
+        assert_eq!(error.file, None);
+    }
+}
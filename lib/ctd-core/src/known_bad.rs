@@ -0,0 +1,245 @@
+//! Third-party DLLs known to crash games.
+//!
+//! Some crashes have nothing to do with the load order: an X3DAudio
+//! replacement left in the game folder by an old audio fix, a DLSS DLL
+//! swapped in years ago, or an allocator replacement fighting the game's
+//! heap. [`scan`] checks the modules loaded in the process against a list
+//! of such DLLs, and the matches go in the report's `knownBadModules`
+//! section with the reason each is flagged.
+//!
+//! The list bundled with the client is extended by the server, which can
+//! send rules with its capabilities (`knownBadModules`), and by
+//! `[known_bad_modules] rules` in the config. A later rule for the same DLL
+//! replaces an earlier one, so the server can correct a bundled rule without
+//! a client release. `[known_bad_modules] enabled = false` turns the scan
+//! off.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api_client::{self, is_newer};
+use crate::config::Config;
+use crate::crash_report::{KnownBadModule, MAX_KNOWN_BAD_MODULES};
+use crate::memory_map::{self, LoadedModule};
+use crate::version::get_dll_version;
+
+/// Rules shipped with the client: module, version it was fixed in, whether
+/// only copies outside the Windows directory count, and the reason.
+const BUNDLED_RULES: &[(&str, Option<&str>, bool, &str)] = &[
+    (
+        "x3daudio1_7.dll",
+        None,
+        true,
+        "An X3DAudio replacement in the game folder, usually left by an old audio fix. \
+         These builds crash on current Windows when sounds play; delete it so the game uses \
+         the DirectX runtime's copy.",
+    ),
+    (
+        "nvngx_dlss.dll",
+        Some("2.0"),
+        false,
+        "A DLSS 1.x DLL, which current NVIDIA drivers no longer support well. Replace it with \
+         the version the game or upscaler mod ships, or remove it.",
+    ),
+    (
+        "tbbmalloc.dll",
+        Some("2021.0"),
+        false,
+        "A legacy Intel TBB memory allocator from an old heap replacement mod. Memory freed by a \
+         module that didn't allocate it corrupts the heap; update the mod or keep a single, \
+         current memory manager.",
+    ),
+];
+
+/// A DLL that is known to crash games, and when it is flagged.
+///
+/// The server sends rules in camelCase like the rest of its JSON; the
+/// config file spells the fields in snake_case like its other keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BadModuleRule {
+    /// File name, matched case-insensitively (e.g., "tbbmalloc.dll").
+    pub module: String,
+    /// Only versions older than this are flagged; every version if unset.
+    #[serde(
+        default,
+        alias = "below_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub below_version: Option<String>,
+    /// Only copies loaded from outside the Windows directory are flagged,
+    /// for DLLs that Windows or a redistributable also installs.
+    #[serde(default, alias = "outside_windows")]
+    pub outside_windows: bool,
+    /// Why the DLL crashes games and what to do about it.
+    pub reason: String,
+}
+
+/// Returns the rules shipped with the client.
+pub fn bundled_rules() -> Vec<BadModuleRule> {
+    BUNDLED_RULES
+        .iter()
+        .map(
+            |&(module, below_version, outside_windows, reason)| BadModuleRule {
+                module: module.to_string(),
+                below_version: below_version.map(str::to_string),
+                outside_windows,
+                reason: reason.to_string(),
+            },
+        )
+        .collect()
+}
+
+/// Returns the loaded modules that match a rule: the bundled rules, then
+/// the server's, then the config's.
+///
+/// Empty when `[known_bad_modules] enabled` is off.
+pub fn scan() -> Vec<KnownBadModule> {
+    let config = Config::load().unwrap_or_default().known_bad_modules;
+    if !config.enabled {
+        return Vec::new();
+    }
+
+    let mut rules = bundled_rules();
+    if let Some(capabilities) = api_client::cached_capabilities() {
+        rules.extend(capabilities.known_bad_modules.iter().cloned());
+    }
+    rules.extend(config.rules);
+
+    let windows_dir = std::env::var_os("SystemRoot").map(std::path::PathBuf::from);
+    find(
+        &memory_map::loaded_modules(),
+        &rules,
+        windows_dir.as_deref(),
+        &|path| get_dll_version(path).ok(),
+    )
+}
+
+/// Matches `modules` against `rules`, where a later rule for a module
+/// replaces an earlier one. `version_of` reads a DLL's file version.
+fn find(
+    modules: &[LoadedModule],
+    rules: &[BadModuleRule],
+    windows_dir: Option<&Path>,
+    version_of: &dyn Fn(&Path) -> Option<String>,
+) -> Vec<KnownBadModule> {
+    modules
+        .iter()
+        .filter_map(|module| {
+            let rule = rules
+                .iter()
+                .rev()
+                .find(|rule| rule.module.eq_ignore_ascii_case(&module.name))?;
+            if rule.outside_windows && windows_dir.is_some_and(|dir| is_under(&module.path, dir)) {
+                return None;
+            }
+
+            let version = version_of(&module.path);
+            if let Some(ref fixed) = rule.below_version {
+                // A version rule can't judge a DLL without a version resource
+                if !is_newer(fixed, version.as_deref()?) {
+                    return None;
+                }
+            }
+            Some(KnownBadModule {
+                name: module.name.clone(),
+                version,
+                reason: rule.reason.clone(),
+            })
+        })
+        .take(MAX_KNOWN_BAD_MODULES)
+        .collect()
+}
+
+/// Returns true if `path` is inside `dir`, ignoring case as Windows does.
+fn is_under(path: &Path, dir: &Path) -> bool {
+    let path = path.to_string_lossy().to_ascii_lowercase();
+    let dir = dir.to_string_lossy().to_ascii_lowercase();
+    path.strip_prefix(dir.trim_end_matches(['\\', '/']))
+        .is_some_and(|rest| rest.starts_with(['\\', '/']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn module(path: &str) -> LoadedModule {
+        LoadedModule {
+            name: path.rsplit('\\').next().unwrap().to_string(),
+            path: PathBuf::from(path),
+            base: 0x1_8000_0000,
+            size: 0x10000,
+        }
+    }
+
+    fn version(path: &Path) -> Option<String> {
+        let path = path.to_string_lossy();
+        if path.ends_with("nvngx_dlss.dll") {
+            Some("1.0.13.0".into())
+        } else if path.contains("tbbmalloc") {
+            Some("2021.9.0.0".into())
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn flags_bundled_modules() {
+        let modules = [
+            module(r"C:\Games\Skyrim\X3DAudio1_7.dll"),
+            module(r"C:\WINDOWS\SYSTEM32\X3DAudio1_7.dll"),
+            module(r"C:\Games\Skyrim\nvngx_dlss.dll"),
+            module(r"C:\Games\Skyrim\tbbmalloc.dll"),
+            module(r"C:\Games\Skyrim\SkyrimSE.exe"),
+        ];
+        let found = find(
+            &modules,
+            &bundled_rules(),
+            Some(Path::new(r"C:\Windows")),
+            &version,
+        );
+
+        let names: Vec<&str> = found.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["X3DAudio1_7.dll", "nvngx_dlss.dll"]);
+        assert_eq!(found[1].version.as_deref(), Some("1.0.13.0"));
+        assert!(found[1].reason.starts_with("A DLSS 1.x DLL"));
+    }
+
+    #[test]
+    fn later_rules_replace_earlier_ones() {
+        let mut rules = bundled_rules();
+        rules.push(BadModuleRule {
+            module: "TBBMALLOC.DLL".into(),
+            below_version: None,
+            outside_windows: false,
+            reason: "Conflicts with Engine Fixes.".into(),
+        });
+        let found = find(
+            &[module(r"C:\Games\Skyrim\tbbmalloc.dll")],
+            &rules,
+            None,
+            &version,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].reason, "Conflicts with Engine Fixes.");
+    }
+
+    #[test]
+    fn parses_server_rules() {
+        let json = r#"{"module":"example.dll","belowVersion":"1.2","reason":"Crashes on load."}"#;
+        let rule: BadModuleRule = serde_json::from_str(json).unwrap();
+        assert_eq!(rule.below_version.as_deref(), Some("1.2"));
+        assert!(!rule.outside_windows);
+
+        assert!(is_under(
+            Path::new(r"C:\Windows\System32\a.dll"),
+            Path::new(r"c:\windows\")
+        ));
+        assert!(!is_under(
+            Path::new(r"C:\WindowsApps\a.dll"),
+            Path::new(r"C:\Windows")
+        ));
+    }
+}
@@ -0,0 +1,171 @@
+//! The most recently submitted report for each game.
+//!
+//! Crash handlers record the submission response here so that, on the next
+//! launch, an in-game prompt can ask what the user was doing and attach the
+//! answer with [`append_note_to_last`].
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::api_client::ApiClient;
+use crate::crash_report::CrashReportResponse;
+use crate::{CtdError, Result};
+
+/// A submitted report that notes can still be attached to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastReport {
+    /// The report ID (ULID).
+    pub id: String,
+    /// Share token returned on submission; authorizes appending notes.
+    pub share_token: String,
+    /// Game the report was submitted for.
+    pub game_id: String,
+    /// Unix timestamp (milliseconds) when the report was submitted.
+    pub submitted_at: u64,
+}
+
+impl LastReport {
+    /// Records a submission response for a game.
+    pub fn new(game_id: impl Into<String>, response: &CrashReportResponse) -> Self {
+        let submitted_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        Self {
+            id: response.id.clone(),
+            share_token: response.share_token.clone(),
+            game_id: game_id.into(),
+            submitted_at,
+        }
+    }
+
+    /// Returns the default directory (`<local data dir>/ctd/last-report`).
+    pub fn default_dir() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("ctd")
+            .join("last-report")
+    }
+
+    /// Saves this report as the game's last report in the default directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> io::Result<PathBuf> {
+        self.save_in(&Self::default_dir())
+    }
+
+    /// Saves this report as the game's last report in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save_in(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = file_path(dir, &self.game_id);
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Loads a game's last report from the default directory.
+    pub fn load(game_id: &str) -> Option<Self> {
+        Self::load_from(&Self::default_dir(), game_id)
+    }
+
+    /// Loads a game's last report from `dir`.
+    ///
+    /// Returns `None` if there is none or the file is unreadable.
+    pub fn load_from(dir: &Path, game_id: &str) -> Option<Self> {
+        let path = file_path(dir, game_id);
+        let json = fs::read(&path).ok()?;
+        match serde_json::from_slice(&json) {
+            Ok(report) => Some(report),
+            Err(e) => {
+                debug!("Ignoring unreadable {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Appends a note to a game's last report, blocking until it is sent.
+///
+/// Intended for FFI hooks behind an in-game prompt. Uses the share token
+/// saved with the report, so no API key is needed. Returns the report ID.
+///
+/// # Errors
+///
+/// Returns `CtdError::Validation` if the game has no last report or the
+/// note is invalid, or `CtdError::ApiRequest` if the request fails.
+pub fn append_note_to_last(game_id: &str, text: &str) -> Result<String> {
+    let last = LastReport::load(game_id)
+        .ok_or_else(|| CtdError::Validation(format!("No submitted report for {}", game_id)))?;
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CtdError::ApiRequest(format!("Failed to create runtime: {}", e)))?;
+
+    rt.block_on(async {
+        let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
+        client
+            .append_note_with_token(&last.id, &last.share_token, text)
+            .await
+    })?;
+    Ok(last.id)
+}
+
+/// Returns the file holding a game's last report.
+fn file_path(dir: &Path, game_id: &str) -> PathBuf {
+    // Game IDs are slugs, but keep anything else from escaping the directory
+    let name: String = game_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{}.json", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response() -> CrashReportResponse {
+        CrashReportResponse {
+            id: "01ABC".into(),
+            share_token: "token123".into(),
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let last = LastReport::new("skyrim-se", &response());
+        last.save_in(dir.path()).unwrap();
+
+        assert_eq!(LastReport::load_from(dir.path(), "skyrim-se"), Some(last));
+        assert_eq!(LastReport::load_from(dir.path(), "fallout4"), None);
+    }
+
+    #[test]
+    fn game_id_cannot_escape_dir() {
+        let dir = Path::new("/data");
+        assert_eq!(
+            file_path(dir, "../skyrim"),
+            Path::new("/data/___skyrim.json")
+        );
+    }
+}
@@ -1,18 +1,105 @@
 //! CTD Core Library
 //!
 //! This crate provides core functionality for the CTD (Crash To Desktop) project:
-//! - Configuration management
-//! - Load order parsing and management
+//! - Configuration management, with hot reload of `ctd.toml`
+//! - Load order parsing and management, with plugin header (masters) parsing
+//! - Mod fingerprinting in the background, ahead of a crash
+//! - Attribution of form IDs to the plugins that define them
+//! - Mod Organizer 2 detection and mod metadata
+//! - Wabbajack list and Nexus Collection detection, or a modpack named in the config
+//! - Script extender DLL plugin scanning
+//! - ENB and ReShade detection
+//! - Save game plugin lists compared against the load order
 //! - Crash report generation and serialization
+//! - Crash Logger-compatible text logs for community analyzers
+//! - Local history of generated reports, submitted or not
+//! - Reentrancy guard and fallback report for the crash handlers
+//! - Preallocated buffers for reporting a crash without allocating
+//! - Attribution of the faulting module to the mod that shipped it
 //! - API client for backend communication
+//! - Sentry envelopes as an alternative submission backend
+//! - Discord webhook notifications after each submission
+//! - Rolling log files for the game plugins
+//! - Windows Event Log entries for captured crashes
+//! - Tails of script and loader logs attached to reports
+//! - Coexistence with other crash loggers (Crash Logger, Buffout 4, Trainwreck)
+//! - Import of community crash logs (Crash Logger, Buffout 4, .NET Script Framework)
+//! - Freeze detection with all-thread stack capture
+//! - Counts of non-fatal exceptions for the next crash report
+//! - Nested and recent first-chance exceptions behind a crash
+//! - Failed disk reads behind `IN_PAGE_ERROR` crashes, and the file being read
+//! - GPU driver resets and WHEA hardware errors logged before a crash
+//! - Memory regions and neighbouring modules for addresses outside every module
+//! - Detours on the faulting module's exported functions
+//! - Class names of objects the crashed thread pointed at, from MSVC RTTI
+//! - Crash categories and plain-language triage hints
+//! - Process uptime and time since the last save
+//! - Frame time percentiles over the seconds before a crash
+//! - Resolution, window mode, HDR, active GPU, and upscaler DLLs
+//! - Loaded DLLs known to crash games, from a bundled and server-updated list
+//! - A C API (`ctd.h`) for other mods to add breadcrumbs, context, and handled errors
+//! - Session summaries on clean exit, for crash rates per mod
+//! - Anonymous installation and session IDs
+//! - Windows Error Reporting dumps: enabling `LocalDumps` and reading `.dmp` files
 
+pub mod abi;
 pub mod api_client;
+pub mod blame;
+pub mod coexistence;
 pub mod config;
+pub mod config_watcher;
+pub mod crash_arena;
+pub mod crash_guard;
+pub mod crash_hash;
+pub mod crash_log;
 pub mod crash_report;
+pub mod demangle;
+pub mod detours;
+mod dpapi;
+pub mod dwarf;
+pub mod event_log;
+pub mod exception_chain;
+pub mod exception_tally;
 pub mod file_hash;
+pub mod fingerprint_cache;
+pub mod formid;
+pub mod frame_times;
+pub mod graphics;
+pub mod graphics_injectors;
+pub mod hardware_events;
+pub mod history;
+pub mod identity;
+pub mod import;
+pub mod in_page;
+pub mod known_bad;
+pub mod last_report;
 pub mod load_order;
+pub mod log_tail;
+pub mod logging;
+pub mod memory_map;
+pub mod mo2;
+pub mod modpack;
+pub mod native_plugins;
+pub mod notify;
+pub mod pe;
+pub mod plugin_header;
+pub mod rtti;
+pub mod save_game;
+pub mod sentry;
+pub mod session;
+pub mod spool;
+pub mod startup;
+pub mod submit;
 pub mod symbols;
+pub mod test_crash;
+pub mod throttle;
+pub mod transport;
+pub mod triage;
+pub mod uptime;
 pub mod version;
+pub mod watchdog;
+#[cfg(feature = "wer")]
+pub mod wer;
 
 use thiserror::Error;
 
@@ -42,6 +129,18 @@ pub enum CtdError {
     /// Symbol resolution failed.
     #[error("Symbol resolution error: {0}")]
     Symbol(String),
+
+    /// Failed to import an external crash log.
+    #[error("Failed to import crash log: {0}")]
+    Import(String),
+
+    /// Failed to read or write the pending report spool.
+    #[error("Spool error: {0}")]
+    Spool(String),
+
+    /// Failed to write the local report history.
+    #[error("History error: {0}")]
+    History(String),
 }
 
 /// A specialized Result type for CTD operations.
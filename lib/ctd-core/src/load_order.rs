@@ -7,8 +7,31 @@
 //!
 //! - **v1**: Uses `LoadOrderEntry` with name/enabled/index only
 //! - **v2**: Uses `ModEntry` with file_hash/file_size/version for pattern detection
+//!
+//! Both carry an optional `isLight` flag for ESL-flagged plugins, which load
+//! into the shared `FE` slot and so change how form IDs map to plugins.
+//! Servers that predate it ignore the extra key.
+//!
+//! `ModEntry` also lists a plugin's masters (read from its TES4 header by
+//! [`crate::plugin_header`]), so a report can name the dependency that was
+//! missing when the game crashed on load.
+//!
+//! When the game runs under a mod manager, entries also carry the name and
+//! Nexus ID of the mod that installed them (see [`crate::mo2`]).
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::file_hash::{CachedHasher, UNHASHED, compute_file_hash_with};
+
+/// How long [`ModList::fingerprint_parallel`] hashes by default before
+/// giving up on the remaining files.
+pub const DEFAULT_HASH_BUDGET: Duration = Duration::from_millis(500);
 
 // ============================================================================
 // Schema v2: ModEntry with fingerprinting data
@@ -24,7 +47,10 @@ pub struct ModEntry {
     /// Mod/plugin name (e.g., "SkyUI_SE.esp", "[RED4ext] ArchiveXL")
     pub name: String,
 
-    /// SHA256 fingerprint (16 hex chars from file_hash module)
+    /// Fingerprint from the file_hash module (16 hex chars, or 8 for CRC32).
+    /// Empty when the file was never seen, as for imported crash logs, and
+    /// then left out so the entry isn't matched by hash.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub file_hash: String,
 
     /// File size in bytes
@@ -41,6 +67,22 @@ pub struct ModEntry {
     /// Whether this mod is enabled
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enabled: Option<bool>,
+
+    /// Whether this is a light (ESL-flagged) plugin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_light: Option<bool>,
+
+    /// Master files this plugin depends on (from its TES4 header)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masters: Vec<String>,
+
+    /// Name of the mod manager mod that installed this file (e.g., MO2's "SkyUI")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_name: Option<String>,
+
+    /// Nexus Mods ID of that mod
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nexus_id: Option<u32>,
 }
 
 impl ModEntry {
@@ -53,6 +95,10 @@ impl ModEntry {
             version: None,
             index: None,
             enabled: None,
+            is_light: None,
+            masters: Vec::new(),
+            mod_name: None,
+            nexus_id: None,
         }
     }
 
@@ -73,6 +119,53 @@ impl ModEntry {
         self.enabled = Some(enabled);
         self
     }
+
+    /// Builder method to mark the entry as a light (ESL) plugin or not.
+    pub fn with_light(mut self, is_light: bool) -> Self {
+        self.is_light = Some(is_light);
+        self
+    }
+
+    /// Builder method to add the plugin's masters.
+    pub fn with_masters(mut self, masters: Vec<String>) -> Self {
+        self.masters = masters;
+        self
+    }
+
+    /// Builder method to add the installing mod's name.
+    pub fn with_mod_name(mut self, mod_name: impl Into<String>) -> Self {
+        self.mod_name = Some(mod_name.into());
+        self
+    }
+
+    /// Builder method to add the Nexus Mods ID.
+    pub fn with_nexus_id(mut self, nexus_id: u32) -> Self {
+        self.nexus_id = Some(nexus_id);
+        self
+    }
+
+    /// Returns a human-readable name, e.g. "SkyUI 5.2SE (Nexus 12604)".
+    ///
+    /// Falls back to the file name when the installing mod is unknown.
+    pub fn display_name(&self) -> String {
+        match &self.mod_name {
+            Some(mod_name) => format_mod_name(mod_name, self.version.as_deref(), self.nexus_id),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Formats a mod as "Name Version (Nexus ID)", omitting unknown parts.
+pub(crate) fn format_mod_name(name: &str, version: Option<&str>, nexus_id: Option<u32>) -> String {
+    let mut formatted = name.to_string();
+    if let Some(version) = version {
+        formatted.push(' ');
+        formatted.push_str(version);
+    }
+    if let Some(id) = nexus_id {
+        formatted.push_str(&format!(" (Nexus {})", id));
+    }
+    formatted
 }
 
 /// Collection of mod entries with fingerprint data (schema v2).
@@ -111,6 +204,101 @@ impl ModList {
         self.0.iter()
     }
 
+    /// Fingerprints `paths` across all cores, one entry per path in order.
+    ///
+    /// Entries are named after the file and indexed by position. Hashes
+    /// come from `hasher` when the file is unchanged; the rest are computed
+    /// in parallel until `budget` runs out. Files left over, and files that
+    /// can't be read, get [`UNHASHED`] so the list is still complete. New
+    /// hashes are added to `hasher`; the caller saves it.
+    pub fn fingerprint_parallel(
+        paths: &[PathBuf],
+        budget: Duration,
+        hasher: &mut CachedHasher,
+    ) -> Self {
+        let deadline = Instant::now() + budget;
+        let metadata: Vec<Option<fs::Metadata>> =
+            paths.iter().map(|path| fs::metadata(path).ok()).collect();
+
+        let cache = &*hasher;
+        let algorithm = cache.algorithm();
+        let hashes: Vec<Option<String>> = paths
+            .par_iter()
+            .zip(&metadata)
+            .map(|(path, metadata)| {
+                let metadata = metadata.as_ref()?;
+                if let Some(hash) = cache.lookup(path, metadata) {
+                    return Some(hash);
+                }
+                if Instant::now() >= deadline {
+                    return None;
+                }
+                compute_file_hash_with(path, algorithm)
+                    .ok()
+                    .filter(|&(_, size)| size == metadata.len())
+                    .map(|(hash, _)| hash)
+            })
+            .collect();
+
+        let mut unhashed = 0;
+        let list: Self = paths
+            .iter()
+            .zip(metadata)
+            .zip(hashes)
+            .enumerate()
+            .map(|(index, ((path, metadata), hash))| {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let size = metadata.as_ref().map_or(0, fs::Metadata::len);
+                let hash = match (hash, metadata) {
+                    (Some(hash), Some(metadata)) => {
+                        hasher.record(path, &metadata, &hash);
+                        hash
+                    }
+                    (_, metadata) => {
+                        unhashed += usize::from(metadata.is_some());
+                        UNHASHED.to_string()
+                    }
+                };
+                ModEntry::new(name, hash, size).with_index(index as u32)
+            })
+            .collect();
+
+        if unhashed > 0 {
+            info!(
+                "{} of {} files left unhashed after {} ms",
+                unhashed,
+                paths.len(),
+                budget.as_millis()
+            );
+        }
+        list
+    }
+
+    /// Returns `(plugin, master)` pairs for masters that are missing or disabled.
+    ///
+    /// Names are compared case-insensitively, as the game does.
+    pub fn missing_masters(&self) -> Vec<(&str, &str)> {
+        let loaded: std::collections::HashSet<String> = self
+            .iter()
+            .filter(|entry| entry.enabled != Some(false))
+            .map(|entry| entry.name.to_lowercase())
+            .collect();
+
+        self.iter()
+            .filter(|entry| entry.enabled != Some(false))
+            .flat_map(|entry| {
+                entry
+                    .masters
+                    .iter()
+                    .filter(|master| !loaded.contains(&master.to_lowercase()))
+                    .map(move |master| (entry.name.as_str(), master.as_str()))
+            })
+            .collect()
+    }
+
     /// Serializes to JSON string for the API's `loadOrderJson` field.
     pub fn to_json(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(&self.0)
@@ -121,6 +309,21 @@ impl ModList {
         let entries: Vec<ModEntry> = serde_json::from_str(json)?;
         Ok(Self(entries))
     }
+
+    /// Converts to a v1 load order for servers that predate schema v2.
+    ///
+    /// Hashes, sizes, and versions are dropped; name, enabled state,
+    /// index, and light flag are kept.
+    pub fn to_load_order(&self) -> LoadOrder {
+        self.iter()
+            .map(|entry| LoadOrderEntry {
+                name: entry.name.clone(),
+                enabled: entry.enabled,
+                index: entry.index,
+                is_light: entry.is_light,
+            })
+            .collect()
+    }
 }
 
 impl IntoIterator for ModList {
@@ -157,6 +360,7 @@ impl FromIterator<ModEntry> for ModList {
 /// - `name`: required string
 /// - `enabled`: optional boolean
 /// - `index`: optional integer
+/// - `isLight`: optional boolean
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LoadOrderEntry {
     /// The name of the mod/plugin file (e.g., "SkyUI_SE.esp").
@@ -171,6 +375,11 @@ pub struct LoadOrderEntry {
     /// are ordered implicitly by file position.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index: Option<u32>,
+
+    /// Whether this is a light (ESL-flagged) plugin. Optional because
+    /// only some games and formats know.
+    #[serde(rename = "isLight", skip_serializing_if = "Option::is_none")]
+    pub is_light: Option<bool>,
 }
 
 impl LoadOrderEntry {
@@ -180,6 +389,7 @@ impl LoadOrderEntry {
             name: name.into(),
             enabled: None,
             index: None,
+            is_light: None,
         }
     }
 
@@ -189,6 +399,7 @@ impl LoadOrderEntry {
             name: name.into(),
             enabled: Some(enabled),
             index: None,
+            is_light: None,
         }
     }
 
@@ -198,6 +409,7 @@ impl LoadOrderEntry {
             name: name.into(),
             enabled: Some(enabled),
             index: Some(index),
+            is_light: None,
         }
     }
 }
@@ -279,6 +491,7 @@ impl FromIterator<LoadOrderEntry> for LoadOrder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_hash::compute_file_hash;
 
     #[test]
     fn entry_minimal() {
@@ -390,4 +603,102 @@ mod tests {
         let list: ModList = entries.into_iter().collect();
         assert_eq!(list.len(), 2);
     }
+
+    #[test]
+    fn mod_list_downgrades_to_load_order() {
+        let list = ModList::from_entries(vec![
+            ModEntry::new("a.esp", "aaaaaaaaaaaaaaaa", 10)
+                .with_index(0)
+                .with_enabled(true),
+            ModEntry::new("b.esp", "bbbbbbbbbbbbbbbb", 20).with_version("1.2"),
+        ]);
+
+        let lo = list.to_load_order();
+        assert_eq!(lo.len(), 2);
+        assert_eq!(lo.0[0], LoadOrderEntry::full("a.esp", true, 0));
+        assert_eq!(lo.0[1], LoadOrderEntry::new("b.esp"));
+    }
+
+    #[test]
+    fn light_flag_serializes_as_is_light() {
+        let entry = ModEntry::new("ccBGSSSE001-Fish.esm", "abcd1234abcd1234", 10).with_light(true);
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains("\"isLight\":true"));
+
+        let lo = ModList::from_entries(vec![entry]).to_load_order();
+        assert_eq!(lo.0[0].is_light, Some(true));
+        assert!(lo.to_json().unwrap().contains("\"isLight\":true"));
+    }
+
+    #[test]
+    fn masters_serialize_and_report_missing() {
+        let list = ModList::from_entries(vec![
+            ModEntry::new("Skyrim.esm", "aaaaaaaaaaaaaaaa", 10),
+            ModEntry::new("Patch.esp", "bbbbbbbbbbbbbbbb", 20)
+                .with_masters(vec!["skyrim.esm".into(), "Dawnguard.esm".into()]),
+            ModEntry::new("Off.esp", "cccccccccccccccc", 30)
+                .with_enabled(false)
+                .with_masters(vec!["Missing.esm".into()]),
+        ]);
+
+        assert_eq!(list.missing_masters(), vec![("Patch.esp", "Dawnguard.esm")]);
+
+        let json = list.to_json().unwrap();
+        assert!(json.contains("\"masters\":[\"skyrim.esm\",\"Dawnguard.esm\"]"));
+        assert_eq!(ModList::from_json(&json).unwrap(), list);
+        // Entries without masters omit the key
+        assert!(
+            !serde_json::to_string(&list.0[0])
+                .unwrap()
+                .contains("masters")
+        );
+    }
+
+    #[test]
+    fn fingerprint_parallel_keeps_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..20)
+            .map(|i| {
+                let path = dir.path().join(format!("Mod{:02}.esp", i));
+                fs::write(&path, format!("plugin {}", i)).unwrap();
+                path
+            })
+            .chain([dir.path().join("Missing.esp")])
+            .collect();
+        let mut hasher = CachedHasher::open(dir.path().join("hash-cache.json"));
+
+        let list = ModList::fingerprint_parallel(&paths, Duration::from_secs(60), &mut hasher);
+        assert_eq!(list.len(), 21);
+        for (i, entry) in list.iter().take(20).enumerate() {
+            assert_eq!(entry.name, format!("Mod{:02}.esp", i));
+            assert_eq!(entry.index, Some(i as u32));
+            assert_eq!(
+                (entry.file_hash.clone(), entry.file_size),
+                compute_file_hash(&paths[i]).unwrap()
+            );
+        }
+        assert_eq!(list.0[20].file_hash, UNHASHED);
+        assert_eq!(list.0[20].file_size, 0);
+    }
+
+    #[test]
+    fn fingerprint_parallel_stops_at_budget() {
+        let dir = tempfile::tempdir().unwrap();
+        let cached = dir.path().join("Cached.esp");
+        let fresh = dir.path().join("Fresh.esp");
+        fs::write(&cached, b"cached").unwrap();
+        fs::write(&fresh, b"fresh").unwrap();
+        let mut hasher = CachedHasher::open(dir.path().join("hash-cache.json"));
+        let (cached_hash, _) = hasher.hash(&cached).unwrap();
+
+        // With no time left, only cached hashes are used
+        let list = ModList::fingerprint_parallel(
+            &[cached.clone(), fresh.clone()],
+            Duration::ZERO,
+            &mut hasher,
+        );
+        assert_eq!(list.0[0].file_hash, cached_hash);
+        assert_eq!(list.0[1].file_hash, UNHASHED);
+        assert_eq!(list.0[1].file_size, 5);
+    }
 }
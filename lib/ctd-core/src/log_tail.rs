@@ -0,0 +1,119 @@
+//! The last lines of another tool's log file, for attaching to reports.
+//!
+//! Script errors logged just before a crash (CET, redscript, RED4ext) are
+//! often the real cause. [`read_tail`] reads only the end of a log, so a
+//! log that has grown for hours costs no more than a short one, and the
+//! result always fits in an [`Attachment`].
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::crash_report::{Attachment, MAX_ATTACHMENT_LEN};
+
+/// Default number of lines kept from each log.
+pub const DEFAULT_TAIL_LINES: usize = 200;
+
+/// Returns the last `max_lines` lines of the file at `path`.
+///
+/// Invalid UTF-8 is replaced, and the result is cut at the front to at most
+/// [`MAX_ATTACHMENT_LEN`] bytes.
+pub fn read_tail(path: &Path, max_lines: usize) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(MAX_ATTACHMENT_LEN as u64);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut bytes = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    // Drop the partial line the read started in
+    let text = match text.find('\n') {
+        Some(newline) if start > 0 => &text[newline + 1..],
+        _ => &text[..],
+    };
+
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = lines[lines.len().saturating_sub(max_lines)..].join("\n");
+    Ok(truncate_front(tail, MAX_ATTACHMENT_LEN))
+}
+
+/// Returns the tail of the log at `path` as an attachment named after the
+/// file, or `None` if the log is missing, unreadable, or empty.
+pub fn attach(path: &Path, max_lines: usize) -> Option<Attachment> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let tail = read_tail(path, max_lines).ok()?;
+    (!tail.trim().is_empty()).then(|| Attachment::new(name, tail))
+}
+
+/// Cuts `text` at the front to at most `max_len` bytes, on a char boundary.
+fn truncate_front(text: String, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text;
+    }
+    let mut start = text.len() - max_len;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    text[start..].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn keeps_the_last_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cyber_engine_tweaks.log");
+        let log: String = (1..=300).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, log).unwrap();
+
+        let tail = read_tail(&path, 200).unwrap();
+        assert_eq!(tail.lines().count(), 200);
+        assert!(tail.starts_with("line 101\n"));
+        assert!(tail.ends_with("line 300"));
+    }
+
+    #[test]
+    fn reads_only_the_end_of_large_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("red4ext.log");
+        let line = format!("{}\n", "x".repeat(99));
+        let mut log = line.repeat(2000);
+        log.push_str("[error] last line");
+        fs::write(&path, log).unwrap();
+
+        let tail = read_tail(&path, usize::MAX).unwrap();
+        assert!(tail.len() <= MAX_ATTACHMENT_LEN);
+        assert!(
+            tail.lines()
+                .all(|l| l.len() == 99 || l == "[error] last line")
+        );
+        assert!(tail.ends_with("[error] last line"));
+    }
+
+    #[test]
+    fn attach_skips_missing_and_empty_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("redscript_rCURRENT.log");
+        assert!(attach(&path, DEFAULT_TAIL_LINES).is_none());
+
+        fs::write(&path, "\n\n").unwrap();
+        assert!(attach(&path, DEFAULT_TAIL_LINES).is_none());
+
+        fs::write(&path, "[ERROR] failed to compile scripts\n").unwrap();
+        let attachment = attach(&path, DEFAULT_TAIL_LINES).unwrap();
+        assert_eq!(attachment.name, "redscript_rCURRENT.log");
+        assert_eq!(attachment.content, "[ERROR] failed to compile scripts");
+    }
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        let text = "é".repeat(10);
+        let cut = truncate_front(text, 5);
+        assert_eq!(cut, "éé");
+    }
+}
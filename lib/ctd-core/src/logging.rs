@@ -0,0 +1,303 @@
+//! Rolling log files for the game plugins.
+//!
+//! Each plugin writes to `<log dir>/<game id>.log`, rotating to
+//! `<game id>.1.log`, `<game id>.2.log`, ... once the file reaches
+//! `[logging] max_size_mb`. The log is the first thing to ask for when a
+//! user's crash never reached the server, so it records every step of the
+//! submission pipeline at the configured level.
+//!
+//! Users rarely know that file exists, though, so [`init_with_host`] also
+//! mirrors warnings and errors into the script extender's own log (SKSE,
+//! F4SE, UE4SS, RED4ext) through a [`HostSink`] supplied by the game crate.
+
+use std::fmt::{self as std_fmt, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, fmt};
+
+use crate::config::{Config, LoggingConfig};
+
+/// Writes a warning or error to the host's log.
+///
+/// Called on the thread that logged the event; must not log through
+/// `tracing` itself.
+pub type HostSink = fn(Level, &str);
+
+/// Installs the global tracing subscriber writing to the game's log file.
+///
+/// Reads `[logging]` from the loaded config. Returns the path of the log
+/// file, or `None` if logging is off, the file cannot be opened, or a
+/// subscriber is already installed.
+pub fn init(game_id: &str) -> Option<PathBuf> {
+    install(game_id, None)
+}
+
+/// Like [`init`], also forwarding warnings and errors to `host`.
+///
+/// Forwarding happens even when the file log is off or cannot be opened.
+pub fn init_with_host(game_id: &str, host: HostSink) -> Option<PathBuf> {
+    install(game_id, Some(host))
+}
+
+/// Installs the file layer and, if given, the host layer.
+fn install(game_id: &str, host: Option<HostSink>) -> Option<PathBuf> {
+    let config = Config::load().unwrap_or_default().logging;
+    let level = LevelFilter::from_str(&config.level).unwrap_or(LevelFilter::INFO);
+
+    let file = if level == LevelFilter::OFF {
+        None
+    } else {
+        RollingFile::open(&config, game_id).ok()
+    };
+    let path = file.as_ref().map(RollingFile::current_path);
+
+    let file_layer = file.map(|file| {
+        fmt::layer()
+            .with_ansi(false)
+            .with_thread_names(true)
+            .with_writer(Mutex::new(file))
+            .with_filter(level)
+    });
+    let host_layer = host.map(|sink| HostLayer { sink }.with_filter(LevelFilter::WARN));
+
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(host_layer)
+        .try_init()
+        .ok()?;
+
+    path
+}
+
+/// Forwards events to a [`HostSink`] as single-line messages.
+struct HostLayer {
+    sink: HostSink,
+}
+
+impl<S: Subscriber> Layer<S> for HostLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        (self.sink)(*event.metadata().level(), &message.0);
+    }
+}
+
+/// Formats an event as its message followed by ` key=value` fields.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std_fmt::Debug) {
+        if field.name() == "message" {
+            let fields = std::mem::take(&mut self.0);
+            let _ = write!(self.0, "{:?}{}", value, fields);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.insert_str(0, value);
+        } else {
+            let _ = write!(self.0, " {}={}", field.name(), value);
+        }
+    }
+}
+
+/// A log file that rotates when it grows past a size limit.
+#[derive(Debug)]
+pub struct RollingFile {
+    dir: PathBuf,
+    name: String,
+    max_files: usize,
+    max_bytes: u64,
+    file: File,
+    written: u64,
+}
+
+impl RollingFile {
+    /// Opens (appending to) the current log file for `name` in the configured directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be created.
+    pub fn open(config: &LoggingConfig, name: &str) -> io::Result<Self> {
+        Self::open_in(
+            &config.log_dir(),
+            name,
+            config.max_files,
+            config.max_size_mb.saturating_mul(1024 * 1024),
+        )
+    }
+
+    /// Opens (appending to) `<dir>/<name>.log`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be created.
+    pub fn open_in(dir: &Path, name: &str, max_files: usize, max_bytes: u64) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let name = sanitize(name);
+        let file = append(&dir.join(format!("{}.log", name)))?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            name,
+            max_files,
+            max_bytes,
+            file,
+            written,
+        })
+    }
+
+    /// Returns the path of the file currently written to.
+    pub fn current_path(&self) -> PathBuf {
+        self.rotated_path(0)
+    }
+
+    /// Returns the path of the `index`th file; 0 is the current one.
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(format!("{}.log", self.name))
+        } else {
+            self.dir.join(format!("{}.{}.log", self.name, index))
+        }
+    }
+
+    /// Shifts every file up one index, dropping the oldest, and starts a new file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let _ = fs::remove_file(self.rotated_path(self.max_files));
+        for index in (0..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))?;
+            }
+        }
+        if self.max_files == 0 {
+            // Nothing is kept, so the rename above never moved the current file
+            let _ = fs::remove_file(self.current_path());
+        }
+
+        self.file = append(&self.current_path())?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Opens a file for appending, creating it if needed.
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Keeps a log name from escaping the log directory.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_and_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RollingFile::open_in(dir.path(), "skyrim-se", 2, 10).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        log.flush().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("skyrim-se.log"), "fourth\n");
+        assert_eq!(read("skyrim-se.1.log"), "third\n");
+        assert_eq!(read("skyrim-se.2.log"), "second\n");
+        assert!(!dir.path().join("skyrim-se.3.log").exists());
+    }
+
+    #[test]
+    fn appends_to_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("fallout4.log"), "old\n").unwrap();
+
+        let mut log = RollingFile::open_in(dir.path(), "fallout4", 1, 1024).unwrap();
+        log.write_all(b"new\n").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(log.current_path()).unwrap(),
+            "old\nnew\n"
+        );
+    }
+
+    static FORWARDED: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+    fn record(level: Level, message: &str) {
+        FORWARDED.lock().unwrap().push((level, message.to_string()));
+    }
+
+    #[test]
+    fn host_layer_forwards_warnings_and_errors() {
+        let subscriber = tracing_subscriber::registry()
+            .with(HostLayer { sink: record }.with_filter(LevelFilter::WARN));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("not forwarded");
+            tracing::warn!(status = 503, "Submission failed: {}", "server busy");
+            tracing::error!("Crash handler failed");
+        });
+
+        assert_eq!(
+            *FORWARDED.lock().unwrap(),
+            vec![
+                (
+                    Level::WARN,
+                    "Submission failed: server busy status=503".to_string()
+                ),
+                (Level::ERROR, "Crash handler failed".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn name_cannot_escape_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = RollingFile::open_in(dir.path(), "../evil", 1, 1024).unwrap();
+        assert_eq!(log.current_path(), dir.path().join("___evil.log"));
+    }
+}
@@ -0,0 +1,435 @@
+//! Where a faulting address lies when no module contains it.
+//!
+//! A crash in JIT code, freed memory, or a hook trampoline has no faulting
+//! module, so the report used to end at "unknown". [`address_region`] asks
+//! `VirtualQuery` about the memory at the address (its state, protection,
+//! and whether it is image, mapped, or private memory) and names the loaded
+//! modules on either side. Executable private memory right after a mod's
+//! DLL is usually that mod's trampolines; a free region is a call through a
+//! dangling pointer.
+//!
+//! The module also lists the loaded modules, reads this process's memory
+//! without faulting, and names the file mapped at an address.
+
+use std::path::PathBuf;
+
+use crate::crash_report::AddressRegion;
+
+const MEM_COMMIT: u32 = 0x1000;
+const MEM_RESERVE: u32 = 0x2000;
+const MEM_FREE: u32 = 0x10000;
+
+const MEM_PRIVATE: u32 = 0x20000;
+const MEM_MAPPED: u32 = 0x40000;
+const MEM_IMAGE: u32 = 0x1000000;
+
+/// Reads `len` bytes of this process's memory at an address, as
+/// [`read`] does; tests substitute fake memory.
+pub(crate) type ReadMemory<'a> = &'a dyn Fn(u64, usize) -> Option<Vec<u8>>;
+
+/// Base page protections, by `PAGE_*` value.
+const PROTECTIONS: &[(u32, &str)] = &[
+    (0x01, "PAGE_NOACCESS"),
+    (0x02, "PAGE_READONLY"),
+    (0x04, "PAGE_READWRITE"),
+    (0x08, "PAGE_WRITECOPY"),
+    (0x10, "PAGE_EXECUTE"),
+    (0x20, "PAGE_EXECUTE_READ"),
+    (0x40, "PAGE_EXECUTE_READWRITE"),
+    (0x80, "PAGE_EXECUTE_WRITECOPY"),
+];
+
+/// Protection modifiers, by `PAGE_*` flag.
+const MODIFIERS: &[(u32, &str)] = &[
+    (0x100, "PAGE_GUARD"),
+    (0x200, "PAGE_NOCACHE"),
+    (0x400, "PAGE_WRITECOMBINE"),
+];
+
+/// A module loaded in this process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedModule {
+    /// File name (e.g., "SkyrimSE.exe").
+    pub name: String,
+    /// Full path of the file it was loaded from.
+    pub path: PathBuf,
+    /// Load address.
+    pub base: u64,
+    /// Size of the image in memory.
+    pub size: u64,
+}
+
+impl LoadedModule {
+    /// Returns true if `address` is inside the image.
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base && address - self.base < self.size
+    }
+}
+
+/// Describes the memory at `address` and the modules around it.
+///
+/// Returns `None` if `VirtualQuery` fails, as for kernel addresses.
+#[cfg(windows)]
+pub fn address_region(address: u64) -> Option<AddressRegion> {
+    use windows::Win32::System::Memory::{MEMORY_BASIC_INFORMATION, VirtualQuery};
+
+    let mut info = MEMORY_BASIC_INFORMATION::default();
+    // SAFETY: VirtualQuery only describes the region; info is sized correctly
+    let written = unsafe {
+        VirtualQuery(
+            Some(address as usize as *const std::ffi::c_void),
+            &mut info,
+            std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+    if written == 0 {
+        return None;
+    }
+
+    Some(describe(
+        address,
+        info.BaseAddress as u64,
+        info.RegionSize as u64,
+        info.State.0,
+        info.Protect.0,
+        info.Type.0,
+        &loaded_modules(),
+    ))
+}
+
+/// Describes the memory at `address` and the modules around it.
+///
+/// `VirtualQuery` only exists on Windows; always returns `None`.
+#[cfg(not(windows))]
+pub fn address_region(_address: u64) -> Option<AddressRegion> {
+    None
+}
+
+/// Builds the report section from `VirtualQuery`'s answer and the module
+/// list.
+pub fn describe(
+    address: u64,
+    base: u64,
+    size: u64,
+    state: u32,
+    protect: u32,
+    kind: u32,
+    modules: &[LoadedModule],
+) -> AddressRegion {
+    let (before, after) = nearest_modules(modules, address);
+    AddressRegion {
+        base: format!("0x{:X}", base),
+        size,
+        state: state_name(state).to_string(),
+        // Only committed pages have a protection
+        protection: (state == MEM_COMMIT).then(|| protection_name(protect)),
+        kind: kind_name(kind).map(str::to_string),
+        module_before: before
+            .map(|module| format!("{}+0x{:X}", module.name, address - module.base)),
+        module_after: after.map(|module| format!("{}-0x{:X}", module.name, module.base - address)),
+    }
+}
+
+/// Names a `MEM_*` state.
+fn state_name(state: u32) -> &'static str {
+    match state {
+        MEM_COMMIT => "commit",
+        MEM_RESERVE => "reserve",
+        MEM_FREE => "free",
+        _ => "unknown",
+    }
+}
+
+/// Names a `MEM_*` type, or `None` for free memory.
+fn kind_name(kind: u32) -> Option<&'static str> {
+    match kind {
+        MEM_IMAGE => Some("image"),
+        MEM_MAPPED => Some("mapped"),
+        MEM_PRIVATE => Some("private"),
+        _ => None,
+    }
+}
+
+/// Names a page protection, with its modifiers joined by `|`.
+fn protection_name(protect: u32) -> String {
+    let base = PROTECTIONS
+        .iter()
+        .find(|(value, _)| protect & 0xFF == *value)
+        .map_or_else(
+            || format!("0x{:X}", protect & 0xFF),
+            |(_, name)| name.to_string(),
+        );
+    MODIFIERS
+        .iter()
+        .filter(|(flag, _)| protect & flag != 0)
+        .fold(base, |name, (_, modifier)| format!("{}|{}", name, modifier))
+}
+
+/// Returns the module loaded closest below `address` and the one loaded
+/// closest above it.
+fn nearest_modules(
+    modules: &[LoadedModule],
+    address: u64,
+) -> (Option<&LoadedModule>, Option<&LoadedModule>) {
+    let before = modules
+        .iter()
+        .filter(|module| module.base <= address)
+        .max_by_key(|module| module.base);
+    let after = modules
+        .iter()
+        .filter(|module| module.base > address)
+        .min_by_key(|module| module.base);
+    (before, after)
+}
+
+/// Lists the modules loaded in this process.
+#[cfg(windows)]
+pub fn loaded_modules() -> Vec<LoadedModule> {
+    use windows::Win32::Foundation::HMODULE;
+    use windows::Win32::System::LibraryLoader::GetModuleFileNameW;
+    use windows::Win32::System::ProcessStatus::{
+        EnumProcessModules, GetModuleInformation, MODULEINFO,
+    };
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut handles = [HMODULE::default(); 1024];
+    let mut needed = 0u32;
+    // SAFETY: Returns a pseudo-handle that needs no closing
+    let process = unsafe { GetCurrentProcess() };
+    // SAFETY: The buffer size matches the array
+    let enumerated = unsafe {
+        EnumProcessModules(
+            process,
+            handles.as_mut_ptr(),
+            std::mem::size_of_val(&handles) as u32,
+            &mut needed,
+        )
+    };
+    if enumerated.is_err() {
+        return Vec::new();
+    }
+
+    let count = (needed as usize / std::mem::size_of::<HMODULE>()).min(handles.len());
+    handles[..count]
+        .iter()
+        .filter_map(|&handle| {
+            let mut info = MODULEINFO::default();
+            // SAFETY: info is sized correctly
+            unsafe {
+                GetModuleInformation(
+                    process,
+                    handle,
+                    &mut info,
+                    std::mem::size_of::<MODULEINFO>() as u32,
+                )
+            }
+            .ok()?;
+
+            let mut filename = [0u16; 260];
+            // SAFETY: GetModuleFileNameW is safe with valid buffer
+            let len = unsafe { GetModuleFileNameW(handle, &mut filename) };
+            let path = String::from_utf16_lossy(&filename[..len as usize]);
+            Some(LoadedModule {
+                name: path.rsplit('\\').next().unwrap_or_default().to_string(),
+                path: PathBuf::from(path),
+                base: info.lpBaseOfDll as u64,
+                size: u64::from(info.SizeOfImage),
+            })
+        })
+        .collect()
+}
+
+/// Lists the modules loaded in this process.
+///
+/// Module enumeration only exists on Windows; always returns an empty list.
+#[cfg(not(windows))]
+pub fn loaded_modules() -> Vec<LoadedModule> {
+    Vec::new()
+}
+
+/// Copies `len` bytes from this process, or `None` if they aren't all
+/// readable.
+#[cfg(windows)]
+pub fn read(address: u64, len: usize) -> Option<Vec<u8>> {
+    use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut bytes = vec![0u8; len];
+    let mut read = 0usize;
+    // SAFETY: The buffer is writable for its whole length; the source is
+    // checked by ReadProcessMemory
+    unsafe {
+        ReadProcessMemory(
+            GetCurrentProcess(),
+            address as *const std::ffi::c_void,
+            bytes.as_mut_ptr().cast(),
+            len,
+            Some(&mut read),
+        )
+    }
+    .ok()?;
+    (read == len).then_some(bytes)
+}
+
+/// Copies `len` bytes from this process.
+///
+/// `ReadProcessMemory` only exists on Windows; always returns `None`.
+#[cfg(not(windows))]
+pub fn read(_address: u64, _len: usize) -> Option<Vec<u8>> {
+    None
+}
+
+/// Returns the path of the file mapped at `address`, for image and mapped
+/// memory.
+///
+/// `GetMappedFileNameW` names the file by its device (e.g.,
+/// `\Device\HarddiskVolume3\Games\...`); the path is rewritten with the
+/// drive letter mapped to that device when there is one.
+#[cfg(windows)]
+pub fn mapped_file(address: u64) -> Option<PathBuf> {
+    use windows::Win32::Storage::FileSystem::QueryDosDeviceW;
+    use windows::Win32::System::ProcessStatus::GetMappedFileNameW;
+    use windows::Win32::System::Threading::GetCurrentProcess;
+    use windows::core::PCWSTR;
+
+    let mut name = [0u16; 1024];
+    // SAFETY: Only names the file mapped at the address; the buffer is
+    // sized by the slice
+    let len = unsafe {
+        GetMappedFileNameW(
+            GetCurrentProcess(),
+            address as *const std::ffi::c_void,
+            &mut name,
+        )
+    };
+    if len == 0 {
+        return None;
+    }
+    let device_path = String::from_utf16_lossy(&name[..len as usize]);
+
+    let drives: Vec<(String, String)> = (b'A'..=b'Z')
+        .filter_map(|letter| {
+            let drive = format!("{}:", letter as char);
+            let wide: Vec<u16> = drive.encode_utf16().chain(Some(0)).collect();
+            let mut target = [0u16; 260];
+            // SAFETY: wide is null-terminated; target is sized by the slice
+            let len = unsafe { QueryDosDeviceW(PCWSTR(wide.as_ptr()), Some(&mut target)) };
+            // The target list is null-separated; the first entry is the device
+            let device = target[..len as usize].split(|&c| c == 0).next()?;
+            (!device.is_empty()).then(|| (drive, String::from_utf16_lossy(device)))
+        })
+        .collect();
+
+    Some(dos_path(&device_path, &drives).unwrap_or_else(|| PathBuf::from(device_path)))
+}
+
+/// Returns the path of the file mapped at `address`.
+///
+/// `GetMappedFileNameW` only exists on Windows; always returns `None`.
+#[cfg(not(windows))]
+pub fn mapped_file(_address: u64) -> Option<PathBuf> {
+    None
+}
+
+/// Rewrites a device path with the drive letter mapped to its device, given
+/// `(drive, device)` pairs such as `("C:", "\Device\HarddiskVolume3")`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn dos_path(device_path: &str, drives: &[(String, String)]) -> Option<PathBuf> {
+    drives.iter().find_map(|(drive, device)| {
+        let rest = device_path.strip_prefix(device.as_str())?;
+        rest.starts_with('\\')
+            .then(|| PathBuf::from(format!("{}{}", drive, rest)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modules() -> Vec<LoadedModule> {
+        vec![
+            LoadedModule {
+                name: "SkyrimSE.exe".into(),
+                path: PathBuf::from(r"C:\Games\Skyrim\SkyrimSE.exe"),
+                base: 0x7FF600000000,
+                size: 0x3000000,
+            },
+            LoadedModule {
+                name: "EngineFixes.dll".into(),
+                path: PathBuf::from(r"C:\Games\Skyrim\Data\SKSE\Plugins\EngineFixes.dll"),
+                base: 0x7FFA10000000,
+                size: 0x100000,
+            },
+        ]
+    }
+
+    #[test]
+    fn describes_trampolines_between_modules() {
+        let region = describe(
+            0x7FF800001234,
+            0x7FF800000000,
+            0x10000,
+            MEM_COMMIT,
+            0x40,
+            MEM_PRIVATE,
+            &modules(),
+        );
+
+        assert_eq!(region.base, "0x7FF800000000");
+        assert_eq!(region.state, "commit");
+        assert_eq!(region.protection.as_deref(), Some("PAGE_EXECUTE_READWRITE"));
+        assert_eq!(region.kind.as_deref(), Some("private"));
+        assert_eq!(
+            region.module_before.as_deref(),
+            Some("SkyrimSE.exe+0x200001234")
+        );
+        assert_eq!(
+            region.module_after.as_deref(),
+            Some("EngineFixes.dll-0x20FFFEDCC")
+        );
+    }
+
+    #[test]
+    fn free_memory_has_no_protection_or_kind() {
+        let region = describe(0x1000, 0, 0x10000, MEM_FREE, 0x01, 0, &modules());
+
+        assert_eq!(region.state, "free");
+        assert_eq!(region.protection, None);
+        assert_eq!(region.kind, None);
+        assert_eq!(region.module_before, None);
+        assert_eq!(
+            region.module_after.as_deref(),
+            Some("SkyrimSE.exe-0x7FF5FFFFF000")
+        );
+    }
+
+    #[test]
+    fn protection_names_include_modifiers() {
+        assert_eq!(protection_name(0x20), "PAGE_EXECUTE_READ");
+        assert_eq!(protection_name(0x104), "PAGE_READWRITE|PAGE_GUARD");
+        assert_eq!(protection_name(0x3), "0x3");
+    }
+
+    #[test]
+    fn device_paths_get_drive_letters() {
+        let drives = vec![
+            ("C:".to_string(), r"\Device\HarddiskVolume3".to_string()),
+            ("D:".to_string(), r"\Device\HarddiskVolume1".to_string()),
+        ];
+
+        assert_eq!(
+            dos_path(
+                r"\Device\HarddiskVolume1\Steam\Skyrim\Data\Skyrim - Textures0.bsa",
+                &drives
+            ),
+            Some(PathBuf::from(
+                r"D:\Steam\Skyrim\Data\Skyrim - Textures0.bsa"
+            ))
+        );
+        // HarddiskVolume3 is not a prefix of HarddiskVolume30's paths
+        assert_eq!(
+            dos_path(r"\Device\HarddiskVolume30\game.exe", &drives),
+            None
+        );
+    }
+}
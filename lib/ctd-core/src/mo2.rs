@@ -0,0 +1,387 @@
+//! Mod Organizer 2 detection and mod metadata.
+//!
+//! Most Bethesda players install mods through MO2, which keeps every mod in
+//! its own folder and overlays them onto `Data/` with a virtual file system
+//! (usvfs). Plugin names alone ("SkyUI_SE.esp") say little about which mod
+//! or version is installed; MO2's `modlist.txt` and each mod's `meta.ini`
+//! do, so reports can say "SkyUI 5.2SE (Nexus 12604)" instead.
+//!
+//! The instance is found from `MO2_PROFILE_DIR` (and optionally
+//! `MO2_MODS_DIR`) if set, otherwise from the usvfs DLL that MO2 injects
+//! into the game and the `ModOrganizer.ini` of the instance managing it.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tracing::debug;
+
+use crate::load_order::ModList;
+
+/// Environment variable naming the active profile directory.
+pub const PROFILE_DIR_VAR: &str = "MO2_PROFILE_DIR";
+
+/// Environment variable naming the mods directory.
+///
+/// Defaults to `<profile dir>/../../mods`, MO2's default layout.
+pub const MODS_DIR_VAR: &str = "MO2_MODS_DIR";
+
+/// Plugin file extensions MO2 mods can provide.
+const PLUGIN_EXTENSIONS: &[&str] = &["esp", "esm", "esl"];
+
+/// A Mod Organizer 2 instance and its active profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mo2Instance {
+    /// Directory of the active profile (holds `modlist.txt`).
+    pub profile_dir: PathBuf,
+    /// Directory holding one folder per installed mod.
+    pub mods_dir: PathBuf,
+}
+
+/// An enabled mod in the active profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mo2Mod {
+    /// Mod name as shown in MO2 (also its folder name).
+    pub name: String,
+    /// The mod's folder.
+    pub dir: PathBuf,
+    /// Nexus Mods ID from `meta.ini`, if installed from Nexus.
+    pub nexus_id: Option<u32>,
+    /// Installed version from `meta.ini`.
+    pub version: Option<String>,
+}
+
+impl Mo2Instance {
+    /// Creates an instance from explicit directories.
+    pub fn new(profile_dir: impl Into<PathBuf>, mods_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            profile_dir: profile_dir.into(),
+            mods_dir: mods_dir.into(),
+        }
+    }
+
+    /// Detects the MO2 instance the game was launched from.
+    ///
+    /// Returns `None` if the game isn't running under MO2 or the instance's
+    /// profile cannot be found.
+    pub fn detect() -> Option<Self> {
+        let instance = Self::from_env().or_else(Self::from_vfs)?;
+        if !instance.profile_dir.join("modlist.txt").is_file() {
+            debug!(
+                "MO2 profile {} has no modlist.txt",
+                instance.profile_dir.display()
+            );
+            return None;
+        }
+        Some(instance)
+    }
+
+    /// Reads the instance from `MO2_PROFILE_DIR` and `MO2_MODS_DIR`.
+    fn from_env() -> Option<Self> {
+        let profile_dir = PathBuf::from(std::env::var_os(PROFILE_DIR_VAR)?);
+        let mods_dir = std::env::var_os(MODS_DIR_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| profile_dir.join("..").join("..").join("mods"));
+        Some(Self::new(profile_dir, mods_dir))
+    }
+
+    /// Finds the instance from the usvfs DLL loaded into the game.
+    fn from_vfs() -> Option<Self> {
+        let mo2_dir = vfs_dll_path()?.parent()?.to_path_buf();
+
+        // Portable instances keep their ini next to ModOrganizer.exe
+        let portable = mo2_dir.join("ModOrganizer.ini");
+        if portable.is_file() {
+            return Self::from_ini(&portable);
+        }
+
+        // Global instances live in %LOCALAPPDATA%; pick the one for this game
+        let game_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+        let instances = dirs::data_local_dir()?.join("ModOrganizer");
+        fs::read_dir(instances)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("ModOrganizer.ini"))
+            .find(|ini| manages_game(ini, &game_dir))
+            .and_then(|ini| Self::from_ini(&ini))
+    }
+
+    /// Reads an instance's directories and selected profile from its `ModOrganizer.ini`.
+    ///
+    /// Returns `None` if the file cannot be read.
+    pub fn from_ini(path: &Path) -> Option<Self> {
+        let ini = parse_ini(&fs::read_to_string(path).ok()?);
+        let get = |section: &str, key: &str| ini.get(&(section.to_string(), key.to_string()));
+
+        let base_dir = get("Settings", "base_directory")
+            .map(PathBuf::from)
+            .or_else(|| path.parent().map(Path::to_path_buf))?;
+        let resolve = |key: &str, default: &str| {
+            let value = get("Settings", key).map_or(default, String::as_str);
+            PathBuf::from(value.replace("%BASE_DIR%", &base_dir.to_string_lossy()))
+        };
+
+        let profile = get("General", "selected_profile").map_or("Default", String::as_str);
+        Some(Self::new(
+            resolve("profiles_directory", "%BASE_DIR%/profiles").join(profile),
+            resolve("mod_directory", "%BASE_DIR%/mods"),
+        ))
+    }
+
+    /// Returns the enabled mods of the active profile, highest priority first.
+    pub fn active_mods(&self) -> Vec<Mo2Mod> {
+        let Ok(modlist) = fs::read_to_string(self.profile_dir.join("modlist.txt")) else {
+            return Vec::new();
+        };
+
+        // "+Name" is enabled, "-Name" disabled, "*Name" unmanaged (DLC, CC)
+        modlist
+            .lines()
+            .filter_map(|line| line.trim_end().strip_prefix('+'))
+            .map(|name| {
+                let dir = self.mods_dir.join(name);
+                let (nexus_id, version) = read_meta(&dir);
+                Mo2Mod {
+                    name: name.to_string(),
+                    dir,
+                    nexus_id,
+                    version,
+                }
+            })
+            .collect()
+    }
+
+    /// Fills in the MO2 mod name, Nexus ID, and version of each plugin in `list`.
+    ///
+    /// When several mods provide the same plugin, the highest-priority one
+    /// wins, as it does in MO2's virtual `Data/`. Existing versions are kept.
+    pub fn enrich(&self, list: &mut ModList) {
+        let mods = self.active_mods();
+
+        let mut owners: HashMap<String, &Mo2Mod> = HashMap::new();
+        for m in &mods {
+            for plugin in plugins_in(&m.dir) {
+                owners.entry(plugin.to_lowercase()).or_insert(m);
+            }
+        }
+
+        for entry in list.0.iter_mut() {
+            let Some(owner) = owners.get(&entry.name.to_lowercase()) else {
+                continue;
+            };
+            entry.mod_name = Some(owner.name.clone());
+            entry.nexus_id = owner.nexus_id;
+            if entry.version.is_none() {
+                entry.version = owner.version.clone();
+            }
+        }
+    }
+}
+
+/// Reads the Nexus ID and version from a mod's `meta.ini`.
+fn read_meta(mod_dir: &Path) -> (Option<u32>, Option<String>) {
+    let Ok(contents) = fs::read_to_string(mod_dir.join("meta.ini")) else {
+        return (None, None);
+    };
+    let ini = parse_ini(&contents);
+    let get = |key: &str| ini.get(&("General".to_string(), key.to_string()));
+
+    // MO2 writes modid=0 and version= for mods not installed from Nexus
+    let nexus_id = get("modid")
+        .and_then(|id| id.parse().ok())
+        .filter(|&id| id > 0);
+    let version = get("version").filter(|v| !v.is_empty()).cloned();
+    (nexus_id, version)
+}
+
+/// Lists the plugin files at the top level of a mod folder.
+fn plugins_in(mod_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(mod_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| PLUGIN_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .filter_map(|path| Some(path.file_name()?.to_str()?.to_string()))
+        .collect()
+}
+
+/// Returns true if the instance at `ini` manages the game in `game_dir`.
+fn manages_game(ini: &Path, game_dir: &Path) -> bool {
+    let Ok(contents) = fs::read_to_string(ini) else {
+        return false;
+    };
+    let normalize = |path: &str| path.replace('\\', "/").trim_end_matches('/').to_lowercase();
+    parse_ini(&contents)
+        .get(&("General".to_string(), "gamePath".to_string()))
+        .is_some_and(|path| normalize(path) == normalize(&game_dir.to_string_lossy()))
+}
+
+/// Parses a Qt-style ini file into `(section, key) -> value`.
+///
+/// Unwraps `@ByteArray(...)` values and Qt's doubled backslashes.
+fn parse_ini(contents: &str) -> HashMap<(String, String), String> {
+    let mut values = HashMap::new();
+    let mut section = String::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        let value = value.trim();
+        let value = value
+            .strip_prefix("@ByteArray(")
+            .and_then(|v| v.strip_suffix(')'))
+            .unwrap_or(value);
+        values.insert(
+            (section.clone(), key.trim().to_string()),
+            value.replace("\\\\", "\\"),
+        );
+    }
+
+    values
+}
+
+/// Returns the path of MO2's usvfs DLL if it is loaded into this process.
+#[cfg(windows)]
+fn vfs_dll_path() -> Option<PathBuf> {
+    use windows::Win32::System::LibraryLoader::{GetModuleFileNameW, GetModuleHandleW};
+    use windows::core::w;
+
+    #[cfg(target_pointer_width = "64")]
+    let name = w!("usvfs_x64.dll");
+    #[cfg(target_pointer_width = "32")]
+    let name = w!("usvfs_x86.dll");
+
+    unsafe {
+        let module = GetModuleHandleW(name).ok()?;
+        let mut path = [0u16; 1024];
+        let len = GetModuleFileNameW(module, &mut path) as usize;
+        (len > 0).then(|| PathBuf::from(String::from_utf16_lossy(&path[..len])))
+    }
+}
+
+/// Stub implementation for non-Windows platforms.
+///
+/// Always returns `None` since MO2's VFS only exists on Windows.
+#[cfg(not(windows))]
+fn vfs_dll_path() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModEntry;
+
+    /// Creates an instance with the default layout under `base`.
+    fn instance(base: &Path) -> Mo2Instance {
+        let profile = base.join("profiles").join("Default");
+        fs::create_dir_all(&profile).unwrap();
+        fs::write(
+            profile.join("modlist.txt"),
+            "# This file was automatically generated by Mod Organizer.\n\
+             +SkyUI Patch\n\
+             -Disabled Mod\n\
+             +SkyUI\n\
+             *DLC: Dawnguard\n",
+        )
+        .unwrap();
+
+        let add_mod = |name: &str, plugins: &[&str], meta: &str| {
+            let dir = base.join("mods").join(name);
+            fs::create_dir_all(&dir).unwrap();
+            for plugin in plugins {
+                fs::write(dir.join(plugin), "").unwrap();
+            }
+            fs::write(dir.join("meta.ini"), meta).unwrap();
+        };
+        add_mod(
+            "SkyUI",
+            &["SkyUI_SE.esp"],
+            "[General]\nmodid=12604\nversion=5.2SE\n",
+        );
+        add_mod(
+            "SkyUI Patch",
+            &["SkyUI_Patch.esp"],
+            "[General]\nmodid=0\nversion=\n",
+        );
+        add_mod("Disabled Mod", &["Disabled.esp"], "[General]\nmodid=1\n");
+
+        Mo2Instance::new(profile, base.join("mods"))
+    }
+
+    #[test]
+    fn reads_enabled_mods_in_priority_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mods = instance(dir.path()).active_mods();
+
+        let names: Vec<&str> = mods.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["SkyUI Patch", "SkyUI"]);
+        assert_eq!(mods[1].nexus_id, Some(12604));
+        assert_eq!(mods[1].version.as_deref(), Some("5.2SE"));
+        assert_eq!(mods[0].nexus_id, None);
+        assert_eq!(mods[0].version, None);
+    }
+
+    #[test]
+    fn enriches_plugins_with_mod_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut list = ModList::from_entries(vec![
+            ModEntry::new("Skyrim.esm", "0000000000000000", 0),
+            ModEntry::new("skyui_se.esp", "0000000000000000", 0),
+            ModEntry::new("Disabled.esp", "0000000000000000", 0),
+        ]);
+
+        instance(dir.path()).enrich(&mut list);
+
+        assert_eq!(list.0[0].mod_name, None);
+        assert_eq!(list.0[1].mod_name.as_deref(), Some("SkyUI"));
+        assert_eq!(list.0[1].nexus_id, Some(12604));
+        assert_eq!(list.0[1].display_name(), "SkyUI 5.2SE (Nexus 12604)");
+        assert_eq!(list.0[2].mod_name, None);
+    }
+
+    #[test]
+    fn reads_directories_from_mod_organizer_ini() {
+        let dir = tempfile::tempdir().unwrap();
+        let ini = dir.path().join("ModOrganizer.ini");
+        fs::write(
+            &ini,
+            "[General]\n\
+             gamePath=@ByteArray(C:\\\\Games\\\\Skyrim Special Edition)\n\
+             selected_profile=@ByteArray(Survival)\n\
+             \n\
+             [Settings]\n\
+             base_directory=D:/MO2\n\
+             mod_directory=%BASE_DIR%/my mods\n",
+        )
+        .unwrap();
+
+        let instance = Mo2Instance::from_ini(&ini).unwrap();
+        assert_eq!(
+            instance.profile_dir,
+            Path::new("D:/MO2/profiles").join("Survival")
+        );
+        assert_eq!(instance.mods_dir, PathBuf::from("D:/MO2/my mods"));
+
+        assert!(manages_game(
+            &ini,
+            Path::new("c:\\games\\Skyrim Special Edition\\")
+        ));
+        assert!(!manages_game(&ini, Path::new("C:\\Games\\Fallout 4")));
+    }
+}
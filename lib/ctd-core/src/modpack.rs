@@ -0,0 +1,256 @@
+//! Modpack detection: which Wabbajack list or Nexus Collection a game runs.
+//!
+//! Players of a popular modpack crash in the same known configuration, and
+//! the pack's name says more about the load order than its 1,500 plugins.
+//! [`detect`] names the pack from the markers the installers leave behind:
+//!
+//! - **Wabbajack** installs a list as a portable Mod Organizer 2 instance,
+//!   and the list's `*.metadata.json` (its `title` and `version`) sits in
+//!   the instance directory.
+//! - **Nexus Collections** are installed by Vortex as a mod in its staging
+//!   folder (`%APPDATA%/Vortex/<game>/mods`) holding a `collection.json`
+//!   manifest, whose `info` names the collection.
+//!
+//! List authors can stamp their pack instead with `[modpack] name` and
+//! `version` in the `ctd.toml` they ship, which wins over detection.
+//! `[modpack] detect = false` turns detection off.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::mo2::Mo2Instance;
+
+/// Maximum length of a modpack name; longer names are truncated.
+pub const MAX_NAME_LEN: usize = 100;
+
+/// Maximum length of a modpack version; longer versions are dropped.
+pub const MAX_VERSION_LEN: usize = 50;
+
+/// Vortex's game IDs, which name its staging folders, by CTD game ID.
+const VORTEX_GAMES: &[(&str, &str)] = &[
+    ("skyrim-se", "skyrimse"),
+    ("fallout4", "fallout4"),
+    ("fallout3", "fallout3"),
+    ("newvegas", "falloutnv"),
+    ("cyberpunk-2077", "cyberpunk2077"),
+    ("baldurs-gate-3", "baldursgate3"),
+    ("witcher3", "witcher3"),
+    ("oblivion-remastered", "oblivionremastered"),
+    ("elden-ring", "eldenring"),
+];
+
+/// The modpack a game was installed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Modpack {
+    /// Pack name (e.g., "Lorerim").
+    pub name: String,
+    /// Pack version, if the marker or config gives one.
+    pub version: Option<String>,
+}
+
+impl Modpack {
+    /// Creates a modpack, trimming the name and version and fitting them to
+    /// [`MAX_NAME_LEN`] and [`MAX_VERSION_LEN`].
+    ///
+    /// Returns `None` if the name is empty.
+    pub fn new(name: &str, version: Option<&str>) -> Option<Self> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut end = name.len().min(MAX_NAME_LEN);
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        let version = version
+            .map(str::trim)
+            .filter(|version| !version.is_empty() && version.len() <= MAX_VERSION_LEN);
+        Some(Self {
+            name: name[..end].to_string(),
+            version: version.map(str::to_string),
+        })
+    }
+}
+
+/// Returns the modpack the game `game_id` was installed from: the one
+/// named in the config, else a detected Wabbajack list, else a detected
+/// Nexus Collection.
+pub fn detect(game_id: &str) -> Option<Modpack> {
+    let config = Config::load().unwrap_or_default().modpack;
+    let stamped = config
+        .name
+        .as_deref()
+        .and_then(|name| Modpack::new(name, config.version.as_deref()));
+    if stamped.is_some() {
+        return stamped;
+    }
+    if !config.detect {
+        return None;
+    }
+
+    Mo2Instance::detect()
+        .and_then(|instance| {
+            instance_dirs(&instance)
+                .iter()
+                .find_map(|dir| find_wabbajack(dir))
+        })
+        .or_else(|| vortex_staging_dir(game_id).and_then(|dir| find_collection(&dir)))
+}
+
+/// Directories a Wabbajack list may have been installed to: the instance's
+/// base directory, which normally holds both `mods` and `profiles`.
+fn instance_dirs(instance: &Mo2Instance) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let candidates = [
+        instance.mods_dir.parent(),
+        instance.profile_dir.parent().and_then(Path::parent),
+    ];
+    for dir in candidates.into_iter().flatten() {
+        if !dirs.iter().any(|known: &PathBuf| known == dir) {
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// Reads the newest `*.metadata.json` in `dir` that names a list.
+fn find_wabbajack(dir: &Path) -> Option<Modpack> {
+    newest_first(
+        fs::read_dir(dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.to_ascii_lowercase().ends_with(".metadata.json"))
+            })
+            .collect(),
+    )
+    .iter()
+    .find_map(|path| {
+        let json = read_json(path)?;
+        Modpack::new(
+            string_field(&json, "title")?,
+            string_field(&json, "version"),
+        )
+    })
+}
+
+/// Returns Vortex's staging folder for `game_id`, if it has a Vortex ID.
+fn vortex_staging_dir(game_id: &str) -> Option<PathBuf> {
+    let (_, vortex_id) = VORTEX_GAMES.iter().find(|(id, _)| *id == game_id)?;
+    Some(
+        dirs::data_dir()?
+            .join("Vortex")
+            .join(vortex_id)
+            .join("mods"),
+    )
+}
+
+/// Reads the newest `collection.json` among the mods in `staging_dir`.
+fn find_collection(staging_dir: &Path) -> Option<Modpack> {
+    newest_first(
+        fs::read_dir(staging_dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("collection.json"))
+            .filter(|path| path.is_file())
+            .collect(),
+    )
+    .iter()
+    .find_map(|path| {
+        let info = read_json(path)?.get("info")?.clone();
+        Modpack::new(string_field(&info, "name")?, string_field(&info, "version"))
+    })
+}
+
+/// Sorts `paths` by modification time, newest first, since the pack
+/// installed last is the one most likely running.
+fn newest_first(mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let modified = |path: &PathBuf| {
+        fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    };
+    paths.sort_by_key(|path| std::cmp::Reverse(modified(path)));
+    paths
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Returns a string field, also accepting its PascalCase spelling (`Title`),
+/// since .NET serializers write either.
+fn string_field<'a>(json: &'a Value, key: &str) -> Option<&'a str> {
+    let mut pascal = key.to_string();
+    pascal[..1].make_ascii_uppercase();
+    json.get(key)
+        .or_else(|| json.get(&pascal))
+        .and_then(Value::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_wabbajack_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("ModOrganizer.ini"), "[General]").unwrap();
+        fs::write(dir.path().join("notes.metadata.json"), "{}").unwrap();
+        fs::write(
+            dir.path().join("Lorerim.wabbajack.metadata.json"),
+            r#"{"title":" Lorerim ","version":"3.1.4","author":"Odin"}"#,
+        )
+        .unwrap();
+
+        let pack = find_wabbajack(dir.path()).unwrap();
+        assert_eq!(pack.name, "Lorerim");
+        assert_eq!(pack.version.as_deref(), Some("3.1.4"));
+
+        let pascal = r#"{"Title":"Nolvus","Version":"5.0"}"#;
+        let json: Value = serde_json::from_str(pascal).unwrap();
+        assert_eq!(string_field(&json, "title"), Some("Nolvus"));
+    }
+
+    #[test]
+    fn finds_collection_manifests() {
+        let staging = tempfile::tempdir().unwrap();
+        let collection = staging.path().join("collection-ultimate-survival-rev12");
+        fs::create_dir_all(&collection).unwrap();
+        fs::create_dir_all(staging.path().join("SkyUI")).unwrap();
+        fs::write(
+            collection.join("collection.json"),
+            r#"{"info":{"name":"Ultimate Survival","author":"someone"},"mods":[]}"#,
+        )
+        .unwrap();
+
+        let pack = find_collection(staging.path()).unwrap();
+        assert_eq!(pack.name, "Ultimate Survival");
+        assert_eq!(pack.version, None);
+        assert_eq!(find_collection(&staging.path().join("SkyUI")), None);
+    }
+
+    #[test]
+    fn names_are_fitted_to_the_report() {
+        assert_eq!(Modpack::new("  ", Some("1.0")), None);
+        let pack = Modpack::new(&"é".repeat(MAX_NAME_LEN), Some(&"9".repeat(51))).unwrap();
+        assert!(pack.name.len() <= MAX_NAME_LEN);
+        assert_eq!(pack.version, None);
+
+        assert_eq!(
+            instance_dirs(&Mo2Instance::new(
+                "D:/Lorerim/profiles/Lorerim",
+                "D:/Lorerim/mods"
+            )),
+            [PathBuf::from("D:/Lorerim")]
+        );
+        assert!(vortex_staging_dir("palworld").is_none());
+    }
+}
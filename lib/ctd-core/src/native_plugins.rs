@@ -0,0 +1,83 @@
+//! Script extender DLL plugin scanning.
+//!
+//! The load order only lists ESP/ESM/ESL plugins, but most crashes in a
+//! modded Bethesda game come from native DLL plugins loaded by the script
+//! extender (SKSE, F4SE, FOSE, or NVSE), such as NVAC or JIP in the older
+//! games. This module fingerprints every DLL in the script extender's
+//! `Plugins` directory so reports carry them in a separate
+//! `nativePlugins` section.
+
+use std::fs;
+use std::path::Path;
+
+use tracing::debug;
+
+use crate::file_hash::CachedHasher;
+use crate::load_order::{DEFAULT_HASH_BUDGET, ModList};
+use crate::version::get_dll_version;
+
+/// Scans `dir` (e.g., `Data/SKSE/Plugins`) for DLL plugins.
+///
+/// Each entry has the DLL's file name, hash, size, and PE file version
+/// when it has one. Entries are sorted by name so reports are stable.
+/// Returns an empty list if the directory doesn't exist. Hashes go through
+/// `hasher`, so unchanged DLLs are not read again.
+pub fn scan_native_plugins(dir: &Path, hasher: &mut CachedHasher) -> ModList {
+    let Ok(entries) = fs::read_dir(dir) else {
+        debug!("No native plugin directory at {}", dir.display());
+        return ModList::new();
+    };
+
+    let mut dlls: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("dll"))
+        })
+        .collect();
+    dlls.sort_by_key(|path| path.file_name().map(|name| name.to_ascii_lowercase()));
+
+    ModList::fingerprint_parallel(&dlls, DEFAULT_HASH_BUDGET, hasher)
+        .into_iter()
+        .zip(&dlls)
+        .map(|(entry, path)| {
+            let entry = entry.with_enabled(true);
+            match get_dll_version(path) {
+                Ok(version) => entry.with_version(version),
+                Err(_) => entry,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_dlls_sorted_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("po3_Tweaks.dll"), b"tweaks").unwrap();
+        fs::write(dir.path().join("EngineFixes.DLL"), b"fixes").unwrap();
+        fs::write(dir.path().join("EngineFixes.toml"), b"[Patches]").unwrap();
+        fs::create_dir(dir.path().join("folder.dll")).unwrap();
+
+        let mut hasher = CachedHasher::open(dir.path().join("hash-cache.json"));
+        let plugins = scan_native_plugins(dir.path(), &mut hasher);
+        let names: Vec<&str> = plugins.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["EngineFixes.DLL", "po3_Tweaks.dll"]);
+        assert_eq!(plugins.0[1].index, Some(1));
+        assert_eq!(plugins.0[1].file_size, 6);
+        assert_ne!(plugins.0[1].file_hash, "0000000000000000");
+    }
+
+    #[test]
+    fn missing_directory_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut hasher = CachedHasher::open(dir.path().join("hash-cache.json"));
+        assert!(scan_native_plugins(&dir.path().join("SKSE/Plugins"), &mut hasher).is_empty());
+    }
+}
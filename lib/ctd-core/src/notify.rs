@@ -0,0 +1,202 @@
+//! Notifications after a report is submitted.
+//!
+//! Modpack authors want to hear about their testers' crashes where they
+//! already talk. With `[notify.discord] webhook_url` set,
+//! [`ApiClient`](crate::api_client::ApiClient) posts a compact embed to the
+//! channel after each accepted report: the game, the exception, the
+//! suspected mod, and the share link. Notifications are best effort; a
+//! failed post is logged and never fails the submission.
+
+use serde_json::{Value, json};
+use tracing::{debug, warn};
+
+use crate::crash_report::{CrashReportResponse, CreateCrashReport, ReportType};
+use crate::import::exception_name;
+use crate::{CtdError, Result};
+
+/// Name the webhook posts under.
+const USERNAME: &str = "CTD";
+
+/// Embed colors by report type, as Discord's 24-bit integers.
+const CRASH_COLOR: u32 = 0xE74C3C;
+const HANG_COLOR: u32 = 0xE67E22;
+const INFO_COLOR: u32 = 0x3498DB;
+
+/// A Discord channel webhook.
+#[derive(Debug, Clone)]
+pub struct DiscordWebhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl DiscordWebhook {
+    /// Creates a webhook that posts to `url` with `client`.
+    pub fn new(url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+
+    /// Posts the embed for a submitted report, logging failures.
+    pub async fn notify(
+        &self,
+        report: &CreateCrashReport,
+        response: &CrashReportResponse,
+        share_url: Option<&str>,
+    ) {
+        match self.post(&message(report, response, share_url)).await {
+            Ok(()) => debug!("Posted Discord notification for {}", response.id),
+            Err(e) => warn!("Discord notification failed: {}", e),
+        }
+    }
+
+    /// Posts a webhook message.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::ApiRequest` if Discord cannot be reached or
+    /// rejects the message.
+    pub async fn post(&self, message: &Value) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(message)
+            .send()
+            .await
+            .map_err(|e| CtdError::ApiRequest(format!("Cannot reach Discord: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(CtdError::ApiRequest(format!(
+                "Discord returned status {}: {}",
+                status, body
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Builds the webhook message for a submitted report.
+pub fn message(
+    report: &CreateCrashReport,
+    response: &CrashReportResponse,
+    share_url: Option<&str>,
+) -> Value {
+    let (kind, color) = match report.report_type {
+        ReportType::Crash => ("Crash", CRASH_COLOR),
+        ReportType::Hang => ("Hang", HANG_COLOR),
+        ReportType::ScriptError => ("Script error", HANG_COLOR),
+        ReportType::Manual => ("Manual report", INFO_COLOR),
+        ReportType::Session => ("Session", INFO_COLOR),
+    };
+
+    let mut fields = vec![json!({
+        "name": "Game",
+        "value": format!("{} {}", report.game_id, report.game_version),
+        "inline": true,
+    })];
+    if let Some(exception) = exception(report) {
+        fields.push(json!({ "name": "Exception", "value": exception, "inline": true }));
+    }
+    if let Some(suspect) = &report.suspected_mod {
+        fields.push(json!({ "name": "Suspected mod", "value": suspect }));
+    }
+    if let Some(url) = share_url {
+        fields.push(json!({ "name": "Report", "value": url }));
+    }
+
+    let mut embed = json!({
+        "title": format!("{} in {}", kind, report.game_id),
+        "color": color,
+        "fields": fields,
+        "footer": { "text": response.id },
+    });
+    if let Some(url) = share_url {
+        embed["url"] = json!(url);
+    }
+
+    json!({ "username": USERNAME, "embeds": [embed] })
+}
+
+/// Formats the exception as `EXCEPTION_ACCESS_VIOLATION in SkyrimSE.exe`,
+/// falling back to the code for unnamed exceptions.
+fn exception(report: &CreateCrashReport) -> Option<String> {
+    let code = report.exception_code.as_deref()?;
+    let name = exception_name(code).unwrap_or(code);
+
+    Some(match &report.faulting_module {
+        Some(module) => format!("{} in {}", name, module),
+        None => name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModList;
+
+    fn sample_report() -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("[0] SkyrimSE.exe+0x1234")
+            .exception_code("0xC0000005")
+            .faulting_module("SkyrimSE.exe")
+            .suspected_mod("Engine Fixes")
+            .load_order_v2(ModList::new())
+            .crashed_at(1000)
+            .build()
+            .unwrap()
+    }
+
+    fn response() -> CrashReportResponse {
+        CrashReportResponse {
+            id: "01HX".into(),
+            share_token: "tok".into(),
+        }
+    }
+
+    #[test]
+    fn crash_message_has_compact_fields() {
+        let message = message(
+            &sample_report(),
+            &response(),
+            Some("https://ctd.example/crashes/01HX?token=tok"),
+        );
+        let embed = &message["embeds"][0];
+
+        assert_eq!(embed["title"], "Crash in skyrim-se");
+        assert_eq!(embed["color"], CRASH_COLOR);
+        assert_eq!(embed["url"], "https://ctd.example/crashes/01HX?token=tok");
+        let fields = embed["fields"].as_array().unwrap();
+        let values: Vec<&str> = fields
+            .iter()
+            .map(|f| f["value"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                "skyrim-se 1.6.1170",
+                "EXCEPTION_ACCESS_VIOLATION in SkyrimSE.exe",
+                "Engine Fixes",
+                "https://ctd.example/crashes/01HX?token=tok",
+            ]
+        );
+    }
+
+    #[test]
+    fn hang_without_exception_or_link() {
+        let mut report = sample_report();
+        report.report_type = ReportType::Hang;
+        report.exception_code = None;
+        report.suspected_mod = None;
+
+        let message = message(&report, &response(), None);
+        let embed = &message["embeds"][0];
+        assert_eq!(embed["title"], "Hang in skyrim-se");
+        assert!(embed.get("url").is_none());
+        assert_eq!(embed["fields"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,595 @@
+//! Minimal PE (Portable Executable) header parsing.
+//!
+//! This module reads just enough of a DLL/EXE to locate its data directories
+//! and sections. It is used to extract the CodeView debug record (PDB GUID,
+//! age, and path) so symbol files can be matched to the exact build of a
+//! module instead of by filename alone, and to list a DLL's exports, which
+//! tell graphics injectors such as ENB and ReShade apart and give the entry
+//! points checked for detours.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use thiserror::Error;
+
+/// Errors that can occur when parsing a PE file.
+#[derive(Error, Debug)]
+pub enum PeError {
+    /// Failed to read the file.
+    #[error("Failed to read PE file: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// The file is not a valid PE image.
+    #[error("Invalid PE image: {0}")]
+    Invalid(&'static str),
+
+    /// The image has no CodeView (RSDS) debug record.
+    #[error("No CodeView debug record found")]
+    NoCodeView,
+}
+
+/// Index of the export table in the data directory array.
+pub const DIRECTORY_EXPORT: usize = 0;
+
+/// Index of the debug directory in the data directory array.
+pub const DIRECTORY_DEBUG: usize = 6;
+
+/// Debug directory entry type for CodeView records.
+const DEBUG_TYPE_CODEVIEW: u32 = 2;
+
+/// Size of one `IMAGE_DEBUG_DIRECTORY` entry.
+const DEBUG_ENTRY_SIZE: u32 = 28;
+
+/// Size of one `IMAGE_SECTION_HEADER`.
+const SECTION_HEADER_SIZE: u64 = 40;
+
+/// Upper bound on the PDB path read from a CodeView record.
+const MAX_CODEVIEW_SIZE: u32 = 4096;
+
+/// Size of `IMAGE_EXPORT_DIRECTORY`.
+const EXPORT_DIRECTORY_SIZE: usize = 40;
+
+/// Upper bound on the number of export names read from an image.
+const MAX_EXPORT_NAMES: u32 = 65536;
+
+/// Upper bound on the length of one export name.
+const MAX_EXPORT_NAME_LEN: usize = 512;
+
+/// Identifies one specific build of a PDB (GUID + age).
+///
+/// The same identity is stored in the module's CodeView record and in the
+/// PDB itself, so comparing them detects stale or mismatched symbol files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DebugId {
+    /// GUID bytes exactly as stored on disk (little-endian fields).
+    pub guid: [u8; 16],
+    /// Incremented each time the PDB is rewritten by the linker.
+    pub age: u32,
+}
+
+impl DebugId {
+    /// Creates a debug ID from the GUID's component fields.
+    pub fn from_fields(data1: u32, data2: u16, data3: u16, data4: [u8; 8], age: u32) -> Self {
+        let mut guid = [0u8; 16];
+        guid[0..4].copy_from_slice(&data1.to_le_bytes());
+        guid[4..6].copy_from_slice(&data2.to_le_bytes());
+        guid[6..8].copy_from_slice(&data3.to_le_bytes());
+        guid[8..16].copy_from_slice(&data4);
+        Self { guid, age }
+    }
+
+    /// Formats the GUID in registry form (e.g., "3844DBB9-2017-4967-BE7A-A4A2C20430FA").
+    pub fn guid_string(&self) -> String {
+        let g = &self.guid;
+        format!(
+            "{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{}",
+            u32::from_le_bytes([g[0], g[1], g[2], g[3]]),
+            u16::from_le_bytes([g[4], g[5]]),
+            u16::from_le_bytes([g[6], g[7]]),
+            g[8],
+            g[9],
+            hex::encode_upper(&g[10..16])
+        )
+    }
+
+    /// Formats the symbol-server key: GUID without dashes followed by the age in hex.
+    pub fn signature(&self) -> String {
+        format!("{}{:X}", self.guid_string().replace('-', ""), self.age)
+    }
+}
+
+/// The CodeView (RSDS) debug record of a PE image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeViewInfo {
+    /// GUID and age of the PDB the module was linked with.
+    pub id: DebugId,
+    /// PDB path recorded by the linker (usually an absolute build path).
+    pub pdb_path: String,
+}
+
+impl CodeViewInfo {
+    /// Returns just the file name of the recorded PDB path.
+    pub fn pdb_file_name(&self) -> Option<&str> {
+        self.pdb_path
+            .rsplit(['\\', '/'])
+            .next()
+            .filter(|name| !name.is_empty())
+    }
+}
+
+/// A function exported by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    /// Exported name (e.g., "D3D11CreateDevice").
+    pub name: String,
+    /// RVA of the function's first instruction.
+    pub rva: u32,
+}
+
+/// A section header from the PE section table.
+#[derive(Debug, Clone, Copy)]
+pub struct Section {
+    /// RVA where the section is mapped.
+    pub virtual_address: u32,
+    /// Size of the section in memory.
+    pub virtual_size: u32,
+    /// File offset of the section's raw data.
+    pub raw_offset: u32,
+    /// Size of the section's raw data on disk.
+    pub raw_size: u32,
+}
+
+/// An entry from the optional header's data directory array.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataDirectory {
+    /// RVA of the directory.
+    pub rva: u32,
+    /// Size of the directory in bytes.
+    pub size: u32,
+}
+
+/// A parsed PE image backed by a seekable reader.
+pub struct PeImage<R> {
+    reader: R,
+    is_64: bool,
+    sections: Vec<Section>,
+    directories: Vec<DataDirectory>,
+}
+
+impl PeImage<BufReader<File>> {
+    /// Opens and parses the PE headers of a file on disk.
+    pub fn open(path: &Path) -> Result<Self, PeError> {
+        Self::parse(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> PeImage<R> {
+    /// Parses the PE headers from a reader.
+    pub fn parse(mut reader: R) -> Result<Self, PeError> {
+        let dos = read_at(&mut reader, 0, 64)?;
+        if &dos[0..2] != b"MZ" {
+            return Err(PeError::Invalid("missing MZ signature"));
+        }
+        let pe_offset = u64::from(le_u32(&dos, 0x3C));
+
+        let nt = read_at(&mut reader, pe_offset, 24)?;
+        if &nt[0..4] != b"PE\0\0" {
+            return Err(PeError::Invalid("missing PE signature"));
+        }
+        let section_count = u64::from(le_u16(&nt, 6));
+        let optional_size = le_u16(&nt, 20);
+
+        let optional = read_at(&mut reader, pe_offset + 24, usize::from(optional_size))?;
+        if optional.len() < 2 {
+            return Err(PeError::Invalid("optional header too small"));
+        }
+        let is_64 = match le_u16(&optional, 0) {
+            0x10B => false,
+            0x20B => true,
+            _ => return Err(PeError::Invalid("unknown optional header magic")),
+        };
+
+        // NumberOfRvaAndSizes precedes the data directory array
+        let (count_offset, dirs_offset) = if is_64 { (108, 112) } else { (92, 96) };
+        if optional.len() < dirs_offset {
+            return Err(PeError::Invalid("optional header too small"));
+        }
+        let dir_count = le_u32(&optional, count_offset) as usize;
+        let available = (optional.len() - dirs_offset) / 8;
+        let directories = (0..dir_count.min(available))
+            .map(|i| DataDirectory {
+                rva: le_u32(&optional, dirs_offset + i * 8),
+                size: le_u32(&optional, dirs_offset + i * 8 + 4),
+            })
+            .collect();
+
+        let table_offset = pe_offset + 24 + u64::from(optional_size);
+        let table = read_at(
+            &mut reader,
+            table_offset,
+            (section_count * SECTION_HEADER_SIZE) as usize,
+        )?;
+        let sections = table
+            .chunks_exact(SECTION_HEADER_SIZE as usize)
+            .map(|s| Section {
+                virtual_size: le_u32(s, 8),
+                virtual_address: le_u32(s, 12),
+                raw_size: le_u32(s, 16),
+                raw_offset: le_u32(s, 20),
+            })
+            .collect();
+
+        Ok(Self {
+            reader,
+            is_64,
+            sections,
+            directories,
+        })
+    }
+
+    /// Returns true for PE32+ (64-bit) images.
+    pub fn is_64(&self) -> bool {
+        self.is_64
+    }
+
+    /// Returns the section table.
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Returns a data directory by index, if present and non-empty.
+    pub fn directory(&self, index: usize) -> Option<DataDirectory> {
+        self.directories
+            .get(index)
+            .copied()
+            .filter(|d| d.rva != 0 && d.size != 0)
+    }
+
+    /// Converts an RVA to a file offset using the section table.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<u64> {
+        self.sections.iter().find_map(|s| {
+            let span = s.virtual_size.max(s.raw_size);
+            (rva >= s.virtual_address && rva - s.virtual_address < span)
+                .then(|| u64::from(rva - s.virtual_address) + u64::from(s.raw_offset))
+        })
+    }
+
+    /// Reads `len` bytes starting at an RVA.
+    pub fn read_rva(&mut self, rva: u32, len: usize) -> Result<Vec<u8>, PeError> {
+        let offset = self
+            .rva_to_offset(rva)
+            .ok_or(PeError::Invalid("RVA outside of any section"))?;
+        read_at(&mut self.reader, offset, len)
+    }
+
+    /// Extracts the CodeView (RSDS) record from the debug directory.
+    pub fn codeview(&mut self) -> Result<CodeViewInfo, PeError> {
+        let debug = self.directory(DIRECTORY_DEBUG).ok_or(PeError::NoCodeView)?;
+        let entries = self.read_rva(debug.rva, debug.size as usize)?;
+
+        for entry in entries.chunks_exact(DEBUG_ENTRY_SIZE as usize) {
+            if le_u32(entry, 12) != DEBUG_TYPE_CODEVIEW {
+                continue;
+            }
+
+            let size = le_u32(entry, 16).min(MAX_CODEVIEW_SIZE);
+            let raw_offset = u64::from(le_u32(entry, 24));
+            if size < 24 {
+                continue;
+            }
+
+            let record = read_at(&mut self.reader, raw_offset, size as usize)?;
+            if &record[0..4] != b"RSDS" {
+                continue;
+            }
+
+            let mut guid = [0u8; 16];
+            guid.copy_from_slice(&record[4..20]);
+            let age = le_u32(&record, 20);
+
+            let path_bytes = &record[24..];
+            let end = path_bytes
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(path_bytes.len());
+
+            return Ok(CodeViewInfo {
+                id: DebugId { guid, age },
+                pdb_path: String::from_utf8_lossy(&path_bytes[..end]).into_owned(),
+            });
+        }
+
+        Err(PeError::NoCodeView)
+    }
+
+    /// Returns the names of the functions the image exports by name.
+    ///
+    /// Images without an export table return an empty list.
+    pub fn export_names(&mut self) -> Result<Vec<String>, PeError> {
+        let Some(exports) = self.directory(DIRECTORY_EXPORT) else {
+            return Ok(Vec::new());
+        };
+        let dir = self.read_rva(exports.rva, EXPORT_DIRECTORY_SIZE)?;
+        let count = le_u32(&dir, 24).min(MAX_EXPORT_NAMES) as usize;
+        let names_rva = le_u32(&dir, 32);
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let table = self.read_rva(names_rva, count * 4)?;
+        let mut names = Vec::with_capacity(count);
+        for i in 0..count {
+            let name = self.read_cstr_rva(le_u32(&table, i * 4))?;
+            names.push(name);
+        }
+        Ok(names)
+    }
+
+    /// Returns the functions the image exports by name, with their RVAs.
+    ///
+    /// Forwarded exports, which name a function in another DLL instead of
+    /// code in this one, are left out. Images without an export table
+    /// return an empty list.
+    pub fn exports(&mut self) -> Result<Vec<Export>, PeError> {
+        let Some(exports) = self.directory(DIRECTORY_EXPORT) else {
+            return Ok(Vec::new());
+        };
+        let dir = self.read_rva(exports.rva, EXPORT_DIRECTORY_SIZE)?;
+        let function_count = le_u32(&dir, 20).min(MAX_EXPORT_NAMES) as usize;
+        let count = le_u32(&dir, 24).min(MAX_EXPORT_NAMES) as usize;
+        if count == 0 || function_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let functions = self.read_rva(le_u32(&dir, 28), function_count * 4)?;
+        let names = self.read_rva(le_u32(&dir, 32), count * 4)?;
+        let ordinals = self.read_rva(le_u32(&dir, 36), count * 2)?;
+
+        let mut result = Vec::with_capacity(count);
+        for i in 0..count {
+            let index = usize::from(le_u16(&ordinals, i * 2));
+            if index >= function_count {
+                continue;
+            }
+            let rva = le_u32(&functions, index * 4);
+            // A forwarder's RVA points at its "DLL.Function" string
+            if rva.wrapping_sub(exports.rva) < exports.size {
+                continue;
+            }
+            let name = self.read_cstr_rva(le_u32(&names, i * 4))?;
+            result.push(Export { name, rva });
+        }
+        Ok(result)
+    }
+
+    /// Reads a NUL-terminated ASCII string starting at an RVA.
+    fn read_cstr_rva(&mut self, rva: u32) -> Result<String, PeError> {
+        let offset = self
+            .rva_to_offset(rva)
+            .ok_or(PeError::Invalid("RVA outside of any section"))?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        let mut buf = Vec::new();
+        (&mut self.reader)
+            .take(MAX_EXPORT_NAME_LEN as u64)
+            .read_to_end(&mut buf)?;
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        Ok(String::from_utf8_lossy(&buf[..end]).into_owned())
+    }
+}
+
+/// Reads the CodeView record of a PE file on disk.
+///
+/// This is a convenience wrapper around [`PeImage::open`] and [`PeImage::codeview`].
+pub fn read_codeview(path: &Path) -> Result<CodeViewInfo, PeError> {
+    PeImage::open(path)?.codeview()
+}
+
+/// Reads exactly `len` bytes at `offset`.
+fn read_at<R: Read + Seek>(reader: &mut R, offset: u64, len: usize) -> Result<Vec<u8>, PeError> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut buf = vec![0u8; len];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn le_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn le_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const TEST_GUID: [u8; 16] = [
+        0xB9, 0xDB, 0x44, 0x38, 0x17, 0x20, 0x67, 0x49, 0xBE, 0x7A, 0xA4, 0xA2, 0xC2, 0x04, 0x30,
+        0xFA,
+    ];
+
+    /// Builds a minimal PE32+ image with one section holding a debug
+    /// directory and a CodeView record.
+    pub(crate) fn build_test_pe(guid: [u8; 16], age: u32, pdb_path: &str) -> Vec<u8> {
+        let mut image = vec![0u8; 0x400];
+        image[0..2].copy_from_slice(b"MZ");
+        image[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+
+        // PE signature + COFF header
+        image[0x80..0x84].copy_from_slice(b"PE\0\0");
+        image[0x84..0x86].copy_from_slice(&0x8664u16.to_le_bytes());
+        image[0x86..0x88].copy_from_slice(&1u16.to_le_bytes()); // one section
+        image[0x94..0x96].copy_from_slice(&240u16.to_le_bytes()); // optional header size
+
+        // Optional header (PE32+) with 16 data directories
+        let opt = 0x98;
+        image[opt..opt + 2].copy_from_slice(&0x20Bu16.to_le_bytes());
+        image[opt + 108..opt + 112].copy_from_slice(&16u32.to_le_bytes());
+        let debug_dir = opt + 112 + DIRECTORY_DEBUG * 8;
+        image[debug_dir..debug_dir + 4].copy_from_slice(&0x1000u32.to_le_bytes());
+        image[debug_dir + 4..debug_dir + 8].copy_from_slice(&DEBUG_ENTRY_SIZE.to_le_bytes());
+
+        // Section table: .rdata mapped at RVA 0x1000, file offset 0x200
+        let sec = opt + 240;
+        image[sec..sec + 6].copy_from_slice(b".rdata");
+        image[sec + 8..sec + 12].copy_from_slice(&0x200u32.to_le_bytes());
+        image[sec + 12..sec + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        image[sec + 16..sec + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        image[sec + 20..sec + 24].copy_from_slice(&0x200u32.to_le_bytes());
+
+        // Debug directory entry at file offset 0x200 pointing at 0x240
+        let record_len = 24 + pdb_path.len() as u32 + 1;
+        let entry = 0x200;
+        image[entry + 12..entry + 16].copy_from_slice(&DEBUG_TYPE_CODEVIEW.to_le_bytes());
+        image[entry + 16..entry + 20].copy_from_slice(&record_len.to_le_bytes());
+        image[entry + 20..entry + 24].copy_from_slice(&0x1040u32.to_le_bytes());
+        image[entry + 24..entry + 28].copy_from_slice(&0x240u32.to_le_bytes());
+
+        // RSDS record
+        let rec = 0x240;
+        image[rec..rec + 4].copy_from_slice(b"RSDS");
+        image[rec + 4..rec + 20].copy_from_slice(&guid);
+        image[rec + 20..rec + 24].copy_from_slice(&age.to_le_bytes());
+        image[rec + 24..rec + 24 + pdb_path.len()].copy_from_slice(pdb_path.as_bytes());
+
+        image
+    }
+
+    #[test]
+    fn parses_codeview_record() {
+        let image = build_test_pe(TEST_GUID, 3, "C:\\build\\MyMod.pdb");
+        let mut pe = PeImage::parse(Cursor::new(image)).unwrap();
+
+        assert!(pe.is_64());
+        let cv = pe.codeview().unwrap();
+        assert_eq!(cv.id.guid, TEST_GUID);
+        assert_eq!(cv.id.age, 3);
+        assert_eq!(cv.pdb_path, "C:\\build\\MyMod.pdb");
+        assert_eq!(cv.pdb_file_name(), Some("MyMod.pdb"));
+    }
+
+    #[test]
+    fn debug_id_formats_guid_and_signature() {
+        let id = DebugId {
+            guid: TEST_GUID,
+            age: 2,
+        };
+        assert_eq!(id.guid_string(), "3844DBB9-2017-4967-BE7A-A4A2C20430FA");
+        assert_eq!(id.signature(), "3844DBB920174967BE7AA4A2C20430FA2");
+    }
+
+    #[test]
+    fn debug_id_from_fields_matches_raw_bytes() {
+        let id = DebugId::from_fields(
+            0x3844DBB9,
+            0x2017,
+            0x4967,
+            [0xBE, 0x7A, 0xA4, 0xA2, 0xC2, 0x04, 0x30, 0xFA],
+            1,
+        );
+        assert_eq!(id.guid, TEST_GUID);
+    }
+
+    #[test]
+    fn reads_export_names() {
+        let mut image = build_test_pe(TEST_GUID, 1, "d3d11.pdb");
+
+        // Export directory at RVA 0x1100 (file 0x300) naming two functions
+        let opt = 0x98;
+        let export_dir = opt + 112 + DIRECTORY_EXPORT * 8;
+        image[export_dir..export_dir + 4].copy_from_slice(&0x1100u32.to_le_bytes());
+        image[export_dir + 4..export_dir + 8].copy_from_slice(&0x80u32.to_le_bytes());
+
+        let dir = 0x300;
+        image[dir + 24..dir + 28].copy_from_slice(&2u32.to_le_bytes());
+        image[dir + 32..dir + 36].copy_from_slice(&0x1130u32.to_le_bytes());
+        image[0x330..0x334].copy_from_slice(&0x1140u32.to_le_bytes());
+        image[0x334..0x338].copy_from_slice(&0x1150u32.to_le_bytes());
+        image[0x340..0x34E].copy_from_slice(b"ENBGetVersion\0");
+        image[0x350..0x361].copy_from_slice(b"D3D11CreateDevice");
+
+        let mut pe = PeImage::parse(Cursor::new(image)).unwrap();
+        assert_eq!(
+            pe.export_names().unwrap(),
+            vec!["ENBGetVersion", "D3D11CreateDevice"]
+        );
+
+        let image = build_test_pe(TEST_GUID, 1, "a.pdb");
+        let mut pe = PeImage::parse(Cursor::new(image)).unwrap();
+        assert!(pe.export_names().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reads_exports_without_forwarders() {
+        let mut image = build_test_pe(TEST_GUID, 1, "d3d11.pdb");
+
+        // Export directory at RVA 0x1100 (file 0x300), size 0x80
+        let opt = 0x98;
+        let export_dir = opt + 112 + DIRECTORY_EXPORT * 8;
+        image[export_dir..export_dir + 4].copy_from_slice(&0x1100u32.to_le_bytes());
+        image[export_dir + 4..export_dir + 8].copy_from_slice(&0x80u32.to_le_bytes());
+
+        let dir = 0x300;
+        image[dir + 20..dir + 24].copy_from_slice(&2u32.to_le_bytes());
+        image[dir + 24..dir + 28].copy_from_slice(&2u32.to_le_bytes());
+        image[dir + 28..dir + 32].copy_from_slice(&0x1160u32.to_le_bytes());
+        image[dir + 32..dir + 36].copy_from_slice(&0x1130u32.to_le_bytes());
+        image[dir + 36..dir + 40].copy_from_slice(&0x1138u32.to_le_bytes());
+        // Names
+        image[0x330..0x334].copy_from_slice(&0x1140u32.to_le_bytes());
+        image[0x334..0x338].copy_from_slice(&0x1150u32.to_le_bytes());
+        // Ordinals, in reverse
+        image[0x338..0x33A].copy_from_slice(&1u16.to_le_bytes());
+        image[0x33A..0x33C].copy_from_slice(&0u16.to_le_bytes());
+        image[0x340..0x34E].copy_from_slice(b"ENBGetVersion\0");
+        image[0x350..0x35A].copy_from_slice(b"Forwarded\0");
+        // Functions: the first is a forwarder inside the export directory
+        image[0x360..0x364].copy_from_slice(&0x1170u32.to_le_bytes());
+        image[0x364..0x368].copy_from_slice(&0x2040u32.to_le_bytes());
+
+        let mut pe = PeImage::parse(Cursor::new(image)).unwrap();
+        assert_eq!(
+            pe.exports().unwrap(),
+            vec![Export {
+                name: "ENBGetVersion".into(),
+                rva: 0x2040,
+            }]
+        );
+    }
+
+    #[test]
+    fn rva_to_offset_uses_section_table() {
+        let image = build_test_pe(TEST_GUID, 1, "a.pdb");
+        let pe = PeImage::parse(Cursor::new(image)).unwrap();
+
+        assert_eq!(pe.rva_to_offset(0x1000), Some(0x200));
+        assert_eq!(pe.rva_to_offset(0x1040), Some(0x240));
+        assert_eq!(pe.rva_to_offset(0x5000), None);
+    }
+
+    #[test]
+    fn rejects_non_pe_data() {
+        let result = PeImage::parse(Cursor::new(vec![0u8; 128]));
+        assert!(matches!(result, Err(PeError::Invalid(_))));
+    }
+
+    #[test]
+    fn missing_debug_directory_is_no_codeview() {
+        let mut image = build_test_pe(TEST_GUID, 1, "a.pdb");
+        // Clear the debug directory entry
+        let debug_dir = 0x98 + 112 + DIRECTORY_DEBUG * 8;
+        image[debug_dir..debug_dir + 8].fill(0);
+
+        let mut pe = PeImage::parse(Cursor::new(image)).unwrap();
+        assert!(matches!(pe.codeview(), Err(PeError::NoCodeView)));
+    }
+}
@@ -0,0 +1,298 @@
+//! TES4 header parsing for Bethesda plugin files (ESP/ESM/ESL).
+//!
+//! Every plugin starts with a `TES4` record holding its masters, author,
+//! and record count. Reading just that record is cheap, and the master
+//! list lets a report show exactly which plugin is missing a dependency
+//! when the game crashes on load.
+//!
+//! Supports the 24-byte record header of Skyrim, Fallout 3/NV, and
+//! Fallout 4, and the 20-byte header of Oblivion.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use thiserror::Error;
+
+/// Record flag: plugin is a master (ESM).
+const FLAG_MASTER: u32 = 0x0000_0001;
+
+/// Record flag: strings live in separate `.STRINGS` files.
+const FLAG_LOCALIZED: u32 = 0x0000_0080;
+
+/// Record flag: plugin is light (ESL) and loads into the `FE` slot.
+const FLAG_LIGHT: u32 = 0x0000_0200;
+
+/// Largest TES4 record accepted; real headers are a few KB even with 250+ masters.
+const MAX_HEADER_SIZE: usize = 1024 * 1024;
+
+/// Errors that can occur when reading a plugin header.
+#[derive(Error, Debug)]
+pub enum HeaderError {
+    /// Failed to open or read the file.
+    #[error("Failed to read plugin: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// The file does not start with a `TES4` record.
+    #[error("Not a plugin file (no TES4 record)")]
+    NotAPlugin,
+
+    /// The header ends in the middle of a field.
+    #[error("Truncated plugin header: {0}")]
+    Truncated(String),
+}
+
+/// The fields of a plugin's `TES4` record that matter for crash diagnosis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginHeader {
+    /// Plugin format version from `HEDR` (e.g., 1.71 for Skyrim SE).
+    pub version: f32,
+    /// Number of records and groups in the plugin.
+    pub record_count: u32,
+    /// Next available object ID.
+    pub next_object_id: u32,
+    /// Author (`CNAM`), if set.
+    pub author: Option<String>,
+    /// Description (`SNAM`), if set.
+    pub description: Option<String>,
+    /// Master files this plugin depends on, in load order.
+    pub masters: Vec<String>,
+    /// ESM flag.
+    pub is_master: bool,
+    /// ESL flag.
+    pub is_light: bool,
+    /// Localized strings flag.
+    pub is_localized: bool,
+}
+
+/// Reads the header of a plugin file without loading the rest of it.
+///
+/// # Errors
+///
+/// Returns `HeaderError` if the file cannot be read or is not a plugin.
+pub fn read_header(path: &Path) -> Result<PluginHeader, HeaderError> {
+    let mut file = File::open(path)?;
+
+    let mut prefix = [0u8; 24];
+    file.read_exact(&mut prefix)
+        .map_err(|_| HeaderError::NotAPlugin)?;
+    if &prefix[..4] != b"TES4" {
+        return Err(HeaderError::NotAPlugin);
+    }
+
+    let data_size = u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]) as usize;
+    if data_size > MAX_HEADER_SIZE {
+        return Err(HeaderError::Truncated(format!(
+            "TES4 record claims {} bytes",
+            data_size
+        )));
+    }
+
+    // Read enough for either header size; parse() works out which it is
+    let mut bytes = prefix.to_vec();
+    bytes.resize(24 + data_size, 0);
+    let read = read_up_to(&mut file, &mut bytes[24..])?;
+    bytes.truncate(24 + read);
+
+    parse_header(&bytes)
+}
+
+/// Parses a plugin header from the start of a plugin file.
+///
+/// # Errors
+///
+/// Returns `HeaderError::NotAPlugin` if `bytes` doesn't start with a
+/// `TES4` record, or `HeaderError::Truncated` if the record is cut short.
+pub fn parse_header(bytes: &[u8]) -> Result<PluginHeader, HeaderError> {
+    if bytes.len() < 20 || &bytes[..4] != b"TES4" {
+        return Err(HeaderError::NotAPlugin);
+    }
+
+    let data_size = read_u32(bytes, 4)? as usize;
+    let flags = read_u32(bytes, 8)?;
+
+    // Oblivion's record header is 20 bytes; everything later uses 24
+    let header_size = if bytes.get(20..24) == Some(b"HEDR") {
+        20
+    } else {
+        24
+    };
+    let data = bytes
+        .get(header_size..header_size + data_size)
+        .ok_or_else(|| {
+            HeaderError::Truncated(format!("expected {} bytes of TES4 data", data_size))
+        })?;
+
+    let mut header = PluginHeader {
+        version: 0.0,
+        record_count: 0,
+        next_object_id: 0,
+        author: None,
+        description: None,
+        masters: Vec::new(),
+        is_master: flags & FLAG_MASTER != 0,
+        is_light: flags & FLAG_LIGHT != 0,
+        is_localized: flags & FLAG_LOCALIZED != 0,
+    };
+
+    let mut pos = 0;
+    let mut large_size = None;
+    while pos + 6 <= data.len() {
+        let kind = &data[pos..pos + 4];
+        let size = large_size
+            .take()
+            .unwrap_or(u16::from_le_bytes([data[pos + 4], data[pos + 5]]) as usize);
+        pos += 6;
+
+        let field = data.get(pos..pos + size).ok_or_else(|| {
+            HeaderError::Truncated(format!("{} subrecord", String::from_utf8_lossy(kind)))
+        })?;
+        pos += size;
+
+        match kind {
+            // Size of the next subrecord when it doesn't fit in a u16
+            b"XXXX" => large_size = Some(read_u32(field, 0)? as usize),
+            b"HEDR" => {
+                header.version = f32::from_bits(read_u32(field, 0)?);
+                header.record_count = read_u32(field, 4)?;
+                header.next_object_id = read_u32(field, 8)?;
+            }
+            b"CNAM" => header.author = Some(zstring(field)).filter(|s| !s.is_empty()),
+            b"SNAM" => header.description = Some(zstring(field)).filter(|s| !s.is_empty()),
+            b"MAST" => header.masters.push(zstring(field)),
+            _ => {}
+        }
+    }
+
+    Ok(header)
+}
+
+/// Reads a little-endian u32 at `offset`.
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, HeaderError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| HeaderError::Truncated(format!("u32 at offset {}", offset)))
+}
+
+/// Decodes a NUL-terminated string.
+///
+/// Plugins use the Windows-1252 code page; bytes that aren't valid UTF-8
+/// are mapped as Latin-1, which matches it for nearly all characters.
+fn zstring(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    let bytes = &field[..end];
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// Reads until `buf` is full or the file ends, returning the bytes read.
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        match file.read(&mut buf[total..])? {
+            0 => break,
+            n => total += n,
+        }
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a TES4 record with a 24-byte header.
+    fn tes4(flags: u32, subrecords: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (kind, field) in subrecords {
+            data.extend_from_slice(*kind);
+            data.extend_from_slice(&(field.len() as u16).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+
+        let mut bytes = b"TES4".to_vec();
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&flags.to_le_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    fn hedr(version: f32, records: u32) -> Vec<u8> {
+        let mut field = version.to_bits().to_le_bytes().to_vec();
+        field.extend_from_slice(&records.to_le_bytes());
+        field.extend_from_slice(&0x800u32.to_le_bytes());
+        field
+    }
+
+    #[test]
+    fn parses_masters_and_author() {
+        let bytes = tes4(
+            FLAG_MASTER | FLAG_LIGHT,
+            &[
+                (b"HEDR", hedr(1.71, 42)),
+                (b"CNAM", b"Arthmoor\0".to_vec()),
+                (b"MAST", b"Skyrim.esm\0".to_vec()),
+                (b"DATA", vec![0; 8]),
+                (b"MAST", b"Update.esm\0".to_vec()),
+                (b"DATA", vec![0; 8]),
+            ],
+        );
+
+        let header = parse_header(&bytes).unwrap();
+        assert_eq!(header.version, 1.71);
+        assert_eq!(header.record_count, 42);
+        assert_eq!(header.author.as_deref(), Some("Arthmoor"));
+        assert_eq!(header.masters, vec!["Skyrim.esm", "Update.esm"]);
+        assert!(header.is_master);
+        assert!(header.is_light);
+        assert!(!header.is_localized);
+    }
+
+    #[test]
+    fn parses_oblivion_header() {
+        let mut bytes = tes4(
+            0,
+            &[
+                (b"HEDR", hedr(1.0, 3)),
+                (b"MAST", b"Oblivion.esm\0".to_vec()),
+            ],
+        );
+        // Drop the last 4 bytes of the record header
+        bytes.drain(20..24);
+
+        let header = parse_header(&bytes).unwrap();
+        assert_eq!(header.masters, vec!["Oblivion.esm"]);
+        assert_eq!(header.record_count, 3);
+    }
+
+    #[test]
+    fn rejects_non_plugins() {
+        assert!(matches!(
+            parse_header(b"BSA\0 not a plugin at all"),
+            Err(HeaderError::NotAPlugin)
+        ));
+
+        let mut truncated = tes4(0, &[(b"MAST", b"Skyrim.esm\0".to_vec())]);
+        truncated.truncate(30);
+        assert!(matches!(
+            parse_header(&truncated),
+            Err(HeaderError::Truncated(_))
+        ));
+    }
+
+    #[test]
+    fn reads_header_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.esp");
+        let mut bytes = tes4(0, &[(b"MAST", b"Fallout4.esm\0".to_vec())]);
+        // Records after the header must not be read
+        bytes.extend_from_slice(b"GRUP....");
+        std::fs::write(&path, bytes).unwrap();
+
+        assert_eq!(read_header(&path).unwrap().masters, vec!["Fallout4.esm"]);
+    }
+}
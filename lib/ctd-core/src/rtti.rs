@@ -0,0 +1,408 @@
+//! Class names of the objects the crashing code was working on.
+//!
+//! A register or stack slot pointing at an `Actor` or a `NiNode` says more
+//! about a crash than the faulting address: which kind of game object was
+//! broken, and often which mod made it. MSVC emits run-time type
+//! information for every polymorphic class, reachable from the object
+//! itself: the object's first field is its vtable, the pointer just before
+//! the vtable is the class's complete object locator, and that leads to a
+//! type descriptor holding the decorated class name (`.?AVActor@@`).
+//! [`scan`] follows that chain from every general-purpose register and the
+//! top of the stack, and keeps the values that end at a well-formed name.
+//!
+//! All memory is read with `ReadProcessMemory`, so garbage values fail
+//! rather than fault.
+
+use std::collections::HashSet;
+
+use crate::crash_report::{MAX_PROBABLE_OBJECTS, ProbableObject};
+use crate::memory_map::{self, ReadMemory};
+
+/// x64 register names, in the order [`scan`] takes their values.
+pub const REGISTERS_64: [&str; 16] = [
+    "RAX", "RBX", "RCX", "RDX", "RSI", "RDI", "RBP", "RSP", "R8", "R9", "R10", "R11", "R12", "R13",
+    "R14", "R15",
+];
+
+/// x86 register names, in the order [`scan`] takes their values.
+pub const REGISTERS_32: [&str; 8] = ["EAX", "EBX", "ECX", "EDX", "ESI", "EDI", "EBP", "ESP"];
+
+/// Index of the stack pointer in both register orders.
+const STACK_POINTER: usize = 7;
+
+/// Stack slots checked above the stack pointer.
+const STACK_SLOTS: usize = 128;
+
+/// Longest decorated name read from a type descriptor.
+const MAX_NAME_LEN: usize = 512;
+
+/// Longest class name kept in a report.
+const MAX_CLASS_NAME_LEN: usize = 300;
+
+/// Values below this are small integers, not pointers.
+const MIN_POINTER: u64 = 0x10000;
+
+/// Returns the objects with MSVC RTTI that the crashed thread's registers
+/// and top stack slots point at, registers first, each object once.
+///
+/// `registers` holds the general-purpose register values in
+/// [`REGISTERS_64`] order, or [`REGISTERS_32`] order in a 32-bit process.
+/// Returns an empty list off Windows, where no memory can be read.
+pub fn scan(registers: &[u64]) -> Vec<ProbableObject> {
+    scan_with(
+        registers,
+        cfg!(target_pointer_width = "64"),
+        &memory_map::read,
+    )
+}
+
+/// Returns the general-purpose register values of a thread context, in
+/// [`REGISTERS_64`] order.
+#[cfg(all(windows, target_pointer_width = "64"))]
+pub fn context_registers(
+    context: &windows::Win32::System::Diagnostics::Debug::CONTEXT,
+) -> Vec<u64> {
+    vec![
+        context.Rax,
+        context.Rbx,
+        context.Rcx,
+        context.Rdx,
+        context.Rsi,
+        context.Rdi,
+        context.Rbp,
+        context.Rsp,
+        context.R8,
+        context.R9,
+        context.R10,
+        context.R11,
+        context.R12,
+        context.R13,
+        context.R14,
+        context.R15,
+    ]
+}
+
+/// Returns the general-purpose register values of a thread context, in
+/// [`REGISTERS_32`] order.
+#[cfg(all(windows, target_pointer_width = "32"))]
+pub fn context_registers(
+    context: &windows::Win32::System::Diagnostics::Debug::CONTEXT,
+) -> Vec<u64> {
+    [
+        context.Eax,
+        context.Ebx,
+        context.Ecx,
+        context.Edx,
+        context.Esi,
+        context.Edi,
+        context.Ebp,
+        context.Esp,
+    ]
+    .map(u64::from)
+    .to_vec()
+}
+
+/// Finds objects from register values and the stack above the stack
+/// pointer.
+fn scan_with(registers: &[u64], is_64: bool, read: ReadMemory) -> Vec<ProbableObject> {
+    let names: &[&str] = if is_64 { &REGISTERS_64 } else { &REGISTERS_32 };
+    let pointer_size = if is_64 { 8 } else { 4 };
+
+    let mut candidates: Vec<(String, u64)> = names
+        .iter()
+        .zip(registers)
+        .map(|(name, &value)| (name.to_string(), value))
+        .collect();
+    if let Some(&stack) = registers.get(STACK_POINTER) {
+        for slot in 0..STACK_SLOTS as u64 {
+            let offset = slot * pointer_size;
+            let Some(value) = read_pointer(stack + offset, is_64, read) else {
+                break;
+            };
+            let location = format!("{}+0x{:X}", names[STACK_POINTER], offset);
+            candidates.push((location, value));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|&(_, value)| value >= MIN_POINTER && seen.insert(value))
+        .filter_map(|(location, value)| {
+            Some(ProbableObject {
+                location,
+                address: format!("0x{:X}", value),
+                class_name: class_name(value, is_64, read)?,
+            })
+        })
+        .take(MAX_PROBABLE_OBJECTS)
+        .collect()
+}
+
+/// Returns the class of the object at `object`, or `None` if it has no
+/// readable RTTI.
+fn class_name(object: u64, is_64: bool, read: ReadMemory) -> Option<String> {
+    let pointer_size = if is_64 { 8 } else { 4 };
+    let vtable = read_pointer(object, is_64, read)?;
+    let locator = read_pointer(vtable.checked_sub(pointer_size)?, is_64, read)?;
+
+    let name_address = if is_64 {
+        // Signature 1; the descriptor and the locator itself are image RVAs
+        let fields = read(locator, 24)?;
+        if u32_at(&fields, 0) != 1 {
+            return None;
+        }
+        let image_base = locator.checked_sub(u64::from(u32_at(&fields, 20)))?;
+        image_base + u64::from(u32_at(&fields, 12)) + 16
+    } else {
+        // Signature 0; the descriptor is a plain pointer
+        let fields = read(locator, 16)?;
+        if u32_at(&fields, 0) != 0 {
+            return None;
+        }
+        u64::from(u32_at(&fields, 12)) + 8
+    };
+
+    let name = undecorate(&read_name(name_address, read)?)?;
+    Some(name.chars().take(MAX_CLASS_NAME_LEN).collect())
+}
+
+/// Reads a NUL-terminated name in 64-byte aligned chunks, which never cross
+/// into an unmapped page.
+fn read_name(address: u64, read: ReadMemory) -> Option<String> {
+    let mut name = Vec::new();
+    let mut at = address;
+    while name.len() < MAX_NAME_LEN {
+        let chunk_end = (at | 63) + 1;
+        let chunk = read(at, (chunk_end - at) as usize)?;
+        match chunk.iter().position(|&byte| byte == 0) {
+            Some(end) => {
+                name.extend(&chunk[..end]);
+                return String::from_utf8(name).ok();
+            }
+            None => name.extend(chunk),
+        }
+        at = chunk_end;
+    }
+    None
+}
+
+/// Turns a type descriptor's decorated name (`.?AVNiNode@RE@@`) into a
+/// class name (`RE::NiNode`).
+///
+/// Returns `None` for strings that aren't class, struct, or union names.
+/// Names using forms this doesn't parse, such as back-references, are
+/// returned with only the prefix removed.
+pub fn undecorate(decorated: &str) -> Option<String> {
+    let name = decorated.strip_prefix(".?A")?;
+    let name = name.strip_prefix(['V', 'U', 'T'])?;
+    if name.is_empty() || !name.bytes().all(|byte| byte.is_ascii_graphic()) {
+        return None;
+    }
+
+    let mut parser = Parser { rest: name };
+    match parser.qualified_name() {
+        Some(parsed) if parser.rest.is_empty() => Some(parsed),
+        _ => Some(name.to_string()),
+    }
+}
+
+/// A recursive descent parser for decorated type names.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    /// `Name@Namespace@@`, innermost name first.
+    fn qualified_name(&mut self) -> Option<String> {
+        let mut parts = Vec::new();
+        while !self.eat("@") {
+            parts.push(self.name_part()?);
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        parts.reverse();
+        Some(parts.join("::"))
+    }
+
+    /// `Name@` or a template, `?$Name@Arguments@`.
+    fn name_part(&mut self) -> Option<String> {
+        if self.eat("?$") {
+            let name = self.identifier()?;
+            let mut arguments = Vec::new();
+            while !self.eat("@") {
+                arguments.push(self.argument()?);
+            }
+            return Some(format!("{}<{}>", name, arguments.join(", ")));
+        }
+        self.identifier()
+    }
+
+    /// A template argument: a class, struct, or enum, or a basic type.
+    fn argument(&mut self) -> Option<String> {
+        if self.eat("V") || self.eat("U") || self.eat("W4") {
+            return self.qualified_name();
+        }
+        const BASIC: [(&str, &str); 14] = [
+            ("_N", "bool"),
+            ("_J", "__int64"),
+            ("_K", "unsigned __int64"),
+            ("D", "char"),
+            ("E", "unsigned char"),
+            ("F", "short"),
+            ("G", "unsigned short"),
+            ("H", "int"),
+            ("I", "unsigned int"),
+            ("J", "long"),
+            ("K", "unsigned long"),
+            ("M", "float"),
+            ("N", "double"),
+            ("X", "void"),
+        ];
+        BASIC
+            .iter()
+            .find(|(code, _)| self.eat(code))
+            .map(|(_, name)| name.to_string())
+    }
+
+    /// An identifier ending in `@`.
+    fn identifier(&mut self) -> Option<String> {
+        let end = self.rest.find('@')?;
+        let identifier = &self.rest[..end];
+        // Back-references and other special names aren't parsed
+        if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit() || c == '?')
+        {
+            return None;
+        }
+        self.rest = &self.rest[end + 1..];
+        Some(identifier.to_string())
+    }
+
+    /// Consumes `prefix` if the rest starts with it.
+    fn eat(&mut self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Reads a pointer-sized value.
+fn read_pointer(address: u64, is_64: bool, read: ReadMemory) -> Option<u64> {
+    if is_64 {
+        let bytes = read(address, 8)?;
+        Some(u64::from_le_bytes(bytes.as_slice().try_into().ok()?))
+    } else {
+        read(address, 4).map(|bytes| u64::from(u32_at(&bytes, 0)))
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    const IMAGE_BASE: u64 = 0x7FF600000000;
+    const LOCATOR: u64 = IMAGE_BASE + 0x2000;
+    const DESCRIPTOR: u64 = IMAGE_BASE + 0x3000;
+    const VTABLE: u64 = IMAGE_BASE + 0x1008;
+    const ACTOR: u64 = 0x1F2A0000;
+    const STACK: u64 = 0x14F000;
+
+    /// Fake process memory: byte blocks by start address.
+    fn reader(memory: BTreeMap<u64, Vec<u8>>) -> impl Fn(u64, usize) -> Option<Vec<u8>> {
+        move |address, len| {
+            let (&start, bytes) = memory.range(..=address).next_back()?;
+            let offset = (address - start) as usize;
+            bytes.get(offset..offset + len).map(<[u8]>::to_vec)
+        }
+    }
+
+    /// An x64 `Actor` with its vtable, locator, and type descriptor, and a
+    /// stack holding a pointer to it.
+    fn memory() -> BTreeMap<u64, Vec<u8>> {
+        let mut locator = vec![0u8; 24];
+        locator[0..4].copy_from_slice(&1u32.to_le_bytes());
+        locator[12..16].copy_from_slice(&0x3000u32.to_le_bytes());
+        locator[20..24].copy_from_slice(&0x2000u32.to_le_bytes());
+
+        let mut descriptor = vec![0u8; 16];
+        descriptor.extend(b".?AVActor@@\0");
+        descriptor.resize(64, 0);
+
+        let mut stack = vec![0u8; 16];
+        stack.extend(ACTOR.to_le_bytes());
+
+        BTreeMap::from([
+            (ACTOR, VTABLE.to_le_bytes().to_vec()),
+            (VTABLE - 8, LOCATOR.to_le_bytes().to_vec()),
+            (LOCATOR, locator),
+            (DESCRIPTOR, descriptor),
+            (STACK, stack),
+        ])
+    }
+
+    #[test]
+    fn finds_objects_in_registers_and_on_the_stack() {
+        let read = reader(memory());
+        let mut registers = vec![0u64; 16];
+        registers[2] = ACTOR; // RCX
+        registers[7] = STACK; // RSP
+
+        let objects = scan_with(&registers, true, &read);
+        assert_eq!(
+            objects,
+            [ProbableObject {
+                location: "RCX".to_string(),
+                address: "0x1F2A0000".to_string(),
+                class_name: "Actor".to_string(),
+            }]
+        );
+
+        // Only on the stack: found at its slot
+        registers[2] = 0;
+        let objects = scan_with(&registers, true, &read);
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].location, "RSP+0x10");
+    }
+
+    #[test]
+    fn rejects_pointers_without_rtti() {
+        let mut memory = memory();
+        // Wrong locator signature
+        memory.get_mut(&LOCATOR).unwrap()[0] = 0;
+        let read = reader(memory);
+        assert_eq!(class_name(ACTOR, true, &read), None);
+        // Unreadable object
+        assert_eq!(class_name(0xDEAD0000, true, &read), None);
+    }
+
+    #[test]
+    fn undecorates_class_names() {
+        assert_eq!(undecorate(".?AVActor@@").as_deref(), Some("Actor"));
+        assert_eq!(
+            undecorate(".?AUBGSKeyword@@").as_deref(),
+            Some("BGSKeyword")
+        );
+        assert_eq!(undecorate(".?AVNiNode@RE@@").as_deref(), Some("RE::NiNode"));
+        assert_eq!(
+            undecorate(".?AV?$BSTEventSink@UTESHitEvent@RE@@@RE@@").as_deref(),
+            Some("RE::BSTEventSink<RE::TESHitEvent>")
+        );
+        assert_eq!(
+            undecorate(".?AV?$BSTArray@PEAVActor@@@@").as_deref(),
+            Some("?$BSTArray@PEAVActor@@@@")
+        );
+        assert_eq!(undecorate("Actor"), None);
+        assert_eq!(undecorate(".?AV"), None);
+    }
+}
@@ -0,0 +1,495 @@
+//! Save game header parsing for Skyrim SE (`.ess`) and Fallout 4 (`.fos`).
+//!
+//! A save records the plugins it was made with. Adding or removing plugins
+//! mid-playthrough is a classic crash source, so reports compare the most
+//! recent save's plugin list against the live load order.
+//!
+//! Skyrim SE compresses everything after the screenshot with LZ4 (or zlib
+//! in early builds); Fallout 4 saves are uncompressed.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::load_order::ModList;
+
+const SKYRIM_MAGIC: &[u8] = b"TESV_SAVEGAME";
+const FALLOUT4_MAGIC: &[u8] = b"FO4_SAVEGAME";
+
+/// Skyrim header version that added the compression type (SE).
+const SKYRIM_COMPRESSED_VERSION: u32 = 12;
+
+/// Fallout 4 header version that added the game version string.
+const FALLOUT4_GAME_VERSION_VERSION: u32 = 11;
+
+/// First Skyrim form version with a light (ESL) plugin list.
+const SKYRIM_LIGHT_FORM_VERSION: u8 = 78;
+
+/// First Fallout 4 form version with a light (ESL) plugin list.
+const FALLOUT4_LIGHT_FORM_VERSION: u8 = 68;
+
+/// Largest decompressed body accepted; real saves are tens of MB at most.
+const MAX_UNCOMPRESSED_SIZE: usize = 256 * 1024 * 1024;
+
+/// Errors that can occur when reading a save file.
+#[derive(Error, Debug)]
+pub enum SaveError {
+    /// Failed to read the file.
+    #[error("Failed to read save: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// The file is not a Skyrim SE or Fallout 4 save.
+    #[error("Not a save file")]
+    NotASave,
+
+    /// The save ends in the middle of a field.
+    #[error("Truncated save: {0}")]
+    Truncated(String),
+
+    /// The compressed body could not be decompressed.
+    #[error("Failed to decompress save: {0}")]
+    Compression(String),
+}
+
+/// The parts of a save header that matter for crash diagnosis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveHeader {
+    /// Save number shown in the load menu.
+    pub save_number: u32,
+    /// Character name; never reported, see [`SaveHeader::character_hash`].
+    pub character_name: String,
+    /// Character level.
+    pub level: u32,
+    /// Play time as the game shows it (e.g., "012.34.56").
+    pub playtime: String,
+    /// Full plugins the save was made with, in load order.
+    pub plugins: Vec<String>,
+    /// Light (ESL) plugins the save was made with.
+    pub light_plugins: Vec<String>,
+}
+
+impl SaveHeader {
+    /// Returns a hash of the character name, so reports from the same
+    /// playthrough can be grouped without revealing the name.
+    pub fn character_hash(&self) -> String {
+        let digest = Sha256::digest(self.character_name.as_bytes());
+        hex::encode(&digest[..8])
+    }
+
+    /// Compares the save's plugins against the live load order.
+    ///
+    /// Plugin names are compared case-insensitively; disabled entries in
+    /// `mods` count as not loaded.
+    pub fn compare(&self, mods: &ModList) -> SaveGameInfo {
+        let loaded: Vec<&str> = mods
+            .iter()
+            .filter(|entry| entry.enabled != Some(false))
+            .map(|entry| entry.name.as_str())
+            .collect();
+        let saved: Vec<&str> = self
+            .plugins
+            .iter()
+            .chain(&self.light_plugins)
+            .map(String::as_str)
+            .collect();
+        let contains =
+            |list: &[&str], name: &str| list.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+        SaveGameInfo {
+            save_number: self.save_number,
+            character_hash: self.character_hash(),
+            level: self.level,
+            playtime: self.playtime.clone(),
+            missing_from_save: saved
+                .iter()
+                .filter(|name| !contains(&loaded, name))
+                .map(|name| name.to_string())
+                .collect(),
+            added_since_save: loaded
+                .iter()
+                .filter(|name| !contains(&saved, name))
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// The most recent save, as reported in a crash report's `saveGame` section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveGameInfo {
+    /// Save number shown in the load menu.
+    pub save_number: u32,
+    /// Truncated SHA-256 of the character name.
+    pub character_hash: String,
+    /// Character level.
+    pub level: u32,
+    /// Play time as the game shows it.
+    pub playtime: String,
+    /// Plugins the save was made with that are no longer loaded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_from_save: Vec<String>,
+    /// Plugins loaded now that the save was not made with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_since_save: Vec<String>,
+}
+
+/// Returns the default save directory of a game, e.g.
+/// `Documents/My Games/Skyrim Special Edition/Saves`.
+pub fn saves_dir(my_games_folder: &str) -> Option<PathBuf> {
+    Some(
+        dirs::document_dir()?
+            .join("My Games")
+            .join(my_games_folder)
+            .join("Saves"),
+    )
+}
+
+/// Returns the most recently written `.ess` or `.fos` file in `dir`.
+pub fn latest_save(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            entry.path().extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("ess") || ext.eq_ignore_ascii_case("fos")
+            })
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Compares the newest save in `Documents/My Games/<my_games_folder>/Saves`
+/// against the live load order.
+///
+/// Returns `None` if there is no save or it cannot be read.
+pub fn latest_save_info(my_games_folder: &str, mods: &ModList) -> Option<SaveGameInfo> {
+    let path = latest_save(&saves_dir(my_games_folder)?)?;
+    match read_save(&path) {
+        Ok(header) => Some(header.compare(mods)),
+        Err(e) => {
+            debug!("Could not read save {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Reads the header and plugin lists of a save file.
+///
+/// # Errors
+///
+/// Returns `SaveError` if the file cannot be read or is not a save.
+pub fn read_save(path: &Path) -> Result<SaveHeader, SaveError> {
+    parse_save(&fs::read(path)?)
+}
+
+/// Parses the header and plugin lists of a save file.
+///
+/// # Errors
+///
+/// Returns `SaveError::NotASave` for unknown formats, `SaveError::Truncated`
+/// if the data is cut short, or `SaveError::Compression` for a corrupt body.
+pub fn parse_save(bytes: &[u8]) -> Result<SaveHeader, SaveError> {
+    let (magic, light_form_version) = if bytes.starts_with(SKYRIM_MAGIC) {
+        (SKYRIM_MAGIC, SKYRIM_LIGHT_FORM_VERSION)
+    } else if bytes.starts_with(FALLOUT4_MAGIC) {
+        (FALLOUT4_MAGIC, FALLOUT4_LIGHT_FORM_VERSION)
+    } else {
+        return Err(SaveError::NotASave);
+    };
+    let is_skyrim = magic == SKYRIM_MAGIC;
+
+    let mut reader = Reader::new(bytes, magic.len());
+    let header_size = reader.u32()? as usize;
+    let header_end = reader.pos + header_size;
+
+    let version = reader.u32()?;
+    let save_number = reader.u32()?;
+    let character_name = reader.wstring()?;
+    let level = reader.u32()?;
+    let _location = reader.wstring()?;
+    let playtime = reader.wstring()?;
+    let _race = reader.wstring()?;
+    reader.skip(2 + 4 + 4 + 8)?; // sex, current XP, level-up XP, FILETIME
+    let shot_width = reader.u32()? as usize;
+    let shot_height = reader.u32()? as usize;
+    let compression = if is_skyrim && version >= SKYRIM_COMPRESSED_VERSION {
+        reader.u16()?
+    } else {
+        0
+    };
+
+    // Screenshot pixels follow the header: RGBA, or RGB in pre-SE Skyrim
+    let bytes_per_pixel = if is_skyrim && version < SKYRIM_COMPRESSED_VERSION {
+        3
+    } else {
+        4
+    };
+    reader.pos = header_end;
+    reader.skip(
+        shot_width
+            .saturating_mul(shot_height)
+            .saturating_mul(bytes_per_pixel),
+    )?;
+
+    let body;
+    let mut reader = match compression {
+        0 => reader,
+        kind => {
+            let uncompressed_size = reader.u32()? as usize;
+            let compressed_size = reader.u32()? as usize;
+            let compressed = reader.take(compressed_size)?;
+            body = decompress(kind, compressed, uncompressed_size)?;
+            Reader::new(&body, 0)
+        }
+    };
+
+    let form_version = reader.u8()?;
+    if !is_skyrim && version >= FALLOUT4_GAME_VERSION_VERSION {
+        let _game_version = reader.wstring()?;
+    }
+    let _plugin_info_size = reader.u32()?;
+
+    let count = reader.u8()?;
+    let plugins = (0..count)
+        .map(|_| reader.wstring())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let light_plugins = if form_version >= light_form_version {
+        let count = reader.u16()?;
+        (0..count)
+            .map(|_| reader.wstring())
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    Ok(SaveHeader {
+        save_number,
+        character_name,
+        level,
+        playtime,
+        plugins,
+        light_plugins,
+    })
+}
+
+/// Decompresses a Skyrim SE save body (1 = zlib, 2 = LZ4 block).
+fn decompress(kind: u16, data: &[u8], size: usize) -> Result<Vec<u8>, SaveError> {
+    if size > MAX_UNCOMPRESSED_SIZE {
+        return Err(SaveError::Compression(format!(
+            "body claims {} bytes",
+            size
+        )));
+    }
+
+    match kind {
+        1 => {
+            let mut body = Vec::with_capacity(size);
+            flate2::read::ZlibDecoder::new(data)
+                .read_to_end(&mut body)
+                .map_err(|e| SaveError::Compression(e.to_string()))?;
+            Ok(body)
+        }
+        2 => lz4_flex::block::decompress(data, size)
+            .map_err(|e| SaveError::Compression(e.to_string())),
+        other => Err(SaveError::Compression(format!(
+            "unknown compression type {}",
+            other
+        ))),
+    }
+}
+
+/// Little-endian cursor over a save's bytes.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], pos: usize) -> Self {
+        Self { bytes, pos }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SaveError> {
+        let field = self
+            .bytes
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| SaveError::Truncated(format!("{} bytes at offset {}", len, self.pos)))?;
+        self.pos += len;
+        Ok(field)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), SaveError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, SaveError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SaveError> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SaveError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Reads a u16-length-prefixed string (Windows-1252, mapped as Latin-1
+    /// when it isn't valid UTF-8).
+    fn wstring(&mut self) -> Result<String, SaveError> {
+        let len = self.u16()? as usize;
+        let bytes = self.take(len)?;
+        Ok(match std::str::from_utf8(bytes) {
+            Ok(s) => s.to_string(),
+            Err(_) => bytes.iter().map(|&b| b as char).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModEntry;
+
+    fn wstring(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(&(s.len() as u16).to_le_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Builds the plugin section that follows the screenshot.
+    fn plugin_section(
+        form_version: u8,
+        game_version: Option<&str>,
+        plugins: &[&str],
+        light: &[&str],
+    ) -> Vec<u8> {
+        let mut out = vec![form_version];
+        if let Some(version) = game_version {
+            wstring(&mut out, version);
+        }
+        out.extend_from_slice(&0u32.to_le_bytes());
+        out.push(plugins.len() as u8);
+        for plugin in plugins {
+            wstring(&mut out, plugin);
+        }
+        out.extend_from_slice(&(light.len() as u16).to_le_bytes());
+        for plugin in light {
+            wstring(&mut out, plugin);
+        }
+        out
+    }
+
+    /// Builds a save with a 2x1 screenshot and `body` after it.
+    fn save(magic: &[u8], version: u32, compression: Option<u16>, body: &[u8]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&version.to_le_bytes());
+        header.extend_from_slice(&7u32.to_le_bytes());
+        wstring(&mut header, "Lydia");
+        header.extend_from_slice(&31u32.to_le_bytes());
+        wstring(&mut header, "Whiterun");
+        wstring(&mut header, "012.34.56");
+        wstring(&mut header, "NordRace");
+        header.extend_from_slice(&[0; 18]);
+        header.extend_from_slice(&2u32.to_le_bytes());
+        header.extend_from_slice(&1u32.to_le_bytes());
+        if let Some(kind) = compression {
+            header.extend_from_slice(&kind.to_le_bytes());
+        }
+
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&[0xAB; 8]);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn parses_lz4_skyrim_save() {
+        let section = plugin_section(78, None, &["Skyrim.esm", "Patch.esp"], &["Small.esl"]);
+        let compressed = lz4_flex::block::compress(&section);
+        let mut body = (section.len() as u32).to_le_bytes().to_vec();
+        body.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        body.extend_from_slice(&compressed);
+
+        let header = parse_save(&save(SKYRIM_MAGIC, 12, Some(2), &body)).unwrap();
+        assert_eq!(header.save_number, 7);
+        assert_eq!(header.level, 31);
+        assert_eq!(header.playtime, "012.34.56");
+        assert_eq!(header.plugins, vec!["Skyrim.esm", "Patch.esp"]);
+        assert_eq!(header.light_plugins, vec!["Small.esl"]);
+        assert_eq!(header.character_hash().len(), 16);
+        assert_ne!(header.character_hash(), "Lydia");
+    }
+
+    #[test]
+    fn parses_uncompressed_fallout4_save() {
+        let section = plugin_section(68, Some("1.10.984.0"), &["Fallout4.esm"], &[]);
+        let header = parse_save(&save(FALLOUT4_MAGIC, 15, None, &section)).unwrap();
+        assert_eq!(header.plugins, vec!["Fallout4.esm"]);
+        assert!(header.light_plugins.is_empty());
+
+        assert!(matches!(
+            parse_save(b"not a save at all"),
+            Err(SaveError::NotASave)
+        ));
+        let truncated = save(FALLOUT4_MAGIC, 15, None, &section[..4]);
+        assert!(matches!(
+            parse_save(&truncated),
+            Err(SaveError::Truncated(_))
+        ));
+    }
+
+    #[test]
+    fn compares_save_plugins_with_load_order() {
+        let header = SaveHeader {
+            save_number: 7,
+            character_name: "Lydia".to_string(),
+            level: 31,
+            playtime: "012.34.56".to_string(),
+            plugins: vec!["Skyrim.esm".to_string(), "Removed.esp".to_string()],
+            light_plugins: vec!["Small.esl".to_string()],
+        };
+        let mods = ModList::from_entries(vec![
+            ModEntry::new("skyrim.esm", "0000000000000000", 0),
+            ModEntry::new("Small.esl", "0000000000000000", 0),
+            ModEntry::new("New.esp", "0000000000000000", 0),
+            ModEntry::new("Disabled.esp", "0000000000000000", 0).with_enabled(false),
+        ]);
+
+        let info = header.compare(&mods);
+        assert_eq!(info.missing_from_save, vec!["Removed.esp"]);
+        assert_eq!(info.added_since_save, vec!["New.esp"]);
+        assert_eq!(info.character_hash, header.character_hash());
+    }
+
+    #[test]
+    fn finds_latest_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("Save1.ess");
+        fs::write(&old, b"old").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(60))
+            .unwrap();
+        fs::write(dir.path().join("Save2.ess"), b"new").unwrap();
+        fs::write(dir.path().join("Save2.skse"), b"co-save").unwrap();
+
+        assert_eq!(
+            latest_save(dir.path()).unwrap().file_name().unwrap(),
+            "Save2.ess"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Session summaries submitted when the game exits cleanly.
+//!
+//! Crash reports alone only give the backend raw counts: a mod in half of
+//! all reports may be crashy or just popular. With `[session] enabled =
+//! true`, the plugins also send one small summary per clean exit, listing
+//! the mods loaded and how the session went, so crash rates per mod can be
+//! computed.
+//!
+//! The plugins call [`start`] when they load and [`submit_on_exit`] from
+//! the game's shutdown hook. Summaries that fail to send are logged and
+//! dropped, not spooled: one lost session barely moves a rate.
+
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::api_client::ApiClient;
+use crate::config::Config;
+use crate::crash_report::{HandledException, ReportType};
+use crate::file_hash::{self, HashAlgorithm};
+use crate::load_order::ModList;
+use crate::{CtdError, Result, exception_tally, identity, watchdog};
+
+/// When the session started, set by [`start`].
+static STARTED: OnceLock<SessionStart> = OnceLock::new();
+
+struct SessionStart {
+    instant: Instant,
+    unix_ms: u64,
+}
+
+/// Summary of one game session, from plugin load to clean exit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionReport {
+    /// Game identifier (e.g., "cyberpunk2077").
+    pub game_id: String,
+    /// Always [`ReportType::Session`].
+    pub report_type: ReportType,
+    /// Game version.
+    pub game_version: String,
+    /// Script extender or mod loader version, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_extender_version: Option<String>,
+    /// Seconds from plugin load to exit.
+    pub play_time_secs: u64,
+    /// Number of mods loaded.
+    pub mod_count: u32,
+    /// The mods loaded, fingerprinted like a crash report's load order.
+    pub mods: ModList,
+    /// Algorithm behind the mods' `fileHash` values; omitted for the
+    /// default.
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_default")]
+    pub hash_algorithm: HashAlgorithm,
+    /// Peak working set of the game process in bytes, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+    /// Non-fatal exceptions counted during the session, most frequent first
+    /// (see [`crate::exception_tally`]); omitted when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handled_exceptions: Vec<HandledException>,
+    /// Hangs the watchdog detected during the session.
+    pub hang_count: u32,
+    /// Unix timestamp (milliseconds) when the session started.
+    pub started_at: u64,
+    /// Unix timestamp (milliseconds) when the session ended.
+    pub ended_at: u64,
+    /// Random ID of the installation (see [`crate::identity`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installation_id: Option<String>,
+    /// Random ID of this launch, matching the session's crash reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+impl SessionReport {
+    /// Summarizes the session so far.
+    ///
+    /// Play time runs from [`start`]; the handled-exception and hang counts
+    /// come from [`exception_tally`] and [`watchdog`].
+    pub fn collect(game_id: &str, game_version: &str, mods: ModList) -> Self {
+        let ended_at = unix_millis();
+        let (play_time_secs, started_at) = match STARTED.get() {
+            Some(start) => (start.instant.elapsed().as_secs(), start.unix_ms),
+            None => (0, ended_at),
+        };
+        let ids = identity::current();
+
+        Self {
+            game_id: game_id.to_string(),
+            report_type: ReportType::Session,
+            game_version: game_version.to_string(),
+            script_extender_version: None,
+            play_time_secs,
+            mod_count: mods.len() as u32,
+            mods,
+            hash_algorithm: file_hash::configured_algorithm(),
+            peak_memory_bytes: peak_memory_bytes(),
+            handled_exceptions: exception_tally::snapshot(),
+            hang_count: watchdog::hang_count(),
+            started_at,
+            ended_at,
+            installation_id: ids.as_ref().and_then(|ids| ids.installation_id.clone()),
+            session_id: ids.map(|ids| ids.session_id),
+        }
+    }
+
+    /// Sets the script extender or mod loader version.
+    pub fn script_extender_version(mut self, version: impl Into<String>) -> Self {
+        self.script_extender_version = Some(version.into());
+        self
+    }
+}
+
+/// Marks the start of the session. Later calls do nothing.
+pub fn start() {
+    STARTED.get_or_init(|| SessionStart {
+        instant: Instant::now(),
+        unix_ms: unix_millis(),
+    });
+}
+
+/// Submits a summary of the session if `[session] enabled` is set.
+///
+/// Call once, when the game exits cleanly. `summary` is only called when a
+/// summary is sent. Blocks until it is sent or the API timeout passes;
+/// failures are logged.
+pub fn submit_on_exit(summary: impl FnOnce() -> SessionReport) {
+    let config = Config::load().unwrap_or_default();
+    if !config.session.enabled {
+        return;
+    }
+
+    let report = summary();
+    match submit(&report) {
+        Ok(()) => info!(
+            "Session summary submitted ({} minutes played)",
+            report.play_time_secs / 60
+        ),
+        Err(e) => warn!("Failed to submit session summary: {}", e),
+    }
+}
+
+/// Submits a session summary, blocking until it is sent.
+fn submit(report: &SessionReport) -> Result<()> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CtdError::ApiRequest(format!("Failed to create runtime: {}", e)))?;
+
+    rt.block_on(async {
+        let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
+        client.submit_session(report).await
+    })
+}
+
+/// Returns the current time as a Unix timestamp in milliseconds.
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Returns the peak working set of this process.
+#[cfg(windows)]
+fn peak_memory_bytes() -> Option<u64> {
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters = PROCESS_MEMORY_COUNTERS::default();
+    // SAFETY: counters is a valid PROCESS_MEMORY_COUNTERS of the size passed
+    unsafe {
+        GetProcessMemoryInfo(
+            GetCurrentProcess(),
+            &mut counters,
+            size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+        )
+    }
+    .ok()?;
+    Some(counters.PeakWorkingSetSize as u64)
+}
+
+#[cfg(not(windows))]
+fn peak_memory_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModEntry;
+
+    #[test]
+    fn collect_counts_mods_and_times_the_session() {
+        start();
+        let mods = ModList::from_entries(vec![ModEntry::new(
+            "[RED4ext] ArchiveXL",
+            "0123456789abcdef",
+            1024,
+        )]);
+        let report =
+            SessionReport::collect("cyberpunk2077", "2.21", mods).script_extender_version("1.27.0");
+
+        assert_eq!(report.mod_count, 1);
+        assert!(report.started_at <= report.ended_at);
+        assert_eq!(report.script_extender_version.as_deref(), Some("1.27.0"));
+    }
+
+    #[test]
+    fn serializes_camel_case() {
+        let report = SessionReport {
+            game_id: "cyberpunk2077".into(),
+            report_type: ReportType::Session,
+            game_version: "2.21".into(),
+            script_extender_version: None,
+            play_time_secs: 3600,
+            mod_count: 0,
+            mods: ModList::new(),
+            hash_algorithm: HashAlgorithm::PartialSha256,
+            peak_memory_bytes: Some(8 << 30),
+            handled_exceptions: Vec::new(),
+            hang_count: 1,
+            started_at: 1000,
+            ended_at: 3_601_000,
+            installation_id: None,
+            session_id: Some("0d9e8f7a-6b5c-4d3e-8f2a-1b0c9d8e7f6a".into()),
+        };
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""reportType":"session""#));
+        assert!(json.contains(r#""playTimeSecs":3600"#));
+        assert!(json.contains(r#""peakMemoryBytes":8589934592"#));
+        assert!(json.contains(r#""hangCount":1"#));
+        assert!(json.contains(r#""sessionId":"0d9e8f7a-6b5c-4d3e-8f2a-1b0c9d8e7f6a""#));
+        assert!(!json.contains("installationId"));
+        assert!(!json.contains("handledExceptions"));
+        assert!(!json.contains("hashAlgorithm"));
+        assert!(!json.contains("scriptExtenderVersion"));
+
+        let parsed: SessionReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+}
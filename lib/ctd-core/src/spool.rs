@@ -0,0 +1,344 @@
+//! Persistent queue of crash reports awaiting submission.
+//!
+//! When a report cannot be submitted (server down, no network, bad API key),
+//! it is written to the spool directory as JSON and retried later with
+//! [`Spool::flush`] instead of being lost. The default spool also keeps the
+//! [`History`] entry of each report up to date.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use tracing::{debug, warn};
+
+use crate::crash_report::CreateCrashReport;
+use crate::history::History;
+use crate::transport::CrashTransport;
+use crate::{CtdError, Result};
+
+/// Extension for queued report files.
+const PENDING_EXTENSION: &str = "json";
+
+/// Extension given to queued files that could not be parsed.
+const REJECTED_EXTENSION: &str = "bad";
+
+/// Distinguishes reports queued within the same millisecond.
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// Outcome of a [`Spool::flush`] run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlushSummary {
+    /// Reports submitted and removed from the spool.
+    pub submitted: usize,
+    /// Reports that failed to submit and remain queued.
+    pub failed: usize,
+    /// Unreadable files moved aside with a `.bad` extension.
+    pub rejected: usize,
+}
+
+/// A directory of crash reports waiting to be submitted.
+#[derive(Debug, Clone)]
+pub struct Spool {
+    dir: PathBuf,
+    history: Option<History>,
+}
+
+impl Spool {
+    /// Creates a spool backed by the given directory.
+    ///
+    /// The directory is created on first enqueue.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            history: None,
+        }
+    }
+
+    /// Creates a spool in the default location (`<local data dir>/ctd/pending`),
+    /// recording into the default history.
+    pub fn open_default() -> Self {
+        Self::new(Self::default_dir()).with_history(History::open_default())
+    }
+
+    /// Records queued reports in `history` as pending, and flushed ones as
+    /// submitted.
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Returns the default spool directory.
+    pub fn default_dir() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("ctd")
+            .join("pending")
+    }
+
+    /// Returns the spool directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Verifies the spool directory can be created and written to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Spool` describing the first operation that failed.
+    pub fn check_writable(&self) -> Result<()> {
+        let probe = self.dir.join(format!(".probe-{}", std::process::id()));
+
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| fs::write(&probe, b"ctd"))
+            .and_then(|()| fs::remove_file(&probe))
+            .map_err(|e| CtdError::Spool(format!("{} is not writable: {}", self.dir.display(), e)))
+    }
+
+    /// Writes a report to the spool, returning the path of the queued file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Spool` if the report cannot be written.
+    pub fn enqueue(&self, report: &CreateCrashReport) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| CtdError::Spool(format!("Failed to create spool directory: {}", e)))?;
+
+        // Zero-padded timestamp first so lexical order is chronological
+        let name = format!(
+            "{:020}-{}-{}",
+            report.crashed_at,
+            std::process::id(),
+            SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = self.dir.join(&name).with_extension(PENDING_EXTENSION);
+        let tmp_path = self.dir.join(&name).with_extension("tmp");
+
+        // Write then rename so a half-written file is never picked up by flush
+        fs::write(&tmp_path, report.to_json()?)
+            .and_then(|()| fs::rename(&tmp_path, &path))
+            .map_err(|e| CtdError::Spool(format!("Failed to write pending report: {}", e)))?;
+
+        debug!("Queued crash report at {:?}", path);
+        if let Some(history) = &self.history
+            && let Err(e) = history.record(report)
+        {
+            warn!("Failed to record queued report in history: {}", e);
+        }
+        Ok(path)
+    }
+
+    /// Lists queued report files, oldest first.
+    pub fn pending(&self) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == PENDING_EXTENSION))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Returns the number of queued reports.
+    pub fn len(&self) -> usize {
+        self.pending().len()
+    }
+
+    /// Returns true if no reports are queued.
+    pub fn is_empty(&self) -> bool {
+        self.pending().is_empty()
+    }
+
+    /// Reads a queued report.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Spool` if the file cannot be read, or
+    /// `CtdError::Serialize` if it is not a valid report.
+    pub fn load(&self, path: &Path) -> Result<CreateCrashReport> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| CtdError::Spool(format!("Failed to read pending report: {}", e)))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Removes a queued report.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CtdError::Spool` if the file cannot be removed.
+    pub fn remove(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path)
+            .map_err(|e| CtdError::Spool(format!("Failed to remove pending report: {}", e)))
+    }
+
+    /// Submits every queued report, removing those that succeed.
+    ///
+    /// Failed submissions stay queued for the next flush. Files that cannot
+    /// be parsed are renamed with a `.bad` extension so they are not retried.
+    pub async fn flush(&self, transport: &impl CrashTransport) -> FlushSummary {
+        let mut summary = FlushSummary::default();
+
+        for path in self.pending() {
+            let report = match self.load(&path) {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("Rejecting unreadable pending report {:?}: {}", path, e);
+                    if fs::rename(&path, path.with_extension(REJECTED_EXTENSION)).is_ok() {
+                        summary.rejected += 1;
+                    }
+                    continue;
+                }
+            };
+
+            match transport.submit(&report).await {
+                Ok(response) => {
+                    debug!("Submitted pending report {:?} as {}", path, response.id);
+                    if let Some(history) = &self.history
+                        && let Err(e) = history.mark_submitted(
+                            &report,
+                            &response,
+                            transport.share_url(&response),
+                        )
+                    {
+                        warn!("Failed to record submitted report in history: {}", e);
+                    }
+                    if let Err(e) = self.remove(&path) {
+                        warn!("{}", e);
+                    }
+                    summary.submitted += 1;
+                }
+                Err(e) => {
+                    warn!("Pending report {:?} still failing: {}", path, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::ApiClient;
+    use crate::history::ReportStatus;
+    use crate::load_order::ModList;
+    use crate::transport::MockTransport;
+    use tempfile::tempdir;
+
+    fn sample_report(crashed_at: u64) -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("[0] SkyrimSE.exe+0x1234")
+            .load_order_v2(ModList::new())
+            .crashed_at(crashed_at)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn enqueue_and_load_roundtrip() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("pending"));
+        assert!(spool.is_empty());
+
+        let path = spool.enqueue(&sample_report(1000)).unwrap();
+        assert_eq!(spool.pending(), vec![path.clone()]);
+
+        let report = spool.load(&path).unwrap();
+        assert_eq!(report.game_id, "skyrim-se");
+        assert_eq!(report.crashed_at, 1000);
+
+        spool.remove(&path).unwrap();
+        assert!(spool.is_empty());
+    }
+
+    #[test]
+    fn pending_is_oldest_first() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+
+        let newer = spool.enqueue(&sample_report(2000)).unwrap();
+        let older = spool.enqueue(&sample_report(1000)).unwrap();
+
+        assert_eq!(spool.pending(), vec![older, newer]);
+    }
+
+    #[test]
+    fn pending_ignores_other_files() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+        fs::write(dir.path().join("notes.txt"), "hi").unwrap();
+        fs::write(dir.path().join("1-1-1.tmp"), "{").unwrap();
+
+        assert!(spool.is_empty());
+    }
+
+    #[test]
+    fn check_writable_creates_directory() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("a").join("b"));
+
+        spool.check_writable().unwrap();
+        assert!(spool.dir().is_dir());
+        assert!(spool.is_empty());
+    }
+
+    #[test]
+    fn missing_directory_has_no_pending() {
+        let spool = Spool::new("/nonexistent/ctd/pending");
+        assert!(spool.pending().is_empty());
+    }
+
+    #[tokio::test]
+    async fn flush_submits_and_removes_reports() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+        spool.enqueue(&sample_report(1000)).unwrap();
+        spool.enqueue(&sample_report(2000)).unwrap();
+
+        let transport = MockTransport::new();
+        transport.push_error("503 Service Unavailable");
+        let summary = spool.flush(&transport).await;
+
+        assert_eq!(summary.submitted, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(spool.len(), 1);
+        assert_eq!(transport.submitted()[0].crashed_at, 1000);
+    }
+
+    #[tokio::test]
+    async fn flush_updates_history() {
+        let dir = tempdir().unwrap();
+        let history = History::new(dir.path().join("history"));
+        let spool = Spool::new(dir.path().join("pending")).with_history(history.clone());
+        spool.enqueue(&sample_report(1000)).unwrap();
+        assert_eq!(history.list()[0].status, ReportStatus::Pending);
+
+        spool.flush(&MockTransport::new()).await;
+        let entries = history.list();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, ReportStatus::Submitted);
+        assert_eq!(entries[0].report_id.as_deref(), Some("mock-1"));
+    }
+
+    #[tokio::test]
+    async fn flush_rejects_unreadable_files() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+        let bad = dir.path().join("1-1-1.json");
+        fs::write(&bad, "not json").unwrap();
+
+        let client = ApiClient::with_defaults().unwrap();
+        let summary = spool.flush(&client).await;
+
+        assert_eq!(summary.rejected, 1);
+        assert!(!bad.exists());
+        assert!(dir.path().join("1-1-1.bad").exists());
+    }
+}
@@ -0,0 +1,20 @@
+//! Startup steps every game plugin shares.
+//!
+//! Each plugin sets up logging its own way (some mirror into the host's
+//! log), then calls [`init`] once, before anything can crash.
+
+use crate::api_client;
+use crate::config::Config;
+use crate::uptime;
+
+/// Runs the shared startup steps. Call once logging is set up.
+///
+/// Writes `ctd.toml` on first run and logs config mistakes
+/// ([`Config::startup_check`]), starts the uptime clock reports carry, and
+/// pings the server in the background so a bad API key or an unreachable
+/// server shows up in the log before a crash ([`api_client::spawn_ping_check`]).
+pub fn init() {
+    Config::startup_check();
+    uptime::start();
+    api_client::spawn_ping_check();
+}
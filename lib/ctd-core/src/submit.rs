@@ -0,0 +1,152 @@
+//! Submitting a finished report.
+//!
+//! Every plugin sends a report the same way, through [`submit_report`]: it
+//! submits with the configured client, logs the known fix when the server
+//! recognises the crash, and records the outcome. A report the server
+//! rejected or never saw is queued in the spool for `ctd pending flush`; a
+//! submitted one is remembered for post-hoc notes and `ctd history`
+//! ([`record_submitted`]). Both go to the Windows event log and discard the
+//! crash arena's record, which the full report supersedes. [`submit_with`]
+//! does the same through any [`CrashTransport`] and spool.
+
+use tracing::{error, warn};
+
+use crate::api_client::ApiClient;
+use crate::crash_arena;
+use crate::crash_report::{CrashReportResponse, CreateCrashReport};
+use crate::event_log;
+use crate::history;
+use crate::last_report::LastReport;
+use crate::spool::Spool;
+use crate::transport::CrashTransport;
+use crate::{CtdError, Result};
+
+/// Submits a report, blocking until the server answers.
+///
+/// Uses the client from ctd.toml, or the defaults if it can't be loaded,
+/// and queues a report that fails to submit in the default spool.
+///
+/// # Errors
+///
+/// Returns `CtdError::ApiRequest` if the runtime can't be created, or any
+/// error from creating the client or submitting.
+pub fn submit_report(report: &CreateCrashReport) -> Result<CrashReportResponse> {
+    let spool = Spool::open_default();
+    let client = match ApiClient::from_config().or_else(|_| ApiClient::with_defaults()) {
+        Ok(client) => client,
+        Err(e) => {
+            queue_failed(&spool, report);
+            return Err(e);
+        }
+    };
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| CtdError::ApiRequest(format!("Failed to create runtime: {}", e)))?;
+    rt.block_on(submit_with(&client, &spool, report))
+}
+
+/// Submits a report through `transport`, queueing it in `spool` on failure.
+///
+/// # Errors
+///
+/// Returns the transport's error if submission fails.
+pub async fn submit_with(
+    transport: &impl CrashTransport,
+    spool: &Spool,
+    report: &CreateCrashReport,
+) -> Result<CrashReportResponse> {
+    let response = match transport.submit(report).await {
+        Ok(response) => response,
+        Err(e) => {
+            queue_failed(spool, report);
+            return Err(e);
+        }
+    };
+
+    // Ask the server whether it recognises the crash
+    if let Some(issue) = transport.known_issue_for(report).await {
+        warn!("{}", issue.advice());
+    }
+    record_submitted(report, &response);
+    Ok(response)
+}
+
+/// Records a report the server accepted.
+pub fn record_submitted(report: &CreateCrashReport, response: &CrashReportResponse) {
+    // The full report supersedes the one recorded without allocating
+    crash_arena::discard_record();
+
+    // Remember the report so a note can be attached on next launch
+    if let Err(e) = LastReport::new(&report.game_id, response).save() {
+        warn!("Failed to record last report: {}", e);
+    }
+    history::record_submitted(report, response);
+    event_log::record(report, Some(response));
+}
+
+/// Queues a report that could not be submitted.
+///
+/// The crash arena's record is only discarded once the report is queued, so
+/// a crash whose report can't be written anywhere is still sent next launch.
+fn queue_failed(spool: &Spool, report: &CreateCrashReport) {
+    event_log::record(report, None);
+    match spool.enqueue(report) {
+        Ok(path) => {
+            warn!("Submission failed, report queued at {:?}", path);
+            crash_arena::discard_record();
+        }
+        Err(e) => error!("Failed to queue crash report: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModList;
+    use crate::transport::MockTransport;
+    use tempfile::tempdir;
+
+    fn sample_report() -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("[0] SkyrimSE.exe+0x1234")
+            .load_order_v2(ModList::new())
+            .crashed_at(1000)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_submission_is_queued() {
+        let dir = tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+        let transport = MockTransport::failing("503 Service Unavailable");
+
+        let err = submit_with(&transport, &spool, &sample_report())
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("503"));
+        assert_eq!(transport.submission_count(), 1);
+        assert_eq!(spool.len(), 1);
+        let queued = spool.load(&spool.pending()[0]).unwrap();
+        assert_eq!(queued.stack_trace, "[0] SkyrimSE.exe+0x1234");
+    }
+
+    #[tokio::test]
+    async fn unwritable_spool_still_returns_the_error() {
+        let dir = tempdir().unwrap();
+        let blocker = dir.path().join("pending");
+        std::fs::write(&blocker, "not a directory").unwrap();
+        let spool = Spool::new(&blocker);
+
+        let result =
+            submit_with(&MockTransport::failing("offline"), &spool, &sample_report()).await;
+
+        assert!(result.unwrap_err().to_string().contains("offline"));
+        assert!(spool.is_empty());
+    }
+}
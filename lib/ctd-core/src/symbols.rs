@@ -3,17 +3,70 @@
 //! This module provides functionality to resolve raw stack trace addresses
 //! into function names, file paths, and line numbers using PDB debug symbols.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
 
 use pdb::{FallibleIterator, PDB};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use crate::config::SymbolsConfig;
+use crate::demangle;
+use crate::dwarf::{self, DwarfSymbols};
+use crate::pe::{self, CodeViewInfo, DebugId};
 use crate::{CtdError, Result};
 
+/// How strictly a PDB must match the module it is loaded for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PdbMatchMode {
+    /// Never load a PDB whose GUID and age differ from the module's CodeView
+    /// record. A PDB that can't be checked (the module has no readable
+    /// CodeView record, or the PDB's own identity can't be read) is loaded
+    /// with a warning.
+    #[default]
+    #[serde(rename = "strict")]
+    StrictMatch,
+    /// Prefer a matching PDB, but fall back to a filename match with a warning.
+    #[serde(rename = "best-effort")]
+    BestEffort,
+}
+
+impl PdbMatchMode {
+    /// Picks the PDB to load for `module_name` when none matched it: one that
+    /// couldn't be checked, else one from another build if the mode allows.
+    fn fallback(
+        self,
+        module_name: &str,
+        unverified: Option<PathBuf>,
+        mismatched: Option<PathBuf>,
+    ) -> Option<PathBuf> {
+        if let Some(path) = unverified {
+            warn!("Loading unverified PDB {:?} for {}", path, module_name);
+            return Some(path);
+        }
+
+        match (self, mismatched) {
+            (Self::BestEffort, Some(path)) => {
+                warn!("Loading mismatched PDB {:?} for {}", path, module_name);
+                Some(path)
+            }
+            (Self::StrictMatch, Some(path)) => {
+                warn!(
+                    "Skipping PDB {:?} for {}: GUID/age does not match",
+                    path, module_name
+                );
+                None
+            }
+            (_, None) => {
+                debug!("PDB not found for module: {}", module_name);
+                None
+            }
+        }
+    }
+}
+
 /// A resolved stack frame with optional symbol information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedFrame {
@@ -27,6 +80,9 @@ pub struct ResolvedFrame {
     pub file: Option<String>,
     /// Line number in source file, if available.
     pub line: Option<u32>,
+    /// Raw decorated name when `function` holds a demangled form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mangled: Option<String>,
 }
 
 impl ResolvedFrame {
@@ -38,6 +94,7 @@ impl ResolvedFrame {
             function: None,
             file: None,
             line: None,
+            mangled: None,
         }
     }
 
@@ -55,9 +112,15 @@ impl ResolvedFrame {
             function: Some(function.into()),
             file,
             line,
+            mangled: None,
         }
     }
 
+    /// Returns the demangled function name, or the raw name if it is not mangled.
+    pub fn demangled(&self) -> Option<String> {
+        self.function.as_deref().map(demangle::demangle_or_raw)
+    }
+
     /// Returns true if this frame has symbol information.
     pub fn is_resolved(&self) -> bool {
         self.function.is_some()
@@ -81,7 +144,17 @@ impl ResolvedFrame {
     }
 }
 
+/// Magic bytes at the start of a symbol cache file.
+const CACHE_MAGIC: &[u8; 4] = b"CTDS";
+
+/// Version of the symbol cache file format.
+const CACHE_VERSION: u32 = 1;
+
+/// File extension for symbol cache files.
+const CACHE_EXTENSION: &str = "symcache";
+
 /// Cached symbol information for a single module.
+#[derive(Debug, PartialEq)]
 struct ModuleSymbols {
     /// Function addresses sorted for binary search.
     /// Each entry is (rva, function_name).
@@ -98,6 +171,91 @@ impl ModuleSymbols {
             Err(idx) => Some(&self.functions[idx - 1].1),
         }
     }
+
+    /// Writes the symbol table to a compact binary cache file.
+    ///
+    /// Layout (little-endian): magic, format version, entry count, then
+    /// `(rva: u32, name_len: u32, name: [u8])` per entry in RVA order.
+    fn save(&self, path: &Path) -> Result<()> {
+        let io_err =
+            |e: std::io::Error| CtdError::Symbol(format!("Failed to write symbol cache: {}", e));
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err)?;
+        }
+
+        // Write to a temp file first so a crash mid-write never leaves a
+        // truncated cache behind
+        let tmp_path = path.with_extension("tmp");
+        let mut out = BufWriter::new(File::create(&tmp_path).map_err(io_err)?);
+
+        out.write_all(CACHE_MAGIC).map_err(io_err)?;
+        out.write_all(&CACHE_VERSION.to_le_bytes())
+            .map_err(io_err)?;
+        out.write_all(&(self.functions.len() as u32).to_le_bytes())
+            .map_err(io_err)?;
+
+        for (rva, name) in &self.functions {
+            out.write_all(&rva.to_le_bytes()).map_err(io_err)?;
+            out.write_all(&(name.len() as u32).to_le_bytes())
+                .map_err(io_err)?;
+            out.write_all(name.as_bytes()).map_err(io_err)?;
+        }
+
+        out.flush().map_err(io_err)?;
+        drop(out);
+        std::fs::rename(&tmp_path, path).map_err(io_err)
+    }
+
+    /// Reads a symbol table previously written by [`ModuleSymbols::save`].
+    fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| CtdError::Symbol(format!("Failed to open symbol cache: {}", e)))?;
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let mut input = BufReader::new(file);
+
+        let corrupt = || CtdError::Symbol(format!("Corrupt symbol cache: {:?}", path));
+        let read_u32 = |input: &mut BufReader<File>| -> Result<u32> {
+            let mut buf = [0u8; 4];
+            input.read_exact(&mut buf).map_err(|_| corrupt())?;
+            Ok(u32::from_le_bytes(buf))
+        };
+
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic).map_err(|_| corrupt())?;
+        if &magic != CACHE_MAGIC {
+            return Err(CtdError::Symbol(format!("Not a symbol cache: {:?}", path)));
+        }
+        if read_u32(&mut input)? != CACHE_VERSION {
+            return Err(CtdError::Symbol("Unsupported symbol cache version".into()));
+        }
+
+        // Each entry is at least 8 bytes, which bounds the allocation for
+        // corrupt counts
+        let count = read_u32(&mut input)? as usize;
+        let mut functions = Vec::with_capacity(count.min(len as usize / 8));
+        for _ in 0..count {
+            let rva = read_u32(&mut input)?;
+            let name_len = read_u32(&mut input)? as u64;
+            if name_len > len {
+                return Err(corrupt());
+            }
+
+            let mut name = Vec::with_capacity(name_len as usize);
+            (&mut input)
+                .take(name_len)
+                .read_to_end(&mut name)
+                .map_err(|_| corrupt())?;
+            if name.len() as u64 != name_len {
+                return Err(corrupt());
+            }
+
+            let name = String::from_utf8(name).map_err(|_| corrupt())?;
+            functions.push((rva, name));
+        }
+
+        Ok(Self { functions })
+    }
 }
 
 /// Symbol resolver that parses PDB files and resolves addresses.
@@ -108,6 +266,12 @@ pub struct SymbolResolver {
     search_dirs: Vec<PathBuf>,
     /// Cached parsed symbols by module name (lowercase).
     modules: HashMap<String, ModuleSymbols>,
+    /// Modules for which no usable PDB was found.
+    missing: HashSet<String>,
+    /// DWARF symbols for ELF modules by module name (lowercase).
+    elf_modules: HashMap<String, DwarfSymbols>,
+    /// How candidate PDBs are validated before loading.
+    match_mode: PdbMatchMode,
 }
 
 impl SymbolResolver {
@@ -117,15 +281,49 @@ impl SymbolResolver {
             cache_dir: cache_dir.into(),
             search_dirs: Vec::new(),
             modules: HashMap::new(),
+            missing: HashSet::new(),
+            elf_modules: HashMap::new(),
+            match_mode: PdbMatchMode::default(),
         }
     }
 
+    /// Creates a resolver from the symbols section of the config.
+    ///
+    /// Falls back to `<system cache dir>/ctd/symbols` when no cache
+    /// directory is configured.
+    pub fn from_config(config: &SymbolsConfig) -> Self {
+        let cache_dir = config.cache_dir.clone().unwrap_or_else(|| {
+            dirs::cache_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("ctd")
+                .join("symbols")
+        });
+
+        let mut resolver = Self::new(cache_dir);
+        resolver.search_dirs = config.search_dirs.clone();
+        resolver.match_mode = config.match_mode;
+        resolver
+    }
+
+    /// Sets how candidate PDBs are validated against their modules.
+    pub fn set_match_mode(&mut self, mode: PdbMatchMode) {
+        self.match_mode = mode;
+    }
+
+    /// Returns the current PDB match mode.
+    pub fn match_mode(&self) -> PdbMatchMode {
+        self.match_mode
+    }
+
     /// Adds a directory to search for PDB files.
     pub fn add_search_dir(&mut self, dir: impl Into<PathBuf>) {
         self.search_dirs.push(dir.into());
     }
 
     /// Loads a PDB file and caches its symbols.
+    ///
+    /// The module name is taken from the PDB file name. No GUID validation
+    /// is performed; use [`SymbolResolver::resolve`] for matched loading.
     pub fn add_pdb(&mut self, pdb_path: &Path) -> Result<()> {
         let module_name = pdb_path
             .file_stem()
@@ -133,20 +331,49 @@ impl SymbolResolver {
             .ok_or_else(|| CtdError::Symbol("Invalid PDB path".into()))?
             .to_lowercase();
 
-        debug!("Loading PDB for module: {}", module_name);
+        self.load_pdb(module_name, pdb_path)
+    }
+
+    /// Loads a PDB file and caches its symbols under the given module key.
+    ///
+    /// Parsed tables are persisted in the cache directory keyed by the PDB's
+    /// GUID and age, so later runs skip re-parsing the PDB entirely.
+    fn load_pdb(&mut self, module_key: String, pdb_path: &Path) -> Result<()> {
+        debug!("Loading PDB for module: {}", module_key);
 
-        let file = File::open(pdb_path)
-            .map_err(|e| CtdError::Symbol(format!("Failed to open PDB: {}", e)))?;
+        let mut pdb = open_pdb(pdb_path)?;
+        let cache_path = pdb_id(&mut pdb).ok().map(|id| self.symbol_cache_path(&id));
 
-        let mut pdb = PDB::open(BufReader::new(file))
-            .map_err(|e| CtdError::Symbol(format!("Failed to parse PDB: {}", e)))?;
+        if let Some(path) = &cache_path
+            && path.exists()
+        {
+            match ModuleSymbols::load(path) {
+                Ok(symbols) => {
+                    debug!("Loaded {} symbols from cache", symbols.functions.len());
+                    self.modules.insert(module_key, symbols);
+                    return Ok(());
+                }
+                Err(e) => warn!("Ignoring symbol cache {:?}: {}", path, e),
+            }
+        }
 
         let symbols = self.extract_symbols(&mut pdb)?;
-        self.modules.insert(module_name, symbols);
+        if let Some(path) = &cache_path
+            && let Err(e) = symbols.save(path)
+        {
+            warn!("Failed to cache symbols for {}: {}", module_key, e);
+        }
+        self.modules.insert(module_key, symbols);
 
         Ok(())
     }
 
+    /// Returns the cache file path for a PDB with the given identity.
+    fn symbol_cache_path(&self, id: &DebugId) -> PathBuf {
+        self.cache_dir
+            .join(format!("{}.{}", id.signature(), CACHE_EXTENSION))
+    }
+
     /// Extracts function symbols from a PDB file.
     fn extract_symbols<'s, S: pdb::Source<'s> + 's>(
         &self,
@@ -199,19 +426,60 @@ impl SymbolResolver {
             .unwrap_or("unknown")
             .to_lowercase();
 
-        // Try to load PDB if not already cached
-        if !self.modules.contains_key(&module_key)
-            && let Some(pdb_path) = self.find_pdb(&module_key)
-            && let Err(e) = self.add_pdb(&pdb_path)
+        // ELF modules carry their own DWARF info, so no PDB search is needed.
+        // Each file is probed once: after that it is in one of the caches.
+        if !self.elf_modules.contains_key(&module_key)
+            && !self.modules.contains_key(&module_key)
+            && !self.missing.contains(&module_key)
+            && dwarf::is_elf(module_path)
         {
-            debug!("Failed to load PDB for {}: {}", module_key, e);
+            match DwarfSymbols::open(module_path) {
+                Ok(symbols) => {
+                    self.elf_modules.insert(module_key.clone(), symbols);
+                }
+                Err(e) => {
+                    debug!("Failed to load DWARF for {}: {}", module_key, e);
+                    self.missing.insert(module_key.clone());
+                }
+            }
+        }
+
+        if let Some(symbols) = self.elf_modules.get(&module_key) {
+            return match symbols.lookup(offset) {
+                Some((function, file, line)) => {
+                    ResolvedFrame::resolved(&module_name, offset, function, file, line)
+                }
+                None => ResolvedFrame::unresolved(&module_name, offset),
+            };
+        }
+
+        // Try to load PDB if not already cached
+        if !self.modules.contains_key(&module_key) && !self.missing.contains(&module_key) {
+            let loaded = match self.find_pdb(module_path, &module_key) {
+                Some(pdb_path) => match self.load_pdb(module_key.clone(), &pdb_path) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        debug!("Failed to load PDB for {}: {}", module_key, e);
+                        false
+                    }
+                },
+                None => false,
+            };
+            if !loaded {
+                self.missing.insert(module_key.clone());
+            }
         }
 
         // Look up the symbol
         if let Some(symbols) = self.modules.get(&module_key)
             && let Some(func_name) = symbols.lookup(offset as u32)
         {
-            return ResolvedFrame::resolved(&module_name, offset, func_name, None, None);
+            let mut frame = ResolvedFrame::resolved(&module_name, offset, func_name, None, None);
+            if let Some(demangled) = demangle::demangle(func_name) {
+                frame.function = Some(demangled);
+                frame.mangled = Some(func_name.to_string());
+            }
+            return frame;
         }
 
         ResolvedFrame::unresolved(&module_name, offset)
@@ -225,27 +493,76 @@ impl SymbolResolver {
             .collect()
     }
 
-    /// Searches for a PDB file matching the given module name.
-    fn find_pdb(&self, module_name: &str) -> Option<PathBuf> {
-        let pdb_name = format!("{}.pdb", module_name);
-
-        // Search in configured directories
-        for dir in &self.search_dirs {
-            let path = dir.join(&pdb_name);
-            if path.exists() {
-                debug!("Found PDB: {:?}", path);
-                return Some(path);
+    /// Searches for a PDB file matching the given module.
+    ///
+    /// Candidates are looked up in the search directories, the module's own
+    /// directory, and the cache directory, under both the module name and the
+    /// PDB name recorded in the module's CodeView record. Each candidate is
+    /// validated against the module's GUID and age; without a match, the
+    /// match mode decides whether a PDB that couldn't be checked or one from
+    /// another build is loaded instead.
+    fn find_pdb(&self, module_path: &Path, module_name: &str) -> Option<PathBuf> {
+        let codeview = match pe::read_codeview(module_path) {
+            Ok(cv) => Some(cv),
+            Err(e) => {
+                debug!("No CodeView record for {:?}: {}", module_path, e);
+                None
             }
+        };
+
+        let mut pdb_names = vec![format!("{}.pdb", module_name)];
+        if let Some(name) = codeview.as_ref().and_then(CodeViewInfo::pdb_file_name)
+            && !pdb_names.iter().any(|n| n.eq_ignore_ascii_case(name))
+        {
+            pdb_names.push(name.to_string());
         }
 
-        // Search in cache directory
-        let cache_path = self.cache_dir.join(&pdb_name);
-        if cache_path.exists() {
-            return Some(cache_path);
+        let mut dirs: Vec<&Path> = self.search_dirs.iter().map(PathBuf::as_path).collect();
+        if let Some(parent) = module_path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            dirs.push(parent);
         }
+        dirs.push(&self.cache_dir);
+
+        let mut unverified = None;
+        let mut mismatched = None;
+        for dir in dirs {
+            for pdb_name in &pdb_names {
+                let path = dir.join(pdb_name);
+                if !path.exists() {
+                    continue;
+                }
 
-        debug!("PDB not found for module: {}", module_name);
-        None
+                let Some(cv) = &codeview else {
+                    unverified.get_or_insert(path);
+                    continue;
+                };
+                match read_pdb_id(&path) {
+                    Ok(id) if id == cv.id => {
+                        debug!("Found matching PDB: {:?}", path);
+                        return Some(path);
+                    }
+                    Ok(id) => {
+                        debug!(
+                            "PDB {:?} does not match {} (expected {}, found {})",
+                            path,
+                            module_name,
+                            cv.id.signature(),
+                            id.signature()
+                        );
+                        mismatched.get_or_insert(path);
+                    }
+                    Err(e) => {
+                        debug!("Failed to read PDB identity {:?}: {}", path, e);
+                        unverified.get_or_insert(path);
+                    }
+                }
+            }
+        }
+
+        self.match_mode
+            .fallback(module_name, unverified, mismatched)
     }
 
     /// Discovers and loads all PDB files in the search directories.
@@ -273,7 +590,7 @@ impl SymbolResolver {
 
     /// Returns the number of loaded modules.
     pub fn loaded_module_count(&self) -> usize {
-        self.modules.len()
+        self.modules.len() + self.elf_modules.len()
     }
 
     /// Returns the cache directory path.
@@ -282,6 +599,40 @@ impl SymbolResolver {
     }
 }
 
+/// Opens a PDB file for parsing.
+fn open_pdb(pdb_path: &Path) -> Result<PDB<'static, BufReader<File>>> {
+    let file =
+        File::open(pdb_path).map_err(|e| CtdError::Symbol(format!("Failed to open PDB: {}", e)))?;
+
+    PDB::open(BufReader::new(file))
+        .map_err(|e| CtdError::Symbol(format!("Failed to parse PDB: {}", e)))
+}
+
+/// Reads the GUID and age identifying a PDB file.
+///
+/// The age is taken from the DBI stream, which is what the linker writes
+/// into the module's CodeView record; the PDB info stream age is only used
+/// when the DBI stream does not carry one.
+pub fn read_pdb_id(pdb_path: &Path) -> Result<DebugId> {
+    pdb_id(&mut open_pdb(pdb_path)?)
+}
+
+/// Reads the GUID and age of an already opened PDB.
+fn pdb_id<'s, S: pdb::Source<'s> + 's>(pdb: &mut PDB<'s, S>) -> Result<DebugId> {
+    let info = pdb
+        .pdb_information()
+        .map_err(|e| CtdError::Symbol(format!("Failed to read PDB info stream: {}", e)))?;
+
+    let age = pdb
+        .debug_information()
+        .ok()
+        .and_then(|dbi| dbi.age())
+        .unwrap_or(info.age);
+
+    let (data1, data2, data3, data4) = info.guid.as_fields();
+    Ok(DebugId::from_fields(data1, data2, data3, *data4, age))
+}
+
 /// Formats a stack trace string with resolved symbols.
 pub fn format_stack_trace(frames: &[ResolvedFrame]) -> String {
     frames
@@ -326,6 +677,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn resolved_frame_demangles_raw_name() {
+        let frame =
+            ResolvedFrame::resolved("test.dll", 0x10, "?Update@Actor@@UEAAXM@Z", None, None);
+        assert!(frame.demangled().unwrap().contains("Actor::Update"));
+
+        let plain = ResolvedFrame::resolved("test.dll", 0x10, "WinMain", None, None);
+        assert_eq!(plain.demangled().as_deref(), Some("WinMain"));
+        assert_eq!(
+            ResolvedFrame::unresolved("test.dll", 0x10).demangled(),
+            None
+        );
+    }
+
+    #[test]
+    fn resolve_demangles_cached_symbols() {
+        let dir = tempdir().unwrap();
+        let mut resolver = SymbolResolver::new(dir.path());
+        resolver.modules.insert(
+            "game".into(),
+            ModuleSymbols {
+                functions: vec![(0x1000, "?Update@Actor@@UEAAXM@Z".into())],
+            },
+        );
+
+        let frame = resolver.resolve(Path::new("Game.exe"), 0x1010);
+        assert!(frame.function.as_deref().unwrap().contains("Actor::Update"));
+        assert_eq!(frame.mangled.as_deref(), Some("?Update@Actor@@UEAAXM@Z"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn resolver_symbolicates_elf_frames() {
+        let dir = tempdir().unwrap();
+        let mut resolver = SymbolResolver::new(dir.path());
+        let exe = std::env::current_exe().unwrap();
+        let base = dwarf::tests::current_exe_base().expect("test executable mapping");
+        let offset = dwarf::tests::dwarf_probe_marker as *const () as usize as u64 - base;
+
+        let frame = resolver.resolve(&exe, offset);
+        assert!(
+            frame
+                .function
+                .as_deref()
+                .is_some_and(|f| f.contains("dwarf_probe_marker")),
+            "got {:?}",
+            frame.function
+        );
+        assert_eq!(resolver.loaded_module_count(), 1);
+    }
+
     #[test]
     fn resolver_creates_with_cache_dir() {
         let dir = tempdir().unwrap();
@@ -344,6 +746,216 @@ mod tests {
         assert_eq!(frame.offset, 0x1234);
     }
 
+    #[test]
+    fn module_symbols_cache_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("test.symcache");
+        let symbols = ModuleSymbols {
+            functions: vec![(0x1000, "Alpha".into()), (0x2000, "Beta".into())],
+        };
+
+        symbols.save(&path).unwrap();
+        let loaded = ModuleSymbols::load(&path).unwrap();
+
+        assert_eq!(loaded, symbols);
+        assert_eq!(loaded.lookup(0x1800), Some("Alpha"));
+        assert_eq!(loaded.lookup(0x2004), Some("Beta"));
+    }
+
+    #[test]
+    fn module_symbols_cache_rejects_truncated_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.symcache");
+        let symbols = ModuleSymbols {
+            functions: vec![(0x1000, "Alpha".into())],
+        };
+        symbols.save(&path).unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &data[..data.len() - 2]).unwrap();
+
+        assert!(ModuleSymbols::load(&path).is_err());
+    }
+
+    #[test]
+    fn module_symbols_cache_rejects_foreign_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.symcache");
+        std::fs::write(&path, b"garbage data").unwrap();
+
+        assert!(ModuleSymbols::load(&path).is_err());
+    }
+
+    #[test]
+    fn symbol_cache_path_is_keyed_by_debug_id() {
+        let dir = tempdir().unwrap();
+        let resolver = SymbolResolver::new(dir.path());
+        let id = DebugId {
+            guid: [0xAB; 16],
+            age: 3,
+        };
+
+        let path = resolver.symbol_cache_path(&id);
+        assert_eq!(path.parent(), Some(dir.path()));
+        assert_eq!(
+            path.file_name().and_then(|n| n.to_str()),
+            Some("ABABABABABABABABABABABABABABABAB3.symcache")
+        );
+    }
+
+    #[test]
+    fn match_mode_defaults_to_strict() {
+        let dir = tempdir().unwrap();
+        let resolver = SymbolResolver::new(dir.path());
+        assert_eq!(resolver.match_mode(), PdbMatchMode::StrictMatch);
+    }
+
+    #[test]
+    fn strict_mode_loads_unverifiable_pdb() {
+        let dir = tempdir().unwrap();
+        let module = dir.path().join("mymod.dll");
+        std::fs::write(&module, pe::tests::build_test_pe([7; 16], 1, "mymod.pdb")).unwrap();
+        let pdb_path = dir.path().join("mymod.pdb");
+        std::fs::write(&pdb_path, b"not a pdb").unwrap();
+
+        let resolver = SymbolResolver::new(dir.path());
+        assert_eq!(resolver.find_pdb(&module, "mymod"), Some(pdb_path));
+    }
+
+    #[test]
+    fn strict_mode_loads_pdb_for_module_without_codeview() {
+        let dir = tempdir().unwrap();
+        let pdb_path = dir.path().join("mymod.pdb");
+        std::fs::write(&pdb_path, b"not a pdb").unwrap();
+
+        // Only the module name is known, as in `ctd resolve` without `--modules`
+        let resolver = SymbolResolver::new(dir.path().join("cache"));
+        let module = dir.path().join("mymod.dll");
+        assert_eq!(resolver.find_pdb(&module, "mymod"), Some(pdb_path));
+    }
+
+    #[test]
+    fn strict_mode_rejects_mismatched_pdb() {
+        let mismatched = PathBuf::from("other-build/mymod.pdb");
+
+        assert_eq!(
+            PdbMatchMode::StrictMatch.fallback("mymod", None, Some(mismatched.clone())),
+            None
+        );
+        assert_eq!(
+            PdbMatchMode::BestEffort.fallback("mymod", None, Some(mismatched.clone())),
+            Some(mismatched.clone())
+        );
+
+        // A PDB that couldn't be checked wins over one known to be wrong
+        let unverified = PathBuf::from("mymod.pdb");
+        assert_eq!(
+            PdbMatchMode::StrictMatch.fallback("mymod", Some(unverified.clone()), Some(mismatched)),
+            Some(unverified)
+        );
+    }
+
+    /// Writes a minimal MSF 7.00 file holding only the PDB info stream, so
+    /// [`read_pdb_id`] sees the given GUID and age.
+    fn write_test_pdb(path: &Path, guid: [u8; 16], age: u32) {
+        const PAGE: usize = 0x1000;
+        let mut file = vec![0u8; PAGE * 5];
+        let put = |file: &mut Vec<u8>, at: usize, value: u32| {
+            file[at..at + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        // Header: page size, free page map, pages used, directory size
+        file[..32].copy_from_slice(b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0");
+        put(&mut file, 32, PAGE as u32);
+        put(&mut file, 36, 1);
+        put(&mut file, 40, 5);
+        put(&mut file, 44, 16);
+        // Page 2 lists the directory's pages
+        put(&mut file, 52, 2);
+        put(&mut file, 2 * PAGE, 3);
+
+        // Directory on page 3: two streams, the info stream on page 4
+        for (i, value) in [2, 0, 32, 4].into_iter().enumerate() {
+            put(&mut file, 3 * PAGE + i * 4, value);
+        }
+
+        // Info stream: version (VC70), signature, age, GUID, names size
+        let info = 4 * PAGE;
+        put(&mut file, info, 20000404);
+        put(&mut file, info + 8, age);
+        file[info + 12..info + 28].copy_from_slice(&guid);
+
+        std::fs::write(path, file).unwrap();
+    }
+
+    #[test]
+    fn test_pdb_identity_is_readable() {
+        let dir = tempdir().unwrap();
+        let pdb_path = dir.path().join("mymod.pdb");
+        write_test_pdb(&pdb_path, [7; 16], 3);
+
+        let id = read_pdb_id(&pdb_path).unwrap();
+        assert_eq!(
+            id,
+            DebugId {
+                guid: [7; 16],
+                age: 3
+            }
+        );
+    }
+
+    #[test]
+    fn matching_pdb_is_loaded() {
+        let dir = tempdir().unwrap();
+        let module = dir.path().join("mymod.dll");
+        std::fs::write(&module, pe::tests::build_test_pe([7; 16], 1, "mymod.pdb")).unwrap();
+        let pdb_path = dir.path().join("mymod.pdb");
+        write_test_pdb(&pdb_path, [7; 16], 1);
+
+        let resolver = SymbolResolver::new(dir.path());
+        assert_eq!(resolver.find_pdb(&module, "mymod"), Some(pdb_path));
+    }
+
+    #[test]
+    fn best_effort_mode_falls_back_to_filename_match() {
+        let dir = tempdir().unwrap();
+        let module = dir.path().join("mymod.dll");
+        std::fs::write(&module, pe::tests::build_test_pe([7; 16], 1, "mymod.pdb")).unwrap();
+        let pdb_path = dir.path().join("mymod.pdb");
+        // Same name, but from another build
+        write_test_pdb(&pdb_path, [8; 16], 2);
+
+        let mut resolver = SymbolResolver::new(dir.path());
+        assert_eq!(resolver.find_pdb(&module, "mymod"), None);
+
+        resolver.set_match_mode(PdbMatchMode::BestEffort);
+        assert_eq!(resolver.find_pdb(&module, "mymod"), Some(pdb_path));
+    }
+
+    #[test]
+    fn codeview_pdb_name_is_searched() {
+        let dir = tempdir().unwrap();
+        let module = dir.path().join("mymod.dll");
+        std::fs::write(
+            &module,
+            pe::tests::build_test_pe([7; 16], 1, "C:\\build\\MyMod_x64.pdb"),
+        )
+        .unwrap();
+        let pdb_path = dir.path().join("MyMod_x64.pdb");
+        write_test_pdb(&pdb_path, [7; 16], 2);
+
+        let mut resolver = SymbolResolver::new(dir.path());
+        assert_eq!(resolver.find_pdb(&module, "mymod"), None);
+
+        resolver.set_match_mode(PdbMatchMode::BestEffort);
+        assert_eq!(resolver.find_pdb(&module, "mymod"), Some(pdb_path.clone()));
+
+        // Once the age matches, the CodeView name is found in strict mode too
+        write_test_pdb(&pdb_path, [7; 16], 1);
+        resolver.set_match_mode(PdbMatchMode::StrictMatch);
+        assert_eq!(resolver.find_pdb(&module, "mymod"), Some(pdb_path));
+    }
+
     #[test]
     fn format_stack_trace_numbers_frames() {
         let frames = vec![
@@ -0,0 +1,71 @@
+//! Deliberate test crashes for verifying an installation.
+//!
+//! Users can't tell whether crash capture works until the game actually
+//! crashes. With `allow_test_crash = true` in ctd.toml, each game plugin
+//! exports `trigger_test_crash()`, which raises a real access violation on a
+//! scratch thread so the whole capture → submit pipeline runs end to end.
+
+use std::thread;
+
+use tracing::warn;
+
+use crate::config::Config;
+use crate::{CtdError, Result};
+
+/// Address written to by the test crash.
+///
+/// Lies in the unmapped first page on every supported platform, and is
+/// recognisable ("7E57") in the submitted exception address.
+pub const TEST_CRASH_ADDRESS: usize = 0x7E57;
+
+/// Name of the scratch thread that faults.
+pub const TEST_CRASH_THREAD: &str = "ctd-test-crash";
+
+/// Schedules a test crash if the config allows it.
+///
+/// The access violation happens on a new thread, so the caller returns
+/// normally and the process goes down shortly afterwards.
+///
+/// # Errors
+///
+/// Returns `CtdError::Config` if `allow_test_crash` is not enabled, or if the
+/// scratch thread cannot be spawned.
+pub fn trigger(config: &Config) -> Result<()> {
+    if !config.allow_test_crash {
+        return Err(CtdError::Config(
+            "Test crashes are disabled; set allow_test_crash = true in ctd.toml".to_string(),
+        ));
+    }
+
+    warn!("Triggering test crash on purpose");
+    thread::Builder::new()
+        .name(TEST_CRASH_THREAD.to_string())
+        .spawn(|| {
+            raise_access_violation();
+        })
+        .map(|_| ())
+        .map_err(|e| CtdError::Config(format!("Failed to spawn test crash thread: {}", e)))
+}
+
+/// Writes to an unmapped address, raising an access violation (SIGSEGV).
+fn raise_access_violation() -> ! {
+    // SAFETY: not safe by design - the fault is the whole point, and the
+    // installed crash handler takes over before anything else runs
+    unsafe {
+        std::ptr::write_volatile(TEST_CRASH_ADDRESS as *mut u32, 0xC7D);
+    }
+
+    // Only reached if the write somehow succeeded
+    std::process::abort()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_by_default() {
+        let err = trigger(&Config::default()).unwrap_err();
+        assert!(err.to_string().contains("allow_test_crash"));
+    }
+}
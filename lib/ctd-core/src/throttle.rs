@@ -0,0 +1,222 @@
+//! Sampling and rate limiting of crash report submissions.
+//!
+//! Enforces `[submission] sample_rate` and `max_reports_per_hour` so that a
+//! game stuck in a crash loop (or a modpack author testing) doesn't flood
+//! the backend. Recent submission times are persisted so the hourly limit
+//! holds across game restarts.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::fs;
+use std::hash::BuildHasher;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::config::{Config, SubmissionConfig};
+
+/// Length of the rate-limit window in milliseconds.
+const WINDOW_MS: u64 = 60 * 60 * 1000;
+
+/// Whether a crash report may be submitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Submit the report.
+    Allowed,
+    /// Skipped by `sample_rate`.
+    Sampled,
+    /// Skipped because `max_reports_per_hour` was reached.
+    RateLimited,
+}
+
+impl Verdict {
+    /// Returns true if the report should be submitted.
+    pub fn is_allowed(self) -> bool {
+        self == Self::Allowed
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Allowed => write!(f, "allowed"),
+            Self::Sampled => write!(f, "skipped by submission.sample_rate"),
+            Self::RateLimited => write!(f, "submission.max_reports_per_hour reached"),
+        }
+    }
+}
+
+/// Submission times within the current window, persisted between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ThrottleState {
+    /// Unix timestamps (milliseconds) of recent submissions.
+    recent: Vec<u64>,
+}
+
+/// Decides which crash reports are submitted.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    config: SubmissionConfig,
+    state_path: PathBuf,
+}
+
+impl Throttle {
+    /// Creates a throttle that persists its counters at `state_path`.
+    pub fn new(config: SubmissionConfig, state_path: impl Into<PathBuf>) -> Self {
+        Self {
+            config,
+            state_path: state_path.into(),
+        }
+    }
+
+    /// Creates a throttle from the loaded config with counters in the default location.
+    pub fn load() -> Self {
+        let config = Config::load().unwrap_or_default();
+        Self::new(config.submission, Self::default_state_path())
+    }
+
+    /// Returns the default counter file (`<local data dir>/ctd/throttle.json`).
+    pub fn default_state_path() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("ctd")
+            .join("throttle.json")
+    }
+
+    /// Decides whether to submit a report now, counting it if allowed.
+    pub fn admit(&self) -> Verdict {
+        self.admit_at(now_millis(), random_unit())
+    }
+
+    /// Decides whether to submit a report the player asked for.
+    ///
+    /// Skips the sampling roll but counts toward `max_reports_per_hour`;
+    /// `sample_rate = 0.0` still stops it.
+    pub fn admit_requested(&self) -> Verdict {
+        self.admit_at(now_millis(), 0.0)
+    }
+
+    /// Decides at time `now` (ms) with a sampling roll in `[0, 1)`.
+    fn admit_at(&self, now: u64, roll: f64) -> Verdict {
+        if roll >= self.config.sample_rate {
+            return Verdict::Sampled;
+        }
+
+        let Some(limit) = self.config.max_reports_per_hour else {
+            return Verdict::Allowed;
+        };
+
+        let mut state = read_state(&self.state_path);
+        state.recent.retain(|&t| t <= now && now - t < WINDOW_MS);
+        if state.recent.len() >= limit as usize {
+            return Verdict::RateLimited;
+        }
+
+        state.recent.push(now);
+        write_state(&self.state_path, &state);
+        Verdict::Allowed
+    }
+}
+
+/// Reads the persisted counters, treating a missing or corrupt file as empty.
+fn read_state(path: &Path) -> ThrottleState {
+    fs::read(path)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Persists the counters; failures only loosen the limit, so they are not fatal.
+fn write_state(path: &Path, state: &ThrottleState) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(path, serde_json::to_vec(state).unwrap_or_default()));
+    if let Err(e) = result {
+        debug!(
+            "Failed to persist throttle state to {}: {}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Returns the current time as a Unix timestamp in milliseconds.
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Returns a random number in `[0, 1)` for sampling.
+fn random_unit() -> f64 {
+    // RandomState is randomly seeded per instance; good enough for sampling
+    let bits = RandomState::new().hash_one(std::time::SystemTime::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle_with(
+        sample_rate: f64,
+        max_reports_per_hour: Option<u32>,
+    ) -> (Throttle, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SubmissionConfig {
+            sample_rate,
+            max_reports_per_hour,
+        };
+        (Throttle::new(config, dir.path().join("throttle.json")), dir)
+    }
+
+    #[test]
+    fn default_config_allows_everything() {
+        let (throttle, _dir) = throttle_with(1.0, None);
+        assert!((0..100).all(|_| throttle.admit().is_allowed()));
+    }
+
+    #[test]
+    fn sample_rate_skips_rolls_above_rate() {
+        let (throttle, _dir) = throttle_with(0.25, None);
+        assert_eq!(throttle.admit_at(0, 0.1), Verdict::Allowed);
+        assert_eq!(throttle.admit_at(0, 0.5), Verdict::Sampled);
+
+        let (never, _dir) = throttle_with(0.0, None);
+        assert_eq!(never.admit_at(0, 0.0), Verdict::Sampled);
+    }
+
+    #[test]
+    fn requested_reports_skip_sampling() {
+        let (throttle, _dir) = throttle_with(0.01, Some(1));
+        assert_eq!(throttle.admit_requested(), Verdict::Allowed);
+        assert_eq!(throttle.admit_requested(), Verdict::RateLimited);
+
+        let (never, _dir) = throttle_with(0.0, None);
+        assert_eq!(never.admit_requested(), Verdict::Sampled);
+    }
+
+    #[test]
+    fn hourly_limit_persists_and_expires() {
+        let (throttle, dir) = throttle_with(1.0, Some(2));
+        assert!(throttle.admit_at(1_000, 0.0).is_allowed());
+        assert!(throttle.admit_at(2_000, 0.0).is_allowed());
+
+        // A fresh throttle (e.g., after a game restart) sees the same counters
+        let restarted = Throttle::new(throttle.config.clone(), dir.path().join("throttle.json"));
+        assert_eq!(restarted.admit_at(3_000, 0.0), Verdict::RateLimited);
+        assert!(restarted.admit_at(1_000 + WINDOW_MS, 0.0).is_allowed());
+    }
+
+    #[test]
+    fn random_unit_is_in_range() {
+        assert!(
+            (0..100)
+                .map(|_| random_unit())
+                .all(|r| (0.0..1.0).contains(&r))
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Crash report transports.
+//!
+//! [`CrashTransport`] abstracts over how a report reaches the server so that
+//! submission logic (`crate::submit`, `Spool::flush`) can be tested without
+//! a network. [`ApiClient`] is the HTTP implementation; [`MockTransport`]
+//! keeps everything in memory.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::api_client::{ApiClient, KnownIssue};
+use crate::crash_report::{CrashReportResponse, CreateCrashReport};
+use crate::{CtdError, Result};
+
+/// Something that can deliver crash reports to the CTD backend.
+pub trait CrashTransport: Send + Sync {
+    /// Submits a crash report, returning the server's response.
+    fn submit(
+        &self,
+        report: &CreateCrashReport,
+    ) -> impl Future<Output = Result<CrashReportResponse>> + Send;
+
+    /// Returns the link that opens a submitted report, if the transport has one.
+    fn share_url(&self, _response: &CrashReportResponse) -> Option<String> {
+        None
+    }
+
+    /// Looks up the known fix for a submitted report, if the transport has
+    /// a known-issue database.
+    fn known_issue_for(
+        &self,
+        _report: &CreateCrashReport,
+    ) -> impl Future<Output = Option<KnownIssue>> + Send {
+        async { None }
+    }
+}
+
+impl CrashTransport for ApiClient {
+    fn submit(
+        &self,
+        report: &CreateCrashReport,
+    ) -> impl Future<Output = Result<CrashReportResponse>> + Send {
+        self.submit_crash_report(report)
+    }
+
+    fn share_url(&self, response: &CrashReportResponse) -> Option<String> {
+        // Sentry events have no share token
+        (!self.uses_sentry()).then(|| ApiClient::share_url(self, response))
+    }
+
+    fn known_issue_for(
+        &self,
+        report: &CreateCrashReport,
+    ) -> impl Future<Output = Option<KnownIssue>> + Send {
+        ApiClient::known_issue_for(self, report)
+    }
+}
+
+/// Outcome queued on a [`MockTransport`]; errors become `CtdError::ApiRequest`.
+type QueuedResponse = std::result::Result<CrashReportResponse, String>;
+
+/// In-memory transport that records submissions.
+///
+/// Succeeds with a generated ID unless responses were queued with
+/// [`MockTransport::push_response`] or [`MockTransport::push_error`].
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    submitted: Mutex<Vec<CreateCrashReport>>,
+    responses: Mutex<VecDeque<QueuedResponse>>,
+    fail_all: Option<String>,
+}
+
+impl MockTransport {
+    /// Creates a transport that accepts every report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a transport that rejects every report with the given message.
+    pub fn failing(message: impl Into<String>) -> Self {
+        Self {
+            fail_all: Some(message.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Queues a successful response for the next submission.
+    pub fn push_response(&self, response: CrashReportResponse) {
+        self.lock_responses().push_back(Ok(response));
+    }
+
+    /// Queues a failure for the next submission.
+    pub fn push_error(&self, message: impl Into<String>) {
+        self.lock_responses().push_back(Err(message.into()));
+    }
+
+    /// Returns every report submitted so far, including failed attempts.
+    pub fn submitted(&self) -> Vec<CreateCrashReport> {
+        self.submitted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns the number of submission attempts.
+    pub fn submission_count(&self) -> usize {
+        self.submitted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    fn lock_responses(&self) -> MutexGuard<'_, VecDeque<QueuedResponse>> {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn respond(&self, report: &CreateCrashReport) -> Result<CrashReportResponse> {
+        let attempt = {
+            let mut submitted = self.submitted.lock().unwrap_or_else(|e| e.into_inner());
+            submitted.push(report.clone());
+            submitted.len()
+        };
+
+        if let Some(message) = &self.fail_all {
+            return Err(CtdError::ApiRequest(message.clone()));
+        }
+
+        match self.lock_responses().pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(message)) => Err(CtdError::ApiRequest(message)),
+            None => Ok(CrashReportResponse {
+                id: format!("mock-{}", attempt),
+                share_token: format!("share-{}", attempt),
+            }),
+        }
+    }
+}
+
+impl CrashTransport for MockTransport {
+    fn submit(
+        &self,
+        report: &CreateCrashReport,
+    ) -> impl Future<Output = Result<CrashReportResponse>> + Send {
+        let result = self.respond(report);
+        async move { result }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_order::ModList;
+
+    fn sample_report() -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("[0] SkyrimSE.exe+0x1234")
+            .load_order_v2(ModList::new())
+            .crashed_at(1000)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn mock_records_and_accepts() {
+        let transport = MockTransport::new();
+        let response = transport.submit(&sample_report()).await.unwrap();
+
+        assert_eq!(response.id, "mock-1");
+        assert_eq!(transport.submission_count(), 1);
+        assert_eq!(transport.submitted()[0].game_id, "skyrim-se");
+    }
+
+    #[tokio::test]
+    async fn mock_replays_queued_responses() {
+        let transport = MockTransport::new();
+        transport.push_error("503 Service Unavailable");
+        transport.push_response(CrashReportResponse {
+            id: "01HX".into(),
+            share_token: "tok".into(),
+        });
+
+        assert!(transport.submit(&sample_report()).await.is_err());
+        assert_eq!(transport.submit(&sample_report()).await.unwrap().id, "01HX");
+        assert_eq!(
+            transport.submit(&sample_report()).await.unwrap().id,
+            "mock-3"
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_mock_rejects_everything() {
+        let transport = MockTransport::failing("offline");
+        let err = transport.submit(&sample_report()).await.unwrap_err();
+
+        assert!(err.to_string().contains("offline"));
+        assert_eq!(transport.submission_count(), 1);
+    }
+}
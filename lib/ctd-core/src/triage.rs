@@ -0,0 +1,315 @@
+//! Crash categories and triage hints.
+//!
+//! Most people reading a crash report want to know what to do next, not
+//! which instruction faulted. [`classify`] sorts a crash into a
+//! [`CrashCategory`] from its exception and what the diagnostics modules
+//! found: the in-page status from [`crate::in_page`], the GPU removal
+//! reason, detoured exports from [`crate::detours`], and Papyrus stacks.
+//! [`hint`] turns the category into a sentence for the report's
+//! `triageHint`, naming the module, file, or script involved.
+//!
+//! [`CrashReportBuilder::build`](crate::crash_report::CrashReportBuilder::build)
+//! runs both for crashes, keeping a category the plugin set itself. Causes
+//! outside game and mod code are checked first, so a failing drive isn't
+//! reported as the null dereference it led to.
+
+use crate::crash_report::{CrashCategory, CreateCrashReport, MAX_TRIAGE_HINT_LEN, ReportType};
+use crate::{graphics, in_page};
+
+const ACCESS_VIOLATION: u32 = 0xC0000005;
+const STACK_OVERFLOW: u32 = 0xC00000FD;
+const NO_MEMORY: u32 = 0xC0000017;
+const COMMITMENT_LIMIT: u32 = 0xC000012D;
+
+/// Addresses below this are in the never-mapped first 64 KiB, so an access
+/// there came from a null pointer plus a field offset.
+const NULL_PAGE_END: u64 = 0x10000;
+
+/// Sets a crash report's category and hint, unless the plugin set them.
+///
+/// `parameters` is the exception record's `ExceptionInformation`.
+pub(crate) fn apply(report: &mut CreateCrashReport, parameters: &[u64]) {
+    if report.report_type != ReportType::Crash {
+        return;
+    }
+    if report.crash_category.is_none() {
+        report.crash_category = classify(report, parameters);
+    }
+    if report.triage_hint.is_none()
+        && let Some(category) = report.crash_category
+    {
+        report.triage_hint = Some(hint(report, category, parameters));
+    }
+}
+
+/// Returns the kind of crash a report describes, if it is recognized.
+///
+/// `parameters` is the exception record's `ExceptionInformation`, needed to
+/// tell a null dereference from other access violations.
+pub fn classify(report: &CreateCrashReport, parameters: &[u64]) -> Option<CrashCategory> {
+    let code = exception_code(report);
+    if code == Some(STACK_OVERFLOW) {
+        return Some(CrashCategory::StackOverflow);
+    }
+    if matches!(code, Some(NO_MEMORY | COMMITMENT_LIMIT)) {
+        return Some(CrashCategory::OutOfMemory);
+    }
+    if let Some(category) = report.in_page_error.as_ref().and_then(in_page::category) {
+        return Some(category);
+    }
+    if code.is_some_and(is_dxgi_error)
+        || report.device_removed_reason.is_some()
+        || report
+            .faulting_module
+            .as_deref()
+            .and_then(graphics::driver_vendor)
+            .is_some()
+    {
+        return Some(CrashCategory::Gpu);
+    }
+    if !report.hooked_functions.is_empty() {
+        return Some(CrashCategory::HookConflict);
+    }
+    if code == Some(ACCESS_VIOLATION) && null_access(parameters).is_some() {
+        return Some(CrashCategory::NullDeref);
+    }
+    if !report.papyrus_stacks.is_empty() {
+        return Some(CrashCategory::Script);
+    }
+    None
+}
+
+/// Describes what a crash of `category` means and what to try, in one or
+/// two sentences of at most [`MAX_TRIAGE_HINT_LEN`] characters.
+pub fn hint(report: &CreateCrashReport, category: CrashCategory, parameters: &[u64]) -> String {
+    let module = report.faulting_module.as_deref().unwrap_or("the game");
+    let text = match category {
+        CrashCategory::NullDeref => {
+            let access = match null_access(parameters) {
+                Some((0, address)) => format!("read from a null pointer (address 0x{:X})", address),
+                Some((1, address)) => format!("wrote to a null pointer (address 0x{:X})", address),
+                Some((8, _)) => "called a null function pointer".to_string(),
+                _ => "used a null pointer".to_string(),
+            };
+            let check = match &report.suspected_mod {
+                Some(suspect) => format!(" Check {} first.", suspect),
+                None => String::new(),
+            };
+            format!(
+                "{} {}. An object it expected was missing, often a form, mesh, or \
+                 script target that a mod removed or never loaded.{}",
+                module, access, check
+            )
+        }
+        CrashCategory::StackOverflow => format!(
+            "A thread ran out of stack space in {}, usually from runaway recursion \
+             such as two mods calling into each other.",
+            module
+        ),
+        CrashCategory::OutOfMemory => "The game ran out of memory. Close other programs, use \
+             smaller texture packs, or let Windows manage the page file size."
+            .to_string(),
+        CrashCategory::Gpu => {
+            let reason = match &report.device_removed_reason {
+                Some(reason) => format!(" ({})", reason),
+                None => String::new(),
+            };
+            format!(
+                "The graphics driver crashed or stopped responding{}. Update or reinstall \
+                 the GPU driver and undo GPU overclocks; this is rarely a mod bug.",
+                reason
+            )
+        }
+        CrashCategory::DiskIo => {
+            let (file, status) = match &report.in_page_error {
+                Some(error) => (
+                    error.file.as_deref().unwrap_or("a game file"),
+                    error.status_name.as_deref().unwrap_or(&error.status),
+                ),
+                None => ("a game file", "a read error"),
+            };
+            format!(
+                "Windows couldn't read {} from disk ({}). Check the drive's health and \
+                 verify the game files; this is not a mod bug.",
+                file, status
+            )
+        }
+        CrashCategory::HookConflict => {
+            let mut owners: Vec<&str> = report
+                .hooked_functions
+                .iter()
+                .filter_map(|hook| hook.owner.as_deref())
+                .collect();
+            owners.sort_unstable();
+            owners.dedup();
+            let by = if owners.is_empty() {
+                "code outside every module".to_string()
+            } else {
+                owners.join(", ")
+            };
+            format!(
+                "{} crashed with functions hooked by {}. Hooks from two mods on the same \
+                 functions often conflict; try disabling one of them.",
+                module, by
+            )
+        }
+        CrashCategory::Script => match report.papyrus_stacks.iter().min_by_key(|f| f.depth) {
+            Some(frame) => format!(
+                "The Papyrus script {}.{} was running when the game crashed. Check the \
+                 mod that ships it.",
+                frame.script, frame.function
+            ),
+            None => "A script was running when the game crashed. Check the mods that add \
+                 scripts."
+                .to_string(),
+        },
+    };
+    truncate(text, MAX_TRIAGE_HINT_LEN)
+}
+
+/// Returns the access type and address of an access violation in the null
+/// page.
+fn null_access(parameters: &[u64]) -> Option<(u64, u64)> {
+    let (&access, &address) = (parameters.first()?, parameters.get(1)?);
+    (address < NULL_PAGE_END).then_some((access, address))
+}
+
+fn exception_code(report: &CreateCrashReport) -> Option<u32> {
+    let code = report.exception_code.as_deref()?;
+    u32::from_str_radix(code.trim_start_matches("0x"), 16).ok()
+}
+
+/// DXGI errors are `0x887Axxxx`.
+fn is_dxgi_error(code: u32) -> bool {
+    code >> 16 == 0x887A
+}
+
+fn truncate(mut text: String, max_len: usize) -> String {
+    if text.len() > max_len {
+        let mut end = max_len;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crash_report::{HookedFunction, PapyrusFrame};
+    use crate::load_order::ModList;
+
+    fn report(code: &str) -> CreateCrashReport {
+        CreateCrashReport::builder()
+            .game_id("skyrim-se")
+            .game_version("1.6.1170")
+            .stack_trace("trace")
+            .load_order_v2(ModList::new())
+            .exception_code(code)
+            .faulting_module("SkyrimSE.exe")
+            .crashed_at(1000)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn null_page_access_is_a_null_deref() {
+        let report = report("0xC0000005");
+        assert_eq!(
+            classify(&report, &[0, 0x28]),
+            Some(CrashCategory::NullDeref)
+        );
+        assert_eq!(classify(&report, &[0, 0x1F2A0000]), None);
+        assert_eq!(classify(&report, &[]), None);
+
+        let text = hint(&report, CrashCategory::NullDeref, &[1, 0x28]);
+        assert!(text.starts_with("SkyrimSE.exe wrote to a null pointer (address 0x28)"));
+    }
+
+    #[test]
+    fn environment_comes_before_the_fault() {
+        assert_eq!(
+            classify(&report("0xC00000FD"), &[]),
+            Some(CrashCategory::StackOverflow)
+        );
+        assert_eq!(
+            classify(&report("0xC0000017"), &[]),
+            Some(CrashCategory::OutOfMemory)
+        );
+        assert_eq!(
+            classify(&report("0x887A0006"), &[]),
+            Some(CrashCategory::Gpu)
+        );
+
+        let mut report = report("0xC0000006");
+        report.in_page_error = Some(in_page::describe(0x28, 0xC000009C, None));
+        report.hooked_functions = vec![HookedFunction {
+            function: "D3D11CreateDevice".into(),
+            target: "0x1F2A0000".into(),
+            owner: None,
+        }];
+        assert_eq!(classify(&report, &[0, 0x28]), Some(CrashCategory::DiskIo));
+    }
+
+    #[test]
+    fn hints_name_the_culprit() {
+        let mut report = report("0xC0000005");
+        report.hooked_functions = ["ReShade64.dll", "EngineFixes.dll", "ReShade64.dll"]
+            .into_iter()
+            .map(|owner| HookedFunction {
+                function: "Present".into(),
+                target: format!("{}+0x1000", owner),
+                owner: Some(owner.into()),
+            })
+            .collect();
+        assert_eq!(classify(&report, &[]), Some(CrashCategory::HookConflict));
+        assert!(
+            hint(&report, CrashCategory::HookConflict, &[])
+                .contains("hooked by EngineFixes.dll, ReShade64.dll.")
+        );
+
+        report.papyrus_stacks = vec![PapyrusFrame {
+            stack_id: 7,
+            depth: 0,
+            script: "MyModQuestScript".into(),
+            function: "OnUpdate".into(),
+            line: Some(42),
+        }];
+        assert!(
+            hint(&report, CrashCategory::Script, &[])
+                .starts_with("The Papyrus script MyModQuestScript.OnUpdate was running")
+        );
+    }
+
+    #[test]
+    fn only_crashes_are_triaged() {
+        let mut crash = report("0xC00000FD");
+        crash.crash_category = None;
+        apply(&mut crash, &[]);
+        assert_eq!(crash.crash_category, Some(CrashCategory::StackOverflow));
+        assert!(crash.triage_hint.is_some());
+
+        let mut hang = report("0xC00000FD");
+        hang.report_type = ReportType::Hang;
+        hang.crash_category = None;
+        hang.triage_hint = None;
+        apply(&mut hang, &[]);
+        assert_eq!(hang.crash_category, None);
+        assert_eq!(hang.triage_hint, None);
+    }
+
+    #[test]
+    fn long_hints_are_truncated() {
+        let mut report = report("0xC0000006");
+        report.in_page_error = Some(in_page::describe(
+            0x28,
+            0xC000009C,
+            Some(format!(r"D:\{}é.bsa", "x".repeat(MAX_TRIAGE_HINT_LEN))),
+        ));
+        let text = hint(&report, CrashCategory::DiskIo, &[]);
+        assert!(text.len() <= MAX_TRIAGE_HINT_LEN);
+        assert!(text.starts_with(r"Windows couldn't read D:\xxx"));
+    }
+}
@@ -0,0 +1,122 @@
+//! How long the game ran before a crash, and how long since it saved.
+//!
+//! A crash three hours into a session (a leak running out of memory or
+//! handles) and one seconds after launch (a missing master, a bad DLL) are
+//! different bugs, and a crash right after a save points at the save code.
+//! The plugins call [`start`] when they load, which takes the process's
+//! creation time from Windows so time spent before the plugin loaded counts,
+//! and [`record_save`] from the script extender's save message. Reports get
+//! `uptimeSecs` and `secsSinceLastSave` through
+//! [`CrashReportBuilder::uptime`](crate::crash_report::CrashReportBuilder::uptime).
+//!
+//! Both times are atomics, so [`record_save`] is safe from any thread.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::crash_guard::now_millis;
+
+/// Milliseconds from the `FILETIME` epoch (1601) to the Unix epoch.
+const FILETIME_UNIX_OFFSET_MS: u64 = 11_644_473_600_000;
+
+/// When the process started, as Unix milliseconds; 0 until [`start`].
+static STARTED_AT: AtomicU64 = AtomicU64::new(0);
+
+/// When the game last saved, as Unix milliseconds; 0 if it hasn't.
+static LAST_SAVE_AT: AtomicU64 = AtomicU64::new(0);
+
+/// Records when the process started. Later calls do nothing.
+///
+/// Falls back to the current time if the creation time can't be read.
+pub fn start() {
+    let started_at = process_started_at().unwrap_or_else(now_millis);
+    let _ = STARTED_AT.compare_exchange(0, started_at, Ordering::Relaxed, Ordering::Relaxed);
+}
+
+/// Records that the game just saved.
+pub fn record_save() {
+    LAST_SAVE_AT.store(now_millis(), Ordering::Relaxed);
+}
+
+/// Seconds since the process started, or `None` before [`start`].
+pub fn uptime_secs() -> Option<u64> {
+    elapsed_secs(STARTED_AT.load(Ordering::Relaxed), now_millis())
+}
+
+/// Seconds since the game last saved, or `None` if it hasn't this session.
+pub fn secs_since_last_save() -> Option<u64> {
+    elapsed_secs(LAST_SAVE_AT.load(Ordering::Relaxed), now_millis())
+}
+
+/// Whole seconds from `since` to `now`, both Unix milliseconds; `None` if
+/// `since` was never set.
+fn elapsed_secs(since: u64, now: u64) -> Option<u64> {
+    (since != 0).then(|| now.saturating_sub(since) / 1000)
+}
+
+/// Converts a `FILETIME` (100 ns ticks since 1601) to Unix milliseconds.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn filetime_to_unix_ms(ticks: u64) -> Option<u64> {
+    (ticks / 10_000).checked_sub(FILETIME_UNIX_OFFSET_MS)
+}
+
+#[cfg(windows)]
+fn process_started_at() -> Option<u64> {
+    use windows::Win32::Foundation::FILETIME;
+    use windows::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    let mut created = FILETIME::default();
+    let mut exited = FILETIME::default();
+    let mut kernel = FILETIME::default();
+    let mut user = FILETIME::default();
+    // SAFETY: all four out-pointers are valid FILETIMEs on this stack; the
+    // pseudo-handle for the current process needs no closing
+    unsafe {
+        GetProcessTimes(
+            GetCurrentProcess(),
+            &mut created,
+            &mut exited,
+            &mut kernel,
+            &mut user,
+        )
+    }
+    .ok()?;
+    filetime_to_unix_ms(
+        (u64::from(created.dwHighDateTime) << 32) | u64::from(created.dwLowDateTime),
+    )
+}
+
+#[cfg(not(windows))]
+fn process_started_at() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filetimes_convert_to_unix_time() {
+        // 2024-01-01T00:00:00Z
+        assert_eq!(
+            filetime_to_unix_ms(133_485_408_000_000_000),
+            Some(1_704_067_200_000)
+        );
+        assert_eq!(filetime_to_unix_ms(0), None);
+    }
+
+    #[test]
+    fn unset_times_are_omitted() {
+        assert_eq!(elapsed_secs(0, 5000), None);
+        assert_eq!(elapsed_secs(1000, 5999), Some(4));
+        assert_eq!(elapsed_secs(5000, 1000), Some(0));
+    }
+
+    #[test]
+    fn saves_and_start_are_recorded() {
+        start();
+        record_save();
+
+        assert!(uptime_secs().is_some());
+        assert!(secs_since_last_save().is_some_and(|secs| secs < 60));
+    }
+}
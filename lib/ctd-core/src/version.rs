@@ -1,7 +1,8 @@
 //! Windows PE version extraction utilities.
 //!
 //! This module provides functionality to extract version information from
-//! Windows PE files (DLLs and EXEs) using the Windows API.
+//! Windows PE files (DLLs and EXEs) using the Windows API, and the version
+//! of Windows itself.
 
 use std::path::Path;
 use thiserror::Error;
@@ -104,6 +105,97 @@ pub fn get_dll_version(path: &Path) -> Result<String, VersionError> {
     }
 }
 
+/// Extract the product name (e.g., "Skyrim VR") from a Windows PE file.
+///
+/// Reads `ProductName` from the first language listed in the file's
+/// `VarFileInfo\Translation` table.
+#[cfg(windows)]
+pub fn get_product_name(path: &Path) -> Result<String, VersionError> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use windows::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW,
+    };
+    use windows::core::PCWSTR;
+
+    let wide = |s: &OsStr| -> Vec<u16> { s.encode_wide().chain(std::iter::once(0)).collect() };
+    let wide_path = wide(path.as_os_str());
+
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(wide_path.as_ptr()), None);
+        if size == 0 {
+            return Err(VersionError::NoVersionInfo);
+        }
+
+        let mut buffer = vec![0u8; size as usize];
+        GetFileVersionInfoW(
+            PCWSTR(wide_path.as_ptr()),
+            0,
+            size,
+            buffer.as_mut_ptr().cast(),
+        )
+        .map_err(|_| VersionError::ParseError)?;
+
+        // Each translation is a (language, code page) pair of u16s
+        let mut translation: *mut u16 = std::ptr::null_mut();
+        let mut len: u32 = 0;
+        let success = VerQueryValueW(
+            buffer.as_ptr().cast(),
+            PCWSTR(wide(OsStr::new("\\VarFileInfo\\Translation")).as_ptr()),
+            std::ptr::addr_of_mut!(translation).cast(),
+            &mut len,
+        );
+        if !success.as_bool() || translation.is_null() || len < 4 {
+            return Err(VersionError::NoVersionInfo);
+        }
+        let lang = translation.read_unaligned();
+        let codepage = translation.add(1).read_unaligned();
+
+        let sub_block = format!(
+            "\\StringFileInfo\\{:04x}{:04x}\\ProductName",
+            lang, codepage
+        );
+        let mut name: *mut u16 = std::ptr::null_mut();
+        let success = VerQueryValueW(
+            buffer.as_ptr().cast(),
+            PCWSTR(wide(OsStr::new(&sub_block)).as_ptr()),
+            std::ptr::addr_of_mut!(name).cast(),
+            &mut len,
+        );
+        if !success.as_bool() || name.is_null() {
+            return Err(VersionError::NoVersionInfo);
+        }
+
+        // String lengths are in characters and include the terminator
+        let chars = std::slice::from_raw_parts(name, len as usize);
+        let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+        Ok(String::from_utf16_lossy(&chars[..end]))
+    }
+}
+
+/// Returns the running Windows version (e.g., "Windows 10.0.22631").
+///
+/// Reads it with `RtlGetVersion`, which unlike `GetVersionExW` reports the
+/// real version whatever the game's manifest declares support for.
+#[cfg(windows)]
+pub fn os_version() -> Option<String> {
+    use windows::Wdk::System::SystemServices::RtlGetVersion;
+    use windows::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+    let mut info = OSVERSIONINFOW {
+        dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32,
+        ..Default::default()
+    };
+
+    // SAFETY: `info` is a properly sized OSVERSIONINFOW.
+    unsafe { RtlGetVersion(&mut info) }.ok().ok()?;
+
+    Some(format!(
+        "Windows {}.{}.{}",
+        info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber
+    ))
+}
+
 /// Stub implementation for non-Windows platforms.
 ///
 /// Always returns `VersionError::NoVersionInfo` since PE version extraction
@@ -113,6 +205,21 @@ pub fn get_dll_version(_path: &Path) -> Result<String, VersionError> {
     Err(VersionError::NoVersionInfo)
 }
 
+/// Stub implementation for non-Windows platforms.
+///
+/// Always returns `VersionError::NoVersionInfo` since version resources
+/// can only be read on Windows.
+#[cfg(not(windows))]
+pub fn get_product_name(_path: &Path) -> Result<String, VersionError> {
+    Err(VersionError::NoVersionInfo)
+}
+
+/// Non-Windows stub: there is no Windows version to report.
+#[cfg(not(windows))]
+pub fn os_version() -> Option<String> {
+    None
+}
+
 #[cfg(test)]
 #[cfg(windows)]
 mod tests {
@@ -132,6 +239,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_extract_product_name_from_system_dll() {
+        let path = Path::new("C:\\Windows\\System32\\kernel32.dll");
+        let name = get_product_name(path).unwrap();
+        assert!(name.contains("Windows"));
+    }
+
+    #[test]
+    fn test_os_version() {
+        let version = os_version().unwrap();
+        assert!(version.starts_with("Windows 10.0."));
+    }
+
     #[test]
     fn test_nonexistent_file_error() {
         let result = get_dll_version(Path::new("C:\\nonexistent.dll"));
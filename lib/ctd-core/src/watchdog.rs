@@ -0,0 +1,457 @@
+//! Freeze detection for the game's main thread.
+//!
+//! An infinite loop or a deadlock leaves the game frozen without raising an
+//! exception, so the crash handlers never see it. The game plugins call
+//! [`beat`] once per frame from the game thread; a monitor thread started
+//! with [`start`] reports a hang when the beats stop for
+//! `[watchdog] timeout_secs`, once per freeze.
+//!
+//! A hang report carries the stack of every thread in the process, the
+//! game thread first. Each thread is suspended only while its registers are
+//! read and its return addresses walked, which allocates nothing: the
+//! suspended thread may hold the heap lock.
+
+use std::io;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::config::WatchdogConfig;
+#[cfg(windows)]
+use crate::crash_arena::{FixedBuf, MAX_FRAMES, MODULE_NAME_CAPACITY, resolve_module};
+
+/// How often the monitor thread checks for beats.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest stack trace a report may carry.
+#[cfg(windows)]
+const MAX_STACK_TRACE_LEN: usize = 100_000;
+
+/// Beats since the process started.
+static BEATS: AtomicU64 = AtomicU64::new(0);
+
+/// ID of the thread that beat last, or 0 before the first beat.
+static GAME_THREAD: AtomicU32 = AtomicU32::new(0);
+
+/// Hangs detected since the process started.
+static HANGS: AtomicU32 = AtomicU32::new(0);
+
+/// Set once the monitor thread has been started.
+static STARTED: OnceLock<()> = OnceLock::new();
+
+/// A freeze the watchdog detected.
+#[derive(Debug, Clone)]
+pub struct Hang {
+    /// How long the game thread had gone without a beat.
+    pub stalled_for: Duration,
+    /// Every thread's stack, the game thread first.
+    pub stack_trace: String,
+}
+
+/// Records that the game thread is alive.
+///
+/// Call once per frame from the game thread. Cheap enough for every frame.
+pub fn beat() {
+    BEATS.fetch_add(1, Ordering::Relaxed);
+    GAME_THREAD.store(current_thread_id(), Ordering::Relaxed);
+}
+
+/// Returns how many hangs the watchdog has detected in this process.
+pub fn hang_count() -> u32 {
+    HANGS.load(Ordering::Relaxed)
+}
+
+/// Starts the monitor thread, which calls `on_hang` on itself once per
+/// freeze.
+///
+/// Does nothing if `[watchdog]` is disabled or the monitor already runs.
+/// Hangs are only timed after the first [`beat`], so loading before the
+/// game loop starts is never reported.
+///
+/// # Errors
+///
+/// Returns an error if the thread cannot be spawned.
+pub fn start(config: &WatchdogConfig, on_hang: impl Fn(Hang) + Send + 'static) -> io::Result<()> {
+    if !config.enabled || STARTED.set(()).is_err() {
+        return Ok(());
+    }
+
+    let timeout = Duration::from_secs(config.timeout_secs);
+    std::thread::Builder::new()
+        .name("ctd-watchdog".to_string())
+        .spawn(move || monitor(timeout, on_hang))?;
+    Ok(())
+}
+
+/// Watches the beat count for the life of the process.
+fn monitor(timeout: Duration, on_hang: impl Fn(Hang)) {
+    let mut stall = StallDetector::new(timeout, Instant::now());
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let Some(stalled_for) = stall.poll(BEATS.load(Ordering::Relaxed), Instant::now()) else {
+            continue;
+        };
+
+        if debugger_attached() {
+            info!("Game thread paused under a debugger; not reporting a hang");
+            continue;
+        }
+
+        warn!(
+            "Game thread has not responded for {} seconds; reporting a hang",
+            stalled_for.as_secs()
+        );
+        HANGS.fetch_add(1, Ordering::Relaxed);
+        on_hang(Hang {
+            stalled_for,
+            stack_trace: capture_all_threads(),
+        });
+    }
+}
+
+/// Decides from successive beat counts when the game thread has stalled.
+#[derive(Debug)]
+struct StallDetector {
+    timeout: Duration,
+    beats: u64,
+    last_change: Instant,
+    reported: bool,
+}
+
+impl StallDetector {
+    fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            timeout,
+            beats: 0,
+            last_change: now,
+            reported: false,
+        }
+    }
+
+    /// Returns how long the game thread has stalled, the first time the
+    /// stall reaches the timeout. Beats resuming re-arms the detector.
+    fn poll(&mut self, beats: u64, now: Instant) -> Option<Duration> {
+        if beats != self.beats {
+            self.beats = beats;
+            self.last_change = now;
+            self.reported = false;
+            return None;
+        }
+
+        let stalled_for = now.duration_since(self.last_change);
+        if beats == 0 || self.reported || stalled_for < self.timeout {
+            return None;
+        }
+        self.reported = true;
+        Some(stalled_for)
+    }
+}
+
+/// Captures the stack of every thread in the process but the caller's, the
+/// game thread first.
+///
+/// Threads are listed until the trace would exceed what a report can carry.
+#[cfg(windows)]
+pub fn capture_all_threads() -> String {
+    use std::fmt::Write;
+
+    let game_thread = GAME_THREAD.load(Ordering::Relaxed);
+    let mut threads = thread_ids();
+    threads.sort_by_key(|&id| id != game_thread);
+
+    let mut trace = String::new();
+    let mut block = String::new();
+    let mut module = FixedBuf::with_capacity(MODULE_NAME_CAPACITY);
+    let mut frames = [0u64; MAX_FRAMES];
+
+    for id in threads {
+        let Some(count) = walk_thread(id, &mut frames) else {
+            continue;
+        };
+
+        block.clear();
+        let label = if id == game_thread {
+            " (game thread)"
+        } else {
+            ""
+        };
+        let _ = writeln!(block, "Thread {}{}:", id, label);
+        for (i, &address) in frames[..count].iter().enumerate() {
+            module.clear();
+            let base = resolve_module(address, &mut module);
+            let _ = writeln!(
+                block,
+                "[{}] {}+0x{:X} (0x{:016X})",
+                i,
+                module.as_str(),
+                address.wrapping_sub(base),
+                address
+            );
+        }
+        block.push('\n');
+
+        if trace.len() + block.len() > MAX_STACK_TRACE_LEN {
+            break;
+        }
+        trace.push_str(&block);
+    }
+    trace
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+pub fn capture_all_threads() -> String {
+    "Thread stacks are only captured on Windows\n".to_string()
+}
+
+/// Lists the process's threads, except the calling one.
+#[cfg(windows)]
+fn thread_ids() -> Vec<u32> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, TH32CS_SNAPTHREAD, THREADENTRY32, Thread32First, Thread32Next,
+    };
+    use windows::Win32::System::Threading::GetCurrentProcessId;
+
+    let mut ids = Vec::new();
+    // SAFETY: Snapshots are plain kernel objects, closed below
+    let Ok(snapshot) = (unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0) }) else {
+        return ids;
+    };
+
+    // SAFETY: No preconditions
+    let process = unsafe { GetCurrentProcessId() };
+    let current = current_thread_id();
+    let mut entry = THREADENTRY32 {
+        dwSize: std::mem::size_of::<THREADENTRY32>() as u32,
+        ..Default::default()
+    };
+
+    // SAFETY: entry is a THREADENTRY32 with dwSize set
+    let mut more = unsafe { Thread32First(snapshot, &mut entry) }.is_ok();
+    while more {
+        if entry.th32OwnerProcessID == process && entry.th32ThreadID != current {
+            ids.push(entry.th32ThreadID);
+        }
+        more = unsafe { Thread32Next(snapshot, &mut entry) }.is_ok();
+    }
+
+    // SAFETY: snapshot came from CreateToolhelp32Snapshot
+    let _ = unsafe { CloseHandle(snapshot) };
+    ids
+}
+
+/// Suspends a thread, walks its return addresses into `frames`, and
+/// resumes it. Returns the number of frames, or `None` if the thread could
+/// not be read.
+#[cfg(windows)]
+fn walk_thread(thread_id: u32, frames: &mut [u64; MAX_FRAMES]) -> Option<usize> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::Debug::{CONTEXT, GetThreadContext};
+    use windows::Win32::System::Threading::{
+        OpenThread, ResumeThread, SuspendThread, THREAD_GET_CONTEXT, THREAD_SUSPEND_RESUME,
+    };
+
+    // SAFETY: Opening a thread of our own process by ID
+    let thread =
+        unsafe { OpenThread(THREAD_SUSPEND_RESUME | THREAD_GET_CONTEXT, false, thread_id) }.ok()?;
+
+    // Nothing between suspending and resuming may allocate
+    let mut count = None;
+    // SAFETY: The thread is resumed below whatever happens
+    if unsafe { SuspendThread(thread) } != u32::MAX {
+        let mut context = CONTEXT {
+            ContextFlags: CONTEXT_FULL,
+            ..Default::default()
+        };
+        // SAFETY: The thread is suspended, so its context is stable
+        if unsafe { GetThreadContext(thread, &mut context) }.is_ok() {
+            count = Some(walk_frames(&mut context, frames));
+        }
+        unsafe { ResumeThread(thread) };
+    }
+
+    // SAFETY: thread came from OpenThread
+    let _ = unsafe { CloseHandle(thread) };
+    count
+}
+
+#[cfg(all(windows, target_arch = "x86_64"))]
+const CONTEXT_FULL: windows::Win32::System::Diagnostics::Debug::CONTEXT_FLAGS =
+    windows::Win32::System::Diagnostics::Debug::CONTEXT_FULL_AMD64;
+
+#[cfg(all(windows, target_arch = "x86"))]
+const CONTEXT_FULL: windows::Win32::System::Diagnostics::Debug::CONTEXT_FLAGS =
+    windows::Win32::System::Diagnostics::Debug::CONTEXT_FULL_X86;
+
+/// Walks a suspended thread's return addresses with the unwind tables.
+#[cfg(all(windows, target_arch = "x86_64"))]
+fn walk_frames(
+    context: &mut windows::Win32::System::Diagnostics::Debug::CONTEXT,
+    frames: &mut [u64; MAX_FRAMES],
+) -> usize {
+    use windows::Win32::System::Diagnostics::Debug::{
+        RtlLookupFunctionEntry, RtlVirtualUnwind, UNW_FLAG_NHANDLER,
+    };
+
+    let mut count = 0;
+    while count < MAX_FRAMES && context.Rip != 0 {
+        frames[count] = context.Rip;
+        count += 1;
+
+        let mut image_base = 0;
+        // SAFETY: Looks up the unwind data for an address; no memory is written
+        let function = unsafe { RtlLookupFunctionEntry(context.Rip, &mut image_base, None) };
+        if function.is_null() {
+            // Leaf function: the return address is on top of the stack
+            if !is_readable(context.Rsp, 8) {
+                break;
+            }
+            // SAFETY: Checked readable above
+            context.Rip = unsafe { *(context.Rsp as *const u64) };
+            context.Rsp += 8;
+            continue;
+        }
+
+        let mut handler_data = std::ptr::null_mut();
+        let mut establisher_frame = 0;
+        // SAFETY: function came from RtlLookupFunctionEntry for this Rip, and
+        // the thread owning the stack is suspended
+        unsafe {
+            RtlVirtualUnwind(
+                UNW_FLAG_NHANDLER,
+                image_base,
+                context.Rip,
+                function,
+                context,
+                &mut handler_data,
+                &mut establisher_frame,
+                None,
+            );
+        }
+    }
+    count
+}
+
+/// Walks a suspended thread's return addresses along the EBP chain.
+#[cfg(all(windows, target_arch = "x86"))]
+fn walk_frames(
+    context: &mut windows::Win32::System::Diagnostics::Debug::CONTEXT,
+    frames: &mut [u64; MAX_FRAMES],
+) -> usize {
+    frames[0] = u64::from(context.Eip);
+    let mut count = 1;
+
+    let mut ebp = context.Ebp;
+    while count < MAX_FRAMES && ebp != 0 && is_readable(u64::from(ebp), 8) {
+        let frame = ebp as usize as *const u32;
+        // SAFETY: Both words were checked readable above
+        let (next, ret) = unsafe { (*frame, *frame.add(1)) };
+        if ret == 0 {
+            break;
+        }
+        frames[count] = u64::from(ret);
+        count += 1;
+
+        // Frames move up the stack; anything else is a broken chain
+        if next <= ebp {
+            break;
+        }
+        ebp = next;
+    }
+    count
+}
+
+/// Returns true if `len` bytes at `address` are committed and readable.
+#[cfg(windows)]
+fn is_readable(address: u64, len: u64) -> bool {
+    use windows::Win32::System::Memory::{
+        MEM_COMMIT, MEMORY_BASIC_INFORMATION, PAGE_GUARD, PAGE_NOACCESS, VirtualQuery,
+    };
+
+    let mut info = MEMORY_BASIC_INFORMATION::default();
+    // SAFETY: VirtualQuery only describes the region; info is sized correctly
+    let written = unsafe {
+        VirtualQuery(
+            Some(address as usize as *const std::ffi::c_void),
+            &mut info,
+            std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+    if written == 0 {
+        return false;
+    }
+
+    let end = info.BaseAddress as u64 + info.RegionSize as u64;
+    info.State == MEM_COMMIT
+        && info.Protect.0 & (PAGE_NOACCESS.0 | PAGE_GUARD.0) == 0
+        && address.saturating_add(len) <= end
+}
+
+/// Returns the calling thread's ID.
+#[cfg(windows)]
+fn current_thread_id() -> u32 {
+    // SAFETY: No preconditions
+    unsafe { windows::Win32::System::Threading::GetCurrentThreadId() }
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+fn current_thread_id() -> u32 {
+    0
+}
+
+/// Returns true if a debugger is attached, which pauses the game thread
+/// at every breakpoint.
+#[cfg(windows)]
+fn debugger_attached() -> bool {
+    // SAFETY: No preconditions
+    unsafe { windows::Win32::System::Diagnostics::Debug::IsDebuggerPresent() }.as_bool()
+}
+
+/// Stub implementation for non-Windows platforms.
+#[cfg(not(windows))]
+fn debugger_attached() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_the_first_beat() {
+        let start = Instant::now();
+        let mut stall = StallDetector::new(Duration::from_secs(10), start);
+
+        assert_eq!(stall.poll(0, start + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn reports_once_per_freeze() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut stall = StallDetector::new(Duration::from_secs(10), start);
+
+        assert_eq!(stall.poll(1, at(1)), None);
+        assert_eq!(stall.poll(1, at(5)), None);
+        assert_eq!(stall.poll(1, at(11)), Some(Duration::from_secs(10)));
+        assert_eq!(stall.poll(1, at(30)), None);
+
+        // Beats resume, then stop again
+        assert_eq!(stall.poll(2, at(31)), None);
+        assert_eq!(stall.poll(2, at(41)), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn disabled_watchdog_does_not_start() {
+        let config = WatchdogConfig {
+            enabled: false,
+            ..WatchdogConfig::default()
+        };
+        start(&config, |_| {}).unwrap();
+        assert!(STARTED.get().is_none());
+    }
+}
@@ -0,0 +1,577 @@
+//! Windows Error Reporting `LocalDumps` and minidump ingestion.
+//!
+//! With a `LocalDumps\<exe>` key under
+//! `HKLM\SOFTWARE\Microsoft\Windows\Windows Error Reporting`, WER writes a
+//! minidump named `<exe>.<pid>.dmp` to the key's `DumpFolder` whenever the
+//! executable dies of an unhandled exception. That covers crashes no plugin
+//! was loaded to catch: games without a plugin host, and crashes before a
+//! plugin's handler is registered.
+//!
+//! [`enable_local_dumps`] writes the key for one executable on request; it
+//! needs administrator rights and is harmless to leave behind. [`DumpCrash`]
+//! reads a dump with the `minidump` crates, walking the crashing thread's
+//! stack with the unwind info in the dump, and turns it into a report.
+//! `ctd dumps submit` ingests the configured dump folder and `ctd-watch`
+//! reads the dump of the process it watched.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use minidump::{Minidump, MinidumpException, MinidumpModule, MinidumpModuleList, Module};
+use minidump_unwind::{Symbolizer, simple_symbol_supplier};
+use thiserror::Error;
+
+use crate::blame;
+use crate::crash_report::{CrashReportBuilder, CreateCrashReport};
+use crate::load_order::ModList;
+
+/// Errors that can occur when enabling or reading WER dumps.
+#[derive(Error, Debug)]
+pub enum WerError {
+    /// Failed to read or move a dump, or to write the registry key.
+    #[error("I/O error: {0}")]
+    IoError(#[from] io::Error),
+
+    /// The file is not a readable minidump.
+    #[error("Invalid minidump: {0}")]
+    Minidump(String),
+
+    /// WER is not available on this platform.
+    #[error("Windows Error Reporting is only available on Windows")]
+    Unsupported,
+}
+
+/// Registry path of the `LocalDumps` keys, under `HKEY_LOCAL_MACHINE`.
+pub const LOCAL_DUMPS_KEY: &str = r"SOFTWARE\Microsoft\Windows\Windows Error Reporting\LocalDumps";
+
+/// `DumpType` 1: a minidump, which holds the exception, the module list, and
+/// the crashing thread's stack memory.
+#[cfg_attr(not(windows), allow(dead_code))]
+const DUMP_TYPE_MINI: u32 = 1;
+
+/// Dumps WER keeps per executable before deleting the oldest.
+#[cfg_attr(not(windows), allow(dead_code))]
+const DUMP_COUNT: u32 = 10;
+
+/// Longest [`wait_for_dump`] waits for WER to finish writing.
+const DUMP_WAIT: Duration = Duration::from_secs(10);
+
+/// Subfolder of the dump folder that submitted dumps are moved to.
+const SUBMITTED_DIR: &str = "submitted";
+
+/// Most frames put in a stack trace, matching in-process capture.
+const MAX_FRAMES: usize = 64;
+
+/// Returns the file name WER gives the dump of process `pid` of `exe_name`.
+pub fn dump_file_name(exe_name: &str, pid: u32) -> String {
+    format!("{}.{}.dmp", exe_name, pid)
+}
+
+/// Returns the executable a WER dump was written for, from its file name
+/// (`Game.exe.4242.dmp` is `Game.exe`).
+pub fn dump_executable(path: &Path) -> Option<&str> {
+    let stem = path.file_stem()?.to_str()?;
+    let (exe, pid) = stem.rsplit_once('.')?;
+    (!exe.is_empty() && pid.bytes().all(|b| b.is_ascii_digit())).then_some(exe)
+}
+
+/// Returns a game ID for an executable: its lowercased stem, with each run
+/// of other characters as one `-` (`Some Game.exe` is `some-game`).
+pub fn game_id_from_exe(exe: &Path) -> String {
+    let stem = exe
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let mut id = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    if id.ends_with('-') {
+        id.pop();
+    }
+    id
+}
+
+/// Waits for WER to write the dump of process `pid`, up to 10 seconds.
+///
+/// Returns `None` if no dump appears, e.g. because `LocalDumps` is not set
+/// or the game handled the exception itself.
+pub fn wait_for_dump(dump_dir: &Path, exe_name: &str, pid: u32) -> Option<PathBuf> {
+    let path = dump_dir.join(dump_file_name(exe_name, pid));
+    let deadline = Instant::now() + DUMP_WAIT;
+    while !path.is_file() {
+        if Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    Some(path)
+}
+
+/// Lists the `.dmp` files waiting in `dump_dir`, oldest name first.
+///
+/// Dumps already moved to `submitted/` by [`mark_submitted`] are skipped.
+/// A missing folder has no dumps.
+pub fn find_dumps(dump_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dump_dir) else {
+        return Vec::new();
+    };
+    let mut dumps: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("dmp"))
+        })
+        .collect();
+    dumps.sort();
+    dumps
+}
+
+/// Moves a submitted dump into the `submitted/` subfolder, so the next
+/// ingestion skips it. WER's `DumpCount` does not prune that folder.
+///
+/// Returns the new path.
+pub fn mark_submitted(dump: &Path) -> Result<PathBuf, WerError> {
+    let dir = dump
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(SUBMITTED_DIR);
+    fs::create_dir_all(&dir)?;
+    let target = dir.join(dump.file_name().unwrap_or_default());
+    fs::rename(dump, &target)?;
+    Ok(target)
+}
+
+/// Registers `exe_name` (e.g. `Game.exe`) for minidumps in `dump_dir`.
+///
+/// # Errors
+///
+/// Fails if the folder cannot be created or the key cannot be written,
+/// usually because the process is not running as administrator.
+#[cfg(windows)]
+pub fn enable_local_dumps(exe_name: &str, dump_dir: &Path) -> Result<(), WerError> {
+    use windows::Win32::System::Registry::{
+        HKEY, HKEY_LOCAL_MACHINE, KEY_SET_VALUE, REG_DWORD, REG_EXPAND_SZ, REG_OPTION_NON_VOLATILE,
+        RegCloseKey, RegCreateKeyExW, RegSetValueExW,
+    };
+    use windows::core::{HSTRING, PCWSTR};
+
+    fs::create_dir_all(dump_dir)?;
+
+    let subkey = HSTRING::from(format!("{}\\{}", LOCAL_DUMPS_KEY, exe_name));
+    let mut key = HKEY::default();
+    // SAFETY: The subkey is a valid wide string; the key is closed below
+    unsafe {
+        RegCreateKeyExW(
+            HKEY_LOCAL_MACHINE,
+            &subkey,
+            0,
+            PCWSTR::null(),
+            REG_OPTION_NON_VOLATILE,
+            KEY_SET_VALUE,
+            None,
+            &mut key,
+            None,
+        )
+    }
+    .ok()
+    .map_err(io::Error::other)?;
+
+    // REG_EXPAND_SZ data is the null-terminated UTF-16 string
+    let folder: Vec<u8> = dump_dir
+        .to_string_lossy()
+        .encode_utf16()
+        .chain([0])
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let values = [
+        ("DumpFolder", REG_EXPAND_SZ, folder),
+        ("DumpType", REG_DWORD, DUMP_TYPE_MINI.to_le_bytes().to_vec()),
+        ("DumpCount", REG_DWORD, DUMP_COUNT.to_le_bytes().to_vec()),
+    ];
+    let result = values.iter().try_for_each(|(name, kind, data)| {
+        // SAFETY: The key is open for writing; the data outlives the call
+        unsafe { RegSetValueExW(key, &HSTRING::from(*name), 0, *kind, Some(data.as_slice())) }
+            .ok()
+            .map_err(io::Error::other)
+    });
+    // SAFETY: The key was opened above
+    let _ = unsafe { RegCloseKey(key) };
+    Ok(result?)
+}
+
+/// Non-Windows stub: WER only exists on Windows.
+#[cfg(not(windows))]
+pub fn enable_local_dumps(_exe_name: &str, _dump_dir: &Path) -> Result<(), WerError> {
+    Err(WerError::Unsupported)
+}
+
+/// The exception that caused the dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpException {
+    /// Thread that raised the exception.
+    pub thread_id: u32,
+    /// Exception code, e.g. 0xC0000005.
+    pub code: u32,
+    /// Address the exception was raised at.
+    pub address: u64,
+}
+
+/// A module loaded in the dumped process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpModule {
+    /// Load address.
+    pub base: u64,
+    /// Size of the image in memory.
+    pub size: u64,
+    /// Full path of the module.
+    pub path: String,
+}
+
+impl DumpModule {
+    /// Returns the module's file name, e.g. `game.exe`.
+    pub fn name(&self) -> &str {
+        self.path.rsplit(['\\', '/']).next().unwrap_or(&self.path)
+    }
+
+    /// Returns true if `address` falls inside the module's image.
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.base && address - self.base < self.size
+    }
+}
+
+impl From<&MinidumpModule> for DumpModule {
+    fn from(module: &MinidumpModule) -> Self {
+        Self {
+            base: module.base_address(),
+            size: module.size(),
+            path: module.code_file().into_owned(),
+        }
+    }
+}
+
+/// One frame of the crashing thread's stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpFrame {
+    /// Instruction address.
+    pub address: u64,
+    /// Module containing the address.
+    pub module: Option<DumpModule>,
+    /// Function name with offset, when symbols were available.
+    pub function: Option<String>,
+}
+
+/// A crash read from a minidump.
+#[derive(Debug, Clone, Default)]
+pub struct DumpCrash {
+    /// The exception, if the dump was written for one.
+    pub exception: Option<DumpException>,
+    /// Modules loaded at the time of the dump.
+    pub modules: Vec<DumpModule>,
+    /// The crashing thread's stack, innermost frame first.
+    pub frames: Vec<DumpFrame>,
+    /// File version of the main executable.
+    pub game_version: Option<String>,
+    /// When the dump was written, in milliseconds since the Unix epoch.
+    pub crashed_at: Option<u64>,
+}
+
+impl DumpCrash {
+    /// Reads a minidump and walks the crashing thread's stack.
+    ///
+    /// The walk uses the unwind info and stack memory in the dump; no symbol
+    /// files are fetched. A dump whose stack cannot be walked still yields
+    /// the exception and modules, and [`DumpCrash::stack_trace`] falls back
+    /// to the faulting frame.
+    pub async fn read(path: &Path) -> Result<Self, WerError> {
+        let dump = Minidump::read_path(path)
+            .map_err(|e| WerError::Minidump(format!("{:?}: {}", path, e)))?;
+
+        let exception = dump
+            .get_stream::<MinidumpException>()
+            .ok()
+            .map(|exception| DumpException {
+                thread_id: exception.raw.thread_id,
+                code: exception.raw.exception_record.exception_code,
+                address: exception.raw.exception_record.exception_address,
+            });
+        let module_list = dump
+            .get_stream::<MinidumpModuleList>()
+            .map_err(|e| WerError::Minidump(format!("{:?}: {}", path, e)))?;
+        let game_version = module_list
+            .main_module()
+            .and_then(|module| module.version())
+            .map(|version| version.into_owned());
+
+        let provider = Symbolizer::new(simple_symbol_supplier(Vec::new()));
+        let frames = match minidump_processor::process_minidump(&dump, &provider).await {
+            Ok(state) => state
+                .requesting_thread
+                .and_then(|index| state.threads.get(index))
+                .map(|thread| {
+                    thread
+                        .frames
+                        .iter()
+                        .take(MAX_FRAMES)
+                        .map(|frame| DumpFrame {
+                            address: frame.instruction,
+                            module: frame.module.as_ref().map(DumpModule::from),
+                            function: frame.function_name.as_ref().map(|name| {
+                                let base = frame.function_base.unwrap_or(frame.instruction);
+                                format!("{}+0x{:X}", name, frame.instruction - base)
+                            }),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Could not walk the stack of {:?}: {}", path, e);
+                Vec::new()
+            }
+        };
+
+        Ok(Self {
+            exception,
+            modules: module_list.iter().map(DumpModule::from).collect(),
+            frames,
+            game_version,
+            crashed_at: (dump.header.time_date_stamp > 0)
+                .then(|| u64::from(dump.header.time_date_stamp) * 1000),
+        })
+    }
+
+    /// Returns the module containing `address`.
+    pub fn module_at(&self, address: u64) -> Option<&DumpModule> {
+        self.modules.iter().find(|module| module.contains(address))
+    }
+
+    /// Returns the module the exception was raised in.
+    pub fn faulting_module(&self) -> Option<&DumpModule> {
+        self.module_at(self.exception?.address)
+    }
+
+    /// Formats the stack in CTD's `[i] module+0xOFFSET (0xADDR)` format,
+    /// followed by the function when it is known.
+    ///
+    /// Without a walked stack this is the single faulting frame, or `None`
+    /// if the dump has no exception either.
+    pub fn stack_trace(&self) -> Option<String> {
+        if self.frames.is_empty() {
+            let exception = self.exception?;
+            return Some(format_frame(
+                0,
+                &DumpFrame {
+                    address: exception.address,
+                    module: self.module_at(exception.address).cloned(),
+                    function: None,
+                },
+            ));
+        }
+        Some(
+            self.frames
+                .iter()
+                .enumerate()
+                .map(|(index, frame)| format_frame(index, frame))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
+    /// Creates a report builder pre-filled from the dump and `mods`.
+    ///
+    /// The game version falls back to "unknown" when the executable has no
+    /// version resource, and the crash time to now. Callers add notes and
+    /// the other optional fields.
+    pub fn report_builder(&self, game_id: &str, mods: ModList) -> CrashReportBuilder {
+        let faulting_module = self.faulting_module();
+        let suspected_mod = faulting_module.and_then(|module| {
+            blame::suspected_mod(Path::new(&module.path), &mods, &blame::installed_mods())
+        });
+
+        let mut builder = CreateCrashReport::builder()
+            .game_id(game_id)
+            .game_version(self.game_version.as_deref().unwrap_or("unknown"))
+            .stack_trace(self.stack_trace().unwrap_or_default())
+            .load_order_v2(mods);
+        builder = match self.crashed_at {
+            Some(timestamp) => builder.crashed_at(timestamp),
+            None => builder.crashed_now(),
+        };
+
+        if let Some(exception) = self.exception {
+            builder = builder
+                .exception_code(format!("0x{:08X}", exception.code))
+                .exception_address(format!("0x{:016X}", exception.address));
+        }
+        if let Some(module) = faulting_module {
+            builder = builder.faulting_module(module.name());
+        }
+        if let Some(suspect) = suspected_mod {
+            builder = builder.suspected_mod(suspect);
+        }
+        builder
+    }
+}
+
+/// Formats one frame like `ctd_capture`'s stack walk.
+fn format_frame(index: usize, frame: &DumpFrame) -> String {
+    let (name, base) = match &frame.module {
+        Some(module) => (module.name(), module.base),
+        None => ("unknown", 0),
+    };
+    let mut line = format!(
+        "[{:2}] {}+0x{:X} (0x{:016X})",
+        index,
+        name,
+        frame.address.saturating_sub(base),
+        frame.address
+    );
+    if let Some(function) = &frame.function {
+        line.push(' ');
+        line.push_str(function);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game_module() -> DumpModule {
+        DumpModule {
+            base: 0x1_4000_0000,
+            size: 0x10000,
+            path: "C:\\Games\\Game\\game.exe".to_string(),
+        }
+    }
+
+    fn crash(frames: Vec<DumpFrame>) -> DumpCrash {
+        DumpCrash {
+            exception: Some(DumpException {
+                thread_id: 42,
+                code: 0xC0000005,
+                address: 0x1_4000_1234,
+            }),
+            modules: vec![game_module()],
+            frames,
+            game_version: Some("1.2.0.0".to_string()),
+            crashed_at: Some(1_700_000_000_000),
+        }
+    }
+
+    #[test]
+    fn test_dump_file_names() {
+        assert_eq!(dump_file_name("Game.exe", 4242), "Game.exe.4242.dmp");
+        assert_eq!(
+            dump_executable(Path::new("dumps/Game.exe.4242.dmp")),
+            Some("Game.exe")
+        );
+        assert_eq!(dump_executable(Path::new("dumps/manual.dmp")), None);
+    }
+
+    #[test]
+    fn test_game_id_from_exe() {
+        assert_eq!(
+            game_id_from_exe(Path::new("C:/Games/Some Game/Some Game.exe")),
+            "some-game"
+        );
+        assert_eq!(game_id_from_exe(Path::new("RDR2.exe")), "rdr2");
+    }
+
+    #[test]
+    fn test_find_and_mark_submitted() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Game.exe.7.dmp"), "MDMP").unwrap();
+        fs::write(dir.path().join("Game.exe.3.DMP"), "MDMP").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let dumps = find_dumps(dir.path());
+        assert_eq!(
+            dumps,
+            [
+                dir.path().join("Game.exe.3.DMP"),
+                dir.path().join("Game.exe.7.dmp")
+            ]
+        );
+
+        let moved = mark_submitted(&dumps[0]).unwrap();
+        assert_eq!(moved, dir.path().join("submitted").join("Game.exe.3.DMP"));
+        assert_eq!(find_dumps(dir.path()), [dir.path().join("Game.exe.7.dmp")]);
+        assert!(find_dumps(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_wait_for_dump_finds_existing_dump() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Game.exe.7.dmp"), "MDMP").unwrap();
+        assert_eq!(
+            wait_for_dump(dir.path(), "Game.exe", 7),
+            Some(dir.path().join("Game.exe.7.dmp"))
+        );
+    }
+
+    #[test]
+    fn test_stack_trace_from_walked_frames() {
+        let crash = crash(vec![
+            DumpFrame {
+                address: 0x1_4000_1234,
+                module: Some(game_module()),
+                function: Some("Game::Update+0x14".to_string()),
+            },
+            DumpFrame {
+                address: 0x7FF8_0000_2000,
+                module: None,
+                function: None,
+            },
+        ]);
+        assert_eq!(
+            crash.stack_trace().unwrap(),
+            "[ 0] game.exe+0x1234 (0x0000000140001234) Game::Update+0x14\n\
+             [ 1] unknown+0x7FF800002000 (0x00007FF800002000)"
+        );
+    }
+
+    #[test]
+    fn test_stack_trace_falls_back_to_faulting_frame() {
+        assert_eq!(
+            crash(Vec::new()).stack_trace().as_deref(),
+            Some("[ 0] game.exe+0x1234 (0x0000000140001234)")
+        );
+        assert_eq!(DumpCrash::default().stack_trace(), None);
+    }
+
+    #[test]
+    fn test_report_builder() {
+        let report = crash(Vec::new())
+            .report_builder("game", ModList::new())
+            .build()
+            .unwrap();
+        assert_eq!(report.game_version, "1.2.0.0");
+        assert_eq!(report.exception_code.as_deref(), Some("0xC0000005"));
+        assert_eq!(
+            report.exception_address.as_deref(),
+            Some("0x0000000140001234")
+        );
+        assert_eq!(report.faulting_module.as_deref(), Some("game.exe"));
+        assert_eq!(report.crashed_at, 1_700_000_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_minidump() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Game.exe.1.dmp");
+        fs::write(&path, vec![0u8; 64]).unwrap();
+
+        let result = DumpCrash::read(&path).await;
+        assert!(matches!(result, Err(WerError::Minidump(_))));
+    }
+}
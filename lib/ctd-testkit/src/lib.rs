@@ -0,0 +1,435 @@
+//! Test helpers for CTD crates.
+//!
+//! Provides a local fake of the CTD API ([`FakeServer`]) with canned
+//! responses, plus sample reports, so game crates can run integration tests
+//! of their submission logic against real HTTP without a backend.
+//!
+//! For tests that don't need HTTP at all, use
+//! [`MockTransport`](ctd_core::transport::MockTransport) from ctd-core.
+//!
+//! ```no_run
+//! # async fn example() {
+//! let server = ctd_testkit::FakeServer::start().await;
+//! let client = server.client();
+//! client.submit_crash_report(&ctd_testkit::sample_report("skyrim-se")).await.unwrap();
+//! assert_eq!(server.received_reports().await.len(), 1);
+//! # }
+//! ```
+
+use ctd_core::api_client::{
+    ApiClient, CAPABILITIES_PATH, HEALTH_PATH, KNOWN_ISSUES_PATH, KnownIssue, LATEST_VERSION_PATH,
+    LatestVersion, ServerCapabilities,
+};
+use ctd_core::config::{ApiConfig, DEFAULT_CRASHES_PATH, DEFAULT_SESSIONS_PATH};
+use ctd_core::crash_report::CURRENT_SCHEMA_VERSION;
+use ctd_core::crash_report::{CrashReportResponse, CreateCrashReport};
+use ctd_core::load_order::ModList;
+use ctd_core::session::SessionReport;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+pub use wiremock;
+
+/// Report ID returned by a [`FakeServer`] that accepts reports.
+pub const CANNED_REPORT_ID: &str = "01HZTESTREPORT000000000000";
+
+/// Share token returned by a [`FakeServer`] that accepts reports.
+pub const CANNED_SHARE_TOKEN: &str = "test-share-token";
+
+/// A local HTTP server standing in for the CTD API.
+///
+/// The server shuts down when dropped.
+pub struct FakeServer {
+    server: MockServer,
+}
+
+impl FakeServer {
+    /// Starts a server that accepts every crash report with [`canned_response`].
+    pub async fn start() -> Self {
+        let body = serde_json::to_value(canned_response()).expect("serializable response");
+        Self::with_response(ResponseTemplate::new(201).set_body_json(body)).await
+    }
+
+    /// Starts a server that answers every crash report with an error status.
+    pub async fn rejecting(status: u16, body: &str) -> Self {
+        Self::with_response(ResponseTemplate::new(status).set_body_string(body)).await
+    }
+
+    /// Starts a server that answers crash reports with a custom response.
+    ///
+    /// The health endpoint always answers 200, the capabilities endpoint
+    /// advertises the current schema version, and session summaries are
+    /// accepted with 204.
+    pub async fn with_response(response: ResponseTemplate) -> Self {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(HEALTH_PATH))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let capabilities = ServerCapabilities {
+            max_schema_version: CURRENT_SCHEMA_VERSION,
+            features: Vec::new(),
+            known_bad_modules: Vec::new(),
+        };
+        Mock::given(method("GET"))
+            .and(path(CAPABILITIES_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::to_value(capabilities).expect("serializable capabilities"),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(DEFAULT_CRASHES_PATH))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(DEFAULT_SESSIONS_PATH))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        Self { server }
+    }
+
+    /// Returns the base URL of the server.
+    pub fn url(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Returns an API config pointing at this server.
+    pub fn api_config(&self) -> ApiConfig {
+        ApiConfig {
+            url: self.url(),
+            timeout_secs: 5,
+            ..ApiConfig::default()
+        }
+    }
+
+    /// Returns an [`ApiClient`] pointing at this server.
+    pub fn client(&self) -> ApiClient {
+        ApiClient::new(self.api_config()).expect("HTTP client")
+    }
+
+    /// Returns the crash reports the server has received, oldest first.
+    ///
+    /// Requests whose body is not a valid report are skipped.
+    pub async fn received_reports(&self) -> Vec<CreateCrashReport> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| r.url.path() == DEFAULT_CRASHES_PATH)
+            .filter_map(|r| r.body_json().ok())
+            .collect()
+    }
+
+    /// Returns the session summaries the server has received, oldest first.
+    pub async fn received_sessions(&self) -> Vec<SessionReport> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|r| r.url.path() == DEFAULT_SESSIONS_PATH)
+            .filter_map(|r| r.body_json().ok())
+            .collect()
+    }
+
+    /// Makes the server report a known issue for its crash hash.
+    ///
+    /// Other hashes answer 404, like a server with no advice for them.
+    pub async fn add_known_issue(&self, issue: &KnownIssue) {
+        let body = serde_json::to_value(issue).expect("serializable known issue");
+        Mock::given(method("GET"))
+            .and(path(format!("{}/{}", KNOWN_ISSUES_PATH, issue.crash_hash)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Makes the server announce `latest` as the newest CTD release.
+    ///
+    /// Until this is called, the release endpoint answers 404.
+    pub async fn set_latest_version(&self, latest: &LatestVersion) {
+        let body = serde_json::to_value(latest).expect("serializable version");
+        Mock::given(method("GET"))
+            .and(path(LATEST_VERSION_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Returns the underlying wiremock server for mounting extra mocks.
+    pub fn mock_server(&self) -> &MockServer {
+        &self.server
+    }
+}
+
+/// The response a [`FakeServer`] returns for accepted reports.
+pub fn canned_response() -> CrashReportResponse {
+    CrashReportResponse {
+        id: CANNED_REPORT_ID.to_string(),
+        share_token: CANNED_SHARE_TOKEN.to_string(),
+    }
+}
+
+/// Builds a minimal valid crash report for a game.
+pub fn sample_report(game_id: &str) -> CreateCrashReport {
+    CreateCrashReport::builder()
+        .game_id(game_id)
+        .game_version("1.0.0")
+        .stack_trace("[0] game.exe+0x1234 (0x140001234)")
+        .exception_code("0xC0000005")
+        .load_order_v2(ModList::new())
+        .crashed_at(1_700_000_000_000)
+        .build()
+        .expect("valid sample report")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ctd_core::api_client::{SIGNATURE_HEADER, sign_body};
+    use ctd_core::crash_hash::compute_crash_hash;
+
+    #[tokio::test]
+    async fn accepts_reports_with_canned_response() {
+        let server = FakeServer::start().await;
+        let response = server
+            .client()
+            .submit_crash_report(&sample_report("skyrim-se"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.id, CANNED_REPORT_ID);
+        let received = server.received_reports().await;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].game_id, "skyrim-se");
+    }
+
+    #[tokio::test]
+    async fn accepts_session_summaries() {
+        let server = FakeServer::start().await;
+        let session = SessionReport::collect("cyberpunk2077", "2.21", ModList::new());
+        server.client().submit_session(&session).await.unwrap();
+
+        assert_eq!(server.received_sessions().await, vec![session]);
+        assert!(server.received_reports().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn ping_hits_health_endpoint() {
+        let server = FakeServer::start().await;
+        server.client().ping().await.unwrap();
+        assert!(server.received_reports().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn advertises_current_schema() {
+        let server = FakeServer::start().await;
+        let caps = server.client().capabilities().await.unwrap();
+        assert_eq!(caps.max_schema_version, CURRENT_SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn signs_submissions_when_configured() {
+        let server = FakeServer::start().await;
+        let config = ApiConfig {
+            signing_secret: Some("shared".into()),
+            ..server.api_config()
+        };
+        ApiClient::new(config)
+            .unwrap()
+            .submit_crash_report(&sample_report("skyrim-se"))
+            .await
+            .unwrap();
+
+        let requests = server.mock_server().received_requests().await.unwrap();
+        let request = requests.last().unwrap();
+        let signature = request.headers.get(SIGNATURE_HEADER).unwrap();
+        assert_eq!(
+            signature.to_str().unwrap(),
+            sign_body("shared", &request.body)
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_envelopes_to_sentry_when_configured() {
+        use ctd_core::config::SentryConfig;
+        use ctd_core::sentry::{AUTH_HEADER, ENVELOPE_CONTENT_TYPE};
+
+        let server = FakeServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/42/envelope/"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": "abc" })),
+            )
+            .mount(server.mock_server())
+            .await;
+
+        let dsn = server.url().replace("://", "://key@") + "/42";
+        let client = server
+            .client()
+            .with_sentry(&SentryConfig {
+                dsn: Some(dsn),
+                environment: None,
+            })
+            .unwrap();
+        let report = sample_report("skyrim-se");
+        let response = client.submit_crash_report(&report).await.unwrap();
+
+        assert_eq!(response.id, "abc");
+        assert!(client.known_issue_for(&report).await.is_none());
+        assert!(server.received_reports().await.is_empty());
+
+        let requests = server.mock_server().received_requests().await.unwrap();
+        let request = requests.last().unwrap();
+        assert_eq!(
+            request.headers.get("content-type").unwrap(),
+            ENVELOPE_CONTENT_TYPE
+        );
+        assert!(
+            request.headers[AUTH_HEADER]
+                .to_str()
+                .unwrap()
+                .contains("sentry_key=key")
+        );
+        let body = String::from_utf8(request.body.clone()).unwrap();
+        let event: serde_json::Value = serde_json::from_str(body.lines().nth(2).unwrap()).unwrap();
+        assert_eq!(event["tags"]["game_id"], "skyrim-se");
+    }
+
+    #[tokio::test]
+    async fn posts_discord_notification_after_submission() {
+        use ctd_core::config::{DiscordConfig, NotifyConfig};
+
+        let server = FakeServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/webhook"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(server.mock_server())
+            .await;
+
+        let client = server.client().with_notify(&NotifyConfig {
+            discord: DiscordConfig {
+                webhook_url: Some(format!("{}/webhook", server.url())),
+            },
+        });
+        client
+            .submit_crash_report(&sample_report("skyrim-se"))
+            .await
+            .unwrap();
+
+        let requests = server.mock_server().received_requests().await.unwrap();
+        let webhook = requests
+            .iter()
+            .find(|r| r.url.path() == "/webhook")
+            .unwrap();
+        let message: serde_json::Value = serde_json::from_slice(&webhook.body).unwrap();
+        let embed = &message["embeds"][0];
+        assert_eq!(embed["title"], "Crash in skyrim-se");
+        assert_eq!(embed["url"], client.share_url(&canned_response()));
+    }
+
+    #[tokio::test]
+    async fn identifies_client_version() {
+        use ctd_core::api_client::{CLIENT_HEADER, CLIENT_VERSION};
+
+        let server = FakeServer::start().await;
+        server.client().ping().await.unwrap();
+
+        let requests = server.mock_server().received_requests().await.unwrap();
+        let headers = &requests.last().unwrap().headers;
+        assert_eq!(
+            headers.get(CLIENT_HEADER).unwrap().to_str().unwrap(),
+            CLIENT_VERSION
+        );
+        assert_eq!(
+            headers.get("user-agent").unwrap().to_str().unwrap(),
+            format!("ctd/{}", CLIENT_VERSION)
+        );
+    }
+
+    #[tokio::test]
+    async fn announces_newer_releases_only() {
+        let server = FakeServer::start().await;
+        let client = server.client();
+        assert_eq!(client.check_latest_version().await.unwrap(), None);
+
+        let latest = LatestVersion {
+            version: "999.0.0".into(),
+            url: None,
+        };
+        server.set_latest_version(&latest).await;
+        assert_eq!(client.check_latest_version().await.unwrap(), Some(latest));
+    }
+
+    #[tokio::test]
+    async fn reports_known_issue_for_submitted_crash() {
+        let server = FakeServer::start().await;
+        let report = sample_report("skyrim-se");
+        let client = server.client();
+        assert_eq!(client.known_issue_for(&report).await, None);
+
+        let issue = KnownIssue {
+            crash_hash: compute_crash_hash(&report.stack_trace),
+            pattern_name: None,
+            known_fix: "Caused by OldMod.dll < 1.4".into(),
+            occurrence_count: 3,
+            suspected_mods: Vec::new(),
+        };
+        server.add_known_issue(&issue).await;
+
+        assert_eq!(client.known_issue_for(&report).await, Some(issue));
+    }
+
+    #[tokio::test]
+    async fn lists_reports_with_api_key() {
+        use ctd_core::crash_report::ReportPage;
+        use wiremock::matchers::{header, query_param};
+
+        let server = FakeServer::start().await;
+        let page = ReportPage {
+            reports: Vec::new(),
+            page: 2,
+            per_page: 20,
+            total: 20,
+        };
+        Mock::given(method("GET"))
+            .and(path(DEFAULT_CRASHES_PATH))
+            .and(query_param("page", "2"))
+            .and(header("Authorization", "Bearer ctd_test"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::to_value(&page).unwrap()),
+            )
+            .mount(server.mock_server())
+            .await;
+
+        let config = ApiConfig {
+            api_key: Some("ctd_test".into()),
+            ..server.api_config()
+        };
+        let listed = ApiClient::new(config)
+            .unwrap()
+            .list_my_reports(2)
+            .await
+            .unwrap();
+        assert_eq!(listed, page);
+        assert!(!listed.has_more());
+    }
+
+    #[tokio::test]
+    async fn rejecting_server_surfaces_status() {
+        let server = FakeServer::rejecting(401, "invalid api key").await;
+        let err = server
+            .client()
+            .submit_crash_report(&sample_report("fallout4"))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("401"));
+        assert!(err.to_string().contains("invalid api key"));
+    }
+}
@@ -0,0 +1,103 @@
+//! CTD Crash Reporter for Baldur's Gate 3
+//!
+//! A native mod that captures crashes via the shared Vectored Exception
+//! Handler in `ctd-capture` and submits crash reports to the CTD backend
+//! API, with the `Mods/*.pak` load order and the BG3 Script Extender
+//! version.
+//!
+//! ## Installation
+//!
+//! Place the compiled `ctd_bg3.dll` in `<game>/bin/NativeMods/`, where
+//! BG3 Native Mod Loader loads it at startup. BG3 Script Extender itself
+//! has no native plugin API, so the reporter sits beside it rather than
+//! inside it.
+//!
+//! ## Features
+//!
+//! - Captures crashes with stack traces and exception information
+//! - Reports paks in `modsettings.lsx` load order, with inactive paks after
+//! - Fire-and-forget submission (never blocks the game)
+//!
+//! ## Platform Support
+//!
+//! This crate is designed for Windows (x86_64-pc-windows-msvc) but can be
+//! compiled on other platforms for development purposes. Non-Windows builds
+//! produce a stub library.
+
+// Allow dead code on non-Windows platforms where the actual implementation isn't used
+#![cfg_attr(not(windows), allow(dead_code, unused_imports))]
+
+pub mod mod_scanner;
+pub mod report;
+
+#[cfg(windows)]
+use ctd_capture::handler::{self, HandlerOptions};
+#[cfg(windows)]
+use ctd_core::config::Config;
+#[cfg(windows)]
+use tracing::{error, info};
+
+/// DLL entry point.
+///
+/// The loader lock is held here, so startup runs on a thread of its own.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub extern "system" fn DllMain(
+    _module: windows::Win32::Foundation::HINSTANCE,
+    reason: u32,
+    _reserved: *mut std::ffi::c_void,
+) -> windows::Win32::Foundation::BOOL {
+    use windows::Win32::System::SystemServices::DLL_PROCESS_ATTACH;
+
+    if reason == DLL_PROCESS_ATTACH {
+        std::thread::spawn(start);
+    }
+    windows::Win32::Foundation::TRUE
+}
+
+/// Registers the crash handler and caches the mod list.
+#[cfg(windows)]
+fn start() {
+    if let Some(path) = ctd_core::logging::init(report::GAME_ID) {
+        info!("Logging to {}", path.display());
+    }
+
+    info!("CTD Crash Reporter initializing...");
+
+    ctd_core::startup::init();
+
+    let options = HandlerOptions::from_config(&Config::load().unwrap_or_default());
+    if let Err(e) = handler::register(
+        report::GAME_ID,
+        &report::get_game_version(),
+        options,
+        report::submit_async,
+    ) {
+        error!("Failed to register crash handler: {}", e);
+    } else {
+        info!("VEH crash handler registered");
+    }
+
+    // Cache mod list on startup; the crash path only copies it
+    let count = mod_scanner::scan_and_cache();
+    info!("Cached {} mods", count);
+
+    info!("CTD Crash Reporter initialized successfully");
+}
+
+// Breadcrumbs, context, and handled errors from other mods (see ctd.h)
+#[cfg(windows)]
+ctd_core::export_abi!();
+
+// ===========================================================================
+// Non-Windows stubs for development/testing on other platforms
+// ===========================================================================
+
+/// Stub initialization function for non-Windows platforms.
+///
+/// This is only used for development testing - the actual plugin
+/// functionality only works on Windows.
+#[cfg(not(windows))]
+pub fn init() {
+    eprintln!("ctd-bg3: This plugin only works on Windows");
+}
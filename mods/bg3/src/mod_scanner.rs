@@ -0,0 +1,338 @@
+//! Baldur's Gate 3 mod enumeration.
+//!
+//! Mods are `.pak` files in `%LOCALAPPDATA%\Larian Studios\Baldur's Gate 3\Mods`.
+//! The game loads the modules `PlayerProfiles\Public\modsettings.lsx` lists,
+//! in its order; paks it doesn't list are installed but inactive. Since
+//! Patch 7 the `Mods` node alone is the load order (the old `ModOrder` node
+//! is gone), so that is all [`parse_modsettings`] reads.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use ctd_core::file_hash::{CachedHasher, UNHASHED};
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
+use tracing::info;
+
+/// The game's data directory, under `%LOCALAPPDATA%`.
+const DATA_DIR: &str = "Larian Studios/Baldur's Gate 3";
+
+/// The load order, relative to the data directory.
+const MODSETTINGS: &str = "PlayerProfiles/Public/modsettings.lsx";
+
+/// Modules the game ships, listed in `modsettings.lsx` but not mods.
+pub const BASE_MODULES: &[&str] = &[
+    "Gustav",
+    "GustavDev",
+    "GustavX",
+    "Shared",
+    "SharedDev",
+    "Honour",
+    "HonourX",
+    "MainUI",
+    "ModBrowser",
+];
+
+/// Mods found at startup, for the crash path to copy.
+static CACHED_MODS: RwLock<Option<ModList>> = RwLock::new(None);
+
+/// A module entry from `modsettings.lsx`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    /// Display name (`Name`)
+    pub name: String,
+    /// Folder inside the pak, usually the pak's file name (`Folder`)
+    pub folder: String,
+    /// Module UUID
+    pub uuid: String,
+    /// Version from `Version64`, as "major.minor.revision.build"
+    pub version: Option<String>,
+}
+
+impl Module {
+    /// Returns true for the modules the game ships.
+    pub fn is_base(&self) -> bool {
+        BASE_MODULES
+            .iter()
+            .any(|base| base.eq_ignore_ascii_case(&self.folder))
+    }
+}
+
+/// Parses the `ModuleShortDesc` entries of `modsettings.lsx`, in load order.
+pub fn parse_modsettings(content: &str) -> Vec<Module> {
+    content
+        .split(r#"<node id="ModuleShortDesc""#)
+        .skip(1)
+        .filter_map(|node| {
+            let node = &node[..node.find("</node>").unwrap_or(node.len())];
+            let folder = attribute(node, "Folder")?;
+            Some(Module {
+                name: attribute(node, "Name").unwrap_or_else(|| folder.clone()),
+                folder,
+                uuid: attribute(node, "UUID").unwrap_or_default(),
+                version: attribute(node, "Version64")
+                    .and_then(|v| v.parse().ok())
+                    .and_then(format_version64),
+            })
+        })
+        .collect()
+}
+
+/// Returns the `value` of the `<attribute id="{id}" .../>` in `node`.
+fn attribute(node: &str, id: &str) -> Option<String> {
+    node.split("<attribute").skip(1).find_map(|tag| {
+        let tag = &tag[..tag.find('>')?];
+        if tag_value(tag, "id")? != id {
+            return None;
+        }
+        tag_value(tag, "value").map(unescape)
+    })
+}
+
+/// Returns the raw value of `key="..."` in an XML tag.
+fn tag_value<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {}=\"", key))? + key.len() + 3;
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+/// Replaces the XML entities LSX files use.
+fn unescape(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Formats a Larian `Version64` as "major.minor.revision.build", or `None`
+/// for 0 (no version).
+///
+/// The fields are packed as 9, 8, 16, and 31 bits, most significant first.
+fn format_version64(version: u64) -> Option<String> {
+    (version != 0).then(|| {
+        format!(
+            "{}.{}.{}.{}",
+            version >> 55,
+            (version >> 47) & 0xFF,
+            (version >> 31) & 0xFFFF,
+            version & 0x7FFF_FFFF
+        )
+    })
+}
+
+/// Returns the game's data directory, which holds `Mods` and the load order.
+pub fn get_data_directory() -> Option<PathBuf> {
+    let local_app_data = std::env::var_os("LOCALAPPDATA")?;
+    Some(PathBuf::from(local_app_data).join(DATA_DIR))
+}
+
+/// Scans `Mods/*.pak` in `data_dir` into a load order.
+///
+/// Modules `modsettings.lsx` lists come first, in its order and enabled,
+/// each matched to the pak named after its folder or name (ignoring case).
+/// A listed module with no pak is kept with no hash, since a missing
+/// dependency often causes the crash. Paks it doesn't list follow, disabled,
+/// in file name order.
+pub fn scan_mods(data_dir: &Path) -> ModList {
+    let modules = std::fs::read_to_string(data_dir.join(MODSETTINGS))
+        .map(|content| parse_modsettings(&content))
+        .unwrap_or_default();
+
+    let paks = list_paks(&data_dir.join("Mods"));
+    let mut hasher = CachedHasher::open_default();
+    let hashed = ModList::fingerprint_parallel(&paks, DEFAULT_HASH_BUDGET, &mut hasher);
+    hasher.save();
+
+    let mut unlisted: Vec<(&PathBuf, ModEntry)> = paks.iter().zip(hashed.0).collect();
+    let mut ordered = Vec::with_capacity(unlisted.len());
+    for module in modules.iter().filter(|module| !module.is_base()) {
+        let pos = unlisted.iter().position(|(path, _)| {
+            path.file_stem().is_some_and(|stem| {
+                let stem = stem.to_string_lossy();
+                stem.eq_ignore_ascii_case(&module.folder) || stem.eq_ignore_ascii_case(&module.name)
+            })
+        });
+        let entry = match pos {
+            Some(pos) => unlisted.remove(pos).1,
+            None => ModEntry::new(&module.folder, UNHASHED, 0),
+        };
+        let mut entry = entry.with_enabled(true).with_mod_name(&module.name);
+        if let Some(version) = &module.version {
+            entry = entry.with_version(version);
+        }
+        ordered.push(entry);
+    }
+    ordered.extend(
+        unlisted
+            .into_iter()
+            .map(|(_, entry)| entry.with_enabled(false)),
+    );
+
+    let mut list = ModList::new();
+    for (index, entry) in ordered.into_iter().enumerate() {
+        // Paks have no light/full distinction
+        list.push(entry.with_index(index as u32).with_light(false));
+    }
+    list
+}
+
+/// Lists the `.pak` files directly in `mods_dir`, by file name.
+fn list_paks(mods_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(mods_dir) else {
+        return Vec::new();
+    };
+    let mut paks: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("pak"))
+        })
+        .collect();
+    paks.sort();
+    paks
+}
+
+/// Scans the mods and caches them for crash reports.
+///
+/// Returns the number of mods found.
+pub fn scan_and_cache() -> usize {
+    let Some(data_dir) = get_data_directory() else {
+        info!("LOCALAPPDATA is not set; no mods to scan");
+        return 0;
+    };
+    let mods = scan_mods(&data_dir);
+    let count = mods.len();
+    *CACHED_MODS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(mods);
+    count
+}
+
+/// Returns the cached mods, or an empty list if no scan has finished.
+pub fn get_cached_or_empty() -> ModList {
+    CACHED_MODS
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Returns the game's `bin` directory, which holds the executable.
+pub fn get_game_bin_directory() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn module(folder: &str, name: &str, version64: &str) -> String {
+        format!(
+            r#"<node id="ModuleShortDesc">
+                <attribute id="Folder" type="LSString" value="{folder}"/>
+                <attribute id="MD5" type="LSString" value=""/>
+                <attribute id="Name" type="LSString" value="{name}"/>
+                <attribute id="UUID" type="guid" value="{folder}-uuid"/>
+                <attribute id="Version64" type="int64" value="{version64}"/>
+            </node>"#
+        )
+    }
+
+    fn modsettings(modules: &[String]) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<save>
+  <version major="4" minor="7" revision="1" build="3"/>
+  <region id="ModuleSettings">
+    <node id="root">
+      <children>
+        <node id="Mods">
+          <children>
+            {}
+          </children>
+        </node>
+      </children>
+    </node>
+  </region>
+</save>"#,
+            modules.join("\n")
+        )
+    }
+
+    #[test]
+    fn test_parse_modsettings() {
+        let content = modsettings(&[
+            module("GustavX", "GustavX", "36028797018963968"),
+            module("ImpUI", "ImpUI &amp; Tav&apos;s Tweaks", "0"),
+        ]);
+        let modules = parse_modsettings(&content);
+
+        assert_eq!(modules.len(), 2);
+        assert!(modules[0].is_base());
+        assert_eq!(modules[0].version.as_deref(), Some("1.0.0.0"));
+        assert_eq!(modules[1].folder, "ImpUI");
+        assert_eq!(modules[1].name, "ImpUI & Tav's Tweaks");
+        assert_eq!(modules[1].uuid, "ImpUI-uuid");
+        assert_eq!(modules[1].version, None);
+        assert!(parse_modsettings("not xml").is_empty());
+    }
+
+    #[test]
+    fn test_format_version64() {
+        let version = (1u64 << 55) | (2 << 47) | (3 << 31) | 4;
+        assert_eq!(format_version64(version).as_deref(), Some("1.2.3.4"));
+        assert_eq!(format_version64(0), None);
+    }
+
+    #[test]
+    fn test_scan_mods_follows_modsettings() {
+        let dir = tempfile::tempdir().unwrap();
+        let mods_dir = dir.path().join("Mods");
+        fs::create_dir_all(&mods_dir).unwrap();
+        fs::create_dir_all(dir.path().join("PlayerProfiles/Public")).unwrap();
+        for pak in ["Alpha.pak", "Unused.pak", "zeta.pak", "readme.txt"] {
+            fs::write(mods_dir.join(pak), pak).unwrap();
+        }
+        fs::write(
+            dir.path().join(MODSETTINGS),
+            modsettings(&[
+                module("GustavX", "GustavX", "0"),
+                module("Zeta", "Zeta Spells", "36028797018963968"),
+                module("Missing", "Missing Dependency", "0"),
+                module("Alpha", "Alpha", "0"),
+            ]),
+        )
+        .unwrap();
+
+        let mods = scan_mods(dir.path());
+        let names: Vec<&str> = mods.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["zeta.pak", "Missing", "Alpha.pak", "Unused.pak"]);
+
+        let enabled: Vec<Option<bool>> = mods.iter().map(|entry| entry.enabled).collect();
+        assert_eq!(enabled, [Some(true), Some(true), Some(true), Some(false)]);
+
+        assert_eq!(mods.0[0].mod_name.as_deref(), Some("Zeta Spells"));
+        assert_eq!(mods.0[0].version.as_deref(), Some("1.0.0.0"));
+        assert_eq!(mods.0[1].file_hash, UNHASHED);
+        assert_eq!(mods.0[3].index, Some(3));
+    }
+
+    #[test]
+    fn test_scan_mods_without_modsettings() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(scan_mods(dir.path()).is_empty());
+
+        fs::create_dir_all(dir.path().join("Mods")).unwrap();
+        fs::write(dir.path().join("Mods/Alpha.pak"), "pak").unwrap();
+        let mods = scan_mods(dir.path());
+        assert_eq!(mods.len(), 1);
+        assert_eq!(mods.0[0].enabled, Some(false));
+    }
+}
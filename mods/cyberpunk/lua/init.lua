@@ -0,0 +1,39 @@
+-- CTD Crash Reporter: CET bindings (ctd-cyberpunk @VERSION@)
+--
+-- Written by the ctd-cyberpunk RED4ext plugin each time the game starts;
+-- edits are overwritten. Other CET mods leave context for the next crash
+-- report through it:
+--
+--     local CTD = GetMod("ctd")
+--     if CTD then
+--         CTD.Breadcrumb("Started the Afterlife heist", "MyMod")
+--         CTD.Note("Night City Interactive: 40 NPCs spawned", "MyMod")
+--     end
+--
+-- The second argument names your mod and defaults to "CET". Each function
+-- returns false if CTD rejected the call or the plugin isn't loaded.
+
+local CTD = { version = "@VERSION@" }
+
+-- Calls a global native registered by the plugin, which may be missing if
+-- the plugin was removed and this file left behind
+local function call(name, ...)
+    local args = { ... }
+    local ok, result = pcall(function()
+        return Game[name](table.unpack(args))
+    end)
+    return ok and result == true
+end
+
+-- Records a step in the last 32 steps reported as breadcrumbs.
+function CTD.Breadcrumb(message, source)
+    return call("CTDBreadcrumb", source or "CET", tostring(message))
+end
+
+-- Adds a note to the end of the next report's notes, as "[source] message".
+-- The last 8 notes are kept.
+function CTD.Note(message, source)
+    return call("CTDNote", source or "CET", tostring(message))
+end
+
+return CTD
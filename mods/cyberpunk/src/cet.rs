@@ -0,0 +1,78 @@
+//! Lua bindings for Cyber Engine Tweaks mods.
+//!
+//! CET mods can call the plugin's global natives as `Game.CTDBreadcrumb`,
+//! but each would have to know the names and guard against the plugin being
+//! missing. Instead the plugin writes a small CET mod, `mods/ctd/init.lua`,
+//! from the template in `lua/init.lua` each time it loads, and other mods
+//! use it through `GetMod("ctd")`:
+//!
+//! - `CTD.Breadcrumb(message, source)` calls `CTDBreadcrumb`, which records a
+//!   breadcrumb (see [`ctd_core::abi::add_breadcrumb`]).
+//! - `CTD.Note(message, source)` calls `CTDNote`, which adds a note for the
+//!   end of the next report's notes (see [`ctd_core::abi::add_note`]).
+//!
+//! `source` defaults to "CET". Nothing is written when CET isn't installed,
+//! and the file is only rewritten when the plugin's version of it changed.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// CET's directory, relative to the game directory.
+const CET_DIR: &str = "bin/x64/plugins/cyber_engine_tweaks";
+
+/// The bindings mod, relative to [`CET_DIR`].
+const MOD_FILE: &str = "mods/ctd/init.lua";
+
+/// Source of `init.lua`, with `@VERSION@` standing in for the plugin version.
+const INIT_LUA_TEMPLATE: &str = include_str!("../lua/init.lua");
+
+/// Returns the bindings mod for this version of the plugin.
+pub fn init_lua() -> String {
+    INIT_LUA_TEMPLATE.replace("@VERSION@", env!("CARGO_PKG_VERSION"))
+}
+
+/// Writes the bindings mod under `game_dir` if CET is installed.
+///
+/// Returns the path written, or `None` if CET isn't installed or the file
+/// is already current.
+pub fn install(game_dir: &Path) -> io::Result<Option<PathBuf>> {
+    let cet_dir = game_dir.join(CET_DIR);
+    if !cet_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let path = cet_dir.join(MOD_FILE);
+    let contents = init_lua();
+    if fs::read_to_string(&path).is_ok_and(|existing| existing == contents) {
+        return Ok(None);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, contents)?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_the_bindings_only_with_cet_installed() {
+        let game_dir = tempfile::tempdir().unwrap();
+        assert_eq!(install(game_dir.path()).unwrap(), None);
+
+        fs::create_dir_all(game_dir.path().join(CET_DIR)).unwrap();
+        let path = install(game_dir.path()).unwrap().unwrap();
+        let lua = fs::read_to_string(&path).unwrap();
+        assert!(lua.contains(env!("CARGO_PKG_VERSION")));
+        assert!(!lua.contains("@VERSION@"));
+        assert!(lua.contains("\"CTDBreadcrumb\"") && lua.contains("\"CTDNote\""));
+
+        // Current files are left alone; stale ones are replaced
+        assert_eq!(install(game_dir.path()).unwrap(), None);
+        fs::write(&path, "return {}").unwrap();
+        assert_eq!(install(game_dir.path()).unwrap(), Some(path));
+    }
+}
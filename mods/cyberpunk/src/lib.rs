@@ -14,6 +14,7 @@
 //! - Enumerates all installed mods from multiple sources
 //! - Fire-and-forget submission (never blocks the game)
 //! - Graceful failure (never crashes the crash handler)
+//! - Breadcrumbs and notes from CET mods (`GetMod("ctd")`, see [`cet`])
 //!
 //! ## Platform Support
 //!
@@ -24,15 +25,25 @@
 // Allow dead code on non-Windows platforms where the actual implementation isn't used
 #![cfg_attr(not(windows), allow(dead_code, unused_imports))]
 
-pub mod crash_handler;
+pub mod cet;
 pub mod mod_scanner;
 pub mod report;
 
 #[cfg(windows)]
-use red4ext_rs::{Exportable, Plugin, SemVer, U16CStr, export_plugin_symbols, exports, wcstr};
+use std::ffi::{CStr, c_char};
 
 #[cfg(windows)]
-use tracing::{error, info};
+use ctd_capture::handler::{self, HandlerOptions};
+#[cfg(windows)]
+use ctd_core::config::Config;
+#[cfg(windows)]
+use red4ext_rs::{
+    Exportable, GameApp, GlobalExport, Plugin, PluginOps, SemVer, StateListener, StateType,
+    U16CStr, export_plugin_symbols, exports, global, wcstr,
+};
+
+#[cfg(windows)]
+use tracing::{error, info, warn};
 
 /// The CTD Crash Reporter plugin for RED4ext.
 #[cfg(windows)]
@@ -53,38 +64,245 @@ impl Plugin for CtdReporter {
 
         info!("CTD Crash Reporter initializing...");
 
+        ctd_core::startup::init();
+
         // Register VEH handler for crash capture
-        if let Err(e) = crash_handler::register() {
+        let options = HandlerOptions::from_config(&Config::load().unwrap_or_default());
+        if let Err(e) = handler::register(
+            report::GAME_ID,
+            &report::get_game_version(),
+            options,
+            report::submit_async,
+        ) {
             error!("Failed to register crash handler: {}", e);
         } else {
             info!("VEH crash handler registered");
         }
 
-        // Cache mod list on startup (filesystem scan is expensive)
+        ctd_core::session::start();
+
+        // Report freezes: the game loop beats the watchdog every frame
+        let running = StateListener::default().with_on_update(on_running_update);
+        if !env.add_listener(StateType::Running, running) {
+            warn!("Failed to hook the game loop; freezes won't be reported");
+        }
+        let watchdog_config = Config::load().unwrap_or_default().watchdog;
+        if let Err(e) = ctd_core::watchdog::start(&watchdog_config, report::submit_hang) {
+            warn!("Failed to start freeze watchdog: {}", e);
+        }
+
+        // Summarize the session on clean exit, if [session] is enabled
+        let shutdown = StateListener::default().with_on_enter(on_shutdown);
+        if !env.add_listener(StateType::Shutdown, shutdown) {
+            warn!("Failed to hook shutdown; session summaries won't be sent");
+        }
+
+        // Cache mod list on startup (filesystem scan is expensive), then
+        // refresh it in the background to catch mods installed mid-session
         match mod_scanner::scan_and_cache() {
             Ok(count) => info!("Cached {} mods from all sources", count),
             Err(e) => error!("Failed to scan mods: {}", e),
         }
+        mod_scanner::start_periodic_rescan();
+
+        // Give CET mods `GetMod("ctd")` for breadcrumbs and notes
+        if let Some(game_dir) = mod_scanner::get_game_directory_path() {
+            match cet::install(&game_dir) {
+                Ok(Some(path)) => info!("Wrote CET bindings to {}", path.display()),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to write CET bindings: {}", e),
+            }
+        }
 
         info!("CTD Crash Reporter initialized successfully");
     }
 
     fn exports() -> impl Exportable {
-        // No exported functions needed - we only handle crashes
-        exports![]
+        // Lets redscript and CET file a report: `CTDSubmitReport("notes")`,
+        // pick up newly installed mods: `CTDRescanMods()`, and leave context
+        // for the next report: `CTDBreadcrumb("MyMod", "message")` and
+        // `CTDNote("MyMod", "text")` (wrapped for CET by the `cet` module)
+        exports![
+            GlobalExport(global!(c"CTDSubmitReport", submit_report_native)),
+            GlobalExport(global!(c"CTDRescanMods", rescan_mods_native)),
+            GlobalExport(global!(c"CTDBreadcrumb", breadcrumb_native)),
+            GlobalExport(global!(c"CTDNote", note_native)),
+        ]
+    }
+}
+
+/// Files a manual report from redscript or CET.
+#[cfg(windows)]
+fn submit_report_native(notes: String) -> bool {
+    match report::submit_manual(&notes) {
+        Ok(()) => {
+            info!("Filing manual report");
+            true
+        }
+        Err(e) => {
+            warn!("Cannot file report: {}", e);
+            false
+        }
     }
 }
 
-/// Initialize tracing to output to RED4ext's logging system.
+/// Rescans the mod directories in the background from redscript or CET.
+#[cfg(windows)]
+fn rescan_mods_native() {
+    mod_scanner::spawn_rescan();
+}
+
+/// Records a breadcrumb from redscript or CET.
+#[cfg(windows)]
+fn breadcrumb_native(source: String, message: String) -> bool {
+    ctd_core::abi::add_breadcrumb(&source, &message)
+}
+
+/// Leaves a note for the next report from redscript or CET.
+#[cfg(windows)]
+fn note_native(source: String, text: String) -> bool {
+    ctd_core::abi::add_note(&source, &text)
+}
+
+/// Beats the freeze watchdog and times the frame; RED4ext calls this every
+/// frame while the game runs.
+#[cfg(windows)]
+unsafe extern "C" fn on_running_update(_app: &GameApp) {
+    ctd_core::watchdog::beat();
+    ctd_core::frame_times::record_frame();
+}
+
+/// Submits the session summary; RED4ext calls this when the game starts
+/// shutting down.
+#[cfg(windows)]
+unsafe extern "C" fn on_shutdown(_app: &GameApp) {
+    report::submit_session();
+}
+
+/// Initialize tracing to the CTD log file, mirroring warnings and errors
+/// into RED4ext's log.
 #[cfg(windows)]
 fn init_logging(_env: &red4ext_rs::SdkEnv) {
-    // red4ext-rs with the "log" feature handles this automatically
-    // via the tracing subscriber integration
+    if let Some(path) = ctd_core::logging::init_with_host(report::GAME_ID, forward_to_red4ext) {
+        info!("Logging to {}", path.display());
+    }
+}
+
+/// Mirrors a warning or error into the RED4ext log, where users look first.
+#[cfg(windows)]
+fn forward_to_red4ext(level: tracing::Level, message: &str) {
+    let env = CtdReporter::env();
+    if level == tracing::Level::ERROR {
+        env.error(message);
+    } else {
+        env.warn(message);
+    }
 }
 
 #[cfg(windows)]
 export_plugin_symbols!(CtdReporter);
 
+/// Crash the game on purpose if `allow_test_crash` is set in ctd.toml.
+///
+/// Returns false (and logs why) when test crashes are disabled.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub extern "C" fn trigger_test_crash() -> bool {
+    let config = Config::load().unwrap_or_default();
+    match ctd_core::test_crash::trigger(&config) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("{}", e);
+            false
+        }
+    }
+}
+
+/// Copies the ID of the last submitted report into `buffer`.
+///
+/// Lets a next-launch prompt add context to the last report. The ID is
+/// NUL-terminated and truncated to fit; returns its full length (0 if none).
+///
+/// # Safety
+///
+/// `buffer` must be null or valid for writes of `size` bytes.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn last_report_id(buffer: *mut c_char, size: usize) -> usize {
+    let id = ctd_core::last_report::LastReport::load(report::GAME_ID)
+        .map(|report| report.id)
+        .unwrap_or_default();
+
+    if !buffer.is_null() && size > 0 {
+        let len = id.len().min(size - 1);
+        // SAFETY: the caller guarantees `buffer` holds `size` bytes, and len < size
+        unsafe {
+            std::ptr::copy_nonoverlapping(id.as_ptr().cast::<c_char>(), buffer, len);
+            *buffer.add(len) = 0;
+        }
+    }
+    id.len()
+}
+
+/// Attaches a note to the last submitted report.
+///
+/// Blocks until the note is sent. Returns false (and logs why) on failure.
+///
+/// # Safety
+///
+/// `text` must be null or a valid NUL-terminated string.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn append_note_to_last_report(text: *const c_char) -> bool {
+    if text.is_null() {
+        return false;
+    }
+    // SAFETY: the caller guarantees `text` is a valid C string
+    let Ok(text) = unsafe { CStr::from_ptr(text) }.to_str() else {
+        warn!("Cannot attach note: text is not valid UTF-8");
+        return false;
+    };
+
+    match ctd_core::last_report::append_note_to_last(report::GAME_ID, text) {
+        Ok(id) => {
+            info!("Note attached to report {}", id);
+            true
+        }
+        Err(e) => {
+            warn!("Failed to attach note: {}", e);
+            false
+        }
+    }
+}
+
+/// Files a manual report with the current load order and every thread's
+/// stack, for tools that want a "report a problem" button.
+///
+/// Returns false if `notes` is empty, too long, or not valid UTF-8. The
+/// report is built and sent on a background thread.
+///
+/// # Safety
+///
+/// `notes` must be null or a valid NUL-terminated string.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn submit_manual_report(notes: *const c_char) -> bool {
+    if notes.is_null() {
+        return false;
+    }
+    // SAFETY: the caller guarantees `notes` is a valid C string
+    let Ok(notes) = unsafe { CStr::from_ptr(notes) }.to_str() else {
+        warn!("Cannot file report: notes are not valid UTF-8");
+        return false;
+    };
+
+    submit_report_native(notes.to_string())
+}
+
+// Breadcrumbs, context, and handled errors from other mods (see ctd.h)
+#[cfg(windows)]
+ctd_core::export_abi!();
+
 // ===========================================================================
 // Non-Windows stubs for development/testing on other platforms
 // ===========================================================================
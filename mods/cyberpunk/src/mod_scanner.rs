@@ -9,17 +9,35 @@
 //! - **REDmod**: Directories with `info.json` in `mods/`
 //! - **RED4ext**: `.dll` plugins in `red4ext/plugins/`
 //! - **CET**: Lua mods with `init.lua` in `bin/x64/plugins/cyber_engine_tweaks/mods/`
+//!
+//! The list is scanned once at startup and cached for crash reports, then
+//! refreshed on a background thread every [`RESCAN_INTERVAL`] so mods
+//! installed while the game runs (e.g. CET mods) show up. Rescans hash
+//! through the same [`CachedHasher`], so only new or changed files are read.
+//!
+//! Each scan also checks `.archive` files for double installs: the same
+//! archive name both loose in `archive/pc/mod/` and inside a REDmod's
+//! `archives/`, or the same content under two names. These are a common
+//! crash trigger and are reported as `conflicts`.
+//!
+//! Every entry carries a file hash and size. Versions come from REDmod
+//! `info.json`, RED4ext DLL version resources, and CET mod manifests.
 //! - **Redscript**: `.reds` scripts in `r6/scripts/`
 //! - **TweakXL**: `.yaml`/`.yml` files in `r6/tweaks/`
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
-
-use ctd_core::file_hash::compute_file_hash;
-use ctd_core::load_order::{ModEntry, ModList};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{PoisonError, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use ctd_core::crash_report::{ConflictKind, MAX_CONFLICTS, ModConflict};
+use ctd_core::file_hash::{CachedHasher, UNHASHED};
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
 use ctd_core::version::get_dll_version;
 use thiserror::Error;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
 /// Errors that can occur during mod scanning.
@@ -81,13 +99,99 @@ const MOD_PATHS: &[(&str, ModType)] = &[
     ("r6/tweaks", ModType::TweakXL),
 ];
 
-/// Cached mod list from startup scan.
-static CACHED_MODS: OnceLock<ModList> = OnceLock::new();
+/// How often the background thread rescans the mod directories.
+pub const RESCAN_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Cached mod list from the latest scan, `None` until the first one.
+static CACHED_MODS: RwLock<Option<ModList>> = RwLock::new(None);
+
+/// Archive conflicts found by the latest scan.
+static CACHED_CONFLICTS: RwLock<Vec<ModConflict>> = RwLock::new(Vec::new());
+
+/// Set while a scan is running, so rescans never overlap.
+static SCANNING: AtomicBool = AtomicBool::new(false);
+
+/// Set once the periodic rescan thread has started.
+static RESCAN_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Frameworks most mods depend on, with their DLL relative to the game
+/// directory. Crashes after a game patch are usually one of these being
+/// out of date.
+const FRAMEWORKS: &[(&str, &str)] = &[
+    ("ArchiveXL", "red4ext/plugins/ArchiveXL/ArchiveXL.dll"),
+    ("TweakXL", "red4ext/plugins/TweakXL/TweakXL.dll"),
+    ("Codeware", "red4ext/plugins/Codeware/Codeware.dll"),
+    (
+        "Cyber Engine Tweaks",
+        "bin/x64/plugins/cyber_engine_tweaks.asi",
+    ),
+];
+
+/// Manifests CET mods keep their version in, checked in order.
+///
+/// CET has no standard manifest, but many mods ship one of these next to
+/// `init.lua` with a `version` field.
+const CET_MANIFESTS: &[&str] = &["metadata.json", "manifest.json"];
+
+/// Metadata a REDmod declares in its `info.json`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct RedmodInfo {
+    /// Declared mod name, which may differ from the directory name.
+    name: Option<String>,
+    /// Declared version.
+    version: Option<String>,
+    /// Number of entries in `customSounds`.
+    custom_sounds: usize,
+}
 
-/// Parse REDmod info.json for version.
-fn get_redmod_version(mod_dir: &Path) -> Option<String> {
+impl RedmodInfo {
+    /// Parses `info.json` content, or returns `None` if it isn't a JSON
+    /// object. Missing or mistyped fields are left empty.
+    fn parse(content: &str) -> Option<Self> {
+        let json: serde_json::Value = serde_json::from_str(content).ok()?;
+        let json = json.as_object()?;
+        let text = |key: &str| {
+            let value = match json.get(key)? {
+                serde_json::Value::String(s) => s.trim().to_string(),
+                // Some authors write versions as bare numbers
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => return None,
+            };
+            Some(value).filter(|s| !s.is_empty())
+        };
+
+        Some(Self {
+            name: text("name"),
+            version: text("version"),
+            custom_sounds: json
+                .get("customSounds")
+                .and_then(serde_json::Value::as_array)
+                .map_or(0, Vec::len),
+        })
+    }
+}
+
+/// Reads a REDmod's `info.json`, logging and skipping a malformed one.
+fn read_redmod_info(mod_dir: &Path) -> Option<RedmodInfo> {
     let info_path = mod_dir.join("info.json");
-    let content = std::fs::read_to_string(info_path).ok()?;
+    let content = std::fs::read_to_string(&info_path).ok()?;
+    let info = RedmodInfo::parse(&content);
+    if info.is_none() {
+        warn!("Ignoring malformed REDmod {}", info_path.display());
+    }
+    info
+}
+
+/// Parse a CET mod's manifest for version.
+fn get_cet_version(mod_dir: &Path) -> Option<String> {
+    CET_MANIFESTS
+        .iter()
+        .find_map(|manifest| read_json_version(&mod_dir.join(manifest)))
+}
+
+/// Read the `version` string from a JSON file.
+fn read_json_version(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
     let json: serde_json::Value = serde_json::from_str(&content).ok()?;
     json["version"].as_str().map(String::from)
 }
@@ -95,46 +199,125 @@ fn get_redmod_version(mod_dir: &Path) -> Option<String> {
 /// Scans all mod locations and caches the result.
 ///
 /// This should be called once during plugin initialization.
-/// Subsequent calls will return the cached count without rescanning.
+/// Subsequent calls will return the cached count without rescanning;
+/// use [`rescan`] to refresh the list.
 ///
 /// # Returns
 ///
 /// The number of mods found, or an error if scanning failed.
 pub fn scan_and_cache() -> Result<usize> {
-    if let Some(cached) = CACHED_MODS.get() {
+    if let Some(cached) = get_cached() {
         return Ok(cached.len());
     }
 
-    let mods = scan_mods()?;
-    let count = mods.len();
-
-    // Store in cache (if another thread beat us, that's fine)
-    let _ = CACHED_MODS.set(mods);
-
-    Ok(count)
+    rescan()
 }
 
-/// Returns the cached mod list.
+/// Scans all mod locations again and replaces the cached list.
 ///
-/// # Panics
+/// Unchanged files are not re-hashed. If another scan is already running,
+/// returns the cached count without scanning.
 ///
-/// Panics if `scan_and_cache()` was not called first.
-pub fn get_cached() -> &'static ModList {
+/// # Returns
+///
+/// The number of mods found, or an error if scanning failed (the cached
+/// list is kept).
+pub fn rescan() -> Result<usize> {
+    if SCANNING.swap(true, Ordering::AcqRel) {
+        return Ok(get_cached().map_or(0, |mods| mods.len()));
+    }
+
+    let scanned = scan_mods();
+    let result = scanned.map(|(mods, conflicts)| {
+        if !conflicts.is_empty() {
+            warn!("Found {} conflicting archive installs", conflicts.len());
+        }
+        *CACHED_CONFLICTS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = conflicts;
+
+        let count = mods.len();
+        let previous = CACHED_MODS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(mods);
+        if let Some(previous) = previous
+            && previous.len() != count
+        {
+            info!("Mod list changed: {} -> {} mods", previous.len(), count);
+        }
+        count
+    });
+
+    SCANNING.store(false, Ordering::Release);
+    result
+}
+
+/// Starts a background thread that calls [`rescan`] every
+/// [`RESCAN_INTERVAL`]. Later calls do nothing.
+pub fn start_periodic_rescan() {
+    if RESCAN_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    let spawned = thread::Builder::new()
+        .name("ctd-mod-rescan".into())
+        .spawn(|| {
+            loop {
+                thread::sleep(RESCAN_INTERVAL);
+                if let Err(e) = rescan() {
+                    warn!("Failed to rescan mods: {}", e);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to start mod rescan thread: {}", e);
+    }
+}
+
+/// Rescans on a background thread, for callers that must not block.
+pub fn spawn_rescan() {
+    let spawned = thread::Builder::new()
+        .name("ctd-mod-rescan-once".into())
+        .spawn(|| {
+            if let Err(e) = rescan() {
+                warn!("Failed to rescan mods: {}", e);
+            }
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to start mod rescan: {}", e);
+    }
+}
+
+/// Returns a clone of the cached mod list, or `None` if not scanned yet.
+pub fn get_cached() -> Option<ModList> {
     CACHED_MODS
-        .get()
-        .expect("Mods not scanned yet - call scan_and_cache() first")
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
 }
 
 /// Returns a clone of the cached mod list, or an empty list if not scanned.
 pub fn get_cached_or_empty() -> ModList {
-    CACHED_MODS.get().cloned().unwrap_or_default()
+    get_cached().unwrap_or_default()
+}
+
+/// Returns the archive conflicts found by the latest scan.
+pub fn get_conflicts() -> Vec<ModConflict> {
+    CACHED_CONFLICTS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
 }
 
-/// Scans all mod locations and returns a ModList with fingerprints.
-fn scan_mods() -> Result<ModList> {
+/// Scans all mod locations and returns a ModList with fingerprints, plus
+/// the archive conflicts found along the way.
+fn scan_mods() -> Result<(ModList, Vec<ModConflict>)> {
     let game_dir = get_game_directory()?;
     let mut list = ModList::new();
     let mut index = 0u32;
+    let mut hasher = CachedHasher::open_default();
+    let mut archives = Vec::new();
 
     debug!("Scanning mods in game directory: {:?}", game_dir);
 
@@ -150,22 +333,27 @@ fn scan_mods() -> Result<ModList> {
 
         match mod_type {
             ModType::Archive => {
-                scan_archive_mods(&full_path, &mut list, &mut index);
+                scan_archive_mods(&full_path, &mut list, &mut index, &mut hasher);
+                archives.extend(list.iter().skip(count_before).map(|entry| ArchiveFile {
+                    path: format!("{}/{}", path, entry.name),
+                    hash: entry.file_hash.clone(),
+                }));
             }
             ModType::RedMod => {
-                scan_redmod_mods(&full_path, &mut list, &mut index);
+                scan_redmod_mods(&full_path, &mut list, &mut index, &mut hasher);
+                archives.extend(scan_redmod_archives(&full_path, path, &mut hasher));
             }
             ModType::Red4ext => {
-                scan_red4ext_mods(&full_path, &mut list, &mut index);
+                scan_red4ext_mods(&full_path, &mut list, &mut index, &mut hasher);
             }
             ModType::Cet => {
-                scan_cet_mods(&full_path, &mut list, &mut index);
+                scan_cet_mods(&full_path, &mut list, &mut index, &mut hasher);
             }
             ModType::Redscript => {
-                scan_redscript_mods(&full_path, &mut list, &mut index);
+                scan_redscript_mods(&full_path, &mut list, &mut index, &mut hasher);
             }
             ModType::TweakXL => {
-                scan_tweakxl_mods(&full_path, &mut list, &mut index);
+                scan_tweakxl_mods(&full_path, &mut list, &mut index, &mut hasher);
             }
         }
 
@@ -174,35 +362,137 @@ fn scan_mods() -> Result<ModList> {
             debug!("Found {} {:?} mods in {:?}", count_found, mod_type, path);
         }
     }
+    hasher.save();
 
-    Ok(list)
+    Ok((list, find_conflicts(&archives)))
 }
 
-/// Scans for Archive mods (`.archive` files).
-fn scan_archive_mods(path: &Path, list: &mut ModList, index: &mut u32) {
-    for entry in WalkDir::new(path).max_depth(1).into_iter().flatten() {
-        let file_path = entry.path();
-        if file_path.extension().is_some_and(|ext| ext == "archive") {
-            let name = entry.file_name().to_string_lossy().into_owned();
+/// An `.archive` file checked for conflicts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchiveFile {
+    /// Path relative to the game directory, with `/` separators.
+    path: String,
+    /// File hash, or [`UNHASHED`].
+    hash: String,
+}
 
-            // Compute hash and size
-            let (hash, size) = compute_file_hash(file_path).unwrap_or_else(|e| {
-                warn!("Failed to hash archive {}: {}", file_path.display(), e);
-                ("0000000000000000".to_string(), 0)
-            });
+impl ArchiveFile {
+    /// Lowercase file name; the game's archive lookup ignores case.
+    fn name(&self) -> String {
+        self.path
+            .rsplit('/')
+            .next()
+            .unwrap_or(&self.path)
+            .to_lowercase()
+    }
+}
 
-            let mod_entry = ModEntry::new(name, hash, size)
-                .with_index(*index)
-                .with_enabled(true);
+/// Hashes the `.archive` files inside REDmods (`<mod>/archives/`).
+///
+/// `relative` is `path` relative to the game directory, used to build the
+/// reported paths.
+fn scan_redmod_archives(
+    path: &Path,
+    relative: &str,
+    hasher: &mut CachedHasher,
+) -> Vec<ArchiveFile> {
+    let archives: Vec<PathBuf> = WalkDir::new(path)
+        .min_depth(3)
+        .max_depth(3)
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.into_path())
+        .filter(|file_path| {
+            file_path.extension().is_some_and(|ext| ext == "archive")
+                && file_path
+                    .parent()
+                    .and_then(Path::file_name)
+                    .is_some_and(|dir| dir == "archives")
+        })
+        .collect();
+
+    let hashed = ModList::fingerprint_parallel(&archives, DEFAULT_HASH_BUDGET, hasher);
+    archives
+        .iter()
+        .zip(hashed.iter())
+        .map(|(file_path, entry)| {
+            let inner = file_path.strip_prefix(path).unwrap_or(file_path);
+            ArchiveFile {
+                path: format!(
+                    "{}/{}",
+                    relative,
+                    inner.to_string_lossy().replace('\\', "/")
+                ),
+                hash: entry.file_hash.clone(),
+            }
+        })
+        .collect()
+}
 
-            list.push(mod_entry);
-            *index += 1;
+/// Finds archives installed twice: the same name in more than one place,
+/// or the same content under different names.
+///
+/// Returns at most [`MAX_CONFLICTS`] conflicts.
+fn find_conflicts(archives: &[ArchiveFile]) -> Vec<ModConflict> {
+    let mut by_name: BTreeMap<String, Vec<&ArchiveFile>> = BTreeMap::new();
+    let mut by_hash: BTreeMap<&str, Vec<&ArchiveFile>> = BTreeMap::new();
+    for archive in archives {
+        by_name.entry(archive.name()).or_default().push(archive);
+        if archive.hash != UNHASHED {
+            by_hash
+                .entry(archive.hash.as_str())
+                .or_default()
+                .push(archive);
         }
     }
+
+    let duplicate_names = by_name
+        .into_values()
+        .filter(|files| files.len() > 1)
+        .map(|files| (ConflictKind::DuplicateName, files));
+
+    // Same-name copies are already reported above
+    let duplicate_content = by_hash
+        .into_values()
+        .filter(|files| {
+            files
+                .iter()
+                .map(|file| file.name())
+                .collect::<BTreeSet<_>>()
+                .len()
+                > 1
+        })
+        .map(|files| (ConflictKind::DuplicateContent, files));
+
+    duplicate_names
+        .chain(duplicate_content)
+        .take(MAX_CONFLICTS)
+        .map(|(kind, files)| ModConflict {
+            kind,
+            files: files.into_iter().map(|file| file.path.clone()).collect(),
+        })
+        .collect()
+}
+
+/// Scans for Archive mods (`.archive` files).
+fn scan_archive_mods(path: &Path, list: &mut ModList, index: &mut u32, hasher: &mut CachedHasher) {
+    let archives: Vec<PathBuf> = WalkDir::new(path)
+        .max_depth(1)
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.into_path())
+        .filter(|file_path| file_path.extension().is_some_and(|ext| ext == "archive"))
+        .collect();
+
+    // Large setups have thousands of archives, so hash them on all cores
+    for hashed in ModList::fingerprint_parallel(&archives, DEFAULT_HASH_BUDGET, hasher) {
+        list.push(hashed.with_index(*index).with_enabled(true));
+        *index += 1;
+    }
 }
 
 /// Scans for REDmod mods (directories with `info.json`).
-fn scan_redmod_mods(path: &Path, list: &mut ModList, index: &mut u32) {
+fn scan_redmod_mods(path: &Path, list: &mut ModList, index: &mut u32, hasher: &mut CachedHasher) {
     // REDmod mods are directories containing info.json
     for entry in WalkDir::new(path).max_depth(2).into_iter().flatten() {
         let file_path = entry.path();
@@ -217,24 +507,35 @@ fn scan_redmod_mods(path: &Path, list: &mut ModList, index: &mut u32) {
             );
 
             // Compute hash from info.json file
-            let (hash, size) = compute_file_hash(file_path).unwrap_or_else(|e| {
+            let (hash, size) = hasher.hash(file_path).unwrap_or_else(|e| {
                 warn!(
                     "Failed to hash REDmod info.json {}: {}",
                     file_path.display(),
                     e
                 );
-                ("0000000000000000".to_string(), 0)
+                (UNHASHED.to_string(), 0)
             });
 
-            // Extract version from info.json
-            let version = get_redmod_version(mod_dir);
-
             let mut mod_entry = ModEntry::new(name, hash, size)
                 .with_index(*index)
                 .with_enabled(true);
 
-            if let Some(v) = version {
-                mod_entry = mod_entry.with_version(v);
+            // Attach the name and version the mod declares in info.json
+            if let Some(info) = read_redmod_info(mod_dir) {
+                if info.custom_sounds > 0 {
+                    debug!(
+                        "REDmod {:?} declares {} custom sounds",
+                        mod_name, info.custom_sounds
+                    );
+                }
+                if let Some(v) = info.version {
+                    mod_entry = mod_entry.with_version(v);
+                }
+                if let Some(declared) = info.name
+                    && declared != *mod_name.to_string_lossy()
+                {
+                    mod_entry = mod_entry.with_mod_name(declared);
+                }
             }
 
             list.push(mod_entry);
@@ -244,7 +545,7 @@ fn scan_redmod_mods(path: &Path, list: &mut ModList, index: &mut u32) {
 }
 
 /// Scans for RED4ext plugins (`.dll` files).
-fn scan_red4ext_mods(path: &Path, list: &mut ModList, index: &mut u32) {
+fn scan_red4ext_mods(path: &Path, list: &mut ModList, index: &mut u32, hasher: &mut CachedHasher) {
     // RED4ext plugins are DLLs, typically in subdirectories
     for entry in WalkDir::new(path).max_depth(2).into_iter().flatten() {
         let file_path = entry.path();
@@ -258,9 +559,9 @@ fn scan_red4ext_mods(path: &Path, list: &mut ModList, index: &mut u32) {
             let name = format!("{} {}", ModType::Red4ext.prefix(), filename);
 
             // Compute hash and size
-            let (hash, size) = compute_file_hash(file_path).unwrap_or_else(|e| {
+            let (hash, size) = hasher.hash(file_path).unwrap_or_else(|e| {
                 warn!("Failed to hash RED4ext DLL {}: {}", file_path.display(), e);
-                ("0000000000000000".to_string(), 0)
+                (UNHASHED.to_string(), 0)
             });
 
             // Extract DLL version
@@ -281,7 +582,7 @@ fn scan_red4ext_mods(path: &Path, list: &mut ModList, index: &mut u32) {
 }
 
 /// Scans for CET mods (directories with `init.lua`).
-fn scan_cet_mods(path: &Path, list: &mut ModList, index: &mut u32) {
+fn scan_cet_mods(path: &Path, list: &mut ModList, index: &mut u32, hasher: &mut CachedHasher) {
     // CET mods are directories containing init.lua
     for entry in WalkDir::new(path).max_depth(2).into_iter().flatten() {
         let file_path = entry.path();
@@ -292,15 +593,19 @@ fn scan_cet_mods(path: &Path, list: &mut ModList, index: &mut u32) {
             let name = format!("{} {}", ModType::Cet.prefix(), mod_name.to_string_lossy());
 
             // Compute hash from init.lua
-            let (hash, size) = compute_file_hash(file_path).unwrap_or_else(|e| {
+            let (hash, size) = hasher.hash(file_path).unwrap_or_else(|e| {
                 warn!("Failed to hash CET init.lua {}: {}", file_path.display(), e);
-                ("0000000000000000".to_string(), 0)
+                (UNHASHED.to_string(), 0)
             });
 
-            let mod_entry = ModEntry::new(name, hash, size)
+            let mut mod_entry = ModEntry::new(name, hash, size)
                 .with_index(*index)
                 .with_enabled(true);
 
+            if let Some(v) = get_cet_version(mod_dir) {
+                mod_entry = mod_entry.with_version(v);
+            }
+
             list.push(mod_entry);
             *index += 1;
         }
@@ -308,7 +613,12 @@ fn scan_cet_mods(path: &Path, list: &mut ModList, index: &mut u32) {
 }
 
 /// Scans for Redscript mods (`.reds` files).
-fn scan_redscript_mods(path: &Path, list: &mut ModList, index: &mut u32) {
+fn scan_redscript_mods(
+    path: &Path,
+    list: &mut ModList,
+    index: &mut u32,
+    hasher: &mut CachedHasher,
+) {
     // Collect unique script directories/files
     let mut seen_mods = std::collections::HashSet::new();
 
@@ -335,9 +645,9 @@ fn scan_redscript_mods(path: &Path, list: &mut ModList, index: &mut u32) {
                 let name = format!("{} {}", ModType::Redscript.prefix(), mod_name);
 
                 // Compute hash from the .reds file
-                let (hash, size) = compute_file_hash(file_path).unwrap_or_else(|e| {
+                let (hash, size) = hasher.hash(file_path).unwrap_or_else(|e| {
                     warn!("Failed to hash Redscript {}: {}", file_path.display(), e);
-                    ("0000000000000000".to_string(), 0)
+                    (UNHASHED.to_string(), 0)
                 });
 
                 let mod_entry = ModEntry::new(name, hash, size)
@@ -352,7 +662,7 @@ fn scan_redscript_mods(path: &Path, list: &mut ModList, index: &mut u32) {
 }
 
 /// Scans for TweakXL mods (`.yaml`/`.yml` files).
-fn scan_tweakxl_mods(path: &Path, list: &mut ModList, index: &mut u32) {
+fn scan_tweakxl_mods(path: &Path, list: &mut ModList, index: &mut u32, hasher: &mut CachedHasher) {
     // Collect unique tweak directories/files
     let mut seen_mods = std::collections::HashSet::new();
 
@@ -381,9 +691,9 @@ fn scan_tweakxl_mods(path: &Path, list: &mut ModList, index: &mut u32) {
                 let name = format!("{} {}", ModType::TweakXL.prefix(), mod_name);
 
                 // Compute hash from the yaml file
-                let (hash, size) = compute_file_hash(file_path).unwrap_or_else(|e| {
+                let (hash, size) = hasher.hash(file_path).unwrap_or_else(|e| {
                     warn!("Failed to hash TweakXL {}: {}", file_path.display(), e);
-                    ("0000000000000000".to_string(), 0)
+                    (UNHASHED.to_string(), 0)
                 });
 
                 let mod_entry = ModEntry::new(name, hash, size)
@@ -397,6 +707,28 @@ fn scan_tweakxl_mods(path: &Path, list: &mut ModList, index: &mut u32) {
     }
 }
 
+/// Returns the installed frameworks and their DLL versions, by name.
+///
+/// A framework whose DLL has no version resource is listed as "unknown".
+pub fn detect_frameworks() -> BTreeMap<String, String> {
+    get_game_directory()
+        .map(|game_dir| frameworks_in(&game_dir))
+        .unwrap_or_default()
+}
+
+/// Finds the [`FRAMEWORKS`] installed under `game_dir`.
+fn frameworks_in(game_dir: &Path) -> BTreeMap<String, String> {
+    FRAMEWORKS
+        .iter()
+        .filter(|(_, dll)| game_dir.join(dll).is_file())
+        .map(|(name, dll)| {
+            let version =
+                get_dll_version(&game_dir.join(dll)).unwrap_or_else(|_| "unknown".to_string());
+            (name.to_string(), version)
+        })
+        .collect()
+}
+
 /// Gets the game directory from the current DLL location.
 ///
 /// The plugin DLL is expected to be at:
@@ -459,6 +791,11 @@ pub(crate) fn get_game_directory_path() -> Option<PathBuf> {
 mod tests {
     use super::*;
 
+    /// Parse REDmod info.json for version.
+    fn get_redmod_version(mod_dir: &Path) -> Option<String> {
+        read_redmod_info(mod_dir).and_then(|info| info.version)
+    }
+
     #[test]
     fn test_mod_type_prefix() {
         assert_eq!(ModType::Archive.prefix(), "");
@@ -483,6 +820,99 @@ mod tests {
         assert!(types.contains(&ModType::TweakXL));
     }
 
+    #[test]
+    fn test_frameworks_in_lists_installed_frameworks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(frameworks_in(temp_dir.path()).is_empty());
+
+        let archive_xl = temp_dir.path().join("red4ext/plugins/ArchiveXL");
+        std::fs::create_dir_all(&archive_xl).unwrap();
+        // Not a real PE file, so it has no version resource
+        std::fs::write(archive_xl.join("ArchiveXL.dll"), b"MZ").unwrap();
+
+        let frameworks = frameworks_in(temp_dir.path());
+        assert_eq!(frameworks.len(), 1);
+        assert_eq!(frameworks["ArchiveXL"], "unknown");
+    }
+
+    fn archive(path: &str, hash: &str) -> ArchiveFile {
+        ArchiveFile {
+            path: path.to_string(),
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts_reports_duplicate_names() {
+        let archives = [
+            archive("archive/pc/mod/Hair.archive", "1111111111111111"),
+            archive("archive/pc/mod/other.archive", "2222222222222222"),
+            archive("mods/HairMod/archives/hair.archive", "3333333333333333"),
+        ];
+
+        let conflicts = find_conflicts(&archives);
+        assert_eq!(
+            conflicts,
+            vec![ModConflict {
+                kind: ConflictKind::DuplicateName,
+                files: vec![
+                    "archive/pc/mod/Hair.archive".to_string(),
+                    "mods/HairMod/archives/hair.archive".to_string(),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_conflicts_reports_duplicate_content() {
+        let archives = [
+            archive("archive/pc/mod/hair.archive", "1111111111111111"),
+            archive("archive/pc/mod/hair_v2.archive", "1111111111111111"),
+            archive("archive/pc/mod/a.archive", UNHASHED),
+            archive("archive/pc/mod/b.archive", UNHASHED),
+        ];
+
+        let conflicts = find_conflicts(&archives);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::DuplicateContent);
+        assert_eq!(
+            conflicts[0].files,
+            [
+                "archive/pc/mod/hair.archive",
+                "archive/pc/mod/hair_v2.archive"
+            ]
+        );
+
+        // A same-name copy is a name conflict, not also a content one
+        let copies = [
+            archive("archive/pc/mod/hair.archive", "1111111111111111"),
+            archive("mods/HairMod/archives/hair.archive", "1111111111111111"),
+        ];
+        let conflicts = find_conflicts(&copies);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::DuplicateName);
+    }
+
+    #[test]
+    fn test_scan_redmod_archives_finds_mod_archives() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archives = temp_dir.path().join("HairMod").join("archives");
+        std::fs::create_dir_all(&archives).unwrap();
+        std::fs::write(archives.join("hair.archive"), b"RDAR").unwrap();
+        std::fs::write(archives.join("readme.txt"), b"notes").unwrap();
+        std::fs::write(
+            temp_dir.path().join("HairMod").join("stray.archive"),
+            b"RDAR",
+        )
+        .unwrap();
+
+        let mut hasher = CachedHasher::open(temp_dir.path().join("hash-cache.json"));
+        let found = scan_redmod_archives(temp_dir.path(), "mods", &mut hasher);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "mods/HairMod/archives/hair.archive");
+        assert_ne!(found[0].hash, UNHASHED);
+    }
+
     #[test]
     fn test_get_cached_or_empty() {
         // Should return empty if not scanned
@@ -492,6 +922,13 @@ mod tests {
         let _ = result.len();
     }
 
+    #[test]
+    fn test_rescan_replaces_cache() {
+        let count = rescan().unwrap();
+        assert_eq!(get_cached().map(|mods| mods.len()), Some(count));
+        assert_eq!(scan_and_cache().unwrap(), count);
+    }
+
     #[test]
     fn test_get_redmod_version_parses_json() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -519,6 +956,92 @@ mod tests {
         assert_eq!(version, None);
     }
 
+    #[test]
+    fn test_get_cet_version_reads_manifests() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(get_cet_version(temp_dir.path()), None);
+
+        let manifest = temp_dir.path().join("manifest.json");
+        std::fs::write(&manifest, r#"{"version": "2.0.0"}"#).unwrap();
+        assert_eq!(get_cet_version(temp_dir.path()), Some("2.0.0".to_string()));
+
+        // metadata.json wins when a mod ships both
+        let metadata = temp_dir.path().join("metadata.json");
+        std::fs::write(&metadata, r#"{"name": "Mod", "version": "2.1.0"}"#).unwrap();
+        assert_eq!(get_cet_version(temp_dir.path()), Some("2.1.0".to_string()));
+    }
+
+    #[test]
+    fn test_scan_cet_mods_reads_versions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mod_dir = temp_dir.path().join("SimpleMenu");
+        std::fs::create_dir(&mod_dir).unwrap();
+        std::fs::write(mod_dir.join("init.lua"), "registerForEvent()").unwrap();
+        std::fs::write(mod_dir.join("metadata.json"), r#"{"version": "1.4"}"#).unwrap();
+
+        let mut list = ModList::new();
+        let mut index = 0;
+        let mut hasher = CachedHasher::open(temp_dir.path().join("hash-cache.json"));
+        scan_cet_mods(temp_dir.path(), &mut list, &mut index, &mut hasher);
+
+        assert_eq!(list.len(), 1);
+        let entry = &list.0[0];
+        assert_eq!(entry.name, "[CET] SimpleMenu");
+        assert_eq!(entry.version.as_deref(), Some("1.4"));
+        assert_eq!(entry.file_size, 18);
+        assert_ne!(entry.file_hash, UNHASHED);
+        assert_eq!(index, 1);
+    }
+
+    #[test]
+    fn test_redmod_info_parses_metadata() {
+        let info = RedmodInfo::parse(
+            r#"{"name": "Better Vehicle Sounds", "version": "2.1", "customSounds": [{"name": "a"}, {"name": "b"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(info.name.as_deref(), Some("Better Vehicle Sounds"));
+        assert_eq!(info.version.as_deref(), Some("2.1"));
+        assert_eq!(info.custom_sounds, 2);
+
+        // Numeric versions and mistyped fields are tolerated
+        let info =
+            RedmodInfo::parse(r#"{"version": 3, "name": ["x"], "customSounds": {}}"#).unwrap();
+        assert_eq!(info.version.as_deref(), Some("3"));
+        assert_eq!(info.name, None);
+        assert_eq!(info.custom_sounds, 0);
+
+        assert_eq!(RedmodInfo::parse("[1, 2]"), None);
+        assert_eq!(RedmodInfo::parse("{ truncated"), None);
+    }
+
+    #[test]
+    fn test_scan_redmod_mods_uses_declared_metadata() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mod_dir = temp_dir.path().join("bvs");
+        std::fs::create_dir(&mod_dir).unwrap();
+        std::fs::write(
+            mod_dir.join("info.json"),
+            r#"{"name": "Better Vehicle Sounds", "version": "2.1"}"#,
+        )
+        .unwrap();
+        let broken_dir = temp_dir.path().join("broken");
+        std::fs::create_dir(&broken_dir).unwrap();
+        std::fs::write(broken_dir.join("info.json"), "{ not json").unwrap();
+
+        let mut list = ModList::new();
+        let mut index = 0;
+        let mut hasher = CachedHasher::open(temp_dir.path().join("hash-cache.json"));
+        scan_redmod_mods(temp_dir.path(), &mut list, &mut index, &mut hasher);
+
+        assert_eq!(list.len(), 2);
+        let bvs = list.iter().find(|e| e.name == "[REDmod] bvs").unwrap();
+        assert_eq!(bvs.version.as_deref(), Some("2.1"));
+        assert_eq!(bvs.mod_name.as_deref(), Some("Better Vehicle Sounds"));
+        let broken = list.iter().find(|e| e.name == "[REDmod] broken").unwrap();
+        assert_eq!(broken.version, None);
+        assert_eq!(broken.mod_name, None);
+    }
+
     #[test]
     fn test_get_redmod_version_missing_version_field() {
         let temp_dir = tempfile::tempdir().unwrap();
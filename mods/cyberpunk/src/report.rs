@@ -3,14 +3,32 @@
 //! This module handles creating crash reports from captured crash data
 //! and submitting them to the CTD API.
 
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
-
-use ctd_core::api_client::ApiClient;
-use ctd_core::crash_report::CreateCrashReport;
+use std::thread::JoinHandle;
+
+use ctd_capture::handler::CrashData;
+use ctd_core::api_client;
+use ctd_core::blame;
+use ctd_core::crash_arena;
+use ctd_core::crash_report::{Attachment, CreateCrashReport, ReportType, manual_report_notes};
+use ctd_core::detours;
+use ctd_core::exception_tally;
+use ctd_core::file_hash;
+use ctd_core::graphics;
+use ctd_core::hardware_events;
+use ctd_core::known_bad;
+use ctd_core::log_tail::{self, DEFAULT_TAIL_LINES};
+use ctd_core::memory_map;
+use ctd_core::modpack;
+use ctd_core::session::{self, SessionReport};
+use ctd_core::submit;
+use ctd_core::throttle::Throttle;
+use ctd_core::version::{self, get_dll_version};
+use ctd_core::watchdog::{self, Hang};
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
-use crate::crash_handler::CrashData;
 use crate::mod_scanner;
 
 /// Errors that can occur during report submission.
@@ -20,10 +38,6 @@ pub enum ReportError {
     #[error("Failed to build crash report: {0}")]
     BuildFailed(String),
 
-    /// Failed to create the API client.
-    #[error("Failed to create API client: {0}")]
-    ClientCreation(String),
-
     /// Failed to submit the report.
     #[error("Failed to submit crash report: {0}")]
     Submission(String),
@@ -40,7 +54,7 @@ pub type Result<T> = std::result::Result<T, ReportError>;
 static SUBMISSION_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
 
 /// Game ID for Cyberpunk 2077 crash reports.
-const GAME_ID: &str = "cyberpunk-2077";
+pub(crate) const GAME_ID: &str = "cyberpunk-2077";
 
 /// Submits a crash report asynchronously (fire-and-forget).
 ///
@@ -51,20 +65,33 @@ const GAME_ID: &str = "cyberpunk-2077";
 /// # Arguments
 ///
 /// * `crash_data` - The captured crash data to report.
-pub fn submit_async(crash_data: CrashData) {
+///
+/// # Returns
+///
+/// The submission thread, or `None` if the call was ignored.
+pub fn submit_async(crash_data: CrashData) -> Option<JoinHandle<()>> {
     // Check if we're already submitting
     if SUBMISSION_IN_PROGRESS
         .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
         .is_err()
     {
         warn!("Crash report submission already in progress, skipping duplicate");
-        return;
+        return None;
     }
 
     // Spawn a thread for submission
     // We use a thread instead of tokio::spawn because we may not have
     // a runtime active, and we want to guarantee this doesn't block
-    std::thread::spawn(move || {
+    let submission = std::thread::spawn(move || {
+        // Honor [submission] sample_rate and max_reports_per_hour
+        let verdict = Throttle::load().admit();
+        if !verdict.is_allowed() {
+            info!("Crash report not submitted: {}", verdict);
+            crash_arena::discard_record();
+            SUBMISSION_IN_PROGRESS.store(false, Ordering::SeqCst);
+            return;
+        }
+
         let result = submit_sync(crash_data);
 
         // Reset the in-progress flag
@@ -80,11 +107,12 @@ pub fn submit_async(crash_data: CrashData) {
             }
         }
     });
+    Some(submission)
 }
 
 /// Submits a crash report synchronously.
 ///
-/// This creates a tokio runtime to execute the async API call.
+/// Builds the report from crash data and sends it with [`send_report`].
 ///
 /// # Returns
 ///
@@ -100,26 +128,67 @@ fn submit_sync(crash_data: CrashData) -> Result<String> {
 
     // Build the crash report
     let report = build_report(&crash_data, mod_list)?;
+    send_report(&report)
+}
 
-    // Create a single-threaded runtime for the API call
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()
-        .map_err(|e| ReportError::Submission(format!("Failed to create runtime: {}", e)))?;
+/// Submits a hang report from the watchdog's monitor thread.
+pub fn submit_hang(hang: Hang) {
+    // Honor [submission] sample_rate and max_reports_per_hour
+    let verdict = Throttle::load().admit();
+    if !verdict.is_allowed() {
+        info!("Hang report not submitted: {}", verdict);
+        return;
+    }
 
-    // Execute the API call
-    let response = rt.block_on(async {
-        let client = ApiClient::from_config()
-            .or_else(|_| ApiClient::with_defaults())
-            .map_err(|e| ReportError::ClientCreation(e.to_string()))?;
+    let mod_list = mod_scanner::get_cached_or_empty();
+    let result = build_hang_report(&hang, mod_list).and_then(|report| send_report(&report));
+    match result {
+        Ok(response_id) => info!("Hang report submitted successfully: {}", response_id),
+        Err(e) => error!("Failed to submit hang report: {}", e),
+    }
+}
 
-        client
-            .submit_crash_report(&report)
-            .await
-            .map_err(|e| ReportError::Submission(e.to_string()))
-    })?;
+/// Files a report the player asked for, without a crash.
+///
+/// The report carries every thread's stack and is sent on a new thread.
+///
+/// # Errors
+///
+/// Returns `ReportError::BuildFailed` if `notes` is empty or too long.
+pub fn submit_manual(notes: &str) -> Result<()> {
+    let notes = manual_report_notes(notes)
+        .map_err(|e| ReportError::BuildFailed(e.to_string()))?
+        .to_string();
 
-    Ok(response.id)
+    std::thread::spawn(move || {
+        // Counts toward max_reports_per_hour, but the player asked, so no sampling
+        let verdict = Throttle::load().admit_requested();
+        if !verdict.is_allowed() {
+            info!("Manual report not submitted: {}", verdict);
+            return;
+        }
+
+        let mod_list = mod_scanner::get_cached_or_empty();
+        let stack_trace = watchdog::capture_all_threads();
+        let result = build_manual_report(&notes, &stack_trace, mod_list)
+            .and_then(|report| send_report(&report));
+        match result {
+            Ok(response_id) => info!("Manual report submitted successfully: {}", response_id),
+            Err(e) => error!("Failed to submit manual report: {}", e),
+        }
+    });
+    Ok(())
+}
+
+/// Submits a built report, queueing it in the spool if submission fails.
+///
+/// # Returns
+///
+/// The report ID on success, or an error on failure.
+fn send_report(report: &CreateCrashReport) -> Result<String> {
+    submit::submit_report(report)
+        .map(|response| response.id)
+        .map_err(|e| ReportError::Submission(e.to_string()))
 }
 
 /// Builds a crash report from crash data.
@@ -127,6 +196,11 @@ fn build_report(
     crash_data: &CrashData,
     mod_list: ctd_core::load_order::ModList,
 ) -> Result<CreateCrashReport> {
+    // Work out which mod shipped the faulting DLL (RED4ext plugins match by hash)
+    let suspected_mod = crash_data.faulting_module.as_deref().and_then(|module| {
+        blame::suspected_mod(Path::new(module), &mod_list, &blame::installed_mods())
+    });
+
     let mut builder = CreateCrashReport::builder()
         .game_id(GAME_ID)
         .game_version(get_game_version())
@@ -134,11 +208,49 @@ fn build_report(
         .exception_code(format!("0x{:08X}", crash_data.exception_code))
         .exception_address(format!("0x{:016X}", crash_data.exception_address))
         .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
+        .handled_exceptions(exception_tally::snapshot())
+        .hardware_events(hardware_events::recent())
+        .probable_objects(crash_data.probable_objects.clone())
+        .exception_chain(crash_data.exception_chain.clone())
+        .frameworks(mod_scanner::detect_frameworks())
+        .conflicts(mod_scanner::get_conflicts())
+        .attachments(script_log_attachments())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(GAME_ID))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .recent_frame_times()
         .crashed_now();
 
     // Add faulting module if available
     if let Some(ref module) = crash_data.faulting_module {
-        builder = builder.faulting_module(module);
+        builder = builder
+            .faulting_module(module)
+            .hooked_functions(detours::scan(module));
+    }
+
+    if let Some(ref text) = crash_data.disassembly {
+        builder = builder.disassembly(text);
+    }
+
+    if crash_data.faulting_module.is_none()
+        && let Some(region) = memory_map::address_region(crash_data.exception_address)
+    {
+        builder = builder.address_region(region);
+    }
+
+    builder = builder.exception_parameters(&crash_data.exception_parameters);
+    if let Some(ref error) = crash_data.in_page_error {
+        builder = builder.in_page_error(error.clone());
+    }
+
+    if let Some(suspect) = suspected_mod {
+        info!("Suspected mod: {}", suspect);
+        builder = builder.suspected_mod(suspect);
     }
 
     // Add RED4ext version if we can detect it
@@ -147,7 +259,130 @@ fn build_report(
     }
 
     // Add OS version
-    if let Some(os_version) = get_os_version() {
+    if let Some(os_version) = version::os_version() {
+        builder = builder.os_version(os_version);
+    }
+
+    builder
+        .build()
+        .map_err(|e| ReportError::BuildFailed(e.to_string()))
+}
+
+/// Script and loader logs, relative to the game directory, whose tails are
+/// attached to crash reports.
+const SCRIPT_LOGS: &[&str] = &[
+    "bin/x64/plugins/cyber_engine_tweaks/cyber_engine_tweaks.log",
+    "r6/logs/redscript_rCURRENT.log",
+    "red4ext/logs/red4ext.log",
+];
+
+/// Reads the last lines of the CET, redscript, and RED4ext logs.
+///
+/// Script errors logged just before a crash are often its real cause.
+/// Missing or empty logs are skipped.
+fn script_log_attachments() -> Vec<Attachment> {
+    let Some(game_dir) = mod_scanner::get_game_directory_path() else {
+        return Vec::new();
+    };
+    SCRIPT_LOGS
+        .iter()
+        .filter_map(|log| log_tail::attach(&game_dir.join(log), DEFAULT_TAIL_LINES))
+        .collect()
+}
+
+/// Submits a summary of the session, if `[session] enabled` is set.
+///
+/// Called when the game shuts down cleanly. Blocks until the summary is sent.
+pub fn submit_session() {
+    session::submit_on_exit(|| {
+        let summary = SessionReport::collect(
+            GAME_ID,
+            &get_game_version(),
+            mod_scanner::get_cached_or_empty(),
+        );
+        match get_red4ext_version() {
+            Some(version) => summary.script_extender_version(version),
+            None => summary,
+        }
+    });
+}
+
+/// Builds a hang report from the watchdog's capture.
+fn build_hang_report(
+    hang: &Hang,
+    mod_list: ctd_core::load_order::ModList,
+) -> Result<CreateCrashReport> {
+    let mut builder = CreateCrashReport::builder()
+        .game_id(GAME_ID)
+        .report_type(ReportType::Hang)
+        .game_version(get_game_version())
+        .stack_trace(&hang.stack_trace)
+        .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
+        .notes(format!(
+            "Game thread stopped responding for {} seconds",
+            hang.stalled_for.as_secs()
+        ))
+        .handled_exceptions(exception_tally::snapshot())
+        .hardware_events(hardware_events::recent())
+        .frameworks(mod_scanner::detect_frameworks())
+        .conflicts(mod_scanner::get_conflicts())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(GAME_ID))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .recent_frame_times()
+        .crashed_now();
+
+    if let Some(version) = get_red4ext_version() {
+        builder = builder.script_extender_version(version);
+    }
+
+    if let Some(os_version) = version::os_version() {
+        builder = builder.os_version(os_version);
+    }
+
+    builder
+        .build()
+        .map_err(|e| ReportError::BuildFailed(e.to_string()))
+}
+
+/// Builds a report the player filed, with their description as the notes.
+fn build_manual_report(
+    notes: &str,
+    stack_trace: &str,
+    mod_list: ctd_core::load_order::ModList,
+) -> Result<CreateCrashReport> {
+    let mut builder = CreateCrashReport::builder()
+        .game_id(GAME_ID)
+        .report_type(ReportType::Manual)
+        .game_version(get_game_version())
+        .stack_trace(stack_trace)
+        .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
+        .notes(notes)
+        .handled_exceptions(exception_tally::snapshot())
+        .hardware_events(hardware_events::recent())
+        .frameworks(mod_scanner::detect_frameworks())
+        .conflicts(mod_scanner::get_conflicts())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(GAME_ID))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .recent_frame_times()
+        .crashed_now();
+
+    if let Some(version) = get_red4ext_version() {
+        builder = builder.script_extender_version(version);
+    }
+
+    if let Some(os_version) = version::os_version() {
         builder = builder.os_version(os_version);
     }
 
@@ -156,21 +391,86 @@ fn build_report(
         .map_err(|e| ReportError::BuildFailed(e.to_string()))
 }
 
+/// Steam app ID of Cyberpunk 2077.
+const STEAM_APP_ID: u32 = 1091500;
+
 /// Gets the Cyberpunk 2077 game version.
 ///
-/// Attempts to read the version from the game executable.
-/// Falls back to "unknown" if detection fails.
-fn get_game_version() -> String {
+/// Attempts to read the version from the game executable, then from the
+/// GOG or Steam manifest. Falls back to "unknown" if detection fails.
+pub(crate) fn get_game_version() -> String {
     // Try to detect version from game files
     #[cfg(windows)]
     if let Some(version) = detect_game_version_windows() {
         return version;
     }
 
+    // The exe may be locked or moved; the store manifests still know
+    if let Some(version) = mod_scanner::get_game_directory_path()
+        .and_then(|dir| detect_game_version_from_manifests(&dir))
+    {
+        return version;
+    }
+
     // Fallback to unknown
     "unknown".to_string()
 }
 
+/// Reads the game version from GOG's `goggame-*.info`, or the build ID
+/// from Steam's app manifest as "build <id>".
+fn detect_game_version_from_manifests(game_dir: &Path) -> Option<String> {
+    gog_game_version(game_dir)
+        .or_else(|| steam_build_id(game_dir).map(|id| format!("build {}", id)))
+}
+
+/// Reads `versionName` from the `goggame-<id>.info` in the game root.
+fn gog_game_version(game_dir: &Path) -> Option<String> {
+    std::fs::read_dir(game_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("goggame-") && name.ends_with(".info"))
+        })
+        .find_map(|path| parse_gog_info_version(&std::fs::read_to_string(path).ok()?))
+}
+
+/// Parses the game version out of a `goggame-*.info` file.
+fn parse_gog_info_version(content: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    normalize_version(json.get("versionName")?.as_str()?)
+}
+
+/// Reads the build ID from `steamapps/appmanifest_1091500.acf`, two levels
+/// above a game in `steamapps/common/Cyberpunk 2077`.
+fn steam_build_id(game_dir: &Path) -> Option<String> {
+    let steamapps = game_dir.parent()?.parent()?;
+    let manifest = steamapps.join(format!("appmanifest_{}.acf", STEAM_APP_ID));
+    parse_appmanifest_build_id(&std::fs::read_to_string(manifest).ok()?)
+}
+
+/// Parses the `"buildid"` value out of a Steam app manifest.
+fn parse_appmanifest_build_id(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut fields = line.split('"').filter(|field| !field.trim().is_empty());
+        let key = fields.next()?;
+        let value = fields.next()?;
+        (key.eq_ignore_ascii_case("buildid") && value.chars().all(|c| c.is_ascii_digit()))
+            .then(|| value.to_string())
+    })
+}
+
+/// Normalizes a store version like "v2.21" to the dotted "2.21", or returns
+/// `None` if it isn't a version.
+fn normalize_version(raw: &str) -> Option<String> {
+    let version = raw.trim().trim_start_matches(['v', 'V']);
+    let valid = version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_digit() || c == '.');
+    valid.then(|| version.to_string())
+}
+
 /// Attempts to detect the game version on Windows.
 #[cfg(windows)]
 fn detect_game_version_windows() -> Option<String> {
@@ -256,40 +556,29 @@ fn detect_game_version_windows() -> Option<String> {
 
 /// Gets the RED4ext version if available.
 ///
-/// RED4ext doesn't expose a Rust-friendly version API yet,
-/// so this returns None for now.
+/// Reads the version resource of `red4ext/RED4ext.dll`, falling back to the
+/// version RED4ext writes at the top of `red4ext/logs/red4ext.log`.
 fn get_red4ext_version() -> Option<String> {
-    // RED4ext doesn't expose a Rust-friendly version API yet
-    // This would need to be implemented using the SDK
-    None
-}
-
-/// Gets the Windows version string.
-#[cfg(windows)]
-fn get_os_version() -> Option<String> {
-    use windows::Win32::System::SystemInformation::{GetVersionExW, OSVERSIONINFOW};
-
-    let mut info = OSVERSIONINFOW::default();
-    info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOW>() as u32;
-
-    // SAFETY: GetVersionExW is safe with a properly sized OSVERSIONINFOW
-    #[allow(deprecated)]
-    let result = unsafe { GetVersionExW(&mut info) };
-
-    if result.is_ok() {
-        Some(format!(
-            "Windows {}.{}.{}",
-            info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber
-        ))
-    } else {
-        None
-    }
+    let game_dir = mod_scanner::get_game_directory_path()?;
+    let red4ext_dir = game_dir.join("red4ext");
+
+    get_dll_version(&red4ext_dir.join("RED4ext.dll"))
+        .ok()
+        .or_else(|| {
+            let log = std::fs::read_to_string(red4ext_dir.join("logs/red4ext.log")).ok()?;
+            parse_red4ext_log_version(&log)
+        })
 }
 
-/// Non-Windows stub.
-#[cfg(not(windows))]
-fn get_os_version() -> Option<String> {
-    None
+/// Finds the version in RED4ext's startup line, e.g.
+/// `[info] RED4ext (v1.25.1) is initializing...`.
+fn parse_red4ext_log_version(log: &str) -> Option<String> {
+    log.lines().take(50).find_map(|line| {
+        let rest = &line[line.find("RED4ext (v")? + "RED4ext (v".len()..];
+        let version = &rest[..rest.find(')')?];
+        let valid = !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.');
+        valid.then(|| version.to_string())
+    })
 }
 
 #[cfg(test)]
@@ -304,6 +593,12 @@ mod tests {
             exception_address: 0x7FF712345678,
             stack_trace: "test stack trace".to_string(),
             faulting_module: Some("test.dll".to_string()),
+            disassembly: None,
+            registers: Vec::new(),
+            probable_objects: Vec::new(),
+            exception_chain: Vec::new(),
+            in_page_error: None,
+            exception_parameters: Vec::new(),
         };
 
         let mod_list = ModList::new();
@@ -319,6 +614,98 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_build_hang_report() {
+        let hang = Hang {
+            stalled_for: std::time::Duration::from_secs(60),
+            stack_trace: "Thread 1234 (game thread):\n[0] test.dll+0x10 (0x0000000180001010)\n"
+                .to_string(),
+        };
+
+        let report = build_hang_report(&hang, ModList::new()).unwrap();
+        assert_eq!(report.report_type, ReportType::Hang);
+        assert_eq!(report.exception_code, None);
+        assert!(report.notes.unwrap().contains("60 seconds"));
+    }
+
+    #[test]
+    fn test_parse_gog_info_version() {
+        let info = r#"{"buildId": "58012345678901234", "gameId": "1423049311", "name": "Cyberpunk 2077", "version": 1, "versionName": "v2.21"}"#;
+        assert_eq!(parse_gog_info_version(info), Some("2.21".to_string()));
+        assert_eq!(parse_gog_info_version(r#"{"version": 1}"#), None);
+        assert_eq!(parse_gog_info_version(r#"{"versionName": "latest"}"#), None);
+        assert_eq!(parse_gog_info_version("not json"), None);
+    }
+
+    #[test]
+    fn test_parse_appmanifest_build_id() {
+        let manifest =
+            "\"AppState\"\n{\n\t\"appid\"\t\t\"1091500\"\n\t\"buildid\"\t\t\"14981234\"\n}\n";
+        assert_eq!(
+            parse_appmanifest_build_id(manifest),
+            Some("14981234".to_string())
+        );
+        assert_eq!(parse_appmanifest_build_id("\"appid\" \"1091500\""), None);
+    }
+
+    #[test]
+    fn test_detect_game_version_from_manifests() {
+        let library = tempfile::tempdir().unwrap();
+        let game_dir = library.path().join("common").join("Cyberpunk 2077");
+        std::fs::create_dir_all(&game_dir).unwrap();
+        assert_eq!(detect_game_version_from_manifests(&game_dir), None);
+
+        std::fs::write(
+            library.path().join("appmanifest_1091500.acf"),
+            "\"AppState\"\n{\n\t\"buildid\"\t\t\"14981234\"\n}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_game_version_from_manifests(&game_dir),
+            Some("build 14981234".to_string())
+        );
+
+        // GOG's info file wins: it names the actual version
+        std::fs::write(
+            game_dir.join("goggame-1423049311.info"),
+            r#"{"versionName": "2.21"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            detect_game_version_from_manifests(&game_dir),
+            Some("2.21".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_red4ext_log_version() {
+        let log = "[2025-01-10 18:02:11.123] [RED4ext] [info] RED4ext (v1.25.1) is initializing...\n\
+                   [2025-01-10 18:02:11.130] [RED4ext] [info] Game patch: 2.21\n";
+        assert_eq!(parse_red4ext_log_version(log), Some("1.25.1".to_string()));
+
+        assert_eq!(parse_red4ext_log_version("RED4ext (vnext) loaded"), None);
+        assert_eq!(parse_red4ext_log_version("RED4ext (v1.2"), None);
+        assert_eq!(parse_red4ext_log_version(""), None);
+    }
+
+    #[test]
+    fn test_build_manual_report() {
+        let report = build_manual_report(
+            "Save won't load past the main menu",
+            "Thread 1234 (game thread):\n[0] test.dll+0x10 (0x0000000180001010)\n",
+            ModList::new(),
+        )
+        .unwrap();
+        assert_eq!(report.report_type, ReportType::Manual);
+        assert_eq!(report.exception_code, None);
+        assert_eq!(
+            report.notes.as_deref(),
+            Some("Save won't load past the main menu")
+        );
+
+        assert!(submit_manual("   ").is_err());
+    }
+
     #[test]
     fn test_game_id_constant() {
         assert_eq!(GAME_ID, "cyberpunk-2077");
@@ -8,9 +8,18 @@
 
 #include <Windows.h>
 
+#include <algorithm>
+#include <cstring>
+#include <stdexcept>
+
 #include "ctd-fallout3/src/lib.rs.h"  // CXX-generated Rust interface
 #include "bridge.hpp"
-#include "veh.hpp"
+
+// The C API for other mods, defined by ctd.h on top of the bridge
+#define CTD_API __declspec(dllexport)
+#define CTD_NO_LOADER
+#define CTD_DEFINE_EXPORTS
+#include "ctd.h"
 
 // FOSE interface types (minimal definitions for scaffolding)
 // Full definitions require FOSE SDK headers
@@ -47,15 +56,61 @@ extern "C" __declspec(dllexport) bool FOSEPlugin_Query(
 
 // Plugin load - called by FOSE after query succeeds
 extern "C" __declspec(dllexport) bool FOSEPlugin_Load(FOSEInterface* fose) {
-    // Register VEH handler for crash capture
-    ctd::register_veh_handler();
+    // Register the crash handler
+    ctd::register_handler();
 
     // Initialize Rust side
     ctd::init();
 
+    // Without the full SDK there is no main loop callback to beat a freeze
+    // watchdog from, so freezes aren't reported. Likewise
+    // ctd::on_game_saved() needs FOSE's save message, so reports leave out
+    // the time since the last save.
+
     return true;
 }
 
+// Test crash - lets users verify crash capture (requires allow_test_crash = true)
+extern "C" __declspec(dllexport) bool trigger_test_crash() {
+    return ctd::trigger_test_crash();
+}
+
+// Post-hoc notes - lets a next-launch prompt add context to the last report.
+// Copies the ID (NUL-terminated, truncated to fit) and returns its full length.
+extern "C" __declspec(dllexport) size_t last_report_id(char* buffer, size_t size) {
+    auto id = ctd::last_report_id();
+    if (buffer && size > 0) {
+        size_t len = std::min(size - 1, id.size());
+        std::memcpy(buffer, id.data(), len);
+        buffer[len] = '\0';
+    }
+    return id.size();
+}
+
+extern "C" __declspec(dllexport) bool append_note_to_last_report(const char* text) {
+    if (!text) {
+        return false;
+    }
+    try {
+        return ctd::append_note_to_last_report(rust::Str(text));
+    } catch (const std::invalid_argument&) {
+        return false;  // Not valid UTF-8
+    }
+}
+
+// Manual reports - lets a console-command mod or in-game prompt file a report
+// with the current load order and game state when the game didn't crash.
+extern "C" __declspec(dllexport) bool submit_manual_report(const char* notes) {
+    if (!notes) {
+        return false;
+    }
+    try {
+        return ctd::submit_manual_report(rust::Str(notes));
+    } catch (const std::invalid_argument&) {
+        return false;  // Not valid UTF-8
+    }
+}
+
 namespace ctd {
 
 // Get load order from game
@@ -86,4 +141,14 @@ rust::String get_fose_version() {
     return rust::String("1.3.0");
 }
 
+// Mirror Rust warnings and errors into the FOSE log
+// Scaffolding: goes to the debugger output until the FOSE SDK's
+// _WARNING/_ERROR macros (which write fose.log) are available
+void log_to_fose(LogLevel level, rust::Str message) {
+    std::string text = level == LogLevel::Error ? "[CTD] ERROR: " : "[CTD] WARN: ";
+    text.append(message.data(), message.size());
+    text.push_back('\n');
+    OutputDebugStringA(text.c_str());
+}
+
 }  // namespace ctd
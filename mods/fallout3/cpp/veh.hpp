@@ -1,8 +0,0 @@
-#pragma once
-
-namespace ctd {
-
-// Register the vectored exception handler for crash capture.
-void register_veh_handler();
-
-}  // namespace ctd
@@ -1,30 +1,24 @@
 //! FOSE plugin for Fallout 3 crash capture.
 //!
 //! This crate provides the Rust side of a hybrid C++/Rust FOSE plugin.
-//! The C++ layer handles FOSE registration and VEH setup, while Rust
-//! handles crash processing and API submission.
+//! The C++ layer handles FOSE registration and reads the load order, while
+//! Rust registers the crash handler and builds and submits reports.
 
 mod crash;
 mod fingerprint;
 
-use tracing::info;
+use std::thread::JoinHandle;
+
+use ctd_capture::handler::{self, CrashData, HandlerOptions};
+use ctd_core::abi::{add_breadcrumb, report_handled_error, set_context};
+use ctd_core::config::Config;
+use ctd_core::last_report::LastReport;
+use ctd_core::uptime;
+use tracing::{info, warn};
 
 /// CXX bridge between C++ and Rust.
 #[cxx::bridge(namespace = "ctd")]
 mod ffi {
-    /// Exception data passed from C++ VEH handler.
-    #[derive(Debug, Clone)]
-    struct ExceptionData {
-        /// Windows exception code (e.g., 0xC0000005).
-        code: u32,
-        /// Address where the exception occurred.
-        address: u64,
-        /// Formatted stack trace with module offsets.
-        stack_trace: String,
-        /// Module name where the crash occurred (if known).
-        faulting_module: String,
-    }
-
     /// Plugin information from TESDataHandler.
     #[derive(Debug, Clone)]
     struct PluginInfo {
@@ -34,16 +28,46 @@ mod ffi {
         index: u8,
     }
 
+    /// Severity of a message mirrored into the FOSE log.
+    enum LogLevel {
+        Warn,
+        Error,
+    }
+
     // Functions exported from Rust to C++
     extern "Rust" {
         /// Initialize the Rust side of the plugin.
         fn init();
 
+        /// Register the crash handler, per `[handler]`.
+        fn register_handler();
+
         /// Called when FOSE's kDataLoaded message is received.
         fn on_data_loaded();
 
-        /// Handle a crash from the VEH handler.
-        fn handle_crash(data: ExceptionData);
+        /// Called when FOSE reports that the game saved.
+        fn on_game_saved();
+
+        /// Crash the game on purpose if `allow_test_crash` is set in ctd.toml.
+        fn trigger_test_crash() -> bool;
+
+        /// ID of the last report submitted for this game, or empty if none.
+        fn last_report_id() -> String;
+
+        /// Attach a note to the last report submitted for this game.
+        fn append_note_to_last_report(text: &str) -> bool;
+
+        /// File a report without a crash, with the player's description.
+        fn submit_manual_report(notes: &str) -> bool;
+
+        /// Record a step another mod took, for `ctd_add_breadcrumb`.
+        fn add_breadcrumb(source: &str, message: &str) -> bool;
+
+        /// Set or (with an empty value) remove a context key, for `ctd_set_context_kv`.
+        fn set_context(key: &str, value: &str) -> bool;
+
+        /// Record an error another mod handled, for `ctd_report_handled_error`.
+        fn report_handled_error(source: &str, message: &str) -> bool;
     }
 
     // Functions imported from C++ to Rust
@@ -58,26 +82,120 @@ mod ffi {
 
         /// Get the FOSE version string.
         fn get_fose_version() -> String;
+
+        /// Write a message to the FOSE log.
+        fn log_to_fose(level: LogLevel, message: &str);
     }
 }
 
 /// Initialize the Rust side of the plugin.
 pub fn init() {
+    if let Some(path) = ctd_core::logging::init_with_host(crash::GAME_ID, forward_to_fose) {
+        info!("Logging to {}", path.display());
+    }
     info!("CTD Crash Reporter initializing");
+
+    ctd_core::startup::init();
+}
+
+/// Mirrors a warning or error into the FOSE log, where users look first.
+fn forward_to_fose(level: tracing::Level, message: &str) {
+    let level = if level == tracing::Level::ERROR {
+        ffi::LogLevel::Error
+    } else {
+        ffi::LogLevel::Warn
+    };
+    ffi::log_to_fose(level, message);
+}
+
+/// Register the crash handler, per `[handler]`.
+pub fn register_handler() {
+    let options = HandlerOptions::from_config(&Config::load().unwrap_or_default());
+    if let Err(e) = handler::register(
+        crash::GAME_ID,
+        &ffi::get_game_version(),
+        options,
+        report_crash,
+    ) {
+        warn!("Failed to register crash handler: {}", e);
+    }
 }
 
 /// Called when game data is loaded.
 pub fn on_data_loaded() {
     info!("Game data loaded, load order available");
+
+    // Hash the load order now so a crash doesn't have to
+    let mod_names = ffi::get_load_order().into_iter().map(|p| p.name).collect();
+    fingerprint::fingerprint_in_background(mod_names);
 }
 
-/// Handle a crash from the VEH handler.
-pub fn handle_crash(data: ffi::ExceptionData) {
+/// Build and send the report for a crash the handler captured.
+fn report_crash(data: CrashData) -> Option<JoinHandle<()>> {
     info!(
-        "Crash captured: 0x{:08X} at 0x{:016X}",
-        data.code, data.address
+        "Crash captured: 0x{:08X} ({}) at 0x{:016X}",
+        data.exception_code,
+        ctd_capture::exception::exception_code_name(data.exception_code),
+        data.exception_address
     );
 
-    // Delegate to crash module
-    crash::process_crash(data);
+    Some(crash::process_crash(data))
+}
+
+/// Remember when the game last saved, for the next crash report.
+pub fn on_game_saved() {
+    uptime::record_save();
+}
+
+/// Crash the game on purpose if `allow_test_crash` is set in ctd.toml.
+///
+/// Returns false (and logs why) when test crashes are disabled.
+pub fn trigger_test_crash() -> bool {
+    match ctd_core::test_crash::trigger(&Config::load().unwrap_or_default()) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("{}", e);
+            false
+        }
+    }
+}
+
+/// ID of the last report submitted for this game, or empty if none.
+pub fn last_report_id() -> String {
+    LastReport::load(crash::GAME_ID)
+        .map(|report| report.id)
+        .unwrap_or_default()
+}
+
+/// Attach a note to the last report submitted for this game.
+///
+/// Blocks until the note is sent. Returns false (and logs why) on failure.
+pub fn append_note_to_last_report(text: &str) -> bool {
+    match ctd_core::last_report::append_note_to_last(crash::GAME_ID, text) {
+        Ok(id) => {
+            info!("Note attached to report {}", id);
+            true
+        }
+        Err(e) => {
+            warn!("Failed to attach note: {}", e);
+            false
+        }
+    }
+}
+
+/// File a report without a crash, with the player's description.
+///
+/// Returns once the game state is read; the report is sent in the
+/// background. Returns false (and logs why) if the description is invalid.
+pub fn submit_manual_report(notes: &str) -> bool {
+    match crash::process_manual(notes) {
+        Ok(()) => {
+            info!("Filing manual report");
+            true
+        }
+        Err(e) => {
+            warn!("Cannot file report: {}", e);
+            false
+        }
+    }
 }
@@ -13,10 +13,13 @@ namespace ctd {
 
 // Forward declare the Rust types (defined in lib.rs.h)
 struct PluginInfo;
+enum class LogLevel : std::uint8_t;
 
 // C++ functions callable from Rust
 rust::Vec<PluginInfo> get_load_order();
 rust::String get_game_version();
 rust::String get_f4se_version();
+void log_to_f4se(LogLevel level, rust::Str message);
+std::uint32_t find_touched_form(rust::Slice<const std::uint64_t> registers);
 
 }  // namespace ctd
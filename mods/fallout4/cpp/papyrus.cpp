@@ -0,0 +1,96 @@
+// Native functions of the CTD Papyrus script.
+//
+// Quest and framework mods know what they were doing when the game went
+// down; the stack trace doesn't. scripts/CTD.psc declares these as global
+// natives so scripts can leave notes and breadcrumbs for the next report, or
+// file a manual report, tagged with the name of the calling script.
+
+#include <F4SE/F4SE.h>
+#include <RE/Fallout.h>
+
+#include <stdexcept>
+#include <string>
+#include <string_view>
+#include <variant>
+
+#include "ctd-fallout4/src/lib.rs.h"  // CXX-generated Rust interface
+#include "papyrus.hpp"
+
+namespace {
+
+    constexpr auto kScript = "CTD";
+
+    // The script that called into CTD: the innermost frame on its stack that
+    // isn't one of CTD's own natives. Read without the VM lock, so a stack
+    // changing under us only costs the name.
+    const char* read_calling_script(RE::BSScript::IVirtualMachine& vm, std::uint32_t stack_id) {
+        __try {
+            auto& internal = static_cast<RE::BSScript::Internal::VirtualMachine&>(vm);
+            auto it = internal.allRunningStacks.find(stack_id);
+            if (it == internal.allRunningStacks.end() || !it->second) {
+                return nullptr;
+            }
+            for (auto* frame = it->second->top; frame; frame = frame->previousFrame) {
+                auto* function = frame->owningFunction.get();
+                if (function && _stricmp(function->GetObjectTypeName().c_str(), kScript) != 0) {
+                    return function->GetObjectTypeName().c_str();
+                }
+            }
+            return nullptr;
+        } __except (EXCEPTION_EXECUTE_HANDLER) {
+            return nullptr;
+        }
+    }
+
+    std::string calling_script(RE::BSScript::IVirtualMachine& vm, std::uint32_t stack_id) {
+        auto* script = read_calling_script(vm, stack_id);
+        return script && *script ? script : "Papyrus";
+    }
+
+    void AddNote(RE::BSScript::IVirtualMachine& vm, std::uint32_t stack_id, std::monostate, RE::BSFixedString note) {
+        try {
+            ctd::add_note(rust::Str(calling_script(vm, stack_id)), rust::Str(note.c_str()));
+        } catch (const std::invalid_argument&) {
+            // Not valid UTF-8
+        }
+    }
+
+    void AddBreadcrumb(RE::BSScript::IVirtualMachine& vm, std::uint32_t stack_id, std::monostate, RE::BSFixedString message) {
+        try {
+            ctd::add_breadcrumb(rust::Str(calling_script(vm, stack_id)), rust::Str(message.c_str()));
+        } catch (const std::invalid_argument&) {
+            // Not valid UTF-8
+        }
+    }
+
+    bool SubmitManualReport(RE::BSScript::IVirtualMachine& vm, std::uint32_t stack_id, std::monostate) {
+        auto notes = "Filed by a Papyrus script (" + calling_script(vm, stack_id) + ")";
+        try {
+            return ctd::submit_manual_report(rust::Str(notes));
+        } catch (const std::invalid_argument&) {
+            return false;  // Not valid UTF-8
+        }
+    }
+
+    bool register_functions(RE::BSScript::IVirtualMachine* vm) {
+        using namespace std::literals;
+
+        // Notes and breadcrumbs only take a lock, so tasklets may call them
+        // directly; a manual report reads game state on the main thread
+        vm->BindNativeMethod(kScript, "AddNote"sv, AddNote, true);
+        vm->BindNativeMethod(kScript, "AddBreadcrumb"sv, AddBreadcrumb, true);
+        vm->BindNativeMethod(kScript, "SubmitManualReport"sv, SubmitManualReport);
+        return true;
+    }
+
+}  // namespace
+
+namespace ctd {
+
+void register_papyrus_functions() {
+    if (auto* papyrus = F4SE::GetPapyrusInterface()) {
+        papyrus->Register(register_functions);
+    }
+}
+
+}  // namespace ctd
@@ -6,22 +6,85 @@
 #include <F4SE/F4SE.h>
 #include <RE/Fallout.h>
 
+#include <dxgi.h>
+
+#include <algorithm>
+#include <cstring>
+#include <stdexcept>
+
 #include "ctd-fallout4/src/lib.rs.h"  // CXX-generated Rust interface
 #include "bridge.hpp"
-#include "veh.hpp"
+#include "papyrus.hpp"
+
+// The C API for other mods, defined by ctd.h on top of the bridge
+#define CTD_API __declspec(dllexport)
+#define CTD_NO_LOADER
+#define CTD_DEFINE_EXPORTS
+#include "ctd.h"
 
 namespace {
 
+// Beats the freeze watchdog from the game's swap chain, which the main thread
+// presents once per frame, loading screens included
+struct PresentHook {
+    static HRESULT STDMETHODCALLTYPE Present(IDXGISwapChain* swap_chain, UINT sync_interval, UINT flags) {
+        ctd::watchdog_beat();
+        return original(swap_chain, sync_interval, flags);
+    }
+
+    static inline REL::Relocation<decltype(Present)> original;
+};
+
+void install_present_hook() {
+    auto* renderer = RE::BSGraphics::RendererData::GetSingleton();
+    auto* swap_chain = renderer
+        ? reinterpret_cast<IDXGISwapChain*>(renderer->renderWindowArray[0].swapChain)
+        : nullptr;
+    if (!swap_chain) {
+        F4SE::log::warn("No swap chain to hook; freezes won't be reported");
+        return;
+    }
+
+    // IDXGISwapChain::Present is the ninth virtual function
+    auto* vtable = *reinterpret_cast<std::uintptr_t**>(swap_chain);
+    PresentHook::original = vtable[8];
+    REL::safe_write(
+        reinterpret_cast<std::uintptr_t>(&vtable[8]),
+        reinterpret_cast<std::uintptr_t>(&PresentHook::Present)
+    );
+}
+
 void MessageHandler(F4SE::MessagingInterface::Message* message) {
     switch (message->type) {
         case F4SE::MessagingInterface::kGameDataReady:
             ctd::on_data_loaded();
+            install_present_hook();
+            break;
+        case F4SE::MessagingInterface::kPostSaveGame:
+            ctd::on_game_saved();
             break;
         default:
             break;
     }
 }
 
+// Read a form ID, or 0 if `form` doesn't look like a live form. Crash
+// registers often hold garbage, so every read is guarded.
+std::uint32_t read_form_id(const RE::TESForm* form, std::uintptr_t vtables_begin, std::uintptr_t vtables_end) {
+    __try {
+        auto vtable = *reinterpret_cast<const std::uintptr_t*>(form);
+        if (vtable < vtables_begin || vtable >= vtables_end) {
+            return 0;
+        }
+        if (form->GetFormType() >= RE::ENUM_FORM_ID::kTotal) {
+            return 0;
+        }
+        return form->GetFormID();
+    } __except (EXCEPTION_EXECUTE_HANDLER) {
+        return 0;
+    }
+}
+
 }  // namespace
 
 // Plugin query - called by F4SE to get plugin info
@@ -46,8 +109,11 @@ extern "C" __declspec(dllexport) bool F4SEAPI F4SEPlugin_Load(
 ) {
     F4SE::Init(f4se);
 
-    // Register VEH handler for crash capture
-    ctd::register_veh_handler();
+    // Register the crash handler
+    ctd::register_handler();
+
+    // Bind the CTD script's natives (CTD.AddNote, ...)
+    ctd::register_papyrus_functions();
 
     // Register for messaging events
     auto messaging = F4SE::GetMessagingInterface();
@@ -62,6 +128,47 @@ extern "C" __declspec(dllexport) bool F4SEAPI F4SEPlugin_Load(
     return true;
 }
 
+// Test crash - lets users verify crash capture (requires allow_test_crash = true)
+extern "C" __declspec(dllexport) bool trigger_test_crash() {
+    return ctd::trigger_test_crash();
+}
+
+// Post-hoc notes - lets a next-launch prompt add context to the last report.
+// Copies the ID (NUL-terminated, truncated to fit) and returns its full length.
+extern "C" __declspec(dllexport) size_t last_report_id(char* buffer, size_t size) {
+    auto id = ctd::last_report_id();
+    if (buffer && size > 0) {
+        size_t len = std::min(size - 1, id.size());
+        std::memcpy(buffer, id.data(), len);
+        buffer[len] = '\0';
+    }
+    return id.size();
+}
+
+extern "C" __declspec(dllexport) bool append_note_to_last_report(const char* text) {
+    if (!text) {
+        return false;
+    }
+    try {
+        return ctd::append_note_to_last_report(rust::Str(text));
+    } catch (const std::invalid_argument&) {
+        return false;  // Not valid UTF-8
+    }
+}
+
+// Manual reports - lets a console-command mod or in-game prompt file a report
+// with the current load order and game state when the game didn't crash.
+extern "C" __declspec(dllexport) bool submit_manual_report(const char* notes) {
+    if (!notes) {
+        return false;
+    }
+    try {
+        return ctd::submit_manual_report(rust::Str(notes));
+    } catch (const std::invalid_argument&) {
+        return false;  // Not valid UTF-8
+    }
+}
+
 namespace ctd {
 
 // Get load order from TESDataHandler
@@ -80,7 +187,7 @@ rust::Vec<PluginInfo> get_load_order() {
         PluginInfo info;
         info.name = rust::String(file->filename);
         info.index = file->compileIndex;
-        info.is_light = false;  // F4 doesn't have light plugins (ESL), that's Skyrim SE only
+        info.is_light = file->IsLight();  // ESL support arrived with the Creation Club
         plugins.push_back(std::move(info));
     }
 
@@ -99,4 +206,39 @@ rust::String get_f4se_version() {
     return rust::String(F4SE::GetF4SEVersion().string());
 }
 
+// Mirror Rust warnings and errors into the plugin's F4SE log
+void log_to_f4se(LogLevel level, rust::Str message) {
+    std::string text(message);
+    if (level == LogLevel::Error) {
+        F4SE::log::error("{}", text);
+    } else {
+        F4SE::log::warn("{}", text);
+    }
+}
+
+// Find a form the crashing code was working on: the first register that
+// points at an object with a game vtable, a valid form type, and a form ID.
+// The registers come in ctd_core::rtti::REGISTERS_64 order.
+std::uint32_t find_touched_form(rust::Slice<const std::uint64_t> registers) {
+    const auto rdata = REL::Module::get().segment(REL::Segment::rdata);
+    const std::uintptr_t vtables_begin = rdata.address();
+    const std::uintptr_t vtables_end = vtables_begin + rdata.size();
+
+    // Argument registers first, then the ones compilers keep objects in:
+    // RCX, RDX, R8, R9, RAX, RBX, RSI, RDI, R12-R15
+    constexpr std::size_t kOrder[] = {2, 3, 8, 9, 0, 1, 4, 5, 12, 13, 14, 15};
+
+    for (auto index : kOrder) {
+        if (index >= registers.size()) {
+            continue;
+        }
+        auto id = read_form_id(
+            reinterpret_cast<const RE::TESForm*>(registers[index]), vtables_begin, vtables_end);
+        if (id != 0) {
+            return id;
+        }
+    }
+    return 0;
+}
+
 }  // namespace ctd
@@ -1,65 +1,212 @@
 //! Crash processing and report submission.
 
-use ctd_core::api_client::ApiClient;
-use ctd_core::crash_report::CreateCrashReport;
+use ctd_capture::handler::CrashData;
+use ctd_core::CtdError;
+use ctd_core::api_client;
+use ctd_core::blame;
+use ctd_core::crash_arena;
+use ctd_core::crash_log;
+use ctd_core::crash_report::{CreateCrashReport, ReportType, manual_report_notes};
+use ctd_core::detours;
+use ctd_core::exception_tally;
+use ctd_core::file_hash;
+use ctd_core::formid;
+use ctd_core::graphics;
+use ctd_core::hardware_events;
+use ctd_core::known_bad;
+use ctd_core::memory_map;
+use ctd_core::modpack;
+use ctd_core::save_game::latest_save_info;
+use ctd_core::submit;
+use ctd_core::throttle::Throttle;
+use ctd_core::watchdog::{self, Hang};
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::Duration;
 use tracing::{error, info};
 
 use crate::ffi;
-use crate::ffi::ExceptionData;
-use crate::fingerprint::build_mod_list;
+use crate::fingerprint::{build_graphics_injector_list, build_native_plugin_list, cached_mod_list};
 
 /// Game ID for Fallout 4.
-const GAME_ID: &str = "fallout4";
+pub(crate) const GAME_ID: &str = "fallout4";
+
+/// What a report is about.
+enum Cause {
+    /// The crash handler caught a fatal exception, with the ID of the form
+    /// the crashing code was touching, or 0 if none was found.
+    Crash(u32),
+    /// The game thread stopped responding for this long.
+    Hang(Duration),
+    /// The player filed a report with this description.
+    Manual(String),
+}
 
 /// Process a crash and submit it to the API.
-pub fn process_crash(data: ExceptionData) {
+///
+/// Returns the thread submitting the report, which the crash handler waits
+/// for when the process ends next.
+pub fn process_crash(data: CrashData) -> JoinHandle<()> {
+    // Read the touched form now, while the crashing thread is still stopped
+    let form_id = ffi::find_touched_form(&data.registers);
+
     // Spawn a thread for submission to avoid blocking
     std::thread::spawn(move || {
-        if let Err(e) = submit_crash_report(data) {
+        if let Err(e) = submit_crash_report(data, Cause::Crash(form_id)) {
             error!("Failed to submit crash report: {}", e);
         }
+    })
+}
+
+/// Report a hang the watchdog detected, on its monitor thread.
+pub fn process_hang(hang: Hang) {
+    let data = CrashData {
+        stack_trace: hang.stack_trace,
+        ..CrashData::default()
+    };
+
+    if let Err(e) = submit_crash_report(data, Cause::Hang(hang.stalled_for)) {
+        error!("Failed to submit hang report: {}", e);
+    }
+}
+
+/// File a report the player asked for, without a crash.
+///
+/// The report carries every thread's stack and is sent in the background.
+///
+/// # Errors
+///
+/// Returns `CtdError::Validation` if `notes` is empty or too long.
+pub fn process_manual(notes: &str) -> Result<(), CtdError> {
+    let notes = manual_report_notes(notes)?.to_string();
+
+    std::thread::spawn(move || {
+        let data = CrashData {
+            stack_trace: watchdog::capture_all_threads(),
+            ..CrashData::default()
+        };
+        if let Err(e) = submit_crash_report(data, Cause::Manual(notes)) {
+            error!("Failed to submit manual report: {}", e);
+        }
     });
+    Ok(())
 }
 
-/// Build and submit a crash report.
+/// Build and submit a report for a crash, hang, or player request.
 fn submit_crash_report(
-    data: ExceptionData,
+    data: CrashData,
+    cause: Cause,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Get load order from game
+    // Honor [submission] sample_rate and max_reports_per_hour; a report the
+    // player asked for isn't sampled
+    let throttle = Throttle::load();
+    let verdict = match cause {
+        Cause::Manual(_) => throttle.admit_requested(),
+        Cause::Crash(_) | Cause::Hang(_) => throttle.admit(),
+    };
+    if !verdict.is_allowed() {
+        info!("Report not submitted: {}", verdict);
+        crash_arena::discard_record();
+        return Ok(());
+    }
+
+    // Get load order from game, with the hashes made when data loaded
     let plugins = ffi::get_load_order();
-    let mod_names: Vec<String> = plugins.into_iter().map(|p| p.name).collect();
-    let mod_list = build_mod_list(mod_names);
+    let plugins = plugins.into_iter().map(|p| (p.name, p.is_light)).collect();
+    let mod_list = cached_mod_list(plugins);
+
+    // Work out which mod shipped the faulting DLL
+    let suspected_mod = data.faulting_module.as_deref().and_then(|module| {
+        let installed = blame::installed_mods();
+        blame::suspected_mod(Path::new(module), &mod_list, &installed)
+    });
+
+    // Name the plugin that defines the form the crashing code was touching
+    let touched_form = match cause {
+        Cause::Crash(form_id) if form_id != 0 => Some(formid::describe(form_id, &mod_list)),
+        _ => None,
+    };
+
+    // Compare the most recent save's plugins with the live load order
+    let save_game = latest_save_info("Fallout4", &mod_list);
 
     // Build the crash report
     let mut builder = CreateCrashReport::builder()
         .game_id(GAME_ID)
         .game_version(ffi::get_game_version())
         .stack_trace(&data.stack_trace)
-        .exception_code(format!("0x{:08X}", data.code))
-        .exception_address(format!("0x{:016X}", data.address))
         .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
         .script_extender_version(ffi::get_f4se_version())
+        .native_plugins(build_native_plugin_list())
+        .graphics_injectors(build_graphics_injector_list())
+        .handled_exceptions(exception_tally::snapshot())
+        .hardware_events(hardware_events::recent())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(GAME_ID))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .recent_frame_times()
         .crashed_now();
 
+    let address_region = (matches!(cause, Cause::Crash(_)) && data.faulting_module.is_none())
+        .then(|| memory_map::address_region(data.exception_address))
+        .flatten();
+
+    builder = match cause {
+        Cause::Crash(_) => builder
+            .exception_code(format!("0x{:08X}", data.exception_code))
+            .exception_address(format!("0x{:016X}", data.exception_address))
+            .probable_objects(data.probable_objects)
+            .exception_chain(data.exception_chain),
+        Cause::Hang(stalled_for) => builder.report_type(ReportType::Hang).notes(format!(
+            "Game thread stopped responding for {} seconds",
+            stalled_for.as_secs()
+        )),
+        Cause::Manual(notes) => builder.report_type(ReportType::Manual).notes(notes),
+    };
+
     // Add faulting module if available
-    if !data.faulting_module.is_empty() {
-        builder = builder.faulting_module(&data.faulting_module);
+    if let Some(module) = &data.faulting_module {
+        builder = builder
+            .faulting_module(module)
+            .hooked_functions(detours::scan(module));
     }
 
-    let report = builder.build()?;
+    if let Some(region) = address_region {
+        builder = builder.address_region(region);
+    }
+
+    builder = builder.exception_parameters(&data.exception_parameters);
+    if let Some(error) = data.in_page_error {
+        builder = builder.in_page_error(error);
+    }
+
+    if let Some(suspect) = suspected_mod {
+        info!("Suspected mod: {}", suspect);
+        builder = builder.suspected_mod(suspect);
+    }
+
+    if let Some(save) = save_game {
+        builder = builder.save_game(save);
+    }
 
-    // Create runtime for async API call
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()?;
+    if let Some(form) = touched_form {
+        info!("Crash touched form {}", form);
+        builder = builder.notes(format!("Last touched form: {}", form));
+    }
 
-    // Submit the report
-    let response = rt.block_on(async {
-        let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
+    let report = builder.build()?;
 
-        client.submit_crash_report(&report).await
-    })?;
+    // Mirror crashes in Crash Logger's format for community analyzers
+    if let Some(path) = crash_log::write_if_enabled(&report) {
+        info!("Crash log written to {:?}", path);
+    }
 
+    let response = submit::submit_report(&report)?;
     info!("Crash report submitted: {}", response.id);
     Ok(())
 }
@@ -1,9 +1,18 @@
 //! File fingerprinting for Fallout 4 mods.
 
-use ctd_core::file_hash::compute_file_hash;
-use ctd_core::load_order::{ModEntry, ModList};
+use ctd_core::coexistence::{CrashLogger, detect_crash_loggers};
+use ctd_core::file_hash::{CachedHasher, UNHASHED};
+use ctd_core::fingerprint_cache::FingerprintCache;
+use ctd_core::graphics_injectors::{GraphicsInjector, detect_graphics_injectors};
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
+use ctd_core::mo2::Mo2Instance;
+use ctd_core::native_plugins::scan_native_plugins;
+use ctd_core::plugin_header::read_header;
 use std::path::PathBuf;
 
+/// Mod list fingerprinted in the background once game data has loaded.
+static MOD_LIST: FingerprintCache = FingerprintCache::new();
+
 /// Get the game's Data directory from the DLL location.
 #[cfg(windows)]
 pub fn get_data_dir() -> Option<PathBuf> {
@@ -34,25 +43,91 @@ pub fn get_data_dir() -> Option<PathBuf> {
 }
 
 /// Build ModList with hashes for all loaded mods.
-pub fn build_mod_list(mod_names: Vec<String>) -> ModList {
+///
+/// Takes each plugin's file name and whether it is ESL-flagged.
+pub fn build_mod_list(plugins: impl IntoIterator<Item = (String, bool)>) -> ModList {
     let data_dir = get_data_dir().unwrap_or_else(|| PathBuf::from("."));
-    let mut list = ModList::new();
+    let plugins: Vec<(String, bool)> = plugins.into_iter().collect();
+    let paths: Vec<PathBuf> = plugins
+        .iter()
+        .map(|(name, _)| data_dir.join(name))
+        .collect();
 
-    for (index, name) in mod_names.into_iter().enumerate() {
-        let path = data_dir.join(&name);
-
-        let (hash, size) = match compute_file_hash(&path) {
-            Ok((h, s)) => (h, s),
-            Err(_) => ("0000000000000000".to_string(), 0),
-        };
+    let mut hasher = CachedHasher::open_default();
+    let hashed = ModList::fingerprint_parallel(&paths, DEFAULT_HASH_BUDGET, &mut hasher);
+    hasher.save();
 
+    let mut list = ModList::new();
+    for (((name, is_light), path), hashed) in plugins.into_iter().zip(&paths).zip(hashed) {
         // ESP/ESM files don't have version info, just use hash
-        let entry = ModEntry::new(&name, hash, size)
-            .with_index(index as u32)
-            .with_enabled(true);
+        let mut entry = ModEntry::new(name, hashed.file_hash, hashed.file_size)
+            .with_index(list.len() as u32)
+            .with_enabled(true)
+            .with_light(is_light);
+
+        if let Ok(header) = read_header(path) {
+            entry = entry.with_masters(header.masters);
+        }
 
         list.push(entry);
     }
 
+    // Name the MO2 mods that installed each plugin
+    if let Some(mo2) = Mo2Instance::detect() {
+        mo2.enrich(&mut list);
+    }
+
     list
 }
+
+/// Start fingerprinting the load order on a background thread.
+///
+/// Takes each plugin's file name and whether it is ESL-flagged.
+pub fn fingerprint_in_background(plugins: Vec<(String, bool)>) {
+    MOD_LIST.populate_in_background(move || build_mod_list(plugins));
+}
+
+/// Get the mod list fingerprinted in the background, or names only if the
+/// background scan hasn't finished or saw a different load order.
+pub fn cached_mod_list(plugins: Vec<(String, bool)>) -> ModList {
+    let names: Vec<&str> = plugins.iter().map(|(name, _)| name.as_str()).collect();
+    MOD_LIST
+        .get_matching(&names)
+        .unwrap_or_else(|| names_only(plugins))
+}
+
+/// Build ModList with names and ESL flags but no hashes, without touching
+/// the disk.
+fn names_only(plugins: Vec<(String, bool)>) -> ModList {
+    let mut list = ModList::new();
+    for (name, is_light) in plugins {
+        let entry = ModEntry::new(name, UNHASHED, 0)
+            .with_index(list.len() as u32)
+            .with_enabled(true)
+            .with_light(is_light);
+        list.push(entry);
+    }
+    list
+}
+
+/// Build ModList with hashes and versions for all F4SE DLL plugins.
+pub fn build_native_plugin_list() -> ModList {
+    let data_dir = get_data_dir().unwrap_or_else(|| PathBuf::from("."));
+    let mut hasher = CachedHasher::open_default();
+    let plugins = scan_native_plugins(&data_dir.join("F4SE").join("Plugins"), &mut hasher);
+    hasher.save();
+    plugins
+}
+
+/// Detect other crash loggers installed as F4SE plugins.
+pub fn build_crash_logger_list() -> Vec<CrashLogger> {
+    let data_dir = get_data_dir().unwrap_or_else(|| PathBuf::from("."));
+    detect_crash_loggers(&data_dir.join("F4SE").join("Plugins"))
+}
+
+/// Detect ENB/ReShade proxy DLLs in the game root (the parent of Data/).
+pub fn build_graphics_injector_list() -> Vec<GraphicsInjector> {
+    get_data_dir()
+        .and_then(|data_dir| data_dir.parent().map(detect_graphics_injectors))
+        .unwrap_or_default()
+}
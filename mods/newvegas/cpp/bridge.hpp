@@ -13,10 +13,12 @@ namespace ctd {
 
 // Forward declare the Rust types (defined in lib.rs.h)
 struct PluginInfo;
+enum class LogLevel : std::uint8_t;
 
 // C++ functions callable from Rust
 rust::Vec<PluginInfo> get_load_order();
 rust::String get_game_version();
 rust::String get_nvse_version();
+void log_to_nvse(LogLevel level, rust::Str message);
 
 }  // namespace ctd
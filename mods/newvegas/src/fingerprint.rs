@@ -1,9 +1,17 @@
 //! File fingerprinting for Fallout: New Vegas mods.
 
-use ctd_core::file_hash::compute_file_hash;
-use ctd_core::load_order::{ModEntry, ModList};
+use ctd_core::file_hash::{CachedHasher, UNHASHED};
+use ctd_core::fingerprint_cache::FingerprintCache;
+use ctd_core::graphics_injectors::{GraphicsInjector, detect_graphics_injectors};
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
+use ctd_core::mo2::Mo2Instance;
+use ctd_core::native_plugins::scan_native_plugins;
+use ctd_core::plugin_header::read_header;
 use std::path::PathBuf;
 
+/// Mod list fingerprinted in the background once game data has loaded.
+static MOD_LIST: FingerprintCache = FingerprintCache::new();
+
 /// Get the game's Data directory from the DLL location.
 #[cfg(windows)]
 pub fn get_data_dir() -> Option<PathBuf> {
@@ -36,22 +44,101 @@ pub fn get_data_dir() -> Option<PathBuf> {
 /// Build ModList with hashes for all loaded mods.
 pub fn build_mod_list(mod_names: Vec<String>) -> ModList {
     let data_dir = get_data_dir().unwrap_or_else(|| PathBuf::from("."));
-    let mut list = ModList::new();
+    let paths: Vec<PathBuf> = mod_names.iter().map(|name| data_dir.join(name)).collect();
 
-    for (index, name) in mod_names.into_iter().enumerate() {
-        let path = data_dir.join(&name);
+    let mut hasher = CachedHasher::open_default();
+    let hashed = ModList::fingerprint_parallel(&paths, DEFAULT_HASH_BUDGET, &mut hasher);
+    hasher.save();
 
-        let (hash, size) = match compute_file_hash(&path) {
-            Ok((h, s)) => (h, s),
-            Err(_) => ("0000000000000000".to_string(), 0),
-        };
-
-        let entry = ModEntry::new(&name, hash, size)
-            .with_index(index as u32)
+    let mut list = ModList::new();
+    for ((name, path), hashed) in mod_names.into_iter().zip(&paths).zip(hashed) {
+        let mut entry = ModEntry::new(name, hashed.file_hash, hashed.file_size)
+            .with_index(list.len() as u32)
             .with_enabled(true);
 
+        if let Ok(header) = read_header(path) {
+            entry = entry.with_masters(header.masters);
+        }
+
         list.push(entry);
     }
 
+    // Name the MO2 mods that installed each plugin
+    if let Some(mo2) = Mo2Instance::detect() {
+        mo2.enrich(&mut list);
+    }
+
+    list
+}
+
+/// Start fingerprinting the load order on a background thread.
+pub fn fingerprint_in_background(mod_names: Vec<String>) {
+    MOD_LIST.populate_in_background(move || build_mod_list(mod_names));
+}
+
+/// Get the mod list fingerprinted in the background, or names only if the
+/// background scan hasn't finished or saw a different load order.
+pub fn cached_mod_list(mod_names: Vec<String>) -> ModList {
+    let names: Vec<&str> = mod_names.iter().map(String::as_str).collect();
+    MOD_LIST
+        .get_matching(&names)
+        .unwrap_or_else(|| names_only(mod_names))
+}
+
+/// Build ModList with names but no hashes, without touching the disk.
+fn names_only(mod_names: Vec<String>) -> ModList {
+    let mut list = ModList::new();
+    for name in mod_names {
+        let entry = ModEntry::new(name, UNHASHED, 0)
+            .with_index(list.len() as u32)
+            .with_enabled(true);
+        list.push(entry);
+    }
     list
 }
+
+/// Build ModList with hashes and versions for all NVSE DLL plugins.
+pub fn build_native_plugin_list() -> ModList {
+    let data_dir = get_data_dir().unwrap_or_else(|| PathBuf::from("."));
+    let mut hasher = CachedHasher::open_default();
+    let plugins = scan_native_plugins(&data_dir.join("NVSE").join("Plugins"), &mut hasher);
+    hasher.save();
+    plugins
+}
+
+/// Detect ENB/ReShade proxy DLLs in the game root (the parent of Data/).
+pub fn build_graphics_injector_list() -> Vec<GraphicsInjector> {
+    get_data_dir()
+        .and_then(|data_dir| data_dir.parent().map(detect_graphics_injectors))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_names_only_keeps_load_order() {
+        let list = names_only(names(&["FalloutNV.esm", "DeadMoney.esm", "JIP.esp"]));
+
+        assert_eq!(list.len(), 3);
+        for (index, entry) in list.iter().enumerate() {
+            assert_eq!(entry.index, Some(index as u32));
+            assert_eq!(entry.enabled, Some(true));
+            assert_eq!(entry.file_hash, UNHASHED);
+            assert_eq!(entry.file_size, 0);
+        }
+        assert_eq!(list.0[2].name, "JIP.esp");
+    }
+
+    #[test]
+    fn test_cached_mod_list_falls_back_to_names() {
+        // Nothing has populated the cache in tests
+        let list = cached_mod_list(names(&["FalloutNV.esm"]));
+        assert_eq!(list, names_only(names(&["FalloutNV.esm"])));
+    }
+}
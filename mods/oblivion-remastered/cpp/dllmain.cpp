@@ -5,10 +5,14 @@
 #include <DynamicOutput/DynamicOutput.hpp>
 #include <Unreal/UObjectGlobals.hpp>
 #include <Unreal/UnrealVersion.hpp>
+#include <LuaMadeSimple/LuaMadeSimple.hpp>
 
+#include <algorithm>
+#include <cstring>
 #include <filesystem>
 #include <fstream>
 #include <sstream>
+#include <stdexcept>
 #include <string>
 #include <vector>
 
@@ -167,6 +171,67 @@ std::vector<std::string> scan_mods_directory(const std::filesystem::path& mods_d
     return mods;
 }
 
+// ============================================================================
+// Lua functions for UE4SS Lua mods
+// ============================================================================
+
+// Registry key naming the Lua mod a state belongs to, the breadcrumb source
+constexpr const char* kLuaSourceKey = "CTD.source";
+
+std::string lua_source(lua_State* L)
+{
+    lua_getfield(L, LUA_REGISTRYINDEX, kLuaSourceKey);
+    std::string source = lua_isstring(L, -1) ? lua_tostring(L, -1) : "UE4SS Lua";
+    lua_pop(L, 1);
+    return source;
+}
+
+// CTD_AddBreadcrumb(message) -> bool: record a step for the next report
+int lua_add_breadcrumb(lua_State* L)
+{
+    const char* message = luaL_checkstring(L, 1);
+    bool added = false;
+    try
+    {
+        added = ctd::add_breadcrumb(rust::Str(lua_source(L)), rust::Str(message));
+    }
+    catch (const std::invalid_argument&)
+    {
+        // Not valid UTF-8
+    }
+    lua_pushboolean(L, added);
+    return 1;
+}
+
+// CTD_SubmitReport([notes]) -> bool: file a manual report, sent in the background
+int lua_submit_report(lua_State* L)
+{
+    std::string notes = luaL_optstring(L, 1, "");
+    if (notes.empty())
+    {
+        notes = "Filed by the " + lua_source(L) + " Lua mod";
+    }
+    bool filed = false;
+    try
+    {
+        filed = ctd::submit_manual_report(rust::Str(notes));
+    }
+    catch (const std::invalid_argument&)
+    {
+        // Not valid UTF-8
+    }
+    lua_pushboolean(L, filed);
+    return 1;
+}
+
+void register_lua_functions(lua_State* L, const std::string& mod_name)
+{
+    lua_pushstring(L, mod_name.c_str());
+    lua_setfield(L, LUA_REGISTRYINDEX, kLuaSourceKey);
+    lua_register(L, "CTD_AddBreadcrumb", lua_add_breadcrumb);
+    lua_register(L, "CTD_SubmitReport", lua_submit_report);
+}
+
 }  // anonymous namespace
 
 // ============================================================================
@@ -231,6 +296,20 @@ rust::String get_game_version()
     return rust::String(version);
 }
 
+// Mirror Rust warnings and errors into the UE4SS log
+void log_to_ue4ss(LogLevel level, rust::Str message)
+{
+    auto text = ensure_str(to_generic_string(std::string(message)));
+    if (level == LogLevel::Error)
+    {
+        Output::send<RC::LogLevel::Error>(STR("[CTD] {}\n"), text);
+    }
+    else
+    {
+        Output::send<RC::LogLevel::Warning>(STR("[CTD] {}\n"), text);
+    }
+}
+
 }  // namespace ctd
 
 // ============================================================================
@@ -271,9 +350,30 @@ public:
         // Initialize the Rust crash handler
         ctd::init("oblivion-remastered", game_version.c_str(), ue_version.c_str());
 
+        // Ctrl+F12 files a report without a crash, for bugs that aren't CTDs
+        register_keydown_event(Input::Key::F12, {Input::ModifierKey::CONTROL}, [] {
+            if (ctd::submit_manual_report("Filed from the in-game keybind (Ctrl+F12)"))
+            {
+                Output::send<LogLevel::Verbose>(STR("[CTD] Filing report\n"));
+            }
+        });
+
         Output::send<LogLevel::Verbose>(STR("[CTD] Crash reporter initialized\n"));
     }
 
+    // Give every Lua mod CTD_AddBreadcrumb and CTD_SubmitReport, with the
+    // mod's name as the source, in its async state as well as its main one
+    auto on_lua_start(StringViewType mod_name,
+                      LuaMadeSimple::Lua& lua,
+                      LuaMadeSimple::Lua& main_lua,
+                      LuaMadeSimple::Lua& async_lua,
+                      std::vector<LuaMadeSimple::Lua*>& hook_luas) -> void override
+    {
+        auto name = to_string(mod_name);
+        register_lua_functions(lua.get_lua_state(), name);
+        register_lua_functions(async_lua.get_lua_state(), name);
+    }
+
     auto on_update() -> void override
     {
         // Called each frame - not needed for crash reporting
@@ -282,6 +382,12 @@ public:
 
 #define CTD_MOD_API __declspec(dllexport)
 
+// The C API for other mods, defined by ctd.h on top of the bridge
+#define CTD_API CTD_MOD_API
+#define CTD_NO_LOADER
+#define CTD_DEFINE_EXPORTS
+#include "ctd.h"
+
 extern "C"
 {
     CTD_MOD_API RC::CppUserModBase* start_mod()
@@ -293,4 +399,57 @@ extern "C"
     {
         delete mod;
     }
+
+    // Test crash - lets users verify crash capture (requires allow_test_crash = true)
+    CTD_MOD_API bool trigger_test_crash()
+    {
+        return ctd::trigger_test_crash();
+    }
+
+    // Post-hoc notes - lets a next-launch prompt add context to the last report.
+    // Copies the ID (NUL-terminated, truncated to fit) and returns its full length.
+    CTD_MOD_API size_t last_report_id(char* buffer, size_t size)
+    {
+        auto id = ctd::last_report_id();
+        if (buffer && size > 0)
+        {
+            size_t len = std::min(size - 1, id.size());
+            std::memcpy(buffer, id.data(), len);
+            buffer[len] = '\0';
+        }
+        return id.size();
+    }
+
+    CTD_MOD_API bool append_note_to_last_report(const char* text)
+    {
+        if (!text)
+        {
+            return false;
+        }
+        try
+        {
+            return ctd::append_note_to_last_report(rust::Str(text));
+        }
+        catch (const std::invalid_argument&)
+        {
+            return false; // Not valid UTF-8
+        }
+    }
+
+    // Manual reports - lets a "report a problem" UI file a report without a crash
+    CTD_MOD_API bool submit_manual_report(const char* notes)
+    {
+        if (!notes)
+        {
+            return false;
+        }
+        try
+        {
+            return ctd::submit_manual_report(rust::Str(notes));
+        }
+        catch (const std::invalid_argument&)
+        {
+            return false; // Not valid UTF-8
+        }
+    }
 }
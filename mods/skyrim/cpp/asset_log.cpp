@@ -0,0 +1,74 @@
+// Ring buffer of the asset paths the game opened most recently.
+//
+// Meshes, textures, and animations that crash the game while loading leave
+// no trace in the stack beyond the loader itself. Recording every loose file
+// stream the game opens names the asset that was loading when it crashed.
+
+#include <SKSE/SKSE.h>
+#include <RE/Skyrim.h>
+
+#include <algorithm>
+#include <array>
+#include <atomic>
+#include <cstring>
+
+#include "ctd-skyrim/src/lib.rs.h"  // CXX-generated Rust interface
+#include "asset_log.hpp"
+#include "bridge.hpp"
+
+namespace {
+
+    constexpr std::size_t kRecentAssets = 32;
+    constexpr std::size_t kMaxAssetPath = 260;
+
+    // Written from the game's loader threads without locking; a slot being
+    // overwritten during a crash only costs one garbled entry
+    std::array<std::array<char, kMaxAssetPath>, kRecentAssets> g_recent{};
+    std::atomic<std::uint64_t> g_next{0};
+
+    void record(const char* path) {
+        if (!path || !*path) {
+            return;
+        }
+        auto slot = g_next.fetch_add(1, std::memory_order_relaxed) % kRecentAssets;
+        strncpy_s(g_recent[slot].data(), kMaxAssetPath, path, _TRUNCATE);
+    }
+
+    struct LooseFileLocationHook {
+        static RE::BSResource::ErrorCode DoCreateStream(
+            RE::BSResource::LooseFileLocation* location,
+            const char* path,
+            RE::BSTSmartPointer<RE::BSResource::Stream>& stream,
+            RE::BSResource::Location*& result_location,
+            bool read_only
+        ) {
+            record(path);
+            return original(location, path, stream, result_location, read_only);
+        }
+
+        static inline REL::Relocation<decltype(DoCreateStream)> original;
+    };
+
+}  // namespace
+
+namespace ctd {
+
+void install_asset_log_hook() {
+    // BSResource::Location::DoCreateStream is the fourth virtual function
+    REL::Relocation<std::uintptr_t> vtable{RE::VTABLE_BSResource__LooseFileLocation[0]};
+    LooseFileLocationHook::original = vtable.write_vfunc(0x3, LooseFileLocationHook::DoCreateStream);
+}
+
+// Get the asset paths the game opened last, newest first
+rust::Vec<rust::String> get_recent_assets() {
+    rust::Vec<rust::String> assets;
+    auto next = g_next.load(std::memory_order_relaxed);
+    auto count = std::min<std::uint64_t>(next, kRecentAssets);
+    for (std::uint64_t i = 1; i <= count; ++i) {
+        const auto& slot = g_recent[(next - i) % kRecentAssets];
+        assets.push_back(rust::String::lossy(slot.data(), strnlen(slot.data(), kMaxAssetPath)));
+    }
+    return assets;
+}
+
+}  // namespace ctd
@@ -0,0 +1,8 @@
+#pragma once
+
+namespace ctd {
+
+// Hook loose file streams so the assets the game opened last are known at crash time
+void install_asset_log_hook();
+
+}  // namespace ctd
@@ -13,10 +13,18 @@ namespace ctd {
 
 // Forward declare the Rust types (defined in lib.rs.h)
 struct ModInfo;
+struct PapyrusFrameInfo;
+struct GameStateInfo;
+enum class LogLevel : std::uint8_t;
 
 // C++ functions callable from Rust
 rust::Vec<ModInfo> get_load_order();
 rust::String get_game_version();
 rust::String get_skse_version();
+rust::Vec<PapyrusFrameInfo> get_papyrus_stacks();
+GameStateInfo get_game_state();
+rust::Vec<rust::String> get_recent_assets();
+void log_to_skse(LogLevel level, rust::Str message);
+std::uint32_t find_touched_form(rust::Slice<const std::uint64_t> registers);
 
 } // namespace ctd
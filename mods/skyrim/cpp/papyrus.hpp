@@ -0,0 +1,8 @@
+#pragma once
+
+namespace ctd {
+
+// Bind the CTD script's native functions (scripts/CTD.psc) in the Papyrus VM
+void register_papyrus_functions();
+
+}  // namespace ctd
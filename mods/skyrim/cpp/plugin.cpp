@@ -6,22 +6,174 @@
 #include <SKSE/SKSE.h>
 #include <RE/Skyrim.h>
 
+#include <dxgi.h>
+
+#include <algorithm>
+#include <cstring>
+#include <stdexcept>
+
 #include "ctd-skyrim/src/lib.rs.h"  // CXX-generated Rust interface
+#include "asset_log.hpp"
 #include "bridge.hpp"
-#include "veh.hpp"
+#include "papyrus.hpp"
+
+// The C API for other mods, defined by ctd.h on top of the bridge
+#define CTD_API __declspec(dllexport)
+#define CTD_NO_LOADER
+#define CTD_DEFINE_EXPORTS
+#include "ctd.h"
 
 namespace {
 
+    // Beats the freeze watchdog from the game's swap chain, which the main
+    // thread presents once per frame, loading screens included
+    struct PresentHook {
+        static HRESULT STDMETHODCALLTYPE Present(IDXGISwapChain* swap_chain, UINT sync_interval, UINT flags) {
+            ctd::watchdog_beat();
+            return original(swap_chain, sync_interval, flags);
+        }
+
+        static inline REL::Relocation<decltype(Present)> original;
+    };
+
+    void install_present_hook() {
+        auto* renderer = RE::BSGraphics::Renderer::GetSingleton();
+        auto* swap_chain = renderer
+            ? reinterpret_cast<IDXGISwapChain*>(renderer->GetRuntimeData().renderWindows[0].swapChain)
+            : nullptr;
+        if (!swap_chain) {
+            SKSE::log::warn("No swap chain to hook; freezes won't be reported");
+            return;
+        }
+
+        // IDXGISwapChain::Present is the ninth virtual function
+        auto* vtable = *reinterpret_cast<std::uintptr_t**>(swap_chain);
+        PresentHook::original = vtable[8];
+        REL::safe_write(
+            reinterpret_cast<std::uintptr_t>(&vtable[8]),
+            reinterpret_cast<std::uintptr_t>(&PresentHook::Present)
+        );
+    }
+
     void MessageHandler(SKSE::MessagingInterface::Message* message) {
         switch (message->type) {
             case SKSE::MessagingInterface::kDataLoaded:
                 ctd::on_data_loaded();
+                install_present_hook();
+                break;
+            case SKSE::MessagingInterface::kSaveGame:
+                ctd::on_game_saved();
                 break;
             default:
                 break;
         }
     }
 
+    // Read a form ID, or 0 if `form` doesn't look like a live form. Crash
+    // registers often hold garbage, so every read is guarded.
+    std::uint32_t read_form_id(const RE::TESForm* form, std::uintptr_t vtables_begin, std::uintptr_t vtables_end) {
+        __try {
+            auto vtable = *reinterpret_cast<const std::uintptr_t*>(form);
+            if (vtable < vtables_begin || vtable >= vtables_end) {
+                return 0;
+            }
+            if (form->GetFormType() >= RE::FormType::Max) {
+                return 0;
+            }
+            return form->GetFormID();
+        } __except (EXCEPTION_EXECUTE_HANDLER) {
+            return 0;
+        }
+    }
+
+    struct RawGameState {
+        const char* worldspace;
+        std::uint32_t worldspace_id;
+        const char* cell;
+        std::uint32_t cell_id;
+        bool has_position;
+        RE::NiPoint3 position;
+        float days_passed;
+    };
+
+    // Read where the player is and when, guarded like read_form_id since the
+    // player or their cell may be mid-change when the game crashes
+    bool read_game_state(RawGameState& out) {
+        __try {
+            auto* player = RE::PlayerCharacter::GetSingleton();
+            if (!player) {
+                return false;
+            }
+
+            if (auto* worldspace = player->GetWorldspace()) {
+                out.worldspace = worldspace->GetFormEditorID();
+                out.worldspace_id = worldspace->GetFormID();
+            }
+            if (auto* cell = player->GetParentCell()) {
+                out.cell = cell->GetFormEditorID();
+                out.cell_id = cell->GetFormID();
+            }
+            out.position = player->GetPosition();
+            out.has_position = true;
+
+            if (auto* calendar = RE::Calendar::GetSingleton()) {
+                out.days_passed = calendar->GetDaysPassed();
+            }
+            return true;
+        } __except (EXCEPTION_EXECUTE_HANDLER) {
+            return out.has_position;
+        }
+    }
+
+    // Deepest Papyrus frames read per stack, and stacks read in total
+    constexpr std::uint32_t kMaxPapyrusDepth = 32;
+    constexpr std::size_t kMaxPapyrusFrames = 256;
+
+    struct RawPapyrusFrame {
+        std::uint32_t stack_id;
+        std::uint32_t depth;
+        const char* script;
+        const char* function;
+        std::uint32_t line;
+    };
+
+    // Copy frames from the VM's running stacks into `out`, returning how many
+    // were read. The VM lock is not taken: another thread may hold it while
+    // we crash, and a stack that changes under us only costs a partial read.
+    std::size_t read_papyrus_frames(RawPapyrusFrame* out, std::size_t capacity) {
+        std::size_t count = 0;
+        __try {
+            auto* vm = RE::BSScript::Internal::VirtualMachine::GetSingleton();
+            if (!vm) {
+                return 0;
+            }
+
+            for (auto& [id, stack] : vm->allRunningStacks) {
+                auto* frame = stack ? stack->top : nullptr;
+                for (std::uint32_t depth = 0; frame && depth < kMaxPapyrusDepth && count < capacity; ++depth) {
+                    auto* function = frame->owningFunction.get();
+                    if (function) {
+                        std::uint32_t line = 0;
+                        if (!function->TranslateIPToLineNumber(frame->instructionPointer, line)) {
+                            line = 0;
+                        }
+                        out[count++] = {
+                            id,
+                            depth,
+                            function->GetObjectTypeName().c_str(),
+                            function->GetName().c_str(),
+                            line,
+                        };
+                    }
+                    frame = frame->previousFrame;
+                }
+            }
+            return count;
+        } __except (EXCEPTION_EXECUTE_HANDLER) {
+            return count;
+        }
+    }
+
 }  // namespace
 
 // Plugin query - called by SKSE to get plugin info
@@ -46,8 +198,14 @@ extern "C" __declspec(dllexport) bool SKSEAPI SKSEPlugin_Load(
 ) {
     SKSE::Init(skse);
 
-    // Register VEH handler for crash capture
-    ctd::register_veh_handler();
+    // Register the crash handler
+    ctd::register_handler();
+
+    // Track recently opened assets for crash reports
+    ctd::install_asset_log_hook();
+
+    // Bind the CTD script's natives (CTD.AddNote, ...)
+    ctd::register_papyrus_functions();
 
     // Register for messaging events
     auto messaging = SKSE::GetMessagingInterface();
@@ -62,6 +220,47 @@ extern "C" __declspec(dllexport) bool SKSEAPI SKSEPlugin_Load(
     return true;
 }
 
+// Test crash - lets users verify crash capture (requires allow_test_crash = true)
+extern "C" __declspec(dllexport) bool trigger_test_crash() {
+    return ctd::trigger_test_crash();
+}
+
+// Post-hoc notes - lets a next-launch prompt add context to the last report.
+// Copies the ID (NUL-terminated, truncated to fit) and returns its full length.
+extern "C" __declspec(dllexport) size_t last_report_id(char* buffer, size_t size) {
+    auto id = ctd::last_report_id();
+    if (buffer && size > 0) {
+        size_t len = std::min(size - 1, id.size());
+        std::memcpy(buffer, id.data(), len);
+        buffer[len] = '\0';
+    }
+    return id.size();
+}
+
+extern "C" __declspec(dllexport) bool append_note_to_last_report(const char* text) {
+    if (!text) {
+        return false;
+    }
+    try {
+        return ctd::append_note_to_last_report(rust::Str(text));
+    } catch (const std::invalid_argument&) {
+        return false;  // Not valid UTF-8
+    }
+}
+
+// Manual reports - lets a console-command mod or in-game prompt file a report
+// with the current load order and game state when the game didn't crash.
+extern "C" __declspec(dllexport) bool submit_manual_report(const char* notes) {
+    if (!notes) {
+        return false;
+    }
+    try {
+        return ctd::submit_manual_report(rust::Str(notes));
+    } catch (const std::invalid_argument&) {
+        return false;  // Not valid UTF-8
+    }
+}
+
 namespace ctd {
 
 // Get load order from TESDataHandler
@@ -98,4 +297,74 @@ rust::String get_skse_version() {
     return rust::String(SKSE::PluginDeclaration::GetSingleton()->GetVersion().string());
 }
 
+// Get the Papyrus call stacks running when the game crashed
+rust::Vec<PapyrusFrameInfo> get_papyrus_stacks() {
+    RawPapyrusFrame raw[kMaxPapyrusFrames];
+    std::size_t count = read_papyrus_frames(raw, kMaxPapyrusFrames);
+
+    rust::Vec<PapyrusFrameInfo> frames;
+    for (std::size_t i = 0; i < count; ++i) {
+        PapyrusFrameInfo info;
+        info.stack_id = raw[i].stack_id;
+        info.depth = raw[i].depth;
+        info.script = rust::String::lossy(raw[i].script ? raw[i].script : "");
+        info.function = rust::String::lossy(raw[i].function ? raw[i].function : "");
+        info.line = raw[i].line;
+        frames.push_back(std::move(info));
+    }
+    return frames;
+}
+
+// Get the player's location and the in-game time when the game crashed
+GameStateInfo get_game_state() {
+    RawGameState raw{};
+    GameStateInfo info{};
+    info.available = read_game_state(raw);
+    info.worldspace = rust::String::lossy(raw.worldspace ? raw.worldspace : "");
+    info.worldspace_id = raw.worldspace_id;
+    info.cell = rust::String::lossy(raw.cell ? raw.cell : "");
+    info.cell_id = raw.cell_id;
+    info.has_position = raw.has_position;
+    info.x = raw.position.x;
+    info.y = raw.position.y;
+    info.z = raw.position.z;
+    info.days_passed = raw.days_passed;
+    return info;
+}
+
+// Mirror Rust warnings and errors into the plugin's SKSE log
+void log_to_skse(LogLevel level, rust::Str message) {
+    std::string text(message);
+    if (level == LogLevel::Error) {
+        SKSE::log::error("{}", text);
+    } else {
+        SKSE::log::warn("{}", text);
+    }
+}
+
+// Find a form the crashing code was working on: the first register that
+// points at an object with a game vtable, a valid form type, and a form ID.
+// The registers come in ctd_core::rtti::REGISTERS_64 order.
+std::uint32_t find_touched_form(rust::Slice<const std::uint64_t> registers) {
+    const auto rdata = REL::Module::get().segment(REL::Segment::rdata);
+    const std::uintptr_t vtables_begin = rdata.address();
+    const std::uintptr_t vtables_end = vtables_begin + rdata.size();
+
+    // Argument registers first, then the ones compilers keep objects in:
+    // RCX, RDX, R8, R9, RAX, RBX, RSI, RDI, R12-R15
+    constexpr std::size_t kOrder[] = {2, 3, 8, 9, 0, 1, 4, 5, 12, 13, 14, 15};
+
+    for (auto index : kOrder) {
+        if (index >= registers.size()) {
+            continue;
+        }
+        auto id = read_form_id(
+            reinterpret_cast<const RE::TESForm*>(registers[index]), vtables_begin, vtables_end);
+        if (id != 0) {
+            return id;
+        }
+    }
+    return 0;
+}
+
 }  // namespace ctd
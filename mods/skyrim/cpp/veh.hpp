@@ -1,8 +0,0 @@
-#pragma once
-
-namespace ctd {
-
-// Register the Vectored Exception Handler
-void register_veh_handler();
-
-}  // namespace ctd
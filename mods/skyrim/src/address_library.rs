@@ -0,0 +1,323 @@
+//! Address Library database support.
+//!
+//! The Address Library for SKSE Plugins ships one `.bin` database per game
+//! version that maps stable IDs to offsets in `SkyrimSE.exe`. Offsets move
+//! with every game patch, but IDs do not, so rewriting stack frames in terms
+//! of IDs lets crash signatures match across SE and AE builds.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+/// Module name that Address Library offsets are relative to.
+const GAME_MODULE: &str = "SkyrimSE.exe";
+
+/// Errors that can occur when loading an Address Library database.
+#[derive(Error, Debug)]
+pub enum AddressLibraryError {
+    /// Failed to read the database file.
+    #[error("Failed to read Address Library database: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// The database uses an unknown format or is corrupt.
+    #[error("Invalid Address Library database: {0}")]
+    Invalid(String),
+}
+
+/// A parsed Address Library database.
+#[derive(Debug, Clone)]
+pub struct AddressLibrary {
+    /// Game version the database was generated for.
+    version: [u32; 4],
+    /// `(offset, id)` pairs sorted by offset for nearest-ID lookup.
+    entries: Vec<(u64, u64)>,
+}
+
+impl AddressLibrary {
+    /// Loads the database matching a game version from `Data/SKSE/Plugins`.
+    ///
+    /// Accepts versions separated by `.` or `-` (e.g., "1.6.1170.0").
+    /// Looks for the AE (`versionlib-*.bin`) and SE (`version-*.bin`) names.
+    pub fn open_for_version(data_dir: &Path, game_version: &str) -> Option<Self> {
+        let parts: Vec<&str> = game_version.split(['.', '-']).collect();
+        if parts.len() != 4 {
+            return None;
+        }
+        let version = parts.join("-");
+
+        let dir = plugins_dir(data_dir);
+        [
+            dir.join(format!("versionlib-{}.bin", version)),
+            dir.join(format!("version-{}.bin", version)),
+        ]
+        .iter()
+        .find(|p| p.exists())
+        .and_then(|p| Self::load(p).ok())
+    }
+
+    /// Loads a database from a file.
+    pub fn load(path: &Path) -> Result<Self, AddressLibraryError> {
+        Self::parse(BufReader::new(File::open(path)?))
+    }
+
+    /// Parses a database in the delta-encoded format shared by SE (format 1)
+    /// and AE (format 2) releases.
+    pub fn parse(mut reader: impl Read) -> Result<Self, AddressLibraryError> {
+        let format = read_u32(&mut reader)?;
+        if format != 1 && format != 2 {
+            return Err(AddressLibraryError::Invalid(format!(
+                "unsupported format {}",
+                format
+            )));
+        }
+
+        let mut version = [0u32; 4];
+        for part in &mut version {
+            *part = read_u32(&mut reader)?;
+        }
+
+        // Module name, length-prefixed
+        let name_len = read_u32(&mut reader)? as usize;
+        let mut name = vec![0u8; name_len.min(1024)];
+        reader.read_exact(&mut name)?;
+
+        let pointer_size = u64::from(read_u32(&mut reader)?);
+        if pointer_size == 0 {
+            return Err(AddressLibraryError::Invalid("zero pointer size".into()));
+        }
+        let count = read_u32(&mut reader)? as usize;
+
+        let mut entries = Vec::with_capacity(count.min(1 << 20));
+        let (mut prev_id, mut prev_offset) = (0u64, 0u64);
+
+        for _ in 0..count {
+            let kind = read_u8(&mut reader)?;
+            let low = kind & 0x0F;
+            let high = kind >> 4;
+
+            let id = match low {
+                0 => read_u64(&mut reader)?,
+                1 => prev_id.wrapping_add(1),
+                2 => prev_id.wrapping_add(u64::from(read_u8(&mut reader)?)),
+                3 => prev_id.wrapping_sub(u64::from(read_u8(&mut reader)?)),
+                4 => prev_id.wrapping_add(u64::from(read_u16(&mut reader)?)),
+                5 => prev_id.wrapping_sub(u64::from(read_u16(&mut reader)?)),
+                6 => u64::from(read_u16(&mut reader)?),
+                7 => u64::from(read_u32(&mut reader)?),
+                _ => {
+                    return Err(AddressLibraryError::Invalid(format!(
+                        "unknown id encoding {}",
+                        low
+                    )));
+                }
+            };
+
+            // Bit 3 of the high nibble means offsets are stored in pointer-size units
+            let scaled = high & 8 != 0;
+            let base = if scaled {
+                prev_offset / pointer_size
+            } else {
+                prev_offset
+            };
+
+            let mut offset = match high & 7 {
+                0 => read_u64(&mut reader)?,
+                1 => base.wrapping_add(1),
+                2 => base.wrapping_add(u64::from(read_u8(&mut reader)?)),
+                3 => base.wrapping_sub(u64::from(read_u8(&mut reader)?)),
+                4 => base.wrapping_add(u64::from(read_u16(&mut reader)?)),
+                5 => base.wrapping_sub(u64::from(read_u16(&mut reader)?)),
+                6 => u64::from(read_u16(&mut reader)?),
+                _ => u64::from(read_u32(&mut reader)?),
+            };
+            if scaled {
+                offset = offset.wrapping_mul(pointer_size);
+            }
+
+            entries.push((offset, id));
+            prev_id = id;
+            prev_offset = offset;
+        }
+
+        entries.sort_unstable();
+        Ok(Self { version, entries })
+    }
+
+    /// Returns the game version the database was generated for.
+    pub fn version(&self) -> [u32; 4] {
+        self.version
+    }
+
+    /// Returns the number of IDs in the database.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the database contains no IDs.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Finds the closest ID at or before an offset, returning `(id, delta)`.
+    pub fn lookup(&self, offset: u64) -> Option<(u64, u64)> {
+        let idx = match self.entries.binary_search_by_key(&offset, |(o, _)| *o) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+        let (start, id) = self.entries[idx];
+        Some((id, offset - start))
+    }
+
+    /// Rewrites `SkyrimSE.exe+0xOFFSET` frames as `SkyrimSE.exe (ID 50988+0x12)`.
+    ///
+    /// Lines that do not reference the game executable, or whose offset
+    /// precedes every known ID, are left unchanged.
+    pub fn rewrite_stack_trace(&self, trace: &str) -> String {
+        trace
+            .lines()
+            .map(|line| self.rewrite_frame(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Rewrites a single stack trace line.
+    fn rewrite_frame(&self, line: &str) -> String {
+        let pattern = format!("{}+0x", GAME_MODULE);
+        let Some(start) = line.find(&pattern) else {
+            return line.to_string();
+        };
+
+        let hex_start = start + pattern.len();
+        let hex_len = line[hex_start..]
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(line.len() - hex_start);
+
+        let Ok(offset) = u64::from_str_radix(&line[hex_start..hex_start + hex_len], 16) else {
+            return line.to_string();
+        };
+        let Some((id, delta)) = self.lookup(offset) else {
+            return line.to_string();
+        };
+
+        format!(
+            "{}{} (ID {}+0x{:X}){}",
+            &line[..start],
+            GAME_MODULE,
+            id,
+            delta,
+            &line[hex_start + hex_len..]
+        )
+    }
+}
+
+/// Returns the `Data/SKSE/Plugins` directory where Address Library databases live.
+pub fn plugins_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("SKSE").join("Plugins")
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, AddressLibraryError> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> Result<u16, AddressLibraryError> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, AddressLibraryError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, AddressLibraryError> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a small format-2 database with three entries.
+    fn sample_database() -> Vec<u8> {
+        let mut db = Vec::new();
+        db.extend_from_slice(&2u32.to_le_bytes());
+        for part in [1u32, 6, 1170, 0] {
+            db.extend_from_slice(&part.to_le_bytes());
+        }
+        db.extend_from_slice(&12u32.to_le_bytes());
+        db.extend_from_slice(b"SkyrimSE.exe");
+        db.extend_from_slice(&8u32.to_le_bytes()); // pointer size
+        db.extend_from_slice(&3u32.to_le_bytes()); // count
+
+        // ID 100 at 0x1000: explicit u64 id and offset
+        db.push(0x00);
+        db.extend_from_slice(&100u64.to_le_bytes());
+        db.extend_from_slice(&0x1000u64.to_le_bytes());
+
+        // ID 101 at 0x1040: id + 1, offset + u8
+        db.push(0x21);
+        db.push(0x40);
+
+        // ID 50988 at 0x2000: explicit u32 id, scaled offset (0x1040 / 8 + 0x1F8) * 8
+        db.push(0xC7);
+        db.extend_from_slice(&50988u32.to_le_bytes());
+        db.extend_from_slice(&0x1F8u16.to_le_bytes());
+
+        db
+    }
+
+    #[test]
+    fn parses_delta_encoded_entries() {
+        let lib = AddressLibrary::parse(sample_database().as_slice()).unwrap();
+        assert_eq!(lib.version(), [1, 6, 1170, 0]);
+        assert_eq!(lib.len(), 3);
+        assert_eq!(lib.lookup(0x1000), Some((100, 0)));
+        assert_eq!(lib.lookup(0x1050), Some((101, 0x10)));
+        assert_eq!(lib.lookup(0x2012), Some((50988, 0x12)));
+        assert_eq!(lib.lookup(0x10), None);
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let mut db = sample_database();
+        db[0] = 9;
+        assert!(AddressLibrary::parse(db.as_slice()).is_err());
+    }
+
+    #[test]
+    fn rewrites_game_frames_only() {
+        let lib = AddressLibrary::parse(sample_database().as_slice()).unwrap();
+        let trace =
+            "[0] SkyrimSE.exe+0x2012 (0x7ff6a0002012)\n[1] ctd_skyrim.dll+0x2012 (0x7ffb00002012)";
+
+        let rewritten = lib.rewrite_stack_trace(trace);
+        let lines: Vec<&str> = rewritten.lines().collect();
+        assert_eq!(
+            lines[0],
+            "[0] SkyrimSE.exe (ID 50988+0x12) (0x7ff6a0002012)"
+        );
+        assert_eq!(lines[1], "[1] ctd_skyrim.dll+0x2012 (0x7ffb00002012)");
+    }
+
+    #[test]
+    fn open_for_version_finds_ae_database() {
+        let dir = std::env::temp_dir().join(format!("ctd-addrlib-{}", std::process::id()));
+        let plugins = plugins_dir(&dir);
+        std::fs::create_dir_all(&plugins).unwrap();
+        std::fs::write(plugins.join("versionlib-1-6-1170-0.bin"), sample_database()).unwrap();
+
+        let lib = AddressLibrary::open_for_version(&dir, "1.6.1170.0");
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(lib.map(|l| l.len()), Some(3));
+    }
+}
@@ -1,65 +1,314 @@
 //! Crash processing and report submission.
 
-use ctd_core::api_client::ApiClient;
-use ctd_core::crash_report::CreateCrashReport;
+use ctd_capture::handler::CrashData;
+use ctd_core::CtdError;
+use ctd_core::api_client;
+use ctd_core::blame;
+use ctd_core::crash_arena;
+use ctd_core::crash_log;
+use ctd_core::crash_report::{
+    CreateCrashReport, GameState, PapyrusFrame, ReportType, manual_report_notes,
+};
+use ctd_core::detours;
+use ctd_core::exception_tally;
+use ctd_core::file_hash;
+use ctd_core::formid;
+use ctd_core::graphics;
+use ctd_core::hardware_events;
+use ctd_core::known_bad;
+use ctd_core::load_order::ModList;
+use ctd_core::memory_map;
+use ctd_core::modpack;
+use ctd_core::save_game::latest_save_info;
+use ctd_core::submit;
+use ctd_core::throttle::Throttle;
+use ctd_core::watchdog::{self, Hang};
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::Duration;
 use tracing::{error, info};
 
+use crate::address_library::AddressLibrary;
+use crate::edition::GameEdition;
 use crate::ffi;
-use crate::ffi::ExceptionData;
-use crate::fingerprint::build_mod_list;
+use crate::ffi::{GameStateInfo, PapyrusFrameInfo};
+use crate::fingerprint::{
+    build_graphics_injector_list, build_native_plugin_list, cached_mod_list, get_data_dir,
+};
 
 /// Game ID for Skyrim Special Edition.
-const GAME_ID: &str = "skyrim-se";
+pub(crate) const GAME_ID: &str = "skyrim-se";
+
+/// What a report is about.
+enum Cause {
+    /// The crash handler caught a fatal exception.
+    Crash,
+    /// The game thread stopped responding for this long.
+    Hang(Duration),
+    /// The player filed a report with this description.
+    Manual(String),
+}
+
+/// Game context read on the crashing thread, before it is released.
+pub struct CrashContext {
+    /// Running Papyrus stacks.
+    papyrus: Vec<PapyrusFrameInfo>,
+    /// Player location and in-game time.
+    game_state: GameStateInfo,
+    /// Asset paths the game opened last, newest first.
+    recent_assets: Vec<String>,
+    /// Form ID of the form the crashing code was touching, or 0 if none was found.
+    form_id: u32,
+}
+
+impl CrashContext {
+    /// Read the game context through the C++ bridge, looking for a touched
+    /// form in `registers` (empty for hangs and manual reports).
+    pub fn capture(registers: &[u64]) -> Self {
+        Self {
+            papyrus: ffi::get_papyrus_stacks(),
+            game_state: ffi::get_game_state(),
+            recent_assets: ffi::get_recent_assets(),
+            form_id: if registers.is_empty() {
+                0
+            } else {
+                ffi::find_touched_form(registers)
+            },
+        }
+    }
+}
 
 /// Process a crash and submit it to the API.
-pub fn process_crash(data: ExceptionData) {
+///
+/// Returns the thread submitting the report, which the crash handler waits
+/// for when the process ends next.
+pub fn process_crash(data: CrashData, context: CrashContext) -> JoinHandle<()> {
     // Spawn a thread for submission to avoid blocking
     std::thread::spawn(move || {
-        if let Err(e) = submit_crash_report(data) {
+        if let Err(e) = submit_crash_report(data, context, Cause::Crash) {
             error!("Failed to submit crash report: {}", e);
         }
+    })
+}
+
+/// Report a hang the watchdog detected, on its monitor thread.
+pub fn process_hang(hang: Hang) {
+    let data = CrashData {
+        stack_trace: hang.stack_trace,
+        ..CrashData::default()
+    };
+
+    // The game thread is stuck, so its state reads as it would after a crash
+    let context = CrashContext::capture(&[]);
+
+    if let Err(e) = submit_crash_report(data, context, Cause::Hang(hang.stalled_for)) {
+        error!("Failed to submit hang report: {}", e);
+    }
+}
+
+/// File a report the player asked for, without a crash.
+///
+/// The report carries every thread's stack and is sent in the background.
+///
+/// # Errors
+///
+/// Returns `CtdError::Validation` if `notes` is empty or too long.
+pub fn process_manual(notes: &str) -> Result<(), CtdError> {
+    let notes = manual_report_notes(notes)?.to_string();
+
+    // Read the game state on the caller's thread, before handing off
+    let context = CrashContext::capture(&[]);
+
+    std::thread::spawn(move || {
+        let data = CrashData {
+            stack_trace: watchdog::capture_all_threads(),
+            ..CrashData::default()
+        };
+        if let Err(e) = submit_crash_report(data, context, Cause::Manual(notes)) {
+            error!("Failed to submit manual report: {}", e);
+        }
     });
+    Ok(())
 }
 
-/// Build and submit a crash report.
+/// Build and submit a report for a crash, hang, or player request.
 fn submit_crash_report(
-    data: ExceptionData,
+    data: CrashData,
+    context: CrashContext,
+    cause: Cause,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Get load order from game and build mod list with file hashes
+    // Honor [submission] sample_rate and max_reports_per_hour; a report the
+    // player asked for isn't sampled
+    let throttle = Throttle::load();
+    let verdict = match cause {
+        Cause::Manual(_) => throttle.admit_requested(),
+        Cause::Crash | Cause::Hang(_) => throttle.admit(),
+    };
+    if !verdict.is_allowed() {
+        info!("Report not submitted: {}", verdict);
+        crash_arena::discard_record();
+        return Ok(());
+    }
+
+    // Get load order from game, with the hashes made when data loaded
     let mods = ffi::get_load_order();
-    let mod_names: Vec<String> = mods.into_iter().map(|m| m.name).collect();
-    let mod_list = build_mod_list(mod_names);
+    let plugins = mods.into_iter().map(|m| (m.name, m.is_light)).collect();
+    let mod_list = cached_mod_list(plugins);
+
+    // Work out which mod shipped the faulting DLL
+    let suspected_mod = data.faulting_module.as_deref().and_then(|module| {
+        let installed = blame::installed_mods();
+        blame::suspected_mod(Path::new(module), &mod_list, &installed)
+    });
+
+    // Name the plugin that defines the form the crashing code was touching
+    let touched_form = (context.form_id != 0).then(|| formid::describe(context.form_id, &mod_list));
+
+    // Name the player's cell and worldspace, by plugin if editor IDs weren't kept
+    let game_state = context
+        .game_state
+        .available
+        .then(|| to_game_state(&context.game_state, &mod_list));
+
+    // Compare the most recent save's plugins with the live load order
+    let edition = GameEdition::detect();
+    let save_game = latest_save_info(
+        edition.map_or("Skyrim Special Edition", GameEdition::my_games_folder),
+        &mod_list,
+    );
+
+    // Rewrite game frames as Address Library IDs so signatures survive patches
+    let game_version = ffi::get_game_version();
+    let stack_trace = get_data_dir()
+        .and_then(|dir| AddressLibrary::open_for_version(&dir, &game_version))
+        .map(|lib| lib.rewrite_stack_trace(&data.stack_trace))
+        .unwrap_or_else(|| data.stack_trace.clone());
 
     // Build the crash report
     let mut builder = CreateCrashReport::builder()
         .game_id(GAME_ID)
-        .game_version(ffi::get_game_version())
-        .stack_trace(&data.stack_trace)
-        .exception_code(format!("0x{:08X}", data.code))
-        .exception_address(format!("0x{:016X}", data.address))
+        .game_version(game_version)
+        .stack_trace(&stack_trace)
         .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
         .script_extender_version(ffi::get_skse_version())
+        .native_plugins(build_native_plugin_list())
+        .graphics_injectors(build_graphics_injector_list())
+        .papyrus_stacks(papyrus_frames(context.papyrus))
+        .recent_assets(context.recent_assets)
+        .handled_exceptions(exception_tally::snapshot())
+        .hardware_events(hardware_events::recent())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(GAME_ID))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .recent_frame_times()
         .crashed_now();
 
+    let address_region = (matches!(cause, Cause::Crash) && data.faulting_module.is_none())
+        .then(|| memory_map::address_region(data.exception_address))
+        .flatten();
+
+    builder = match cause {
+        Cause::Crash => builder
+            .exception_code(format!("0x{:08X}", data.exception_code))
+            .exception_address(format!("0x{:016X}", data.exception_address))
+            .probable_objects(data.probable_objects)
+            .exception_chain(data.exception_chain),
+        Cause::Hang(stalled_for) => builder.report_type(ReportType::Hang).notes(format!(
+            "Game thread stopped responding for {} seconds",
+            stalled_for.as_secs()
+        )),
+        Cause::Manual(notes) => builder.report_type(ReportType::Manual).notes(notes),
+    };
+
     // Add faulting module if available
-    if !data.faulting_module.is_empty() {
-        builder = builder.faulting_module(&data.faulting_module);
+    if let Some(module) = &data.faulting_module {
+        builder = builder
+            .faulting_module(module)
+            .hooked_functions(detours::scan(module));
     }
 
-    let report = builder.build()?;
+    if let Some(region) = address_region {
+        builder = builder.address_region(region);
+    }
+
+    builder = builder.exception_parameters(&data.exception_parameters);
+    if let Some(error) = data.in_page_error {
+        builder = builder.in_page_error(error);
+    }
+
+    if let Some(edition) = edition {
+        info!("Game edition: {}", edition);
+        builder = builder.game_edition(edition.as_str());
+    }
+
+    if let Some(suspect) = suspected_mod {
+        info!("Suspected mod: {}", suspect);
+        builder = builder.suspected_mod(suspect);
+    }
+
+    if let Some(state) = game_state {
+        builder = builder.game_state(state);
+    }
 
-    // Create runtime for async API call
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()?;
+    if let Some(save) = save_game {
+        builder = builder.save_game(save);
+    }
 
-    // Submit the report
-    let response = rt.block_on(async {
-        let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
+    if let Some(form) = touched_form {
+        info!("Crash touched form {}", form);
+        builder = builder.notes(format!("Last touched form: {}", form));
+    }
 
-        client.submit_crash_report(&report).await
-    })?;
+    let report = builder.build()?;
 
+    // Mirror crashes in Crash Logger's format for community analyzers
+    if let Some(path) = crash_log::write_if_enabled(&report) {
+        info!("Crash log written to {:?}", path);
+    }
+
+    let response = submit::submit_report(&report)?;
     info!("Crash report submitted: {}", response.id);
     Ok(())
 }
+
+/// Convert Papyrus frames from the C++ side into report frames.
+fn papyrus_frames(frames: Vec<PapyrusFrameInfo>) -> Vec<PapyrusFrame> {
+    frames
+        .into_iter()
+        .map(|f| PapyrusFrame {
+            stack_id: f.stack_id,
+            depth: f.depth,
+            script: f.script,
+            function: f.function,
+            line: (f.line != 0).then_some(f.line),
+        })
+        .collect()
+}
+
+/// Convert the C++ game state into the report's `gameState` section.
+///
+/// Editor IDs are only kept by the game with a mod like po3's Tweaks, so
+/// forms without one are named by ID and owning plugin instead.
+fn to_game_state(info: &GameStateInfo, mod_list: &ModList) -> GameState {
+    let name = |editor_id: &str, form_id: u32| {
+        if !editor_id.is_empty() {
+            Some(editor_id.to_string())
+        } else if form_id != 0 {
+            Some(formid::describe(form_id, mod_list))
+        } else {
+            None
+        }
+    };
+
+    GameState {
+        worldspace: name(&info.worldspace, info.worldspace_id),
+        cell: name(&info.cell, info.cell_id),
+        position: info.has_position.then_some([info.x, info.y, info.z]),
+        days_passed: Some(info.days_passed).filter(|days| *days > 0.0),
+    }
+}
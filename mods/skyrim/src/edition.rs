@@ -0,0 +1,137 @@
+//! Skyrim runtime edition detection.
+//!
+//! Skyrim ships as several incompatible executables: Special Edition
+//! (1.5.x, with 1.5.97 the long-lived modding target), Anniversary Edition
+//! (1.6.x), Skyrim VR (1.4.x), and the GOG release of AE. Offsets in a stack
+//! trace only mean something for the runtime they came from, so reports
+//! carry a normalized edition alongside the raw version.
+
+use std::path::Path;
+
+use ctd_core::version::{get_dll_version, get_product_name};
+
+/// DLL only shipped with the GOG release (GOG Galaxy's client library).
+const GOG_MARKER: &str = "Galaxy64.dll";
+
+/// A Skyrim runtime edition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameEdition {
+    /// Special Edition, 1.5.x on Steam.
+    Se,
+    /// Anniversary Edition, 1.6.x on Steam.
+    Ae,
+    /// Skyrim VR, 1.4.x.
+    Vr,
+    /// The GOG release.
+    Gog,
+}
+
+impl GameEdition {
+    /// Normalized value reported as `gameEdition`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Se => "SE",
+            Self::Ae => "AE",
+            Self::Vr => "VR",
+            Self::Gog => "GOG",
+        }
+    }
+
+    /// Folder under `Documents/My Games` holding this edition's INIs and saves.
+    pub fn my_games_folder(self) -> &'static str {
+        match self {
+            Self::Se | Self::Ae => "Skyrim Special Edition",
+            Self::Vr => "Skyrim VR",
+            Self::Gog => "Skyrim Special Edition GOG",
+        }
+    }
+
+    /// Classifies a runtime from its executable's version and product name.
+    ///
+    /// `is_gog` is set when the game directory holds GOG-only files.
+    /// Returns `None` for versions outside the SE/AE/VR ranges.
+    pub fn classify(version: &str, product_name: &str, is_gog: bool) -> Option<Self> {
+        if product_name.to_ascii_lowercase().contains("vr") || version.starts_with("1.4.") {
+            Some(Self::Vr)
+        } else if is_gog {
+            Some(Self::Gog)
+        } else if version.starts_with("1.5.") {
+            Some(Self::Se)
+        } else if version.starts_with("1.6.") {
+            Some(Self::Ae)
+        } else {
+            None
+        }
+    }
+
+    /// Detects the edition of the running game from its executable.
+    pub fn detect() -> Option<Self> {
+        let exe = std::env::current_exe().ok()?;
+        Self::detect_at(&exe)
+    }
+
+    /// Detects the edition of the game executable at `exe`.
+    ///
+    /// Falls back to the executable's file name (e.g., "SkyrimVR.exe") when
+    /// it has no product name.
+    pub fn detect_at(exe: &Path) -> Option<Self> {
+        let version = get_dll_version(exe).ok()?;
+        let product_name = get_product_name(exe)
+            .ok()
+            .or_else(|| exe.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        let is_gog = exe
+            .parent()
+            .is_some_and(|dir| dir.join(GOG_MARKER).is_file());
+
+        Self::classify(&version, &product_name, is_gog)
+    }
+}
+
+impl std::fmt::Display for GameEdition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_steam_builds_by_version() {
+        assert_eq!(
+            GameEdition::classify("1.5.97.0", "Skyrim Special Edition", false),
+            Some(GameEdition::Se)
+        );
+        assert_eq!(
+            GameEdition::classify("1.6.1170.0", "Skyrim Special Edition", false),
+            Some(GameEdition::Ae)
+        );
+        assert_eq!(
+            GameEdition::classify("1.9.32.0", "Skyrim Special Edition", false),
+            None
+        );
+    }
+
+    #[test]
+    fn classifies_vr_and_gog() {
+        assert_eq!(
+            GameEdition::classify("1.4.15.0", "Skyrim VR", false),
+            Some(GameEdition::Vr)
+        );
+        assert_eq!(
+            GameEdition::classify("1.4.15.0", "SkyrimVR", false),
+            Some(GameEdition::Vr)
+        );
+        assert_eq!(
+            GameEdition::classify("1.6.1179.0", "Skyrim Special Edition", true),
+            Some(GameEdition::Gog)
+        );
+        assert_eq!(GameEdition::Gog.to_string(), "GOG");
+        assert_eq!(
+            GameEdition::Gog.my_games_folder(),
+            "Skyrim Special Edition GOG"
+        );
+    }
+}
@@ -1,30 +1,30 @@
 //! SKSE64 plugin for Skyrim Special Edition crash capture.
 //!
 //! This crate provides the Rust side of a hybrid C++/Rust SKSE plugin.
-//! The C++ layer handles SKSE registration and VEH setup, while Rust
-//! handles crash processing and API submission.
+//! The C++ layer handles SKSE registration and reads game state, while
+//! Rust registers the crash handler and builds and submits reports.
 
+pub mod address_library;
 mod crash;
+pub mod edition;
 pub mod fingerprint;
 
-use tracing::info;
+use std::thread::JoinHandle;
+
+use ctd_capture::handler::{self, CrashData, HandlerOptions};
+use ctd_core::abi::{add_breadcrumb, add_note, report_handled_error, set_context};
+use ctd_core::coexistence::{self, CoexistencePlan};
+use ctd_core::config::Config;
+use ctd_core::last_report::LastReport;
+use ctd_core::{frame_times, uptime, watchdog};
+use tracing::{info, warn};
+
+use crate::edition::GameEdition;
+use crate::fingerprint::build_crash_logger_list;
 
 /// CXX bridge between C++ and Rust.
 #[cxx::bridge(namespace = "ctd")]
 mod ffi {
-    /// Exception data passed from C++ VEH handler.
-    #[derive(Debug, Clone)]
-    struct ExceptionData {
-        /// Windows exception code (e.g., 0xC0000005).
-        code: u32,
-        /// Address where the exception occurred.
-        address: u64,
-        /// Formatted stack trace with module offsets.
-        stack_trace: String,
-        /// Module name where the crash occurred (if known).
-        faulting_module: String,
-    }
-
     /// Mod information from TESDataHandler.
     #[derive(Debug, Clone)]
     struct ModInfo {
@@ -36,16 +36,90 @@ mod ffi {
         is_light: bool,
     }
 
+    /// One frame of a Papyrus call stack running at crash time.
+    #[derive(Debug, Clone)]
+    struct PapyrusFrameInfo {
+        /// VM stack the frame belongs to.
+        stack_id: u32,
+        /// Position in the stack, 0 being the innermost call.
+        depth: u32,
+        /// Script (object type) name.
+        script: String,
+        /// Function name.
+        function: String,
+        /// Source line, or 0 if the script was compiled without line info.
+        line: u32,
+    }
+
+    /// Where the player was, and when, at crash time.
+    #[derive(Debug, Clone)]
+    struct GameStateInfo {
+        /// False if the player doesn't exist yet (e.g., in the main menu).
+        available: bool,
+        /// Worldspace editor ID, or empty if unknown or in an interior.
+        worldspace: String,
+        /// Worldspace form ID, or 0 in an interior.
+        worldspace_id: u32,
+        /// Cell editor ID, or empty if the game didn't keep it.
+        cell: String,
+        /// Cell form ID, or 0 if the player has no cell.
+        cell_id: u32,
+        /// Whether `x`, `y`, and `z` were read.
+        has_position: bool,
+        /// Player position.
+        x: f32,
+        y: f32,
+        z: f32,
+        /// In-game days passed since the start of the game.
+        days_passed: f32,
+    }
+
+    /// Severity of a message mirrored into the SKSE log.
+    enum LogLevel {
+        Warn,
+        Error,
+    }
+
     // Functions exported from Rust to C++
     extern "Rust" {
         /// Initialize the Rust side of the plugin.
         fn init();
 
+        /// Register the crash handler, per `[handler]` and `[coexistence]`.
+        fn register_handler();
+
         /// Called when SKSE's kDataLoaded message is received.
         fn on_data_loaded();
 
-        /// Handle a crash from the VEH handler.
-        fn handle_crash(data: ExceptionData);
+        /// Record that the game thread is alive; called once per frame.
+        fn watchdog_beat();
+
+        /// Called when SKSE reports that the game saved.
+        fn on_game_saved();
+
+        /// Crash the game on purpose if `allow_test_crash` is set in ctd.toml.
+        fn trigger_test_crash() -> bool;
+
+        /// ID of the last report submitted for this game, or empty if none.
+        fn last_report_id() -> String;
+
+        /// Attach a note to the last report submitted for this game.
+        fn append_note_to_last_report(text: &str) -> bool;
+
+        /// File a report without a crash, with the player's description.
+        fn submit_manual_report(notes: &str) -> bool;
+
+        /// Record a step another mod took, for `ctd_add_breadcrumb`.
+        fn add_breadcrumb(source: &str, message: &str) -> bool;
+
+        /// Set or (with an empty value) remove a context key, for `ctd_set_context_kv`.
+        fn set_context(key: &str, value: &str) -> bool;
+
+        /// Record an error another mod handled, for `ctd_report_handled_error`.
+        fn report_handled_error(source: &str, message: &str) -> bool;
+
+        /// Leave a note for the next report, for Papyrus `CTD.AddNote`.
+        fn add_note(source: &str, text: &str) -> bool;
     }
 
     // Functions imported from C++ to Rust
@@ -60,26 +134,183 @@ mod ffi {
 
         /// Get the SKSE version string.
         fn get_skse_version() -> String;
+
+        /// Get the frames of every running Papyrus stack.
+        fn get_papyrus_stacks() -> Vec<PapyrusFrameInfo>;
+
+        /// Get the player's location and the in-game time.
+        fn get_game_state() -> GameStateInfo;
+
+        /// Get the asset paths the game opened last, newest first.
+        fn get_recent_assets() -> Vec<String>;
+
+        /// Form ID of a form the registers (in `ctd_core::rtti::REGISTERS_64`
+        /// order) point at, or 0 if none.
+        fn find_touched_form(registers: &[u64]) -> u32;
+
+        /// Write a message to the SKSE log.
+        fn log_to_skse(level: LogLevel, message: &str);
     }
 }
 
 /// Initialize the Rust side of the plugin.
 pub fn init() {
+    if let Some(path) = ctd_core::logging::init_with_host(crash::GAME_ID, forward_to_skse) {
+        info!("Logging to {}", path.display());
+    }
     info!("CTD Crash Reporter initializing");
+
+    ctd_core::startup::init();
+
+    // Report freezes once the game loop starts beating the watchdog
+    let watchdog_config = Config::load().unwrap_or_default().watchdog;
+    if let Err(e) = watchdog::start(&watchdog_config, crash::process_hang) {
+        warn!("Failed to start freeze watchdog: {}", e);
+    }
+
+    match coexistence_plan(&Config::load().unwrap_or_default()) {
+        CoexistencePlan::Standalone => {}
+        CoexistencePlan::Passive(logger) => {
+            info!(
+                "{} is installed; capturing crashes without a stack walk",
+                logger
+            );
+        }
+        CoexistencePlan::ImportLog(logger) => {
+            info!("{} is installed; submitting its crash logs instead", logger);
+            coexistence::spawn_log_import(
+                logger,
+                GameEdition::detect()
+                    .map_or("Skyrim Special Edition", GameEdition::my_games_folder),
+                crash::GAME_ID,
+            );
+        }
+    }
+}
+
+/// Mirrors a warning or error into the SKSE log, where users look first.
+fn forward_to_skse(level: tracing::Level, message: &str) {
+    let level = if level == tracing::Level::ERROR {
+        ffi::LogLevel::Error
+    } else {
+        ffi::LogLevel::Warn
+    };
+    ffi::log_to_skse(level, message);
+}
+
+/// Register the crash handler, per `[handler]` and `[coexistence]`.
+pub fn register_handler() {
+    let config = Config::load().unwrap_or_default();
+    let options = HandlerOptions::from_config(&config);
+    let options = match coexistence_plan(&config) {
+        CoexistencePlan::Standalone => options,
+        CoexistencePlan::Passive(_) => options.passive(),
+        // Another crash logger handles crashes
+        CoexistencePlan::ImportLog(_) => return,
+    };
+
+    if let Err(e) = handler::register(
+        crash::GAME_ID,
+        &ffi::get_game_version(),
+        options,
+        report_crash,
+    ) {
+        warn!("Failed to register crash handler: {}", e);
+    }
+}
+
+/// Decide how to share crashes with other crash loggers, per `[coexistence]`.
+fn coexistence_plan(config: &Config) -> CoexistencePlan {
+    coexistence::plan(config.coexistence.mode, &build_crash_logger_list())
 }
 
 /// Called when game data is loaded.
 pub fn on_data_loaded() {
     info!("Game data loaded, load order available");
+
+    // Hash the load order now so a crash doesn't have to
+    let plugins = ffi::get_load_order()
+        .into_iter()
+        .map(|m| (m.name, m.is_light))
+        .collect();
+    fingerprint::fingerprint_in_background(plugins);
 }
 
-/// Handle a crash from the VEH handler.
-pub fn handle_crash(data: ffi::ExceptionData) {
+/// Build and send the report for a crash the handler captured.
+fn report_crash(data: CrashData) -> Option<JoinHandle<()>> {
     info!(
-        "Crash captured: 0x{:08X} at 0x{:016X}",
-        data.code, data.address
+        "Crash captured: 0x{:08X} ({}) at 0x{:016X}",
+        data.exception_code,
+        ctd_capture::exception::exception_code_name(data.exception_code),
+        data.exception_address
     );
 
-    // Delegate to crash module
-    crash::process_crash(data);
+    // Read game state now, while the crashing thread is still stopped
+    let context = crash::CrashContext::capture(&data.registers);
+
+    Some(crash::process_crash(data, context))
+}
+
+/// Record that the game thread is alive and time the frame, once per frame.
+pub fn watchdog_beat() {
+    watchdog::beat();
+    frame_times::record_frame();
+}
+
+/// Remember when the game last saved, for the next crash report.
+pub fn on_game_saved() {
+    uptime::record_save();
+}
+
+/// Crash the game on purpose if `allow_test_crash` is set in ctd.toml.
+///
+/// Returns false (and logs why) when test crashes are disabled.
+pub fn trigger_test_crash() -> bool {
+    match ctd_core::test_crash::trigger(&Config::load().unwrap_or_default()) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("{}", e);
+            false
+        }
+    }
+}
+
+/// ID of the last report submitted for this game, or empty if none.
+pub fn last_report_id() -> String {
+    LastReport::load(crash::GAME_ID)
+        .map(|report| report.id)
+        .unwrap_or_default()
+}
+
+/// Attach a note to the last report submitted for this game.
+///
+/// Blocks until the note is sent. Returns false (and logs why) on failure.
+pub fn append_note_to_last_report(text: &str) -> bool {
+    match ctd_core::last_report::append_note_to_last(crash::GAME_ID, text) {
+        Ok(id) => {
+            info!("Note attached to report {}", id);
+            true
+        }
+        Err(e) => {
+            warn!("Failed to attach note: {}", e);
+            false
+        }
+    }
+}
+
+/// File a report without a crash, with the player's description.
+///
+/// Returns once the game state is read; the report is sent in the
+/// background. Returns false (and logs why) if the description is invalid.
+pub fn submit_manual_report(notes: &str) -> bool {
+    match crash::process_manual(notes) {
+        Ok(()) => {
+            info!("Filing manual report");
+            true
+        }
+        Err(e) => {
+            warn!("Cannot file report: {}", e);
+            false
+        }
+    }
 }
@@ -0,0 +1,100 @@
+//! Detecting the running UE5 game from its executable.
+//!
+//! There is no UE4SS mod to pass a game name, so the project name in the
+//! shipping executable (`Stalker2-Win64-Shipping.exe` is `Stalker2`) stands
+//! in for it. Games in the `ctd_ue5::games` registry get their canonical ID
+//! and quirks; the rest get a slug of the project name and the generic UE5
+//! profile.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use ctd_core::version::get_dll_version;
+use ctd_ue5::games::{self, Game};
+
+/// Suffixes of Unreal's packaged executables, after the project name.
+const SHIPPING_SUFFIXES: &[&str] = &[
+    "-Win64-Shipping",
+    "-WinGDK-Shipping",
+    "-Win64-Test",
+    "-Win64-Development",
+];
+
+/// The game, resolved once at startup.
+static GAME: OnceLock<Game> = OnceLock::new();
+
+/// Returns the Unreal project name from an executable file name.
+///
+/// Executables that don't follow Unreal's naming keep their stem.
+pub fn project_name(executable: &str) -> &str {
+    let stem = Path::new(executable)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(executable);
+    SHIPPING_SUFFIXES
+        .iter()
+        .find_map(|suffix| {
+            let split = stem.len().checked_sub(suffix.len())?;
+            let (project, tail) = (stem.get(..split)?, stem.get(split..)?);
+            tail.eq_ignore_ascii_case(suffix).then_some(project)
+        })
+        .unwrap_or(stem)
+}
+
+/// Resolves a game from its executable file name.
+pub fn resolve(executable: &str) -> Game {
+    games::resolve(project_name(executable), Some(executable))
+}
+
+/// Returns the running game, resolving it on first use.
+pub fn current() -> &'static Game {
+    GAME.get_or_init(|| {
+        let executable = std::env::current_exe().ok();
+        let executable = executable
+            .as_deref()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        resolve(&executable)
+    })
+}
+
+/// Returns the directory of the game executable, `<Project>/Binaries/Win64`.
+pub fn get_game_directory() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .parent()
+        .map(Path::to_path_buf)
+}
+
+/// Gets the game version from the running executable, or "unknown".
+pub fn get_game_version() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| get_dll_version(&exe).ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_name() {
+        assert_eq!(project_name("Stalker2-Win64-Shipping.exe"), "Stalker2");
+        assert_eq!(project_name("b1-WinGDK-Shipping.exe"), "b1");
+        assert_eq!(project_name("MyGame-win64-shipping.EXE"), "MyGame");
+        assert_eq!(project_name("MyGame.exe"), "MyGame");
+    }
+
+    #[test]
+    fn test_resolve_known_and_generic() {
+        let stalker = resolve("Stalker2-Win64-Shipping.exe");
+        assert_eq!(stalker.id, "stalker-2");
+        assert_eq!(stalker.quirks.pak_mod_dirs, ["~mods"]);
+
+        let generic = resolve("Tiny_Tactics-Win64-Shipping.exe");
+        assert_eq!(generic.id, "tiny-tactics");
+        assert_eq!(generic.quirks, games::Quirks::default());
+    }
+}
@@ -0,0 +1,108 @@
+//! CTD Crash Reporter for UE5 games without UE4SS
+//!
+//! A proxy DLL that captures crashes via the shared Vectored Exception
+//! Handler in `ctd-capture` and submits crash reports to the CTD backend
+//! API, with the game's pak mods as the load order. It needs no Lua host
+//! or C++ bridge, so it works in games UE4SS doesn't support, such as
+//! S.T.A.L.K.E.R. 2.
+//!
+//! ## Installation
+//!
+//! Rename the compiled `ctd_ue5_standalone.dll` to `dinput8.dll` and place
+//! it next to the game executable (`<Project>/Binaries/Win64`). It forwards
+//! DirectInput to the system `dinput8.dll`. An ASI loader or any other
+//! injector can load it under its own name instead.
+//!
+//! ## Features
+//!
+//! - Detects the game from its executable, using the `ctd_ue5::games`
+//!   registry, with a generic UE5 profile for unknown titles
+//! - Reports `Content/Paks/~mods` (and the game's other pak mod folders)
+//! - Fire-and-forget submission (never blocks the game)
+//!
+//! ## Platform Support
+//!
+//! This crate is designed for Windows (x86_64-pc-windows-msvc) but can be
+//! compiled on other platforms for development purposes. Non-Windows builds
+//! produce a stub library.
+
+// Allow dead code on non-Windows platforms where the actual implementation isn't used
+#![cfg_attr(not(windows), allow(dead_code, unused_imports))]
+
+pub mod game;
+pub mod mod_scanner;
+pub mod proxy;
+pub mod report;
+
+#[cfg(windows)]
+use ctd_capture::handler::{self, HandlerOptions};
+#[cfg(windows)]
+use ctd_core::config::Config;
+#[cfg(windows)]
+use tracing::{error, info};
+
+/// DLL entry point.
+///
+/// The loader lock is held here, so startup runs on a thread of its own.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub extern "system" fn DllMain(
+    _module: windows::Win32::Foundation::HINSTANCE,
+    reason: u32,
+    _reserved: *mut std::ffi::c_void,
+) -> windows::Win32::Foundation::BOOL {
+    use windows::Win32::System::SystemServices::DLL_PROCESS_ATTACH;
+
+    if reason == DLL_PROCESS_ATTACH {
+        std::thread::spawn(start);
+    }
+    windows::Win32::Foundation::TRUE
+}
+
+/// Detects the game, registers the crash handler, and caches the mod list.
+#[cfg(windows)]
+fn start() {
+    let game = game::current();
+    if let Some(path) = ctd_core::logging::init(&game.id) {
+        info!("Logging to {}", path.display());
+    }
+
+    info!("CTD Crash Reporter initializing for {}...", game.id);
+
+    ctd_core::startup::init();
+
+    let options = HandlerOptions::from_config(&Config::load().unwrap_or_default());
+    if let Err(e) = handler::register(
+        &game.id,
+        &game::get_game_version(),
+        options,
+        report::submit_async,
+    ) {
+        error!("Failed to register crash handler: {}", e);
+    } else {
+        info!("VEH crash handler registered");
+    }
+
+    // Cache mod list on startup; the crash path only copies it
+    let count = mod_scanner::scan_and_cache();
+    info!("Cached {} pak mods", count);
+
+    info!("CTD Crash Reporter initialized successfully");
+}
+
+// Breadcrumbs, context, and handled errors from other mods (see ctd.h)
+#[cfg(windows)]
+ctd_core::export_abi!();
+
+// ===========================================================================
+// Non-Windows stubs for development/testing on other platforms
+// ===========================================================================
+
+/// Stub initialization function for non-Windows platforms.
+///
+/// This is only used for development testing - the actual plugin
+/// functionality only works on Windows.
+#[cfg(not(windows))]
+pub fn init() {
+    eprintln!("ctd-ue5-standalone: This plugin only works on Windows");
+}
@@ -0,0 +1,47 @@
+//! Pak mod enumeration for games without UE4SS.
+//!
+//! Without UE4SS there are no `Mods/` script or DLL mods, so the load order
+//! is the game's pak mod folders (`~mods` and the rest of its quirks'
+//! `pak_mod_dirs`), scanned by `ctd_ue5::fingerprint::scan_pak_mods`.
+
+use std::sync::RwLock;
+
+use ctd_core::load_order::ModList;
+use ctd_ue5::fingerprint::scan_pak_mods;
+use tracing::info;
+
+use crate::game;
+
+/// Mods found at startup, for the crash path to copy.
+static CACHED_MODS: RwLock<Option<ModList>> = RwLock::new(None);
+
+/// Scans the game's pak mods and caches them for crash reports.
+///
+/// Returns the number of mods found.
+pub fn scan_and_cache() -> usize {
+    let Some(game_dir) = game::get_game_directory() else {
+        info!("Game directory unknown; no mods to scan");
+        return 0;
+    };
+    let mut mods = ModList::new();
+    for (index, entry) in scan_pak_mods(&game_dir, game::current().quirks.pak_mod_dirs)
+        .iter()
+        .enumerate()
+    {
+        mods.push(entry.clone().with_index(index as u32));
+    }
+    let count = mods.len();
+    *CACHED_MODS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(mods);
+    count
+}
+
+/// Returns the cached mods, or an empty list if no scan has finished.
+pub fn get_cached_or_empty() -> ModList {
+    CACHED_MODS
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
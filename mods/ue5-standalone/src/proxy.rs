@@ -0,0 +1,89 @@
+//! The `dinput8.dll` proxy.
+//!
+//! A game that loads `dinput8.dll` finds it in its own directory before the
+//! system one, which is how this DLL gets into the process without a
+//! script extender. The game still needs DirectInput, so
+//! `DirectInput8Create` forwards to the real `dinput8.dll` in the system
+//! directory, loaded on the first call.
+
+#[cfg(windows)]
+use std::ffi::c_void;
+#[cfg(windows)]
+use std::sync::OnceLock;
+
+#[cfg(windows)]
+use windows::Win32::Foundation::{E_FAIL, HINSTANCE};
+#[cfg(windows)]
+use windows::core::{GUID, HRESULT};
+
+/// Signature of `DirectInput8Create`.
+#[cfg(windows)]
+type DirectInput8CreateFn = unsafe extern "system" fn(
+    HINSTANCE,
+    u32,
+    *const GUID,
+    *mut *mut c_void,
+    *mut c_void,
+) -> HRESULT;
+
+/// The system `DirectInput8Create`, or `None` if it could not be loaded.
+#[cfg(windows)]
+static SYSTEM_CREATE: OnceLock<Option<DirectInput8CreateFn>> = OnceLock::new();
+
+/// Forwards to the system `DirectInput8Create`.
+///
+/// Returns `E_FAIL` if the system `dinput8.dll` could not be loaded.
+///
+/// # Safety
+///
+/// Same contract as the system `DirectInput8Create`.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn DirectInput8Create(
+    instance: HINSTANCE,
+    version: u32,
+    riid: *const GUID,
+    out: *mut *mut c_void,
+    outer: *mut c_void,
+) -> HRESULT {
+    match system_create() {
+        // SAFETY: The caller's arguments are passed through unchanged
+        Some(create) => unsafe { create(instance, version, riid, out, outer) },
+        None => {
+            tracing::error!("System dinput8.dll not found; DirectInput unavailable");
+            E_FAIL
+        }
+    }
+}
+
+/// Loads `DirectInput8Create` from the system directory's `dinput8.dll`.
+///
+/// Never called from `DllMain`: the game calls it once it is running, so
+/// loading another DLL here is safe.
+#[cfg(windows)]
+fn system_create() -> Option<DirectInput8CreateFn> {
+    *SYSTEM_CREATE.get_or_init(|| {
+        use windows::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+        use windows::Win32::System::SystemInformation::GetSystemDirectoryW;
+        use windows::core::{HSTRING, s};
+
+        let mut dir = [0u16; 260];
+        // SAFETY: The buffer is valid for its length
+        let len = unsafe { GetSystemDirectoryW(Some(&mut dir)) } as usize;
+        if len == 0 || len > dir.len() {
+            return None;
+        }
+        let path = format!("{}\\dinput8.dll", String::from_utf16_lossy(&dir[..len]));
+
+        // SAFETY: The path is a valid wide string; the module stays loaded
+        let module = unsafe { LoadLibraryW(&HSTRING::from(path)) }.ok()?;
+        // SAFETY: The name is a static, null-terminated string
+        let create = unsafe { GetProcAddress(module, s!("DirectInput8Create")) }?;
+        // SAFETY: The system export has the DirectInput8Create signature
+        Some(unsafe {
+            std::mem::transmute::<unsafe extern "system" fn() -> isize, DirectInput8CreateFn>(
+                create,
+            )
+        })
+    })
+}
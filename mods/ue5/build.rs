@@ -1,4 +1,6 @@
 fn main() {
+    // Only the UE4SS mods link the C++ half of the bridge
+    #[cfg(feature = "ue4ss")]
     cxx_build::bridge("src/lib.rs")
         .file("cpp/bridge.cpp")
         .include("cpp")
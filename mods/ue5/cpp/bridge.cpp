@@ -17,4 +17,8 @@ rust::String get_game_version() {
     return rust::String("unknown");
 }
 
+void log_to_ue4ss(LogLevel, rust::Str) {
+    // No host log by default - game-specific mod should override
+}
+
 }  // namespace ctd
@@ -1,5 +1,6 @@
 #pragma once
 
+#include <cstdint>
 #include <string>
 #include <vector>
 #include "rust/cxx.h"
@@ -9,6 +10,9 @@ namespace ctd {
 /// Plugin info for load order (matches Rust struct)
 struct PluginInfo;
 
+/// Severity of a mirrored log message (matches Rust enum)
+enum class LogLevel : std::uint8_t;
+
 /// Get the load order from the game
 /// This is implemented by the game-specific UE4SS mod
 rust::Vec<PluginInfo> get_load_order();
@@ -17,4 +21,8 @@ rust::Vec<PluginInfo> get_load_order();
 /// This is implemented by the game-specific UE4SS mod
 rust::String get_game_version();
 
+/// Write a warning or error to the UE4SS log
+/// This is implemented by the game-specific UE4SS mod
+void log_to_ue4ss(LogLevel level, rust::Str message);
+
 }  // namespace ctd
@@ -3,16 +3,39 @@
 //! This module wraps the crash-handler crate to capture Windows SEH exceptions
 //! and generate minidumps for crash reporting.
 
+// The handler only exists on Windows
+#![cfg_attr(not(windows), allow(dead_code, unused_imports))]
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 
-use ctd_core::api_client::ApiClient;
-use ctd_core::crash_report::CreateCrashReport;
+use ctd_core::CtdError;
+use ctd_core::api_client;
+use ctd_core::crash_report::{Attachment, CreateCrashReport, ReportType, manual_report_notes};
+use ctd_core::detours;
+use ctd_core::exception_chain;
+use ctd_core::file_hash;
+use ctd_core::graphics;
+use ctd_core::in_page;
+use ctd_core::known_bad;
+use ctd_core::log_tail::{self, DEFAULT_TAIL_LINES};
+use ctd_core::memory_map;
+use ctd_core::modpack;
+use ctd_core::submit;
+use ctd_core::version;
 use tracing::{error, info};
 
-use crate::fingerprint::{get_game_directory, scan_ue4ss_mods};
+use crate::GameInfo;
+use crate::fingerprint::{get_game_directory, scan_mods};
+use crate::games::Quirks;
 
 static HANDLER_INSTALLED: AtomicBool = AtomicBool::new(false);
 
+/// Configured DbgHelp search path, or `None` when runtime symbolization is
+/// off. Read at install so the handler never loads config while crashing.
+static SYMBOL_SEARCH_PATH: OnceLock<Option<String>> = OnceLock::new();
+
 /// Install the crash handler
 pub fn install_handler() {
     if HANDLER_INSTALLED.swap(true, Ordering::SeqCst) {
@@ -24,6 +47,13 @@ pub fn install_handler() {
     {
         use crash_handler::CrashHandler;
 
+        let symbols = ctd_core::config::Config::load().unwrap_or_default().symbols;
+        let _ = SYMBOL_SEARCH_PATH.set(
+            symbols
+                .runtime_symbolization()
+                .then(|| symbols.dbghelp_search_path()),
+        );
+
         let handler = CrashHandler::attach(unsafe {
             crash_handler::make_crash_event(move |crash_context| {
                 handle_crash(crash_context);
@@ -59,6 +89,10 @@ pub fn remove_handler() {
 /// Handle a crash event
 #[cfg(windows)]
 fn handle_crash(crash_context: &crash_handler::CrashContext) {
+    use ctd_capture::veh::{
+        MAX_NESTED, MAX_PARAMETERS, NestedException, exception_parameters, nested_exceptions,
+    };
+
     use crate::game_info;
 
     // Get game info
@@ -72,29 +106,119 @@ fn handle_crash(crash_context: &crash_handler::CrashContext) {
 
     // Build mod list with file hashes using fingerprint module
     let game_dir = get_game_directory().unwrap_or_default();
-    let mod_list = scan_ue4ss_mods(&game_dir);
+    let mod_list = scan_mods(&game_dir, &game_info.quirks);
 
     // Extract exception info
     let exception_code = format!("0x{:08X}", crash_context.exception_code);
 
-    // Build stack trace
-    let stack_trace = format!(
-        "Exception: {}\nGame: {} v{}\nUE: {}",
-        exception_code, game_info.game_name, game_info.game_version, game_info.ue_version
-    );
+    // Walk the crashed thread's stack from its exception context
+    // SAFETY: The exception pointers are valid for the duration of the callback
+    let pointers = unsafe { crash_context.exception_pointers.as_ref() };
+    let exception_address = pointers
+        .and_then(|pointers| unsafe { pointers.ExceptionRecord.as_ref() })
+        .map_or(0, |record| record.ExceptionAddress as u64);
+    // SAFETY: crash-context's CONTEXT mirrors the Win32 layout
+    let context = pointers.and_then(|pointers| unsafe {
+        pointers
+            .ContextRecord
+            .cast::<windows::Win32::System::Diagnostics::Debug::CONTEXT>()
+            .as_ref()
+    });
+    let stack_trace = match context {
+        Some(context) => ctd_capture::stack_walk::capture_stack_trace(
+            context,
+            exception_address,
+            SYMBOL_SEARCH_PATH.get().and_then(Option::as_deref),
+        ),
+        None => format!(
+            "Exception: {}\nGame: {} v{}\nUE: {}",
+            exception_code, game_info.game_name, game_info.game_version, game_info.ue_version
+        ),
+    };
+
+    // Recognize GPU device-removed crashes from the code or the game log
+    let attachments = log_attachments(&game_dir, &game_info.quirks);
+    let logs: Vec<&str> = attachments.iter().map(|log| log.content.as_str()).collect();
+    let device_removed = crate::device_removed::detect(crash_context.exception_code as u32, &logs);
 
     // Build crash report using ctd-core builder
-    let report = match CreateCrashReport::builder()
-        .game_id(&game_info.game_name)
+    let mut builder = CreateCrashReport::builder()
+        .game_id(&game_info.game_id)
         .game_version(&game_info.game_version)
         .script_extender_version(&game_info.ue_version)
         .stack_trace(stack_trace)
         .exception_code(exception_code)
-        .os_version(get_os_version())
+        .exception_address(format!("0x{:016X}", exception_address))
         .load_order_v2(mod_list)
-        .crashed_now()
-        .build()
-    {
+        .max_schema_version(api_client::negotiated_schema_version())
+        .attachments(attachments)
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(&game_info.game_id))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .crashed_now();
+
+    if let Some(os_version) = version::os_version() {
+        builder = builder.os_version(os_version);
+    }
+
+    if let Some(module) = ctd_capture::module::module_at_address(exception_address) {
+        let hooks = detours::scan(&module);
+        builder = builder.faulting_module(module).hooked_functions(hooks);
+    } else if let Some(region) = memory_map::address_region(exception_address) {
+        builder = builder.address_region(region);
+    }
+
+    if let Some(text) = ctd_capture::disasm::disassemble_exception(
+        crash_context.exception_code as u32,
+        exception_address,
+    ) {
+        builder = builder.disassembly(text);
+    }
+
+    if let Some(context) = context {
+        let registers = ctd_core::rtti::context_registers(context);
+        builder = builder.probable_objects(ctd_core::rtti::scan(&registers));
+    }
+
+    // SAFETY: As for the context; crash-context's EXCEPTION_RECORD mirrors
+    // the Win32 layout
+    let record = pointers.and_then(|pointers| unsafe {
+        pointers
+            .ExceptionRecord
+            .cast::<ctd_capture::veh::ExceptionRecord>()
+            .as_ref()
+    });
+
+    let mut nested = [NestedException::default(); MAX_NESTED];
+    let nested_count = record.map_or(0, |record| nested_exceptions(record, &mut nested));
+    builder = builder.exception_chain(exception_chain::capture(
+        nested[..nested_count]
+            .iter()
+            .map(|nested| (nested.code, nested.address)),
+    ));
+
+    let mut information = [0u64; MAX_PARAMETERS];
+    let parameters = record.map_or(0, |record| exception_parameters(record, &mut information));
+    builder = builder.exception_parameters(&information[..parameters]);
+    if let Some(error) = in_page::diagnose(
+        crash_context.exception_code as u32,
+        &information[..parameters],
+    ) {
+        builder = builder.in_page_error(error);
+    }
+
+    if let Some(removed) = device_removed {
+        builder = builder.crash_category(ctd_core::crash_report::CrashCategory::Gpu);
+        if let Some(reason) = removed.reason_text() {
+            builder = builder.device_removed_reason(reason);
+        }
+    }
+
+    let report = match builder.build() {
         Ok(r) => r,
         Err(e) => {
             eprintln!("CTD: Failed to build crash report: {:?}", e);
@@ -104,58 +228,147 @@ fn handle_crash(crash_context: &crash_handler::CrashContext) {
 
     // Submit in a separate thread to avoid blocking crash handling
     std::thread::spawn(move || {
+        // Honor [submission] sample_rate and max_reports_per_hour
+        let verdict = ctd_core::throttle::Throttle::load().admit();
+        if !verdict.is_allowed() {
+            info!("Crash report not submitted: {}", verdict);
+            return;
+        }
         if let Err(e) = submit_crash_report(report) {
             error!("Failed to submit crash report: {}", e);
         }
     });
 }
 
-/// Submit crash report using ctd-core ApiClient (respects ctd.toml config)
-#[cfg(windows)]
-fn submit_crash_report(
-    report: CreateCrashReport,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Create runtime for async API call
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()?;
+/// File a report the player asked for, without a crash
+///
+/// Validates `notes`, then builds and sends the report on a new thread.
+pub fn submit_manual_report(game_info: &'static GameInfo, notes: &str) -> Result<(), CtdError> {
+    let notes = manual_report_notes(notes)?.to_string();
 
-    // Submit the report using ApiClient which reads from ctd.toml
-    let response = rt.block_on(async {
-        let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
-        client.submit_crash_report(&report).await
-    })?;
+    #[cfg(windows)]
+    std::thread::spawn(move || {
+        // Counts toward max_reports_per_hour, but the player asked, so no sampling
+        let verdict = ctd_core::throttle::Throttle::load().admit_requested();
+        if !verdict.is_allowed() {
+            info!("Manual report not submitted: {}", verdict);
+            return;
+        }
+
+        let game_dir = get_game_directory().unwrap_or_default();
+        let report = match build_manual_report(
+            game_info,
+            &notes,
+            ctd_core::watchdog::capture_all_threads(),
+            scan_mods(&game_dir, &game_info.quirks),
+        ) {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to build manual report: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = submit_crash_report(report) {
+            error!("Failed to submit manual report: {}", e);
+        }
+    });
+
+    #[cfg(not(windows))]
+    {
+        let _ = (game_info, notes);
+        tracing::warn!("Manual reports only supported on Windows");
+    }
 
-    info!("Crash report submitted: {}", response.id);
     Ok(())
 }
 
-fn get_os_version() -> String {
-    #[cfg(windows)]
-    {
-        use windows::Win32::System::SystemInformation::{GetVersionExW, OSVERSIONINFOW};
+/// Read the last lines of `UE4SS.log` and the game's own log
+///
+/// UE prints assert messages and Blueprint callstacks there right before
+/// native crashes. Missing or empty logs are skipped.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn log_attachments(game_dir: &Path, quirks: &Quirks) -> Vec<Attachment> {
+    let local_app_data = std::env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    log_paths(game_dir, local_app_data.as_deref(), quirks.unreal_logs)
+        .iter()
+        .filter_map(|path| log_tail::attach(path, DEFAULT_TAIL_LINES))
+        .collect()
+}
 
-        let mut info = OSVERSIONINFOW {
-            dwOSVersionInfoSize: std::mem::size_of::<OSVERSIONINFOW>() as u32,
-            ..Default::default()
-        };
+/// Logs worth attaching, first match of each kind
+///
+/// `UE4SS.log` sits next to UE4SS, in `ue4ss/` on newer releases. The game
+/// log is `Saved/Logs/<Project>.log`, under the project directory two
+/// levels above `Binaries/Win64` or, for most shipped games, under
+/// `%LOCALAPPDATA%/<Project>`. Games without `unreal_logs` only get
+/// `UE4SS.log`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn log_paths(game_dir: &Path, local_app_data: Option<&Path>, unreal_logs: bool) -> Vec<PathBuf> {
+    let ue4ss_log = [
+        game_dir.join("UE4SS.log"),
+        game_dir.join("ue4ss").join("UE4SS.log"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file());
 
-        #[allow(deprecated)]
-        let success = unsafe { GetVersionExW(&mut info).is_ok() };
+    let project_dir = game_dir.ancestors().nth(2).filter(|_| unreal_logs);
+    let game_log = project_dir.and_then(|project_dir| {
+        let project = project_dir.file_name()?;
+        let log = Path::new("Saved")
+            .join("Logs")
+            .join(Path::new(project).with_extension("log"));
+        let shipped_dir = local_app_data.map(|dir| dir.join(project));
+        [Some(project_dir), shipped_dir.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(|dir| dir.join(&log))
+            .find(|path| path.is_file())
+    });
 
-        if success {
-            format!(
-                "Windows {}.{}.{}",
-                info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber
-            )
-        } else {
-            "Windows".to_string()
-        }
-    }
-    #[cfg(not(windows))]
-    {
-        "Unknown".to_string()
+    ue4ss_log.into_iter().chain(game_log).collect()
+}
+
+/// Build a manual report with every thread's stack
+#[cfg_attr(not(windows), allow(dead_code))]
+fn build_manual_report(
+    game_info: &GameInfo,
+    notes: &str,
+    stack_trace: String,
+    mod_list: ctd_core::load_order::ModList,
+) -> Result<CreateCrashReport, CtdError> {
+    let mut builder = CreateCrashReport::builder()
+        .game_id(&game_info.game_id)
+        .report_type(ReportType::Manual)
+        .game_version(&game_info.game_version)
+        .script_extender_version(&game_info.ue_version)
+        .stack_trace(stack_trace)
+        .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
+        .notes(notes)
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(&game_info.game_id))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .crashed_now();
+
+    if let Some(os_version) = version::os_version() {
+        builder = builder.os_version(os_version);
     }
+
+    builder.build()
+}
+
+/// Submit crash report with the client from ctd.toml
+#[cfg(windows)]
+fn submit_crash_report(
+    report: CreateCrashReport,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let response = submit::submit_report(&report)?;
+    info!("Crash report submitted: {}", response.id);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -170,11 +383,57 @@ mod tests {
     }
 
     #[test]
-    fn test_get_os_version() {
-        let version = get_os_version();
-        #[cfg(windows)]
-        assert!(version.starts_with("Windows"));
-        #[cfg(not(windows))]
-        assert_eq!(version, "Unknown");
+    fn test_build_manual_report() {
+        let info = GameInfo {
+            game_name: "oblivion-remastered".to_string(),
+            game_id: "oblivion-remastered".to_string(),
+            quirks: Quirks::default(),
+            game_version: "1.0.0".to_string(),
+            ue_version: "5.3".to_string(),
+        };
+        let report = build_manual_report(
+            &info,
+            "Quest marker missing",
+            "Thread 1234:\n[0] game.exe+0x10 (0x0000000140001010)\n".to_string(),
+            ctd_core::load_order::ModList::new(),
+        )
+        .unwrap();
+        assert_eq!(report.report_type, ReportType::Manual);
+        assert_eq!(report.exception_code, None);
+        assert_eq!(report.notes.as_deref(), Some("Quest marker missing"));
+    }
+
+    #[test]
+    fn test_log_paths_finds_ue4ss_and_game_logs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project_dir = temp_dir.path().join("OblivionRemastered");
+        let game_dir = project_dir.join("Binaries").join("Win64");
+        let local_app_data = temp_dir.path().join("AppData");
+        std::fs::create_dir_all(game_dir.join("ue4ss")).unwrap();
+        assert!(log_paths(&game_dir, Some(&local_app_data), true).is_empty());
+
+        std::fs::write(game_dir.join("ue4ss").join("UE4SS.log"), "[UE4SS] started").unwrap();
+        let shipped_logs = local_app_data
+            .join("OblivionRemastered")
+            .join("Saved")
+            .join("Logs");
+        std::fs::create_dir_all(&shipped_logs).unwrap();
+        std::fs::write(shipped_logs.join("OblivionRemastered.log"), "LogInit").unwrap();
+        assert_eq!(
+            log_paths(&game_dir, Some(&local_app_data), true),
+            [
+                game_dir.join("ue4ss").join("UE4SS.log"),
+                shipped_logs.join("OblivionRemastered.log"),
+            ]
+        );
+
+        // A log next to the install wins over the shipped location
+        let install_logs = project_dir.join("Saved").join("Logs");
+        std::fs::create_dir_all(&install_logs).unwrap();
+        std::fs::write(install_logs.join("OblivionRemastered.log"), "LogInit").unwrap();
+        assert_eq!(
+            log_paths(&game_dir, Some(&local_app_data), true)[1],
+            install_logs.join("OblivionRemastered.log")
+        );
     }
 }
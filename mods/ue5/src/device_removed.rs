@@ -0,0 +1,145 @@
+//! Detection of GPU device-removed crashes
+//!
+//! UE5 games often die because the GPU driver removed the D3D device (a
+//! driver timeout, hang, or reset) rather than from an access violation in
+//! game code. D3D12RHI logs the reason and then takes the fatal-error path,
+//! e.g. `Unreal Engine is exiting due to D3D device being lost. (Error:
+//! 0x887A0006 - 'HUNG')`, so the exception the handler sees says little.
+//! [`detect`] recognizes these crashes from the exception code, which some
+//! games raise as the DXGI error itself, or from the game log's tail.
+
+/// DXGI errors that mean the device was removed, with their names
+pub const DXGI_REMOVAL_ERRORS: &[(u32, &str)] = &[
+    (0x887A_0005, "DXGI_ERROR_DEVICE_REMOVED"),
+    (0x887A_0006, "DXGI_ERROR_DEVICE_HUNG"),
+    (0x887A_0007, "DXGI_ERROR_DEVICE_RESET"),
+    (0x887A_0020, "DXGI_ERROR_DRIVER_INTERNAL_ERROR"),
+];
+
+/// Lowercase log fragments that only appear when the D3D device is lost
+///
+/// Plain "device removed" is not enough: the audio mixer logs it whenever
+/// headphones are unplugged.
+const LOG_MARKERS: &[&str] = &[
+    "d3d device being lost",
+    "d3d device removed",
+    "getdeviceremovedreason",
+    "dxgi_error_device_",
+    "dxgi_error_driver_internal_error",
+];
+
+/// A crash caused by the GPU device being removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceRemoved {
+    /// The DXGI removal error, if it was logged or raised
+    pub reason: Option<u32>,
+}
+
+impl DeviceRemoved {
+    /// The reason as `0x887A0006 DXGI_ERROR_DEVICE_HUNG`, if known
+    pub fn reason_text(&self) -> Option<String> {
+        let code = self.reason?;
+        Some(match reason_name(code) {
+            Some(name) => format!("0x{:08X} {}", code, name),
+            None => format!("0x{:08X}", code),
+        })
+    }
+}
+
+/// Name of a DXGI removal error, or `None` for any other code
+pub fn reason_name(code: u32) -> Option<&'static str> {
+    DXGI_REMOVAL_ERRORS
+        .iter()
+        .find(|&&(known, _)| known == code)
+        .map(|&(_, name)| name)
+}
+
+/// Recognize a device-removed crash from its exception code or log tails
+///
+/// The last reason logged wins, since the final fatal message names the
+/// error that ended the game.
+pub fn detect(exception_code: u32, logs: &[&str]) -> Option<DeviceRemoved> {
+    if reason_name(exception_code).is_some() {
+        return Some(DeviceRemoved {
+            reason: Some(exception_code),
+        });
+    }
+
+    let mut found = false;
+    let mut reason = None;
+    for line in logs.iter().flat_map(|log| log.lines()) {
+        let lower = line.to_ascii_lowercase();
+        if !LOG_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            continue;
+        }
+        found = true;
+        if let Some(code) = reason_in(&lower) {
+            reason = Some(code);
+        }
+    }
+    found.then_some(DeviceRemoved { reason })
+}
+
+/// Find a DXGI removal error in a lowercased log line, by code or by name
+fn reason_in(line: &str) -> Option<u32> {
+    let by_code = line.match_indices("0x").find_map(|(start, _)| {
+        let digits = line[start + 2..]
+            .split(|c: char| !c.is_ascii_hexdigit())
+            .next()?;
+        u32::from_str_radix(digits, 16)
+            .ok()
+            .filter(|&code| reason_name(code).is_some())
+    });
+
+    by_code.or_else(|| {
+        DXGI_REMOVAL_ERRORS
+            .iter()
+            .find(|(_, name)| line.contains(&name.to_ascii_lowercase()))
+            .map(|&(code, _)| code)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_from_exception_code() {
+        let removed = detect(0x887A_0005, &[]).unwrap();
+        assert_eq!(
+            removed.reason_text().as_deref(),
+            Some("0x887A0005 DXGI_ERROR_DEVICE_REMOVED")
+        );
+        assert!(detect(0xC000_0005, &[]).is_none());
+    }
+
+    #[test]
+    fn test_detect_from_fatal_log() {
+        let log = "[2025.04.22-10.15.03:120][412]LogD3D12RHI: Error: GPU crash detected\n\
+                   [2025.04.22-10.15.03:121][412]LogRHI: Error: Unreal Engine is exiting \
+                   due to D3D device being lost. (Error: 0x887A0006 - 'HUNG')";
+        let removed = detect(0x0000_0001, &["[UE4SS] started", log]).unwrap();
+        assert_eq!(removed.reason, Some(0x887A_0006));
+        assert_eq!(
+            removed.reason_text().as_deref(),
+            Some("0x887A0006 DXGI_ERROR_DEVICE_HUNG")
+        );
+    }
+
+    #[test]
+    fn test_detect_by_error_name_and_without_reason() {
+        let log = "LogD3D12RHI: Error: GetDeviceRemovedReason: DXGI_ERROR_DEVICE_RESET";
+        assert_eq!(detect(1, &[log]).unwrap().reason, Some(0x887A_0007));
+
+        let log = "LogD3D12RHI: Error: D3D device removed";
+        let removed = detect(1, &[log]).unwrap();
+        assert_eq!(removed.reason, None);
+        assert_eq!(removed.reason_text(), None);
+    }
+
+    #[test]
+    fn test_audio_device_removal_is_not_a_gpu_crash() {
+        let log = "LogAudioMixer: Display: Audio device removed, falling back to default";
+        assert!(detect(0xC000_0005, &[log]).is_none());
+    }
+}
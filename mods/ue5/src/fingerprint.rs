@@ -1,11 +1,17 @@
-//! File fingerprinting for UE4SS mods.
+//! File fingerprinting for UE4SS and pak mods.
 
-use ctd_core::file_hash::compute_file_hash;
-use ctd_core::load_order::{ModEntry, ModList};
+use ctd_core::file_hash::CachedHasher;
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
 use ctd_core::version::get_dll_version;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+use crate::games::Quirks;
+
+/// Pak mod file extensions. IoStore mods ship a `.utoc`/`.ucas` pair next
+/// to a small `.pak`.
+const PAK_EXTENSIONS: &[&str] = &["pak", "utoc", "ucas"];
+
 /// Check if mod is enabled via enabled.txt.
 pub fn is_mod_enabled(mod_dir: &Path) -> bool {
     let enabled_path = mod_dir.join("enabled.txt");
@@ -18,16 +24,36 @@ pub fn is_mod_enabled(mod_dir: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Parse UE4SS `Mods/mods.txt` into mod names and enable flags, in load
+/// order.
+///
+/// Lines look like `ModName : 1`; blank lines and `;` comments are skipped.
+pub fn parse_mods_txt(content: &str) -> Vec<(String, bool)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(';'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, flag)| (name.trim().to_string(), flag.trim() == "1"))
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
 /// Scan the Mods directory and build ModList with fingerprints.
+///
+/// Mods listed in `Mods/mods.txt` come first, in its order and with its
+/// enable flag; the rest follow in directory order, enabled by their
+/// `enabled.txt`.
 pub fn scan_ue4ss_mods(game_dir: &Path) -> ModList {
     let mods_dir = game_dir.join("Mods");
-    let mut list = ModList::new();
-    let mut index = 0u32;
+    let mut entries = Vec::new();
 
     if !mods_dir.exists() {
-        return list;
+        return ModList::new();
     }
 
+    let mut hasher = CachedHasher::open_default();
+
     // Each subdirectory in Mods/ is a mod
     for entry in WalkDir::new(&mods_dir).max_depth(1).into_iter().flatten() {
         if !entry.file_type().is_dir() || entry.path() == mods_dir {
@@ -53,33 +79,135 @@ pub fn scan_ue4ss_mods(game_dir: &Path) -> ModList {
         let lua_path = mod_dir.join("Scripts").join("main.lua");
 
         let (hash, size, version) = if dll_path.exists() {
-            let (h, s) =
-                compute_file_hash(&dll_path).unwrap_or(("0000000000000000".to_string(), 0));
+            let (h, s) = hasher
+                .hash(&dll_path)
+                .unwrap_or(("0000000000000000".to_string(), 0));
             let v = get_dll_version(&dll_path).ok();
             (h, s, v)
         } else if lua_path.exists() {
-            let (h, s) =
-                compute_file_hash(&lua_path).unwrap_or(("0000000000000000".to_string(), 0));
+            let (h, s) = hasher
+                .hash(&lua_path)
+                .unwrap_or(("0000000000000000".to_string(), 0));
             (h, s, None)
         } else {
             ("0000000000000000".to_string(), 0, None)
         };
 
+        // UE4SS mods have no light/full distinction
         let mut mod_entry = ModEntry::new(&mod_name, hash, size)
-            .with_index(index)
-            .with_enabled(enabled);
+            .with_enabled(enabled)
+            .with_light(false);
 
         if let Some(v) = version {
             mod_entry = mod_entry.with_version(v);
         }
 
-        list.push(mod_entry);
-        index += 1;
+        entries.push(mod_entry);
+    }
+    hasher.save();
+
+    let mods_txt = std::fs::read_to_string(mods_dir.join("mods.txt"))
+        .map(|content| parse_mods_txt(&content))
+        .unwrap_or_default();
+    apply_mods_txt(entries, &mods_txt)
+}
+
+/// Order `entries` by `mods_txt` and take its enable flags, then index them.
+///
+/// Names match case-insensitively, as on Windows. Entries `mods_txt` does
+/// not list keep their order and enabled state after the listed ones.
+fn apply_mods_txt(mut entries: Vec<ModEntry>, mods_txt: &[(String, bool)]) -> ModList {
+    let mut ordered = Vec::with_capacity(entries.len());
+    for (name, enabled) in mods_txt {
+        if let Some(pos) = entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))
+        {
+            ordered.push(entries.remove(pos).with_enabled(*enabled));
+        }
+    }
+    ordered.extend(entries);
+
+    let mut list = ModList::new();
+    for (index, entry) in ordered.into_iter().enumerate() {
+        list.push(entry.with_index(index as u32));
+    }
+    list
+}
+
+/// Scan UE4SS mods, then pak mods, into one load order.
+pub fn scan_mods(game_dir: &Path, quirks: &Quirks) -> ModList {
+    let mut list = scan_ue4ss_mods(game_dir);
+    let first = list.len() as u32;
+    for (index, entry) in (first..).zip(scan_pak_mods(game_dir, quirks.pak_mod_dirs).iter()) {
+        list.push(entry.clone().with_index(index));
+    }
+    list
+}
+
+/// Scan the pak mod folders `pak_mod_dirs` under `Content/Paks` and build
+/// ModList with fingerprints.
+///
+/// Blueprint and pak mods are most mods for UE5 games. Entries are named by
+/// their path under `Content/Paks` (e.g. `~mods/MyMod_P.pak`), in file name
+/// order within each folder.
+pub fn scan_pak_mods(game_dir: &Path, pak_mod_dirs: &[&str]) -> ModList {
+    let Some(paks_dir) = get_paks_directory(game_dir) else {
+        return ModList::new();
+    };
+
+    let paks: Vec<PathBuf> = pak_mod_dirs
+        .iter()
+        .map(|dir| paks_dir.join(dir))
+        .filter(|dir| dir.is_dir())
+        .flat_map(|dir| {
+            WalkDir::new(dir)
+                .sort_by_file_name()
+                .into_iter()
+                .flatten()
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .filter(|path| {
+                    path.extension().is_some_and(|ext| {
+                        PAK_EXTENSIONS
+                            .iter()
+                            .any(|pak| ext.eq_ignore_ascii_case(pak))
+                    })
+                })
+        })
+        .collect();
+
+    if paks.is_empty() {
+        return ModList::new();
     }
 
+    let mut hasher = CachedHasher::open_default();
+    let hashed = ModList::fingerprint_parallel(&paks, DEFAULT_HASH_BUDGET, &mut hasher);
+    hasher.save();
+
+    let mut list = ModList::new();
+    for (path, entry) in paks.iter().zip(hashed.iter()) {
+        let relative = path.strip_prefix(&paks_dir).unwrap_or(path);
+        let mut entry = entry.clone().with_enabled(true).with_light(false);
+        entry.name = relative.to_string_lossy().replace('\\', "/");
+        list.push(entry);
+    }
     list
 }
 
+/// Find `Content/Paks` for the game.
+///
+/// UE4SS runs from `<Project>/Binaries/Win64`, so the paks are two levels
+/// up; a layout with `Content` next to UE4SS is also accepted.
+fn get_paks_directory(game_dir: &Path) -> Option<PathBuf> {
+    let project_dir = game_dir.ancestors().nth(2);
+    project_dir
+        .into_iter()
+        .chain([game_dir])
+        .map(|dir| dir.join("Content").join("Paks"))
+        .find(|dir| dir.is_dir())
+}
+
 /// Get game directory from DLL location.
 #[cfg(windows)]
 pub fn get_game_directory() -> Option<PathBuf> {
@@ -109,3 +237,146 @@ pub fn get_game_directory() -> Option<PathBuf> {
 pub fn get_game_directory() -> Option<PathBuf> {
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_scan_pak_mods_finds_mod_folders() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let game_dir = temp_dir
+            .path()
+            .join("Project")
+            .join("Binaries")
+            .join("Win64");
+        let paks_dir = temp_dir.path().join("Project").join("Content").join("Paks");
+        fs::create_dir_all(&game_dir).unwrap();
+        fs::create_dir_all(paks_dir.join("~mods").join("Armor")).unwrap();
+        fs::create_dir_all(paks_dir.join("LogicMods")).unwrap();
+
+        // Base game paks are not mods
+        fs::write(paks_dir.join("Project-Windows.pak"), b"base").unwrap();
+        fs::write(
+            paks_dir.join("~mods").join("Armor").join("Armor_P.pak"),
+            b"pak",
+        )
+        .unwrap();
+        fs::write(
+            paks_dir.join("~mods").join("Armor").join("Armor_P.ucas"),
+            b"ucas",
+        )
+        .unwrap();
+        fs::write(
+            paks_dir.join("~mods").join("Armor").join("Armor_P.utoc"),
+            b"utoc",
+        )
+        .unwrap();
+        fs::write(paks_dir.join("~mods").join("readme.txt"), b"notes").unwrap();
+        fs::write(paks_dir.join("LogicMods").join("Menu.pak"), b"logic").unwrap();
+
+        let mods = scan_pak_mods(&game_dir, crate::games::DEFAULT_PAK_MOD_DIRS);
+        let names: Vec<&str> = mods.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "~mods/Armor/Armor_P.pak",
+                "~mods/Armor/Armor_P.ucas",
+                "~mods/Armor/Armor_P.utoc",
+                "LogicMods/Menu.pak",
+            ]
+        );
+        let pak = mods.iter().next().unwrap();
+        assert_eq!(pak.file_size, 3);
+        assert_ne!(pak.file_hash, "0000000000000000");
+    }
+
+    #[test]
+    fn test_parse_mods_txt() {
+        let content = "; Built-in keybinds, do not move up!\n\
+                       CheatManagerEnablerMod : 1\n\
+                       ActorDumperMod : 0\n\
+                       \n\
+                       BPModLoaderMod:1\n\
+                       not a mod line\n\
+                       Keybinds : 1\n";
+        assert_eq!(
+            parse_mods_txt(content),
+            [
+                ("CheatManagerEnablerMod".to_string(), true),
+                ("ActorDumperMod".to_string(), false),
+                ("BPModLoaderMod".to_string(), true),
+                ("Keybinds".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_ue4ss_mods_follows_mods_txt() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mods_dir = temp_dir.path().join("Mods");
+        for name in ["Alpha", "Beta", "Gamma"] {
+            let scripts = mods_dir.join(name).join("Scripts");
+            fs::create_dir_all(&scripts).unwrap();
+            fs::write(scripts.join("main.lua"), name).unwrap();
+            fs::write(mods_dir.join(name).join("enabled.txt"), "1").unwrap();
+        }
+        fs::write(
+            mods_dir.join("mods.txt"),
+            "gamma : 1\nAlpha : 0\nMissing : 1\n",
+        )
+        .unwrap();
+
+        let mods = scan_ue4ss_mods(temp_dir.path());
+        let entries: Vec<(&str, Option<u32>, Option<bool>)> = mods
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.index, entry.enabled))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("Gamma", Some(0), Some(true)),
+                ("Alpha", Some(1), Some(false)),
+                ("Beta", Some(2), Some(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_mods_indexes_paks_after_ue4ss_mods() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp_dir.path().join("Mods").join("Trainer").join("Scripts")).unwrap();
+        fs::write(
+            temp_dir
+                .path()
+                .join("Mods")
+                .join("Trainer")
+                .join("Scripts")
+                .join("main.lua"),
+            b"print('hi')",
+        )
+        .unwrap();
+        fs::create_dir_all(temp_dir.path().join("Content").join("Paks").join("~mods")).unwrap();
+        fs::write(
+            temp_dir
+                .path()
+                .join("Content")
+                .join("Paks")
+                .join("~mods")
+                .join("Hair_P.pak"),
+            b"pak",
+        )
+        .unwrap();
+
+        let mods = scan_mods(temp_dir.path(), &Quirks::default());
+        let entries: Vec<(&str, Option<u32>)> = mods
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.index))
+            .collect();
+        assert_eq!(
+            entries,
+            [("Trainer", Some(0)), ("~mods/Hair_P.pak", Some(1))]
+        );
+    }
+}
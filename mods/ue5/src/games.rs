@@ -0,0 +1,217 @@
+//! Registry of known UE4SS games
+//!
+//! The UE4SS mods pass a game name to `init()`, and before this registry it
+//! became the report's game ID as-is, so one title could report as both
+//! "Oblivion Remastered" and "oblivion-remastered". [`detect`] maps the
+//! running executable (or, failing that, the name) to a canonical ID and
+//! the title's quirks. Unknown games get an ID slugged from the name and
+//! the generic Unreal defaults.
+
+use std::path::Path;
+
+/// Pak mod folders under `Content/Paks` for games without their own list.
+pub const DEFAULT_PAK_MOD_DIRS: &[&str] = &["~mods", "LogicMods", "mods"];
+
+/// How a title differs from a generic UE5 game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// Pak mod folders under `Content/Paks`, in scan order
+    pub pak_mod_dirs: &'static [&'static str],
+    /// Whether the game writes an Unreal log to `Saved/Logs`
+    pub unreal_logs: bool,
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self {
+            pak_mod_dirs: DEFAULT_PAK_MOD_DIRS,
+            unreal_logs: true,
+        }
+    }
+}
+
+/// A game in the registry
+#[derive(Debug)]
+pub struct KnownGame {
+    /// Canonical game ID sent in reports
+    pub id: &'static str,
+    /// Executable file names, matched case-insensitively
+    pub executables: &'static [&'static str],
+    /// Names the UE4SS mods have passed to `init()` for this game
+    pub names: &'static [&'static str],
+    /// How the game differs from the defaults
+    pub quirks: Quirks,
+}
+
+/// Games with a canonical ID and known quirks
+pub const KNOWN_GAMES: &[KnownGame] = &[
+    KnownGame {
+        id: "oblivion-remastered",
+        executables: &[
+            "OblivionRemastered-Win64-Shipping.exe",
+            "OblivionRemastered-WinGDK-Shipping.exe",
+        ],
+        names: &[
+            "Oblivion Remastered",
+            "The Elder Scrolls IV: Oblivion Remastered",
+        ],
+        quirks: Quirks {
+            pak_mod_dirs: &["~mods", "LogicMods"],
+            unreal_logs: true,
+        },
+    },
+    KnownGame {
+        id: "palworld",
+        executables: &[
+            "Palworld-Win64-Shipping.exe",
+            "Palworld-WinGDK-Shipping.exe",
+        ],
+        names: &["Palworld"],
+        quirks: Quirks {
+            pak_mod_dirs: &["~mods", "LogicMods"],
+            unreal_logs: true,
+        },
+    },
+    KnownGame {
+        id: "stalker-2",
+        executables: &[
+            "Stalker2-Win64-Shipping.exe",
+            "Stalker2-WinGDK-Shipping.exe",
+        ],
+        names: &["S.T.A.L.K.E.R. 2", "Stalker 2"],
+        quirks: Quirks {
+            pak_mod_dirs: &["~mods"],
+            unreal_logs: true,
+        },
+    },
+    KnownGame {
+        id: "black-myth-wukong",
+        executables: &["b1-Win64-Shipping.exe"],
+        names: &["Black Myth: Wukong", "Black Myth Wukong"],
+        quirks: Quirks {
+            pak_mod_dirs: &["~mods", "LogicMods"],
+            unreal_logs: true,
+        },
+    },
+    // Not an Unreal game: no paks and no Unreal log
+    KnownGame {
+        id: "elden-ring",
+        executables: &["eldenring.exe"],
+        names: &["Elden Ring"],
+        quirks: Quirks {
+            pak_mod_dirs: &[],
+            unreal_logs: false,
+        },
+    },
+];
+
+/// The running game, resolved against the registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    /// Canonical game ID sent in reports
+    pub id: String,
+    /// How the game differs from the defaults
+    pub quirks: Quirks,
+}
+
+/// Resolve the running game from its executable, then from `game_name`
+pub fn detect(game_name: &str) -> Game {
+    let executable = std::env::current_exe().ok();
+    let executable = executable
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy());
+    resolve(game_name, executable.as_deref())
+}
+
+/// Resolve a game from its executable file name and the name passed to
+/// `init()`
+///
+/// The executable wins, since mods pass names inconsistently. Unknown games
+/// get a slug of `game_name` and the default quirks.
+pub fn resolve(game_name: &str, executable: Option<&str>) -> Game {
+    let slug = slugify(game_name);
+    let known = executable
+        .and_then(|exe| {
+            KNOWN_GAMES.iter().find(|game| {
+                game.executables
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(exe))
+            })
+        })
+        .or_else(|| {
+            KNOWN_GAMES
+                .iter()
+                .find(|game| game.id == slug || game.names.iter().any(|name| slugify(name) == slug))
+        });
+
+    match known {
+        Some(game) => Game {
+            id: game.id.to_string(),
+            quirks: game.quirks,
+        },
+        None => Game {
+            id: slug,
+            quirks: Quirks::default(),
+        },
+    }
+}
+
+/// Lowercase `name`, with each run of other characters as one `-`
+fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Oblivion Remastered"), "oblivion-remastered");
+        assert_eq!(slugify("oblivion-remastered"), "oblivion-remastered");
+        assert_eq!(slugify("S.T.A.L.K.E.R. 2"), "s-t-a-l-k-e-r-2");
+        assert_eq!(slugify("  Black Myth: Wukong! "), "black-myth-wukong");
+    }
+
+    #[test]
+    fn test_resolve_prefers_executable() {
+        let game = resolve(
+            "Some Mod's Name",
+            Some("oblivionremastered-win64-shipping.exe"),
+        );
+        assert_eq!(game.id, "oblivion-remastered");
+        assert_eq!(game.quirks.pak_mod_dirs, ["~mods", "LogicMods"]);
+    }
+
+    #[test]
+    fn test_resolve_matches_names_consistently() {
+        for name in [
+            "Oblivion Remastered",
+            "oblivion-remastered",
+            "OBLIVION REMASTERED",
+        ] {
+            assert_eq!(resolve(name, Some("unknown.exe")).id, "oblivion-remastered");
+        }
+        assert_eq!(resolve("Stalker 2", None).id, "stalker-2");
+        assert!(!resolve("elden-ring", None).quirks.unreal_logs);
+    }
+
+    #[test]
+    fn test_resolve_falls_back_for_unknown_games() {
+        let game = resolve("My Indie Game", Some("MyIndieGame-Win64-Shipping.exe"));
+        assert_eq!(game.id, "my-indie-game");
+        assert_eq!(game.quirks, Quirks::default());
+    }
+}
@@ -5,20 +5,31 @@
 //! integrates with ctd-core for report submission.
 
 mod crash;
+pub mod device_removed;
 pub mod fingerprint;
+pub mod games;
 
 use std::sync::OnceLock;
 
+#[cfg(feature = "ue4ss")]
+use ctd_core::abi::{add_breadcrumb, report_handled_error, set_context};
+
 /// Game information provided by the UE4SS mod
 static GAME_INFO: OnceLock<GameInfo> = OnceLock::new();
 
 /// Runtime game information
 pub struct GameInfo {
+    /// Name the UE4SS mod passed to `init()`
     pub game_name: String,
+    /// Canonical game ID from the [`games`] registry
+    pub game_id: String,
+    /// How the game differs from a generic UE5 game
+    pub quirks: games::Quirks,
     pub game_version: String,
     pub ue_version: String,
 }
 
+#[cfg(feature = "ue4ss")]
 #[allow(dead_code)]
 #[cxx::bridge(namespace = "ctd")]
 mod ffi {
@@ -29,6 +40,12 @@ mod ffi {
         is_light: bool,
     }
 
+    /// Severity of a message mirrored into the UE4SS log
+    enum LogLevel {
+        Warn,
+        Error,
+    }
+
     extern "Rust" {
         /// Initialize the crash reporter
         fn init(game_name: &str, game_version: &str, ue_version: &str);
@@ -38,6 +55,29 @@ mod ffi {
 
         /// Shutdown the crash reporter
         fn shutdown();
+
+        /// Crash the game on purpose if `allow_test_crash` is set in ctd.toml
+        fn trigger_test_crash() -> bool;
+
+        /// ID of the last report submitted for this game, or empty if none
+        fn last_report_id() -> String;
+
+        /// Attach a note to the last report submitted for this game
+        fn append_note_to_last_report(text: &str) -> bool;
+
+        /// File a report with the current mods and the player's notes, also
+        /// for Lua `CTD_SubmitReport`
+        fn submit_manual_report(notes: &str) -> bool;
+
+        /// Record a step another mod took, for `ctd_add_breadcrumb` and Lua
+        /// `CTD_AddBreadcrumb`
+        fn add_breadcrumb(source: &str, message: &str) -> bool;
+
+        /// Set or (with an empty value) remove a context key, for `ctd_set_context_kv`
+        fn set_context(key: &str, value: &str) -> bool;
+
+        /// Record an error another mod handled, for `ctd_report_handled_error`
+        fn report_handled_error(source: &str, message: &str) -> bool;
     }
 
     unsafe extern "C++" {
@@ -48,29 +88,57 @@ mod ffi {
 
         /// Get game-specific version info
         fn get_game_version() -> String;
+
+        /// Write a message to the UE4SS log
+        fn log_to_ue4ss(level: LogLevel, message: &str);
     }
 }
 
 /// Initialize the crash reporter with game info
 pub fn init(game_name: &str, game_version: &str, ue_version: &str) {
-    // Store game info
+    // Store game info under its canonical ID
+    let game = games::detect(game_name);
+    let game_id = game.id.clone();
     let _ = GAME_INFO.set(GameInfo {
         game_name: game_name.to_string(),
+        game_id: game.id,
+        quirks: game.quirks,
         game_version: game_version.to_string(),
         ue_version: ue_version.to_string(),
     });
 
+    #[cfg(feature = "ue4ss")]
+    let log_path = ctd_core::logging::init_with_host(&game_id, forward_to_ue4ss);
+    #[cfg(not(feature = "ue4ss"))]
+    let log_path = ctd_core::logging::init(&game_id);
+    if let Some(path) = log_path {
+        tracing::info!("Logging to {}", path.display());
+    }
+
     // Install crash handler
     crash::install_handler();
+    ctd_core::session::start();
+    ctd_core::startup::init();
 
     tracing::info!(
         "CTD initialized for {} v{} (UE {})",
-        game_name,
+        game_id,
         game_version,
         ue_version
     );
 }
 
+/// Mirror a warning or error into the UE4SS log, where users look first
+#[cfg(feature = "ue4ss")]
+fn forward_to_ue4ss(level: tracing::Level, message: &str) {
+    let level = if level == tracing::Level::ERROR {
+        ffi::LogLevel::Error
+    } else {
+        ffi::LogLevel::Warn
+    };
+    ffi::log_to_ue4ss(level, message);
+}
+
 /// Called when game data is loaded
 pub fn on_data_loaded() {
     tracing::info!("Game data loaded, crash reporter active");
@@ -78,10 +146,84 @@ pub fn on_data_loaded() {
 
 /// Shutdown and cleanup
 pub fn shutdown() {
+    // The game is exiting cleanly; count the session toward crash rates
+    if let Some(info) = game_info() {
+        ctd_core::session::submit_on_exit(|| {
+            let game_dir = fingerprint::get_game_directory().unwrap_or_default();
+            ctd_core::session::SessionReport::collect(
+                &info.game_id,
+                &info.game_version,
+                fingerprint::scan_mods(&game_dir, &info.quirks),
+            )
+            .script_extender_version(&info.ue_version)
+        });
+    }
+
     crash::remove_handler();
     tracing::info!("CTD shutdown");
 }
 
+/// Crash the game on purpose if `allow_test_crash` is set in ctd.toml
+pub fn trigger_test_crash() -> bool {
+    let config = ctd_core::config::Config::load().unwrap_or_default();
+    match ctd_core::test_crash::trigger(&config) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("{}", e);
+            false
+        }
+    }
+}
+
+/// ID of the last report submitted for this game, or empty if none
+pub fn last_report_id() -> String {
+    game_info()
+        .and_then(|info| ctd_core::last_report::LastReport::load(&info.game_id))
+        .map(|report| report.id)
+        .unwrap_or_default()
+}
+
+/// Attach a note to the last report submitted for this game
+///
+/// Blocks until the note is sent. Returns false (and logs why) on failure.
+pub fn append_note_to_last_report(text: &str) -> bool {
+    let Some(info) = game_info() else {
+        tracing::warn!("Cannot attach note: CTD not initialized");
+        return false;
+    };
+    match ctd_core::last_report::append_note_to_last(&info.game_id, text) {
+        Ok(id) => {
+            tracing::info!("Note attached to report {}", id);
+            true
+        }
+        Err(e) => {
+            tracing::warn!("Failed to attach note: {}", e);
+            false
+        }
+    }
+}
+
+/// File a report with the current mods and the player's notes
+///
+/// Returns false (and logs why) if the notes are empty or too long; the
+/// report itself is sent in the background.
+pub fn submit_manual_report(notes: &str) -> bool {
+    let Some(info) = game_info() else {
+        tracing::warn!("Cannot file report: CTD not initialized");
+        return false;
+    };
+    match crash::submit_manual_report(info, notes) {
+        Ok(()) => {
+            tracing::info!("Filing manual report");
+            true
+        }
+        Err(e) => {
+            tracing::warn!("Cannot file report: {}", e);
+            false
+        }
+    }
+}
+
 /// Get the current game info
 pub fn game_info() -> Option<&'static GameInfo> {
     GAME_INFO.get()
@@ -95,6 +237,8 @@ mod tests {
     fn test_game_info_struct() {
         let info = GameInfo {
             game_name: "oblivion-remastered".to_string(),
+            game_id: "oblivion-remastered".to_string(),
+            quirks: games::Quirks::default(),
             game_version: "1.0.0".to_string(),
             ue_version: "5.3".to_string(),
         };
@@ -103,6 +247,7 @@ mod tests {
         assert_eq!(info.ue_version, "5.3");
     }
 
+    #[cfg(feature = "ue4ss")]
     #[test]
     fn test_ffi_plugin_info() {
         let plugin = ffi::PluginInfo {
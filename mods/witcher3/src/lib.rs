@@ -0,0 +1,106 @@
+//! CTD Crash Reporter for The Witcher 3: Wild Hunt
+//!
+//! A native mod that captures crashes in the next-gen (4.x) executable via
+//! the shared Vectored Exception Handler in `ctd-capture` and submits crash
+//! reports to the CTD backend API. It follows the Cyberpunk 2077 crate's
+//! layout, since both games mod the CDPR way: a `mods` folder of content
+//! mods plus extra DLC.
+//!
+//! ## Installation
+//!
+//! Rename the compiled `ctd_witcher3.dll` to `ctd_witcher3.asi` and place it
+//! next to `witcher3.exe` in `<game>/bin/x64_dx12/` (or `bin/x64/` for
+//! DirectX 11), where an ASI loader such as Ultimate ASI Loader loads it.
+//!
+//! ## Features
+//!
+//! - Captures crashes with stack traces and exception information
+//! - Reports `mods/` in load order (`mods.settings` priority, then name) and
+//!   extra `dlc/` folders
+//! - Fire-and-forget submission (never blocks the game)
+//!
+//! ## Platform Support
+//!
+//! This crate is designed for Windows (x86_64-pc-windows-msvc) but can be
+//! compiled on other platforms for development purposes. Non-Windows builds
+//! produce a stub library.
+
+// Allow dead code on non-Windows platforms where the actual implementation isn't used
+#![cfg_attr(not(windows), allow(dead_code, unused_imports))]
+
+pub mod mod_scanner;
+pub mod report;
+
+#[cfg(windows)]
+use ctd_capture::handler::{self, HandlerOptions};
+#[cfg(windows)]
+use ctd_core::config::Config;
+#[cfg(windows)]
+use tracing::{error, info, warn};
+
+/// DLL entry point.
+///
+/// The loader lock is held here, so startup runs on a thread of its own.
+#[cfg(windows)]
+#[unsafe(no_mangle)]
+pub extern "system" fn DllMain(
+    _module: windows::Win32::Foundation::HINSTANCE,
+    reason: u32,
+    _reserved: *mut std::ffi::c_void,
+) -> windows::Win32::Foundation::BOOL {
+    use windows::Win32::System::SystemServices::DLL_PROCESS_ATTACH;
+
+    if reason == DLL_PROCESS_ATTACH {
+        std::thread::spawn(start);
+    }
+    windows::Win32::Foundation::TRUE
+}
+
+/// Registers the crash handler and caches the mod list.
+#[cfg(windows)]
+fn start() {
+    if let Some(path) = ctd_core::logging::init(report::GAME_ID) {
+        info!("Logging to {}", path.display());
+    }
+
+    info!("CTD Crash Reporter initializing...");
+
+    ctd_core::startup::init();
+
+    let options = HandlerOptions::from_config(&Config::load().unwrap_or_default());
+    if let Err(e) = handler::register(
+        report::GAME_ID,
+        &report::get_game_version(),
+        options,
+        report::submit_async,
+    ) {
+        error!("Failed to register crash handler: {}", e);
+    } else {
+        info!("VEH crash handler registered");
+    }
+
+    // Cache mod list on startup; the crash path only copies it
+    match mod_scanner::scan_and_cache() {
+        Some(count) => info!("Cached {} mods", count),
+        None => warn!("Game directory not found; mods won't be reported"),
+    }
+
+    info!("CTD Crash Reporter initialized successfully");
+}
+
+// Breadcrumbs, context, and handled errors from other mods (see ctd.h)
+#[cfg(windows)]
+ctd_core::export_abi!();
+
+// ===========================================================================
+// Non-Windows stubs for development/testing on other platforms
+// ===========================================================================
+
+/// Stub initialization function for non-Windows platforms.
+///
+/// This is only used for development testing - the actual plugin
+/// functionality only works on Windows.
+#[cfg(not(windows))]
+pub fn init() {
+    eprintln!("ctd-witcher3: This plugin only works on Windows");
+}
@@ -0,0 +1,306 @@
+//! The Witcher 3 mod enumeration.
+//!
+//! Mods are folders in `<game>/mods` (`modFoo/content/...`) and extra DLC
+//! folders in `<game>/dlc`. The game loads mods in ASCII order of their
+//! folder names, so `mod0000_MergedFiles` wins over `modA`; since the
+//! next-gen update `Documents/The Witcher 3/mods.settings` can disable a
+//! mod or give it a priority (lower wins) that overrides the name order.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use ctd_core::file_hash::{CachedHasher, UNHASHED};
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
+
+/// Mods found at startup, for the crash path to copy.
+static CACHED_MODS: RwLock<Option<ModList>> = RwLock::new(None);
+
+/// A mod's section in `mods.settings`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModSetting {
+    /// Mod folder name, e.g. "modFoo"
+    pub name: String,
+    /// `Enabled=` (mods are enabled unless it says 0)
+    pub enabled: bool,
+    /// `Priority=`, lower loads with higher priority
+    pub priority: Option<u32>,
+}
+
+/// Parses `mods.settings`, an INI file with one `[modName]` section per
+/// mod and `Enabled`/`Priority` keys.
+pub fn parse_mods_settings(content: &str) -> Vec<ModSetting> {
+    let mut settings: Vec<ModSetting> = Vec::new();
+    for line in content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .map(str::trim)
+    {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            settings.push(ModSetting {
+                name: name.trim().to_string(),
+                enabled: true,
+                priority: None,
+            });
+            continue;
+        }
+        let (Some(setting), Some((key, value))) = (settings.last_mut(), line.split_once('='))
+        else {
+            continue;
+        };
+        let value = value.trim();
+        if key.trim().eq_ignore_ascii_case("Enabled") {
+            setting.enabled = value != "0";
+        } else if key.trim().eq_ignore_ascii_case("Priority") {
+            setting.priority = value.parse().ok();
+        }
+    }
+    settings
+}
+
+/// Returns the path of `mods.settings`.
+pub fn get_mods_settings_path() -> Option<PathBuf> {
+    Some(
+        dirs::document_dir()?
+            .join("The Witcher 3")
+            .join("mods.settings"),
+    )
+}
+
+/// Returns true for the DLC folders the game ships (`dlc1`..`dlcN`, `ep1`,
+/// `bob`).
+fn is_base_dlc(name: &str) -> bool {
+    let numbered = name
+        .strip_prefix("dlc")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    numbered || name.eq_ignore_ascii_case("ep1") || name.eq_ignore_ascii_case("bob")
+}
+
+/// Lists the folders in `dir`, in ASCII order of their names.
+fn list_folders(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Picks the file that fingerprints a mod or DLC folder: its first
+/// `.bundle`, or else its first script, under `content`.
+///
+/// Script-only mods have no bundle; folders with neither get no hash.
+fn representative_file(folder: &Path) -> Option<PathBuf> {
+    let content = folder.join("content");
+    let mut bundles: Vec<PathBuf> = std::fs::read_dir(&content)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bundle"))
+        .collect();
+    bundles.sort();
+    bundles.into_iter().next().or_else(|| {
+        let mut scripts = Vec::new();
+        collect_scripts(&content.join("scripts"), &mut scripts);
+        scripts.sort();
+        scripts.into_iter().next()
+    })
+}
+
+/// Adds every `.ws` script under `dir` to `scripts`.
+fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_scripts(&path, scripts);
+        } else if path.extension().is_some_and(|ext| ext == "ws") {
+            scripts.push(path);
+        }
+    }
+}
+
+/// Scans `<game_dir>/mods` and `<game_dir>/dlc` into a load order.
+///
+/// Mods with a `mods.settings` priority come first, lowest first; the rest
+/// follow in ASCII name order. Disabled mods are listed with
+/// `enabled: false`. Extra DLC follow the mods; the game's own DLC are
+/// skipped. Entries are named by their path, e.g. `mods/modFoo`.
+pub fn scan_mods(game_dir: &Path, settings: &[ModSetting]) -> ModList {
+    let mut by_priority: Vec<(u32, String, bool)> = list_folders(&game_dir.join("mods"))
+        .into_iter()
+        .map(|name| {
+            let setting = settings
+                .iter()
+                .find(|setting| setting.name.eq_ignore_ascii_case(&name));
+            let enabled = setting.is_none_or(|setting| setting.enabled);
+            let priority = setting.and_then(|setting| setting.priority);
+            (priority.unwrap_or(u32::MAX), name, enabled)
+        })
+        .collect();
+    // Folders are listed in name order; the stable sort keeps it among equals
+    by_priority.sort_by_key(|(priority, _, _)| *priority);
+
+    let mut mods: Vec<(String, bool)> = by_priority
+        .into_iter()
+        .map(|(_, name, enabled)| (format!("mods/{}", name), enabled))
+        .collect();
+    mods.extend(
+        list_folders(&game_dir.join("dlc"))
+            .into_iter()
+            .filter(|name| !is_base_dlc(name))
+            .map(|name| (format!("dlc/{}", name), true)),
+    );
+
+    let files: Vec<Option<PathBuf>> = mods
+        .iter()
+        .map(|(name, _)| representative_file(&game_dir.join(name)))
+        .collect();
+    let paths: Vec<PathBuf> = files.iter().flatten().cloned().collect();
+    let mut hasher = CachedHasher::open_default();
+    let mut hashed = ModList::fingerprint_parallel(&paths, DEFAULT_HASH_BUDGET, &mut hasher)
+        .0
+        .into_iter();
+    hasher.save();
+
+    let mut list = ModList::new();
+    for (index, ((name, enabled), file)) in mods.into_iter().zip(&files).enumerate() {
+        let (hash, size) = match file.as_ref().and_then(|_| hashed.next()) {
+            Some(entry) => (entry.file_hash, entry.file_size),
+            None => (UNHASHED.to_string(), 0),
+        };
+        list.push(
+            ModEntry::new(name, hash, size)
+                .with_index(index as u32)
+                .with_enabled(enabled)
+                .with_light(false),
+        );
+    }
+    list
+}
+
+/// Scans the mods and caches them for crash reports.
+///
+/// Returns the number of mods found, or `None` if the game directory is
+/// unknown.
+pub fn scan_and_cache() -> Option<usize> {
+    let game_dir = get_game_directory()?;
+    let settings = get_mods_settings_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|content| parse_mods_settings(&content))
+        .unwrap_or_default();
+    let mods = scan_mods(&game_dir, &settings);
+    let count = mods.len();
+    *CACHED_MODS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(mods);
+    Some(count)
+}
+
+/// Returns the cached mods, or an empty list if no scan has finished.
+pub fn get_cached_or_empty() -> ModList {
+    CACHED_MODS
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Returns the game directory, three levels above
+/// `bin/x64_dx12/witcher3.exe` (or `bin/x64` for DirectX 11).
+pub fn get_game_directory() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()?
+        .ancestors()
+        .nth(3)
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_mods_settings() {
+        let content = "\u{feff}[modFoo]\r\nEnabled=1\r\nPriority=2\r\n\r\n[modBar]\nEnabled=0\n\n[modBaz]\npriority = 0\n";
+        let settings = parse_mods_settings(content);
+        assert_eq!(
+            settings,
+            [
+                ModSetting {
+                    name: "modFoo".to_string(),
+                    enabled: true,
+                    priority: Some(2),
+                },
+                ModSetting {
+                    name: "modBar".to_string(),
+                    enabled: false,
+                    priority: None,
+                },
+                ModSetting {
+                    name: "modBaz".to_string(),
+                    enabled: true,
+                    priority: Some(0),
+                },
+            ]
+        );
+        assert!(parse_mods_settings("Enabled=1").is_empty());
+    }
+
+    #[test]
+    fn test_is_base_dlc() {
+        assert!(is_base_dlc("dlc1"));
+        assert!(is_base_dlc("dlc16"));
+        assert!(is_base_dlc("ep1"));
+        assert!(is_base_dlc("bob"));
+        assert!(!is_base_dlc("dlc"));
+        assert!(!is_base_dlc("dlcArmorPack"));
+    }
+
+    #[test]
+    fn test_scan_mods_orders_by_priority_then_name() {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in [
+            "mods/modZeta/content",
+            "mods/mod0000_MergedFiles/content/scripts/game",
+            "mods/modAlpha/content",
+            "mods/modDisabled/content",
+            "dlc/dlc1/content",
+            "dlc/dlcArmorPack/content",
+        ] {
+            fs::create_dir_all(dir.path().join(folder)).unwrap();
+        }
+        fs::write(dir.path().join("mods/modZeta/content/blob0.bundle"), "z").unwrap();
+        fs::write(
+            dir.path()
+                .join("mods/mod0000_MergedFiles/content/scripts/game/player.ws"),
+            "ws",
+        )
+        .unwrap();
+        let settings = parse_mods_settings("[modZeta]\nPriority=0\n[modDisabled]\nEnabled=0\n");
+
+        let mods = scan_mods(dir.path(), &settings);
+        let names: Vec<&str> = mods.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "mods/modZeta",
+                "mods/mod0000_MergedFiles",
+                "mods/modAlpha",
+                "mods/modDisabled",
+                "dlc/dlcArmorPack",
+            ]
+        );
+
+        assert_eq!(mods.0[0].file_size, 1);
+        assert_ne!(mods.0[1].file_hash, UNHASHED);
+        assert_eq!(mods.0[2].file_hash, UNHASHED);
+        assert_eq!(mods.0[3].enabled, Some(false));
+        assert_eq!(mods.0[4].index, Some(4));
+    }
+}
@@ -0,0 +1,211 @@
+//! Crash report building and submission.
+//!
+//! This module handles creating crash reports from captured crash data
+//! and submitting them to the CTD API.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+
+use ctd_capture::handler::CrashData;
+use ctd_core::api_client;
+use ctd_core::blame;
+use ctd_core::crash_arena;
+use ctd_core::crash_report::CreateCrashReport;
+use ctd_core::detours;
+use ctd_core::exception_tally;
+use ctd_core::file_hash;
+use ctd_core::graphics;
+use ctd_core::hardware_events;
+use ctd_core::known_bad;
+use ctd_core::load_order::ModList;
+use ctd_core::memory_map;
+use ctd_core::modpack;
+use ctd_core::submit;
+use ctd_core::throttle::Throttle;
+use ctd_core::version::{self, get_dll_version};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+use crate::mod_scanner;
+
+/// Errors that can occur during report submission.
+#[derive(Error, Debug)]
+pub enum ReportError {
+    /// Failed to build the crash report.
+    #[error("Failed to build crash report: {0}")]
+    BuildFailed(String),
+
+    /// Failed to submit the report.
+    #[error("Failed to submit crash report: {0}")]
+    Submission(String),
+}
+
+/// Result type for report operations.
+pub type Result<T> = std::result::Result<T, ReportError>;
+
+/// Guard to prevent multiple simultaneous submissions.
+static SUBMISSION_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Game ID for The Witcher 3 crash reports.
+pub(crate) const GAME_ID: &str = "witcher3";
+
+/// Submits a crash report on a new thread (fire-and-forget).
+///
+/// Returns the submission thread, or `None` if a submission is already in
+/// progress.
+pub fn submit_async(crash_data: CrashData) -> Option<JoinHandle<()>> {
+    if SUBMISSION_IN_PROGRESS
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        warn!("Crash report submission already in progress, skipping duplicate");
+        return None;
+    }
+
+    let submission = std::thread::spawn(move || {
+        // Honor [submission] sample_rate and max_reports_per_hour
+        let verdict = Throttle::load().admit();
+        if !verdict.is_allowed() {
+            info!("Crash report not submitted: {}", verdict);
+            crash_arena::discard_record();
+            SUBMISSION_IN_PROGRESS.store(false, Ordering::SeqCst);
+            return;
+        }
+
+        let result = build_report(&crash_data, mod_scanner::get_cached_or_empty())
+            .and_then(|report| send_report(&report));
+        SUBMISSION_IN_PROGRESS.store(false, Ordering::SeqCst);
+
+        match result {
+            Ok(response_id) => info!("Crash report submitted successfully: {}", response_id),
+            Err(e) => error!("Failed to submit crash report: {}", e),
+        }
+    });
+    Some(submission)
+}
+
+/// Submits a built report, queueing it in the spool if submission fails.
+///
+/// Returns the report ID on success.
+fn send_report(report: &CreateCrashReport) -> Result<String> {
+    submit::submit_report(report)
+        .map(|response| response.id)
+        .map_err(|e| ReportError::Submission(e.to_string()))
+}
+
+/// Builds a crash report from crash data.
+fn build_report(crash_data: &CrashData, mod_list: ModList) -> Result<CreateCrashReport> {
+    let suspected_mod = crash_data.faulting_module.as_deref().and_then(|module| {
+        blame::suspected_mod(Path::new(module), &mod_list, &blame::installed_mods())
+    });
+
+    let mut builder = CreateCrashReport::builder()
+        .game_id(GAME_ID)
+        .game_version(get_game_version())
+        .stack_trace(&crash_data.stack_trace)
+        .exception_code(format!("0x{:08X}", crash_data.exception_code))
+        .exception_address(format!("0x{:016X}", crash_data.exception_address))
+        .load_order_v2(mod_list)
+        .max_schema_version(api_client::negotiated_schema_version())
+        .handled_exceptions(exception_tally::snapshot())
+        .hardware_events(hardware_events::recent())
+        .probable_objects(crash_data.probable_objects.clone())
+        .exception_chain(crash_data.exception_chain.clone())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .graphics(graphics::capture())
+        .known_bad_modules(known_bad::scan())
+        .modpack(modpack::detect(GAME_ID))
+        .abi_context()
+        .anonymous_ids()
+        .uptime()
+        .crashed_now();
+
+    if let Some(ref module) = crash_data.faulting_module {
+        builder = builder
+            .faulting_module(module)
+            .hooked_functions(detours::scan(module));
+    }
+
+    if let Some(ref text) = crash_data.disassembly {
+        builder = builder.disassembly(text);
+    }
+
+    if crash_data.faulting_module.is_none()
+        && let Some(region) = memory_map::address_region(crash_data.exception_address)
+    {
+        builder = builder.address_region(region);
+    }
+
+    builder = builder.exception_parameters(&crash_data.exception_parameters);
+    if let Some(ref error) = crash_data.in_page_error {
+        builder = builder.in_page_error(error.clone());
+    }
+
+    if let Some(suspect) = suspected_mod {
+        info!("Suspected mod: {}", suspect);
+        builder = builder.suspected_mod(suspect);
+    }
+
+    if let Some(os_version) = version::os_version() {
+        builder = builder.os_version(os_version);
+    }
+
+    builder
+        .build()
+        .map_err(|e| ReportError::BuildFailed(e.to_string()))
+}
+
+/// Gets the game version from the running `witcher3.exe`, or "unknown".
+///
+/// Next-gen builds are 4.x; the classic 1.32 executable is reported as is.
+pub(crate) fn get_game_version() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| get_dll_version(&exe).ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ctd_core::crash_report::ProbableObject;
+    use ctd_core::load_order::ModEntry;
+
+    #[test]
+    fn test_build_report() {
+        let crash_data = CrashData {
+            exception_code: 0xC0000005,
+            exception_address: 0x7FF712345678,
+            stack_trace: "[ 0] witcher3.exe+0x1234 (0x00007FF712345678)\n".to_string(),
+            faulting_module: Some("witcher3.exe".to_string()),
+            disassembly: Some(
+                "> 0x00007FF712345678  488B81A8010000    mov rax, [rcx+0x1a8]".to_string(),
+            ),
+            registers: Vec::new(),
+            probable_objects: vec![ProbableObject {
+                location: "RCX".to_string(),
+                address: "0x1F2A0000".to_string(),
+                class_name: "CActor".to_string(),
+            }],
+            exception_chain: Vec::new(),
+            in_page_error: None,
+            exception_parameters: Vec::new(),
+        };
+        let mods = ModList::from_entries(vec![
+            ModEntry::new("mods/mod0000_MergedFiles", "0123456789abcdef", 1024).with_enabled(true),
+        ]);
+
+        let report = build_report(&crash_data, mods).unwrap();
+        assert_eq!(report.game_id, GAME_ID);
+        assert_eq!(report.exception_code, Some("0xC0000005".to_string()));
+        assert_eq!(report.faulting_module, Some("witcher3.exe".to_string()));
+        assert_eq!(report.disassembly, crash_data.disassembly);
+        assert_eq!(report.probable_objects, crash_data.probable_objects);
+    }
+
+    #[test]
+    fn test_game_id_constant() {
+        assert_eq!(GAME_ID, "witcher3");
+    }
+}
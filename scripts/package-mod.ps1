@@ -77,6 +77,22 @@ New-Item -ItemType Directory -Force -Path "$DistDir/fomod" | Out-Null
 $DllName = if ($Mod -eq "oblivion-remastered") { "main.dll" } else { "ctd-$Mod.dll" }
 Copy-Item $Dll "$DistDir/$PluginPath/$DllName"
 
+# Papyrus scripts (Skyrim, Fallout 4): CTD.pex, compiled from scripts/CTD.psc
+# with the Creation Kit's PapyrusCompiler, and the source for modders
+$ScriptFolders = @()
+if (Test-Path "$ModDir/scripts") {
+    $SourcePath = if ($Mod -eq "fallout4") { "Scripts/Source/User" } else { "Source/Scripts" }
+    New-Item -ItemType Directory -Force -Path "$DistDir/Scripts", "$DistDir/$SourcePath" | Out-Null
+    Copy-Item "$ModDir/scripts/*.psc" "$DistDir/$SourcePath"
+    $Compiled = Get-ChildItem "$ModDir/scripts/*.pex" -ErrorAction SilentlyContinue
+    if ($Compiled) {
+        Copy-Item $Compiled "$DistDir/Scripts"
+    } else {
+        Write-Warning "No compiled scripts in $ModDir/scripts; Papyrus calls to CTD will fail"
+    }
+    $ScriptFolders = @("Scripts", $SourcePath.Split("/")[0]) | Select-Object -Unique
+}
+
 # Create config
 @"
 # CTD (Crash to Desktop Reporter) Configuration
@@ -96,12 +112,15 @@ url = "https://ctd.ezmode.games"
 "@ | Set-Content "$DistDir/fomod/info.xml" -Encoding UTF8
 
 $RootFolder = $PluginPath.Split("/")[0]
+$Folders = @($RootFolder) + $ScriptFolders | ForEach-Object {
+    "    <folder source=`"$_`" destination=`"$_`"/>"
+}
 @"
 <?xml version="1.0" encoding="UTF-8"?>
 <config xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
   <moduleName>CTD - $Mod</moduleName>
   <requiredInstallFiles>
-    <folder source="$RootFolder" destination="$RootFolder"/>
+$($Folders -join "`n")
   </requiredInstallFiles>
 </config>
 "@ | Set-Content "$DistDir/fomod/ModuleConfig.xml" -Encoding UTF8
@@ -0,0 +1,233 @@
+//! `ctd config` - validate and create configuration files.
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use ctd_core::config::{Config, ConfigIssue};
+
+use crate::CliResult;
+
+/// Validates a config file, or the discovered one when no path is given.
+pub fn validate(path: Option<PathBuf>) -> CliResult {
+    let Some(path) = path.or_else(Config::discover_path) else {
+        println!("No config file found; defaults will be used. Searched:");
+        for candidate in Config::candidate_paths() {
+            println!("  {}", candidate.display());
+        }
+        return Ok(());
+    };
+
+    let config = Config::load_from_path(&path)?;
+    let (errors, warnings): (Vec<_>, Vec<_>) = config
+        .validate()
+        .into_iter()
+        .partition(ConfigIssue::is_error);
+    for warning in &warnings {
+        eprintln!("  warning: {}", warning);
+    }
+    if !errors.is_empty() {
+        for error in &errors {
+            eprintln!("  {}", error);
+        }
+        return Err(format!("{} is invalid ({} problems)", path.display(), errors.len()).into());
+    }
+
+    println!("{} is valid", path.display());
+    println!("  api.url = {}", config.api.url);
+    println!(
+        "  api.api_key = {}",
+        if config.api.api_key.is_some() {
+            "set"
+        } else {
+            "not set"
+        }
+    );
+    for o in Config::active_env_overrides() {
+        println!("  {} overridden by {}", o.field, o.var);
+    }
+    Ok(())
+}
+
+/// Writes the example config to `path`.
+pub fn init(path: &Path, force: bool) -> CliResult {
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists (use --force to overwrite)",
+            path.display()
+        )
+        .into());
+    }
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, Config::example())?;
+
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Stores an API key in the `[api]` section of a config file.
+///
+/// By default the key is DPAPI-encrypted into `api_key_encrypted`; any
+/// existing `api_key` or `api_key_encrypted` line is replaced.
+pub fn set_key(key: Option<String>, path: Option<PathBuf>, plaintext: bool) -> CliResult {
+    let key = match key {
+        Some(key) => key,
+        None => prompt("API key: ")?,
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("API key is empty".into());
+    }
+
+    let path = path
+        .or_else(Config::discover_path)
+        .unwrap_or_else(|| PathBuf::from("ctd.toml"));
+    let contents = if path.exists() {
+        fs::read_to_string(&path)?
+    } else {
+        Config::example().to_string()
+    };
+
+    let line = if plaintext {
+        format!("api_key = \"{}\"", key)
+    } else {
+        format!("api_key_encrypted = \"{}\"", Config::encrypt_api_key(key)?)
+    };
+    fs::write(&path, replace_api_key(&contents, &line))?;
+
+    let stored = if plaintext { "plaintext" } else { "encrypted" };
+    println!("Stored {} API key in {}", stored, path.display());
+    Ok(())
+}
+
+/// Replaces the key lines of the `[api]` section with `line`, keeping comments.
+fn replace_api_key(contents: &str, line: &str) -> String {
+    let mut lines: Vec<&str> = contents.lines().collect();
+
+    let Some(header) = lines.iter().position(|l| l.trim() == "[api]") else {
+        let mut out = contents.trim_end().to_string();
+        out.push_str(&format!("\n\n[api]\n{}\n", line));
+        return out;
+    };
+
+    let end = lines[header + 1..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map_or(lines.len(), |i| header + 1 + i);
+
+    let is_key_line = |l: &&str| {
+        let Some((name, _)) = l.split_once('=') else {
+            return false;
+        };
+        matches!(name.trim(), "api_key" | "api_key_encrypted")
+    };
+    let existing = lines[header + 1..end].iter().position(is_key_line);
+
+    let mut section: Vec<&str> = lines[header + 1..end]
+        .iter()
+        .copied()
+        .filter(|l| !is_key_line(l))
+        .collect();
+    section.insert(existing.unwrap_or(0), line);
+    lines.splice(header + 1..end, section);
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Reads a line from stdin after printing `label`.
+fn prompt(label: &str) -> io::Result<String> {
+    print!("{}", label);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn init_writes_valid_config() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("ctd.toml");
+
+        init(&path, false).unwrap();
+        assert!(validate(Some(path)).is_ok());
+    }
+
+    #[test]
+    fn init_refuses_to_overwrite() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+        fs::write(&path, "# mine").unwrap();
+
+        assert!(init(&path, false).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# mine");
+
+        init(&path, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), Config::example());
+    }
+
+    #[test]
+    fn validate_allows_warnings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+        fs::write(&path, "[api]\nurl = \"http://localhost:3000\"\n").unwrap();
+
+        assert!(validate(Some(path)).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_bad_url() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+        fs::write(&path, "[api]\nurl = \"ftp://example.com\"\n").unwrap();
+
+        assert!(validate(Some(path)).is_err());
+    }
+
+    #[test]
+    fn replace_api_key_keeps_comments() {
+        let contents = "# Server\n[api]\n# key\napi_key = \"old\"\ntimeout_secs = 30\n\n[symbols]\napi_key = \"x\"\n";
+        let updated = replace_api_key(contents, "api_key_encrypted = \"abc\"");
+
+        assert_eq!(
+            updated,
+            "# Server\n[api]\n# key\napi_key_encrypted = \"abc\"\ntimeout_secs = 30\n\n[symbols]\napi_key = \"x\"\n"
+        );
+    }
+
+    #[test]
+    fn replace_api_key_adds_section() {
+        let updated = replace_api_key("allow_test_crash = false\n", "api_key = \"k\"");
+        assert_eq!(
+            updated,
+            "allow_test_crash = false\n\n[api]\napi_key = \"k\"\n"
+        );
+
+        let updated = replace_api_key("[api]\nurl = \"https://x\"\n", "api_key = \"k\"");
+        assert_eq!(updated, "[api]\napi_key = \"k\"\nurl = \"https://x\"\n");
+    }
+
+    #[test]
+    fn set_key_plaintext_round_trips() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ctd.toml");
+
+        set_key(Some("ctd_abc".into()), Some(path.clone()), true).unwrap();
+        let config = Config::load_from_path(&path).unwrap();
+        if std::env::var("CTD_API_KEY").is_err() {
+            assert_eq!(config.api.api_key.as_deref(), Some("ctd_abc"));
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! `ctd test-connection` - check that the API server is reachable.
+
+use ctd_core::api_client::{ApiClient, HEALTH_PATH};
+use ctd_core::config::Config;
+
+use crate::CliResult;
+
+/// Pings the configured server and reports the result.
+pub async fn run() -> CliResult {
+    let config = Config::load()?;
+    println!(
+        "Checking {}{}",
+        config.api.url.trim_end_matches('/'),
+        HEALTH_PATH
+    );
+
+    check(&config).await?;
+    println!("OK");
+    if config.api.api_key.is_none() {
+        println!("note: no api_key configured");
+    }
+    Ok(())
+}
+
+/// Pings the configured server, returning a readable error on failure.
+pub async fn check(config: &Config) -> Result<(), String> {
+    let client = ApiClient::new(config.api.clone()).map_err(|e| e.to_string())?;
+    client.ping().await.map_err(|e| e.to_string())
+}
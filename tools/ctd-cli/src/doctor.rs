@@ -0,0 +1,295 @@
+//! `ctd doctor` - installation diagnostics.
+//!
+//! Most support threads come down to misconfiguration, so this runs every
+//! check a support volunteer would ask about and prints a pass/fail report.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use ctd_core::config::{Config, ConfigIssue};
+use ctd_core::spool::Spool;
+
+use crate::CliResult;
+use crate::connection;
+
+/// Crash loggers that install their own exception handlers.
+///
+/// Each entry is a display name and a path relative to the game directory.
+const CONFLICTING_LOGGERS: &[(&str, &str)] = &[
+    ("Crash Logger SSE", "Data/SKSE/Plugins/CrashLogger.dll"),
+    ("Trainwreck", "Data/SKSE/Plugins/Trainwreck.dll"),
+    (".NET Script Framework", "Data/NetScriptFramework"),
+    ("Buffout 4", "Data/F4SE/Plugins/Buffout4.dll"),
+    ("Crash Logger (NVSE)", "Data/NVSE/Plugins/CrashLogger.dll"),
+];
+
+/// Result of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+    // Only produced by checks that do not apply on the current platform
+    #[cfg_attr(windows, allow(dead_code))]
+    Skip,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Status::Pass => "PASS",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+            Status::Skip => "SKIP",
+        };
+        write!(f, "[{}]", label)
+    }
+}
+
+/// One line of the doctor report.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Runs all checks and prints the report.
+pub async fn run(game_dir: Option<PathBuf>) -> CliResult {
+    let game_dir = match game_dir {
+        Some(dir) => dir,
+        None => std::env::current_dir()?,
+    };
+
+    let config = Config::load().unwrap_or_default();
+    let checks = vec![
+        check_config(),
+        check_api(&config).await,
+        check_spool(&Spool::open_default()),
+        check_conflicting_loggers(&game_dir),
+        check_veh(),
+    ];
+
+    println!("CTD doctor");
+    for check in &checks {
+        println!("  {} {:<16} {}", check.status, check.name, check.detail);
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        return Err(format!("{} checks failed", failed).into());
+    }
+    Ok(())
+}
+
+/// Checks that a config file is found and valid.
+fn check_config() -> Check {
+    let Some(path) = Config::discover_path() else {
+        return Check::new(
+            "Config file",
+            Status::Warn,
+            "none found; using defaults (run `ctd config init`)",
+        );
+    };
+
+    match Config::load_from_path(&path) {
+        Ok(config) => {
+            let issues = config.validate();
+            let status = if issues.iter().any(ConfigIssue::is_error) {
+                Status::Fail
+            } else if !issues.is_empty() {
+                Status::Warn
+            } else {
+                Status::Pass
+            };
+            if issues.is_empty() {
+                Check::new("Config file", status, path.display().to_string())
+            } else {
+                let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+                Check::new(
+                    "Config file",
+                    status,
+                    format!("{}: {}", path.display(), messages.join("; ")),
+                )
+            }
+        }
+        Err(e) => Check::new(
+            "Config file",
+            Status::Fail,
+            format!("{}: {}", path.display(), e),
+        ),
+    }
+}
+
+/// Checks that the API server is reachable and accepts the API key.
+async fn check_api(config: &Config) -> Check {
+    match connection::check(config).await {
+        Ok(()) => Check::new("API reachable", Status::Pass, config.api.url.clone()),
+        Err(e) => Check::new("API reachable", Status::Fail, e),
+    }
+}
+
+/// Checks that failed reports can be queued.
+fn check_spool(spool: &Spool) -> Check {
+    match spool.check_writable() {
+        Ok(()) => Check::new(
+            "Spool writable",
+            Status::Pass,
+            spool.dir().display().to_string(),
+        ),
+        Err(e) => Check::new("Spool writable", Status::Fail, e.to_string()),
+    }
+}
+
+/// Checks for other crash loggers that compete for the same exceptions.
+fn check_conflicting_loggers(game_dir: &Path) -> Check {
+    let found: Vec<&str> = CONFLICTING_LOGGERS
+        .iter()
+        .filter(|(_, path)| game_dir.join(path).exists())
+        .map(|(name, _)| *name)
+        .collect();
+
+    if found.is_empty() {
+        Check::new(
+            "Crash loggers",
+            Status::Pass,
+            format!("none found in {}", game_dir.display()),
+        )
+    } else {
+        Check::new(
+            "Crash loggers",
+            Status::Warn,
+            format!("{} installed; reports may be incomplete", found.join(", ")),
+        )
+    }
+}
+
+/// Checks that a vectored exception handler fires, in a sandbox child process.
+#[cfg(windows)]
+fn check_veh() -> Check {
+    let status = std::env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .args(["doctor", "--veh-probe"])
+            .status()
+    });
+
+    match status {
+        Ok(status) if status.success() => {
+            Check::new("VEH handler", Status::Pass, "fired in sandbox process")
+        }
+        Ok(status) => Check::new(
+            "VEH handler",
+            Status::Fail,
+            format!("sandbox process exited with {}", status),
+        ),
+        Err(e) => Check::new(
+            "VEH handler",
+            Status::Fail,
+            format!("failed to start sandbox process: {}", e),
+        ),
+    }
+}
+
+#[cfg(not(windows))]
+fn check_veh() -> Check {
+    Check::new("VEH handler", Status::Skip, "only applies on Windows")
+}
+
+/// Exception code raised by the VEH probe (customer bit set, never fatal).
+#[cfg(windows)]
+const PROBE_EXCEPTION_CODE: u32 = 0xE0C7D001;
+
+/// Registers a VEH, raises a probe exception, and exits 0 if the handler fired.
+///
+/// Runs in a child process so a broken handler cannot take down the doctor.
+#[cfg(windows)]
+pub fn veh_probe() -> ! {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use windows::Win32::System::Diagnostics::Debug::{
+        AddVectoredExceptionHandler, EXCEPTION_CONTINUE_EXECUTION, EXCEPTION_CONTINUE_SEARCH,
+        EXCEPTION_POINTERS, RaiseException, RemoveVectoredExceptionHandler,
+    };
+
+    static FIRED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "system" fn handler(info: *mut EXCEPTION_POINTERS) -> i32 {
+        // SAFETY: Windows guarantees the pointers are valid during the callback
+        let code = unsafe { (*info).ExceptionRecord.as_ref() }.map(|r| r.ExceptionCode.0 as u32);
+        if code == Some(PROBE_EXCEPTION_CODE) {
+            FIRED.store(true, Ordering::SeqCst);
+            return EXCEPTION_CONTINUE_EXECUTION;
+        }
+        EXCEPTION_CONTINUE_SEARCH
+    }
+
+    let registration = unsafe { AddVectoredExceptionHandler(1, Some(handler)) };
+    if registration.is_null() {
+        std::process::exit(2);
+    }
+
+    unsafe {
+        RaiseException(PROBE_EXCEPTION_CODE, 0, None);
+        RemoveVectoredExceptionHandler(registration);
+    }
+
+    std::process::exit(if FIRED.load(Ordering::SeqCst) { 0 } else { 1 });
+}
+
+#[cfg(not(windows))]
+pub fn veh_probe() -> ! {
+    std::process::exit(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn detects_conflicting_loggers() {
+        let dir = tempdir().unwrap();
+        let check = check_conflicting_loggers(dir.path());
+        assert_eq!(check.status, Status::Pass);
+
+        let plugins = dir.path().join("Data").join("SKSE").join("Plugins");
+        std::fs::create_dir_all(&plugins).unwrap();
+        std::fs::write(plugins.join("CrashLogger.dll"), b"").unwrap();
+
+        let check = check_conflicting_loggers(dir.path());
+        assert_eq!(check.status, Status::Warn);
+        assert!(check.detail.contains("Crash Logger SSE"));
+    }
+
+    #[test]
+    fn spool_check_fails_for_unwritable_path() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("not-a-dir");
+        std::fs::write(&file, b"").unwrap();
+
+        let check = check_spool(&Spool::new(file.join("pending")));
+        assert_eq!(check.status, Status::Fail);
+    }
+
+    #[test]
+    fn spool_check_passes_for_temp_dir() {
+        let dir = tempdir().unwrap();
+        let check = check_spool(&Spool::new(dir.path()));
+        assert_eq!(check.status, Status::Pass);
+    }
+
+    #[test]
+    fn status_labels() {
+        assert_eq!(Status::Pass.to_string(), "[PASS]");
+        assert_eq!(Status::Fail.to_string(), "[FAIL]");
+    }
+}
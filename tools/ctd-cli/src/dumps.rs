@@ -0,0 +1,108 @@
+//! `ctd dumps` - enable WER crash dumps for a game and submit the dumps.
+
+use std::path::{Path, PathBuf};
+
+use ctd_core::api_client::{self, ApiClient};
+use ctd_core::config::Config;
+use ctd_core::crash_report::CreateCrashReport;
+use ctd_core::file_hash;
+use ctd_core::history;
+use ctd_core::load_order::ModList;
+use ctd_core::wer::{self, DumpCrash};
+
+use crate::CliResult;
+
+/// Registers `exe` for WER `LocalDumps` in the configured dump folder.
+pub fn enable(exe: &str, dir: Option<PathBuf>) -> CliResult {
+    let dir = dir.unwrap_or_else(|| Config::load().unwrap_or_default().dumps.dump_dir());
+    let exe_name = Path::new(exe)
+        .file_name()
+        .ok_or("the executable has no file name")?
+        .to_string_lossy();
+
+    wer::enable_local_dumps(&exe_name, &dir)
+        .map_err(|e| format!("{} (run as administrator to write the registry key)", e))?;
+    println!(
+        "Crash dumps of {} will be written to {}",
+        exe_name,
+        dir.display()
+    );
+    println!("Submit them with `ctd dumps submit`");
+    Ok(())
+}
+
+/// Reads each dump in the dump folder and submits it, continuing past
+/// failures. Submitted dumps are moved to `submitted/`.
+pub async fn submit(dir: Option<PathBuf>, game_id: Option<String>, dry_run: bool) -> CliResult {
+    let dir = dir.unwrap_or_else(|| Config::load().unwrap_or_default().dumps.dump_dir());
+    let dumps = wer::find_dumps(&dir);
+    if dumps.is_empty() {
+        println!("No crash dumps in {}", dir.display());
+        return Ok(());
+    }
+
+    let client = if dry_run {
+        None
+    } else {
+        Some(ApiClient::from_config()?)
+    };
+
+    let mut failed = 0;
+    for path in &dumps {
+        let game_id = game_id.clone().unwrap_or_else(|| {
+            wer::game_id_from_exe(Path::new(wer::dump_executable(path).unwrap_or("unknown")))
+        });
+        let report = match read_report(path, &game_id).await {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let Some(client) = &client else {
+            println!("{}", report.to_json()?);
+            continue;
+        };
+
+        match client.submit_crash_report(&report).await {
+            Ok(response) => {
+                println!("{}: submitted as {}", path.display(), response.id);
+                if let Some(issue) = client.known_issue_for(&report).await {
+                    println!("  {}", issue.advice());
+                }
+                history::record_submitted(&report, &response);
+                if let Err(e) = wer::mark_submitted(path) {
+                    eprintln!("{}: {}", path.display(), e);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                history::record_pending(&report);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} dumps failed", failed, dumps.len()).into());
+    }
+    Ok(())
+}
+
+/// Reads a dump into a report. The dump carries no mod list, so the load
+/// order is empty.
+async fn read_report(
+    path: &Path,
+    game_id: &str,
+) -> Result<CreateCrashReport, Box<dyn std::error::Error>> {
+    let crash = DumpCrash::read(path).await?;
+    Ok(crash
+        .report_builder(game_id, ModList::new())
+        .max_schema_version(api_client::negotiated_schema_version())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .anonymous_ids()
+        .notes("Imported from a Windows Error Reporting dump")
+        .build()?)
+}
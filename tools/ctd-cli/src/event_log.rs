@@ -0,0 +1,17 @@
+//! `ctd event-log` - the Windows Event Log source for crash entries.
+
+use ctd_core::event_log;
+
+use crate::CliResult;
+
+/// Registers the event source so Event Viewer shows entries in full.
+pub fn register() -> CliResult {
+    event_log::register_source()
+        .map_err(|e| format!("{} (run as administrator to write the registry key)", e))?;
+    println!(
+        "Registered the \"{}\" source in the Application log",
+        event_log::SOURCE
+    );
+    println!("Turn entries on with `event_log = true` under [logging] in ctd.toml");
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! `ctd history` - reports generated on this machine, submitted or not.
+
+use ctd_core::history::History;
+
+use crate::CliResult;
+
+/// Lists the newest `limit` recorded reports.
+pub fn list(limit: usize) -> CliResult {
+    let history = History::open_default();
+    let entries = history.list();
+    if entries.is_empty() {
+        println!("No reports recorded in {}", history.dir().display());
+        return Ok(());
+    }
+
+    for entry in entries.iter().take(limit) {
+        println!(
+            "{}  {:9}  {}  {}  {}",
+            entry.key,
+            entry.status.as_str(),
+            entry.report.game_id,
+            entry.report.exception_code.as_deref().unwrap_or("-"),
+            entry.report_id.as_deref().unwrap_or("-")
+        );
+    }
+    if entries.len() > limit {
+        println!(
+            "{} more: ctd history list --limit {}",
+            entries.len() - limit,
+            entries.len()
+        );
+    }
+    Ok(())
+}
+
+/// Prints a recorded report, found by local key or server report ID.
+pub fn show(id: &str) -> CliResult {
+    let entry = History::open_default()
+        .get(id)
+        .ok_or_else(|| format!("No report {} in the local history", id))?;
+
+    println!("{} ({})", entry.key, entry.status.as_str());
+    if let Some(report_id) = &entry.report_id {
+        println!("Report ID: {}", report_id);
+    }
+    if let Some(url) = &entry.share_url {
+        println!("Share URL: {}", url);
+    }
+    println!("Recorded:  {}", entry.recorded_at);
+    if let Some(submitted_at) = entry.submitted_at {
+        println!("Submitted: {}", submitted_at);
+    }
+    println!("{}", entry.report.to_json()?);
+    Ok(())
+}
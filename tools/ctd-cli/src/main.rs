@@ -0,0 +1,311 @@
+//! CTD command-line companion.
+//!
+//! Drives the crash reporting pipeline without launching a game: submit
+//! existing crash logs, check the API connection, run a test crash, manage `ctd.toml`,
+//! symbolicate stack traces, flush reports queued after failed submissions,
+//! submit Windows Error Reporting dumps, browse the local report history,
+//! register the Event Log source, and manage reports already on the server.
+
+mod config;
+mod connection;
+mod doctor;
+mod dumps;
+mod event_log;
+mod history;
+mod note;
+mod pending;
+mod reports;
+mod resolve;
+mod submit;
+mod test_crash;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use tracing_subscriber::EnvFilter;
+
+/// Result type shared by all subcommands.
+pub type CliResult = Result<(), Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "ctd", version, about = "CTD crash reporter companion")]
+struct Cli {
+    /// Enable debug logging.
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Import crash logs (Crash Logger, Buffout 4, .NET Script Framework) and submit them.
+    Submit {
+        /// Crash log files to submit.
+        #[arg(required = true)]
+        logs: Vec<PathBuf>,
+
+        /// Print the report JSON instead of submitting.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check that the configured API server is reachable.
+    TestConnection,
+
+    /// Crash a child process on purpose and print the report for it.
+    ///
+    /// Requires `allow_test_crash = true` in ctd.toml.
+    TestCrash {
+        /// Submit the synthetic test report instead of only printing it.
+        #[arg(long)]
+        submit: bool,
+
+        /// Internal: crash this process.
+        #[arg(long, hide = true)]
+        child: bool,
+    },
+
+    /// Diagnose common installation problems.
+    Doctor {
+        /// Game directory to scan for conflicting crash loggers (default: current directory).
+        #[arg(long)]
+        game_dir: Option<PathBuf>,
+
+        /// Internal: run the VEH sandbox probe and exit.
+        #[arg(long, hide = true)]
+        veh_probe: bool,
+    },
+
+    /// Manage the configuration file.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// Symbolicate a stack trace file with local PDBs.
+    Resolve {
+        /// File containing `module+0xOFFSET` frames, one per line.
+        trace: PathBuf,
+
+        /// Directory containing the modules named in the trace; without it,
+        /// PDBs are matched by file name only.
+        #[arg(long)]
+        modules: Option<PathBuf>,
+
+        /// Additional directories to search for PDB files.
+        #[arg(long = "search-dir")]
+        search_dirs: Vec<PathBuf>,
+    },
+
+    /// Manage reports queued after failed submissions.
+    #[command(subcommand)]
+    Pending(PendingCommand),
+
+    /// Manage reports submitted with your API key.
+    #[command(subcommand)]
+    Reports(ReportsCommand),
+
+    /// Browse reports generated on this machine, submitted or not.
+    #[command(subcommand)]
+    History(HistoryCommand),
+
+    /// Collect and submit Windows Error Reporting crash dumps.
+    #[command(subcommand)]
+    Dumps(DumpsCommand),
+
+    /// Set up the Windows Event Log source for crash entries.
+    #[command(subcommand)]
+    EventLog(EventLogCommand),
+
+    /// Add a note to a report, e.g. what you were doing when the game crashed.
+    Note {
+        /// Report ID.
+        report_id: String,
+
+        /// Note text.
+        text: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Check a config file for errors.
+    Validate {
+        /// Config file to check (default: the discovered config file).
+        path: Option<PathBuf>,
+    },
+
+    /// Write an example config file.
+    Init {
+        /// Destination path.
+        #[arg(default_value = "ctd.toml")]
+        path: PathBuf,
+
+        /// Overwrite an existing file.
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Store an API key in the config file, encrypted for the current Windows user.
+    SetKey {
+        /// The API key (prompted for when omitted, keeping it out of shell history).
+        key: Option<String>,
+
+        /// Config file to update (default: the discovered config file, or ./ctd.toml).
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Store the key in plaintext as `api_key` instead.
+        #[arg(long)]
+        plaintext: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PendingCommand {
+    /// List queued reports.
+    List,
+
+    /// Submit all queued reports.
+    Flush,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List recorded reports, newest first.
+    List {
+        /// Number of reports to show.
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Print a recorded report, with its status and share URL.
+    Show {
+        /// Local key or server report ID.
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DumpsCommand {
+    /// Have WER write a dump when this executable crashes (needs administrator rights).
+    Enable {
+        /// Executable name or path, e.g. `Game.exe`.
+        exe: String,
+
+        /// Dump folder (default: `[dumps] directory` in ctd.toml).
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+
+    /// Submit the dumps in the dump folder, with the crashing thread's stack.
+    Submit {
+        /// Dump folder (default: `[dumps] directory` in ctd.toml).
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Game ID sent in reports (default: from the dump's file name, e.g. `some-game`).
+        #[arg(long)]
+        game_id: Option<String>,
+
+        /// Print the report JSON instead of submitting.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EventLogCommand {
+    /// Register the "CTD Crash Reporter" source (needs administrator rights).
+    Register,
+}
+
+#[derive(Subcommand)]
+enum ReportsCommand {
+    /// List your reports, newest first.
+    List {
+        /// Page number.
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+    },
+
+    /// Print a report as JSON.
+    Show {
+        /// Report ID.
+        id: String,
+    },
+
+    /// Permanently delete a report.
+    Delete {
+        /// Report ID.
+        id: String,
+
+        /// Skip the confirmation prompt.
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging(cli.verbose);
+
+    let result = match cli.command {
+        Command::Submit { logs, dry_run } => submit::run(&logs, dry_run).await,
+        Command::TestConnection => connection::run().await,
+        Command::TestCrash { child: true, .. } => test_crash::child(),
+        Command::TestCrash { submit, .. } => test_crash::run(submit).await,
+        Command::Doctor {
+            veh_probe: true, ..
+        } => doctor::veh_probe(),
+        Command::Doctor { game_dir, .. } => doctor::run(game_dir).await,
+        Command::Config(ConfigCommand::Validate { path }) => config::validate(path),
+        Command::Config(ConfigCommand::Init { path, force }) => config::init(&path, force),
+        Command::Config(ConfigCommand::SetKey {
+            key,
+            path,
+            plaintext,
+        }) => config::set_key(key, path, plaintext),
+        Command::Resolve {
+            trace,
+            modules,
+            search_dirs,
+        } => resolve::run(&trace, modules.as_deref(), search_dirs),
+        Command::Pending(PendingCommand::List) => pending::list(),
+        Command::Pending(PendingCommand::Flush) => pending::flush().await,
+        Command::Reports(ReportsCommand::List { page }) => reports::list(page).await,
+        Command::Reports(ReportsCommand::Show { id }) => reports::show(&id).await,
+        Command::Reports(ReportsCommand::Delete { id, yes }) => reports::delete(&id, yes).await,
+        Command::Note { report_id, text } => note::run(&report_id, &text).await,
+        Command::History(HistoryCommand::List { limit }) => history::list(limit),
+        Command::History(HistoryCommand::Show { id }) => history::show(&id),
+        Command::Dumps(DumpsCommand::Enable { exe, dir }) => dumps::enable(&exe, dir),
+        Command::Dumps(DumpsCommand::Submit {
+            dir,
+            game_id,
+            dry_run,
+        }) => dumps::submit(dir, game_id, dry_run).await,
+        Command::EventLog(EventLogCommand::Register) => event_log::register(),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Initializes logging to stderr, honoring `RUST_LOG`.
+fn init_logging(verbose: bool) {
+    let default = if verbose { "debug" } else { "warn" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
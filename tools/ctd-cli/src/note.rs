@@ -0,0 +1,14 @@
+//! `ctd note` - attach context to a report after the fact.
+
+use ctd_core::api_client::ApiClient;
+
+use crate::CliResult;
+
+/// Appends a note to a report submitted with the configured API key.
+pub async fn run(report_id: &str, text: &str) -> CliResult {
+    ApiClient::from_config()?
+        .append_note(report_id, text)
+        .await?;
+    println!("Note added to {}", report_id);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! `ctd pending` - inspect and flush the pending report spool.
+
+use ctd_core::api_client::ApiClient;
+use ctd_core::spool::Spool;
+
+use crate::CliResult;
+
+/// Lists queued reports.
+pub fn list() -> CliResult {
+    let spool = Spool::open_default();
+    let pending = spool.pending();
+    println!(
+        "{} pending reports in {}",
+        pending.len(),
+        spool.dir().display()
+    );
+
+    for path in pending {
+        match spool.load(&path) {
+            Ok(report) => println!(
+                "  {} {} {}",
+                report.crashed_at,
+                report.game_id,
+                report.exception_code.as_deref().unwrap_or("-")
+            ),
+            Err(e) => println!("  {}: {}", path.display(), e),
+        }
+    }
+    Ok(())
+}
+
+/// Submits all queued reports.
+pub async fn flush() -> CliResult {
+    let spool = Spool::open_default();
+    if spool.is_empty() {
+        println!("No pending reports");
+        return Ok(());
+    }
+
+    let client = ApiClient::from_config()?;
+    let summary = spool.flush(&client).await;
+    println!(
+        "Submitted {}, failed {}, rejected {}",
+        summary.submitted, summary.failed, summary.rejected
+    );
+
+    if summary.failed > 0 {
+        return Err(format!("{} reports are still pending", summary.failed).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! `ctd reports` - list, inspect, and delete reports submitted with your API key.
+
+use std::io::{self, BufRead, Write};
+
+use ctd_core::api_client::ApiClient;
+
+use crate::CliResult;
+
+/// Lists one page of reports, newest first.
+pub async fn list(page: u32) -> CliResult {
+    let listed = ApiClient::from_config()?.list_my_reports(page).await?;
+    if listed.reports.is_empty() {
+        println!("No reports on page {}", listed.page);
+        return Ok(());
+    }
+
+    for report in &listed.reports {
+        println!(
+            "{}  {} {}  {}  {}  {}",
+            report.id,
+            report.game_id,
+            report.game_version,
+            report.report_type,
+            report.exception_code.as_deref().unwrap_or("-"),
+            report.faulting_module.as_deref().unwrap_or("-")
+        );
+    }
+    println!("Page {} ({} reports total)", listed.page, listed.total);
+    if listed.has_more() {
+        println!("More: ctd reports list --page {}", listed.page + 1);
+    }
+    Ok(())
+}
+
+/// Prints a report as JSON.
+pub async fn show(id: &str) -> CliResult {
+    let stored = ApiClient::from_config()?.get_report(id).await?;
+    let visibility = if stored.is_public {
+        "public"
+    } else {
+        "private"
+    };
+    println!(
+        "{} (submitted {}, {})",
+        stored.id, stored.submitted_at, visibility
+    );
+    println!("{}", stored.report.to_json()?);
+    Ok(())
+}
+
+/// Deletes a report after confirmation.
+pub async fn delete(id: &str, yes: bool) -> CliResult {
+    if !yes && !confirm(&format!("Delete report {}? [y/N] ", id))? {
+        println!("Cancelled");
+        return Ok(());
+    }
+
+    ApiClient::from_config()?.delete_report(id).await?;
+    println!("Deleted {}", id);
+    Ok(())
+}
+
+/// Asks a yes/no question on stdin, defaulting to no.
+fn confirm(prompt: &str) -> io::Result<bool> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
@@ -0,0 +1,103 @@
+//! `ctd resolve` - symbolicate a stack trace file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ctd_core::config::Config;
+use ctd_core::symbols::{PdbMatchMode, SymbolResolver, format_stack_trace};
+
+use crate::CliResult;
+
+/// Resolves every `module+0xOFFSET` frame in the trace file.
+pub fn run(trace: &Path, modules: Option<&Path>, search_dirs: Vec<PathBuf>) -> CliResult {
+    let contents = fs::read_to_string(trace)?;
+    let config = Config::load()?;
+
+    let mut resolver = SymbolResolver::from_config(&config.symbols);
+    let mode = match_mode(config.symbols.match_mode, modules);
+    if mode != resolver.match_mode() {
+        eprintln!(
+            "Without --modules PDBs can't be checked against their modules; matching by name"
+        );
+        resolver.set_match_mode(mode);
+    }
+    for dir in search_dirs {
+        resolver.add_search_dir(dir);
+    }
+
+    let frames: Vec<_> = contents
+        .lines()
+        .filter_map(parse_frame)
+        .map(|(module, offset)| {
+            // With a module directory, the resolver can read each module's
+            // CodeView record and look for PDBs next to it
+            let module_path = match modules {
+                Some(dir) => dir.join(module),
+                None => PathBuf::from(module),
+            };
+            resolver.resolve(&module_path, offset)
+        })
+        .collect();
+
+    if frames.is_empty() {
+        return Err(format!("no module+0xOFFSET frames found in {}", trace.display()).into());
+    }
+
+    println!("{}", format_stack_trace(&frames));
+
+    let resolved = frames.iter().filter(|f| f.is_resolved()).count();
+    eprintln!("{} of {} frames resolved", resolved, frames.len());
+    Ok(())
+}
+
+/// Returns the match mode to resolve with: without the modules there is no
+/// CodeView record to check a PDB against, so PDBs are matched by name.
+fn match_mode(configured: PdbMatchMode, modules: Option<&Path>) -> PdbMatchMode {
+    match modules {
+        Some(_) => configured,
+        None => PdbMatchMode::BestEffort,
+    }
+}
+
+/// Extracts `(module, offset)` from the first `module+0xOFFSET` token in a line.
+fn parse_frame(line: &str) -> Option<(&str, u64)> {
+    line.split_whitespace().find_map(|token| {
+        let (module, offset) = token.split_once("+0x")?;
+        let offset = u64::from_str_radix(offset, 16).ok()?;
+        (!module.is_empty()).then_some((module, offset))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ctd_frame_lines() {
+        assert_eq!(
+            parse_frame("[0] SkyrimSE.exe+0x2fc2a0 (0x7ff6d4b3c2a0)"),
+            Some(("SkyrimSE.exe", 0x2FC2A0))
+        );
+        assert_eq!(parse_frame("MyMod.dll+0x1A2B"), Some(("MyMod.dll", 0x1A2B)));
+    }
+
+    #[test]
+    fn matches_by_name_without_modules() {
+        let modules = Path::new("C:/Games/Skyrim");
+        assert_eq!(
+            match_mode(PdbMatchMode::StrictMatch, Some(modules)),
+            PdbMatchMode::StrictMatch
+        );
+        assert_eq!(
+            match_mode(PdbMatchMode::StrictMatch, None),
+            PdbMatchMode::BestEffort
+        );
+    }
+
+    #[test]
+    fn skips_lines_without_frames() {
+        assert_eq!(parse_frame("Stack trace:"), None);
+        assert_eq!(parse_frame("[0] +0x1234"), None);
+        assert_eq!(parse_frame("[0] game.exe+0xZZ"), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! `ctd submit` - import and submit existing crash logs.
+
+use std::path::PathBuf;
+
+use ctd_core::api_client::ApiClient;
+use ctd_core::history;
+use ctd_core::import;
+
+use crate::CliResult;
+
+/// Imports each log and submits it, continuing past failures.
+pub async fn run(logs: &[PathBuf], dry_run: bool) -> CliResult {
+    let client = if dry_run {
+        None
+    } else {
+        Some(ApiClient::from_config()?)
+    };
+
+    let mut failed = 0;
+    for log in logs {
+        let report = match import::import_file(log) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("{}: {}", log.display(), e);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let Some(client) = &client else {
+            println!("{}", report.to_json()?);
+            continue;
+        };
+
+        match client.submit_crash_report(&report).await {
+            Ok(response) => {
+                println!(
+                    "{}: submitted as {} ({} plugins)",
+                    log.display(),
+                    response.id,
+                    report.plugin_count
+                );
+                if let Some(issue) = client.known_issue_for(&report).await {
+                    println!("  {}", issue.advice());
+                }
+                history::record_submitted(&report, &response);
+            }
+            Err(e) => {
+                eprintln!("{}: {}", log.display(), e);
+                history::record_pending(&report);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} logs failed", failed, logs.len()).into());
+    }
+    Ok(())
+}
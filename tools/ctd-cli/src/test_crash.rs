@@ -0,0 +1,117 @@
+//! `ctd test-crash` - verify capture and submission with a deliberate crash.
+//!
+//! Crashes a child copy of `ctd` with the same access violation the game
+//! plugins' `trigger_test_crash()` export raises, confirms the fault was
+//! observed, and prints the report it would send. `--submit` sends it; the
+//! report is marked synthetic by its `ctd-test` game ID and notes.
+
+use std::process::{Command, ExitStatus};
+
+use ctd_core::api_client::ApiClient;
+use ctd_core::config::Config;
+use ctd_core::crash_report::CreateCrashReport;
+use ctd_core::history;
+use ctd_core::load_order::ModList;
+use ctd_core::spool::Spool;
+use ctd_core::test_crash::{self, TEST_CRASH_ADDRESS, TEST_CRASH_THREAD};
+
+use crate::CliResult;
+
+/// Game ID used for reports produced by `ctd test-crash`.
+const TEST_GAME_ID: &str = "ctd-test";
+
+/// Windows `EXCEPTION_ACCESS_VIOLATION`.
+const ACCESS_VIOLATION: u32 = 0xC0000005;
+
+/// Crashes a child process and prints, or with `submit` sends, a report for the crash.
+pub async fn run(submit: bool) -> CliResult {
+    let config = Config::load()?;
+    if !config.allow_test_crash {
+        return Err("test crashes are disabled; set allow_test_crash = true in ctd.toml".into());
+    }
+
+    let status = Command::new(std::env::current_exe()?)
+        .args(["test-crash", "--child"])
+        .status()?;
+    if !is_access_violation(status) {
+        return Err(format!("child process did not crash as expected ({})", status).into());
+    }
+    println!("Access violation raised and observed ({})", status);
+
+    let report = test_report()?;
+    if !submit {
+        println!("{}", report.to_json()?);
+        println!("Not submitted; run with --submit to send this test report");
+        return Ok(());
+    }
+
+    match ApiClient::from_config()?.submit_crash_report(&report).await {
+        Ok(response) => {
+            println!("Test report submitted as {}", response.id);
+            history::record_submitted(&report, &response);
+            Ok(())
+        }
+        Err(e) => {
+            let queued = Spool::open_default().enqueue(&report)?;
+            Err(format!("{} (queued at {})", e, queued.display()).into())
+        }
+    }
+}
+
+/// Runs in the child process: faults on a scratch thread and never returns.
+pub fn child() -> ! {
+    let config = Config {
+        allow_test_crash: true,
+        ..Config::default()
+    };
+    if test_crash::trigger(&config).is_err() {
+        std::process::exit(1);
+    }
+
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Builds the report submitted for a successful test crash.
+fn test_report() -> ctd_core::Result<CreateCrashReport> {
+    CreateCrashReport::builder()
+        .game_id(TEST_GAME_ID)
+        .game_version(env!("CARGO_PKG_VERSION"))
+        .stack_trace(format!("[0] ctd test crash (thread {})", TEST_CRASH_THREAD))
+        .exception_code(format!("0x{:08X}", ACCESS_VIOLATION))
+        .exception_address(format!("0x{:016X}", TEST_CRASH_ADDRESS))
+        .faulting_module("ctd")
+        .load_order_v2(ModList::new())
+        .crashed_now()
+        .notes("Synthetic test report generated by `ctd test-crash`, not a real crash")
+        .build()
+}
+
+/// Returns true if a process exited because of an access violation.
+#[cfg(windows)]
+fn is_access_violation(status: ExitStatus) -> bool {
+    status.code() == Some(ACCESS_VIOLATION as i32)
+}
+
+#[cfg(unix)]
+fn is_access_violation(status: ExitStatus) -> bool {
+    use std::os::unix::process::ExitStatusExt;
+
+    // SIGSEGV, or SIGBUS (7 on Linux, 10 on macOS) where the fault is reported that way
+    matches!(status.signal(), Some(11) | Some(7) | Some(10))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_is_valid() {
+        let report = test_report().unwrap();
+        assert_eq!(report.game_id, TEST_GAME_ID);
+        assert_eq!(report.exception_code.as_deref(), Some("0xC0000005"));
+        assert!(report.exception_address.unwrap().ends_with("7E57"));
+        assert!(report.notes.unwrap().contains("Synthetic"));
+    }
+}
@@ -0,0 +1,190 @@
+//! CTD sidecar watcher.
+//!
+//! Reports crashes of games CTD has no in-process plugin for. The watcher
+//! launches the game (or attaches to it by process ID), registers it for
+//! Windows Error Reporting `LocalDumps`, and waits for it to exit. When the
+//! exit code is an unhandled exception it walks the stack in the dump WER
+//! wrote, fingerprints the mod directories it was given, and submits a
+//! report.
+//!
+//! ```text
+//! ctd-watch --mods-dir "C:/Games/Game/mods" -- "C:/Games/Game/Game.exe" -windowed
+//! ctd-watch --game-id some-game --pid 4242
+//! ```
+
+mod mods;
+mod process;
+mod report;
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use ctd_core::config::Config;
+use ctd_core::version::get_dll_version;
+use ctd_core::wer::{self, DumpCrash};
+use tracing_subscriber::EnvFilter;
+
+/// Result type of the watcher.
+pub type WatchResult = Result<(), Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "ctd-watch",
+    version,
+    about = "Report crashes of games with no CTD plugin"
+)]
+struct Cli {
+    /// Enable debug logging.
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Game ID sent in reports (default: the executable name, e.g. `some-game`).
+    #[arg(long)]
+    game_id: Option<String>,
+
+    /// Mod directory to fingerprint into the load order (repeatable).
+    #[arg(long = "mods-dir")]
+    mods_dirs: Vec<PathBuf>,
+
+    /// Folder WER writes dumps to (default: `[dumps] directory` in ctd.toml).
+    #[arg(long)]
+    dump_dir: Option<PathBuf>,
+
+    /// Don't register the game for WER LocalDumps (it needs administrator rights).
+    #[arg(long)]
+    no_local_dumps: bool,
+
+    /// Print the report JSON instead of submitting.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Attach to this running process instead of launching the game.
+    #[arg(long, conflicts_with = "command")]
+    pid: Option<u32>,
+
+    /// Game executable and its arguments, after `--`.
+    #[arg(last = true, required_unless_present = "pid")]
+    command: Vec<OsString>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging(cli.verbose);
+
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Watches the game and reports it if it crashes.
+async fn run(cli: Cli) -> WatchResult {
+    let exe = match cli.pid {
+        Some(pid) => process::image_path(pid)?,
+        None => PathBuf::from(&cli.command[0]),
+    };
+    let exe_name = exe
+        .file_name()
+        .ok_or("the game executable has no file name")?
+        .to_string_lossy()
+        .into_owned();
+    let game_id = cli.game_id.unwrap_or_else(|| wer::game_id_from_exe(&exe));
+
+    let dump_dir = cli
+        .dump_dir
+        .unwrap_or_else(|| Config::load().unwrap_or_default().dumps.dump_dir());
+    if !cli.no_local_dumps {
+        match wer::enable_local_dumps(&exe_name, &dump_dir) {
+            Ok(()) => tracing::info!("WER dumps for {} go to {}", exe_name, dump_dir.display()),
+            Err(e) => eprintln!(
+                "warning: could not enable WER dumps ({}); crashes will be reported from the exit code only",
+                e
+            ),
+        }
+    }
+
+    let exit = match cli.pid {
+        Some(pid) => {
+            println!("Watching {} (pid {})", exe_name, pid);
+            process::attach(pid)?
+        }
+        None => {
+            println!("Launching {}", exe.display());
+            process::launch(&exe, &cli.command[1..])?
+        }
+    };
+
+    if !exit.is_crash() {
+        println!("{} exited normally", exe_name);
+        return Ok(());
+    }
+    match exit.code {
+        Some(code) => println!("{} crashed (0x{:08X})", exe_name, code),
+        None => println!("{} was killed by a signal", exe_name),
+    }
+
+    let dump = match wer::wait_for_dump(&dump_dir, &exe_name, exit.pid) {
+        Some(path) => DumpCrash::read(&path)
+            .await
+            .inspect_err(|e| eprintln!("warning: {}", e))
+            .ok(),
+        None => None,
+    };
+    let game_version = get_dll_version(&exe).unwrap_or_else(|_| "unknown".to_string());
+    let report = report::build_report(
+        &game_id,
+        &game_version,
+        &exit,
+        dump.as_ref(),
+        mods::scan_mod_dirs(&cli.mods_dirs),
+    )?;
+
+    if cli.dry_run {
+        println!("{}", report.to_json()?);
+        return Ok(());
+    }
+    report::submit(&report).await
+}
+
+/// Initializes logging to stderr, honoring `RUST_LOG`.
+fn init_logging(verbose: bool) {
+    let default = if verbose { "debug" } else { "warn" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_launch_and_attach() {
+        let cli = Cli::try_parse_from([
+            "ctd-watch",
+            "--mods-dir",
+            "mods",
+            "--",
+            "Game.exe",
+            "-windowed",
+        ])
+        .unwrap();
+        assert_eq!(cli.command, ["Game.exe", "-windowed"]);
+        assert_eq!(cli.mods_dirs, [PathBuf::from("mods")]);
+
+        let cli = Cli::try_parse_from(["ctd-watch", "--pid", "42"]).unwrap();
+        assert_eq!(cli.pid, Some(42));
+
+        assert!(Cli::try_parse_from(["ctd-watch"]).is_err());
+        assert!(Cli::try_parse_from(["ctd-watch", "--pid", "42", "--", "Game.exe"]).is_err());
+    }
+}
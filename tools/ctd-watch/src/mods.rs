@@ -0,0 +1,106 @@
+//! Mod directory fingerprinting for games without a known mod layout.
+//!
+//! Each entry directly in a mod directory is taken as one mod: a file (an
+//! archive, a plugin DLL) is hashed itself, and a folder by its first file
+//! in path order. Entries are named `<dir>/<entry>` after the directory's
+//! own name, so several directories can be watched without collisions.
+
+use std::path::{Path, PathBuf};
+
+use ctd_core::file_hash::{CachedHasher, UNHASHED};
+use ctd_core::load_order::{DEFAULT_HASH_BUDGET, ModEntry, ModList};
+
+/// Scans `mod_dirs` into one load order, in directory then name order.
+pub fn scan_mod_dirs(mod_dirs: &[PathBuf]) -> ModList {
+    let mut mods: Vec<(String, Option<PathBuf>)> = Vec::new();
+    for dir in mod_dirs {
+        let label = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dir.to_string_lossy().into_owned());
+        for entry in sorted_entries(dir) {
+            let name = entry
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let file = if entry.is_dir() {
+                first_file(&entry)
+            } else {
+                Some(entry)
+            };
+            mods.push((format!("{}/{}", label, name), file));
+        }
+    }
+
+    let paths: Vec<PathBuf> = mods.iter().filter_map(|(_, file)| file.clone()).collect();
+    let mut hasher = CachedHasher::open_default();
+    let mut hashed = ModList::fingerprint_parallel(&paths, DEFAULT_HASH_BUDGET, &mut hasher)
+        .0
+        .into_iter();
+    hasher.save();
+
+    let mut list = ModList::new();
+    for (index, (name, file)) in mods.into_iter().enumerate() {
+        let (hash, size) = match file.and_then(|_| hashed.next()) {
+            Some(entry) => (entry.file_hash, entry.file_size),
+            None => (UNHASHED.to_string(), 0),
+        };
+        list.push(
+            ModEntry::new(name, hash, size)
+                .with_index(index as u32)
+                .with_enabled(true)
+                .with_light(false),
+        );
+    }
+    list
+}
+
+/// Lists the entries of `dir`, sorted by path.
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    paths
+}
+
+/// Returns the first file under `dir`, depth first in path order.
+fn first_file(dir: &Path) -> Option<PathBuf> {
+    sorted_entries(dir).into_iter().find_map(|path| {
+        if path.is_dir() {
+            first_file(&path)
+        } else {
+            Some(path)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_scan_mod_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let paks = dir.path().join("paks");
+        let plugins = dir.path().join("plugins");
+        fs::create_dir_all(paks.join("Folder/sub")).unwrap();
+        fs::create_dir_all(paks.join("Empty")).unwrap();
+        fs::create_dir_all(&plugins).unwrap();
+        fs::write(paks.join("b.pak"), "pak").unwrap();
+        fs::write(paks.join("Folder/sub/data.bin"), "data").unwrap();
+        fs::write(plugins.join("a.dll"), "dll").unwrap();
+
+        let mods = scan_mod_dirs(&[paks, plugins, dir.path().join("missing")]);
+        let names: Vec<&str> = mods.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["paks/Empty", "paks/Folder", "paks/b.pak", "plugins/a.dll"]
+        );
+        assert_eq!(mods.0[0].file_hash, UNHASHED);
+        assert_eq!(mods.0[1].file_size, 4);
+        assert_eq!(mods.0[3].index, Some(3));
+    }
+}
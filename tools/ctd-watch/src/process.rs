@@ -0,0 +1,153 @@
+//! Launching or attaching to the game and waiting for it to exit.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// NTSTATUS severity bits of an error status.
+const SEVERITY_ERROR: u32 = 0xC000_0000;
+
+/// `STATUS_CONTROL_C_EXIT`: the console window was closed, not a crash.
+const CONTROL_C_EXIT: u32 = 0xC000_013A;
+
+/// How a watched process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exit {
+    /// Process ID, which names the WER dump.
+    pub pid: u32,
+    /// Exit code, or `None` if a Unix process was killed by a signal.
+    pub code: Option<u32>,
+}
+
+impl Exit {
+    /// Returns true if the process died abnormally.
+    ///
+    /// A process killed by an unhandled exception exits with the exception
+    /// code, an NTSTATUS error such as 0xC0000005. Ordinary exit codes,
+    /// including a game's own error codes, are not crashes.
+    pub fn is_crash(&self) -> bool {
+        self.code
+            .is_none_or(|code| code & SEVERITY_ERROR == SEVERITY_ERROR && code != CONTROL_C_EXIT)
+    }
+}
+
+/// Starts `exe` with `args` in its own directory and waits for it to exit.
+pub fn launch(exe: &Path, args: &[OsString]) -> io::Result<Exit> {
+    let mut command = Command::new(exe);
+    command.args(args);
+    if let Some(dir) = exe.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        command.current_dir(dir);
+    }
+    let mut child = command.spawn()?;
+    let pid = child.id();
+    let status = child.wait()?;
+    Ok(Exit {
+        pid,
+        code: status.code().map(|code| code as u32),
+    })
+}
+
+/// Waits for the running process `pid` to exit.
+#[cfg(windows)]
+pub fn attach(pid: u32) -> io::Result<Exit> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        GetExitCodeProcess, INFINITE, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+        PROCESS_SYNCHRONIZE, WaitForSingleObject,
+    };
+
+    // SAFETY: The handle is closed before returning
+    let process = unsafe {
+        OpenProcess(
+            PROCESS_SYNCHRONIZE | PROCESS_QUERY_LIMITED_INFORMATION,
+            false,
+            pid,
+        )
+    }
+    .map_err(io::Error::other)?;
+
+    let mut code = 0u32;
+    // SAFETY: The handle is valid and has SYNCHRONIZE and query access
+    let result = unsafe {
+        WaitForSingleObject(process, INFINITE);
+        GetExitCodeProcess(process, &mut code)
+    };
+    // SAFETY: The handle was opened above
+    let _ = unsafe { CloseHandle(process) };
+    result.map_err(io::Error::other)?;
+
+    Ok(Exit {
+        pid,
+        code: Some(code),
+    })
+}
+
+/// Non-Windows stub: attaching needs the Windows process API.
+#[cfg(not(windows))]
+pub fn attach(_pid: u32) -> io::Result<Exit> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "attaching to a process is only supported on Windows",
+    ))
+}
+
+/// Returns the executable path of the running process `pid`.
+#[cfg(windows)]
+pub fn image_path(pid: u32) -> io::Result<PathBuf> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+        QueryFullProcessImageNameW,
+    };
+    use windows::core::PWSTR;
+
+    // SAFETY: The handle is closed before returning
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }
+        .map_err(io::Error::other)?;
+
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as u32;
+    // SAFETY: The buffer holds `len` characters
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut len,
+        )
+    };
+    // SAFETY: The handle was opened above
+    let _ = unsafe { CloseHandle(process) };
+    result.map_err(io::Error::other)?;
+
+    Ok(PathBuf::from(String::from_utf16_lossy(
+        &buf[..len as usize],
+    )))
+}
+
+/// Non-Windows stub: reads the `/proc` link instead.
+#[cfg(not(windows))]
+pub fn image_path(pid: u32) -> io::Result<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/exe", pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit(code: Option<u32>) -> Exit {
+        Exit { pid: 1, code }
+    }
+
+    #[test]
+    fn test_is_crash() {
+        assert!(exit(Some(0xC0000005)).is_crash());
+        assert!(exit(Some(0xC0000409)).is_crash());
+        assert!(exit(None).is_crash());
+        assert!(!exit(Some(0)).is_crash());
+        assert!(!exit(Some(1)).is_crash());
+        assert!(!exit(Some(0x80000003)).is_crash());
+        assert!(!exit(Some(CONTROL_C_EXIT)).is_crash());
+    }
+}
@@ -0,0 +1,154 @@
+//! Building and submitting the report for a watched crash.
+
+use ctd_capture::exception::exception_code_name;
+use ctd_core::api_client::{self, ApiClient};
+use ctd_core::crash_report::CreateCrashReport;
+use ctd_core::event_log;
+use ctd_core::file_hash;
+use ctd_core::hardware_events;
+use ctd_core::load_order::ModList;
+use ctd_core::spool::Spool;
+use ctd_core::submit;
+use ctd_core::wer::DumpCrash;
+
+use crate::process::Exit;
+
+/// Builds the report for a crash seen from outside the process.
+///
+/// The WER dump, when there is one, gives the walked stack, the exception
+/// address, and the faulting module; without it the exit code is all there
+/// is.
+pub fn build_report(
+    game_id: &str,
+    game_version: &str,
+    exit: &Exit,
+    dump: Option<&DumpCrash>,
+    mod_list: ModList,
+) -> ctd_core::Result<CreateCrashReport> {
+    let dump = dump.filter(|dump| dump.stack_trace().is_some());
+
+    let mut builder = match dump {
+        Some(dump) => dump
+            .report_builder(game_id, mod_list)
+            .notes("Reported by ctd-watch from a Windows Error Reporting dump"),
+        None => CreateCrashReport::builder()
+            .game_id(game_id)
+            .stack_trace(match exit.code {
+                Some(code) => format!("Process exited with 0x{:08X} ({})", code, describe(code)),
+                None => "Process was killed by a signal".to_string(),
+            })
+            .load_order_v2(mod_list)
+            .crashed_now()
+            .notes("Reported by ctd-watch from the process exit code"),
+    };
+    if dump.is_none_or(|dump| dump.game_version.is_none()) {
+        builder = builder.game_version(game_version);
+    }
+    if dump.is_none_or(|dump| dump.exception.is_none())
+        && let Some(code) = exit.code
+    {
+        builder = builder.exception_code(format!("0x{:08X}", code));
+    }
+
+    builder
+        .max_schema_version(api_client::negotiated_schema_version())
+        .hash_algorithm(file_hash::configured_algorithm())
+        .hardware_events(hardware_events::recent())
+        .anonymous_ids()
+        .build()
+}
+
+/// Names an exit code if it is a known exception code.
+fn describe(code: u32) -> &'static str {
+    match exception_code_name(code) {
+        "UNKNOWN_EXCEPTION" => "unhandled exception",
+        name => name,
+    }
+}
+
+/// Submits `report`, queueing it in the spool if submission fails.
+pub async fn submit(report: &CreateCrashReport) -> Result<(), Box<dyn std::error::Error>> {
+    let client = ApiClient::from_config().or_else(|_| ApiClient::with_defaults())?;
+    match client.submit_crash_report(report).await {
+        Ok(response) => {
+            println!("Crash report submitted as {}", response.id);
+            if let Some(issue) = client.known_issue_for(report).await {
+                println!("  {}", issue.advice());
+            }
+            submit::record_submitted(report, &response);
+            Ok(())
+        }
+        Err(e) => {
+            event_log::record(report, None);
+            let queued = Spool::open_default().enqueue(report)?;
+            Err(format!("{} (queued at {})", e, queued.display()).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ctd_core::wer::{DumpException, DumpFrame, DumpModule};
+
+    #[test]
+    fn test_build_report_from_exit_code() {
+        let exit = Exit {
+            pid: 7,
+            code: Some(0xC0000409),
+        };
+        let report = build_report("game", "1.0", &exit, None, ModList::new()).unwrap();
+        assert_eq!(report.exception_code.as_deref(), Some("0xC0000409"));
+        assert!(
+            report
+                .stack_trace
+                .starts_with("Process exited with 0xC0000409")
+        );
+        assert_eq!(report.exception_address, None);
+    }
+
+    #[test]
+    fn test_build_report_from_dump() {
+        let exit = Exit {
+            pid: 7,
+            code: Some(0xC0000005),
+        };
+        let module = DumpModule {
+            base: 0x1_4000_0000,
+            size: 0x10000,
+            path: "C:\\Games\\Game\\game.exe".to_string(),
+        };
+        let dump = DumpCrash {
+            exception: Some(DumpException {
+                thread_id: 1,
+                code: 0xC0000005,
+                address: 0x1_4000_1234,
+            }),
+            frames: vec![
+                DumpFrame {
+                    address: 0x1_4000_1234,
+                    module: Some(module.clone()),
+                    function: None,
+                },
+                DumpFrame {
+                    address: 0x1_4000_5678,
+                    module: Some(module.clone()),
+                    function: None,
+                },
+            ],
+            modules: vec![module],
+            ..DumpCrash::default()
+        };
+        let report = build_report("game", "1.0", &exit, Some(&dump), ModList::new()).unwrap();
+        assert_eq!(
+            report.stack_trace,
+            "[ 0] game.exe+0x1234 (0x0000000140001234)\n[ 1] game.exe+0x5678 (0x0000000140005678)"
+        );
+        assert_eq!(report.game_version, "1.0");
+        assert_eq!(report.faulting_module.as_deref(), Some("game.exe"));
+        assert_eq!(
+            report.exception_address.as_deref(),
+            Some("0x0000000140001234")
+        );
+    }
+}